@@ -0,0 +1,380 @@
+//! DuckCoding 命令行（无界面）
+//!
+//! 直接复用服务层，供 CI 等没有图形环境的场景使用；与 GUI 共用配置目录解析与文件锁，
+//! 可以和运行中的 GUI 同时使用。
+//!
+//! 成功时结果以 JSON 输出到 stdout；失败时错误（`{code, message, details}`）以 JSON
+//! 输出到 stderr，退出码按错误码区分（见 [`exit_code`]）。
+//!
+//! ```text
+//! duckcoding-cli detect [<tool>]
+//! duckcoding-cli providers list
+//! duckcoding-cli providers validate <provider>
+//! duckcoding-cli apply <provider> <tool> [--profile <name>]
+//! duckcoding-cli proxy start <tool> [--port <port>]
+//! duckcoding-cli update <tool|instance> [--force]
+//! ```
+
+use duckcoding::core::{i18n, tr_with, CommandContext, CommandError, CommandResult};
+use duckcoding::models::{Tool, ToolType};
+use duckcoding::services::provider_manager::validate_provider;
+use duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use duckcoding::services::tool_setup::{apply_provider_profile, SetupOptions};
+use duckcoding::services::{DashboardManager, ProfileManager, ProviderManager};
+use duckcoding::utils::config::read_global_config;
+use duckcoding::{ProxyManager, ToolRegistry};
+use serde::Serialize;
+use serde_json::Value;
+
+const USAGE: &str = "用法:
+  duckcoding-cli detect [<tool>]
+  duckcoding-cli providers list
+  duckcoding-cli providers validate <provider>
+  duckcoding-cli apply <provider> <tool> [--profile <name>]
+  duckcoding-cli proxy start <tool> [--port <port>]
+  duckcoding-cli update <tool|instance> [--force]";
+
+/// 子命令
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Help,
+    Detect {
+        tool: Option<String>,
+    },
+    ProvidersList,
+    ProvidersValidate {
+        provider: String,
+    },
+    Apply {
+        provider: String,
+        tool: String,
+        profile: Option<String>,
+    },
+    ProxyStart {
+        tool: String,
+        port: Option<u16>,
+    },
+    Update {
+        target: String,
+        force: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    // 与 GUI 使用相同的后端语言
+    if let Some(config) = read_global_config().ok().flatten() {
+        i18n::set_locale(config.backend_locale);
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match parse_args(&args) {
+        Ok(Command::Help) => {
+            println!("{USAGE}");
+            return;
+        }
+        Ok(command) => run(command).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(value) => print_json(&value),
+        Err(e) => {
+            eprintln!(
+                "{}",
+                serde_json::to_string_pretty(&e).unwrap_or_else(|_| e.to_string())
+            );
+            std::process::exit(exit_code(e.code()));
+        }
+    }
+}
+
+/// 错误码对应的退出码
+fn exit_code(code: &str) -> i32 {
+    match code {
+        "VALIDATION" => 2,
+        "NOT_FOUND" => 3,
+        "IO" => 4,
+        "NETWORK" => 5,
+        "CONFLICT" => 6,
+        "LOCKED" => 7,
+        "UNSUPPORTED" => 8,
+        _ => 1,
+    }
+}
+
+fn usage_error(reason: impl Into<String>) -> CommandError {
+    CommandError::validation(format!("{}\n\n{USAGE}", reason.into()))
+}
+
+fn parse_args(args: &[String]) -> CommandResult<Command> {
+    let mut positional = Vec::new();
+    let mut profile = None;
+    let mut port = None;
+    let mut force = false;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--force" => force = true,
+            "--profile" => {
+                profile = Some(
+                    iter.next()
+                        .ok_or_else(|| usage_error("--profile 缺少参数"))?
+                        .clone(),
+                )
+            }
+            "--port" => {
+                let value = iter.next().ok_or_else(|| usage_error("--port 缺少参数"))?;
+                port = Some(
+                    value
+                        .parse::<u16>()
+                        .map_err(|_| usage_error(format!("无效端口: {value}")))?,
+                );
+            }
+            flag if flag.starts_with("--") => return Err(usage_error(format!("未知选项: {flag}"))),
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    let positional: Vec<&str> = positional.iter().map(String::as_str).collect();
+    match positional.as_slice() {
+        [] | ["help"] => Ok(Command::Help),
+        ["detect"] => Ok(Command::Detect { tool: None }),
+        ["detect", tool] => Ok(Command::Detect {
+            tool: Some(tool.to_string()),
+        }),
+        ["providers", "list"] => Ok(Command::ProvidersList),
+        ["providers", "validate", provider] => Ok(Command::ProvidersValidate {
+            provider: provider.to_string(),
+        }),
+        ["apply", provider, tool] => Ok(Command::Apply {
+            provider: provider.to_string(),
+            tool: tool.to_string(),
+            profile,
+        }),
+        ["proxy", "start", tool] => Ok(Command::ProxyStart {
+            tool: tool.to_string(),
+            port,
+        }),
+        ["update", target] => Ok(Command::Update {
+            target: target.to_string(),
+            force,
+        }),
+        other => Err(usage_error(format!("无法识别的命令: {}", other.join(" ")))),
+    }
+}
+
+async fn run(command: Command) -> CommandResult<Value> {
+    match command {
+        Command::Help => Ok(Value::Null),
+        Command::Detect { tool } => detect(tool).await,
+        Command::ProvidersList => {
+            let providers: Vec<_> = ProviderManager::new()
+                .command_context("provider.list_failed")?
+                .list_providers()
+                .command_context("provider.list_failed")?
+                .iter()
+                .map(|provider| provider.masked())
+                .collect();
+            to_json(&providers)
+        }
+        Command::ProvidersValidate { provider } => {
+            let provider = find_provider(&provider)?;
+            let result = validate_provider(&provider)
+                .await
+                .command_context("provider.api_request_failed")?;
+            to_json(&result)
+        }
+        Command::Apply {
+            provider,
+            tool,
+            profile,
+        } => apply(&provider, &tool, profile).await,
+        Command::ProxyStart { tool, port } => proxy_start(&tool, port).await,
+        Command::Update { target, force } => update(&target, force).await,
+    }
+}
+
+async fn detect(tool: Option<String>) -> CommandResult<Value> {
+    let registry = ToolRegistry::new()
+        .await
+        .command_context("tool.status_check_failed")?;
+    match tool {
+        Some(tool) => {
+            let tool = require_tool(&tool)?;
+            let status = registry
+                .detect_single_tool_with_cache(&tool.id, true)
+                .await
+                .command_context("tool.status_check_failed")?;
+            to_json(&status)
+        }
+        None => {
+            let statuses = registry
+                .refresh_all_tool_versions()
+                .await
+                .command_context("tool.status_check_failed")?;
+            to_json(&statuses)
+        }
+    }
+}
+
+/// 将供应商写入工具 Profile 并激活，同时绑定工具使用的供应商
+async fn apply(provider_id: &str, tool_id: &str, profile: Option<String>) -> CommandResult<Value> {
+    let tool = require_tool(tool_id)?;
+    let provider = find_provider(provider_id)?;
+    let options = SetupOptions {
+        profile_name: profile,
+        ..Default::default()
+    };
+
+    let manager = ProfileManager::new()?;
+    let profile_name = apply_provider_profile(&manager, &tool.id, &provider, &options)?;
+    DashboardManager::new()?
+        .set_tool_provider_binding(tool.id.clone(), Some(provider.id.clone()))?;
+
+    Ok(serde_json::json!({
+        "tool_id": tool.id,
+        "provider_id": provider.id,
+        "profile": profile_name,
+    }))
+}
+
+/// 在前台运行透明代理，直到收到 Ctrl+C
+async fn proxy_start(tool_id: &str, port: Option<u16>) -> CommandResult<Value> {
+    let tool = require_tool(tool_id)?;
+    let mut config = ProxyConfigManager::new()?
+        .get_config(&tool.id)?
+        .ok_or_else(|| CommandError::not_found(format!("未找到 {} 的代理配置", tool.id)))?;
+    if let Some(port) = port {
+        config.port = port;
+    }
+    let port = config.port;
+
+    let manager = ProxyManager::new();
+    manager.start_proxy(&tool.id, config).await?;
+    print_json(&serde_json::json!({
+        "tool_id": tool.id,
+        "port": port,
+        "status": "running",
+    }));
+
+    tokio::signal::ctrl_c()
+        .await
+        .map_err(|e| CommandError::internal(e.to_string()))?;
+    manager.stop_proxy(&tool.id).await?;
+    Ok(serde_json::json!({
+        "tool_id": tool.id,
+        "port": port,
+        "status": "stopped",
+    }))
+}
+
+/// 更新工具的本地实例（参数可为工具 ID 或实例 ID）
+async fn update(target: &str, force: bool) -> CommandResult<Value> {
+    let registry = ToolRegistry::new()
+        .await
+        .command_context("tool.instances_get_failed")?;
+    let instance_id = registry
+        .get_all_grouped()
+        .await
+        .command_context("tool.instances_get_failed")?
+        .into_values()
+        .flatten()
+        .find(|instance| {
+            instance.tool_type == ToolType::Local
+                && (instance.instance_id == target || instance.base_id == target)
+        })
+        .map(|instance| instance.instance_id)
+        .ok_or_else(|| {
+            CommandError::not_found(tr_with("tool.instance_not_found", &[("id", target)]))
+        })?;
+
+    let result = registry.update_instance(&instance_id, force).await?;
+    to_json(&result)
+}
+
+fn require_tool(tool_id: &str) -> CommandResult<Tool> {
+    Tool::by_id(tool_id).ok_or_else(|| {
+        CommandError::not_found(tr_with("tool.instance_not_found", &[("id", tool_id)]))
+    })
+}
+
+fn find_provider(provider_id: &str) -> CommandResult<duckcoding::models::provider::Provider> {
+    ProviderManager::new()
+        .command_context("provider.get_failed")?
+        .get_provider(provider_id)
+        .command_context("provider.get_failed")?
+        .ok_or_else(|| {
+            CommandError::not_found(tr_with("provider.not_found", &[("id", provider_id)]))
+        })
+}
+
+fn to_json<T: Serialize>(value: &T) -> CommandResult<Value> {
+    serde_json::to_value(value).map_err(|e| CommandError::internal(e.to_string()))
+}
+
+fn print_json(value: &Value) {
+    match serde_json::to_string_pretty(value) {
+        Ok(text) => println!("{text}"),
+        Err(e) => eprintln!("{e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        assert_eq!(parse_args(&args("")).unwrap(), Command::Help);
+        assert_eq!(
+            parse_args(&args("detect codex")).unwrap(),
+            Command::Detect {
+                tool: Some("codex".to_string())
+            }
+        );
+        assert_eq!(
+            parse_args(&args("apply duckcoding claude-code --profile ci")).unwrap(),
+            Command::Apply {
+                provider: "duckcoding".to_string(),
+                tool: "claude-code".to_string(),
+                profile: Some("ci".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_args(&args("proxy start codex --port 9000")).unwrap(),
+            Command::ProxyStart {
+                tool: "codex".to_string(),
+                port: Some(9000),
+            }
+        );
+        assert_eq!(
+            parse_args(&args("update --force gemini-cli")).unwrap(),
+            Command::Update {
+                target: "gemini-cli".to_string(),
+                force: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_are_validation() {
+        for line in [
+            "providers",
+            "proxy start codex --port abc",
+            "detect --verbose",
+        ] {
+            let err = parse_args(&args(line)).unwrap_err();
+            assert_eq!(err.code(), "VALIDATION", "{line}");
+            assert_eq!(exit_code(err.code()), 2);
+        }
+        assert_eq!(exit_code("NOT_FOUND"), 3);
+        assert_eq!(exit_code("INTERNAL"), 1);
+    }
+}
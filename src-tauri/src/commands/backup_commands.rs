@@ -0,0 +1,79 @@
+//! 完整状态备份命令
+//!
+//! 设置保存在全局配置的 `state_backup` 中；定时备份由启动时的后台任务执行，
+//! 备份结果通过 `state-backup` 事件通知前端。
+
+use crate::commands::error::{CommandContext, CommandError, CommandResult};
+use ::duckcoding::core::tr;
+use ::duckcoding::models::{BackupComponent, BackupRunResult, RestoreReport, StateBackupSettings};
+use ::duckcoding::services::state_backup::{validate_settings, BackupService, STATE_BACKUP};
+use ::duckcoding::services::tool::TOOL_STATUS_CACHE;
+use ::duckcoding::utils::config::{read_global_config, write_global_config};
+use anyhow::anyhow;
+use std::path::PathBuf;
+
+/// 获取完整状态备份设置
+#[tauri::command]
+pub async fn get_state_backup_settings() -> CommandResult<StateBackupSettings> {
+    Ok(BackupService::settings())
+}
+
+/// 保存完整状态备份设置（备份内容包含密钥时必须设置口令）
+#[tauri::command]
+pub async fn save_state_backup_settings(
+    settings: StateBackupSettings,
+) -> CommandResult<StateBackupSettings> {
+    validate_settings(&settings).map_err(|e| CommandError::validation(e.to_string()))?;
+    let mut config = read_global_config()
+        .map_err(|e| anyhow!(e))
+        .and_then(|config| config.ok_or_else(|| anyhow!("配置文件不存在")))
+        .command_context("backup.settings_failed")?;
+    config.state_backup = settings.clone();
+    write_global_config(&config)
+        .map_err(|e| anyhow!(e))
+        .command_context("backup.settings_failed")?;
+    tracing::info!(schedule = ?settings.schedule, "已保存完整状态备份设置");
+    Ok(settings)
+}
+
+/// 按当前设置立即备份（结果同时通过 `state-backup` 事件通知）
+#[tauri::command]
+pub async fn run_backup_now() -> CommandResult<BackupRunResult> {
+    let settings = BackupService::settings();
+    tokio::task::spawn_blocking(move || STATE_BACKUP.run_backup_now(&settings, false))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result)
+        .command_context("backup.run_failed")
+}
+
+/// 从备份文件恢复全部状态，返回每个组成的结果
+///
+/// 恢复前自动保存当前状态的安全快照（见 `RestoreReport::safety_snapshot`）。
+#[tauri::command]
+pub async fn restore_full_backup(
+    path: String,
+    passphrase: Option<String>,
+) -> CommandResult<RestoreReport> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(CommandError::not_found(tr("backup.file_not_found")));
+    }
+    let report = tokio::task::spawn_blocking(move || {
+        STATE_BACKUP.restore_full_backup(&path, passphrase.as_deref())
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result)
+    .command_context("backup.restore_failed")?;
+
+    // 实例数据库已替换，缓存的检测结果不再可靠
+    if report
+        .components
+        .iter()
+        .any(|c| c.component == BackupComponent::InstanceDb && c.success)
+    {
+        TOOL_STATUS_CACHE.clear();
+    }
+    Ok(report)
+}
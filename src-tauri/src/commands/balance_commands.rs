@@ -3,11 +3,20 @@
 // 支持通过自定义 API 端点和提取器脚本查询余额信息
 // 以及余额监控配置的持久化存储管理
 
+use crate::commands::provider_commands::ProviderManagerState;
 use ::duckcoding::http_client::build_client;
-use ::duckcoding::models::{BalanceConfig, BalanceStore};
-use ::duckcoding::services::balance::BalanceManager;
+use ::duckcoding::models::{
+    AggregateBalance, Balance, BalanceConfig, BalanceDetail, BalancePollSettings, BalanceStore,
+    Provider,
+};
+use ::duckcoding::services::balance::{
+    aggregate_cached_balances, get_provider_balance, get_provider_balance_detail, BalanceManager,
+};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::utils::config::read_global_config;
 use std::collections::HashMap;
+use std::time::Duration;
+use tauri::State;
 
 /// Tauri command: 通用 API 请求
 ///
@@ -123,3 +132,80 @@ pub async fn migrate_balance_from_localstorage(
     tracing::info!("从 localStorage 迁移了 {} 个余额监控配置", count);
     Ok(count)
 }
+
+/// 查找供应商，并按 `force_refresh` 计算可接受的缓存时长
+///
+/// 默认复用轮询间隔内的缓存，`force_refresh` 为 true 时强制实时查询
+fn resolve_balance_query(
+    provider_id: &str,
+    force_refresh: Option<bool>,
+    provider_state: &ProviderManagerState,
+) -> Result<(Provider, Duration), String> {
+    let provider = provider_state
+        .manager
+        .get_provider(provider_id)
+        .map_err(|e| format!("获取供应商失败: {}", e))?
+        .ok_or_else(|| format!("供应商不存在: {}", provider_id))?;
+
+    let max_age = if force_refresh.unwrap_or(false) {
+        Duration::ZERO
+    } else {
+        let settings = read_global_config()?
+            .map(|c| c.balance_poll)
+            .unwrap_or_else(BalancePollSettings::default);
+        Duration::from_secs(u64::from(settings.interval_mins) * 60)
+    };
+
+    Ok((provider, max_age))
+}
+
+/// 查询供应商余额
+#[tauri::command]
+pub async fn get_provider_balance_info(
+    provider_id: String,
+    force_refresh: Option<bool>,
+    provider_state: State<'_, ProviderManagerState>,
+) -> Result<Balance, String> {
+    apply_global_proxy().ok();
+
+    let (provider, max_age) = resolve_balance_query(&provider_id, force_refresh, &provider_state)?;
+    get_provider_balance(&provider, max_age)
+        .await
+        .map_err(|e| format!("查询余额失败: {}", e))
+}
+
+/// 查询供应商余额详情（含本计费周期按模型的用量明细）
+#[tauri::command]
+pub async fn get_provider_balance_detail_info(
+    provider_id: String,
+    force_refresh: Option<bool>,
+    provider_state: State<'_, ProviderManagerState>,
+) -> Result<BalanceDetail, String> {
+    apply_global_proxy().ok();
+
+    let (provider, max_age) = resolve_balance_query(&provider_id, force_refresh, &provider_state)?;
+    get_provider_balance_detail(&provider, max_age)
+        .await
+        .map_err(|e| format!("查询余额详情失败: {}", e))
+}
+
+/// 获取供应商余额历史（按时间升序）
+#[tauri::command]
+pub async fn get_balance_history(provider_id: String) -> Result<Vec<Balance>, String> {
+    let manager = BalanceManager::new().map_err(|e| e.to_string())?;
+    manager.history(&provider_id).map_err(|e| e.to_string())
+}
+
+/// 汇总所有供应商的剩余额度（美元）
+///
+/// 只使用已缓存的余额，不发起请求；`oldest_fetched_at` 表示合计数据的新鲜程度
+#[tauri::command]
+pub async fn get_aggregate_balance(
+    provider_state: State<'_, ProviderManagerState>,
+) -> Result<AggregateBalance, String> {
+    let providers = provider_state
+        .manager
+        .list_providers()
+        .map_err(|e| format!("获取供应商列表失败: {}", e))?;
+    Ok(aggregate_cached_balances(&providers))
+}
@@ -0,0 +1,174 @@
+//! 清理 DuckCoding 管理的状态（停止使用 / 卸载前）
+//!
+//! 先以 `dry_run` 预演得到计划与确认令牌，再携带令牌执行；
+//! 只包含非破坏性范围（还原工具配置、停止代理）时不需要令牌。
+
+use crate::commands::dashboard_commands::{clean_dashboard_selections, DashboardManagerState};
+use crate::commands::error::{CommandContext, CommandError, CommandResult};
+use crate::commands::profile_commands::ProfileManagerState;
+use crate::commands::provider_commands::ProviderManagerState;
+use crate::commands::proxy_commands::ProxyManagerState;
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::core::tr;
+use ::duckcoding::services::cleanup::{
+    self, CleanupAction, CleanupInputs, CleanupReport, CleanupScope,
+};
+use ::duckcoding::services::proxy::tool_routing;
+use ::duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use ::duckcoding::services::tool::{ToolInstanceDB, TOOL_STATUS_CACHE};
+use ::duckcoding::utils::config::app_paths;
+use tauri::{AppHandle, State};
+
+const PROXY_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 按所选范围清理，返回逐项报告
+///
+/// `dry_run` 为 true 时只列出将要执行的改动；包含破坏性范围时必须携带预演返回的
+/// `confirmation_token`，计划在预演后发生变化时令牌失效。单项失败不中断其余项。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn cleanup_managed_state(
+    app: AppHandle,
+    scopes: Vec<CleanupScope>,
+    dry_run: bool,
+    confirmation_token: Option<String>,
+    proxy_state: State<'_, ProxyManagerState>,
+    profile_state: State<'_, ProfileManagerState>,
+    provider_state: State<'_, ProviderManagerState>,
+    dashboard_state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+) -> CommandResult<CleanupReport> {
+    if scopes.is_empty() {
+        return Err(CommandError::validation(tr("cleanup.scopes_required")));
+    }
+
+    let proxy_config_mgr = ProxyConfigManager::new().command_context("cleanup.plan_failed")?;
+    let inputs = collect_inputs(&proxy_config_mgr, &proxy_state, &provider_state).await?;
+    let actions = cleanup::plan(&scopes, &inputs);
+    if dry_run {
+        return Ok(CleanupReport::dry_run(&actions));
+    }
+    if let Some(expected) = cleanup::confirmation_token(&actions) {
+        if confirmation_token.as_deref() != Some(expected.as_str()) {
+            return Err(CommandError::validation(tr(
+                "cleanup.confirmation_required",
+            )));
+        }
+    }
+
+    let mut report = CleanupReport::default();
+    for action in &actions {
+        let result = match action {
+            CleanupAction::StopProxy { tool_id } => proxy_state.manager.stop_proxy(tool_id).await,
+            CleanupAction::RestoreToolRouting { tool_id } => {
+                tool_routing::unconfigure_tool_for_proxy(tool_id)
+            }
+            CleanupAction::RestoreActiveProfile { tool_id, profile } => {
+                let result = profile_state
+                    .manager
+                    .write()
+                    .await
+                    .activate_profile(tool_id, profile);
+                result.and_then(|_| {
+                    update_proxy_config(&proxy_config_mgr, tool_id, |config| {
+                        config.original_active_profile = None;
+                    })
+                })
+            }
+            CleanupAction::DisableProxy { tool_id } => {
+                update_proxy_config(&proxy_config_mgr, tool_id, |config| {
+                    config.enabled = false;
+                    config.auto_start = false;
+                })
+            }
+            CleanupAction::DeleteProvider { provider_id, .. } => provider_state
+                .manager
+                .delete_provider(provider_id)
+                .map(|_| ()),
+            // 数据库连接在进程内复用，清空数据而不是删除文件
+            CleanupAction::DeleteInstanceDb { path } => {
+                ToolInstanceDB::open(path).and_then(|db| db.clear())
+            }
+            CleanupAction::RemoveConfigDir { path } => cleanup::remove_path(path),
+        };
+        report.record(action, result);
+    }
+
+    if actions
+        .iter()
+        .any(|a| matches!(a, CleanupAction::DeleteProvider { .. }))
+    {
+        let registry = registry_state.registry.lock().await;
+        if let Err(e) =
+            clean_dashboard_selections(&app, &dashboard_state.manager, &registry, &provider_state)
+                .await
+        {
+            tracing::warn!(error = %e, "清理仪表板选择失败");
+        }
+    }
+    if actions.iter().any(|a| {
+        matches!(
+            a,
+            CleanupAction::DeleteInstanceDb { .. } | CleanupAction::RemoveConfigDir { .. }
+        )
+    }) {
+        TOOL_STATUS_CACHE.clear();
+    }
+
+    let report = report.finish();
+    tracing::info!(
+        scopes = ?scopes,
+        items = report.items.len(),
+        success = report.success,
+        "已清理 DuckCoding 管理的状态"
+    );
+    Ok(report)
+}
+
+async fn collect_inputs(
+    proxy_config_mgr: &ProxyConfigManager,
+    proxy_state: &ProxyManagerState,
+    provider_state: &ProviderManagerState,
+) -> CommandResult<CleanupInputs> {
+    let mut proxy_configs = Vec::new();
+    for tool_id in PROXY_TOOLS {
+        if let Some(config) = proxy_config_mgr
+            .get_config(tool_id)
+            .command_context("cleanup.plan_failed")?
+        {
+            proxy_configs.push((tool_id.to_string(), config));
+        }
+    }
+    let running_proxies = proxy_state
+        .manager
+        .get_all_status()
+        .await
+        .into_iter()
+        .filter_map(|(tool_id, running)| running.then_some(tool_id))
+        .collect();
+    let providers = provider_state
+        .manager
+        .list_providers()
+        .command_context("provider.list_failed")?;
+    let paths = app_paths().map_err(CommandError::internal)?;
+
+    Ok(CleanupInputs {
+        proxy_configs,
+        running_proxies,
+        providers,
+        instance_db: paths.tool_instances,
+        config_dir: paths.config_dir,
+    })
+}
+
+fn update_proxy_config(
+    proxy_config_mgr: &ProxyConfigManager,
+    tool_id: &str,
+    f: impl FnOnce(&mut ::duckcoding::models::proxy_config::ToolProxyConfig),
+) -> anyhow::Result<()> {
+    let mut config = proxy_config_mgr
+        .get_config(tool_id)?
+        .ok_or_else(|| anyhow::anyhow!("未找到 {tool_id} 的代理配置"))?;
+    f(&mut config);
+    proxy_config_mgr.update_config(tool_id, config)
+}
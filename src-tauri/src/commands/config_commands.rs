@@ -1,14 +1,19 @@
 // 配置管理相关命令
 
-use super::error::{AppError, AppResult};
+use super::error::{AppError, AppResult, CommandContext, CommandResult};
 use serde_json::Value;
 
+use ::duckcoding::core::i18n;
+use ::duckcoding::data::durable::set_durable_writes_enabled;
+use ::duckcoding::models::config::Locale;
+use ::duckcoding::services::config::effective::{self, EffectiveConfig, ResolveContext};
+use ::duckcoding::services::config::write_ledger::{self, FileProvenance};
 use ::duckcoding::services::config::{
     self, claude, codex, gemini, ClaudeSettingsPayload, CodexSettingsPayload, ExternalConfigChange,
     GeminiEnvPayload, GeminiSettingsPayload, ImportExternalChangeResult,
 };
 use ::duckcoding::services::proxy::config::apply_global_proxy;
-use ::duckcoding::utils::config::{read_global_config, write_global_config};
+use ::duckcoding::utils::config::{app_paths, read_global_config, write_global_config, AppPaths};
 use ::duckcoding::GlobalConfig;
 use ::duckcoding::Tool;
 
@@ -62,6 +67,39 @@ pub async fn ack_external_change(tool: String) -> AppResult<()> {
     Ok(config::acknowledge_external_change(&tool_obj)?)
 }
 
+/// 查询配置文件的来源（DuckCoding 最后一次写入的记录，以及之后是否被外部修改）
+#[tauri::command]
+pub async fn get_file_provenance(path: String) -> AppResult<FileProvenance> {
+    Ok(write_ledger::get_file_provenance(std::path::Path::new(
+        &path,
+    ))?)
+}
+
+/// 解析工具实际生效的配置：按工具自身的优先级规则标注每个值的来源，并与当前应用的配置比较
+///
+/// `project_dir` 为在哪个项目目录中启动工具（影响项目级设置与 `.env` 的查找）
+#[tauri::command]
+pub async fn resolve_effective_config(
+    tool_id: String,
+    project_dir: Option<String>,
+) -> CommandResult<EffectiveConfig> {
+    let tool = Tool::by_id(&tool_id).ok_or_else(|| AppError::ToolNotFound {
+        tool: tool_id.clone(),
+    })?;
+    let project_dir = project_dir
+        .filter(|dir| !dir.trim().is_empty())
+        .map(std::path::PathBuf::from);
+    let context = ResolveContext::current(&tool, project_dir);
+    tokio::task::spawn_blocking(move || {
+        let applied = effective::applied_values(&tool.id)?;
+        effective::resolve(&tool.id, &context, &applied)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result)
+    .command_context("config.effective_failed")
+}
+
 /// 将外部修改导入集中仓
 #[tauri::command]
 pub async fn import_native_change(
@@ -261,6 +299,94 @@ pub fn get_gemini_schema() -> Result<Value, String> {
     gemini::get_gemini_schema().map_err(|e| e.to_string())
 }
 
+// ==================== 数据目录 ====================
+
+/// 获取 DuckCoding 各项数据的存放位置
+#[tauri::command]
+pub async fn get_app_paths() -> Result<AppPaths, String> {
+    app_paths().map_err(|e| format!("获取数据目录失败: {e}"))
+}
+
+// ==================== Shell 环境捕获配置命令 ====================
+
+/// 获取是否启用 shell 环境捕获（仅 macOS 生效）
+#[tauri::command]
+pub async fn get_shell_env_capture_config() -> Result<bool, String> {
+    let config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    Ok(config.shell_env_capture_enabled)
+}
+
+/// 更新 shell 环境捕获配置（需要重启应用生效）
+#[tauri::command]
+pub async fn update_shell_env_capture_config(enabled: bool) -> Result<(), String> {
+    let mut config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+
+    config.shell_env_capture_enabled = enabled;
+
+    write_global_config(&config).map_err(|e| format!("保存配置失败: {e}"))?;
+
+    tracing::info!(enabled = enabled, "Shell 环境捕获配置已更新（需重启生效）");
+
+    Ok(())
+}
+
+// ==================== 持久化写入配置命令 ====================
+
+/// 获取是否启用持久化写入（fsync）
+#[tauri::command]
+pub async fn get_durable_writes_config() -> Result<bool, String> {
+    let config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+    Ok(config.durable_writes_enabled)
+}
+
+/// 更新持久化写入配置（立即生效）
+#[tauri::command]
+pub async fn update_durable_writes_config(enabled: bool) -> Result<(), String> {
+    let mut config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+
+    config.durable_writes_enabled = enabled;
+
+    write_global_config(&config).map_err(|e| format!("保存配置失败: {e}"))?;
+    set_durable_writes_enabled(enabled);
+
+    tracing::info!(enabled = enabled, "持久化写入配置已更新");
+
+    Ok(())
+}
+
+// ==================== 后端文案语言命令 ====================
+
+/// 获取后端文案语言（错误提示等）
+#[tauri::command]
+pub async fn get_backend_locale() -> Result<Locale, String> {
+    Ok(i18n::current_locale())
+}
+
+/// 设置后端文案语言（立即生效并持久化）
+#[tauri::command]
+pub async fn set_backend_locale(locale: Locale) -> Result<(), String> {
+    let mut config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or("配置文件不存在")?;
+
+    config.backend_locale = locale;
+
+    write_global_config(&config).map_err(|e| format!("保存配置失败: {e}"))?;
+    i18n::set_locale(locale);
+
+    tracing::info!(locale = locale.as_str(), "后端文案语言已更新");
+
+    Ok(())
+}
+
 // ==================== 单实例模式配置命令 ====================
 
 /// 获取单实例模式配置状态
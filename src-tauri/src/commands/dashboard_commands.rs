@@ -2,9 +2,29 @@
 //
 // 仪表板状态管理 Tauri 命令
 
+use crate::commands::error::{CommandContext, CommandError, CommandResult};
+use crate::commands::provider_commands::ProviderManagerState;
+use crate::commands::proxy_commands::{
+    collect_proxy_status, ProxyManagerState, TransparentProxyStatus,
+};
+use crate::commands::tool_management::ToolRegistryState;
+use crate::commands::types::ToolStatus;
+use ::duckcoding::core::{tr, tr_with};
+use ::duckcoding::models::dashboard::{
+    ActivityEntry, DashboardSelectionCleared, LaunchPreferences, LaunchPreferencesView,
+};
+use ::duckcoding::models::provider::Provider;
+use ::duckcoding::models::ToolType;
+use ::duckcoding::services::dashboard_manager::{
+    partition_pinned, DASHBOARD_SELECTION_CLEARED_EVENT,
+};
+use ::duckcoding::services::provider_readiness::{validate_active_bindings, ToolReadiness};
+use ::duckcoding::services::tool::path_check::PathMismatch;
+use ::duckcoding::services::tool::ToolRegistry;
 use ::duckcoding::services::DashboardManager;
-use anyhow::Result;
-use tauri::State;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter, State};
 
 /// Dashboard 管理器 State
 pub struct DashboardManagerState {
@@ -30,15 +50,15 @@ impl Default for DashboardManagerState {
 pub async fn get_tool_instance_selection(
     tool_id: String,
     state: State<'_, DashboardManagerState>,
-) -> Result<Option<String>, String> {
+) -> CommandResult<Option<String>> {
     if tool_id.is_empty() {
-        return Err("工具 ID 不能为空".to_string());
+        return Err(CommandError::validation(tr("tool.id_required")));
     }
 
     state
         .manager
         .get_tool_instance_selection(&tool_id)
-        .map_err(|e| format!("获取工具实例选择失败: {}", e))
+        .command_context("dashboard.selection_get_failed")
 }
 
 /// 设置工具实例选择
@@ -47,30 +67,30 @@ pub async fn set_tool_instance_selection(
     tool_id: String,
     instance_id: String,
     state: State<'_, DashboardManagerState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     // 验证参数
     if tool_id.is_empty() {
-        return Err("工具 ID 不能为空".to_string());
+        return Err(CommandError::validation(tr("tool.id_required")));
     }
     if instance_id.is_empty() {
-        return Err("实例 ID 不能为空".to_string());
+        return Err(CommandError::validation(tr("tool.instance_id_required")));
     }
 
     state
         .manager
         .set_tool_instance_selection(tool_id, instance_id)
-        .map_err(|e| format!("设置工具实例选择失败: {}", e))
+        .command_context("dashboard.selection_set_failed")
 }
 
 /// 获取最后选中的供应商 ID
 #[tauri::command]
 pub async fn get_selected_provider_id(
     state: State<'_, DashboardManagerState>,
-) -> Result<Option<String>, String> {
+) -> CommandResult<Option<String>> {
     state
         .manager
         .get_selected_provider_id()
-        .map_err(|e| format!("获取选中供应商失败: {}", e))
+        .command_context("dashboard.selected_provider_get_failed")
 }
 
 /// 设置最后选中的供应商 ID
@@ -78,9 +98,343 @@ pub async fn get_selected_provider_id(
 pub async fn set_selected_provider_id(
     provider_id: Option<String>,
     state: State<'_, DashboardManagerState>,
-) -> Result<(), String> {
+) -> CommandResult<()> {
     state
         .manager
         .set_selected_provider_id(provider_id)
-        .map_err(|e| format!("设置选中供应商失败: {}", e))
+        .command_context("dashboard.selected_provider_set_failed")
+}
+
+/// 获取工具实例的启动偏好（工作目录、额外参数、环境变量覆盖）
+///
+/// 附带实例级环境变量覆盖（敏感值已脱敏），两者都没有时返回 None。
+#[tauri::command]
+pub async fn get_launch_preferences(
+    instance_id: String,
+    state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+) -> CommandResult<Option<LaunchPreferencesView>> {
+    let preferences = state
+        .manager
+        .get_launch_preferences(&instance_id)
+        .command_context("dashboard.launch_preferences_get_failed")?;
+    let instance_env = {
+        let registry = registry_state.registry.lock().await;
+        registry
+            .get_all_grouped()
+            .await
+            .command_context("tool.instances_get_failed")?
+            .into_values()
+            .flatten()
+            .find(|instance| instance.instance_id == instance_id)
+            .map(|instance| instance.masked().env_overrides)
+            .unwrap_or_default()
+    };
+
+    if preferences.is_none() && instance_env.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(LaunchPreferencesView {
+        preferences: preferences.unwrap_or_default(),
+        instance_env_overrides: instance_env,
+    }))
+}
+
+/// 设置工具实例的启动偏好
+///
+/// 本地实例要求工作目录存在；WSL/SSH 实例的路径无法在本机校验。
+#[tauri::command]
+pub async fn set_launch_preferences(
+    instance_id: String,
+    preferences: LaunchPreferences,
+    state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+) -> CommandResult<()> {
+    let instance = {
+        let registry = registry_state.registry.lock().await;
+        registry
+            .get_all_grouped()
+            .await
+            .command_context("tool.instances_get_failed")?
+            .into_values()
+            .flatten()
+            .find(|instance| instance.instance_id == instance_id)
+            .ok_or_else(|| {
+                CommandError::not_found(tr_with(
+                    "tool.instance_not_found",
+                    &[("id", instance_id.as_str())],
+                ))
+            })?
+    };
+
+    state
+        .manager
+        .set_launch_preferences(
+            &instance_id,
+            preferences,
+            instance.tool_type == ToolType::Local,
+        )
+        .command_context("dashboard.launch_preferences_set_failed")
+}
+
+/// 置顶供应商，返回置顶后的供应商 ID 列表
+#[tauri::command]
+pub async fn pin_provider(
+    provider_id: String,
+    state: State<'_, DashboardManagerState>,
+    provider_state: State<'_, ProviderManagerState>,
+) -> CommandResult<Vec<String>> {
+    let exists = provider_state
+        .manager
+        .get_provider(&provider_id)
+        .command_context("provider.get_failed")?
+        .is_some();
+    if !exists {
+        return Err(CommandError::not_found(tr_with(
+            "provider.not_found",
+            &[("id", provider_id.as_str())],
+        )));
+    }
+
+    state
+        .manager
+        .pin_provider(&provider_id)
+        .command_context("dashboard.pin_failed")
+}
+
+/// 取消置顶供应商，返回剩余的置顶供应商 ID 列表
+#[tauri::command]
+pub async fn unpin_provider(
+    provider_id: String,
+    state: State<'_, DashboardManagerState>,
+) -> CommandResult<Vec<String>> {
+    state
+        .manager
+        .unpin_provider(&provider_id)
+        .command_context("dashboard.unpin_failed")
+}
+
+/// 获取最近活动记录（按时间倒序，默认 20 条）
+#[tauri::command]
+pub async fn get_recent_activity(
+    limit: Option<usize>,
+    state: State<'_, DashboardManagerState>,
+) -> CommandResult<Vec<ActivityEntry>> {
+    state
+        .manager
+        .recent_activity(limit.unwrap_or(20))
+        .command_context("dashboard.activity_get_failed")
+}
+
+/// 清空活动记录
+#[tauri::command]
+pub async fn clear_activity(state: State<'_, DashboardManagerState>) -> CommandResult<()> {
+    state
+        .manager
+        .clear_activity()
+        .command_context("dashboard.activity_clear_failed")
+}
+
+/// 按当前实例与供应商列表清理失效选择，有清理内容时通知前端
+///
+/// 调用方需已持有注册表锁（传入 `registry`），避免在删除实例的同时重复加锁。
+pub async fn clean_dashboard_selections(
+    app: &AppHandle,
+    dashboard: &DashboardManager,
+    registry: &ToolRegistry,
+    providers: &ProviderManagerState,
+) -> CommandResult<DashboardSelectionCleared> {
+    // 实例 ID 迁移后先改写旧 ID，避免选择被当作失效记录清除
+    let aliases = registry
+        .instance_id_aliases()
+        .await
+        .command_context("tool.instances_get_failed")?;
+    dashboard
+        .rename_instance_ids(&aliases)
+        .command_context("dashboard.clean_selections_failed")?;
+
+    let known_instance_ids: HashSet<String> = registry
+        .get_all_grouped()
+        .await
+        .command_context("tool.instances_get_failed")?
+        .into_values()
+        .flatten()
+        .map(|instance| instance.instance_id)
+        .collect();
+    let known_provider_ids: HashSet<String> = providers
+        .manager
+        .list_providers()
+        .command_context("provider.list_failed")?
+        .into_iter()
+        .map(|provider| provider.id)
+        .collect();
+
+    let cleared = dashboard
+        .validate_and_clean(&known_instance_ids, &known_provider_ids)
+        .command_context("dashboard.clean_selections_failed")?;
+    if !cleared.is_empty() {
+        let _ = app.emit(DASHBOARD_SELECTION_CLEARED_EVENT, &cleared);
+    }
+    Ok(cleared)
+}
+
+/// 手动修复仪表板选择（移除指向已删除实例或供应商的记录）
+#[tauri::command]
+pub async fn repair_dashboard_selections(
+    app: AppHandle,
+    state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+    provider_state: State<'_, ProviderManagerState>,
+) -> CommandResult<DashboardSelectionCleared> {
+    let registry = registry_state.registry.lock().await;
+    clean_dashboard_selections(&app, &state.manager, &registry, &provider_state).await
+}
+
+/// 仪表板快照中的一个分区
+///
+/// 各分区独立加载，某个子系统失败时只有对应分区携带 `error`，其余分区照常返回。
+#[derive(Debug, Serialize)]
+pub struct SnapshotSection<T> {
+    pub data: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T> From<Result<T, String>> for SnapshotSection<T> {
+    fn from(result: Result<T, String>) -> Self {
+        match result {
+            Ok(data) => Self {
+                data: Some(data),
+                error: None,
+            },
+            Err(error) => Self {
+                data: None,
+                error: Some(error),
+            },
+        }
+    }
+}
+
+/// 仪表板中记录的选择
+#[derive(Debug, Serialize)]
+pub struct DashboardSelections {
+    pub tool_instance_selections: HashMap<String, String>,
+    pub selected_provider_id: Option<String>,
+}
+
+/// 仪表板中的供应商列表（令牌已脱敏）
+#[derive(Debug, Serialize)]
+pub struct DashboardProviders {
+    /// 置顶供应商（按置顶顺序）
+    pub pinned: Vec<Provider>,
+    /// 其余供应商
+    pub others: Vec<Provider>,
+}
+
+/// 仪表板首屏所需的全部数据
+#[derive(Serialize)]
+pub struct DashboardSnapshot {
+    pub tool_status: SnapshotSection<Vec<ToolStatus>>,
+    pub selections: SnapshotSection<DashboardSelections>,
+    pub providers: SnapshotSection<DashboardProviders>,
+    pub proxy_status: SnapshotSection<HashMap<String, TransparentProxyStatus>>,
+    /// 所选实例与 shell 中实际运行的命令不一致的工具（WSL/SSH 实例不检查）
+    pub path_mismatches: SnapshotSection<Vec<PathMismatch>>,
+    /// 各工具绑定供应商的就绪状态（仅在 `validate_bindings` 为 true 时返回）
+    pub binding_readiness: Option<SnapshotSection<Vec<ToolReadiness>>>,
+}
+
+/// 一次性获取仪表板数据（替代首屏的多次 IPC 调用）
+///
+/// 工具状态来自状态缓存或数据库中已检测的实例；仅缓存过期的工具会在后台重新检测。
+/// `validate_bindings` 为 true 时额外验证各工具绑定的供应商（优先使用缓存，最多约 3 秒）。
+#[tauri::command]
+pub async fn get_dashboard_snapshot(
+    validate_bindings: Option<bool>,
+    state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+    provider_state: State<'_, ProviderManagerState>,
+    proxy_state: State<'_, ProxyManagerState>,
+) -> CommandResult<DashboardSnapshot> {
+    let tool_status = async {
+        registry_state
+            .tool_statuses()
+            .await
+            .map_err(|e| format!("获取工具状态失败: {}", e))
+    };
+    let selections = async {
+        state
+            .manager
+            .load_store()
+            .map(|store| DashboardSelections {
+                tool_instance_selections: store.tool_instance_selections,
+                selected_provider_id: store.selected_provider_id,
+            })
+            .map_err(|e| format!("读取仪表板选择失败: {}", e))
+    };
+    let providers = async {
+        let providers = provider_state
+            .manager
+            .list_providers()
+            .map_err(|e| format!("获取供应商列表失败: {}", e))?;
+        // 置顶信息读取失败时退化为不分组
+        let pinned_ids = state.manager.pinned_provider_ids().unwrap_or_default();
+        let (pinned, others) = partition_pinned(
+            providers.iter().map(Provider::masked).collect(),
+            &pinned_ids,
+            |p| p.id.as_str(),
+        );
+        Ok(DashboardProviders { pinned, others })
+    };
+    let proxy_status = collect_proxy_status(&proxy_state);
+    let path_mismatches = async {
+        let selections = state
+            .manager
+            .load_store()
+            .map(|store| store.tool_instance_selections)
+            .unwrap_or_default();
+        registry_state
+            .registry
+            .lock()
+            .await
+            .check_path_mismatches(&selections)
+            .await
+            .map_err(|e| format!("检查工具路径失败: {}", e))
+    };
+
+    let binding_readiness = async {
+        if !validate_bindings.unwrap_or(false) {
+            return None;
+        }
+        let result = async {
+            let bindings = state
+                .manager
+                .load_store()
+                .map_err(|e| format!("读取仪表板选择失败: {}", e))?
+                .tool_provider_bindings;
+            let providers = provider_state
+                .manager
+                .list_providers()
+                .map_err(|e| format!("获取供应商列表失败: {}", e))?;
+            Ok::<_, String>(validate_active_bindings(&bindings, &providers).await)
+        };
+        Some(result.await)
+    };
+
+    let (tool_status, selections, providers, proxy_status, path_mismatches, binding_readiness) = tokio::join!(
+        tool_status,
+        selections,
+        providers,
+        proxy_status,
+        path_mismatches,
+        binding_readiness
+    );
+
+    Ok(DashboardSnapshot {
+        tool_status: tool_status.into(),
+        selections: selections.into(),
+        providers: providers.into(),
+        proxy_status: proxy_status.into(),
+        path_mismatches: path_mismatches.into(),
+        binding_readiness: binding_readiness.map(Into::into),
+    })
 }
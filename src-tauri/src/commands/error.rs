@@ -55,6 +55,36 @@
 //! **优点**：关键错误使用结构化类型，其他保持简单
 //! **缺点**：代码风格不统一
 //!
+//! #### 方案 D：使用 CommandError（新命令推荐）
+//!
+//! ```rust
+//! use super::error::{CommandContext, CommandError, CommandResult};
+//!
+//! #[tauri::command]
+//! pub async fn my_command(id: String) -> CommandResult<Data> {
+//!     if id.is_empty() {
+//!         return Err(CommandError::validation("ID 不能为空"));
+//!     }
+//!     service::process(&id).command_context("处理失败")
+//! }
+//! ```
+//!
+//! 序列化结构固定为 `{ code, message, details }`，前端按 `code` 分支或本地化：
+//!
+//! | code | 含义 |
+//! |------|------|
+//! | `NOT_FOUND` | 资源不存在 |
+//! | `VALIDATION` | 参数校验失败 |
+//! | `IO` | 文件读写失败 |
+//! | `NETWORK` | 网络请求失败 |
+//! | `CONFLICT` | 资源已存在或状态冲突 |
+//! | `LOCKED` | 文件被其他进程锁定 |
+//! | `UNSUPPORTED` | 当前平台或环境不支持 |
+//! | `INTERNAL` | 未归类错误 |
+//!
+//! `message` 为可读的兜底文案，`details` 为完整错误链（可能为 `null`）。
+//! 错误码由错误链中的 `DataError` / `AppError` / `io::Error` / `reqwest::Error` 推断。
+//!
 //! ### 错误类型速查
 //!
 //! - `AppError::ToolNotFound { tool }`  - 工具未找到
@@ -66,12 +96,14 @@
 //! ### 迁移计划
 //!
 //! 1. ✅ 创建 error.rs 模块（本文件）
-//! 2. ⏳ 迁移高频命令（config/profile/tool）
-//! 3. ⏳ 迁移中频命令（proxy/session）
-//! 4. ⏳ 迁移低频命令（其他）
+//! 2. ✅ 迁移 provider / dashboard / tool 命令到 CommandError
+//! 3. ⏳ 迁移高频命令（config/profile）
+//! 4. ⏳ 迁移中频命令（proxy/session）
+//! 5. ⏳ 迁移低频命令（其他）
 //!
 //! ## 导出
 //!
 //! 重导出 core::error 中的类型供 commands 层使用
 
+pub use ::duckcoding::core::command_error::{CommandContext, CommandError, CommandResult};
 pub use ::duckcoding::core::error::{AppError, AppResult};
@@ -0,0 +1,186 @@
+//! 在系统文件管理器中打开实例 / 配置 / 日志位置
+
+use crate::commands::error::{CommandContext, CommandError, CommandResult};
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::core::{tr, tr_with};
+use ::duckcoding::models::{Tool, ToolInstance, ToolType};
+use ::duckcoding::utils::config::app_paths;
+use ::duckcoding::utils::wsl_to_windows_path;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// 可打开的位置类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealKind {
+    /// 工具实例的安装位置（`id` 为实例 ID）
+    InstanceInstall,
+    /// 工具配置目录（`id` 为工具 ID 或实例 ID，WSL 实例打开 UNC 路径）
+    ToolConfig,
+    /// DuckCoding 配置目录
+    AppConfig,
+    /// 日志目录
+    Logs,
+}
+
+/// 在系统文件管理器中打开指定位置，返回实际打开的路径
+///
+/// 目标不存在时返回 `NOT_FOUND`，不会静默忽略。
+#[tauri::command]
+pub async fn reveal_path(
+    kind: RevealKind,
+    id: Option<String>,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> CommandResult<String> {
+    let path = match kind {
+        RevealKind::AppConfig => app_paths().map_err(CommandError::internal)?.config_dir,
+        RevealKind::Logs => app_paths().map_err(CommandError::internal)?.log_dir,
+        RevealKind::InstanceInstall => {
+            let instance = find_instance(&registry_state, required_id(&id)?).await?;
+            instance_install_path(&instance).await?
+        }
+        RevealKind::ToolConfig => {
+            let id = required_id(&id)?;
+            match Tool::by_id(id) {
+                Some(tool) => tool.config_dir,
+                None => {
+                    let instance = find_instance(&registry_state, id).await?;
+                    instance_config_dir(&instance).await?
+                }
+            }
+        }
+    };
+
+    if !path.exists() {
+        return Err(CommandError::not_found(tr_with(
+            "reveal.path_missing",
+            &[("path", path.to_string_lossy().as_ref())],
+        )));
+    }
+
+    let result = if path.is_dir() {
+        open_directory(&path)
+    } else {
+        reveal_in_file_manager(&path)
+    };
+    result.command_context("reveal.open_failed")?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+fn required_id(id: &Option<String>) -> CommandResult<&str> {
+    id.as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| CommandError::validation(tr("tool.instance_id_required")))
+}
+
+async fn find_instance(
+    registry_state: &ToolRegistryState,
+    instance_id: &str,
+) -> CommandResult<ToolInstance> {
+    let registry = registry_state.registry.lock().await;
+    registry
+        .get_all_grouped()
+        .await
+        .command_context("tool.instances_get_failed")?
+        .into_values()
+        .flatten()
+        .find(|instance| instance.instance_id == instance_id)
+        .ok_or_else(|| {
+            CommandError::not_found(tr_with("tool.instance_not_found", &[("id", instance_id)]))
+        })
+}
+
+/// 实例安装路径（WSL 实例转换为 Windows 可访问的 UNC 路径）
+async fn instance_install_path(instance: &ToolInstance) -> CommandResult<PathBuf> {
+    let install_path = instance
+        .install_path
+        .as_deref()
+        .ok_or_else(|| CommandError::not_found(tr("reveal.install_path_unknown")))?;
+    match instance.tool_type {
+        ToolType::Local => Ok(PathBuf::from(install_path)),
+        ToolType::WSL => {
+            if let Some(path) = &instance.windows_install_path {
+                return Ok(PathBuf::from(path));
+            }
+            wsl_path(instance, install_path).await
+        }
+        ToolType::SSH => Err(CommandError::unsupported(tr("reveal.ssh_unsupported"))),
+    }
+}
+
+/// 实例所在环境中的工具配置目录
+async fn instance_config_dir(instance: &ToolInstance) -> CommandResult<PathBuf> {
+    let tool = Tool::by_id(&instance.base_id).ok_or_else(|| {
+        CommandError::not_found(tr_with(
+            "tool.instance_not_found",
+            &[("id", instance.base_id.as_str())],
+        ))
+    })?;
+    match instance.tool_type {
+        ToolType::Local => Ok(tool.config_dir),
+        ToolType::WSL => wsl_path(instance, &home_relative(&tool.config_dir)).await,
+        ToolType::SSH => Err(CommandError::unsupported(tr("reveal.ssh_unsupported"))),
+    }
+}
+
+async fn wsl_path(instance: &ToolInstance, path: &str) -> CommandResult<PathBuf> {
+    let distro = instance
+        .wsl_distro
+        .as_deref()
+        .ok_or_else(|| CommandError::internal(tr("reveal.wsl_distro_missing")))?;
+    wsl_to_windows_path(distro, path)
+        .await
+        .map(PathBuf::from)
+        .command_context("reveal.wsl_path_failed")
+}
+
+/// 本机主目录下的路径改写为 `~/` 形式（供 WSL 发行版内按其主目录展开）
+fn home_relative(path: &Path) -> String {
+    let relative = dirs::home_dir()
+        .and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf))
+        .or_else(|| path.file_name().map(PathBuf::from))
+        .unwrap_or_default();
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    format!("~/{}", parts.join("/"))
+}
+
+/// 在系统文件管理器中打开目录
+fn open_directory(path: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program).arg(path).spawn()?;
+    Ok(())
+}
+
+/// 在系统文件管理器中定位文件
+pub(crate) fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .arg(format!("/select,{}", path.display()))
+            .spawn()?;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg("-R")
+            .arg(path)
+            .spawn()?;
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let dir = path.parent().unwrap_or(path);
+        std::process::Command::new("xdg-open").arg(dir).spawn()?;
+    }
+    Ok(())
+}
@@ -1,8 +1,13 @@
 // 日志配置管理命令
 // 提供前端查询和更新日志配置的接口
 
-use duckcoding::models::config::LogConfig;
-use duckcoding::utils::config::{read_global_config, write_global_config};
+use super::file_manager_commands::reveal_in_file_manager;
+use super::tool_commands::get_effective_path;
+use duckcoding::core::logger::get_log_dir;
+use duckcoding::core::{current_log_level, read_log_tail, LogLine};
+use duckcoding::models::config::{LogConfig, LogLevel};
+use duckcoding::services::diagnostics::{self, DiagnosticsInput};
+use duckcoding::utils::config::{app_paths, read_global_config, write_global_config};
 use tauri::command;
 
 /// 检测当前是否为 Release 构建
@@ -70,3 +75,69 @@ pub async fn update_log_config(new_config: LogConfig) -> Result<String, String>
         Ok("日志配置已保存，需要重启应用后生效".to_string())
     }
 }
+
+/// 获取当前生效的日志级别
+#[command]
+pub fn get_log_level() -> LogLevel {
+    current_log_level()
+}
+
+/// 运行时切换日志级别（立即生效并保存到配置）
+#[command]
+pub async fn set_log_level(level: LogLevel) -> Result<(), String> {
+    duckcoding::update_log_level(level).map_err(|e| format!("切换日志级别失败: {e}"))?;
+
+    let mut global_config = read_global_config()
+        .map_err(|e| format!("读取配置失败: {e}"))?
+        .ok_or_else(|| "配置文件不存在".to_string())?;
+    global_config.log_config.level = level;
+    write_global_config(&global_config).map_err(|e| format!("保存配置失败: {e}"))?;
+
+    Ok(())
+}
+
+/// 读取最近的日志（`level_filter` 指定时只返回该级别及以上）
+#[command]
+pub async fn get_log_tail(
+    lines: usize,
+    level_filter: Option<LogLevel>,
+) -> Result<Vec<LogLine>, String> {
+    let log_config = read_global_config()?
+        .map(|config| config.log_config)
+        .unwrap_or_default();
+    let log_dir = get_log_dir(log_config.file_path.as_deref()).map_err(|e| e.to_string())?;
+
+    tokio::task::spawn_blocking(move || read_log_tail(&log_dir, lines, level_filter))
+        .await
+        .map_err(|e| format!("读取日志失败: {e}"))?
+        .map_err(|e| format!("读取日志失败: {e}"))
+}
+
+/// 导出诊断包（版本与平台信息、PATH、脱敏配置、日志尾部）
+///
+/// 导出完成后在文件管理器中定位该文件，返回诊断包路径
+#[command]
+pub async fn export_diagnostics(app: tauri::AppHandle) -> Result<String, String> {
+    let paths = app_paths().map_err(|e| format!("获取数据目录失败: {e}"))?;
+    let effective_path = get_effective_path(Some(false))
+        .await
+        .map(|path| path.entries)
+        .map_err(|e| format!("获取 PATH 失败: {e}"))?;
+
+    let input = DiagnosticsInput {
+        app_version: app.package_info().version.to_string(),
+        effective_path,
+        config_dir: paths.config_dir,
+        log_dir: paths.log_dir,
+    };
+    let path = tokio::task::spawn_blocking(move || diagnostics::export_diagnostics(&input))
+        .await
+        .map_err(|e| format!("导出诊断包失败: {e}"))?
+        .map_err(|e| format!("导出诊断包失败: {e}"))?;
+
+    if let Err(e) = reveal_in_file_manager(&path) {
+        tracing::warn!(error = %e, "打开文件管理器失败");
+    }
+    tracing::info!(path = ?path, "诊断包已导出");
+    Ok(path.to_string_lossy().into_owned())
+}
@@ -1,38 +1,56 @@
+pub mod backup_commands; // 完整状态备份
 pub mod balance_commands;
+pub mod cleanup_commands; // 清理 DuckCoding 管理的状态
 pub mod config_commands;
 pub mod dashboard_commands; // 仪表板状态管理命令
 pub mod error; // 错误处理统一模块
+pub mod file_manager_commands; // 在文件管理器中打开位置
 pub mod log_commands;
 pub mod onboarding;
+pub mod operations; // 长耗时操作进度协议
 pub mod profile_commands; // Profile 管理命令（v2.0）
 pub mod provider_commands; // 供应商管理命令（v1.5.0）
 pub mod proxy_commands;
+pub mod selfcheck_commands; // 启动自检
 pub mod session_commands;
+pub mod setup_commands; // 一键配置工具
 pub mod startup_commands; // 开机自启动管理命令
 pub mod stats_commands;
+pub mod telemetry_commands; // 匿名使用统计
 pub mod token_commands; // 令牌资产管理命令（NEW API 集成）
 pub mod tool_commands;
 pub mod tool_management;
+pub mod trash_commands; // 回收站
+pub mod trial_commands; // 供应商限时试用
 pub mod types;
 pub mod update_commands;
 pub mod watcher_commands;
 pub mod window_commands;
 
 // 重新导出所有命令函数
+pub use backup_commands::*;
 pub use balance_commands::*;
+pub use cleanup_commands::*;
 pub use config_commands::*;
 pub use dashboard_commands::*; // 仪表板状态管理命令
+pub use file_manager_commands::*;
 pub use log_commands::*;
 pub use onboarding::*;
+pub use operations::*;
 pub use profile_commands::*; // Profile 管理命令（v2.0）
 pub use provider_commands::*; // 供应商管理命令（v1.5.0）
 pub use proxy_commands::*;
+pub use selfcheck_commands::*;
 pub use session_commands::*;
+pub use setup_commands::*;
 pub use startup_commands::*; // 开机自启动管理命令
 pub use stats_commands::*;
+pub use telemetry_commands::*;
 pub use token_commands::*; // 令牌资产管理命令（NEW API 集成）
 pub use tool_commands::*;
 pub use tool_management::*;
+pub use trash_commands::*;
+pub use trial_commands::*;
 pub use update_commands::*;
 pub use watcher_commands::*;
 pub use window_commands::*;
@@ -1,6 +1,8 @@
 // filepath: e:\DuckCoding\src-tauri\src\commands\onboarding.rs
 
 use duckcoding::models::config::{GlobalConfig, LogConfig, OnboardingStatus};
+use duckcoding::models::onboarding::OnboardingState;
+use duckcoding::services::OnboardingManager;
 use duckcoding::utils::config::{read_global_config, write_global_config};
 use std::collections::HashMap;
 use tracing::{error, info};
@@ -28,6 +30,19 @@ fn create_minimal_config() -> GlobalConfig {
         external_poll_interval_ms: 5000,
         single_instance_enabled: true,
         startup_enabled: false,
+        pricing: Default::default(),
+        balance_poll: Default::default(),
+        shell_env_capture_enabled: true,
+        durable_writes_enabled: true,
+        tool_status_cache: Default::default(),
+        offline_mode: false,
+        github_token: None,
+        skipped_versions: Default::default(),
+        backend_locale: Default::default(),
+        telemetry: Default::default(),
+        package_overrides: Default::default(),
+        extra_path_entries: Vec::new(),
+        state_backup: Default::default(),
     }
 }
 
@@ -139,6 +154,13 @@ pub async fn reset_onboarding() -> Result<(), String> {
     // 清空引导状态
     config.onboarding_status = None;
 
+    OnboardingManager::new()
+        .and_then(|manager| manager.reset())
+        .map_err(|e| {
+            error!("重置引导步骤失败: {:?}", e);
+            format!("重置引导步骤失败: {}", e)
+        })?;
+
     write_global_config(&config).map_err(|e| {
         error!("写入配置失败: {}", e);
         format!("写入配置失败: {}", e)
@@ -147,3 +169,24 @@ pub async fn reset_onboarding() -> Result<(), String> {
     info!("引导状态已重置");
     Ok(())
 }
+
+/// 获取后端记录的引导状态（已完成/跳过的步骤、选择的工具）
+#[tauri::command]
+pub async fn get_onboarding_state() -> Result<OnboardingState, String> {
+    OnboardingManager::new()
+        .and_then(|manager| manager.load())
+        .map_err(|e| format!("读取引导状态失败: {}", e))
+}
+
+/// 标记引导步骤完成（`skipped` 为 true 时记为跳过）
+#[tauri::command]
+pub async fn mark_onboarding_step(
+    step: String,
+    skipped: Option<bool>,
+) -> Result<OnboardingState, String> {
+    info!("标记引导步骤: step={}, skipped={:?}", step, skipped);
+
+    OnboardingManager::new()
+        .and_then(|manager| manager.mark_step(&step, skipped.unwrap_or(false)))
+        .map_err(|e| format!("记录引导步骤失败: {}", e))
+}
@@ -0,0 +1,398 @@
+//! 长耗时操作进度协议
+//!
+//! 安装、更新、检测等操作统一通过 [`OperationTracker`] 上报进度，前端只需监听两个事件：
+//!
+//! - `operation-progress`：[`OperationProgress`]，`{operation_id, kind, phase, percent?, message, payload?}`
+//! - `operation-finished`：[`OperationFinished`]，携带命令的结果或 [`CommandError`]
+//!
+//! 同一操作的事件通过 `operation_id` 关联，第一条进度事件的阶段固定为 `started`，
+//! `payload.target` 为操作对象（工具 ID、实例 ID 等）。
+//! `list_active_operations` 列出进行中的操作，可取消的操作通过 `cancel_operation` 取消。
+//!
+//! ```rust
+//! let op = OPERATIONS.start_operation(&app, OperationKind::Install, &tool);
+//! let result = async { /* op.progress(...) */ }.await;
+//! op.finish(&result);
+//! result
+//! ```
+
+use crate::commands::error::{CommandError, CommandResult};
+use ::duckcoding::core::tr;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio_util::sync::CancellationToken;
+
+/// 操作进度事件
+pub const OPERATION_PROGRESS_EVENT: &str = "operation-progress";
+
+/// 操作结束事件
+pub const OPERATION_FINISHED_EVENT: &str = "operation-finished";
+
+/// 操作开始时的阶段名
+pub const PHASE_STARTED: &str = "started";
+
+/// 操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// 安装工具
+    Install,
+    /// 更新工具实例
+    Update,
+    /// 检测工具
+    Detection,
+    /// 扫描工具候选路径
+    Scan,
+    /// 一键配置工具（检测、安装、绑定供应商、应用配置）
+    Setup,
+}
+
+/// `operation-progress` 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationProgress {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub phase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f32>,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Value>,
+}
+
+/// `operation-finished` 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct OperationFinished {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    pub success: bool,
+    /// 是否因用户取消而结束
+    pub cancelled: bool,
+    /// 成功时为命令返回值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// 失败时为命令错误
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<CommandError>,
+}
+
+/// 进行中的操作（`list_active_operations` 返回）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperationInfo {
+    pub operation_id: String,
+    pub kind: OperationKind,
+    /// 操作对象（工具 ID、实例 ID 等）
+    pub target: String,
+    pub phase: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f32>,
+    pub message: String,
+    /// 开始时间（Unix 毫秒）
+    pub started_at: i64,
+    /// 是否支持取消
+    pub cancellable: bool,
+}
+
+struct ActiveOperation {
+    info: OperationInfo,
+    cancel: Option<CancellationToken>,
+}
+
+/// 进行中操作的登记表
+#[derive(Default)]
+pub struct OperationTracker {
+    active: Mutex<HashMap<String, ActiveOperation>>,
+    next_id: AtomicU64,
+}
+
+/// 全局操作登记表
+pub static OPERATIONS: Lazy<OperationTracker> = Lazy::new(OperationTracker::default);
+
+impl OperationTracker {
+    /// 开始一个不可取消的操作
+    pub fn start_operation(
+        &'static self,
+        app: &AppHandle,
+        kind: OperationKind,
+        target: &str,
+    ) -> Operation {
+        self.start(app, kind, target, false)
+    }
+
+    /// 开始一个可取消的操作（配合 [`Operation::run_cancellable`] 使用）
+    pub fn start_cancellable(
+        &'static self,
+        app: &AppHandle,
+        kind: OperationKind,
+        target: &str,
+    ) -> Operation {
+        self.start(app, kind, target, true)
+    }
+
+    fn start(
+        &'static self,
+        app: &AppHandle,
+        kind: OperationKind,
+        target: &str,
+        cancellable: bool,
+    ) -> Operation {
+        let (id, cancel) = self.register(kind, target, cancellable);
+        let operation = Operation {
+            id,
+            kind,
+            app: app.clone(),
+            tracker: self,
+            cancel,
+        };
+        operation.emit_progress(
+            PHASE_STARTED,
+            None,
+            String::new(),
+            Some(serde_json::json!({ "target": target })),
+        );
+        operation
+    }
+
+    fn register(
+        &self,
+        kind: OperationKind,
+        target: &str,
+        cancellable: bool,
+    ) -> (String, Option<CancellationToken>) {
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let id = format!("op-{started_at}-{seq}");
+        let cancel = cancellable.then(CancellationToken::new);
+        let info = OperationInfo {
+            operation_id: id.clone(),
+            kind,
+            target: target.to_string(),
+            phase: PHASE_STARTED.to_string(),
+            percent: None,
+            message: String::new(),
+            started_at,
+            cancellable,
+        };
+        self.active.lock().unwrap().insert(
+            id.clone(),
+            ActiveOperation {
+                info,
+                cancel: cancel.clone(),
+            },
+        );
+        (id, cancel)
+    }
+
+    /// 列出进行中的操作（按开始时间排序）
+    pub fn list(&self) -> Vec<OperationInfo> {
+        let mut operations: Vec<OperationInfo> = self
+            .active
+            .lock()
+            .unwrap()
+            .values()
+            .map(|op| op.info.clone())
+            .collect();
+        operations.sort_by(|a, b| {
+            a.started_at
+                .cmp(&b.started_at)
+                .then_with(|| a.operation_id.cmp(&b.operation_id))
+        });
+        operations
+    }
+
+    /// 请求取消操作，返回是否已发出取消（操作不存在或不支持取消时返回 false）
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self
+            .active
+            .lock()
+            .unwrap()
+            .get(operation_id)
+            .and_then(|op| op.cancel.as_ref())
+        {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn update(&self, operation_id: &str, phase: &str, percent: Option<f32>, message: &str) {
+        if let Some(op) = self.active.lock().unwrap().get_mut(operation_id) {
+            op.info.phase = phase.to_string();
+            op.info.percent = percent;
+            op.info.message = message.to_string();
+        }
+    }
+
+    fn remove(&self, operation_id: &str) {
+        self.active.lock().unwrap().remove(operation_id);
+    }
+}
+
+/// 进行中的操作句柄（drop 时自动从登记表移除）
+pub struct Operation {
+    id: String,
+    kind: OperationKind,
+    app: AppHandle,
+    tracker: &'static OperationTracker,
+    cancel: Option<CancellationToken>,
+}
+
+impl Operation {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// 上报进度
+    pub fn progress(&self, phase: &str, percent: Option<f32>, message: impl Into<String>) {
+        self.emit_progress(phase, percent, message.into(), None);
+    }
+
+    /// 上报进度并附带结构化数据
+    pub fn progress_with(
+        &self,
+        phase: &str,
+        percent: Option<f32>,
+        message: impl Into<String>,
+        payload: Value,
+    ) {
+        self.emit_progress(phase, percent, message.into(), Some(payload));
+    }
+
+    fn emit_progress(
+        &self,
+        phase: &str,
+        percent: Option<f32>,
+        message: String,
+        payload: Option<Value>,
+    ) {
+        self.tracker.update(&self.id, phase, percent, &message);
+        let event = OperationProgress {
+            operation_id: self.id.clone(),
+            kind: self.kind,
+            phase: phase.to_string(),
+            percent,
+            message,
+            payload,
+        };
+        if let Err(e) = self.app.emit(OPERATION_PROGRESS_EVENT, &event) {
+            tracing::warn!(operation_id = %self.id, error = ?e, "发送操作进度事件失败");
+        }
+    }
+
+    /// 执行可被取消的任务：取消时放弃任务并返回「操作已取消」
+    ///
+    /// 仅适用于中途放弃不会留下半成品的任务（检测、扫描等）。
+    pub async fn run_cancellable<T>(
+        &self,
+        task: impl Future<Output = CommandResult<T>>,
+    ) -> CommandResult<T> {
+        let Some(token) = &self.cancel else {
+            return task.await;
+        };
+        tokio::select! {
+            result = task => result,
+            _ = token.cancelled() => Err(CommandError::conflict(tr("operation.cancelled"))),
+        }
+    }
+
+    /// 结束操作并发送 `operation-finished`
+    pub fn finish<T: Serialize>(self, result: &CommandResult<T>) {
+        let cancelled = self.cancel.as_ref().is_some_and(|t| t.is_cancelled());
+        let event = match result {
+            Ok(value) => OperationFinished {
+                operation_id: self.id.clone(),
+                kind: self.kind,
+                success: true,
+                cancelled: false,
+                result: serde_json::to_value(value).ok(),
+                error: None,
+            },
+            Err(err) => OperationFinished {
+                operation_id: self.id.clone(),
+                kind: self.kind,
+                success: false,
+                cancelled,
+                result: None,
+                error: Some(err.clone()),
+            },
+        };
+        // 先移除再通知，前端收到结束事件后刷新列表时不会再看到该操作
+        self.tracker.remove(&self.id);
+        if let Err(e) = self.app.emit(OPERATION_FINISHED_EVENT, &event) {
+            tracing::warn!(operation_id = %self.id, error = ?e, "发送操作结束事件失败");
+        }
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        self.tracker.remove(&self.id);
+    }
+}
+
+/// 列出进行中的长耗时操作
+#[tauri::command]
+pub async fn list_active_operations() -> CommandResult<Vec<OperationInfo>> {
+    Ok(OPERATIONS.list())
+}
+
+/// 取消操作（仅对支持取消的操作生效）
+#[tauri::command]
+pub async fn cancel_operation(operation_id: String) -> CommandResult<()> {
+    let exists = OPERATIONS
+        .list()
+        .iter()
+        .any(|op| op.operation_id == operation_id);
+    if !exists {
+        return Err(CommandError::not_found(tr("operation.not_found")));
+    }
+    if !OPERATIONS.cancel(&operation_id) {
+        return Err(CommandError::unsupported(tr("operation.not_cancellable")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_update_and_remove() {
+        let tracker = OperationTracker::default();
+        let (first, cancel) = tracker.register(OperationKind::Install, "codex", false);
+        let (second, _) = tracker.register(OperationKind::Detection, "claude-code", true);
+        assert!(cancel.is_none());
+        assert_ne!(first, second);
+
+        tracker.update(&first, "installing", Some(50.0), "npm install");
+        let listed = tracker.list();
+        assert_eq!(listed.len(), 2);
+        let op = listed.iter().find(|op| op.operation_id == first).unwrap();
+        assert_eq!(op.phase, "installing");
+        assert_eq!(op.percent, Some(50.0));
+        assert_eq!(op.target, "codex");
+
+        tracker.remove(&first);
+        assert_eq!(tracker.list().len(), 1);
+    }
+
+    #[test]
+    fn test_cancel_only_cancellable_operations() {
+        let tracker = OperationTracker::default();
+        let (fixed, _) = tracker.register(OperationKind::Update, "codex-local", false);
+        let (scan, token) = tracker.register(OperationKind::Scan, "codex", true);
+
+        assert!(!tracker.cancel(&fixed));
+        assert!(!tracker.cancel("missing"));
+        assert!(tracker.cancel(&scan));
+        assert!(token.unwrap().is_cancelled());
+    }
+}
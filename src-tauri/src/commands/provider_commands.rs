@@ -2,10 +2,23 @@
 //
 // 供应商管理 Tauri 命令
 
+use crate::commands::dashboard_commands::{clean_dashboard_selections, DashboardManagerState};
+use crate::commands::error::{CommandContext, CommandError, CommandResult};
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::core::{tr, tr_with};
 use ::duckcoding::models::provider::Provider;
+use ::duckcoding::models::Tool;
+use ::duckcoding::services::provider_benchmark::{
+    self, BenchmarkOptions, BenchmarkReport, ProviderBenchmark,
+};
+use ::duckcoding::services::provider_import::{
+    self, ConfirmedImport, ImportedBinding, ProviderDiscovery, ProviderImportResult,
+};
+use ::duckcoding::services::provider_manager::{validate_provider, ProviderValidation};
+use ::duckcoding::services::trash::{TrashManager, TrashedRecord};
 use ::duckcoding::services::ProviderManager;
-use anyhow::Result;
-use tauri::State;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
 
 /// Provider 管理器 State
 pub struct ProviderManagerState {
@@ -37,7 +50,7 @@ pub struct ApiInfo {
 /// 从 {website_url}/api/status 获取 data.api_info 数组
 /// 失败时返回空数组（降级处理）
 #[tauri::command]
-pub async fn fetch_provider_api_addresses(website_url: String) -> Result<Vec<ApiInfo>, String> {
+pub async fn fetch_provider_api_addresses(website_url: String) -> CommandResult<Vec<ApiInfo>> {
     use reqwest::Client;
     use std::time::Duration;
 
@@ -48,7 +61,7 @@ pub async fn fetch_provider_api_addresses(website_url: String) -> Result<Vec<Api
     let client = Client::builder()
         .timeout(Duration::from_secs(10))
         .build()
-        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+        .command_context("http.client_failed")?;
 
     let response = client.get(&api_url).send().await;
 
@@ -97,11 +110,11 @@ pub async fn fetch_provider_api_addresses(website_url: String) -> Result<Vec<Api
 #[tauri::command]
 pub async fn list_providers(
     state: State<'_, ProviderManagerState>,
-) -> Result<Vec<Provider>, String> {
+) -> CommandResult<Vec<Provider>> {
     state
         .manager
         .list_providers()
-        .map_err(|e| format!("获取供应商列表失败: {}", e))
+        .command_context("provider.list_failed")
 }
 
 /// 创建新供应商
@@ -109,22 +122,22 @@ pub async fn list_providers(
 pub async fn create_provider(
     provider: Provider,
     state: State<'_, ProviderManagerState>,
-) -> Result<Provider, String> {
+) -> CommandResult<Provider> {
     // 基础验证
     if provider.id.is_empty() {
-        return Err("供应商 ID 不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.id_required")));
     }
     if provider.name.is_empty() {
-        return Err("供应商名称不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.name_required")));
     }
     if provider.website_url.is_empty() {
-        return Err("官网地址不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.website_required")));
     }
 
     state
         .manager
         .create_provider(provider)
-        .map_err(|e| format!("创建供应商失败: {}", e))
+        .command_context("provider.create_failed")
 }
 
 /// 更新供应商
@@ -133,148 +146,188 @@ pub async fn update_provider(
     id: String,
     provider: Provider,
     state: State<'_, ProviderManagerState>,
-) -> Result<Provider, String> {
+) -> CommandResult<Provider> {
     // 基础验证
     if provider.name.is_empty() {
-        return Err("供应商名称不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.name_required")));
     }
     if provider.website_url.is_empty() {
-        return Err("官网地址不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.website_required")));
     }
 
     state
         .manager
         .update_provider(&id, provider)
-        .map_err(|e| format!("更新供应商失败: {}", e))
+        .command_context("provider.update_failed")
 }
 
 /// 删除供应商
+///
+/// 默认移入回收站（可在 30 天内恢复）；`hard` 为 true 时直接永久删除。
 #[tauri::command]
 pub async fn delete_provider(
+    app: AppHandle,
     id: String,
+    hard: Option<bool>,
     state: State<'_, ProviderManagerState>,
-) -> Result<(), String> {
+    dashboard_state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+) -> CommandResult<()> {
     if id.is_empty() {
-        return Err("供应商 ID 不能为空".to_string());
+        return Err(CommandError::validation(tr("provider.id_required")));
     }
 
-    state
+    let removed = state
         .manager
         .delete_provider(&id)
-        .map_err(|e| format!("删除供应商失败: {}", e))
+        .command_context("provider.delete_failed")?;
+    if let (Some(provider), false) = (removed, hard.unwrap_or(false)) {
+        let trashed = TrashManager::new()
+            .and_then(|trash| trash.put(TrashedRecord::Provider(provider.clone())));
+        // 写入回收站失败时撤销删除，避免记录丢失
+        if let Err(e) = trashed {
+            if let Err(undo_err) = state.manager.restore_provider(provider) {
+                tracing::error!(error = ?undo_err, "移入回收站失败后撤销删除失败");
+            }
+            return Err(e).command_context("trash.move_failed");
+        }
+    }
+
+    // 清理仪表板中指向该供应商的选择（失败不影响删除结果）
+    let registry = registry_state.registry.lock().await;
+    if let Err(e) =
+        clean_dashboard_selections(&app, &dashboard_state.manager, &registry, &state).await
+    {
+        tracing::warn!(error = %e, "清理仪表板选择失败");
+    }
+    Ok(())
 }
 
 /// 验证结果结构
-#[derive(serde::Serialize)]
-pub struct ValidationResult {
-    pub success: bool,
-    pub username: Option<String>,
-    pub error: Option<String>,
-}
+pub type ValidationResult = ProviderValidation;
 
 /// 验证供应商配置（检查 API 连通性）
 #[tauri::command]
-pub async fn validate_provider_config(provider: Provider) -> Result<ValidationResult, String> {
-    use reqwest::Client;
-    use std::time::Duration;
+pub async fn validate_provider_config(provider: Provider) -> CommandResult<ValidationResult> {
+    validate_provider(&provider)
+        .await
+        .command_context("provider.api_request_failed")
+}
 
-    // 基础验证
-    if provider.website_url.is_empty() {
-        return Ok(ValidationResult {
-            success: false,
-            username: None,
-            error: Some("官网地址不能为空".to_string()),
-        });
-    }
-    if provider.user_id.is_empty() {
-        return Ok(ValidationResult {
-            success: false,
-            username: None,
-            error: Some("用户 ID 不能为空".to_string()),
-        });
-    }
-    if provider.access_token.is_empty() {
-        return Ok(ValidationResult {
-            success: false,
-            username: None,
-            error: Some("访问令牌不能为空".to_string()),
-        });
-    }
+/// 批量测速供应商（`provider_ids` 为空时测速全部供应商）
+///
+/// 每轮对所有供应商并发请求一次，共 `samples` 轮；结果按中位延迟排序，并保存为各供应商最近一次测速结果
+#[tauri::command]
+pub async fn benchmark_providers(
+    provider_ids: Vec<String>,
+    samples: u32,
+    options: Option<BenchmarkOptions>,
+    state: State<'_, ProviderManagerState>,
+) -> CommandResult<BenchmarkReport> {
+    let providers = state
+        .manager
+        .list_providers()
+        .command_context("provider.list_failed")?;
+    let selected = if provider_ids.is_empty() {
+        providers
+    } else {
+        let mut selected = Vec::with_capacity(provider_ids.len());
+        for id in &provider_ids {
+            let provider = providers.iter().find(|p| &p.id == id).ok_or_else(|| {
+                CommandError::not_found(tr_with("provider.not_found", &[("id", id)]))
+            })?;
+            selected.push(provider.clone());
+        }
+        selected
+    };
 
-    // 构建 API 端点
-    let api_url = format!(
-        "{}/api/user/self",
-        provider.website_url.trim_end_matches('/')
-    );
+    provider_benchmark::benchmark_providers(&selected, samples, &options.unwrap_or_default())
+        .await
+        .command_context("provider.benchmark_failed")
+}
 
-    // 发送验证请求
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+/// 各供应商最近一次的测速结果（按供应商 ID）
+#[tauri::command]
+pub async fn get_provider_benchmarks() -> CommandResult<HashMap<String, ProviderBenchmark>> {
+    provider_benchmark::last_benchmarks().command_context("provider.benchmark_failed")
+}
 
-    let response = client
-        .get(&api_url)
-        .header("Authorization", format!("Bearer {}", provider.access_token))
-        .header("New-Api-User", &provider.user_id)
-        .send()
-        .await
-        .map_err(|e| format!("API 请求失败: {}", e))?;
-
-    if response.status().is_success() {
-        // 尝试解析响应，提取用户名
-        let json_result = response.json::<serde_json::Value>().await;
-        match json_result {
-            Ok(json) => {
-                // 检查响应体中的 success 字段
-                let api_success = json
-                    .get("success")
-                    .and_then(|s| s.as_bool())
-                    .unwrap_or(true); // 没有 success 字段时默认为 true（兼容不同 API）
-
-                if !api_success {
-                    // API 返回 success: false，提取错误信息
-                    let error_msg = json
-                        .get("message")
-                        .and_then(|m| m.as_str())
-                        .unwrap_or("API 验证失败")
-                        .to_string();
-
-                    return Ok(ValidationResult {
-                        success: false,
-                        username: None,
-                        error: Some(error_msg),
-                    });
+/// 从其他切换工具（cc-switch、claude-code-router）的配置中发现供应商
+///
+/// 只返回草稿与建议绑定，不写入任何数据。
+#[tauri::command]
+pub async fn discover_external_providers(
+    state: State<'_, ProviderManagerState>,
+) -> CommandResult<ProviderDiscovery> {
+    let existing = state
+        .manager
+        .list_providers()
+        .command_context("provider.list_failed")?;
+    Ok(provider_import::discover_providers(&existing))
+}
+
+/// 导入用户确认的供应商并设置工具绑定
+///
+/// 单个条目失败不影响其余条目，失败原因记录在结果中。
+#[tauri::command]
+pub async fn import_discovered_providers(
+    items: Vec<ConfirmedImport>,
+    state: State<'_, ProviderManagerState>,
+    dashboard_state: State<'_, DashboardManagerState>,
+) -> CommandResult<ProviderImportResult> {
+    let mut taken: Vec<String> = state
+        .manager
+        .list_providers()
+        .command_context("provider.list_failed")?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let mut result = ProviderImportResult::default();
+
+    for item in items {
+        let provider_id = match item.existing_provider_id {
+            Some(id) => id,
+            None => {
+                let mut provider = item.provider;
+                if provider.name.trim().is_empty() {
+                    result.failures.push(tr("provider.name_required"));
+                    continue;
+                }
+                provider.id = provider_import::unique_provider_id(&provider.id, &taken);
+                provider.is_default = false;
+                match state.manager.create_provider(provider) {
+                    Ok(created) => {
+                        taken.push(created.id.clone());
+                        let id = created.id.clone();
+                        result.created.push(created);
+                        id
+                    }
+                    Err(e) => {
+                        result
+                            .failures
+                            .push(format!("{}: {e:#}", tr("provider.create_failed")));
+                        continue;
+                    }
                 }
+            }
+        };
 
-                // 尝试从响应中提取用户名 (假设在 data.username 或 username 字段)
-                let username = json
-                    .get("data")
-                    .and_then(|data| data.get("username"))
-                    .or_else(|| json.get("username"))
-                    .and_then(|u| u.as_str())
-                    .map(|s| s.to_string());
-
-                Ok(ValidationResult {
-                    success: true,
-                    username,
-                    error: None,
-                })
+        for tool_id in item.bind_tools {
+            if Tool::by_id(&tool_id).is_none() {
+                result.failures.push(format!("不支持的工具 ID: {tool_id}"));
+                continue;
+            }
+            match dashboard_state
+                .manager
+                .set_tool_provider_binding(tool_id.clone(), Some(provider_id.clone()))
+            {
+                Ok(()) => result.bindings.push(ImportedBinding {
+                    tool_id,
+                    provider_id: provider_id.clone(),
+                }),
+                Err(e) => result.failures.push(format!("绑定 {tool_id} 失败: {e:#}")),
             }
-            Err(e) => Ok(ValidationResult {
-                success: false,
-                username: None,
-                error: Some(format!("API 响应格式错误: {}", e)),
-            }),
         }
-    } else {
-        Ok(ValidationResult {
-            success: false,
-            username: None,
-            error: Some(format!(
-                "API 验证失败，状态码: {}",
-                response.status().as_u16()
-            )),
-        })
     }
+    Ok(result)
 }
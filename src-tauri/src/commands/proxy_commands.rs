@@ -2,10 +2,19 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::DialogExt;
 
 use crate::commands::profile_commands::ProfileManagerState;
-use ::duckcoding::services::proxy::ProxyManager;
+use ::duckcoding::core::logger::get_log_dir;
+use ::duckcoding::models::config::LogFormat;
+use ::duckcoding::services::proxy::capture::{CaptureSnapshot, CAPTURE_STORE};
+use ::duckcoding::services::proxy::export::{self, ExportFormat, ExportRange};
+use ::duckcoding::services::proxy::limits::ProxyUtilization;
+use ::duckcoding::services::proxy::metrics::{MetricsRange, ProxyMetricsReport, PROXY_METRICS};
+use ::duckcoding::services::proxy::sessions::{ProxySession, PROXY_SESSIONS};
+use ::duckcoding::services::proxy::tool_routing;
+use ::duckcoding::services::proxy::{ProxyManager, ProxyRunState};
 use ::duckcoding::services::proxy_config_manager::ProxyConfigManager;
 use ::duckcoding::utils::config::read_global_config;
 
@@ -21,6 +30,16 @@ pub struct ProxyManagerState {
 pub struct TransparentProxyStatus {
     running: bool,
     port: u16,
+    /// 守护任务维护的运行状态（running / restarting / crashed / stopped）
+    state: ProxyRunState,
+    /// 最近 24 小时被端点白名单拒绝的请求数
+    blocked_requests: u64,
+    /// 当前激活的代理配置档
+    active_profile: Option<String>,
+    /// 工具配置是否已指向本地代理
+    routed_through_proxy: bool,
+    /// 并发使用情况（代理未创建时为空）
+    utilization: Option<ProxyUtilization>,
 }
 
 #[derive(serde::Deserialize)]
@@ -337,6 +356,13 @@ pub async fn stop_tool_proxy(
 #[tauri::command]
 pub async fn get_all_proxy_status(
     manager_state: State<'_, ProxyManagerState>,
+) -> Result<HashMap<String, TransparentProxyStatus>, String> {
+    collect_proxy_status(&manager_state).await
+}
+
+/// 汇总所有工具的透明代理状态（供 `get_all_proxy_status` 与仪表板快照共用）
+pub async fn collect_proxy_status(
+    manager_state: &ProxyManagerState,
 ) -> Result<HashMap<String, TransparentProxyStatus>, String> {
     let proxy_config_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
     let proxy_store = proxy_config_mgr
@@ -344,6 +370,8 @@ pub async fn get_all_proxy_status(
         .map_err(|e| e.to_string())?;
 
     let mut status_map = HashMap::new();
+    let states = manager_state.manager.get_all_states().await;
+    let mut utilization = manager_state.manager.get_all_utilization().await;
 
     for tool_id in &["claude-code", "codex", "gemini-cli"] {
         let port = proxy_store
@@ -357,10 +385,24 @@ pub async fn get_all_proxy_status(
             });
 
         let running = manager_state.manager.is_running(tool_id).await;
+        let state = states
+            .get(*tool_id)
+            .cloned()
+            .unwrap_or(ProxyRunState::Stopped);
 
         status_map.insert(
             tool_id.to_string(),
-            TransparentProxyStatus { running, port },
+            TransparentProxyStatus {
+                running,
+                port,
+                state,
+                blocked_requests: PROXY_METRICS.blocked_count(tool_id, MetricsRange::Last24Hours),
+                active_profile: proxy_store.active_profiles.get(*tool_id).cloned(),
+                routed_through_proxy: proxy_store
+                    .get_config(tool_id)
+                    .is_some_and(|tc| tc.tool_routing.is_some()),
+                utilization: utilization.remove(*tool_id),
+            },
         );
     }
 
@@ -527,3 +569,292 @@ pub async fn get_all_proxy_configs(
     let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
     proxy_mgr.get_all_configs().map_err(|e| e.to_string())
 }
+
+/// 更新端点白名单（运行中的代理立即生效，无需重启）
+#[tauri::command]
+pub async fn update_proxy_allowlist(
+    tool_id: String,
+    prefixes: Vec<String>,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<(), String> {
+    let prefixes: Vec<String> = prefixes
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if let Some(invalid) = prefixes.iter().find(|p| !p.starts_with('/')) {
+        return Err(format!("路径前缀必须以 / 开头: {invalid}"));
+    }
+
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut config = proxy_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("未找到 {tool_id} 的代理配置"))?;
+    config.allowed_path_prefixes = prefixes;
+    proxy_mgr
+        .update_config(&tool_id, config.clone())
+        .map_err(|e| e.to_string())?;
+
+    if manager_state.manager.is_running(&tool_id).await {
+        manager_state
+            .manager
+            .update_config(&tool_id, config)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tracing::info!(tool_id = %tool_id, "端点白名单已更新");
+    Ok(())
+}
+
+/// 更新并发上限（运行中的代理立即生效，无需重启）
+#[tauri::command]
+pub async fn update_proxy_limits(
+    tool_id: String,
+    limits: ::duckcoding::models::proxy_config::ConcurrencyLimits,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<(), String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut config = proxy_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("未找到 {tool_id} 的代理配置"))?;
+    config.limits = limits;
+    proxy_mgr
+        .update_config(&tool_id, config.clone())
+        .map_err(|e| e.to_string())?;
+
+    if manager_state.manager.is_running(&tool_id).await {
+        manager_state
+            .manager
+            .update_config(&tool_id, config)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tracing::info!(tool_id = %tool_id, "代理并发上限已更新");
+    Ok(())
+}
+
+/// 更新上游超时（运行中的代理立即生效，无需重启）
+#[tauri::command]
+pub async fn update_proxy_timeouts(
+    tool_id: String,
+    timeouts: ::duckcoding::models::proxy_config::UpstreamTimeouts,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<(), String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let mut config = proxy_mgr
+        .get_config(&tool_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("未找到 {tool_id} 的代理配置"))?;
+    config.timeouts = timeouts;
+    proxy_mgr
+        .update_config(&tool_id, config.clone())
+        .map_err(|e| e.to_string())?;
+
+    if manager_state.manager.is_running(&tool_id).await {
+        manager_state
+            .manager
+            .update_config(&tool_id, config)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    tracing::info!(tool_id = %tool_id, "代理上游超时已更新");
+    Ok(())
+}
+
+/// 一键将工具配置指向本地代理（记录原始值以便还原）
+#[tauri::command]
+pub async fn configure_tool_for_proxy(
+    tool_id: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<String, String> {
+    let state = tool_routing::configure_tool_for_proxy(&manager_state.manager, &tool_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format!("✅ {tool_id} 已指向本地代理: {}", state.proxy_url))
+}
+
+/// 还原工具接入代理前的原始配置
+#[tauri::command]
+pub async fn unconfigure_tool_for_proxy(tool_id: String) -> Result<String, String> {
+    tool_routing::unconfigure_tool_for_proxy(&tool_id).map_err(|e| e.to_string())?;
+    Ok(format!("✅ {tool_id} 已还原原始配置"))
+}
+
+// ==================== 代理配置档命令 ====================
+
+/// 将工具当前代理配置保存为命名配置档
+#[tauri::command]
+pub async fn save_proxy_profile(tool_id: String, name: String) -> Result<(), String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    proxy_mgr
+        .save_profile(&tool_id, &name)
+        .map_err(|e| e.to_string())
+}
+
+/// 列出工具的代理配置档
+#[tauri::command]
+pub async fn list_proxy_profiles(
+    tool_id: String,
+) -> Result<Vec<::duckcoding::models::proxy_config::ProxyProfileInfo>, String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    proxy_mgr.list_profiles(&tool_id).map_err(|e| e.to_string())
+}
+
+/// 激活代理配置档（代理运行中时热切换，端口/监听地址变化才重启监听）
+#[tauri::command]
+pub async fn activate_proxy_profile(
+    tool_id: String,
+    name: String,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<String, String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    let config = proxy_mgr
+        .activate_profile(&tool_id, &name)
+        .map_err(|e| e.to_string())?;
+
+    let restarted = manager_state
+        .manager
+        .reload_config(&tool_id, config)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    tracing::info!(tool_id = %tool_id, profile = %name, restarted, "已切换代理配置档");
+    Ok(if restarted {
+        format!("✅ 已切换到配置档 {name}，代理已在新端口重启")
+    } else {
+        format!("✅ 已切换到配置档 {name}")
+    })
+}
+
+/// 删除代理配置档
+#[tauri::command]
+pub async fn delete_proxy_profile(tool_id: String, name: String) -> Result<(), String> {
+    let proxy_mgr = ProxyConfigManager::new().map_err(|e| e.to_string())?;
+    proxy_mgr
+        .delete_profile(&tool_id, &name)
+        .map_err(|e| e.to_string())
+}
+
+// ==================== 代理指标命令 ====================
+
+/// 获取代理请求指标（延迟百分位、请求数、状态码分布）
+///
+/// `range` 可选值：`1h`（默认）、`6h`、`24h`
+#[tauri::command]
+pub async fn get_proxy_metrics(
+    range: Option<MetricsRange>,
+    manager_state: State<'_, ProxyManagerState>,
+) -> Result<ProxyMetricsReport, String> {
+    let mut report = PROXY_METRICS.report(range.unwrap_or_default());
+    report.utilization = manager_state
+        .manager
+        .get_all_utilization()
+        .await
+        .into_iter()
+        .collect();
+    Ok(report)
+}
+
+/// 获取代理会话统计（请求数、token 总量、持续时长）
+///
+/// `range` 可选值：`1h`（默认）、`6h`、`24h`，返回范围内有活动的会话
+#[tauri::command]
+pub async fn get_proxy_sessions(range: Option<MetricsRange>) -> Result<Vec<ProxySession>, String> {
+    Ok(PROXY_SESSIONS.sessions(range.unwrap_or_default()))
+}
+
+/// 导出代理用量汇总（按天、供应商、端点），返回导出文件路径
+///
+/// `format` 可选值：`csv`（默认）、`json`
+#[tauri::command]
+pub async fn export_proxy_usage(
+    range: ExportRange,
+    format: Option<ExportFormat>,
+) -> Result<String, String> {
+    let rows = PROXY_METRICS.daily_usage(range.from, range.to);
+    export::export_usage(&rows, range, format.unwrap_or_default())
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| e.to_string())
+}
+
+/// 导出代理访问日志（合并范围内的滚动日志文件），返回导出文件路径
+#[tauri::command]
+pub async fn export_proxy_access_log(range: ExportRange) -> Result<String, String> {
+    let log_config = read_global_config()?
+        .map(|config| config.log_config)
+        .unwrap_or_default();
+    let log_dir = get_log_dir(log_config.file_path.as_deref()).map_err(|e| e.to_string())?;
+    let json_lines = matches!(log_config.format, LogFormat::Json);
+
+    export::export_access_log(&log_dir, range, json_lines)
+        .map(|path| path.to_string_lossy().into_owned())
+        .map_err(|e| e.to_string())
+}
+
+// ==================== 调试捕获命令 ====================
+
+/// 开启代理调试捕获（`minutes` 分钟后自动关闭）
+#[tauri::command]
+pub async fn enable_proxy_capture(
+    minutes: u32,
+    max_exchanges: Option<usize>,
+    max_body_bytes: Option<usize>,
+) -> Result<CaptureSnapshot, String> {
+    if minutes == 0 {
+        return Err("捕获时长必须大于 0 分钟".to_string());
+    }
+    CAPTURE_STORE.enable(minutes, max_exchanges, max_body_bytes);
+    Ok(CAPTURE_STORE.snapshot())
+}
+
+/// 立即关闭代理调试捕获（保留已捕获内容）
+#[tauri::command]
+pub async fn disable_proxy_capture() -> Result<(), String> {
+    CAPTURE_STORE.disable();
+    Ok(())
+}
+
+/// 获取已捕获的请求/响应（headers 已脱敏）
+#[tauri::command]
+pub async fn get_captured_exchanges() -> Result<CaptureSnapshot, String> {
+    Ok(CAPTURE_STORE.snapshot())
+}
+
+/// 清空已捕获内容
+#[tauri::command]
+pub async fn clear_captures() -> Result<(), String> {
+    CAPTURE_STORE.clear();
+    Ok(())
+}
+
+/// 弹出保存对话框，导出已捕获内容到用户选择的 JSON 文件
+///
+/// 路径只能来自系统对话框，不接受前端传入的字符串；用户取消时返回 `None`。
+#[tauri::command]
+pub async fn export_captured_exchanges(app: AppHandle) -> Result<Option<usize>, String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("JSON", &["json"])
+        .set_file_name("proxy-captures.json")
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+
+    let Some(chosen) = rx.await.map_err(|e| format!("打开保存对话框失败: {e}"))? else {
+        return Ok(None);
+    };
+    let path = chosen
+        .into_path()
+        .map_err(|e| format!("无效的导出路径: {e}"))?;
+
+    CAPTURE_STORE
+        .export(&path)
+        .map(Some)
+        .map_err(|e| format!("导出调试捕获失败: {e}"))
+}
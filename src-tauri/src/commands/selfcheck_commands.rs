@@ -0,0 +1,35 @@
+//! 启动自检命令
+//!
+//! 应用启动时在后台执行一次（完成后发送 `startup-selfcheck-completed` 事件），
+//! 前端也可随时重新执行；自检结果不影响应用进入界面。
+
+use crate::commands::error::CommandResult;
+use crate::commands::proxy_commands::ProxyManagerState;
+use ::duckcoding::services::proxy::ProxyManager;
+use ::duckcoding::services::selfcheck::{self, SelfCheckItem};
+use tauri::State;
+
+/// 当前由本进程运行的代理所属的工具 ID
+pub async fn running_proxy_tools(manager: &ProxyManager) -> Vec<String> {
+    manager
+        .get_all_status()
+        .await
+        .into_iter()
+        .filter_map(|(tool_id, running)| running.then_some(tool_id))
+        .collect()
+}
+
+/// 重新执行启动自检
+#[tauri::command]
+pub async fn run_startup_selfcheck(
+    proxy_state: State<'_, ProxyManagerState>,
+) -> CommandResult<Vec<SelfCheckItem>> {
+    let running = running_proxy_tools(&proxy_state.manager).await;
+    Ok(selfcheck::run_startup_selfcheck(&running).await)
+}
+
+/// 获取最近一次启动自检的结果（启动时的自检尚未完成时为 null）
+#[tauri::command]
+pub async fn get_startup_selfcheck() -> CommandResult<Option<Vec<SelfCheckItem>>> {
+    Ok(selfcheck::last_selfcheck())
+}
@@ -0,0 +1,365 @@
+//! 一键配置工具命令（新手引导每个工具只需调用一次）
+//!
+//! 流程：检测（优先缓存）→ 未安装时安装 → 绑定供应商 → 应用供应商配置 → 健康检查（含供应商验证）。
+//! 遇到第一个硬失败即停止；已改动的配置（Profile、原生配置文件、供应商绑定）会回滚。
+
+use crate::commands::dashboard_commands::DashboardManagerState;
+use crate::commands::error::{AppError, CommandContext, CommandError, CommandResult};
+use crate::commands::operations::{Operation, OperationKind, OPERATIONS};
+use crate::commands::profile_commands::ProfileManagerState;
+use crate::commands::provider_commands::ProviderManagerState;
+use crate::commands::tool_commands::parse_install_method;
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::core::tr_with;
+use ::duckcoding::models::provider::Provider;
+use ::duckcoding::models::telemetry::TelemetryEvent;
+use ::duckcoding::models::Tool;
+use ::duckcoding::services::provider_readiness::{validate_active_bindings, ReadinessStatus};
+use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::telemetry::record_event;
+use ::duckcoding::services::tool::TOOL_STATUS_CACHE;
+use ::duckcoding::services::tool_setup::{
+    apply_provider_profile, native_config_files, ConfigSnapshot, SetupOptions, SetupReport,
+    SetupStage, StageStatus,
+};
+use ::duckcoding::services::InstallerService;
+use tauri::{AppHandle, State};
+
+/// 一键配置工具，返回各阶段结果
+///
+/// 阶段失败不作为命令错误返回，而是记录在报告中（`success` 为 false）。
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn setup_tool(
+    app: AppHandle,
+    tool_id: String,
+    provider_id: String,
+    options: Option<SetupOptions>,
+    registry_state: State<'_, ToolRegistryState>,
+    provider_state: State<'_, ProviderManagerState>,
+    dashboard_state: State<'_, DashboardManagerState>,
+    profile_state: State<'_, ProfileManagerState>,
+) -> CommandResult<SetupReport> {
+    let tool = Tool::by_id(&tool_id).ok_or_else(|| AppError::ToolNotFound {
+        tool: tool_id.clone(),
+    })?;
+    let provider = provider_state
+        .manager
+        .get_provider(&provider_id)
+        .command_context("provider.get_failed")?
+        .ok_or_else(|| {
+            CommandError::not_found(tr_with(
+                "provider.not_found",
+                &[("id", provider_id.as_str())],
+            ))
+        })?;
+
+    let op = OPERATIONS.start_operation(&app, OperationKind::Setup, &tool_id);
+    let pipeline = SetupPipeline {
+        op: &op,
+        tool: &tool,
+        provider: &provider,
+        options: options.unwrap_or_default(),
+        registry_state: &registry_state,
+        dashboard_state: &dashboard_state,
+        profile_state: &profile_state,
+    };
+    let result = Ok(pipeline.run().await);
+    op.finish(&result);
+    record_event(TelemetryEvent::ToolSetup);
+    result
+}
+
+struct SetupPipeline<'a> {
+    op: &'a Operation,
+    tool: &'a Tool,
+    provider: &'a Provider,
+    options: SetupOptions,
+    registry_state: &'a ToolRegistryState,
+    dashboard_state: &'a DashboardManagerState,
+    profile_state: &'a ProfileManagerState,
+}
+
+/// 需要回滚的改动
+#[derive(Default)]
+struct Changes {
+    /// 绑定前的供应商（`Some` 表示已改动绑定）
+    previous_binding: Option<Option<String>>,
+    /// 应用配置前的文件快照
+    snapshot: Option<ConfigSnapshot>,
+}
+
+impl SetupPipeline<'_> {
+    async fn run(&self) -> SetupReport {
+        let mut report = SetupReport::new(&self.tool.id, &self.provider.id);
+        let mut changes = Changes::default();
+
+        let ok = self.detect_and_install(&mut report).await
+            && self.bind_provider(&mut report, &mut changes)
+            && self.apply_config(&mut report, &mut changes).await
+            && self.health_check(&mut report).await;
+
+        if !ok {
+            report.rolled_back = self.rollback(changes);
+        }
+        self.op.progress(
+            "finished",
+            Some(100.0),
+            if ok { "配置完成" } else { "配置失败" },
+        );
+        report.finish()
+    }
+
+    async fn detect_and_install(&self, report: &mut SetupReport) -> bool {
+        let tool = self.tool;
+        self.op
+            .progress("detect", Some(0.0), format!("正在检测 {}", tool.name));
+        let detected = {
+            let registry = self.registry_state.registry.lock().await;
+            registry
+                .detect_single_tool_with_cache(&tool.id, false)
+                .await
+        };
+        let status = match detected {
+            Ok(status) => status,
+            Err(e) => {
+                report.record(SetupStage::Detect, StageStatus::Failed, format!("{e:#}"));
+                return false;
+            }
+        };
+        report.record(
+            SetupStage::Detect,
+            StageStatus::Succeeded,
+            match &status.version {
+                Some(version) if status.installed => format!("已安装 {version}"),
+                _ if status.installed => "已安装".to_string(),
+                _ => "未安装".to_string(),
+            },
+        );
+
+        if status.installed && !self.options.force_install {
+            report.record(
+                SetupStage::Install,
+                StageStatus::Skipped,
+                "已安装，跳过安装",
+            );
+            return true;
+        }
+
+        let method = match self.options.install_method.as_deref() {
+            Some(method) => match parse_install_method(method) {
+                Ok(method) => method,
+                Err(e) => {
+                    report.record(SetupStage::Install, StageStatus::Failed, e.message());
+                    return false;
+                }
+            },
+            None => tool.recommended_install_method(),
+        };
+        self.op.progress(
+            "install",
+            Some(20.0),
+            format!("正在安装 {}（{method:?}）", tool.name),
+        );
+        apply_global_proxy().ok();
+        if let Err(e) = InstallerService::new()
+            .install(tool, &method, self.options.force_install)
+            .await
+        {
+            report.record(SetupStage::Install, StageStatus::Failed, format!("{e:#}"));
+            return false;
+        }
+        let registry = self.registry_state.registry.lock().await;
+        if let Err(e) = TOOL_STATUS_CACHE.refresh_tool(&registry, &tool.id).await {
+            tracing::warn!(tool = %tool.id, error = ?e, "安装后刷新工具状态失败");
+        }
+        report.record(
+            SetupStage::Install,
+            StageStatus::Succeeded,
+            format!("已通过 {method:?} 安装"),
+        );
+        true
+    }
+
+    fn bind_provider(&self, report: &mut SetupReport, changes: &mut Changes) -> bool {
+        self.op.progress(
+            "bind_provider",
+            Some(50.0),
+            format!("正在绑定供应商 {}", self.provider.name),
+        );
+        let manager = &self.dashboard_state.manager;
+        let result = manager
+            .get_tool_provider_binding(&self.tool.id)
+            .and_then(|previous| {
+                manager.set_tool_provider_binding(
+                    self.tool.id.clone(),
+                    Some(self.provider.id.clone()),
+                )?;
+                Ok(previous)
+            });
+        match result {
+            Ok(previous) => {
+                changes.previous_binding = Some(previous);
+                report.record(
+                    SetupStage::BindProvider,
+                    StageStatus::Succeeded,
+                    format!("已绑定 {}", self.provider.name),
+                );
+                true
+            }
+            Err(e) => {
+                report.record(
+                    SetupStage::BindProvider,
+                    StageStatus::Failed,
+                    format!("{e:#}"),
+                );
+                false
+            }
+        }
+    }
+
+    async fn apply_config(&self, report: &mut SetupReport, changes: &mut Changes) -> bool {
+        self.op
+            .progress("apply_config", Some(65.0), "正在写入工具配置");
+
+        let manager = self.profile_state.manager.write().await;
+        let mut files = manager.store_files();
+        files.extend(native_config_files(self.tool));
+        match ConfigSnapshot::capture(&files) {
+            Ok(snapshot) => changes.snapshot = Some(snapshot),
+            Err(e) => {
+                report.record(
+                    SetupStage::ApplyConfig,
+                    StageStatus::Failed,
+                    format!("备份现有配置失败: {e:#}"),
+                );
+                return false;
+            }
+        }
+
+        match apply_provider_profile(&manager, &self.tool.id, self.provider, &self.options) {
+            Ok(profile_name) => {
+                report.record(
+                    SetupStage::ApplyConfig,
+                    StageStatus::Succeeded,
+                    format!("已应用 Profile {profile_name}"),
+                );
+                true
+            }
+            Err(e) => {
+                report.record(
+                    SetupStage::ApplyConfig,
+                    StageStatus::Failed,
+                    format!("{e:#}"),
+                );
+                false
+            }
+        }
+    }
+
+    async fn health_check(&self, report: &mut SetupReport) -> bool {
+        self.op
+            .progress("health_check", Some(85.0), "正在检查配置结果");
+        let profile_name = self.options.profile_name(self.provider);
+
+        let installed = {
+            let registry = self.registry_state.registry.lock().await;
+            registry
+                .detect_single_tool_with_cache(&self.tool.id, true)
+                .await
+                .map(|status| status.installed)
+        };
+        let active = self
+            .profile_state
+            .manager
+            .read()
+            .await
+            .get_active_profile_name(&self.tool.id);
+        // 只要求主配置文件存在（其余文件按工具与 Profile 内容可能不生成）
+        let missing_files: Vec<String> = native_config_files(self.tool)
+            .into_iter()
+            .take(1)
+            .filter(|path| !path.exists())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+
+        let problem = match (installed, active) {
+            (Err(e), _) => Some(format!("重新检测失败: {e:#}")),
+            (Ok(false), _) => Some(format!("未检测到 {}", self.tool.name)),
+            (_, Err(e)) => Some(format!("读取激活的 Profile 失败: {e:#}")),
+            (_, Ok(active)) if active.as_deref() != Some(profile_name.as_str()) => {
+                Some(format!("Profile {profile_name} 未处于激活状态"))
+            }
+            _ if !missing_files.is_empty() => {
+                Some(format!("配置文件未生成: {}", missing_files.join(", ")))
+            }
+            _ => None,
+        };
+
+        if let Some(problem) = problem {
+            report.record(SetupStage::HealthCheck, StageStatus::Failed, problem);
+            return false;
+        }
+
+        // 供应商拒绝凭证时失败；网络不通或超时不影响配置结果，只在消息中提示
+        let bindings = [(self.tool.id.clone(), self.provider.id.clone())].into();
+        let readiness = validate_active_bindings(&bindings, std::slice::from_ref(self.provider))
+            .await
+            .pop();
+        match readiness {
+            Some(readiness) if readiness.status == ReadinessStatus::Rejected => {
+                report.record(
+                    SetupStage::HealthCheck,
+                    StageStatus::Failed,
+                    format!(
+                        "供应商 {} 验证失败: {}（请重新验证供应商 {}）",
+                        self.provider.name,
+                        readiness.error.unwrap_or_default(),
+                        self.provider.name
+                    ),
+                );
+                false
+            }
+            Some(readiness) if !readiness.is_ready() => {
+                let detail = readiness
+                    .error
+                    .unwrap_or_else(|| "缺少用户 ID 或访问令牌".to_string());
+                report.record(
+                    SetupStage::HealthCheck,
+                    StageStatus::Succeeded,
+                    format!("检查通过（未能验证供应商: {detail}）"),
+                );
+                true
+            }
+            _ => {
+                report.record(SetupStage::HealthCheck, StageStatus::Succeeded, "检查通过");
+                true
+            }
+        }
+    }
+
+    /// 回滚配置改动（安装不回滚），返回是否有改动被回滚
+    fn rollback(&self, changes: Changes) -> bool {
+        let mut rolled_back = false;
+        if let Some(snapshot) = changes.snapshot {
+            match snapshot.restore() {
+                Ok(()) => rolled_back = true,
+                Err(e) => tracing::error!(tool = %self.tool.id, error = ?e, "回滚工具配置失败"),
+            }
+        }
+        if let Some(previous) = changes.previous_binding {
+            match self
+                .dashboard_state
+                .manager
+                .set_tool_provider_binding(self.tool.id.clone(), previous)
+            {
+                Ok(()) => rolled_back = true,
+                Err(e) => tracing::error!(tool = %self.tool.id, error = ?e, "回滚供应商绑定失败"),
+            }
+        }
+        if rolled_back {
+            tracing::info!(tool = %self.tool.id, "一键配置失败，已回滚配置改动");
+        }
+        rolled_back
+    }
+}
@@ -3,7 +3,9 @@
 // 包含用量统计、用户额度查询等功能
 
 use crate::commands::provider_commands::ProviderManagerState;
+use ::duckcoding::services::pricing::{self, DailyCost, PricingResolver};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::utils::config::read_global_config;
 use serde::Serialize;
 use tauri::State;
 
@@ -18,6 +20,12 @@ pub struct UsageData {
     token_used: i64,
     count: i64,
     quota: i64,
+    /// 按模型单价估算的花费（美元）
+    #[serde(default)]
+    estimated_cost_usd: f64,
+    /// 模型不在定价表中，按默认单价估算
+    #[serde(default)]
+    cost_is_estimate: bool,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -32,6 +40,87 @@ pub struct UsageStatsResult {
     success: bool,
     message: String,
     data: Vec<UsageData>,
+    /// 按天汇总的 token 与预估花费
+    daily_costs: Vec<DailyCost>,
+    total_cost_usd: f64,
+    /// 当日预估花费达到提醒阈值
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spend_alert: Option<SpendAlert>,
+}
+
+/// 当日花费提醒
+#[derive(serde::Serialize)]
+pub struct SpendAlert {
+    date: String,
+    spent_usd: f64,
+    threshold_usd: f64,
+}
+
+impl UsageStatsResult {
+    fn failed(message: String) -> Self {
+        Self {
+            success: false,
+            message,
+            data: vec![],
+            daily_costs: vec![],
+            total_cost_usd: 0.0,
+            spend_alert: None,
+        }
+    }
+}
+
+/// 用量统计所用时区（北京时间）
+const USAGE_UTC_OFFSET_SECS: i64 = 8 * 3600;
+
+/// 为用量记录填充预估花费，并按天汇总
+///
+/// NEW API 的用量数据只有总 token 数，统一按输入单价估算（编码场景以输入 token 为主）。
+fn apply_cost_estimates(provider_id: &str, data: &mut [UsageData]) -> UsageStatsCost {
+    let settings = read_global_config()
+        .ok()
+        .flatten()
+        .map(|config| config.pricing)
+        .unwrap_or_default();
+    let resolver = PricingResolver::with_defaults(&settings);
+
+    let mut records = Vec::with_capacity(data.len());
+    for item in data.iter_mut() {
+        let tokens = item.token_used.max(0) as u64;
+        let estimate = resolver.estimate(Some(provider_id), &item.model_name, tokens, 0);
+        item.estimated_cost_usd = estimate.cost_usd;
+        item.cost_is_estimate = estimate.estimated;
+        records.push((item.created_at, tokens, estimate));
+    }
+
+    let daily_costs = pricing::aggregate_daily(records, USAGE_UTC_OFFSET_SECS);
+    let total_cost_usd = daily_costs.iter().map(|day| day.cost_usd).sum();
+
+    let today = chrono::Utc::now()
+        .checked_add_signed(chrono::Duration::seconds(USAGE_UTC_OFFSET_SECS))
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    let spend_alert = settings.daily_spend_alert_usd.and_then(|threshold| {
+        daily_costs
+            .iter()
+            .find(|day| day.date == today && day.cost_usd >= threshold)
+            .map(|day| SpendAlert {
+                date: day.date.clone(),
+                spent_usd: day.cost_usd,
+                threshold_usd: threshold,
+            })
+    });
+
+    UsageStatsCost {
+        daily_costs,
+        total_cost_usd,
+        spend_alert,
+    }
+}
+
+struct UsageStatsCost {
+    daily_costs: Vec<DailyCost>,
+    total_cost_usd: f64,
+    spend_alert: Option<SpendAlert>,
 }
 
 #[derive(serde::Deserialize, Serialize, Debug)]
@@ -91,7 +180,7 @@ pub async fn get_usage_stats(
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_secs() as i64;
-    let beijing_offset = 8 * 3600;
+    let beijing_offset = USAGE_UTC_OFFSET_SECS;
     let today_end = (now + beijing_offset) / 86400 * 86400 + 86400 - beijing_offset;
     let start_timestamp = today_end - 30 * 86400;
     let end_timestamp = today_end;
@@ -121,11 +210,9 @@ pub async fn get_usage_stats(
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
-        return Ok(UsageStatsResult {
-            success: false,
-            message: format!("获取用量统计失败 ({status}): {error_text}"),
-            data: vec![],
-        });
+        return Ok(UsageStatsResult::failed(format!(
+            "获取用量统计失败 ({status}): {error_text}"
+        )));
     }
     let content_type = response
         .headers()
@@ -134,27 +221,29 @@ pub async fn get_usage_stats(
         .map(|s| s.to_string())
         .unwrap_or_default();
     if !content_type.contains("application/json") {
-        return Ok(UsageStatsResult {
-            success: false,
-            message: format!("服务器返回了非JSON格式的响应 (Content-Type: {content_type})"),
-            data: vec![],
-        });
+        return Ok(UsageStatsResult::failed(format!(
+            "服务器返回了非JSON格式的响应 (Content-Type: {content_type})"
+        )));
     }
     let api_response: UsageApiResponse = response
         .json()
         .await
         .map_err(|e| format!("解析响应失败: {e}"))?;
     if !api_response.success {
-        return Ok(UsageStatsResult {
-            success: false,
-            message: format!("API返回错误: {}", api_response.message),
-            data: vec![],
-        });
+        return Ok(UsageStatsResult::failed(format!(
+            "API返回错误: {}",
+            api_response.message
+        )));
     }
+    let mut data = api_response.data.unwrap_or_default();
+    let cost = apply_cost_estimates(&provider.id, &mut data);
     Ok(UsageStatsResult {
         success: true,
         message: "获取成功".to_string(),
-        data: api_response.data.unwrap_or_default(),
+        data,
+        daily_costs: cost.daily_costs,
+        total_cost_usd: cost.total_cost_usd,
+        spend_alert: cost.spend_alert,
     })
 }
 
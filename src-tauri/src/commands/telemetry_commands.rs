@@ -0,0 +1,40 @@
+// 匿名使用统计命令
+//
+// 统计默认关闭；设置、待发送计数、上次发送时间与全部发送历史都可以在设置页查看。
+
+use crate::commands::error::{CommandContext, CommandResult};
+use ::duckcoding::models::telemetry::{TelemetryPing, TelemetryStatus};
+use ::duckcoding::services::TelemetryManager;
+
+/// 获取使用统计设置、待发送计数与发送历史
+#[tauri::command]
+pub async fn get_telemetry_status() -> CommandResult<TelemetryStatus> {
+    TelemetryManager::new()
+        .and_then(|manager| manager.status())
+        .command_context("telemetry.status_failed")
+}
+
+/// 预览下一次将要发送的内容（与实际发送完全一致）
+#[tauri::command]
+pub async fn get_telemetry_preview() -> CommandResult<TelemetryPing> {
+    TelemetryManager::new()
+        .and_then(|manager| manager.preview())
+        .command_context("telemetry.status_failed")
+}
+
+/// 开启或关闭使用统计（关闭时删除尚未发送的计数）
+#[tauri::command]
+pub async fn set_telemetry_enabled(enabled: bool) -> CommandResult<TelemetryStatus> {
+    tracing::info!(enabled, "设置匿名使用统计");
+    TelemetryManager::new()
+        .and_then(|manager| manager.set_enabled(enabled))
+        .command_context("telemetry.update_failed")
+}
+
+/// 设置使用统计的上报地址
+#[tauri::command]
+pub async fn set_telemetry_endpoint(endpoint: String) -> CommandResult<TelemetryStatus> {
+    TelemetryManager::new()
+        .and_then(|manager| manager.set_endpoint(&endpoint))
+        .command_context("telemetry.update_failed")
+}
@@ -300,6 +300,9 @@ mod tests {
             api_address: None,
             user_id: "123".to_string(),
             access_token: "token123".to_string(),
+            api_key: None,
+            balance_template: Default::default(),
+            custom_headers: Default::default(),
             username: None,
             is_default: false,
             created_at: 0,
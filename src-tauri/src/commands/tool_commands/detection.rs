@@ -1,7 +1,9 @@
-use crate::commands::error::{AppError, AppResult};
+use crate::commands::error::{AppError, CommandResult};
+use crate::commands::operations::{OperationKind, OPERATIONS};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::ToolStatus;
-use ::duckcoding::utils::{parse_version_string, CommandExecutor, ToolCandidate};
+use ::duckcoding::utils::{parse_version_string, CommandExecutor, ToolCandidate, PROBE_TIMEOUT};
+use tauri::AppHandle;
 
 /// 扫描所有工具候选（用于自动扫描）
 ///
@@ -12,11 +14,20 @@ use ::duckcoding::utils::{parse_version_string, CommandExecutor, ToolCandidate};
 /// 返回：工具候选列表
 #[tauri::command]
 pub async fn scan_all_tool_candidates(
+    app: AppHandle,
     tool_id: String,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<Vec<ToolCandidate>> {
-    let registry = registry_state.registry.lock().await;
-    Ok(registry.scan_tool_candidates(&tool_id).await?)
+) -> CommandResult<Vec<ToolCandidate>> {
+    let op = OPERATIONS.start_cancellable(&app, OperationKind::Scan, &tool_id);
+    let result = op
+        .run_cancellable(async {
+            let registry = registry_state.registry.lock().await;
+            op.progress("scanning", None, format!("正在扫描 {tool_id}"));
+            Ok(registry.scan_tool_candidates(&tool_id).await?)
+        })
+        .await;
+    op.finish(&result);
+    result
 }
 
 /// 检测单个工具但不保存（仅用于预览）
@@ -30,7 +41,7 @@ pub async fn scan_all_tool_candidates(
 pub async fn detect_tool_without_save(
     tool_id: String,
     _registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<ToolStatus> {
+) -> CommandResult<ToolStatus> {
     let command_executor = CommandExecutor::new();
 
     // 根据工具ID确定检测命令和名称
@@ -38,7 +49,7 @@ pub async fn detect_tool_without_save(
         "claude-code" => ("claude", "Claude Code"),
         "codex" => ("codex", "CodeX"),
         "gemini-cli" => ("gemini", "Gemini CLI"),
-        _ => return Err(AppError::ToolNotFound { tool: tool_id }),
+        _ => return Err(AppError::ToolNotFound { tool: tool_id }.into()),
     };
 
     // 检测工具是否存在
@@ -46,8 +57,10 @@ pub async fn detect_tool_without_save(
 
     let version = if installed {
         // 获取版本
-        let version_cmd = format!("{} --version", check_cmd);
-        let result = command_executor.execute_async(&version_cmd).await;
+        let result = command_executor
+            .with_timeout(PROBE_TIMEOUT)
+            .execute_args_async(check_cmd, &["--version"])
+            .await;
         if result.success {
             let version_str = result.stdout.trim().to_string();
             if !version_str.is_empty() {
@@ -79,12 +92,21 @@ pub async fn detect_tool_without_save(
 /// 返回：工具实例信息
 #[tauri::command]
 pub async fn detect_single_tool(
+    app: AppHandle,
     tool_id: String,
     force_redetect: Option<bool>,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<ToolStatus> {
-    let registry = registry_state.registry.lock().await;
-    Ok(registry
-        .detect_single_tool_with_cache(&tool_id, force_redetect.unwrap_or(false))
-        .await?)
+) -> CommandResult<ToolStatus> {
+    let op = OPERATIONS.start_cancellable(&app, OperationKind::Detection, &tool_id);
+    let result = op
+        .run_cancellable(async {
+            let registry = registry_state.registry.lock().await;
+            op.progress("detecting", None, format!("正在检测 {tool_id}"));
+            Ok(registry
+                .detect_single_tool_with_cache(&tool_id, force_redetect.unwrap_or(false))
+                .await?)
+        })
+        .await;
+    op.finish(&result);
+    result
 }
@@ -1,9 +1,17 @@
-use crate::commands::error::{AppError, AppResult};
+use crate::commands::error::{AppError, CommandContext, CommandResult};
+use crate::commands::operations::{Operation, OperationKind, OPERATIONS};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::{InstallResult, ToolStatus};
-use ::duckcoding::models::{InstallMethod, Tool};
+use ::duckcoding::models::{InstallMethod, PackageCoordinates, Tool};
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::tool::npm_prefix::{
+    self, check_npm_prefix, NpmPrefixIssue, PREFIX_NOT_ON_PATH,
+};
+use ::duckcoding::services::tool::{resolve_all_tools, ToolStatusSnapshot, TOOL_STATUS_CACHE};
 use ::duckcoding::services::InstallerService;
+use ::duckcoding::utils::{CommandExecutor, PlatformInfo};
+use std::collections::HashMap;
+use tauri::AppHandle;
 
 /// 检查所有工具的安装状态（新架构：优先从数据库读取）
 ///
@@ -16,12 +24,11 @@ use ::duckcoding::services::InstallerService;
 #[tauri::command]
 pub async fn check_installations(
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> Result<Vec<ToolStatus>, String> {
-    let registry = registry_state.registry.lock().await;
-    registry
-        .get_local_tool_status()
+) -> CommandResult<Vec<ToolStatus>> {
+    registry_state
+        .tool_statuses()
         .await
-        .map_err(|e| format!("检查工具状态失败: {}", e))
+        .command_context("tool.status_check_failed")
 }
 
 /// 刷新工具状态（仅从数据库读取，不重新检测）
@@ -33,54 +40,104 @@ pub async fn check_installations(
 #[tauri::command]
 pub async fn refresh_tool_status(
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> Result<Vec<ToolStatus>, String> {
-    let registry = registry_state.registry.lock().await;
-    registry
-        .get_local_tool_status()
+) -> CommandResult<Vec<ToolStatus>> {
+    registry_state
+        .tool_statuses()
         .await
-        .map_err(|e| format!("获取工具状态失败: {}", e))
+        .command_context("tool.status_get_failed")
+}
+
+/// 立即返回缓存中的工具状态（可能已过期，不触发检测）及正在检测的工具
+///
+/// 检测开始 / 结束通过 `tool-detection-changed` 事件推送，结果通过 `tool-status-changed` 推送。
+#[tauri::command]
+pub async fn get_cached_tool_status() -> CommandResult<ToolStatusSnapshot> {
+    Ok(TOOL_STATUS_CACHE.try_get_cached())
+}
+
+/// 获取各工具的包坐标（已叠加全局配置中的覆盖），key 为工具 ID
+#[tauri::command]
+pub async fn get_package_coordinates() -> CommandResult<HashMap<String, PackageCoordinates>> {
+    Ok(resolve_all_tools()
+        .into_iter()
+        .map(|tool| (tool.id, tool.package_coordinates))
+        .collect())
 }
 
 /// 安装指定工具
+///
+/// 进度通过 `operation-progress` / `operation-finished` 事件上报（见 `commands::operations`）
 #[tauri::command]
 pub async fn install_tool(
+    app: AppHandle,
     tool: String,
     method: String,
     force: Option<bool>,
-) -> AppResult<InstallResult> {
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> CommandResult<InstallResult> {
+    let op = OPERATIONS.start_operation(&app, OperationKind::Install, &tool);
+    let result = run_install(&op, &tool, &method, force.unwrap_or(false), &registry_state).await;
+    op.finish(&result);
+    result
+}
+
+async fn run_install(
+    op: &Operation,
+    tool: &str,
+    method: &str,
+    force: bool,
+    registry_state: &ToolRegistryState,
+) -> CommandResult<InstallResult> {
     // 应用代理配置（如果已配置）
     apply_global_proxy().ok();
 
-    let force = force.unwrap_or(false);
     #[cfg(debug_assertions)]
     tracing::debug!(tool = %tool, method = %method, force = force, "安装工具（使用InstallerService）");
 
     // 获取工具定义
-    let tool_obj =
-        Tool::by_id(&tool).ok_or_else(|| AppError::ToolNotFound { tool: tool.clone() })?;
+    let tool_obj = Tool::by_id(tool).ok_or_else(|| AppError::ToolNotFound {
+        tool: tool.to_string(),
+    })?;
 
     // 转换安装方法
-    let install_method = match method.as_str() {
-        "npm" => InstallMethod::Npm,
-        "brew" => InstallMethod::Brew,
-        "official" => InstallMethod::Official,
-        _ => {
-            return Err(AppError::ValidationError {
-                field: "method".to_string(),
-                reason: format!("未知的安装方法: {}", method),
-            })
-        }
-    };
+    let install_method = parse_install_method(method)?;
 
     // 使用 InstallerService 安装
     let installer = InstallerService::new();
 
+    op.progress("installing", None, format!("正在安装 {}", tool_obj.name));
     match installer.install(&tool_obj, &install_method, force).await {
         Ok(_) => {
-            // 安装成功（前端会调用 refresh_tool_status 更新数据库）
+            // 安装成功：仅重新检测该工具，写入数据库并更新状态缓存
+            op.progress("refreshing", None, format!("正在检测 {}", tool_obj.name));
+            let registry = registry_state.registry.lock().await;
+            let detected = match TOOL_STATUS_CACHE.refresh_tool(&registry, tool).await {
+                Ok(status) => status.installed,
+                Err(e) => {
+                    tracing::warn!(tool = %tool, error = ?e, "安装后刷新工具状态失败");
+                    true
+                }
+            };
+            drop(registry);
+
+            // npm 安装成功却检测不到：多半是 npm prefix 不在 PATH 中
+            if install_method == InstallMethod::Npm && !detected {
+                if let Some(issue) = diagnose_npm_prefix().await {
+                    return Ok(InstallResult {
+                        success: false,
+                        message: format!(
+                            "{} 已安装，但 npm 全局目录 {} 不在 PATH 中",
+                            tool_obj.name, issue.bin_dir
+                        ),
+                        output: issue.shell_snippet.clone(),
+                        error_code: Some(PREFIX_NOT_ON_PATH.to_string()),
+                        npm_prefix_issue: Some(issue),
+                    });
+                }
+            }
 
             // 构造成功消息
-            let message = match method.as_str() {
+            let message = match method {
                 "npm" => format!("✅ {} 安装成功！(通过 npm)", tool_obj.name),
                 "brew" => format!("✅ {} 安装成功！(通过 Homebrew)", tool_obj.name),
                 "official" => format!("✅ {} 安装成功！", tool_obj.name),
@@ -91,6 +148,8 @@ pub async fn install_tool(
                 success: true,
                 message,
                 output: String::new(),
+                error_code: None,
+                npm_prefix_issue: None,
             })
         }
         Err(e) => {
@@ -99,3 +158,43 @@ pub async fn install_tool(
         }
     }
 }
+
+/// 检查 npm 全局 bin 目录是否在检测使用的 PATH 中
+async fn diagnose_npm_prefix() -> Option<NpmPrefixIssue> {
+    let executor = CommandExecutor::new();
+    let platform = PlatformInfo::current();
+    let path_entries = platform.split_path(&executor.effective_path());
+    let shell = std::env::var("SHELL").ok();
+    check_npm_prefix(&executor, &platform, &path_entries, shell.as_deref()).await
+}
+
+/// 把目录登记为额外检测路径（npm prefix 不在 PATH 中时的自动修复），并重新检测该工具
+///
+/// 只影响 DuckCoding 自身的检测与调用，不修改用户的 shell 配置
+#[tauri::command]
+pub async fn register_extra_detection_path(
+    tool: String,
+    path: String,
+    registry_state: tauri::State<'_, ToolRegistryState>,
+) -> CommandResult<ToolStatus> {
+    npm_prefix::register_extra_path(&path).command_context("tool.extra_path_failed")?;
+    let registry = registry_state.registry.lock().await;
+    TOOL_STATUS_CACHE
+        .refresh_tool(&registry, &tool)
+        .await
+        .command_context("tool.status_check_failed")
+}
+
+/// 解析前端传入的安装方法（npm / brew / official）
+pub(crate) fn parse_install_method(method: &str) -> CommandResult<InstallMethod> {
+    match method {
+        "npm" => Ok(InstallMethod::Npm),
+        "brew" => Ok(InstallMethod::Brew),
+        "official" => Ok(InstallMethod::Official),
+        _ => Err(AppError::ValidationError {
+            field: "method".to_string(),
+            reason: format!("未知的安装方法: {}", method),
+        }
+        .into()),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::commands::error::{AppError, AppResult};
+use crate::commands::error::{AppError, CommandResult};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::ToolStatus;
 use ::duckcoding::models::InstallMethod;
@@ -17,7 +17,7 @@ pub async fn add_manual_tool_instance(
     install_method: String, // "npm" | "brew" | "official" | "other"
     installer_path: Option<String>,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<ToolStatus> {
+) -> CommandResult<ToolStatus> {
     // 解析安装方法
     let parsed_method = match install_method.as_str() {
         "npm" => InstallMethod::Npm,
@@ -28,7 +28,8 @@ pub async fn add_manual_tool_instance(
             return Err(AppError::ValidationError {
                 field: "install_method".to_string(),
                 reason: format!("未知的安装方法: {}", install_method),
-            })
+            }
+            .into())
         }
     };
 
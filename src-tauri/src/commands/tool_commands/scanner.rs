@@ -1,4 +1,4 @@
-use crate::commands::error::AppResult;
+use crate::commands::error::CommandResult;
 use ::duckcoding::utils::{scan_installer_paths, InstallerCandidate};
 
 /// 扫描工具路径的安装器
@@ -11,6 +11,8 @@ use ::duckcoding::utils::{scan_installer_paths, InstallerCandidate};
 ///
 /// 返回：安装器候选列表
 #[tauri::command]
-pub async fn scan_installer_for_tool_path(tool_path: String) -> AppResult<Vec<InstallerCandidate>> {
+pub async fn scan_installer_for_tool_path(
+    tool_path: String,
+) -> CommandResult<Vec<InstallerCandidate>> {
     Ok(scan_installer_paths(&tool_path))
 }
@@ -1,13 +1,17 @@
-use crate::commands::error::{AppError, AppResult};
+use crate::commands::error::{AppError, CommandResult};
+use crate::commands::operations::{Operation, OperationKind, OPERATIONS};
 use crate::commands::tool_management::ToolRegistryState;
 use crate::commands::types::{ToolStatus, UpdateResult};
 use ::duckcoding::models::Tool;
 use ::duckcoding::services::proxy::config::apply_global_proxy;
+use ::duckcoding::services::tool::TOOL_STATUS_CACHE;
+use ::duckcoding::services::update::skipped_versions::mark_skipped;
 use ::duckcoding::services::VersionService;
+use tauri::AppHandle;
 
 /// 检查工具更新（不执行更新）
 #[tauri::command]
-pub async fn check_update(tool: String) -> AppResult<UpdateResult> {
+pub async fn check_update(tool: String) -> CommandResult<UpdateResult> {
     // 应用代理配置（如果已配置）
     apply_global_proxy().ok();
 
@@ -20,16 +24,23 @@ pub async fn check_update(tool: String) -> AppResult<UpdateResult> {
     let version_service = VersionService::new();
 
     match version_service.check_version(&tool_obj).await {
-        Ok(version_info) => Ok(UpdateResult {
-            success: true,
-            message: "检查完成".to_string(),
-            has_update: version_info.has_update,
-            current_version: version_info.installed_version,
-            latest_version: version_info.latest_version,
-            mirror_version: version_info.mirror_version,
-            mirror_is_stale: Some(version_info.mirror_is_stale),
-            tool_id: Some(tool.clone()),
-        }),
+        Ok(version_info) => {
+            let mut result = UpdateResult {
+                success: true,
+                message: "检查完成".to_string(),
+                has_update: version_info.has_update,
+                current_version: version_info.installed_version,
+                latest_version: version_info.latest_version,
+                mirror_version: version_info.mirror_version,
+                mirror_is_stale: Some(version_info.mirror_is_stale),
+                tool_id: Some(tool.clone()),
+                skipped: false,
+                download_size: None,
+                duration_ms: None,
+            };
+            mark_skipped(&mut result);
+            Ok(result)
+        }
         Err(e) => {
             // 降级：如果检查失败，返回无法检查但不报错
             Ok(UpdateResult {
@@ -41,6 +52,9 @@ pub async fn check_update(tool: String) -> AppResult<UpdateResult> {
                 mirror_version: None,
                 mirror_is_stale: None,
                 tool_id: Some(tool.clone()),
+                skipped: false,
+                download_size: None,
+                duration_ms: None,
             })
         }
     }
@@ -57,9 +71,11 @@ pub async fn check_update(tool: String) -> AppResult<UpdateResult> {
 pub async fn check_update_for_instance(
     instance_id: String,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<UpdateResult> {
+) -> CommandResult<UpdateResult> {
     let registry = registry_state.registry.lock().await;
-    Ok(registry.check_update_for_instance(&instance_id).await?)
+    let mut result = registry.check_update_for_instance(&instance_id).await?;
+    mark_skipped(&mut result);
+    Ok(result)
 }
 
 /// 刷新数据库中所有工具的版本号（使用配置的路径检测）
@@ -72,14 +88,14 @@ pub async fn check_update_for_instance(
 #[tauri::command]
 pub async fn refresh_all_tool_versions(
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<Vec<ToolStatus>> {
+) -> CommandResult<Vec<ToolStatus>> {
     let registry = registry_state.registry.lock().await;
     Ok(registry.refresh_all_tool_versions().await?)
 }
 
 /// 批量检查所有工具更新
 #[tauri::command]
-pub async fn check_all_updates() -> AppResult<Vec<UpdateResult>> {
+pub async fn check_all_updates() -> CommandResult<Vec<UpdateResult>> {
     // 应用代理配置（如果已配置）
     apply_global_proxy().ok();
 
@@ -100,6 +116,13 @@ pub async fn check_all_updates() -> AppResult<Vec<UpdateResult>> {
             mirror_version: info.mirror_version,
             mirror_is_stale: Some(info.mirror_is_stale),
             tool_id: Some(info.tool_id),
+            skipped: false,
+            download_size: None,
+            duration_ms: None,
+        })
+        .map(|mut result| {
+            mark_skipped(&mut result);
+            result
         })
         .collect();
 
@@ -113,16 +136,45 @@ pub async fn check_all_updates() -> AppResult<Vec<UpdateResult>> {
 /// 2. Registry 负责从数据库获取实例信息
 /// 3. 使用 InstallerService 执行更新
 /// 4. 更新数据库中的版本号
+/// 5. 仅刷新该工具的状态缓存
 ///
 /// 返回：更新结果
 #[tauri::command]
 pub async fn update_tool_instance(
+    app: AppHandle,
     instance_id: String,
     force: Option<bool>,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<UpdateResult> {
+) -> CommandResult<UpdateResult> {
+    let op = OPERATIONS.start_operation(&app, OperationKind::Update, &instance_id);
+    let result = run_update(&op, &instance_id, force.unwrap_or(false), &registry_state).await;
+    op.finish(&result);
+    result
+}
+
+async fn run_update(
+    op: &Operation,
+    instance_id: &str,
+    force: bool,
+    registry_state: &ToolRegistryState,
+) -> CommandResult<UpdateResult> {
     let registry = registry_state.registry.lock().await;
-    Ok(registry
-        .update_instance(&instance_id, force.unwrap_or(false))
-        .await?)
+    op.progress("updating", None, format!("正在更新 {instance_id}"));
+    let result = registry.update_instance(instance_id, force).await?;
+
+    // 更新成功后仅刷新该工具的状态缓存
+    if result.success {
+        let tool_id = result
+            .tool_id
+            .clone()
+            .or_else(|| instance_id.strip_suffix("-local").map(str::to_string));
+        if let Some(tool_id) = tool_id {
+            op.progress("refreshing", None, format!("正在检测 {tool_id}"));
+            if let Err(e) = TOOL_STATUS_CACHE.refresh_tool(&registry, &tool_id).await {
+                tracing::warn!(tool_id = %tool_id, error = ?e, "更新后刷新工具状态失败");
+            }
+        }
+    }
+
+    Ok(result)
 }
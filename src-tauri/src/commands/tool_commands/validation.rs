@@ -1,15 +1,32 @@
-use crate::commands::error::AppResult;
+use crate::commands::error::CommandResult;
 use crate::commands::tool_management::ToolRegistryState;
-use crate::commands::types::NodeEnvironment;
+use crate::commands::types::{EffectivePath, NodeEnvironment};
+use ::duckcoding::models::NodeRuntime;
+use ::duckcoding::services::tool::node_env;
 use ::duckcoding::utils::platform::PlatformInfo;
+use ::duckcoding::utils::{captured_shell_env, CommandExecutor};
 use std::process::Command;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+/// 获取工具检测实际搜索的 PATH
+///
+/// `existing_only` 为 true 时去掉不存在的目录
+#[tauri::command]
+pub async fn get_effective_path(existing_only: Option<bool>) -> CommandResult<EffectivePath> {
+    let platform = PlatformInfo::current();
+    let path = CommandExecutor::new().effective_path();
+    Ok(EffectivePath {
+        entries: platform
+            .merge_path_entries(platform.split_path(&path), existing_only.unwrap_or(false)),
+        shell_env_applied: captured_shell_env().is_some_and(|env| env.contains_key("PATH")),
+    })
+}
+
 /// 检测 Node.js 和 npm 环境
 #[tauri::command]
-pub async fn check_node_environment() -> AppResult<NodeEnvironment> {
+pub async fn check_node_environment() -> CommandResult<NodeEnvironment> {
     let enhanced_path = PlatformInfo::current().build_enhanced_path();
     let run_command = |cmd: &str| -> Result<std::process::Output, std::io::Error> {
         #[cfg(target_os = "windows")]
@@ -63,6 +80,14 @@ pub async fn check_node_environment() -> AppResult<NodeEnvironment> {
     })
 }
 
+/// 列出本机所有 Node.js 运行时（PATH、nvm / fnm / volta / asdf、系统目录）及其版本与 npm 全局目录
+///
+/// 结果缓存 5 分钟，`refresh` 为 true 时重新扫描
+#[tauri::command]
+pub async fn get_node_environments(refresh: Option<bool>) -> CommandResult<Vec<NodeRuntime>> {
+    Ok(node_env::node_runtimes(&CommandExecutor::new(), refresh.unwrap_or(false)).await)
+}
+
 /// 验证用户指定的工具路径是否有效
 ///
 /// 工作流程：
@@ -75,7 +100,7 @@ pub async fn validate_tool_path(
     _tool_id: String,
     path: String,
     registry_state: tauri::State<'_, ToolRegistryState>,
-) -> AppResult<String> {
+) -> CommandResult<String> {
     let registry = registry_state.registry.lock().await;
     Ok(registry.validate_tool_path(&path).await?)
 }
@@ -1,8 +1,19 @@
-use duckcoding::models::{SSHConfig, ToolInstance};
-use duckcoding::services::tool::ToolRegistry;
-use duckcoding::utils::WSLExecutor;
-use std::collections::HashMap;
-use std::sync::Arc;
+use crate::commands::dashboard_commands::{clean_dashboard_selections, DashboardManagerState};
+use crate::commands::error::{CommandContext, CommandResult};
+use crate::commands::provider_commands::ProviderManagerState;
+use duckcoding::models::{SSHConfig, ToolDefinitions, ToolInstance, ToolStatus};
+use duckcoding::services::environment_report::{self, ReportFormat};
+use duckcoding::services::tool::{node_env, ToolRegistry, TOOL_STATUS_CACHE};
+use duckcoding::services::trash::{TrashManager, TrashedRecord};
+use duckcoding::utils::{
+    CommandExecutor, HostKeyStatus, SSHExecutor, SshConfigHost, SshTestResult, WSLExecutor,
+    WslDistro,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Once};
+use std::time::Instant;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
 use tokio::sync::Mutex;
 
 /// 工具注册表 State
@@ -10,16 +21,116 @@ pub struct ToolRegistryState {
     pub registry: Arc<Mutex<ToolRegistry>>,
 }
 
+impl ToolRegistryState {
+    /// 获取本地工具状态；缓存过期的工具先返回旧值，并在后台重新检测
+    ///
+    /// 检测结果与旧值不同时由状态缓存广播，经 `tool-status-changed` 事件通知前端。
+    pub async fn tool_statuses(&self) -> anyhow::Result<Vec<ToolStatus>> {
+        let view = self
+            .registry
+            .lock()
+            .await
+            .get_local_tool_status_view()
+            .await?;
+
+        static FIRST_SERVED: Once = Once::new();
+        FIRST_SERVED.call_once(|| {
+            tracing::info!(
+                elapsed_ms = crate::setup::elapsed_since_start().as_millis() as u64,
+                stale = view.stale.len(),
+                "首屏工具状态已返回（启动至可交互）"
+            );
+        });
+
+        for tool_id in view.stale {
+            if !TOOL_STATUS_CACHE.begin_refresh(&tool_id) {
+                continue;
+            }
+            let registry = self.registry.clone();
+            tauri::async_runtime::spawn(async move {
+                let registry = registry.lock().await;
+                let result = TOOL_STATUS_CACHE.refresh_tool(&registry, &tool_id).await;
+                if let Err(e) = result {
+                    tracing::warn!(tool_id = %tool_id, error = ?e, "后台刷新工具状态失败");
+                }
+                TOOL_STATUS_CACHE.end_refresh(&tool_id);
+            });
+        }
+
+        Ok(view.statuses)
+    }
+
+    /// 启动时在后台预热工具状态缓存，不阻塞窗口创建
+    ///
+    /// 首屏直接使用持久化缓存或数据库中的状态，缺失或过期的工具逐个重新检测，
+    /// 结果通过 `tool-status-changed` 事件推送。每个工具检测时持有注册表锁，
+    /// 并通过 `begin_refresh` 标记，与用户触发的刷新不会同时检测同一工具。
+    pub fn spawn_warmup(&self) {
+        let registry = self.registry.clone();
+        tauri::async_runtime::spawn(async move {
+            let started = Instant::now();
+            let view = registry.lock().await.get_local_tool_status_view().await;
+            let stale = match view {
+                Ok(view) => view.stale,
+                Err(e) => {
+                    tracing::warn!(error = ?e, "预热工具状态缓存失败");
+                    return;
+                }
+            };
+
+            let mut detected = 0;
+            for tool_id in stale {
+                if !TOOL_STATUS_CACHE.begin_refresh(&tool_id) {
+                    continue;
+                }
+                let result = {
+                    let registry = registry.lock().await;
+                    TOOL_STATUS_CACHE.refresh_tool(&registry, &tool_id).await
+                };
+                match result {
+                    Ok(_) => detected += 1,
+                    Err(e) => tracing::warn!(tool_id = %tool_id, error = ?e, "预热检测工具失败"),
+                }
+                TOOL_STATUS_CACHE.end_refresh(&tool_id);
+            }
+
+            tracing::info!(
+                detected,
+                warmup_ms = started.elapsed().as_millis() as u64,
+                since_start_ms = crate::setup::elapsed_since_start().as_millis() as u64,
+                "工具状态缓存预热完成"
+            );
+        });
+    }
+}
+
 /// 获取所有工具实例（按工具ID分组）- 只从数据库读取
 #[tauri::command]
 pub async fn get_tool_instances(
     state: tauri::State<'_, ToolRegistryState>,
 ) -> Result<HashMap<String, Vec<ToolInstance>>, String> {
     let registry = state.registry.lock().await;
-    registry
+    let mut grouped = registry
         .get_all_grouped()
         .await
-        .map_err(|e| format!("获取工具实例失败: {}", e))
+        .map_err(|e| format!("获取工具实例失败: {}", e))?;
+    registry
+        .annotate_node_runtimes(grouped.values_mut().flatten())
+        .await;
+    Ok(masked_grouped(grouped))
+}
+
+/// 返回前端的实例列表：敏感环境变量脱敏
+fn masked_grouped(
+    grouped: HashMap<String, Vec<ToolInstance>>,
+) -> HashMap<String, Vec<ToolInstance>> {
+    grouped
+        .into_iter()
+        .map(|(tool_id, instances)| {
+            let instances = instances.iter().map(ToolInstance::masked).collect();
+            (tool_id, instances)
+        })
+        .collect()
 }
 
 /// 刷新工具实例状态（仅从数据库读取，不重新检测）
@@ -28,13 +139,28 @@ pub async fn get_tool_instances(
 /// 如需添加新工具，请使用工具管理页面的「添加实例」功能
 #[tauri::command]
 pub async fn refresh_tool_instances(
+    app: AppHandle,
     state: tauri::State<'_, ToolRegistryState>,
+    dashboard_state: tauri::State<'_, DashboardManagerState>,
+    provider_state: tauri::State<'_, ProviderManagerState>,
 ) -> Result<HashMap<String, Vec<ToolInstance>>, String> {
     let registry = state.registry.lock().await;
-    registry
+    let mut grouped = registry
         .get_all_grouped()
         .await
-        .map_err(|e| format!("获取工具实例失败: {}", e))
+        .map_err(|e| format!("获取工具实例失败: {}", e))?;
+    registry
+        .annotate_node_runtimes(grouped.values_mut().flatten())
+        .await;
+
+    // 刷新后清理仪表板中指向已不存在实例的选择（失败不影响刷新结果）
+    if let Err(e) =
+        clean_dashboard_selections(&app, &dashboard_state.manager, &registry, &provider_state).await
+    {
+        tracing::warn!(error = %e, "清理仪表板选择失败");
+    }
+
+    Ok(masked_grouped(grouped))
 }
 
 /// 列出所有可用的WSL发行版
@@ -43,6 +169,14 @@ pub async fn list_wsl_distributions() -> Result<Vec<String>, String> {
     WSLExecutor::list_distributions().map_err(|e| format!("列出WSL发行版失败: {}", e))
 }
 
+/// 列出WSL发行版（含状态、版本与默认发行版标记）
+#[tauri::command]
+pub async fn list_wsl_distros() -> Result<Vec<WslDistro>, String> {
+    WSLExecutor::list_distros()
+        .await
+        .map_err(|e| format!("列出WSL发行版失败: {}", e))
+}
+
 /// 添加WSL工具实例
 #[tauri::command]
 pub async fn add_wsl_tool_instance(
@@ -57,29 +191,217 @@ pub async fn add_wsl_tool_instance(
         .map_err(|e| format!("添加WSL实例失败: {}", e))
 }
 
-/// 添加SSH工具实例（本期仅存储配置）
+/// 添加SSH工具实例（认证失败时拒绝保存，force 为 true 时强制保存）
 #[tauri::command]
 pub async fn add_ssh_tool_instance(
     state: tauri::State<'_, ToolRegistryState>,
     base_id: String,
     ssh_config: SSHConfig,
+    force: Option<bool>,
 ) -> Result<ToolInstance, String> {
     let registry = state.registry.lock().await;
     registry
-        .add_ssh_instance(&base_id, ssh_config)
+        .add_ssh_instance(&base_id, ssh_config, force.unwrap_or(false))
         .await
         .map_err(|e| format!("添加SSH实例失败: {}", e))
 }
 
+/// 列出 ~/.ssh/config 中的主机
+#[tauri::command]
+pub async fn list_ssh_config_hosts() -> Result<Vec<SshConfigHost>, String> {
+    duckcoding::utils::list_ssh_config_hosts().map_err(|e| format!("读取SSH配置失败: {}", e))
+}
+
+/// 从 ~/.ssh/config 的主机创建SSH工具实例
+#[tauri::command]
+pub async fn create_ssh_instance_from_host(
+    state: tauri::State<'_, ToolRegistryState>,
+    alias: String,
+    tool_id: String,
+    force: Option<bool>,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .create_ssh_instance_from_host(&alias, &tool_id, force.unwrap_or(false))
+        .await
+        .map_err(|e| format!("添加SSH实例失败: {}", e))
+}
+
+/// 测试SSH连接（按 DNS、TCP、认证、执行分阶段报告）
+#[tauri::command]
+pub async fn test_ssh_connection(ssh_config: SSHConfig) -> Result<SshTestResult, String> {
+    Ok(SSHExecutor::new(ssh_config).test_connection().await)
+}
+
+/// 查询SSH主机密钥是否已受信任（未知时返回指纹供用户确认）
+#[tauri::command]
+pub async fn get_ssh_host_key_status(ssh_config: SSHConfig) -> Result<HostKeyStatus, String> {
+    SSHExecutor::new(ssh_config)
+        .host_key_status()
+        .await
+        .map_err(|e| format!("查询SSH主机密钥失败: {}", e))
+}
+
+/// 信任SSH主机密钥（指纹须与用户确认的一致）
+#[tauri::command]
+pub async fn trust_ssh_host_key(
+    ssh_config: SSHConfig,
+    fingerprints: Vec<String>,
+) -> Result<(), String> {
+    SSHExecutor::new(ssh_config)
+        .trust_host_key(&fingerprints)
+        .await
+        .map_err(|e| format!("信任SSH主机密钥失败: {}", e))
+}
+
+/// 重新检测SSH工具实例
+#[tauri::command]
+pub async fn refresh_ssh_tool_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .refresh_ssh_instance(&instance_id)
+        .await
+        .map_err(|e| format!("检测SSH实例失败: {}", e))
+}
+
+/// 在SSH实例的远程主机上安装工具
+#[tauri::command]
+pub async fn install_ssh_tool_instance(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+) -> Result<ToolInstance, String> {
+    let registry = state.registry.lock().await;
+    registry
+        .install_ssh_instance(&instance_id)
+        .await
+        .map_err(|e| format!("安装SSH实例失败: {}", e))
+}
+
+/// 设置工具实例的标签与备注（空字符串视为清除）
+#[tauri::command]
+pub async fn set_tool_instance_label(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    label: Option<String>,
+    notes: Option<String>,
+) -> CommandResult<ToolInstance> {
+    let registry = state.registry.lock().await;
+    registry
+        .set_instance_label(&instance_id, label.as_deref(), notes.as_deref())
+        .await
+        .map(|instance| instance.masked())
+        .command_context("tool.instance.label_failed")
+}
+
+/// 设置工具实例的环境变量覆盖（空表示清除）
+///
+/// 版本检查、重新验证与健康检查执行该实例的二进制时注入；返回的实例中敏感值已脱敏，
+/// 原样提交脱敏值时保留原值。
+#[tauri::command]
+pub async fn set_instance_env(
+    state: tauri::State<'_, ToolRegistryState>,
+    instance_id: String,
+    env: BTreeMap<String, String>,
+) -> CommandResult<ToolInstance> {
+    let registry = state.registry.lock().await;
+    registry
+        .set_instance_env(&instance_id, env)
+        .await
+        .map(|instance| instance.masked())
+        .command_context("tool.instance.env_failed")
+}
+
+/// 获取所有工具的完整定义（含解析后的配置路径、包坐标、当前平台的安装方式与可检测性）
+///
+/// 载荷带版本号，引导向导等界面依据它渲染，字段不兼容变更时递增版本
+#[tauri::command]
+pub async fn get_tool_definitions(
+    state: tauri::State<'_, ToolRegistryState>,
+) -> CommandResult<ToolDefinitions> {
+    let registry = state.registry.lock().await;
+    registry
+        .get_tool_definitions()
+        .await
+        .command_context("tool.definitions_failed")
+}
+
+/// 弹出保存对话框，导出环境报告（工具实例、Node.js 运行时、供应商绑定、代理设置），返回写入的路径
+///
+/// 路径只能来自系统对话框，不接受前端传入的字符串；按所选扩展名决定 Markdown 或 JSON，
+/// 用户取消时返回 `None`。报告不含任何凭证，路径中的用户目录替换为 `~`
+#[tauri::command]
+pub async fn export_environment_report(app: AppHandle) -> CommandResult<Option<String>> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("Markdown", &["md"])
+        .add_filter("JSON", &["json"])
+        .set_file_name("duckcoding-environment.md")
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+
+    let chosen = rx
+        .await
+        .map_err(anyhow::Error::from)
+        .command_context("tool.report.export_failed")?;
+    let Some(chosen) = chosen else {
+        return Ok(None);
+    };
+    let output = chosen
+        .into_path()
+        .map_err(|e| anyhow::anyhow!("无效的导出路径: {e}"))
+        .command_context("tool.report.export_failed")?;
+    let format = ReportFormat::from_path(&output);
+
+    let app_version = app.package_info().version.to_string();
+    let node_runtimes = node_env::node_runtimes(&CommandExecutor::new(), false).await;
+    tokio::task::spawn_blocking(move || {
+        environment_report::export_environment_report(&app_version, format, &output, node_runtimes)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result)
+    .map(|path| Some(path.to_string_lossy().into_owned()))
+    .command_context("tool.report.export_failed")
+}
+
 /// 删除工具实例（仅SSH类型）
+///
+/// 默认移入回收站（可在 30 天内恢复）；`hard` 为 true 时直接永久删除。
 #[tauri::command]
 pub async fn delete_tool_instance(
+    app: AppHandle,
     state: tauri::State<'_, ToolRegistryState>,
+    dashboard_state: tauri::State<'_, DashboardManagerState>,
+    provider_state: tauri::State<'_, ProviderManagerState>,
     instance_id: String,
+    hard: Option<bool>,
 ) -> Result<(), String> {
     let registry = state.registry.lock().await;
-    registry
+    let removed = registry
         .delete_instance(&instance_id)
         .await
-        .map_err(|e| format!("删除工具实例失败: {}", e))
+        .map_err(|e| format!("删除工具实例失败: {}", e))?;
+    if !hard.unwrap_or(false) {
+        let trashed = TrashManager::new()
+            .and_then(|trash| trash.put(TrashedRecord::ToolInstance(removed.clone())));
+        // 写入回收站失败时撤销删除，避免记录丢失
+        if let Err(e) = trashed {
+            if let Err(undo_err) = registry.restore_instance(removed).await {
+                tracing::error!(error = ?undo_err, "移入回收站失败后撤销删除失败");
+            }
+            return Err(format!("移入回收站失败: {}", e));
+        }
+    }
+
+    if let Err(e) =
+        clean_dashboard_selections(&app, &dashboard_state.manager, &registry, &provider_state).await
+    {
+        tracing::warn!(error = %e, "清理仪表板选择失败");
+    }
+    Ok(())
 }
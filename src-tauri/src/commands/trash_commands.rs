@@ -0,0 +1,87 @@
+//! 回收站命令
+//!
+//! 删除供应商与工具实例时默认移入回收站（`hard` 为 true 时直接删除），
+//! 记录保留 30 天，期间可恢复；每次列出回收站时顺带清除过期记录。
+
+use crate::commands::dashboard_commands::{clean_dashboard_selections, DashboardManagerState};
+use crate::commands::error::{CommandContext, CommandResult};
+use crate::commands::provider_commands::ProviderManagerState;
+use crate::commands::tool_management::ToolRegistryState;
+use ::duckcoding::services::tool::ToolRegistry;
+use ::duckcoding::services::trash::{
+    RestoreResult, TrashItem, TrashManager, TrashedRecord, TRASH_RETENTION_DAYS,
+};
+use tauri::{AppHandle, State};
+
+/// 列出回收站中的记录（按删除时间倒序）
+#[tauri::command]
+pub async fn list_trash() -> CommandResult<Vec<TrashItem>> {
+    let trash = TrashManager::new().command_context("trash.list_failed")?;
+    if let Err(e) = trash.purge_expired(chrono::Utc::now().timestamp()) {
+        tracing::warn!(error = ?e, days = TRASH_RETENTION_DAYS, "清除过期回收站记录失败");
+    }
+    let entries = trash.list().command_context("trash.list_failed")?;
+    Ok(entries.iter().map(TrashItem::from).collect())
+}
+
+/// 从回收站恢复记录
+///
+/// 原 ID 已被新记录占用时生成新 ID（见 `RestoreResult::restored_id`）。
+#[tauri::command]
+pub async fn restore_from_trash(
+    app: AppHandle,
+    id: String,
+    provider_state: State<'_, ProviderManagerState>,
+    dashboard_state: State<'_, DashboardManagerState>,
+    registry_state: State<'_, ToolRegistryState>,
+) -> CommandResult<RestoreResult> {
+    let trash = TrashManager::new().command_context("trash.restore_failed")?;
+    let entry = trash.get(&id).command_context("trash.restore_failed")?;
+    let kind = entry.item.kind();
+    let original_id = entry.item.original_id().to_string();
+
+    let registry = registry_state.registry.lock().await;
+    let restored_id = restore_record(entry.item, &provider_state, &registry)
+        .await
+        .command_context("trash.restore_failed")?;
+    trash.remove(&id).command_context("trash.restore_failed")?;
+
+    if let Err(e) =
+        clean_dashboard_selections(&app, &dashboard_state.manager, &registry, &provider_state).await
+    {
+        tracing::warn!(error = %e, "清理仪表板选择失败");
+    }
+    Ok(RestoreResult {
+        kind,
+        original_id,
+        restored_id,
+    })
+}
+
+async fn restore_record(
+    record: TrashedRecord,
+    provider_state: &ProviderManagerState,
+    registry: &ToolRegistry,
+) -> anyhow::Result<String> {
+    match record {
+        TrashedRecord::Provider(provider) => provider_state
+            .manager
+            .restore_provider(provider)
+            .map(|provider| provider.id),
+        TrashedRecord::ToolInstance(instance) => registry
+            .restore_instance(instance)
+            .await
+            .map(|instance| instance.instance_id),
+    }
+}
+
+/// 永久删除回收站记录（`id` 为空时清空回收站），返回删除的记录数
+#[tauri::command]
+pub async fn purge_trash(id: Option<String>) -> CommandResult<usize> {
+    let trash = TrashManager::new().command_context("trash.purge_failed")?;
+    match id {
+        Some(id) => trash.remove(&id).map(|()| 1),
+        None => trash.purge_all(),
+    }
+    .command_context("trash.purge_failed")
+}
@@ -0,0 +1,96 @@
+//! 供应商限时试用命令
+//!
+//! 试用期间工具使用所选供应商，到期后自动恢复原配置；到期检查由启动时的后台任务执行，
+//! 应用、即将到期与恢复均通过 `provider-trial` 事件通知前端。
+
+use crate::commands::error::{AppError, CommandContext, CommandError, CommandResult};
+use crate::commands::profile_commands::ProfileManagerState;
+use crate::commands::provider_commands::ProviderManagerState;
+use ::duckcoding::core::tr_with;
+use ::duckcoding::models::Tool;
+use ::duckcoding::services::provider_trial::{
+    ProviderTrialEvent, ProviderTrialInfo, MAX_TRIAL_MINUTES, PROVIDER_TRIALS,
+};
+use tauri::State;
+
+/// 开始试用供应商：应用到工具并在 `duration_minutes` 分钟后自动恢复原配置
+#[tauri::command]
+pub async fn start_provider_trial(
+    provider_id: String,
+    tool_id: String,
+    duration_minutes: u32,
+    provider_state: State<'_, ProviderManagerState>,
+    profile_state: State<'_, ProfileManagerState>,
+) -> CommandResult<ProviderTrialInfo> {
+    let tool = Tool::by_id(&tool_id).ok_or_else(|| AppError::ToolNotFound {
+        tool: tool_id.clone(),
+    })?;
+    if duration_minutes == 0 || duration_minutes > MAX_TRIAL_MINUTES {
+        let max = MAX_TRIAL_MINUTES.to_string();
+        return Err(CommandError::validation(tr_with(
+            "trial.invalid_duration",
+            &[("max", max.as_str())],
+        )));
+    }
+    let provider = provider_state
+        .manager
+        .get_provider(&provider_id)
+        .command_context("provider.get_failed")?
+        .ok_or_else(|| {
+            CommandError::not_found(tr_with(
+                "provider.not_found",
+                &[("id", provider_id.as_str())],
+            ))
+        })?;
+
+    let active = PROVIDER_TRIALS
+        .active_trials()
+        .command_context("trial.list_failed")?;
+    if active.iter().any(|trial| trial.tool_id == tool.id) {
+        return Err(CommandError::conflict(tr_with(
+            "trial.already_active",
+            &[("tool", tool.name.as_str())],
+        )));
+    }
+
+    let manager = profile_state.manager.write().await;
+    PROVIDER_TRIALS
+        .start(&manager, &tool, &provider, duration_minutes)
+        .map(|trial| trial.info())
+        .command_context("trial.start_failed")
+}
+
+/// 提前结束试用：`keep_config` 为 true 时保留当前配置，否则立即恢复原配置
+#[tauri::command]
+pub async fn cancel_provider_trial(
+    trial_id: String,
+    keep_config: Option<bool>,
+    profile_state: State<'_, ProfileManagerState>,
+) -> CommandResult<ProviderTrialEvent> {
+    let exists = PROVIDER_TRIALS
+        .active_trials()
+        .command_context("trial.list_failed")?
+        .iter()
+        .any(|trial| trial.id == trial_id);
+    if !exists {
+        return Err(CommandError::not_found(tr_with(
+            "trial.not_found",
+            &[("id", trial_id.as_str())],
+        )));
+    }
+
+    // 持有 Profile 写锁，避免恢复与 Profile 切换交错
+    let _manager = profile_state.manager.write().await;
+    PROVIDER_TRIALS
+        .cancel(&trial_id, keep_config.unwrap_or(false))
+        .command_context("trial.cancel_failed")
+}
+
+/// 进行中的试用
+#[tauri::command]
+pub async fn get_active_trials() -> CommandResult<Vec<ProviderTrialInfo>> {
+    let trials = PROVIDER_TRIALS
+        .active_trials()
+        .command_context("trial.list_failed")?;
+    Ok(trials.iter().map(|trial| trial.info()).collect())
+}
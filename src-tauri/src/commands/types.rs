@@ -2,6 +2,7 @@
 
 // 重新导出 models 层的类型
 pub use duckcoding::models::{ToolStatus, UpdateResult};
+use duckcoding::services::tool::npm_prefix::NpmPrefixIssue;
 
 /// Node 环境信息
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -12,10 +13,25 @@ pub struct NodeEnvironment {
     pub npm_version: Option<String>,
 }
 
+/// 检测时实际搜索的 PATH（诊断用）
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct EffectivePath {
+    /// 按优先级排列的目录（已去重）
+    pub entries: Vec<String>,
+    /// 是否合并了登录 shell 的 PATH
+    pub shell_env_applied: bool,
+}
+
 /// 安装结果
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct InstallResult {
     pub success: bool,
     pub message: String,
     pub output: String,
+    /// 失败原因的错误码（如 `PREFIX_NOT_ON_PATH`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// npm 全局 bin 目录不在 PATH 中时的详情（含修复片段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npm_prefix_issue: Option<NpmPrefixIssue>,
 }
@@ -6,7 +6,10 @@ use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager, State};
 
 use ::duckcoding::models::update::{PackageFormatInfo, PlatformInfo};
-use ::duckcoding::services::update::{UpdateInfo, UpdateService, UpdateStatus};
+use ::duckcoding::services::update::skipped_versions;
+use ::duckcoding::services::update::{
+    AppUpdateCheck, AppUpdateService, UpdateInfo, UpdateService, UpdateStatus,
+};
 
 /// 统一管理 UpdateService 的 Tauri State
 pub struct UpdateServiceState {
@@ -38,6 +41,25 @@ pub async fn check_for_app_updates(
         .map_err(|e| format!("Failed to check for updates: {e}"))
 }
 
+/// 通过 GitHub Releases 检查 DuckCoding 是否有新版本（结果缓存一天）
+#[tauri::command]
+pub async fn check_app_update(
+    app: AppHandle,
+    force: Option<bool>,
+) -> Result<AppUpdateCheck, String> {
+    let current = app.package_info().version.to_string();
+    AppUpdateService::new()
+        .check(&current, force.unwrap_or(false))
+        .await
+        .map_err(|e| format!("检查应用更新失败: {e}"))
+}
+
+/// 跳过某个对象（`duckcoding` 或工具 ID）的指定版本，定时检查不再提醒
+#[tauri::command]
+pub async fn skip_version(subject: String, version: String) -> Result<(), String> {
+    skipped_versions::skip_version(&subject, &version).map_err(|e| format!("跳过版本失败: {e}"))
+}
+
 /// 下载应用更新
 #[tauri::command]
 pub async fn download_app_update(
@@ -1,3 +1,4 @@
+use duckcoding::data::network_fs;
 use duckcoding::services::config::NotifyWatcherManager;
 use duckcoding::utils::config::{read_global_config, write_global_config};
 use tauri::AppHandle;
@@ -36,6 +37,12 @@ pub async fn start_watcher_if_needed(
         }
     }
 
+    // 配置目录位于网络共享时文件系统通知不可靠
+    if network_fs::network_backed() {
+        warn!("Config dirs are on a network share, skip starting watcher");
+        return Err("配置目录位于网络共享，已停用监听".to_string());
+    }
+
     // 检查全局配置是否允许
     if let Ok(Some(cfg)) = read_global_config() {
         if !cfg.external_watch_enabled {
@@ -1,5 +1,5 @@
 use crate::commands::error::{AppError, AppResult};
-use ::duckcoding::ui;
+use crate::setup::tray;
 use tauri::{Manager, WebviewWindow};
 
 /// 处理窗口关闭操作
@@ -11,8 +11,8 @@ use tauri::{Manager, WebviewWindow};
 pub fn handle_close_action(window: WebviewWindow, action: String) -> AppResult<()> {
     match action.as_str() {
         "minimize" => {
-            // 隐藏到托盘
-            ui::hide_window_to_tray(&window);
+            // 隐藏到托盘（托盘不可用时最小化）
+            tray::hide_window_to_tray(&window);
             Ok(())
         }
         "quit" => {
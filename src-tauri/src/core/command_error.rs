@@ -0,0 +1,416 @@
+//! Tauri 命令边界错误
+//!
+//! 命令统一返回 `Result<T, CommandError>`，序列化后的结构固定为：
+//!
+//! ```json
+//! { "code": "NOT_FOUND", "message": "获取供应商失败: 供应商不存在: p1", "details": "…完整错误链…" }
+//! ```
+//!
+//! - `code`：稳定的错误码，前端据此分支或本地化（取值见 [`CommandError::code`]）
+//! - `message`：人类可读的兜底文案
+//! - `details`：anyhow 错误链（`{:#}` 格式），无额外上下文时为 `null`
+//!
+//! 服务层仍使用 anyhow / AppError / DataError，在命令边界通过 [`CommandContext`]
+//! 或 `?`（`From` 实现）转换，错误码按错误链中的具体类型推断。
+//!
+//! 上下文与 [`LocalizedError`] 通过文案目录（`core::i18n`）按当前后端语言渲染。
+
+use super::error::AppError;
+use super::i18n::{tr, LocalizedError};
+use crate::data::DataError;
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use thiserror::Error;
+
+/// 命令错误
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// 资源不存在（工具、供应商、文件等）
+    #[error("{message}")]
+    NotFound {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 参数校验失败
+    #[error("{message}")]
+    Validation {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 文件读写失败
+    #[error("{message}")]
+    Io {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 网络请求失败
+    #[error("{message}")]
+    Network {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 资源冲突（已存在、状态不允许）
+    #[error("{message}")]
+    Conflict {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 文件被其他进程锁定
+    #[error("{message}")]
+    Locked {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 当前平台或环境不支持
+    #[error("{message}")]
+    Unsupported {
+        message: String,
+        details: Option<String>,
+    },
+
+    /// 未归类的内部错误
+    #[error("{message}")]
+    Internal {
+        message: String,
+        details: Option<String>,
+    },
+}
+
+/// 错误类别（不含文案，用于按错误链推断）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    NotFound,
+    Validation,
+    Io,
+    Network,
+    Conflict,
+    Locked,
+    Unsupported,
+    Internal,
+}
+
+impl CommandError {
+    fn build(kind: Kind, message: String, details: Option<String>) -> Self {
+        match kind {
+            Kind::NotFound => Self::NotFound { message, details },
+            Kind::Validation => Self::Validation { message, details },
+            Kind::Io => Self::Io { message, details },
+            Kind::Network => Self::Network { message, details },
+            Kind::Conflict => Self::Conflict { message, details },
+            Kind::Locked => Self::Locked { message, details },
+            Kind::Unsupported => Self::Unsupported { message, details },
+            Kind::Internal => Self::Internal { message, details },
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::build(Kind::NotFound, message.into(), None)
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::build(Kind::Validation, message.into(), None)
+    }
+
+    /// 由 [`LocalizedError`] 构造（按当前后端语言渲染文案）
+    pub fn localized(err: LocalizedError) -> Self {
+        Self::build(Kind::from_code(err.code), err.to_string(), None)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::build(Kind::Conflict, message.into(), None)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::build(Kind::Unsupported, message.into(), None)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::build(Kind::Internal, message.into(), None)
+    }
+
+    /// 稳定错误码（序列化字段 `code`）
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "NOT_FOUND",
+            Self::Validation { .. } => "VALIDATION",
+            Self::Io { .. } => "IO",
+            Self::Network { .. } => "NETWORK",
+            Self::Conflict { .. } => "CONFLICT",
+            Self::Locked { .. } => "LOCKED",
+            Self::Unsupported { .. } => "UNSUPPORTED",
+            Self::Internal { .. } => "INTERNAL",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        self.parts().0
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        self.parts().1
+    }
+
+    fn parts(&self) -> (&str, Option<&str>) {
+        match self {
+            Self::NotFound { message, details }
+            | Self::Validation { message, details }
+            | Self::Io { message, details }
+            | Self::Network { message, details }
+            | Self::Conflict { message, details }
+            | Self::Locked { message, details }
+            | Self::Unsupported { message, details }
+            | Self::Internal { message, details } => (message, details.as_deref()),
+        }
+    }
+
+    /// 将 anyhow 错误转换为命令错误：`message` 为「上下文: 顶层错误」，`details` 为完整错误链
+    ///
+    /// `context` 为文案目录 key（未收录的文本原样使用）
+    pub fn from_anyhow(context: Option<&str>, err: &anyhow::Error) -> Self {
+        let kind = classify(err);
+        let top = err.to_string();
+        let message = match (context, top.is_empty()) {
+            (Some(context), false) => format!("{}: {top}", tr(context)),
+            (Some(context), true) => tr(context),
+            (None, false) => top.clone(),
+            (None, true) => tr(kind.catalog_key()),
+        };
+        let chain = format!("{err:#}");
+        let details = (chain != top || context.is_some()).then_some(chain);
+        Self::build(kind, message, details)
+    }
+}
+
+impl Kind {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "NOT_FOUND" => Kind::NotFound,
+            "VALIDATION" => Kind::Validation,
+            "IO" => Kind::Io,
+            "NETWORK" => Kind::Network,
+            "CONFLICT" => Kind::Conflict,
+            "LOCKED" => Kind::Locked,
+            "UNSUPPORTED" => Kind::Unsupported,
+            _ => Kind::Internal,
+        }
+    }
+
+    /// 错误码的通用文案（`error.<CODE>`）
+    fn catalog_key(&self) -> &'static str {
+        match self {
+            Kind::NotFound => "error.NOT_FOUND",
+            Kind::Validation => "error.VALIDATION",
+            Kind::Io => "error.IO",
+            Kind::Network => "error.NETWORK",
+            Kind::Conflict => "error.CONFLICT",
+            Kind::Locked => "error.LOCKED",
+            Kind::Unsupported => "error.UNSUPPORTED",
+            Kind::Internal => "error.INTERNAL",
+        }
+    }
+}
+
+/// 按错误链中第一个可识别的具体类型推断错误类别
+fn classify(err: &anyhow::Error) -> Kind {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<LocalizedError>() {
+            return Kind::from_code(e.code);
+        }
+        if let Some(e) = cause.downcast_ref::<DataError>() {
+            match e {
+                DataError::Locked { .. } => return Kind::Locked,
+                DataError::NotFound(_) => return Kind::NotFound,
+                DataError::Io { source, .. } => return io_kind(source),
+                DataError::Permission(_) | DataError::PolicyViolation { .. } => return Kind::Io,
+                DataError::InvalidKey(_) => return Kind::Validation,
+                _ => {}
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<AppError>() {
+            if let Some(kind) = app_error_kind(e) {
+                return kind;
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            return io_kind(e);
+        }
+        if cause.downcast_ref::<reqwest::Error>().is_some() {
+            return Kind::Network;
+        }
+    }
+    Kind::Internal
+}
+
+fn io_kind(err: &std::io::Error) -> Kind {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => Kind::NotFound,
+        std::io::ErrorKind::Unsupported => Kind::Unsupported,
+        _ => Kind::Io,
+    }
+}
+
+fn app_error_kind(err: &AppError) -> Option<Kind> {
+    Some(match err {
+        AppError::ToolNotFound { .. }
+        | AppError::ToolNotInstalled { .. }
+        | AppError::ConfigNotFound { .. }
+        | AppError::ProfileNotFound { .. }
+        | AppError::ProviderNotFound { .. }
+        | AppError::FileNotFound { .. } => Kind::NotFound,
+        AppError::ToolAlreadyInstalled { .. }
+        | AppError::ProfileAlreadyExists { .. }
+        | AppError::ProviderAlreadyExists { .. } => Kind::Conflict,
+        AppError::InvalidConfig { .. }
+        | AppError::ValidationError { .. }
+        | AppError::InvalidApiKey => Kind::Validation,
+        AppError::ConfigReadError { .. }
+        | AppError::ConfigWriteError { .. }
+        | AppError::DirCreationError { .. }
+        | AppError::PermissionDenied { .. } => Kind::Io,
+        AppError::NetworkError { .. }
+        | AppError::DownloadError { .. }
+        | AppError::ApiError { .. }
+        | AppError::Timeout { .. } => Kind::Network,
+        AppError::Unimplemented { .. } | AppError::EnvironmentError { .. } => Kind::Unsupported,
+        _ => return None,
+    })
+}
+
+impl Serialize for CommandError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("CommandError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::from_anyhow(None, &err)
+    }
+}
+
+impl From<AppError> for CommandError {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::Other(err) => err.into(),
+            err => Self::from_anyhow(None, &anyhow::Error::new(err)),
+        }
+    }
+}
+
+impl From<LocalizedError> for CommandError {
+    fn from(err: LocalizedError) -> Self {
+        Self::localized(err)
+    }
+}
+
+impl From<DataError> for CommandError {
+    fn from(err: DataError) -> Self {
+        Self::from_anyhow(None, &anyhow::Error::new(err))
+    }
+}
+
+/// 旧接口返回的字符串错误无法推断类别，统一归为 INTERNAL
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::internal(message)
+    }
+}
+
+impl From<&str> for CommandError {
+    fn from(message: &str) -> Self {
+        Self::internal(message)
+    }
+}
+
+/// 命令边界的错误上下文扩展（替代 `map_err(|e| format!("…失败: {e}"))`）
+pub trait CommandContext<T> {
+    fn command_context(self, context: &str) -> Result<T, CommandError>;
+}
+
+impl<T, E> CommandContext<T> for Result<T, E>
+where
+    E: Into<anyhow::Error>,
+{
+    fn command_context(self, context: &str) -> Result<T, CommandError> {
+        self.map_err(|e| CommandError::from_anyhow(Some(context), &e.into()))
+    }
+}
+
+/// 命令返回类型
+pub type CommandResult<T> = Result<T, CommandError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_wire_shape() {
+        let err = CommandError::not_found("供应商不存在: p1");
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "NOT_FOUND",
+                "message": "供应商不存在: p1",
+                "details": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_context_preserves_chain() {
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let result: anyhow::Result<()> = Err(anyhow::Error::new(io)).context("写入 providers.json");
+        let err = result.command_context("保存供应商失败").unwrap_err();
+
+        assert_eq!(err.code(), "IO");
+        assert_eq!(err.message(), "保存供应商失败: 写入 providers.json");
+        assert_eq!(err.details(), Some("写入 providers.json: denied"));
+    }
+
+    #[test]
+    fn test_classify_typed_errors() {
+        let locked: CommandError = DataError::Locked {
+            path: "providers.json".into(),
+            holder: Some(42),
+        }
+        .into();
+        assert_eq!(locked.code(), "LOCKED");
+
+        let conflict: CommandError = AppError::ProviderAlreadyExists {
+            id: "p1".to_string(),
+        }
+        .into();
+        assert_eq!(conflict.code(), "CONFLICT");
+
+        let wrapped = anyhow::Error::new(AppError::ToolNotFound {
+            tool: "x".to_string(),
+        })
+        .context("检查更新");
+        assert_eq!(CommandError::from(wrapped).code(), "NOT_FOUND");
+
+        let localized = anyhow::Error::new(
+            LocalizedError::new("CONFLICT", "tool.instance.path_conflict").arg("name", "Codex"),
+        )
+        .context("添加实例");
+        assert_eq!(CommandError::from(localized).code(), "CONFLICT");
+
+        let plain: CommandError = anyhow::anyhow!("未知错误").into();
+        assert_eq!(plain.code(), "INTERNAL");
+        assert_eq!(plain.details(), None);
+    }
+}
@@ -65,6 +65,14 @@ pub enum AppError {
     #[error("配置 Profile '{profile}' 已存在")]
     ProfileAlreadyExists { profile: String },
 
+    /// 供应商不存在
+    #[error("供应商不存在: {id}")]
+    ProviderNotFound { id: String },
+
+    /// 供应商已存在
+    #[error("供应商 ID 已存在: {id}")]
+    ProviderAlreadyExists { id: String },
+
     // ==================== 网络相关错误 ====================
     /// 网络请求失败
     #[error("网络请求失败: {url}")]
@@ -415,6 +423,18 @@ impl Serialize for AppError {
                 state.serialize_field("profile", profile)?;
                 state.end()
             }
+            AppError::ProviderNotFound { id } => {
+                let mut state = serializer.serialize_struct("AppError", 2)?;
+                state.serialize_field("type", "ProviderNotFound")?;
+                state.serialize_field("id", id)?;
+                state.end()
+            }
+            AppError::ProviderAlreadyExists { id } => {
+                let mut state = serializer.serialize_struct("AppError", 2)?;
+                state.serialize_field("type", "ProviderAlreadyExists")?;
+                state.serialize_field("id", id)?;
+                state.end()
+            }
 
             // 网络相关错误
             AppError::NetworkError { url, source } => {
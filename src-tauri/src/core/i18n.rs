@@ -0,0 +1,173 @@
+//! 后端文案目录
+//!
+//! 面向用户的错误提示与通知按 key 从 `resources/i18n/<locale>.json` 查找，
+//! 当前语言缺少的 key 回退到 en-US，仍未找到时原样返回 key 本身
+//! （因此尚未迁移的中文文案可以直接作为 key 传入）。
+//!
+//! 文案中的 `{name}` 占位符由调用方传入的参数替换。日志不经过目录。
+
+use crate::models::config::Locale;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+type Catalog = HashMap<String, String>;
+
+fn parse_catalog(locale: Locale, raw: &str) -> Catalog {
+    serde_json::from_str(raw)
+        .unwrap_or_else(|e| panic!("内置文案目录 {} 格式错误: {e}", locale.as_str()))
+}
+
+static CATALOGS: Lazy<HashMap<Locale, Catalog>> = Lazy::new(|| {
+    HashMap::from([
+        (
+            Locale::ZhCn,
+            parse_catalog(
+                Locale::ZhCn,
+                include_str!("../../resources/i18n/zh-CN.json"),
+            ),
+        ),
+        (
+            Locale::EnUs,
+            parse_catalog(
+                Locale::EnUs,
+                include_str!("../../resources/i18n/en-US.json"),
+            ),
+        ),
+    ])
+});
+
+/// 回退语言
+const FALLBACK_LOCALE: Locale = Locale::EnUs;
+
+static CURRENT_LOCALE: RwLock<Locale> = RwLock::new(Locale::ZhCn);
+
+/// 切换后端文案语言（立即生效）
+pub fn set_locale(locale: Locale) {
+    if let Ok(mut current) = CURRENT_LOCALE.write() {
+        *current = locale;
+    }
+}
+
+/// 当前后端文案语言
+pub fn current_locale() -> Locale {
+    CURRENT_LOCALE
+        .read()
+        .map(|locale| *locale)
+        .unwrap_or_default()
+}
+
+/// 按当前语言查找文案
+pub fn tr(key: &str) -> String {
+    translate(current_locale(), key, &[])
+}
+
+/// 按当前语言查找文案并替换 `{name}` 占位符
+pub fn tr_with(key: &str, args: &[(&str, &str)]) -> String {
+    translate(current_locale(), key, args)
+}
+
+fn translate(locale: Locale, key: &str, args: &[(&str, &str)]) -> String {
+    render(lookup(&CATALOGS, locale, key), args)
+}
+
+/// 依次查找当前语言、回退语言，均未找到时返回 key
+fn lookup<'a>(catalogs: &'a HashMap<Locale, Catalog>, locale: Locale, key: &'a str) -> &'a str {
+    [locale, FALLBACK_LOCALE]
+        .iter()
+        .find_map(|locale| catalogs.get(locale)?.get(key))
+        .map(String::as_str)
+        .unwrap_or(key)
+}
+
+fn render(template: &str, args: &[(&str, &str)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// 带文案 key 的错误（显示时按当前语言渲染）
+///
+/// `code` 取 `CommandError` 的错误码（如 `CONFLICT`），用于命令边界推断错误类别。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizedError {
+    pub code: &'static str,
+    pub key: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl LocalizedError {
+    pub fn new(code: &'static str, key: &'static str) -> Self {
+        Self {
+            code,
+            key,
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.args.push((name, value.into()));
+        self
+    }
+}
+
+impl fmt::Display for LocalizedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let args: Vec<(&str, &str)> = self
+            .args
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect();
+        f.write_str(&tr_with(self.key, &args))
+    }
+}
+
+impl std::error::Error for LocalizedError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogs_share_keys() {
+        let zh = &CATALOGS[&Locale::ZhCn];
+        let en = &CATALOGS[&Locale::EnUs];
+        let missing: Vec<&String> = zh.keys().filter(|key| !en.contains_key(*key)).collect();
+        assert!(missing.is_empty(), "en-US 缺少: {missing:?}");
+    }
+
+    #[test]
+    fn test_translate_with_args() {
+        assert_eq!(
+            translate(Locale::EnUs, "provider.not_found", &[("id", "p1")]),
+            "Provider not found: p1"
+        );
+        assert_eq!(
+            translate(Locale::ZhCn, "provider.not_found", &[("id", "p1")]),
+            "供应商不存在: p1"
+        );
+    }
+
+    #[test]
+    fn test_missing_translation_falls_back_to_en_us() {
+        let catalogs = HashMap::from([
+            (Locale::ZhCn, Catalog::new()),
+            (
+                Locale::EnUs,
+                Catalog::from([("only.en".to_string(), "English".to_string())]),
+            ),
+        ]);
+        assert_eq!(lookup(&catalogs, Locale::ZhCn, "only.en"), "English");
+        assert_eq!(lookup(&catalogs, Locale::ZhCn, "missing"), "missing");
+    }
+
+    #[test]
+    fn test_unknown_key_falls_back_to_key() {
+        assert_eq!(
+            translate(Locale::EnUs, "尚未迁移的文案", &[]),
+            "尚未迁移的文案"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! 日志查看
+//!
+//! 读取按天滚动的日志文件尾部，供前端在应用内查看日志（webview 无需直接访问文件系统）。
+//! 同时兼容文本格式与 JSON 格式的日志行。
+
+use crate::models::config::LogLevel;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 滚动日志文件名前缀（tracing_appender::rolling::daily）
+pub const LOG_FILE_PREFIX: &str = "duckcoding.";
+
+/// 单次最多返回的行数
+pub const MAX_TAIL_LINES: usize = 5000;
+
+/// 一行日志
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogLine {
+    /// 解析出的日志级别（无法识别时为 None，如多行消息的续行）
+    pub level: Option<LogLevel>,
+    pub text: String,
+}
+
+/// 列出日志目录中的滚动日志文件（按日期从旧到新）
+pub fn log_files(log_dir: &Path) -> Vec<PathBuf> {
+    let Ok(dir) = fs::read_dir(log_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(LOG_FILE_PREFIX)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// 读取最近 `lines` 行日志（按时间顺序），`min_level` 为 Some 时只保留该级别及以上
+///
+/// 无法识别级别的行在指定筛选级别时会被丢弃
+pub fn read_log_tail(
+    log_dir: &Path,
+    lines: usize,
+    min_level: Option<LogLevel>,
+) -> anyhow::Result<Vec<LogLine>> {
+    let limit = lines.min(MAX_TAIL_LINES);
+    let mut tail = Vec::with_capacity(limit);
+
+    // 从最新的文件往前读，凑够行数即停止
+    for file in log_files(log_dir).iter().rev() {
+        if tail.len() >= limit {
+            break;
+        }
+        let bytes = fs::read(file)
+            .map_err(|e| anyhow::anyhow!("读取日志文件失败 {}: {}", file.display(), e))?;
+        // 日志中可能混有非 UTF-8 内容，逐行容错
+        let content = String::from_utf8_lossy(&bytes);
+        for text in content.lines().rev() {
+            if tail.len() >= limit {
+                break;
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+            let level = parse_level(text);
+            let keep = match (min_level, level) {
+                (None, _) => true,
+                (Some(min), Some(level)) => level.severity() >= min.severity(),
+                (Some(_), None) => false,
+            };
+            if keep {
+                tail.push(LogLine {
+                    level,
+                    text: text.to_string(),
+                });
+            }
+        }
+    }
+
+    tail.reverse();
+    Ok(tail)
+}
+
+/// 解析日志行的级别（文本格式：`<时间>  INFO ...`；JSON 格式：`"level":"INFO"`）
+fn parse_level(line: &str) -> Option<LogLevel> {
+    let token = if line.trim_start().starts_with('{') {
+        let rest = &line[line.find("\"level\":\"")? + "\"level\":\"".len()..];
+        &rest[..rest.find('"')?]
+    } else {
+        line.split_whitespace().nth(1)?
+    };
+
+    match token {
+        "TRACE" => Some(LogLevel::Trace),
+        "DEBUG" => Some(LogLevel::Debug),
+        "INFO" => Some(LogLevel::Info),
+        "WARN" => Some(LogLevel::Warn),
+        "ERROR" => Some(LogLevel::Error),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(
+            parse_level("2024-01-01T00:00:00.000Z  WARN duckcoding: 磁盘空间不足"),
+            Some(LogLevel::Warn)
+        );
+        assert_eq!(
+            parse_level(r#"{"timestamp":"2024","level":"ERROR","fields":{}}"#),
+            Some(LogLevel::Error)
+        );
+        assert_eq!(parse_level("    at stack frame"), None);
+    }
+
+    #[test]
+    fn test_tail_spans_files_and_filters_level() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("duckcoding.2024-01-01"),
+            "t1  INFO old info\nt2 ERROR old error\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("duckcoding.2024-01-02"),
+            "t3 DEBUG new debug\nt4  WARN new warn\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("other.log"), "t5 ERROR ignored\n").unwrap();
+
+        let all = read_log_tail(dir.path(), 3, None).unwrap();
+        let texts: Vec<&str> = all.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "t2 ERROR old error",
+                "t3 DEBUG new debug",
+                "t4  WARN new warn"
+            ]
+        );
+
+        let warn = read_log_tail(dir.path(), 10, Some(LogLevel::Warn)).unwrap();
+        let texts: Vec<&str> = warn.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec!["t2 ERROR old error", "t4  WARN new warn"]);
+    }
+
+    #[test]
+    fn test_missing_dir_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let tail = read_log_tail(&dir.path().join("missing"), 10, None).unwrap();
+        assert!(tail.is_empty());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::models::config::{LogConfig, LogFormat, LogLevel, LogOutput};
-use std::sync::OnceLock;
+use std::sync::{OnceLock, RwLock};
 use tracing_appender::{non_blocking, rolling};
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan},
@@ -12,12 +12,18 @@ use tracing_subscriber::{
 /// 全局日志级别 reload handle
 static LOG_LEVEL_HANDLE: OnceLock<Handle<EnvFilter, Registry>> = OnceLock::new();
 
+/// 当前生效的日志级别（未初始化时为 None）
+static CURRENT_LOG_LEVEL: RwLock<Option<LogLevel>> = RwLock::new(None);
+
+/// 默认日志目录（配置目录下的 `logs/app`）
+const DEFAULT_LOG_SUBDIR: [&str; 2] = ["logs", "app"];
+
 /// 初始化日志系统
 ///
 /// 支持基于配置的日志输出，包括：
 /// - 日志级别（trace/debug/info/warn/error）
 /// - 输出格式（JSON/纯文本）
-/// - 输出目标（控制台/文件/both）
+/// - 输出目标（stderr/文件/both）
 /// - 文件路径（用于文件输出）
 ///
 /// # 热重载支持
@@ -41,6 +47,7 @@ pub fn init_logger(config: &LogConfig) -> anyhow::Result<()> {
     if LOG_LEVEL_HANDLE.set(reload_handle).is_err() {
         anyhow::bail!("日志系统已初始化，不能重复初始化");
     }
+    set_current_log_level(config.level);
 
     // 3. 根据配置添加输出层并初始化
     match (&config.output, &config.format) {
@@ -118,7 +125,7 @@ where
     S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 {
     fmt::layer()
-        .with_writer(std::io::stdout)
+        .with_writer(std::io::stderr)
         .with_target(cfg!(debug_assertions))
         .with_thread_ids(false)
         .with_ansi(true)
@@ -137,7 +144,7 @@ where
 {
     fmt::layer()
         .json()
-        .with_writer(std::io::stdout)
+        .with_writer(std::io::stderr)
         .with_target(cfg!(debug_assertions))
         .with_thread_ids(false)
         .with_ansi(true)
@@ -189,16 +196,16 @@ where
         .boxed())
 }
 
-/// 获取日志目录
-fn get_log_dir(file_path: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
+/// 获取日志目录（`file_path` 为空时使用配置目录下的 logs/app）
+pub fn get_log_dir(file_path: Option<&str>) -> anyhow::Result<std::path::PathBuf> {
     match file_path {
         Some(path) => Ok(std::path::PathBuf::from(path)),
         None => {
-            // 使用用户主目录下的 .duckcoding/logs
-            let app_dir = dirs::home_dir()
-                .ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?
-                .join(".duckcoding")
-                .join("logs");
+            // 使用配置目录（默认 ~/.duckcoding）下的 logs/app
+            let app_dir = DEFAULT_LOG_SUBDIR.iter().fold(
+                crate::utils::config::config_dir().map_err(|e| anyhow::anyhow!(e))?,
+                |dir, part| dir.join(part),
+            );
 
             std::fs::create_dir_all(&app_dir)?;
             Ok(app_dir)
@@ -230,11 +237,27 @@ pub fn update_log_level(new_level: LogLevel) -> anyhow::Result<()> {
     handle
         .reload(new_filter)
         .map_err(|e| anyhow::anyhow!("重载日志级别失败: {}", e))?;
+    set_current_log_level(new_level);
 
     tracing::info!(new_level = new_level.as_str(), "日志级别已动态更新");
     Ok(())
 }
 
+/// 当前生效的日志级别（日志系统未初始化时返回默认级别）
+pub fn current_log_level() -> LogLevel {
+    CURRENT_LOG_LEVEL
+        .read()
+        .ok()
+        .and_then(|level| *level)
+        .unwrap_or_default()
+}
+
+fn set_current_log_level(level: LogLevel) {
+    if let Ok(mut current) = CURRENT_LOG_LEVEL.write() {
+        *current = Some(level);
+    }
+}
+
 /// 运行时调整日志级别（兼容旧接口）
 ///
 /// # 弃用提示
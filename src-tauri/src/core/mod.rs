@@ -1,17 +1,23 @@
+pub mod command_error;
 pub mod error;
 pub mod http;
+pub mod i18n;
 pub mod log_utils;
+pub mod log_viewer;
 pub mod logger;
 
 #[cfg(test)]
 mod error_test;
 
 // 导出核心类型
+pub use command_error::{CommandContext, CommandError, CommandResult};
 pub use error::{AppError, AppResult, ErrorContext};
 pub use http::{build_http_client, get_global_client};
+pub use i18n::{tr, tr_with, LocalizedError};
 pub use log_utils::{LogContext, Timer};
+pub use log_viewer::{read_log_tail, LogLine};
 #[allow(deprecated)]
-pub use logger::{init_logger, set_log_level, update_log_level};
+pub use logger::{current_log_level, init_logger, set_log_level, update_log_level};
 
 // 从 models 重新导出日志配置类型
 pub use crate::models::config::{LogConfig, LogFormat, LogLevel, LogOutput};
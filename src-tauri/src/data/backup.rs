@@ -0,0 +1,302 @@
+//! 写入前自动备份与轮转
+//!
+//! 覆盖配置文件前把旧内容快照到备份目录（`<name>.<时间戳>.bak`），
+//! 只保留最近 `keep_n` 份，然后通过临时文件 + rename 原子写入新内容。
+
+use crate::data::durable::{self, Durability};
+use crate::data::path_policy;
+use crate::data::{DataError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 默认保留的备份份数
+pub const DEFAULT_BACKUP_KEEP: usize = 5;
+
+/// 备份目录名（位于被备份文件的同级目录）
+pub const BACKUP_DIR_NAME: &str = "backups";
+
+/// 备份文件名中的时间戳格式（字典序即时间顺序）
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+
+/// 备份策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupPolicy {
+    /// 备份目录
+    pub dir: PathBuf,
+    /// 保留份数（0 表示不备份）
+    pub keep_n: usize,
+}
+
+impl BackupPolicy {
+    pub fn new(dir: impl Into<PathBuf>, keep_n: usize) -> Self {
+        Self {
+            dir: dir.into(),
+            keep_n,
+        }
+    }
+
+    /// 默认策略：`<文件所在目录>/backups`，保留 5 份
+    pub fn beside(path: &Path) -> Self {
+        let parent = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::new(parent.join(BACKUP_DIR_NAME), DEFAULT_BACKUP_KEEP)
+    }
+}
+
+/// 备份条目
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BackupEntry {
+    /// 备份文件名（用于恢复）
+    pub name: String,
+    pub path: PathBuf,
+    /// 文件大小（字节）
+    pub size: u64,
+}
+
+/// 将 `path` 的当前内容快照到备份目录并清理超出的旧备份
+///
+/// 文件不存在或 `keep_n` 为 0 时不备份，返回 `None`
+pub fn snapshot(path: &Path, policy: &BackupPolicy) -> Result<Option<PathBuf>> {
+    if policy.keep_n == 0 || !path.is_file() {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(&policy.dir).map_err(|e| DataError::io(&policy.dir, e))?;
+    let stamp = chrono::Utc::now().format(TIMESTAMP_FORMAT).to_string();
+    let file_name = file_name_of(path)?;
+
+    // 同一毫秒内多次写入时追加序号，保证名称唯一且有序
+    let mut backup = policy.dir.join(format!("{file_name}.{stamp}.bak"));
+    let mut seq = 1;
+    while backup.exists() {
+        backup = policy.dir.join(format!("{file_name}.{stamp}_{seq:03}.bak"));
+        seq += 1;
+    }
+
+    fs::copy(path, &backup).map_err(|e| DataError::io(&backup, e))?;
+    prune(path, policy)?;
+    Ok(Some(backup))
+}
+
+/// 列出 `path` 的备份（最新的在前）
+pub fn list(path: &Path, policy: &BackupPolicy) -> Result<Vec<BackupEntry>> {
+    let file_name = file_name_of(path)?;
+    let prefix = format!("{file_name}.");
+
+    let entries = match fs::read_dir(&policy.dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(DataError::io(&policy.dir, e)),
+    };
+
+    let mut backups: Vec<BackupEntry> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let stamp = name.strip_prefix(&prefix)?.strip_suffix(".bak")?;
+            // 排除 `config.json.v1.bak` 一类以相同前缀开头的其他文件
+            if !stamp.starts_with(|c: char| c.is_ascii_digit()) {
+                return None;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(BackupEntry {
+                name,
+                path: entry.path(),
+                size,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// 只保留最新的 `keep_n` 份备份
+pub fn prune(path: &Path, policy: &BackupPolicy) -> Result<()> {
+    for stale in list(path, policy)?.into_iter().skip(policy.keep_n) {
+        fs::remove_file(&stale.path).map_err(|e| DataError::io(&stale.path, e))?;
+    }
+    Ok(())
+}
+
+/// 查找指定名称的备份
+pub fn find(path: &Path, policy: &BackupPolicy, backup_name: &str) -> Result<BackupEntry> {
+    // 备份名来自前端，只允许纯文件名
+    path_policy::check_file_name(backup_name)?;
+    list(path, policy)?
+        .into_iter()
+        .find(|entry| entry.name == backup_name)
+        .ok_or_else(|| DataError::NotFound(format!("备份 {backup_name}")))
+}
+
+/// 原子写入：先写同目录临时文件，再 rename 覆盖目标
+///
+/// 临时文件总是建在目标文件（符号链接解析后）所在的目录：rename 不能跨文件系统，
+/// 目标位于网络共享或另一块磁盘时，放在系统临时目录或链接所在目录都会失败（EXDEV）。
+/// 目标是符号链接时写入链接指向的文件，链接本身保留。
+/// `Durability::Durable` 时在 rename 前同步临时文件、rename 后同步所在目录。
+pub fn atomic_write(path: &Path, content: &[u8], durability: Durability) -> Result<()> {
+    let target = resolve_write_target(path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| DataError::io(parent, e))?;
+    }
+
+    let tmp = temp_path_for(&target)?;
+    durable::write_temp(&tmp, content, durability)?;
+    set_permissions(&tmp)?;
+    fs::rename(&tmp, &target).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        DataError::io(&target, e)
+    })?;
+
+    if durability.should_sync() {
+        if let Some(parent) = target.parent() {
+            durable::sync_dir(parent)?;
+        }
+    }
+    Ok(())
+}
+
+/// 实际写入的文件：符号链接解析为其指向的路径（链接失效时仍写入链接位置）
+fn resolve_write_target(path: &Path) -> PathBuf {
+    let is_symlink = fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink());
+    if !is_symlink {
+        return path.to_path_buf();
+    }
+    match fs::read_link(path) {
+        Ok(link) if link.is_absolute() => link,
+        Ok(link) => path.parent().map(|dir| dir.join(&link)).unwrap_or(link),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// 与目标同目录的临时文件名（带进程号与序号，多个进程同时写同一共享目录时互不覆盖）
+fn temp_path_for(target: &Path) -> Result<PathBuf> {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let file_name = file_name_of(target)?;
+    let seq = SEQ.fetch_add(1, Ordering::Relaxed);
+    Ok(target.with_file_name(format!(".{file_name}.{}.{seq}.tmp", std::process::id())))
+}
+
+fn file_name_of(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| DataError::InvalidKey(format!("无效的文件路径: {}", path.display())))
+}
+
+#[cfg(unix)]
+fn set_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600)).map_err(|e| DataError::io(path, e))
+}
+
+#[cfg(not(unix))]
+fn set_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_skips_missing_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let policy = BackupPolicy::beside(&path);
+        assert_eq!(snapshot(&path, &policy).unwrap(), None);
+        assert!(list(&path, &policy).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("providers.json");
+        let policy = BackupPolicy::new(temp_dir.path().join("backups"), 3);
+
+        for i in 0..5 {
+            fs::write(&path, format!("{i}")).unwrap();
+            snapshot(&path, &policy).unwrap();
+        }
+
+        let backups = list(&path, &policy).unwrap();
+        assert_eq!(backups.len(), 3);
+        let contents: Vec<String> = backups
+            .iter()
+            .map(|b| fs::read_to_string(&b.path).unwrap())
+            .collect();
+        assert_eq!(contents, vec!["4", "3", "2"]);
+    }
+
+    #[test]
+    fn test_list_ignores_other_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dashboard.json");
+        let policy = BackupPolicy::beside(&path);
+        fs::create_dir_all(&policy.dir).unwrap();
+        fs::write(policy.dir.join("dashboard.json.v1.bak"), "old").unwrap();
+        fs::write(
+            policy.dir.join("providers.json.20260101T000000000.bak"),
+            "p",
+        )
+        .unwrap();
+        fs::write(
+            policy.dir.join("dashboard.json.20260101T000000000.bak"),
+            "d",
+        )
+        .unwrap();
+
+        let backups = list(&path, &policy).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].name, "dashboard.json.20260101T000000000.bak");
+    }
+
+    #[test]
+    fn test_atomic_write_replaces_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("config.json");
+        atomic_write(&path, b"first", Durability::Durable).unwrap();
+        atomic_write(&path, b"second", Durability::Relaxed).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        assert!(!temp_dir.path().join("nested/.config.json.tmp").exists());
+        let names: Vec<_> = fs::read_dir(temp_dir.path().join("nested"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("config.json")]);
+    }
+
+    #[test]
+    fn test_temp_file_created_beside_target() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("share").join("settings.json");
+        let tmp = temp_path_for(&target).unwrap();
+        assert_eq!(tmp.parent(), target.parent());
+        assert_ne!(tmp, temp_path_for(&target).unwrap());
+    }
+
+    /// 链接指向另一个目录（如网络共享）时，临时文件建在链接目标所在目录，rename 不跨目录
+    #[cfg(unix)]
+    #[test]
+    fn test_atomic_write_through_symlink_keeps_link() {
+        let temp_dir = TempDir::new().unwrap();
+        let share = temp_dir.path().join("share");
+        let home = temp_dir.path().join("home");
+        fs::create_dir_all(&share).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        let real = share.join("settings.json");
+        fs::write(&real, "old").unwrap();
+        let link = home.join("settings.json");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        atomic_write(&link, b"new", Durability::Durable).unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+        assert_eq!(fs::read_dir(&share).unwrap().count(), 1);
+        assert_eq!(fs::read_dir(&home).unwrap().count(), 1);
+    }
+}
@@ -1,7 +1,8 @@
-//! JSON 配置缓存实现
+//! JSON / TOML 配置缓存实现
 //!
-//! 提供基于文件路径的 JSON 配置缓存，支持：
-//! - 文件校验和验证（SHA-256）
+//! 提供基于文件路径的配置缓存（`JsonConfigCache`、`TomlDocumentCache`），支持：
+//! - 文件状态戳（修改时间 + 大小）检测外部修改，无需每次读取文件
+//! - 修改时间过近（时间戳精度内可能被改写）时回退到校验和验证（SHA-256）
 //! - 自动失效过期缓存
 //! - 线程安全访问
 //!
@@ -36,17 +37,62 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+
+/// 修改时间距今小于该值时，状态戳不足以判断文件未变化，需要比对校验和
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// 文件状态戳（修改时间 + 大小）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    /// 读取文件当前状态戳（文件不存在时返回 None）
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+
+    /// 修改时间是否过近（同一时间戳精度内可能再次被写入而不改变状态戳）
+    fn is_racy(&self) -> bool {
+        match self.modified {
+            Some(modified) => SystemTime::now()
+                .duration_since(modified)
+                .map(|age| age < RACY_WINDOW)
+                .unwrap_or(true),
+            None => true,
+        }
+    }
+}
+
+/// 缓存项对应的文件信息
+#[derive(Debug, Clone)]
+struct FileMeta {
+    checksum: String,
+    stamp: Option<FileStamp>,
+}
 
 /// JSON 配置缓存
+pub type JsonConfigCache = FileCache<serde_json::Value>;
+
+/// TOML 可编辑文档缓存
+pub type TomlDocumentCache = FileCache<toml_edit::DocumentMut>;
+
+/// 按文件路径缓存解析结果
 ///
-/// 使用 LRU 缓存存储 JSON 配置，并通过 SHA-256 校验和验证文件是否变更。
+/// 使用 LRU 缓存存储解析后的值，并通过文件状态戳（必要时加上 SHA-256 校验和）验证文件是否变更。
 #[derive(Debug, Clone)]
-pub struct JsonConfigCache {
-    /// LRU 缓存，键为文件路径，值为 JSON Value
-    cache: Arc<RwLock<LruCache<PathBuf, serde_json::Value>>>,
-    /// 文件校验和映射，用于检测文件变更
-    file_checksums: Arc<RwLock<HashMap<PathBuf, String>>>,
+pub struct FileCache<V> {
+    /// LRU 缓存，键为文件路径，值为解析结果
+    cache: Arc<RwLock<LruCache<PathBuf, V>>>,
+    /// 读取时的文件信息，用于检测文件变更
+    file_meta: Arc<RwLock<HashMap<PathBuf, FileMeta>>>,
     /// 缓存容量
     capacity: usize,
     /// 缓存 TTL（存储用于查询）
@@ -54,8 +100,8 @@ pub struct JsonConfigCache {
     ttl: Duration,
 }
 
-impl JsonConfigCache {
-    /// 创建新的 JSON 配置缓存
+impl<V: Clone> FileCache<V> {
+    /// 创建新的缓存
     ///
     /// # 参数
     ///
@@ -71,7 +117,7 @@ impl JsonConfigCache {
     pub fn new(capacity: usize, ttl: Duration) -> Self {
         Self {
             cache: Arc::new(RwLock::new(LruCache::new(capacity, ttl))),
-            file_checksums: Arc::new(RwLock::new(HashMap::new())),
+            file_meta: Arc::new(RwLock::new(HashMap::new())),
             capacity,
             ttl,
         }
@@ -79,37 +125,42 @@ impl JsonConfigCache {
 
     /// 获取缓存的配置
     ///
-    /// 自动校验文件是否变更，如果文件内容变更则使缓存失效。
+    /// 自动校验文件是否变更：状态戳变化或文件被删除时使缓存失效；
+    /// 状态戳相同但修改时间过近时再比对校验和。
     ///
     /// # 返回
     ///
     /// - `Some(Value)`: 缓存命中且未过期
     /// - `None`: 缓存未命中、已过期或文件已变更
-    pub fn get(&self, path: &Path) -> Option<serde_json::Value> {
+    pub fn get(&self, path: &Path) -> Option<V> {
         // 尝试从缓存获取
         let cached_value = {
             let mut cache = self.cache.write().ok()?;
             cache.get(&path.to_path_buf()).cloned()
         }?;
 
-        // 检查文件是否变更
-        if let Ok(current_checksum) = compute_checksum(path) {
-            let checksums = self.file_checksums.read().ok()?;
-            if let Some(stored_checksum) = checksums.get(&path.to_path_buf()) {
-                if stored_checksum != &current_checksum {
-                    // 文件已变更，使缓存失效
-                    drop(checksums);
+        // 没有文件信息记录，认为缓存无效
+        let meta = self.file_meta.read().ok()?.get(path).cloned()?;
+
+        // 文件已删除或状态戳变化，使缓存失效
+        let Some(current) = FileStamp::of(path) else {
+            self.invalidate(path);
+            return None;
+        };
+        if meta.stamp != Some(current) {
+            self.invalidate(path);
+            return None;
+        }
+
+        // 状态戳相同但修改时间过近，需确认内容未变
+        if current.is_racy() {
+            match compute_checksum(path) {
+                Ok(checksum) if checksum == meta.checksum => {}
+                _ => {
                     self.invalidate(path);
                     return None;
                 }
-            } else {
-                // 没有校验和记录，认为缓存无效
-                return None;
             }
-        } else {
-            // 无法计算校验和（文件可能已删除），使缓存失效
-            self.invalidate(path);
-            return None;
         }
 
         Some(cached_value)
@@ -120,23 +171,37 @@ impl JsonConfigCache {
     /// # 参数
     ///
     /// - `path`: 文件路径
-    /// - `value`: JSON 配置值
+    /// - `value`: 解析后的值
     /// - `checksum`: 文件校验和
-    pub fn insert(&self, path: PathBuf, value: serde_json::Value, checksum: String) {
+    pub fn insert(&self, path: PathBuf, value: V, checksum: String) {
+        let stamp = FileStamp::of(&path);
+        self.insert_stamped(path, value, checksum, stamp);
+    }
+
+    /// 插入缓存，使用读取文件前记录的状态戳
+    ///
+    /// 读取与插入之间文件被修改时，记录的旧状态戳会让下次 `get` 重新读取。
+    pub fn insert_stamped(
+        &self,
+        path: PathBuf,
+        value: V,
+        checksum: String,
+        stamp: Option<FileStamp>,
+    ) {
         // 插入缓存
         if let Ok(mut cache) = self.cache.write() {
             cache.insert(path.clone(), value);
         }
 
-        // 记录校验和
-        if let Ok(mut checksums) = self.file_checksums.write() {
-            checksums.insert(path, checksum);
+        // 记录文件信息
+        if let Ok(mut file_meta) = self.file_meta.write() {
+            file_meta.insert(path, FileMeta { checksum, stamp });
         }
     }
 
     /// 使指定路径的缓存失效
     ///
-    /// 删除缓存值和校验和记录。
+    /// 删除缓存值和文件信息记录。
     pub fn invalidate(&self, path: &Path) {
         let path_buf = path.to_path_buf();
 
@@ -145,9 +210,9 @@ impl JsonConfigCache {
             cache.remove(&path_buf);
         }
 
-        // 删除校验和
-        if let Ok(mut checksums) = self.file_checksums.write() {
-            checksums.remove(&path_buf);
+        // 删除文件信息
+        if let Ok(mut file_meta) = self.file_meta.write() {
+            file_meta.remove(&path_buf);
         }
     }
 
@@ -157,8 +222,8 @@ impl JsonConfigCache {
             cache.clear();
         }
 
-        if let Ok(mut checksums) = self.file_checksums.write() {
-            checksums.clear();
+        if let Ok(mut file_meta) = self.file_meta.write() {
+            file_meta.clear();
         }
     }
 
@@ -204,9 +269,14 @@ impl JsonConfigCache {
 /// - `Err(std::io::Error)`: 文件读取失败
 fn compute_checksum(path: &Path) -> std::io::Result<String> {
     let content = fs::read(path)?;
+    Ok(checksum_of(&content))
+}
+
+/// 计算内容的 SHA-256 校验和（十六进制）
+pub fn checksum_of(content: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(&content);
-    Ok(format!("{:x}", hasher.finalize()))
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
 }
 
 #[cfg(test)]
@@ -271,6 +341,47 @@ mod tests {
         assert!(cache.get(&file_path).is_none());
     }
 
+    #[test]
+    fn test_same_size_rewrite_detected() {
+        let cache = JsonConfigCache::new(10, Duration::from_secs(60));
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("dashboard.json");
+
+        let content1 = serde_json::json!({"v": 1});
+        fs::write(&file_path, content1.to_string()).unwrap();
+        let stamp = FileStamp::of(&file_path);
+        cache.insert_stamped(
+            file_path.clone(),
+            content1,
+            compute_checksum(&file_path).unwrap(),
+            stamp,
+        );
+
+        // 大小不变的外部覆盖（如恢复备份），状态戳可能相同，依赖校验和兜底
+        fs::write(&file_path, serde_json::json!({"v": 2}).to_string()).unwrap();
+        assert!(cache.get(&file_path).is_none());
+    }
+
+    #[test]
+    fn test_stale_stamp_not_trusted() {
+        let cache = JsonConfigCache::new(10, Duration::from_secs(60));
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("config.json");
+
+        fs::write(&file_path, "{}").unwrap();
+        // 读取前记录的状态戳与插入时的文件不一致
+        let stamp_before = FileStamp::of(&file_path);
+        fs::write(&file_path, r#"{"changed": true}"#).unwrap();
+        cache.insert_stamped(
+            file_path.clone(),
+            serde_json::json!({}),
+            checksum_of(b"{}"),
+            stamp_before,
+        );
+
+        assert!(cache.get(&file_path).is_none());
+    }
+
     #[test]
     fn test_invalidate() {
         let cache = JsonConfigCache::new(10, Duration::from_secs(60));
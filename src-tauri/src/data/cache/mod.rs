@@ -9,6 +9,6 @@ pub mod json_cache;
 pub mod lru;
 pub mod sql_cache;
 
-pub use json_cache::JsonConfigCache;
+pub use json_cache::{checksum_of, FileCache, FileStamp, JsonConfigCache, TomlDocumentCache};
 pub use lru::LruCache;
 pub use sql_cache::{extract_tables, QueryKey, SqlQueryCache};
@@ -51,6 +51,14 @@ pub enum DataError {
     /// 无效的键路径
     #[error("无效的键路径: {0}")]
     InvalidKey(String),
+
+    /// 文件被其他进程锁定（等待超时）
+    #[error("文件被其他进程锁定: {}{}", path.display(), holder.map(|pid| format!("（PID {pid}）")).unwrap_or_default())]
+    Locked { path: PathBuf, holder: Option<u32> },
+
+    /// 路径不在允许写入的目录内（或包含穿越、UNC 等不安全形式）
+    #[error("路径不被允许: {}: {reason}", path.display())]
+    PolicyViolation { path: PathBuf, reason: String },
 }
 
 /// 便于与现有代码集成的类型别名
@@ -110,6 +118,28 @@ mod tests {
         assert_eq!(err.to_string(), "缓存校验失败: checksum mismatch");
     }
 
+    #[test]
+    fn test_locked_error() {
+        let err = DataError::Locked {
+            path: PathBuf::from("/tmp/dashboard.json"),
+            holder: Some(42),
+        };
+        assert!(err.to_string().contains("文件被其他进程锁定"));
+        assert!(err.to_string().contains("PID 42"));
+    }
+
+    #[test]
+    fn test_policy_violation_error() {
+        let err = DataError::PolicyViolation {
+            path: PathBuf::from("/etc/passwd"),
+            reason: "不在允许的目录内".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "路径不被允许: /etc/passwd: 不在允许的目录内"
+        );
+    }
+
     #[test]
     fn test_invalid_key_error() {
         let err = DataError::InvalidKey("".to_string());
@@ -0,0 +1,226 @@
+//! 跨进程文件锁
+//!
+//! 应用主进程、第二个实例以及 CLI 可能同时对同一个配置文件执行读-改-写，
+//! 因此写入前在同目录的 `<文件名>.lock` 上获取排他锁。
+//!
+//! - 等待有上限（默认 5 秒），超时返回 `DataError::Locked`
+//! - 锁由操作系统在持有者进程退出时释放，因此锁文件从不删除：删除正被持有的锁文件会让
+//!   其他进程锁住新建的文件，两者同时「持有」锁
+//! - 锁文件记录持有者 PID 与获取时间，仅用于超时时的提示
+
+use crate::data::network_fs;
+use crate::data::{DataError, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 获取文件锁的默认等待时间
+pub const LOCK_WAIT: Duration = Duration::from_secs(5);
+
+/// 配置目录位于网络文件系统时的等待时间（共享上加锁与读写都可能慢数秒）
+pub const NETWORK_LOCK_WAIT: Duration = Duration::from_secs(20);
+
+/// 重试间隔
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// 当前环境的锁等待时间（见 `network_fs`）
+pub fn lock_wait() -> Duration {
+    if network_fs::network_backed() {
+        NETWORK_LOCK_WAIT
+    } else {
+        LOCK_WAIT
+    }
+}
+
+/// 已获取的文件锁，drop 时释放
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    lock_path: PathBuf,
+}
+
+/// 锁文件中记录的持有者信息（仅用于诊断）
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LockHolder {
+    pid: u32,
+    acquired_at: i64,
+}
+
+impl FileLock {
+    /// 获取 `target` 的排他锁，最多等待 `wait`
+    pub fn acquire(target: &Path, wait: Duration) -> Result<Self> {
+        let lock_path = lock_path(target);
+        if let Some(parent) = lock_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| DataError::io(parent, e))?;
+        }
+
+        let deadline = Instant::now() + wait;
+        let file = open_lock_file(&lock_path)?;
+        loop {
+            if file.try_lock_exclusive().is_ok() {
+                let mut lock = Self { file, lock_path };
+                lock.record_holder();
+                return Ok(lock);
+            }
+
+            if Instant::now() >= deadline {
+                let holder = read_holder(&lock_path);
+                if let Some(holder) = &holder {
+                    tracing::warn!(
+                        lock = ?lock_path,
+                        pid = holder.pid,
+                        acquired_at = holder.acquired_at,
+                        "等待文件锁超时"
+                    );
+                }
+                return Err(DataError::Locked {
+                    path: target.to_path_buf(),
+                    holder: holder.map(|h| h.pid),
+                });
+            }
+            std::thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+    }
+
+    /// 锁文件路径
+    pub fn lock_path(&self) -> &Path {
+        &self.lock_path
+    }
+
+    /// 写入当前进程 PID 与获取时间（失败不影响加锁本身）
+    fn record_holder(&mut self) {
+        let content = format!(
+            "{}\n{}\n",
+            std::process::id(),
+            chrono::Utc::now().timestamp()
+        );
+        let result = self
+            .file
+            .set_len(0)
+            .and_then(|_| self.file.seek(SeekFrom::Start(0)))
+            .and_then(|_| self.file.write_all(content.as_bytes()))
+            .and_then(|_| self.file.flush());
+        if let Err(e) = result {
+            tracing::debug!(lock = ?self.lock_path, error = %e, "写入锁持有者信息失败");
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+impl LockHolder {
+    fn parse(content: &str) -> Option<Self> {
+        let mut lines = content.lines();
+        let pid = lines.next()?.trim().parse().ok()?;
+        let acquired_at = lines.next()?.trim().parse().ok()?;
+        Some(Self { pid, acquired_at })
+    }
+}
+
+/// `config.json` → `config.json.lock`（保留扩展名，`a.json` 与 `a.toml` 不共用锁）
+pub fn lock_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    target.with_file_name(name)
+}
+
+fn open_lock_file(lock_path: &Path) -> Result<File> {
+    // 不截断：其他进程可能正持有该文件的锁
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path)
+        .map_err(|e| DataError::io(lock_path, e))
+}
+
+fn read_holder(lock_path: &Path) -> Option<LockHolder> {
+    let mut content = String::new();
+    File::open(lock_path)
+        .and_then(|mut f| f.read_to_string(&mut content))
+        .ok()?;
+    LockHolder::parse(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_excludes_second_holder() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("config.json");
+
+        let lock = FileLock::acquire(&target, LOCK_WAIT).unwrap();
+        assert_eq!(lock.lock_path(), temp_dir.path().join("config.json.lock"));
+
+        let err = FileLock::acquire(&target, Duration::from_millis(100)).unwrap_err();
+        match err {
+            DataError::Locked { path, holder } => {
+                assert_eq!(path, target);
+                assert_eq!(holder, Some(std::process::id()));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        drop(lock);
+        assert!(FileLock::acquire(&target, Duration::from_millis(100)).is_ok());
+    }
+
+    #[test]
+    fn test_parse_holder() {
+        assert_eq!(
+            LockHolder::parse("123\n1700000000\n"),
+            Some(LockHolder {
+                pid: 123,
+                acquired_at: 1_700_000_000
+            })
+        );
+        assert_eq!(LockHolder::parse(""), None);
+        assert_eq!(LockHolder::parse("abc\n1\n"), None);
+    }
+
+    #[test]
+    fn test_lock_path_keeps_extension() {
+        let dir = Path::new("/data");
+        assert_eq!(lock_path(&dir.join("a.json")), dir.join("a.json.lock"));
+        assert_ne!(
+            lock_path(&dir.join("a.json")),
+            lock_path(&dir.join("a.toml"))
+        );
+    }
+
+    // Windows 上被锁定的文件无法由其他句柄写入
+    #[cfg(unix)]
+    #[test]
+    fn test_leftover_lock_file_reused() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("dashboard.json");
+        let path = lock_path(&target);
+
+        // 其他句柄持有锁时，即使记录的持有者已不存在也不能获取
+        let holder = open_lock_file(&path).unwrap();
+        holder.try_lock_exclusive().unwrap();
+        std::fs::write(&path, format!("{}\n0\n", u32::MAX - 1)).unwrap();
+        assert!(matches!(
+            FileLock::acquire(&target, Duration::from_millis(100)),
+            Err(DataError::Locked { holder: Some(pid), .. }) if pid == u32::MAX - 1
+        ));
+
+        // 持有者退出（句柄关闭）后锁自动释放，遗留的锁文件直接复用
+        drop(holder);
+        let lock = FileLock::acquire(&target, Duration::from_millis(100)).unwrap();
+        assert!(path.exists());
+        assert_eq!(read_holder(&path).unwrap().pid, std::process::id());
+        drop(lock);
+    }
+}
@@ -5,6 +5,8 @@
 //! - SQLite 连接池管理
 //! - 统一缓存配置
 //! - 线程安全设计
+//! - 跨进程文件锁保护的读-改-写
+//! - 写入前自动备份与轮转
 //!
 //! # 使用示例
 //!
@@ -26,9 +28,15 @@
 //! let rows = db.query("SELECT * FROM users", &[])?;
 //! ```
 
+use crate::data::backup::{self, BackupEntry, BackupPolicy};
+use crate::data::durable::{self, Durability};
+use crate::data::lock::{lock_wait, FileLock};
+use crate::data::managers::json::to_canonical_string;
 use crate::data::managers::{EnvManager, JsonManager, SqliteManager, TomlManager};
-use crate::data::Result;
+use crate::data::watch::{self, WatchGuard};
+use crate::data::{DataError, Result};
 use once_cell::sync::Lazy;
+use serde_json::Value;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
@@ -55,6 +63,8 @@ pub struct DataManager {
     sqlite_connections: Arc<RwLock<HashMap<PathBuf, Arc<SqliteManager>>>>,
     /// 缓存配置
     cache_config: CacheConfig,
+    /// 写入的持久化级别
+    durability: Durability,
 }
 
 impl DataManager {
@@ -109,16 +119,38 @@ impl DataManager {
     /// let manager = DataManager::with_config(config);
     /// ```
     pub fn with_config(config: CacheConfig) -> Self {
+        Self::build(config, Durability::default())
+    }
+
+    /// 指定写入的持久化级别（默认 `Durability::Durable`）
+    ///
+    /// 指标快照等高频写入、丢失可接受的文件使用 `Durability::Relaxed` 跳过 fsync。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// let manager = DataManager::new().with_durability(Durability::Relaxed);
+    /// ```
+    pub fn with_durability(self, durability: Durability) -> Self {
+        Self::build(self.cache_config, durability)
+    }
+
+    fn build(config: CacheConfig, durability: Durability) -> Self {
         Self {
-            json_cached: Arc::new(JsonManager::with_cache(
-                config.json_capacity,
-                config.json_ttl,
-            )),
-            json_uncached: Arc::new(JsonManager::without_cache()),
-            toml: Arc::new(TomlManager::new()),
-            env: Arc::new(EnvManager::new()),
+            json_cached: Arc::new(
+                JsonManager::with_cache(config.json_capacity, config.json_ttl)
+                    .with_durability(durability),
+            ),
+            json_uncached: Arc::new(JsonManager::without_cache().with_durability(durability)),
+            // TOML 文档缓存与 JSON 缓存共用容量和 TTL 配置
+            toml: Arc::new(
+                TomlManager::with_cache(config.json_capacity, config.json_ttl)
+                    .with_durability(durability),
+            ),
+            env: Arc::new(EnvManager::new().with_durability(durability)),
             sqlite_connections: Arc::new(RwLock::new(HashMap::new())),
             cache_config: config,
+            durability,
         }
     }
 
@@ -174,6 +206,7 @@ impl DataManager {
     /// - 使用 `toml_edit` 保留注释和格式
     /// - 支持深度合并
     /// - 支持键路径访问
+    /// - 文档缓存（按修改时间和大小校验，写入后失效）
     ///
     /// # 示例
     ///
@@ -259,6 +292,168 @@ impl DataManager {
         Ok(manager)
     }
 
+    /// 获取文件的跨进程排他锁（最多等待 5 秒，配置目录位于网络共享时 20 秒）
+    ///
+    /// 超时返回 `DataError::Locked`；锁在返回值 drop 时释放。
+    pub fn lock_file(&self, path: &Path) -> Result<FileLock> {
+        FileLock::acquire(path, lock_wait())
+    }
+
+    /// 在文件锁保护下执行闭包
+    ///
+    /// 用于调用方自行读取、反序列化和写入的场景（如 DashboardManager）。
+    /// 锁不可重入，闭包内不要再对同一文件加锁。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// manager.with_file_lock(&path, || -> anyhow::Result<()> {
+    ///     let mut store = load()?;
+    ///     store.count += 1;
+    ///     save(&store)
+    /// })?;
+    /// ```
+    pub fn with_file_lock<T, E>(
+        &self,
+        path: &Path,
+        f: impl FnOnce() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E>
+    where
+        E: From<DataError>,
+    {
+        let _lock = self.lock_file(path)?;
+        f()
+    }
+
+    /// 加锁读-改-写 JSON 文件
+    ///
+    /// 始终从磁盘读取最新内容（文件不存在时从空对象开始），闭包修改后写回。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// manager.read_modify_write_json(&path, |value| {
+    ///     value["count"] = serde_json::json!(1);
+    ///     Ok(())
+    /// })?;
+    /// ```
+    pub fn read_modify_write_json<T>(
+        &self,
+        path: &Path,
+        f: impl FnOnce(&mut Value) -> Result<T>,
+    ) -> Result<T> {
+        self.with_file_lock(path, || {
+            let mut value = if path.exists() {
+                self.json_uncached.read(path)?
+            } else {
+                Value::Object(serde_json::Map::new())
+            };
+            let result = f(&mut value)?;
+            self.json_uncached.write(path, &value)?;
+            Ok(result)
+        })
+    }
+
+    /// 加锁读-改-写 TOML 文件（保留注释和格式）
+    ///
+    /// 文件不存在时从空文档开始。
+    pub fn read_modify_write_toml<T>(
+        &self,
+        path: &Path,
+        f: impl FnOnce(&mut toml_edit::DocumentMut) -> Result<T>,
+    ) -> Result<T> {
+        self.with_file_lock(path, || {
+            let mut doc = if path.exists() {
+                self.toml.read_document(path)?
+            } else {
+                toml_edit::DocumentMut::new()
+            };
+            let result = f(&mut doc)?;
+            self.toml.write(path, &doc)?;
+            Ok(result)
+        })
+    }
+
+    /// 备份后原子写入
+    ///
+    /// 先把当前文件快照到 `policy.dir`（`<name>.<时间戳>.bak`），只保留最近 `keep_n` 份，
+    /// 再通过临时文件 + rename 写入新内容。返回本次生成的备份路径（文件原本不存在时为 `None`）。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// let policy = BackupPolicy::beside(&path);
+    /// manager.write_with_backup(&path, content.as_bytes(), &policy)?;
+    /// ```
+    pub fn write_with_backup(
+        &self,
+        path: &Path,
+        content: &[u8],
+        policy: &BackupPolicy,
+    ) -> Result<Option<PathBuf>> {
+        // 内容未变化时不写入也不产生备份，避免无意义的 mtime 变化与备份轮转
+        if durable::content_matches(path, content) {
+            return Ok(None);
+        }
+
+        let snapshot = backup::snapshot(path, policy)?;
+        backup::atomic_write(path, content, self.durability)?;
+        self.invalidate(path);
+        Ok(snapshot)
+    }
+
+    /// 备份后原子写入 JSON（规范格式输出，见 `to_canonical_string`）
+    pub fn write_json_with_backup(
+        &self,
+        path: &Path,
+        value: &Value,
+        policy: &BackupPolicy,
+    ) -> Result<Option<PathBuf>> {
+        let content = to_canonical_string(value)?;
+        self.write_with_backup(path, content.as_bytes(), policy)
+    }
+
+    /// 列出文件的备份（最新的在前）
+    pub fn list_backups(&self, path: &Path, policy: &BackupPolicy) -> Result<Vec<BackupEntry>> {
+        backup::list(path, policy)
+    }
+
+    /// 从指定备份恢复文件
+    ///
+    /// 恢复前同样会备份当前内容，恢复操作本身可撤销。
+    pub fn restore_backup(
+        &self,
+        path: &Path,
+        backup_name: &str,
+        policy: &BackupPolicy,
+    ) -> Result<()> {
+        let entry = backup::find(path, policy, backup_name)?;
+        let content = std::fs::read(&entry.path).map_err(|e| DataError::io(&entry.path, e))?;
+        self.write_with_backup(path, &content, policy)?;
+        tracing::info!(path = ?path, backup = %backup_name, "已从备份恢复文件");
+        Ok(())
+    }
+
+    /// 使指定文件的 JSON / TOML 缓存失效
+    ///
+    /// 缓存读取会根据文件修改时间和大小自动发现变化；
+    /// 在文件被整体替换（如恢复备份）或监听器发现外部修改后仍建议显式调用。
+    pub fn invalidate(&self, path: &Path) {
+        self.json_cached.invalidate(path);
+        self.toml.invalidate(path);
+    }
+
+    /// 监听文件变更（防抖 `debounce_ms` 毫秒，内容未变化时不回调）
+    ///
+    /// 监听的是文件所在目录，原子 rename 保存后无需重新注册；
+    /// 返回的 `WatchGuard` drop 时停止监听。
+    pub fn watch<F>(&self, path: &Path, debounce_ms: u64, callback: F) -> Result<WatchGuard>
+    where
+        F: Fn(&Path) + Send + 'static,
+    {
+        watch::watch(path, Duration::from_millis(debounce_ms), callback)
+    }
+
     /// 清空所有缓存
     ///
     /// 清空内容包括：
@@ -271,8 +466,9 @@ impl DataManager {
     /// manager.clear_all_caches();
     /// ```
     pub fn clear_all_caches(&self) {
-        // 清空 JSON 缓存
+        // 清空 JSON / TOML 缓存
         self.json_cached.clear_cache();
+        self.toml.clear_cache();
 
         // 清空所有 SQLite 缓存
         if let Ok(connections) = self.sqlite_connections.read() {
@@ -286,6 +482,11 @@ impl DataManager {
     pub fn cache_config(&self) -> &CacheConfig {
         &self.cache_config
     }
+
+    /// 获取写入的持久化级别
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
 }
 
 impl Default for DataManager {
@@ -366,6 +567,28 @@ mod tests {
         assert_eq!(value2["key"], "value");
     }
 
+    #[test]
+    fn test_json_cache_sees_external_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dashboard.json");
+        let manager = DataManager::new();
+
+        std::fs::write(&path, r#"{"selected": "a"}"#).unwrap();
+        assert_eq!(manager.json().read(&path).unwrap()["selected"], "a");
+
+        // 外部直接覆盖文件（大小相同）
+        std::fs::write(&path, r#"{"selected": "b"}"#).unwrap();
+        assert_eq!(manager.json().read(&path).unwrap()["selected"], "b");
+
+        // 外部覆盖（大小变化）
+        std::fs::write(&path, r#"{"selected": "ccc"}"#).unwrap();
+        assert_eq!(manager.json().read(&path).unwrap()["selected"], "ccc");
+
+        // 显式失效后同样读取最新内容
+        manager.invalidate(&path);
+        assert_eq!(manager.json().read(&path).unwrap()["selected"], "ccc");
+    }
+
     #[test]
     fn test_json_uncached_manager() {
         let temp_dir = TempDir::new().unwrap();
@@ -496,6 +719,146 @@ mod tests {
         assert_eq!(rows.len(), 1);
     }
 
+    #[test]
+    fn test_read_modify_write_json_concurrent() {
+        use std::thread;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("counter.json");
+
+        // 每个线程使用独立的 DataManager，模拟不共享进程内状态的多个进程
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    let manager = DataManager::new();
+                    for _ in 0..50 {
+                        manager
+                            .read_modify_write_json(&path, |value| {
+                                let count = value["count"].as_u64().unwrap_or(0);
+                                value["count"] = json!(count + 1);
+                                Ok(())
+                            })
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let value = DataManager::new().json_uncached().read(&path).unwrap();
+        assert_eq!(value["count"], 100);
+    }
+
+    #[test]
+    fn test_read_modify_write_toml() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, "# 注释\nmodel = \"a\"\n").unwrap();
+
+        let manager = DataManager::new();
+        manager
+            .read_modify_write_toml(&path, |doc| {
+                doc["model"] = toml_edit::value("b");
+                Ok(())
+            })
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# 注释"));
+        assert!(content.contains("model = \"b\""));
+    }
+
+    #[test]
+    fn test_with_file_lock_reports_locked() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("providers.json");
+
+        let manager = DataManager::new();
+        let _held = manager.lock_file(&path).unwrap();
+
+        let err = crate::data::FileLock::acquire(&path, Duration::from_millis(50)).unwrap_err();
+        assert!(matches!(err, crate::data::DataError::Locked { .. }));
+    }
+
+    #[test]
+    fn test_restore_newest_backup_after_bad_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("providers.json");
+        let policy = BackupPolicy::new(temp_dir.path().join("backups"), 3);
+        let manager = DataManager::new();
+
+        for i in 1..=3 {
+            manager
+                .write_json_with_backup(&path, &json!({ "version": i }), &policy)
+                .unwrap();
+        }
+        // 模拟写坏的文件
+        manager
+            .write_with_backup(&path, b"{ broken", &policy)
+            .unwrap();
+        assert!(manager.json_uncached().read(&path).is_err());
+
+        let backups = manager.list_backups(&path, &policy).unwrap();
+        assert_eq!(backups.len(), 3);
+        manager
+            .restore_backup(&path, &backups[0].name, &policy)
+            .unwrap();
+
+        let restored = manager.json_uncached().read(&path).unwrap();
+        assert_eq!(restored["version"], 3);
+        // 恢复前的坏文件也被备份，且仍只保留 3 份
+        let backups = manager.list_backups(&path, &policy).unwrap();
+        assert_eq!(backups.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(&backups[0].path).unwrap(),
+            "{ broken"
+        );
+    }
+
+    #[test]
+    fn test_unchanged_save_skips_write_and_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dashboard.json");
+        let policy = BackupPolicy::new(temp_dir.path().join("backups"), 3);
+        let manager = DataManager::new();
+
+        manager
+            .write_json_with_backup(&path, &json!({ "b": 1, "a": [true] }), &policy)
+            .unwrap();
+        let past = std::time::SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        let snapshot = manager
+            .write_json_with_backup(&path, &json!({ "a": [true], "b": 1 }), &policy)
+            .unwrap();
+        assert_eq!(snapshot, None);
+        assert!(manager.list_backups(&path, &policy).unwrap().is_empty());
+        assert_eq!(std::fs::metadata(&path).unwrap().modified().unwrap(), past);
+    }
+
+    #[test]
+    fn test_restore_unknown_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let manager = DataManager::new();
+        let err = manager
+            .restore_backup(
+                &path,
+                "config.json.missing.bak",
+                &BackupPolicy::beside(&path),
+            )
+            .unwrap_err();
+        assert!(matches!(err, crate::data::DataError::NotFound(_)));
+    }
+
     #[test]
     fn test_cache_config_default() {
         let config = CacheConfig::default();
@@ -22,18 +22,30 @@
 //! manager.set(Path::new(".env"), "API_KEY", "secret")?;
 //! ```
 
+use crate::data::durable::{self, Durability};
 use crate::data::{DataError, Result};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 /// ENV 文件管理器
-pub struct EnvManager;
+pub struct EnvManager {
+    /// 写入的持久化级别
+    durability: Durability,
+}
 
 impl EnvManager {
     /// 创建新的 ENV 管理器
     pub fn new() -> Self {
-        Self
+        Self {
+            durability: Durability::default(),
+        }
+    }
+
+    /// 设置写入的持久化级别（默认 `Durability::Durable`）
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
     }
 
     /// 读取 ENV 文件为键值对
@@ -103,7 +115,7 @@ impl EnvManager {
         let content = lines.join("\n") + "\n";
 
         // 写入文件
-        fs::write(path, content).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        durable::write_file(path, content.as_bytes(), self.durability)?;
 
         // 设置权限
         set_permissions(path)?;
@@ -167,7 +179,7 @@ impl EnvManager {
 
         // 写入文件
         let content = lines.join("\n") + "\n";
-        fs::write(path, content).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        durable::write_file(path, content.as_bytes(), self.durability)?;
 
         // 设置权限
         set_permissions(path)?;
@@ -215,7 +227,7 @@ impl EnvManager {
             .collect::<Vec<_>>();
 
         let content = lines.join("\n") + "\n";
-        fs::write(path, content).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        durable::write_file(path, content.as_bytes(), self.durability)?;
 
         set_permissions(path)?;
 
@@ -30,10 +30,10 @@
 //! )?;
 //! ```
 
-use crate::data::cache::JsonConfigCache;
+use crate::data::cache::{checksum_of, FileStamp, JsonConfigCache};
+use crate::data::durable::{self, Durability};
 use crate::data::{DataError, Result};
 use serde_json::Value;
-use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
@@ -44,6 +44,8 @@ use std::time::Duration;
 pub struct JsonManager {
     /// JSON 配置缓存（None 表示无缓存模式）
     cache: Option<JsonConfigCache>,
+    /// 写入的持久化级别
+    durability: Durability,
 }
 
 impl JsonManager {
@@ -63,6 +65,7 @@ impl JsonManager {
     pub fn with_cache(capacity: usize, ttl: Duration) -> Self {
         Self {
             cache: Some(JsonConfigCache::new(capacity, ttl)),
+            durability: Durability::default(),
         }
     }
 
@@ -74,7 +77,16 @@ impl JsonManager {
     /// let manager = JsonManager::without_cache();
     /// ```
     pub fn without_cache() -> Self {
-        Self { cache: None }
+        Self {
+            cache: None,
+            durability: Durability::default(),
+        }
+    }
+
+    /// 设置写入的持久化级别（默认 `Durability::Durable`）
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
     }
 
     /// 读取整个 JSON 文件
@@ -95,16 +107,16 @@ impl JsonManager {
             }
         }
 
-        // 缓存未命中或无缓存模式，从文件读取
+        // 缓存未命中或无缓存模式，从文件读取（先记录状态戳，读取期间被修改时下次会重新读取）
+        let stamp = self.cache.as_ref().and_then(|_| FileStamp::of(path));
         let content = fs::read_to_string(path).map_err(|e| DataError::io(path.to_path_buf(), e))?;
 
         let value: Value = serde_json::from_str(&content)?;
 
         // 插入缓存
         if let Some(cache) = &self.cache {
-            let checksum =
-                compute_checksum(path).map_err(|e| DataError::io(path.to_path_buf(), e))?;
-            cache.insert(path.to_path_buf(), value.clone(), checksum);
+            let checksum = checksum_of(content.as_bytes());
+            cache.insert_stamped(path.to_path_buf(), value.clone(), checksum, stamp);
         }
 
         Ok(value)
@@ -113,20 +125,24 @@ impl JsonManager {
     /// 写入整个 JSON 文件
     ///
     /// 自动创建父目录并设置权限（Unix 平台 0o600）。
+    /// 以规范格式输出（见 [`to_canonical_string`]），内容与磁盘上一致时跳过写入。
     ///
     /// # 参数
     ///
     /// - `path`: 文件路径
     /// - `value`: JSON 值
     pub fn write(&self, path: &Path, value: &Value) -> Result<()> {
+        let content = to_canonical_string(value)?;
+        if durable::content_matches(path, content.as_bytes()) {
+            return Ok(());
+        }
+
         // 创建父目录
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
         }
 
-        // 写入文件（格式化输出）
-        let content = serde_json::to_string_pretty(value)?;
-        fs::write(path, content).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        durable::write_file(path, content.as_bytes(), self.durability)?;
 
         // 设置权限
         set_permissions(path)?;
@@ -270,6 +286,16 @@ impl JsonManager {
         self.write(path, &value)
     }
 
+    /// 使指定文件的缓存失效（仅缓存模式有效）
+    ///
+    /// 缓存会根据文件修改时间和大小自动失效；外部工具替换文件（如恢复备份）后
+    /// 可显式调用以确保下次读取最新内容。
+    pub fn invalidate(&self, path: &Path) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(path);
+        }
+    }
+
     /// 清空缓存（仅缓存模式有效）
     pub fn clear_cache(&self) {
         if let Some(cache) = &self.cache {
@@ -383,12 +409,14 @@ fn merge_values(target: &mut Value, source: &Value) {
     }
 }
 
-/// 计算文件 SHA-256 校验和
-fn compute_checksum(path: &Path) -> std::io::Result<String> {
-    let content = fs::read(path)?;
-    let mut hasher = Sha256::new();
-    hasher.update(&content);
-    Ok(format!("{:x}", hasher.finalize()))
+/// 规范化 JSON 文本：2 空格缩进、键按字典序排列、以换行结尾
+///
+/// `serde_json::Map` 未启用 `preserve_order`（底层为 BTreeMap），因此无论模型字段顺序
+/// 还是 HashMap 的迭代顺序如何，语义相同的值都会得到逐字节相同的输出。
+pub fn to_canonical_string(value: &Value) -> Result<String> {
+    let mut content = serde_json::to_string_pretty(value)?;
+    content.push('\n');
+    Ok(content)
 }
 
 /// 设置文件权限（Unix 平台 0o600）
@@ -650,6 +678,52 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[test]
+    fn test_canonical_string_is_order_independent() {
+        let mut first = std::collections::HashMap::new();
+        let mut second = std::collections::HashMap::new();
+        for key in ["zeta", "alpha", "mid"] {
+            first.insert(key, 1);
+        }
+        for key in ["mid", "zeta", "alpha"] {
+            second.insert(key, 1);
+        }
+
+        let a = to_canonical_string(&serde_json::to_value(&first).unwrap()).unwrap();
+        let b = to_canonical_string(&serde_json::to_value(&second).unwrap()).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, "{\n  \"alpha\": 1,\n  \"mid\": 1,\n  \"zeta\": 1\n}\n");
+    }
+
+    #[test]
+    fn test_unchanged_write_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("providers.json");
+        let manager = JsonManager::with_cache(10, Duration::from_secs(60));
+
+        manager
+            .write(&file_path, &json!({"b": [1, 2], "a": {"y": 1, "x": 2}}))
+            .unwrap();
+        let past = std::time::SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        // 语义相同的值不写入
+        manager
+            .write(&file_path, &json!({"a": {"x": 2, "y": 1}, "b": [1, 2]}))
+            .unwrap();
+        assert_eq!(fs::metadata(&file_path).unwrap().modified().unwrap(), past);
+
+        // 内容变化时正常写入
+        manager.write(&file_path, &json!({"a": 1})).unwrap();
+        assert_ne!(fs::metadata(&file_path).unwrap().modified().unwrap(), past);
+        assert_eq!(manager.read(&file_path).unwrap(), json!({"a": 1}));
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_permissions_unix() {
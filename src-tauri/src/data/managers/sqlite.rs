@@ -36,6 +36,7 @@
 //! ```
 
 use crate::data::cache::{extract_tables, QueryKey, SqlQueryCache};
+use crate::data::lock::lock_wait;
 use crate::data::{DataError, Result};
 use rusqlite::{params_from_iter, Connection, Row, Transaction};
 use serde::{Deserialize, Serialize};
@@ -113,7 +114,11 @@ impl SqliteManager {
             std::fs::create_dir_all(parent).map_err(|e| DataError::io(parent.to_path_buf(), e))?;
         }
 
-        Connection::open(path).map_err(DataError::Database)
+        let conn = Connection::open(path).map_err(DataError::Database)?;
+        // 其他进程（GUI / CLI）持有写锁时等待，而不是立即返回 SQLITE_BUSY
+        conn.busy_timeout(lock_wait())
+            .map_err(DataError::Database)?;
+        Ok(conn)
     }
 
     /// 执行查询（返回通用行格式）
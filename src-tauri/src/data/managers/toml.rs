@@ -2,6 +2,7 @@
 //!
 //! 提供 TOML 配置文件的读写和操作，支持：
 //! - 保留注释和格式（使用 `toml_edit`）
+//! - 可选的文档缓存（按修改时间和大小校验，写入后自动失效）
 //! - 键路径访问（支持嵌套键如 "model_providers.duckcoding.base_url"）
 //! - 深度合并
 //! - 自动创建父目录
@@ -24,21 +25,48 @@
 //! manager.write(Path::new("config.toml"), &doc)?;
 //! ```
 
+use crate::data::cache::{checksum_of, FileStamp, TomlDocumentCache};
+use crate::data::durable::{self, Durability};
 use crate::data::{DataError, Result};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use toml::Value as TomlValue;
 use toml_edit::{DocumentMut, Item, Table, Value as EditValue};
 
 /// TOML 配置管理器
 ///
 /// 使用 `toml_edit` 保留注释和格式。
-pub struct TomlManager;
+pub struct TomlManager {
+    /// 可编辑文档缓存（None 表示无缓存模式）
+    cache: Option<TomlDocumentCache>,
+    /// 写入的持久化级别
+    durability: Durability,
+}
 
 impl TomlManager {
-    /// 创建新的 TOML 管理器
+    /// 创建新的 TOML 管理器（无缓存）
     pub fn new() -> Self {
-        Self
+        Self {
+            cache: None,
+            durability: Durability::default(),
+        }
+    }
+
+    /// 创建带文档缓存的管理器
+    ///
+    /// 缓存按文件修改时间和大小校验，外部修改后自动重新读取。
+    pub fn with_cache(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            cache: Some(TomlDocumentCache::new(capacity, ttl)),
+            durability: Durability::default(),
+        }
+    }
+
+    /// 设置写入的持久化级别（默认 `Durability::Durable`）
+    pub fn with_durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
     }
 
     /// 读取整个 TOML 文件（返回 `toml::Value`）
@@ -52,7 +80,12 @@ impl TomlManager {
     /// - `Ok(TomlValue)`: TOML 值
     /// - `Err(DataError)`: 读取或解析失败
     pub fn read(&self, path: &Path) -> Result<TomlValue> {
-        let content = fs::read_to_string(path).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        // 缓存模式下复用缓存的文档，避免重复读取磁盘
+        let content = if self.cache.is_some() {
+            self.read_document(path)?.to_string()
+        } else {
+            fs::read_to_string(path).map_err(|e| DataError::io(path.to_path_buf(), e))?
+        };
 
         toml::from_str(&content).map_err(Into::into)
     }
@@ -61,11 +94,26 @@ impl TomlManager {
     ///
     /// 使用此方法保留注释和格式。
     pub fn read_document(&self, path: &Path) -> Result<DocumentMut> {
+        if let Some(cache) = &self.cache {
+            if let Some(doc) = cache.get(path) {
+                return Ok(doc);
+            }
+        }
+
+        // 先记录状态戳，读取期间被修改时下次会重新读取
+        let stamp = self.cache.as_ref().and_then(|_| FileStamp::of(path));
         let content = fs::read_to_string(path).map_err(|e| DataError::io(path.to_path_buf(), e))?;
 
-        content
+        let doc = content
             .parse::<DocumentMut>()
-            .map_err(|e| DataError::TomlEdit(e.to_string()))
+            .map_err(|e| DataError::TomlEdit(e.to_string()))?;
+
+        if let Some(cache) = &self.cache {
+            let checksum = checksum_of(content.as_bytes());
+            cache.insert_stamped(path.to_path_buf(), doc.clone(), checksum, stamp);
+        }
+
+        Ok(doc)
     }
 
     /// 写入 TOML 文档
@@ -83,14 +131,31 @@ impl TomlManager {
         }
 
         // 写入文件
-        fs::write(path, doc.to_string()).map_err(|e| DataError::io(path.to_path_buf(), e))?;
+        durable::write_file(path, doc.to_string().as_bytes(), self.durability)?;
 
         // 设置权限
         set_permissions(path)?;
 
+        // 使缓存失效（文件已变更）
+        self.invalidate(path);
+
         Ok(())
     }
 
+    /// 使指定文件的缓存失效（仅缓存模式有效）
+    pub fn invalidate(&self, path: &Path) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate(path);
+        }
+    }
+
+    /// 清空缓存（仅缓存模式有效）
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear();
+        }
+    }
+
     /// 获取指定键的值
     ///
     /// 支持嵌套键，如 "model_providers.duckcoding.base_url"。
@@ -530,4 +595,85 @@ key = "value"
 
         assert!(file_path.exists());
     }
+
+    /// 将文件修改时间调到过去，避开「修改时间过近」时的校验和兜底
+    fn age_file(path: &Path) {
+        let past = std::time::SystemTime::now() - Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_cached_read_document_hits_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(&path, "model = \"aaa\"\n").unwrap();
+        age_file(&path);
+        let stamp = fs::metadata(&path).unwrap().modified().unwrap();
+
+        let manager = TomlManager::with_cache(10, Duration::from_secs(60));
+        let doc = manager.read_document(&path).unwrap();
+        assert_eq!(doc["model"].as_str(), Some("aaa"));
+
+        // 改写内容但保持大小与修改时间不变：仍命中缓存，说明没有重新读取磁盘
+        fs::write(&path, "model = \"bbb\"\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(stamp)
+            .unwrap();
+        assert_eq!(
+            manager.read_document(&path).unwrap()["model"].as_str(),
+            Some("aaa")
+        );
+        assert_eq!(manager.read(&path).unwrap()["model"].as_str(), Some("aaa"));
+
+        // 显式失效后读取到最新内容
+        manager.invalidate(&path);
+        assert_eq!(
+            manager.read_document(&path).unwrap()["model"].as_str(),
+            Some("bbb")
+        );
+    }
+
+    #[test]
+    fn test_cached_read_document_sees_external_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(&path, "model = \"a\"\n").unwrap();
+
+        let manager = TomlManager::with_cache(10, Duration::from_secs(60));
+        assert_eq!(
+            manager.read_document(&path).unwrap()["model"].as_str(),
+            Some("a")
+        );
+
+        // 外部修改（大小变化）
+        fs::write(&path, "model = \"changed\"\n").unwrap();
+        assert_eq!(
+            manager.read_document(&path).unwrap()["model"].as_str(),
+            Some("changed")
+        );
+    }
+
+    #[test]
+    fn test_write_invalidates_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        fs::write(&path, "# 注释\nmodel = \"a\"\n").unwrap();
+
+        let manager = TomlManager::with_cache(10, Duration::from_secs(60));
+        let mut doc = manager.read_document(&path).unwrap();
+        doc["model"] = toml_edit::value("b");
+        manager.write(&path, &doc).unwrap();
+
+        let reread = manager.read_document(&path).unwrap();
+        assert_eq!(reread["model"].as_str(), Some("b"));
+        assert!(reread.to_string().contains("# 注释"));
+    }
 }
@@ -6,6 +6,12 @@
 //!
 //! - `error`: 统一错误类型定义
 //! - `cache`: 缓存层实现（LRU + 文件校验和 + SQL 查询缓存）
+//! - `lock`: 跨进程文件锁（读-改-写保护）
+//! - `backup`: 写入前自动备份与轮转
+//! - `durable`: 持久化写入（fsync）
+//! - `network_fs`: 网络文件系统检测（漫游配置文件等场景）
+//! - `path_policy`: 写入路径校验（限制在允许的根目录内）
+//! - `watch`: 防抖的文件变更监听
 //! - `managers`: 各格式管理器（JSON/TOML/ENV/SQLite）
 //! - `manager`: 统一入口 `DataManager`
 //!
@@ -25,13 +31,24 @@
 //! let settings = manager.json_uncached().read(Path::new("~/.claude/settings.json"))?;
 //! ```
 
+pub mod backup;
 pub mod cache;
+pub mod durable;
 pub mod error;
+pub mod lock;
 pub mod manager;
 pub mod managers;
+pub mod network_fs;
+pub mod path_policy;
+pub mod watch;
 
 #[cfg(test)]
 mod migration_tests;
 
+pub use backup::{BackupEntry, BackupPolicy};
+pub use durable::Durability;
 pub use error::{DataError, Result};
+pub use lock::FileLock;
 pub use manager::{CacheConfig, DataManager};
+pub use path_policy::PathPolicy;
+pub use watch::WatchGuard;
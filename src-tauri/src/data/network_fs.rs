@@ -0,0 +1,289 @@
+//! 网络文件系统检测
+//!
+//! 漫游配置文件（roaming profile）场景下 `~/.claude` 等目录可能位于 SMB/NFS 共享上：
+//! 读取可达数秒、文件系统通知不可用。启动时按平台启发式检测配置目录所在的文件系统：
+//! - Linux：`/proc/self/mountinfo` 中挂载点的文件系统类型
+//! - macOS：`mount` 输出中挂载点的文件系统类型
+//! - Windows：UNC 路径，或 `HKCU\Network` 下登记的映射驱动器
+//!
+//! 命中后放宽文件锁等待时间（见 `lock::lock_wait`），并由调用方停用文件监听。
+
+use once_cell::sync::OnceCell;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 视为网络文件系统的类型（小写）
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "davfs",
+    "fuse.sshfs",
+    "fuse.davfs2",
+    "fuse.rclone",
+    "afs",
+    "ncpfs",
+    "ceph",
+    "glusterfs",
+    "fuse.glusterfs",
+];
+
+/// 位于网络文件系统上的路径
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NetworkMount {
+    /// 检测的路径
+    pub path: PathBuf,
+    /// 所在挂载点（Windows 上为共享根或驱动器）
+    pub mount_point: String,
+    /// 文件系统类型（Windows 上为 `unc` 或 `mapped_drive`）
+    pub fs_type: String,
+}
+
+static DETECTED: OnceCell<Vec<NetworkMount>> = OnceCell::new();
+
+/// 检测一组路径（配置目录、工具配置目录等），结果在进程内只计算一次
+pub fn init(paths: &[PathBuf]) -> &'static [NetworkMount] {
+    DETECTED.get_or_init(|| {
+        let started = Instant::now();
+        let mounts: Vec<NetworkMount> = paths.iter().filter_map(|path| detect(path)).collect();
+        for mount in &mounts {
+            tracing::warn!(
+                path = ?mount.path,
+                mount_point = %mount.mount_point,
+                fs_type = %mount.fs_type,
+                "配置目录位于网络文件系统"
+            );
+        }
+        tracing::debug!(
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            network = mounts.len(),
+            "网络文件系统检测完成"
+        );
+        mounts
+    })
+}
+
+/// 启动时检测到的网络路径（尚未检测时为空）
+pub fn network_mounts() -> &'static [NetworkMount] {
+    DETECTED.get().map(Vec::as_slice).unwrap_or(&[])
+}
+
+/// 是否有配置目录位于网络文件系统
+pub fn network_backed() -> bool {
+    !network_mounts().is_empty()
+}
+
+/// 路径是否位于启动时检测到的网络目录下
+pub fn is_network_path(path: &Path) -> bool {
+    network_mounts()
+        .iter()
+        .any(|mount| path.starts_with(&mount.path))
+}
+
+/// 检测单个路径所在的文件系统是否为网络文件系统
+pub fn detect(path: &Path) -> Option<NetworkMount> {
+    let resolved = existing_ancestor(path)?;
+    let (mount_point, fs_type) = platform_mount_of(&resolved)?;
+    Some(NetworkMount {
+        path: path.to_path_buf(),
+        mount_point,
+        fs_type,
+    })
+}
+
+/// 测量目录的访问延迟：读取元数据、列目录，并写入删除一个探测文件
+pub fn measure_latency(dir: &Path) -> std::io::Result<Duration> {
+    let started = Instant::now();
+    std::fs::metadata(dir)?;
+    let _ = std::fs::read_dir(dir)?.count();
+    let probe = dir.join(format!(".latency-probe-{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(&probe)?;
+    Ok(started.elapsed())
+}
+
+/// 路径本身可能尚未创建，取最近的已存在祖先目录并解析符号链接
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|candidate| candidate.exists())
+        .and_then(|candidate| candidate.canonicalize().ok())
+}
+
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn is_network_fs_type(fs_type: &str) -> bool {
+    let fs_type = fs_type.to_ascii_lowercase();
+    NETWORK_FS_TYPES.contains(&fs_type.as_str())
+}
+
+/// 在挂载表（挂载点，文件系统类型）中找到包含 `path` 的最长挂载点，是网络文件系统时返回
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn network_mount_in(mounts: &[(String, String)], path: &Path) -> Option<(String, String)> {
+    mounts
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .filter(|(_, fs_type)| is_network_fs_type(fs_type))
+        .cloned()
+}
+
+/// 解析 `/proc/self/mountinfo`：第 5 列为挂载点，` - ` 之后第一列为文件系统类型
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_mountinfo(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (left, right) = line.split_once(" - ")?;
+            let mount_point = left.split_whitespace().nth(4)?;
+            let fs_type = right.split_whitespace().next()?;
+            Some((unescape_mount_path(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// mountinfo 中空格等字符以八进制转义（如 `\040`）
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn unescape_mount_path(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let escaped = bytes
+                .get(i + 1..i + 4)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+            if let Some(value) = escaped {
+                out.push(value);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// 解析 macOS `mount` 输出：`//user@server/share on /Volumes/share (smbfs, nodev, ...)`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_bsd_mount_output(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some((mount_point.to_string(), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn platform_mount_of(path: &Path) -> Option<(String, String)> {
+    let content = std::fs::read_to_string("/proc/self/mountinfo").ok()?;
+    network_mount_in(&parse_mountinfo(&content), path)
+}
+
+#[cfg(target_os = "macos")]
+fn platform_mount_of(path: &Path) -> Option<(String, String)> {
+    let output = std::process::Command::new("/sbin/mount").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    network_mount_in(&parse_bsd_mount_output(&output), path)
+}
+
+#[cfg(target_os = "windows")]
+fn platform_mount_of(path: &Path) -> Option<(String, String)> {
+    use std::path::{Component, Prefix};
+
+    match path.components().next()? {
+        Component::Prefix(prefix) => match prefix.kind() {
+            Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => Some((
+                format!(
+                    r"\\{}\{}",
+                    server.to_string_lossy(),
+                    share.to_string_lossy()
+                ),
+                "unc".to_string(),
+            )),
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                let letter = (letter as char).to_ascii_uppercase();
+                is_mapped_drive(letter).then(|| (format!("{letter}:"), "mapped_drive".to_string()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// 映射的网络驱动器登记在 `HKCU\Network\<盘符>`
+#[cfg(target_os = "windows")]
+fn is_mapped_drive(letter: char) -> bool {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey(format!("Network\\{letter}"))
+        .is_ok()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn platform_mount_of(_path: &Path) -> Option<(String, String)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mountinfo_longest_prefix() {
+        let mountinfo = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+40 22 0:45 / /home/alice rw,relatime shared:20 - cifs //fs01/profiles/alice rw,vers=3.0
+41 40 8:2 / /home/alice/local\\040disk rw,relatime - ext4 /dev/sdb1 rw
+";
+        let mounts = parse_mountinfo(mountinfo);
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[2].0, "/home/alice/local disk");
+
+        let (mount_point, fs_type) =
+            network_mount_in(&mounts, Path::new("/home/alice/.claude")).unwrap();
+        assert_eq!(mount_point, "/home/alice");
+        assert_eq!(fs_type, "cifs");
+
+        // 更深的本地挂载点优先
+        assert!(network_mount_in(&mounts, Path::new("/home/alice/local disk/x")).is_none());
+        assert!(network_mount_in(&mounts, Path::new("/var/lib")).is_none());
+        // 只按路径组件匹配，不按字符串前缀
+        assert!(network_mount_in(&mounts, Path::new("/home/alicex")).is_none());
+    }
+
+    #[test]
+    fn test_bsd_mount_output() {
+        let output = "\
+/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)
+//alice@fs01/home on /Volumes/home (smbfs, nodev, nosuid, mounted by alice)
+";
+        let mounts = parse_bsd_mount_output(output);
+        let (mount_point, fs_type) =
+            network_mount_in(&mounts, Path::new("/Volumes/home/.codex")).unwrap();
+        assert_eq!(mount_point, "/Volumes/home");
+        assert_eq!(fs_type, "smbfs");
+        assert!(network_mount_in(&mounts, Path::new("/Users/alice")).is_none());
+    }
+
+    #[test]
+    fn test_measure_latency_cleans_up_probe() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(measure_latency(dir.path()).is_ok());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,226 @@
+//! 写入路径校验
+//!
+//! 前端传入的路径（导出位置、安装包路径、备份名等）不可信：webview 被注入或存在缺陷时，
+//! 可能借后端覆盖任意文件。受管写入在执行前必须通过 `PathPolicy::check`：
+//! - 拒绝相对路径、`..` 穿越以及 Windows 的 UNC / `\\?\` / 设备路径
+//! - 解析符号链接后（文件可不存在，按最近的已存在祖先目录解析）必须位于允许的根目录内
+//! - 尚未解析的部分不能是符号链接（如悬空链接），否则写入时会跟随到根目录之外
+//!
+//! 根目录由调用方给出（如更新包目录），用户通过系统对话框选择的目录可额外加入；
+//! 用户在设置中选择、无法限定根目录的位置（如备份目录）使用 `check_user_chosen`。
+
+use crate::data::{DataError, Result};
+use std::path::{Component, Path, PathBuf, Prefix};
+
+/// 允许写入的根目录集合
+#[derive(Debug, Clone, Default)]
+pub struct PathPolicy {
+    roots: Vec<PathBuf>,
+}
+
+impl PathPolicy {
+    /// 仅允许写入给定目录
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+
+    /// 追加允许的目录（如用户在对话框中选择的导出目录）
+    pub fn allow(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.roots.push(dir.into());
+        self
+    }
+
+    /// 校验路径，返回解析符号链接后的实际路径
+    pub fn check(&self, path: &Path) -> Result<PathBuf> {
+        reject_unsafe_form(path)?;
+
+        let resolved = resolve(path);
+        let inside = self
+            .roots
+            .iter()
+            .map(|root| resolve(root))
+            .any(|root| resolved.starts_with(&root));
+        if !inside {
+            return Err(violation(path, "不在允许写入的目录内"));
+        }
+        reject_unresolved_links(path, &resolved)?;
+        Ok(resolved)
+    }
+}
+
+/// 校验用户选择的位置（不限定根目录）：形式安全且不含无法解析的符号链接
+pub fn check_user_chosen(path: &Path) -> Result<PathBuf> {
+    reject_unsafe_form(path)?;
+    let resolved = resolve(path);
+    reject_unresolved_links(path, &resolved)?;
+    Ok(resolved)
+}
+
+/// 校验单个文件名（如备份名）：不允许包含路径分隔符或特殊目录名
+pub fn check_file_name(name: &str) -> Result<()> {
+    let invalid = name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.contains(['/', '\\', '\0'])
+        || (cfg!(windows) && name.contains(':'));
+    if invalid {
+        return Err(violation(Path::new(name), "不是合法的文件名"));
+    }
+    Ok(())
+}
+
+/// 拒绝相对路径、`..` 以及 Windows 的 UNC / verbatim / 设备路径
+fn reject_unsafe_form(path: &Path) -> Result<()> {
+    for component in path.components() {
+        match component {
+            Component::ParentDir => return Err(violation(path, "包含 .. 路径穿越")),
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(_) => {}
+                Prefix::UNC(..) | Prefix::VerbatimUNC(..) => {
+                    return Err(violation(path, "不允许使用网络共享（UNC）路径"))
+                }
+                Prefix::Verbatim(_) | Prefix::VerbatimDisk(_) | Prefix::DeviceNS(_) => {
+                    return Err(violation(path, "不允许使用设备或 \\\\?\\ 路径"))
+                }
+            },
+            _ => {}
+        }
+    }
+    if !path.is_absolute() {
+        return Err(violation(path, "必须是绝对路径"));
+    }
+    Ok(())
+}
+
+/// 已解析的前缀不含符号链接，剩余部分中出现符号链接说明其无法解析（悬空或循环）
+fn reject_unresolved_links(path: &Path, resolved: &Path) -> Result<()> {
+    let dangling = resolved
+        .ancestors()
+        .any(|p| std::fs::symlink_metadata(p).is_ok_and(|meta| meta.file_type().is_symlink()));
+    if dangling {
+        return Err(violation(path, "包含无法解析的符号链接"));
+    }
+    Ok(())
+}
+
+/// 解析符号链接：规范化最近的已存在祖先目录，再拼接尚不存在的部分
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = dunce_canonicalize(existing) {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc: PathBuf, part| acc.join(part));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// Windows 上 `canonicalize` 返回 `\\?\C:\...`，去掉前缀以便与根目录比较
+fn dunce_canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    if cfg!(windows) {
+        if let Some(stripped) = canonical.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+            if !stripped.starts_with("UNC\\") {
+                return Ok(PathBuf::from(stripped));
+            }
+        }
+    }
+    Ok(canonical)
+}
+
+fn violation(path: &Path, reason: &str) -> DataError {
+    DataError::PolicyViolation {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_allows_paths_inside_root() {
+        let root = TempDir::new().unwrap();
+        let policy = PathPolicy::new([root.path().to_path_buf()]);
+
+        // 文件与中间目录都可以尚不存在
+        let target = root.path().join("exports").join("usage.csv");
+        let resolved = policy.check(&target).unwrap();
+        assert!(resolved.ends_with("exports/usage.csv"));
+    }
+
+    #[test]
+    fn test_rejects_outside_and_traversal() {
+        let root = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        let policy = PathPolicy::new([root.path().to_path_buf()]);
+
+        let err = policy.check(&other.path().join("x.json")).unwrap_err();
+        assert!(matches!(err, DataError::PolicyViolation { .. }));
+
+        let traversal = root.path().join("..").join("escape.json");
+        assert!(policy.check(&traversal).is_err());
+        assert!(policy.check(Path::new("relative.json")).is_err());
+    }
+
+    #[test]
+    fn test_allow_adds_root() {
+        let root = TempDir::new().unwrap();
+        let chosen = TempDir::new().unwrap();
+        let policy = PathPolicy::new([root.path().to_path_buf()]).allow(chosen.path());
+        assert!(policy.check(&chosen.path().join("captures.json")).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_symlink_escape() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("link")).unwrap();
+        let policy = PathPolicy::new([root.path().to_path_buf()]);
+
+        let err = policy
+            .check(&root.path().join("link").join("config.json"))
+            .unwrap_err();
+        assert!(matches!(err, DataError::PolicyViolation { .. }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_dangling_symlink_leaf() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let leaf = root.path().join("report.md");
+        std::os::unix::fs::symlink(outside.path().join("planted.md"), &leaf).unwrap();
+
+        let policy = PathPolicy::new([root.path().to_path_buf()]);
+        assert!(matches!(
+            policy.check(&leaf),
+            Err(DataError::PolicyViolation { .. })
+        ));
+        assert!(check_user_chosen(&leaf).is_err());
+        assert!(check_user_chosen(&root.path().join("backups")).is_ok());
+    }
+
+    #[test]
+    fn test_check_file_name() {
+        assert!(check_file_name("config.json.20260101T000000000.bak").is_ok());
+        assert!(check_file_name("").is_err());
+        assert!(check_file_name("..").is_err());
+        assert!(check_file_name("../config.json").is_err());
+        assert!(check_file_name("a\\b").is_err());
+    }
+}
@@ -0,0 +1,265 @@
+//! 防抖的文件变更监听
+//!
+//! 编辑器保存一次文件往往触发十几个事件，且常用「写临时文件再 rename」的原子保存，
+//! 直接监听目标文件会在 rename 后失效。因此：
+//! - 监听目标文件所在目录，按文件名过滤事件（rename 覆盖后无需重新注册）
+//! - 事件停止 `debounce` 时长后才处理，合并一次保存产生的多个事件
+//! - 对比内容校验和，内容未变（如原样保存）时不回调
+//!
+//! 返回的 `WatchGuard` 在 drop 时停止监听。
+
+use crate::data::cache::checksum_of;
+use crate::data::{DataError, Result};
+use notify::{
+    Config as NotifyConfig, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// 空闲时检查停止标记的间隔
+const IDLE_POLL: Duration = Duration::from_millis(200);
+
+/// 文件监听句柄，drop 时停止监听
+pub struct WatchGuard {
+    path: PathBuf,
+    watcher: Option<RecommendedWatcher>,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WatchGuard {
+    /// 被监听的文件
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // 先释放 watcher，事件通道随之关闭
+        self.watcher.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for WatchGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchGuard")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+/// 监听文件变更
+///
+/// 文件所在目录必须存在（文件本身可以暂不存在，创建时会回调）。
+/// `callback` 在后台线程中调用，参数为被监听的文件路径。
+pub fn watch<F>(path: &Path, debounce: Duration, callback: F) -> Result<WatchGuard>
+where
+    F: Fn(&Path) + Send + 'static,
+{
+    let file_name: OsString = path
+        .file_name()
+        .ok_or_else(|| DataError::InvalidKey(format!("无效的文件路径: {}", path.display())))?
+        .to_os_string();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            let relevant = event
+                .paths
+                .iter()
+                .any(|p| p.file_name() == Some(file_name.as_os_str()));
+            if relevant {
+                let _ = tx.send(());
+            }
+        },
+        NotifyConfig::default(),
+    )
+    .map_err(|e| DataError::Concurrency(format!("创建文件监听失败: {e}")))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| DataError::Concurrency(format!("监听目录失败 {}: {e}", dir.display())))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let worker = {
+        let stop = stop.clone();
+        let path = path.to_path_buf();
+        std::thread::Builder::new()
+            .name("data-file-watch".to_string())
+            .spawn(move || run_worker(&path, debounce, &rx, &stop, callback))
+            .map_err(|e| DataError::io(&dir, e))?
+    };
+
+    Ok(WatchGuard {
+        path: path.to_path_buf(),
+        watcher: Some(watcher),
+        stop,
+        worker: Some(worker),
+    })
+}
+
+/// 后台线程：合并事件突发，内容确实变化时回调
+fn run_worker<F>(
+    path: &Path,
+    debounce: Duration,
+    rx: &mpsc::Receiver<()>,
+    stop: &AtomicBool,
+    callback: F,
+) where
+    F: Fn(&Path),
+{
+    let mut last = fingerprint(path);
+    loop {
+        match rx.recv_timeout(IDLE_POLL) {
+            Ok(()) => {
+                // 等待事件停歇
+                loop {
+                    match rx.recv_timeout(debounce) {
+                        Ok(()) => continue,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+
+                let current = fingerprint(path);
+                if current != last {
+                    last = current;
+                    callback(path);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// 文件内容指纹（文件不存在时为 None）
+fn fingerprint(path: &Path) -> Option<String> {
+    std::fs::read(path)
+        .ok()
+        .map(|content| checksum_of(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Mutex;
+    use std::time::Instant;
+    use tempfile::TempDir;
+
+    const DEBOUNCE: Duration = Duration::from_millis(150);
+
+    fn counting_watch(path: &Path) -> (WatchGuard, Arc<Mutex<usize>>) {
+        let count = Arc::new(Mutex::new(0));
+        let counter = count.clone();
+        let guard = watch(path, DEBOUNCE, move |_| {
+            *counter.lock().unwrap() += 1;
+        })
+        .unwrap();
+        (guard, count)
+    }
+
+    /// 等待计数达到 `expected`，再多等一个防抖周期确认没有多余回调
+    fn settle(count: &Arc<Mutex<usize>>, expected: usize) -> usize {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while *count.lock().unwrap() < expected && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        std::thread::sleep(DEBOUNCE * 3);
+        *count.lock().unwrap()
+    }
+
+    #[test]
+    fn test_rename_style_save_coalesced() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("dashboard.json");
+        std::fs::write(&path, "{}").unwrap();
+        let (_guard, count) = counting_watch(&path);
+
+        // 原子保存：写临时文件后 rename 覆盖
+        let tmp = temp_dir.path().join(".dashboard.json.tmp");
+        std::fs::write(&tmp, r#"{"v": 1}"#).unwrap();
+        std::fs::rename(&tmp, &path).unwrap();
+        assert_eq!(settle(&count, 1), 1);
+
+        // rename 后仍能收到后续修改
+        std::fs::write(&tmp, r#"{"v": 2}"#).unwrap();
+        std::fs::rename(&tmp, &path).unwrap();
+        assert_eq!(settle(&count, 2), 2);
+    }
+
+    #[test]
+    fn test_truncate_style_writes_coalesced() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("providers.json");
+        std::fs::write(&path, "{}").unwrap();
+        let (_guard, count) = counting_watch(&path);
+
+        // 截断后分多次写入，模拟编辑器的一次保存
+        let mut file = std::fs::File::create(&path).unwrap();
+        for chunk in ["{", "\"providers\"", ": []", "}"] {
+            file.write_all(chunk.as_bytes()).unwrap();
+            file.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        drop(file);
+
+        assert_eq!(settle(&count, 1), 1);
+    }
+
+    #[test]
+    fn test_unchanged_content_ignored() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        std::fs::write(&path, "model = \"a\"\n").unwrap();
+        let (_guard, count) = counting_watch(&path);
+
+        std::fs::write(&path, "model = \"a\"\n").unwrap();
+        // 同目录的其他文件不触发
+        std::fs::write(temp_dir.path().join("other.toml"), "x = 1\n").unwrap();
+        assert_eq!(settle(&count, 0), 0);
+    }
+
+    #[test]
+    fn test_drop_stops_watching() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.json");
+        let (guard, count) = counting_watch(&path);
+
+        // 文件创建也会回调
+        std::fs::write(&path, "{}").unwrap();
+        assert_eq!(settle(&count, 1), 1);
+
+        drop(guard);
+        std::fs::write(&path, r#"{"after": true}"#).unwrap();
+        std::thread::sleep(DEBOUNCE * 3);
+        assert_eq!(*count.lock().unwrap(), 1);
+    }
+}
@@ -1,6 +1,8 @@
 //! HTTP 客户端构建工具：统一在一个地方处理代理与超时等配置。
 
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{self, Client};
+use std::collections::BTreeMap;
 
 const USER_AGENT: &str = concat!("DuckCoding-Updater/", env!("CARGO_PKG_VERSION"));
 
@@ -37,3 +39,56 @@ pub fn build_client() -> Result<Client, String> {
             .map_err(|e| format!("Failed to build reqwest client: {e}"))
     }
 }
+
+/// 已由凭证注入设置时不允许被自定义请求头覆盖的请求头
+const CREDENTIAL_HEADERS: &[&str] = &[
+    "authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "new-api-user",
+];
+
+/// 把供应商的自定义请求头合并到请求头中
+///
+/// 同名请求头以自定义值为准；已设置的凭证请求头（Authorization 等）保持不变。
+/// 名称或值无法构成合法请求头的条目跳过并记录日志（保存时已校验，这里只兜底）。
+pub fn merge_custom_headers(headers: &mut HeaderMap, custom: &BTreeMap<String, String>) {
+    for (name, value) in custom {
+        let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) else {
+            tracing::warn!(header = %name, "忽略无效的自定义请求头");
+            continue;
+        };
+        if CREDENTIAL_HEADERS.contains(&name.as_str()) && headers.contains_key(&name) {
+            continue;
+        }
+        headers.insert(name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_custom_headers_keeps_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", HeaderValue::from_static("Bearer real"));
+        headers.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
+
+        let custom = BTreeMap::from([
+            ("Authorization".to_string(), "Bearer custom".to_string()),
+            ("X-Org-Id".to_string(), "org-1".to_string()),
+            ("anthropic-version".to_string(), "2024-01-01".to_string()),
+            ("bad header".to_string(), "x".to_string()),
+        ]);
+        merge_custom_headers(&mut headers, &custom);
+
+        assert_eq!(headers["authorization"], "Bearer real");
+        assert_eq!(headers["x-org-id"], "org-1");
+        assert_eq!(headers["anthropic-version"], "2024-01-01");
+        assert_eq!(headers.len(), 3);
+    }
+}
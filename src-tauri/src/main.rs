@@ -1,9 +1,27 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use duckcoding::data::{network_fs, DataManager, WatchGuard};
+use duckcoding::models::Tool;
+use duckcoding::services::balance::{BalancePoller, PROVIDER_BALANCE_UPDATED_EVENT};
 use duckcoding::services::config::{NotifyWatcherManager, EXTERNAL_CHANGE_EVENT};
+use duckcoding::services::dashboard_manager::{
+    subscribe_dashboard_changes, DASHBOARD_CHANGED_EVENT,
+};
+use duckcoding::services::provider_manager::{
+    invalidate_shared_providers, subscribe_provider_changes, PROVIDERS_CHANGED_EVENT,
+};
+use duckcoding::services::provider_trial::{PROVIDER_TRIALS, PROVIDER_TRIAL_EVENT};
 use duckcoding::services::proxy::config::apply_global_proxy;
-use duckcoding::utils::config::read_global_config;
+use duckcoding::services::proxy::{ProxyRunState, PROXY_CRASHED_EVENT, PROXY_STATUS_EVENT};
+use duckcoding::services::selfcheck::{self, SELFCHECK_COMPLETED_EVENT};
+use duckcoding::services::state_backup::{STATE_BACKUP, STATE_BACKUP_EVENT};
+use duckcoding::services::telemetry::run_telemetry_loop;
+use duckcoding::services::tool::{
+    TOOL_DETECTION_CHANGED_EVENT, TOOL_STATUS_CACHE, TOOL_STATUS_CHANGED_EVENT,
+};
+use duckcoding::services::trash::purge_expired_trash;
+use duckcoding::utils::config::{config_dir, read_global_config};
 use serde::Serialize;
 use std::env;
 use std::sync::Mutex;
@@ -18,10 +36,30 @@ mod setup;
 
 const SINGLE_INSTANCE_EVENT: &str = "single-instance";
 
+/// 应用数据文件（dashboard.json / providers.json / 工具主配置）被修改时发出的事件
+const DATA_FILE_CHANGED_EVENT: &str = "data-file-changed";
+
+/// 数据文件监听的防抖时长（毫秒）
+const DATA_FILE_WATCH_DEBOUNCE_MS: u64 = 300;
+
 struct ExternalWatcherState {
     manager: Mutex<Option<NotifyWatcherManager>>,
 }
 
+/// 数据文件监听句柄（drop 时停止监听）
+struct DataFileWatchState {
+    _guards: Mutex<Vec<WatchGuard>>,
+}
+
+#[derive(Clone, Serialize)]
+struct DataFileChangedPayload {
+    /// dashboard / providers / tool_config
+    kind: &'static str,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_id: Option<String>,
+}
+
 #[derive(Clone, Serialize)]
 struct SingleInstancePayload {
     args: Vec<String>,
@@ -85,8 +123,23 @@ fn setup_working_directory(app: &tauri::App) -> tauri::Result<()> {
     Ok(())
 }
 
+/// 检测配置目录是否位于网络共享（漫游配置文件），命中时放宽锁等待并停用文件监听
+fn detect_network_config_dirs() {
+    let mut paths = Vec::new();
+    match config_dir() {
+        Ok(dir) => paths.push(dir),
+        Err(e) => tracing::warn!(error = %e, "获取配置目录失败，跳过网络共享检测"),
+    }
+    paths.extend(Tool::all().into_iter().map(|tool| tool.config_dir));
+    network_fs::init(&paths);
+}
+
 /// 启动配置文件监听（如果启用）
 fn start_config_watcher(app: &tauri::App) -> tauri::Result<()> {
+    if network_fs::network_backed() {
+        tracing::warn!("配置目录位于网络共享，文件系统通知不可靠，已停用外部改动监听");
+        return Ok(());
+    }
     if let Some(state) = app.try_state::<ExternalWatcherState>() {
         let enable_watch = match read_global_config() {
             Ok(Some(cfg)) => cfg.external_watch_enabled,
@@ -133,7 +186,13 @@ fn schedule_update_check(app_handle: AppHandle) {
         let state = app_handle.state::<UpdateServiceState>();
         match state.service.check_for_updates().await {
             Ok(update_info) => {
-                if update_info.has_update {
+                if update_info.has_update && update_info.skipped {
+                    // 定时检查不再提醒用户已跳过的版本
+                    tracing::debug!(
+                        version = %update_info.latest_version,
+                        "发现新版本，但已被用户跳过"
+                    );
+                } else if update_info.has_update {
                     tracing::info!(
                         version = %update_info.latest_version,
                         "发现新版本"
@@ -149,6 +208,191 @@ fn schedule_update_check(app_handle: AppHandle) {
                 tracing::error!(error = ?e, "启动时检查更新失败");
             }
         }
+
+        // 工具更新结果用于托盘菜单的「有可用更新」标记
+        match check_all_updates().await {
+            Ok(results) => {
+                setup::tray::record_tool_updates(&results);
+                setup::tray::refresh_tray_menu(&app_handle);
+            }
+            Err(e) => tracing::warn!(error = %e, "启动时检查工具更新失败"),
+        }
+    });
+}
+
+/// 转发代理状态事件到前端，并在 setup 阶段自启动代理
+fn start_proxy_supervision(app: &tauri::App) {
+    let manager = app.state::<ProxyManagerState>().manager.clone();
+    let mut events = manager.subscribe();
+    let app_handle = app.handle().clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app_handle.emit(PROXY_STATUS_EVENT, &event) {
+                        tracing::error!(error = ?e, "发送代理状态事件失败");
+                    }
+                    if matches!(event.state, ProxyRunState::Crashed { .. }) {
+                        if let Err(e) = app_handle.emit(PROXY_CRASHED_EVENT, &event) {
+                            tracing::error!(error = ?e, "发送代理崩溃事件失败");
+                        }
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "代理状态事件积压，已跳过部分事件");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    setup::spawn_auto_start_proxies(manager);
+}
+
+/// 启动供应商余额后台轮询，并将余额更新事件转发到前端
+fn start_balance_poller(app: &tauri::App) {
+    let poller = std::sync::Arc::new(BalancePoller::new());
+    let mut events = poller.subscribe();
+    let app_handle = app.handle().clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app_handle.emit(PROVIDER_BALANCE_UPDATED_EVENT, &event) {
+                        tracing::error!(error = ?e, "发送余额更新事件失败");
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "余额更新事件积压，已跳过部分事件");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    tauri::async_runtime::spawn(poller.run());
+}
+
+/// 将后端广播的变化事件转发到所有窗口
+fn forward_changes<T>(
+    app: &tauri::App,
+    mut events: tokio::sync::broadcast::Receiver<T>,
+    event_name: &'static str,
+) where
+    T: Serialize + Clone + Send + 'static,
+{
+    let app_handle = app.handle().clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(payload) => {
+                    if let Err(e) = app_handle.emit(event_name, &payload) {
+                        tracing::error!(event = event_name, error = ?e, "发送变化事件失败");
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(event = event_name, skipped, "变化事件积压，已跳过部分事件");
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// 工具状态、供应商、仪表板写入后通知所有窗口（如主窗口与悬浮状态窗口）保持同步
+fn start_change_events(app: &tauri::App) {
+    forward_changes(
+        app,
+        TOOL_STATUS_CACHE.subscribe(),
+        TOOL_STATUS_CHANGED_EVENT,
+    );
+    forward_changes(
+        app,
+        TOOL_STATUS_CACHE.subscribe_detection(),
+        TOOL_DETECTION_CHANGED_EVENT,
+    );
+    forward_changes(app, subscribe_provider_changes(), PROVIDERS_CHANGED_EVENT);
+    forward_changes(app, subscribe_dashboard_changes(), DASHBOARD_CHANGED_EVENT);
+    forward_changes(app, PROVIDER_TRIALS.subscribe(), PROVIDER_TRIAL_EVENT);
+    forward_changes(app, STATE_BACKUP.subscribe(), STATE_BACKUP_EVENT);
+}
+
+/// 监听应用数据文件与各工具主配置，变化时清理缓存并通知前端
+fn start_data_file_watchers(app: &tauri::App) {
+    let mut targets: Vec<(&'static str, std::path::PathBuf, Option<String>)> = Vec::new();
+    match config_dir() {
+        Ok(dir) => {
+            targets.push(("dashboard", dir.join("dashboard.json"), None));
+            targets.push(("providers", dir.join("providers.json"), None));
+        }
+        Err(e) => tracing::warn!(error = %e, "获取配置目录失败，跳过数据文件监听"),
+    }
+    for tool in Tool::all() {
+        targets.push((
+            "tool_config",
+            tool.config_dir.join(&tool.config_file),
+            Some(tool.id.clone()),
+        ));
+    }
+
+    let mut guards = Vec::new();
+    for (kind, path, tool_id) in targets {
+        // 工具未安装时配置目录可能不存在
+        if !path.parent().is_some_and(|dir| dir.is_dir()) {
+            tracing::debug!(path = ?path, "配置目录不存在，跳过监听");
+            continue;
+        }
+        if network_fs::is_network_path(&path) {
+            tracing::info!(path = ?path, "文件位于网络共享，跳过监听");
+            continue;
+        }
+
+        let app_handle = app.handle().clone();
+        let result =
+            DataManager::global().watch(&path, DATA_FILE_WATCH_DEBOUNCE_MS, move |changed| {
+                DataManager::global().invalidate(changed);
+                if kind == "providers" {
+                    app_handle
+                        .state::<ProviderManagerState>()
+                        .manager
+                        .clear_cache();
+                    invalidate_shared_providers();
+                }
+
+                let payload = DataFileChangedPayload {
+                    kind,
+                    path: changed.to_string_lossy().to_string(),
+                    tool_id: tool_id.clone(),
+                };
+                if let Err(e) = app_handle.emit(DATA_FILE_CHANGED_EVENT, &payload) {
+                    tracing::error!(error = ?e, "发送数据文件变更事件失败");
+                }
+            });
+        match result {
+            Ok(guard) => guards.push(guard),
+            Err(e) => tracing::warn!(path = ?path, error = %e, "启动数据文件监听失败"),
+        }
+    }
+
+    tracing::debug!(count = guards.len(), "数据文件监听已启动");
+    app.manage(DataFileWatchState {
+        _guards: Mutex::new(guards),
+    });
+}
+
+/// 后台执行启动自检，完成后通知前端
+fn start_selfcheck(app: &tauri::App) {
+    let manager = app.state::<ProxyManagerState>().manager.clone();
+    let app_handle = app.handle().clone();
+    tauri::async_runtime::spawn(async move {
+        let running = running_proxy_tools(&manager).await;
+        let items = selfcheck::run_startup_selfcheck(&running).await;
+        if let Err(e) = app_handle.emit(SELFCHECK_COMPLETED_EVENT, &items) {
+            tracing::error!(error = ?e, "发送启动自检事件失败");
+        }
     });
 }
 
@@ -160,7 +404,8 @@ fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     // 2. 设置工作目录
     setup_working_directory(app)?;
 
-    // 3. 启动配置监听
+    // 3. 检测网络共享上的配置目录，启动配置监听
+    detect_network_config_dirs();
     start_config_watcher(app)?;
 
     // 4. 创建系统托盘
@@ -172,6 +417,36 @@ fn setup_app_hooks(app: &mut tauri::App) -> tauri::Result<()> {
     // 6. 启动后检查更新
     schedule_update_check(app.handle().clone());
 
+    // 7. 代理状态事件转发 + 自启动
+    start_proxy_supervision(app);
+
+    // 8. 供应商余额后台轮询
+    start_balance_poller(app);
+
+    // 9. 数据文件变更监听
+    start_data_file_watchers(app);
+
+    // 10. 工具状态、供应商、仪表板变化事件转发
+    start_change_events(app);
+
+    // 11. 后台预热工具状态缓存（需在事件转发之后，检测结果才能推送到前端）
+    app.state::<ToolRegistryState>().spawn_warmup();
+
+    // 12. 匿名使用统计（用户开启后每天最多发送一次）
+    tauri::async_runtime::spawn(run_telemetry_loop());
+
+    // 13. 清除超过保留期限的回收站记录
+    tauri::async_runtime::spawn_blocking(purge_expired_trash);
+
+    // 14. 启动自检（后台执行，结果通过事件推送，不阻塞界面）
+    start_selfcheck(app);
+
+    // 15. 供应商试用到期检查（关闭期间已到期的试用在此补做恢复）
+    tauri::async_runtime::spawn(PROVIDER_TRIALS.run());
+
+    // 16. 完整状态定时备份（失败通过事件通知）
+    tauri::async_runtime::spawn(STATE_BACKUP.run());
+
     Ok(())
 }
 
@@ -259,8 +534,13 @@ fn main() {
         // 工具检测与状态管理
         check_installations,
         refresh_tool_status,
+        get_cached_tool_status,
+        get_package_coordinates,
         check_node_environment,
+        get_node_environments,
+        get_effective_path,
         install_tool,
+        register_extra_detection_path,
         check_update,
         check_update_for_instance,
         refresh_all_tool_versions,
@@ -278,6 +558,8 @@ fn main() {
         generate_api_key_for_tool,
         get_external_changes,
         ack_external_change,
+        get_file_provenance,
+        resolve_effective_config,
         import_native_change,
         // 使用统计
         get_usage_stats,
@@ -290,6 +572,10 @@ fn main() {
         update_balance_config,
         delete_balance_config,
         migrate_balance_from_localstorage,
+        get_provider_balance_info,
+        get_provider_balance_detail_info,
+        get_aggregate_balance,
+        get_balance_history,
         // 窗口管理
         handle_close_action,
         // 代理调试
@@ -316,6 +602,27 @@ fn main() {
         get_proxy_config,
         update_proxy_config,
         get_all_proxy_configs,
+        update_proxy_allowlist,
+        update_proxy_limits,
+        update_proxy_timeouts,
+        configure_tool_for_proxy,
+        unconfigure_tool_for_proxy,
+        // 代理配置档
+        save_proxy_profile,
+        list_proxy_profiles,
+        activate_proxy_profile,
+        delete_proxy_profile,
+        // 代理指标
+        get_proxy_metrics,
+        get_proxy_sessions,
+        export_proxy_usage,
+        export_proxy_access_log,
+        // 代理调试捕获
+        enable_proxy_capture,
+        disable_proxy_capture,
+        get_captured_exchanges,
+        clear_captures,
+        export_captured_exchanges,
         // 会话管理命令
         get_session_list,
         delete_session,
@@ -329,6 +636,8 @@ fn main() {
         save_watcher_settings,
         // 更新管理相关命令
         check_for_app_updates,
+        check_app_update,
+        skip_version,
         download_app_update,
         install_app_update,
         get_app_update_status,
@@ -342,21 +651,59 @@ fn main() {
         get_log_config,
         update_log_config,
         is_release_build,
+        get_log_level,
+        set_log_level,
+        get_log_tail,
+        export_diagnostics,
         // 工具管理命令（工具管理系统）
         get_tool_instances,
         refresh_tool_instances,
         list_wsl_distributions,
+        list_wsl_distros,
         add_wsl_tool_instance,
         add_ssh_tool_instance,
+        test_ssh_connection,
+        list_ssh_config_hosts,
+        create_ssh_instance_from_host,
+        get_ssh_host_key_status,
+        trust_ssh_host_key,
+        refresh_ssh_tool_instance,
+        install_ssh_tool_instance,
         delete_tool_instance,
+        set_tool_instance_label,
+        set_instance_env,
+        get_tool_definitions,
+        export_environment_report,
         // 引导管理命令
         get_onboarding_status,
         save_onboarding_progress,
         complete_onboarding,
         reset_onboarding,
+        get_onboarding_state,
+        mark_onboarding_step,
+        // 匿名使用统计
+        get_telemetry_status,
+        get_telemetry_preview,
+        set_telemetry_enabled,
+        set_telemetry_endpoint,
+        list_active_operations,
+        cancel_operation,
+        reveal_path,
+        setup_tool,
+        cleanup_managed_state,
+        // 启动自检
+        run_startup_selfcheck,
+        get_startup_selfcheck,
         // 单实例模式配置命令
         get_single_instance_config,
         update_single_instance_config,
+        get_app_paths,
+        get_shell_env_capture_config,
+        update_shell_env_capture_config,
+        get_durable_writes_config,
+        update_durable_writes_config,
+        get_backend_locale,
+        set_backend_locale,
         // 开机自启动管理命令
         get_startup_config,
         update_startup_config,
@@ -376,6 +723,21 @@ fn main() {
         update_provider,
         delete_provider,
         validate_provider_config,
+        benchmark_providers,
+        get_provider_benchmarks,
+        start_provider_trial,
+        cancel_provider_trial,
+        get_active_trials,
+        get_state_backup_settings,
+        save_state_backup_settings,
+        run_backup_now,
+        restore_full_backup,
+        // 回收站
+        list_trash,
+        restore_from_trash,
+        purge_trash,
+        discover_external_providers,
+        import_discovered_providers,
         fetch_provider_api_addresses,
         // 令牌资产管理命令（NEW API 集成）
         fetch_provider_tokens,
@@ -392,6 +754,14 @@ fn main() {
         set_tool_instance_selection,
         get_selected_provider_id,
         set_selected_provider_id,
+        repair_dashboard_selections,
+        get_dashboard_snapshot,
+        get_recent_activity,
+        clear_activity,
+        pin_provider,
+        unpin_provider,
+        get_launch_preferences,
+        set_launch_preferences,
     ]);
 
     // 使用自定义事件循环处理 macOS Reopen 事件
@@ -1,8 +1,9 @@
 // Balance 监控数据模型
 //
-// 余额监控配置的持久化存储结构
+// 余额监控配置的持久化存储结构，以及供应商余额的统一模型
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 /// 余额监控配置项
@@ -48,6 +49,208 @@ pub struct BalanceStore {
     pub configs: Vec<BalanceConfig>,
 }
 
+/// NEW API / One API 额度单位换算（500000 额度 = 1 美元）
+pub const QUOTA_PER_USD: f64 = 500_000.0;
+
+/// 供应商余额接口模板（决定请求方式与响应解析方式）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceTemplate {
+    /// NEW API：`/api/user/self`，`{data:{quota, used_quota}}`
+    #[default]
+    NewApi,
+    /// One API 及兼容中转：`/api/user/balance`，`{balance:"12.34"}`（美元）
+    OneApi,
+    /// Anthropic 官方：组织花费报表 `/v1/organizations/cost_report`
+    AnthropicUsage,
+}
+
+impl BalanceTemplate {
+    /// 是否提供按模型汇总的用量统计接口
+    pub fn has_usage_stats(self) -> bool {
+        matches!(self, Self::NewApi)
+    }
+}
+
+/// 余额单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceUnit {
+    /// 美元
+    #[default]
+    Usd,
+    /// NEW API 额度（按 `QUOTA_PER_USD` 换算美元）
+    Quota,
+    /// 无法识别
+    Unknown,
+}
+
+impl BalanceUnit {
+    /// 1 个单位折合的美元数，无法换算时返回 None
+    pub fn usd_per_unit(self) -> Option<f64> {
+        match self {
+            Self::Usd => Some(1.0),
+            Self::Quota => Some(1.0 / QUOTA_PER_USD),
+            Self::Unknown => None,
+        }
+    }
+}
+
+fn default_parsed() -> bool {
+    true
+}
+
+/// 供应商余额
+///
+/// 金额保留供应商原始单位，`unit` 说明如何换算；无法识别响应格式时
+/// `parsed` 为 false，金额为空，仅保留原始响应。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Balance {
+    /// 供应商 ID
+    pub provider_id: String,
+    /// 剩余额度（供应商不提供时为空）
+    pub remaining: Option<f64>,
+    /// 已用额度（供应商不提供时为空）
+    pub used: Option<f64>,
+    /// 金额单位
+    #[serde(default)]
+    pub unit: BalanceUnit,
+    /// 原始响应（写入历史时丢弃）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Value>,
+    /// 是否成功识别响应格式
+    #[serde(default = "default_parsed")]
+    pub parsed: bool,
+    /// 获取时间（Unix 时间戳，毫秒）
+    pub fetched_at: i64,
+}
+
+impl Balance {
+    /// 剩余额度折合美元
+    pub fn remaining_usd(&self) -> Option<f64> {
+        Some(self.remaining? * self.unit.usd_per_unit()?)
+    }
+
+    /// 已用额度折合美元
+    pub fn used_usd(&self) -> Option<f64> {
+        Some(self.used? * self.unit.usd_per_unit()?)
+    }
+}
+
+/// 单个模型在计费周期内的用量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    /// 模型名称
+    pub model: String,
+    /// 请求次数
+    pub requests: u64,
+    /// token 总数
+    pub tokens: u64,
+    /// 花费（单位同 `unit`）
+    pub cost: f64,
+    /// 花费单位
+    pub unit: BalanceUnit,
+    /// 占周期总花费的比例（0~1）
+    pub share: f64,
+}
+
+/// 供应商余额详情：余额摘要 + 按模型的用量明细
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceDetail {
+    /// 余额摘要
+    pub summary: Balance,
+    /// 计费周期起始时间（Unix 时间戳，毫秒）
+    pub period_start: i64,
+    /// 按花费降序的模型用量；供应商不提供统计接口时为空
+    pub models: Vec<ModelUsage>,
+    /// 获取用量明细失败的原因（余额摘要仍然有效）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub models_error: Option<String>,
+}
+
+/// 供应商未计入余额汇总的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceExclusionReason {
+    /// 未配置查询余额所需的凭证
+    MissingCredentials,
+    /// 尚未获取过余额
+    NotFetched,
+    /// 响应格式无法识别
+    Unparsed,
+    /// 供应商不提供剩余额度（如只有花费报表）
+    NoRemaining,
+    /// 金额单位无法换算为美元
+    UnknownUnit,
+}
+
+/// 被排除在汇总之外的供应商
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExcludedBalance {
+    pub provider_id: String,
+    pub reason: BalanceExclusionReason,
+}
+
+/// 所有供应商剩余额度的汇总（美元）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateBalance {
+    /// 剩余额度合计（美元）
+    pub total_remaining_usd: f64,
+    /// 计入合计的供应商 ID
+    pub included: Vec<String>,
+    /// 未计入合计的供应商及原因
+    pub excluded: Vec<ExcludedBalance>,
+    /// 计入合计的余额中最早的获取时间（毫秒），没有计入任何供应商时为空
+    pub oldest_fetched_at: Option<i64>,
+}
+
+/// 余额历史存储结构（balance_history.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceHistoryStore {
+    /// 存储格式版本
+    pub version: u32,
+    /// 各供应商的余额记录（按时间升序）
+    #[serde(default)]
+    pub entries: HashMap<String, Vec<Balance>>,
+}
+
+impl Default for BalanceHistoryStore {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+/// 余额自动轮询设置（保存在全局配置中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalancePollSettings {
+    /// 是否开启后台轮询
+    #[serde(default = "default_poll_enabled")]
+    pub enabled: bool,
+    /// 轮询间隔（分钟）
+    #[serde(default = "default_poll_interval_mins")]
+    pub interval_mins: u32,
+}
+
+fn default_poll_enabled() -> bool {
+    true
+}
+
+fn default_poll_interval_mins() -> u32 {
+    30
+}
+
+impl Default for BalancePollSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_poll_enabled(),
+            interval_mins: default_poll_interval_mins(),
+        }
+    }
+}
+
 impl Default for BalanceStore {
     fn default() -> Self {
         Self {
@@ -1,9 +1,33 @@
 // filepath: e:\DuckCoding\src-tauri\src\models\config.rs
 
 // 全局配置结构，移动到 models 以便在库和二进制之间共享
+use super::balance::BalancePollSettings;
+use super::pricing::PricingSettings;
+use super::state_backup::StateBackupSettings;
+use super::telemetry::TelemetrySettings;
+use super::tool::{PackageCoordinates, ToolStatusCacheSettings};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// 后端文案语言（错误提示、通知）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::EnUs => "en-US",
+        }
+    }
+}
+
 /// 日志级别
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -35,6 +59,17 @@ impl LogLevel {
             LogLevel::Error => "error",
         }
     }
+
+    /// 严重程度（trace 最低，error 最高），用于按级别筛选日志
+    pub fn severity(&self) -> u8 {
+        match self {
+            LogLevel::Trace => 0,
+            LogLevel::Debug => 1,
+            LogLevel::Info => 2,
+            LogLevel::Warn => 3,
+            LogLevel::Error => 4,
+        }
+    }
 }
 
 /// 日志输出格式
@@ -186,6 +221,45 @@ pub struct GlobalConfig {
     /// 开机自启动开关（默认关闭）
     #[serde(default)]
     pub startup_enabled: bool,
+    /// 模型定价覆盖与花费提醒
+    #[serde(default)]
+    pub pricing: PricingSettings,
+    /// 供应商余额后台轮询
+    #[serde(default)]
+    pub balance_poll: BalancePollSettings,
+    /// 启动时捕获登录 shell 环境（仅 macOS，默认开启）
+    #[serde(default = "default_shell_env_capture_enabled")]
+    pub shell_env_capture_enabled: bool,
+    /// 关键状态文件写入后 fsync（默认开启，慢速网络文件系统可关闭）
+    #[serde(default = "default_durable_writes_enabled")]
+    pub durable_writes_enabled: bool,
+    /// 工具状态缓存的有效期与后台刷新策略
+    #[serde(default)]
+    pub tool_status_cache: ToolStatusCacheSettings,
+    /// 离线模式：不发起更新检查等非必要的网络请求
+    #[serde(default)]
+    pub offline_mode: bool,
+    /// 访问 GitHub API 时使用的令牌（可选，用于提高匿名请求的频率限制）
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// 用户选择跳过的版本（对象 ID → 版本），如 `duckcoding` 或工具 ID
+    #[serde(default)]
+    pub skipped_versions: HashMap<String, String>,
+    /// 后端错误提示与通知使用的语言（默认简体中文）
+    #[serde(default)]
+    pub backend_locale: Locale,
+    /// 匿名使用统计（默认关闭）
+    #[serde(default)]
+    pub telemetry: TelemetrySettings,
+    /// 按工具覆盖包坐标（工具 ID → 覆盖的字段），用于 fork 或内部镜像
+    #[serde(default)]
+    pub package_overrides: HashMap<String, PackageCoordinates>,
+    /// 额外的检测路径（置于增强 PATH 最前面），用于 npm prefix 不在 PATH 中等情况
+    #[serde(default)]
+    pub extra_path_entries: Vec<String>,
+    /// 完整状态自动备份（默认关闭）
+    #[serde(default)]
+    pub state_backup: StateBackupSettings,
 }
 
 fn default_proxy_configs() -> HashMap<String, ToolProxyConfig> {
@@ -303,3 +377,11 @@ fn default_external_poll_interval_ms() -> u64 {
 fn default_single_instance_enabled() -> bool {
     true
 }
+
+fn default_shell_env_capture_enabled() -> bool {
+    true
+}
+
+fn default_durable_writes_enabled() -> bool {
+    true
+}
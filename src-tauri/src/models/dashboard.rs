@@ -3,10 +3,137 @@
 // 仪表板状态数据模型
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// dashboard.json 当前结构版本
+///
+/// - v1：`tool_instance_selections` / `selected_provider_id`
+/// - v2：新增按工具的供应商绑定 `tool_provider_bindings`
+/// - v3：新增最近活动记录 `activity`
+/// - v4：新增置顶供应商 `pinned_provider_ids`
+/// - v5：新增按实例的启动偏好 `launch_preferences`
+pub const DASHBOARD_STORE_VERSION: u32 = 5;
+
+/// 最多保留的活动记录条数（超出时丢弃最早的记录）
+pub const MAX_ACTIVITY_ENTRIES: usize = 100;
+
+/// 启动偏好中额外参数的最大个数
+pub const MAX_EXTRA_ARGS: usize = 32;
+
+/// 启动偏好中单个额外参数的最大长度（字节）
+pub const MAX_EXTRA_ARG_LEN: usize = 1024;
+
+/// 启动偏好中环境变量覆盖的最大个数
+pub const MAX_ENV_OVERRIDES: usize = 32;
+
+/// 工具实例的启动偏好（终端集成启动工具时使用）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchPreferences {
+    /// 上次使用的工作目录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// 额外的命令行参数
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+    /// 环境变量覆盖
+    #[serde(default)]
+    pub env_overrides: HashMap<String, String>,
+}
+
+impl LaunchPreferences {
+    /// 校验参数与环境变量（不检查工作目录是否存在）
+    pub fn validate(&self) -> Result<(), String> {
+        if self.extra_args.len() > MAX_EXTRA_ARGS {
+            return Err(format!("额外参数最多 {} 个", MAX_EXTRA_ARGS));
+        }
+        if let Some(arg) = self
+            .extra_args
+            .iter()
+            .find(|arg| arg.len() > MAX_EXTRA_ARG_LEN || arg.contains('\0'))
+        {
+            return Err(format!(
+                "额外参数过长或包含非法字符（上限 {} 字节）: {}",
+                MAX_EXTRA_ARG_LEN,
+                arg.chars().take(32).collect::<String>()
+            ));
+        }
+        validate_env_overrides(&self.env_overrides)
+    }
+}
+
+/// 返回前端的启动偏好，附带实例级环境变量覆盖
+///
+/// 启动工具时先应用实例级覆盖，再应用启动偏好中的覆盖。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LaunchPreferencesView {
+    #[serde(flatten)]
+    pub preferences: LaunchPreferences,
+    /// 实例级环境变量覆盖（敏感值已脱敏）
+    pub instance_env_overrides: BTreeMap<String, String>,
+}
+
+/// 校验环境变量覆盖（个数、变量名与值），启动偏好与工具实例共用
+pub fn validate_env_overrides<'a>(
+    env: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> Result<(), String> {
+    let mut count = 0;
+    for (key, value) in env {
+        count += 1;
+        if count > MAX_ENV_OVERRIDES {
+            return Err(format!("环境变量覆盖最多 {} 个", MAX_ENV_OVERRIDES));
+        }
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("环境变量名无效: {}", key));
+        }
+        if value.len() > MAX_EXTRA_ARG_LEN || value.contains('\0') {
+            return Err(format!("环境变量 {} 的值过长或包含非法字符", key));
+        }
+    }
+    Ok(())
+}
+
+/// 活动类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    /// 安装工具
+    ToolInstalled,
+    /// 更新工具实例
+    ToolUpdated,
+    /// 切换工具的 Profile（供应商配置）
+    ProfileActivated,
+    /// 启动透明代理
+    ProxyStarted,
+    /// 停止透明代理
+    ProxyStopped,
+}
+
+/// 一条活动记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// 发生时间（Unix 时间戳）
+    pub timestamp: i64,
+    pub kind: ActivityKind,
+    /// 活动对象（工具 ID 或实例 ID）
+    pub subject: String,
+    /// 补充说明（如新版本号、Profile 名称）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ActivityEntry {
+    pub fn new(kind: ActivityKind, subject: impl Into<String>, detail: Option<String>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().timestamp(),
+            kind,
+            subject: subject.into(),
+            detail,
+        }
+    }
+}
 
 /// 仪表板配置存储
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DashboardStore {
     /// 数据版本
     pub version: u32,
@@ -15,16 +142,57 @@ pub struct DashboardStore {
     pub tool_instance_selections: HashMap<String, String>,
     /// 最后选中的供应商 ID
     pub selected_provider_id: Option<String>,
+    /// 工具绑定的供应商（key: tool_id, value: provider_id）
+    #[serde(default)]
+    pub tool_provider_bindings: HashMap<String, String>,
+    /// 最近活动记录（按时间升序，最多 `MAX_ACTIVITY_ENTRIES` 条）
+    #[serde(default)]
+    pub activity: Vec<ActivityEntry>,
+    /// 置顶供应商 ID（按置顶顺序）
+    #[serde(default)]
+    pub pinned_provider_ids: Vec<String>,
+    /// 按实例的启动偏好（key: instance_id）
+    #[serde(default)]
+    pub launch_preferences: HashMap<String, LaunchPreferences>,
     /// 最后更新时间（Unix 时间戳）
     pub updated_at: i64,
 }
 
+/// 清理失效选择的结果（`dashboard-selection-cleared` 事件负载）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DashboardSelectionCleared {
+    /// 被移除的实例选择（key: tool_id, value: 失效的 instance_id）
+    pub removed_instance_selections: HashMap<String, String>,
+    /// 被清除的失效供应商 ID
+    pub cleared_provider_id: Option<String>,
+    /// 被移除的供应商绑定（key: tool_id, value: 失效的 provider_id）
+    pub removed_provider_bindings: HashMap<String, String>,
+    /// 被取消置顶的失效供应商 ID
+    pub removed_pinned_provider_ids: Vec<String>,
+    /// 被移除启动偏好的失效实例 ID
+    pub removed_launch_preferences: Vec<String>,
+}
+
+impl DashboardSelectionCleared {
+    pub fn is_empty(&self) -> bool {
+        self.removed_instance_selections.is_empty()
+            && self.cleared_provider_id.is_none()
+            && self.removed_provider_bindings.is_empty()
+            && self.removed_pinned_provider_ids.is_empty()
+            && self.removed_launch_preferences.is_empty()
+    }
+}
+
 impl Default for DashboardStore {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: DASHBOARD_STORE_VERSION,
             tool_instance_selections: HashMap::new(),
             selected_provider_id: None,
+            tool_provider_bindings: HashMap::new(),
+            activity: Vec::new(),
+            pinned_provider_ids: Vec::new(),
+            launch_preferences: HashMap::new(),
             updated_at: chrono::Utc::now().timestamp(),
         }
     }
@@ -37,12 +205,32 @@ mod tests {
     #[test]
     fn test_default_dashboard_store() {
         let store = DashboardStore::default();
-        assert_eq!(store.version, 1);
+        assert_eq!(store.version, DASHBOARD_STORE_VERSION);
         assert!(store.tool_instance_selections.is_empty());
         assert!(store.selected_provider_id.is_none());
         assert!(store.updated_at > 0);
     }
 
+    #[test]
+    fn test_launch_preferences_validate() {
+        let mut prefs = LaunchPreferences {
+            cwd: None,
+            extra_args: vec!["--model".to_string(), "opus".to_string()],
+            env_overrides: HashMap::from([("DEBUG".to_string(), "1".to_string())]),
+        };
+        assert!(prefs.validate().is_ok());
+
+        prefs.extra_args = vec!["x".to_string(); MAX_EXTRA_ARGS + 1];
+        assert!(prefs.validate().is_err());
+
+        prefs.extra_args = vec!["x".repeat(MAX_EXTRA_ARG_LEN + 1)];
+        assert!(prefs.validate().is_err());
+
+        prefs.extra_args.clear();
+        prefs.env_overrides = HashMap::from([("A=B".to_string(), "1".to_string())]);
+        assert!(prefs.validate().is_err());
+    }
+
     #[test]
     fn test_dashboard_store_serialization() {
         let mut selections = HashMap::new();
@@ -53,6 +241,10 @@ mod tests {
             version: 1,
             tool_instance_selections: selections,
             selected_provider_id: Some("duckcoding".to_string()),
+            tool_provider_bindings: HashMap::new(),
+            activity: Vec::new(),
+            pinned_provider_ids: Vec::new(),
+            launch_preferences: HashMap::new(),
             updated_at: 1234567890,
         };
 
@@ -1,18 +1,26 @@
 pub mod balance;
 pub mod config;
 pub mod dashboard;
+pub mod onboarding;
+pub mod pricing;
 pub mod provider;
 pub mod proxy_config;
 pub mod remote_token;
+pub mod state_backup;
+pub mod telemetry;
 pub mod tool;
 pub mod update;
 
 pub use balance::*;
 pub use config::*;
 pub use dashboard::*;
+pub use onboarding::*;
+pub use pricing::*;
 pub use provider::*;
 // 只导出新的 proxy_config 类型，避免与 config.rs 中的旧类型冲突
 pub use proxy_config::{ProxyMetadata, ProxyStore};
 pub use remote_token::*;
+pub use state_backup::*;
+pub use telemetry::*;
 pub use tool::*;
 pub use update::*;
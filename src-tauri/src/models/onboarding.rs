@@ -0,0 +1,122 @@
+// Onboarding State Models
+//
+// 新手引导状态数据模型（onboarding.json）
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 后端记录的引导步骤：本地工具检测完成
+pub const STEP_TOOLS_DETECTED: &str = "tools_detected";
+
+/// 后端记录的引导步骤：已将供应商配置应用到工具
+pub const STEP_PROVIDER_APPLIED: &str = "provider_applied";
+
+/// 完成引导所需的后端步骤（用于仪表板的「继续完成设置」提示）
+pub const REQUIRED_STEPS: &[&str] = &[STEP_TOOLS_DETECTED, STEP_PROVIDER_APPLIED];
+
+/// 新手引导状态
+///
+/// 步骤 ID 为任意字符串：前端向导的步骤 ID 与后端记录的步骤（`STEP_*`）共用同一命名空间。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingState {
+    /// 已完成的步骤 → 完成时间（Unix 秒）
+    #[serde(default)]
+    pub completed_steps: BTreeMap<String, i64>,
+    /// 用户跳过的步骤
+    #[serde(default)]
+    pub skipped_steps: Vec<String>,
+    /// 引导过程中选择（检测到或已配置）的工具 ID
+    #[serde(default)]
+    pub chosen_tools: Vec<String>,
+    /// 首次记录步骤的时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<i64>,
+    /// 最后更新时间（Unix 秒）
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl OnboardingState {
+    /// 记录步骤完成（重复完成保留首次时间，并从跳过列表移除）
+    pub fn mark_completed(&mut self, step: &str, now: i64) {
+        self.touch(now);
+        self.completed_steps.entry(step.to_string()).or_insert(now);
+        self.skipped_steps.retain(|s| s != step);
+    }
+
+    /// 记录步骤被跳过（已完成的步骤不受影响）
+    pub fn mark_skipped(&mut self, step: &str, now: i64) {
+        self.touch(now);
+        if !self.completed_steps.contains_key(step) && !self.skipped_steps.iter().any(|s| s == step)
+        {
+            self.skipped_steps.push(step.to_string());
+        }
+    }
+
+    /// 记录选择的工具（去重，保持首次出现顺序）
+    pub fn add_chosen_tools<'a>(&mut self, tool_ids: impl IntoIterator<Item = &'a str>) {
+        for tool_id in tool_ids {
+            if !self.chosen_tools.iter().any(|t| t == tool_id) {
+                self.chosen_tools.push(tool_id.to_string());
+            }
+        }
+    }
+
+    pub fn is_completed(&self, step: &str) -> bool {
+        self.completed_steps.contains_key(step)
+    }
+
+    /// 尚未完成也未跳过的必需步骤
+    pub fn pending_steps(&self) -> Vec<&'static str> {
+        REQUIRED_STEPS
+            .iter()
+            .copied()
+            .filter(|step| {
+                !self.is_completed(step) && !self.skipped_steps.iter().any(|s| s == step)
+            })
+            .collect()
+    }
+
+    fn touch(&mut self, now: i64) {
+        self.started_at.get_or_insert(now);
+        self.updated_at = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_completed_keeps_first_timestamp_and_clears_skip() {
+        let mut state = OnboardingState::default();
+        state.mark_skipped(STEP_PROVIDER_APPLIED, 10);
+        assert_eq!(state.pending_steps(), vec![STEP_TOOLS_DETECTED]);
+
+        state.mark_completed(STEP_PROVIDER_APPLIED, 20);
+        state.mark_completed(STEP_PROVIDER_APPLIED, 30);
+        assert_eq!(state.completed_steps[STEP_PROVIDER_APPLIED], 20);
+        assert!(state.skipped_steps.is_empty());
+        assert_eq!(state.started_at, Some(10));
+        assert_eq!(state.updated_at, 30);
+    }
+
+    #[test]
+    fn test_mark_skipped_ignores_completed_step() {
+        let mut state = OnboardingState::default();
+        state.mark_completed(STEP_TOOLS_DETECTED, 1);
+        state.mark_skipped(STEP_TOOLS_DETECTED, 2);
+        state.mark_skipped("welcome", 3);
+        state.mark_skipped("welcome", 4);
+        assert_eq!(state.skipped_steps, vec!["welcome".to_string()]);
+        assert_eq!(state.pending_steps(), vec![STEP_PROVIDER_APPLIED]);
+    }
+
+    #[test]
+    fn test_add_chosen_tools_dedups() {
+        let mut state = OnboardingState::default();
+        state.add_chosen_tools(["codex", "claude-code"]);
+        state.add_chosen_tools(["codex"]);
+        assert_eq!(state.chosen_tools, vec!["codex", "claude-code"]);
+    }
+}
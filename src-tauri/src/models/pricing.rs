@@ -0,0 +1,40 @@
+// 模型定价数据模型
+//
+// 默认定价表随应用内置（resources/model_pricing.json），用户可在全局配置中按模型或供应商覆盖
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 单个模型的单价（美元 / 百万 token）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelRate {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+/// 定价表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingTable {
+    /// 未知模型使用的默认单价
+    pub default_rate: ModelRate,
+    /// 模型名前缀 → 单价（按最长前缀匹配）
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelRate>,
+}
+
+/// 用户定价覆盖（保存在全局配置中）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PricingSettings {
+    /// 覆盖内置的默认单价
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_rate: Option<ModelRate>,
+    /// 按模型覆盖（对所有供应商生效）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_overrides: BTreeMap<String, ModelRate>,
+    /// 按供应商覆盖：供应商 ID → 模型名前缀 → 单价
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub provider_overrides: BTreeMap<String, BTreeMap<String, ModelRate>>,
+    /// 当日预估花费达到该值（美元）时提醒，None 表示不提醒
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_spend_alert_usd: Option<f64>,
+}
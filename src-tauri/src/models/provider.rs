@@ -2,7 +2,16 @@
 //
 // 供应商配置数据模型
 
+use super::balance::BalanceTemplate;
+use crate::core::LocalizedError;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 不允许自定义的请求头（由 HTTP 客户端维护）
+const RESERVED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", "connection"];
+
+/// 名称包含这些片段的请求头（或环境变量）视为敏感，只读展示时脱敏
+const SECRET_HEADER_HINTS: &[&str] = &["auth", "key", "token", "secret", "password", "cookie"];
 
 /// 供应商配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +29,15 @@ pub struct Provider {
     pub user_id: String,
     /// 系统访问令牌
     pub access_token: String,
+    /// 模型调用 API Key（透明代理凭证注入使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// 余额接口模板
+    #[serde(default)]
+    pub balance_template: BalanceTemplate,
+    /// 自定义请求头（验证、余额查询与透明代理转发时附加）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom_headers: BTreeMap<String, String>,
     /// 用户名（可选，用于确认）
     pub username: Option<String>,
     /// 是否为默认供应商
@@ -30,6 +48,75 @@ pub struct Provider {
     pub updated_at: i64,
 }
 
+impl Provider {
+    /// 返回脱敏副本（隐藏访问令牌与 API Key，用于只读展示）
+    pub fn masked(&self) -> Self {
+        Self {
+            access_token: mask_secret(&self.access_token),
+            api_key: self.api_key.as_deref().map(mask_secret),
+            custom_headers: self
+                .custom_headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = if is_secret_name(name) {
+                        mask_secret(value)
+                    } else {
+                        value.clone()
+                    };
+                    (name.clone(), value)
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// 校验自定义请求头：名称须为 RFC 7230 token，不能是保留请求头，值不能包含控制字符
+    pub fn validate_custom_headers(&self) -> Result<(), LocalizedError> {
+        for (name, value) in &self.custom_headers {
+            if name.is_empty() || !name.bytes().all(is_token_char) {
+                return Err(
+                    LocalizedError::new("VALIDATION", "provider.header_name_invalid")
+                        .arg("name", name),
+                );
+            }
+            if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                return Err(
+                    LocalizedError::new("VALIDATION", "provider.header_reserved").arg("name", name),
+                );
+            }
+            if value.bytes().any(|b| (b < 0x20 && b != b'\t') || b == 0x7f) {
+                return Err(
+                    LocalizedError::new("VALIDATION", "provider.header_value_invalid")
+                        .arg("name", name),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// RFC 7230 token 字符
+fn is_token_char(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// 请求头或环境变量名是否表示敏感值
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_HEADER_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// 只保留首尾各 4 个字符（过短时整体替换为 `****`）
+pub(crate) fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return "****".to_string();
+    }
+    let prefix: String = chars[..4].iter().collect();
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", prefix, suffix)
+}
+
 /// 供应商存储结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderStore {
@@ -53,6 +140,9 @@ impl Default for ProviderStore {
                 api_address: Some("https://jp.duckcoding.com".to_string()),
                 user_id: String::new(),
                 access_token: String::new(),
+                api_key: None,
+                balance_template: Default::default(),
+                custom_headers: BTreeMap::new(),
                 username: None,
                 is_default: true,
                 created_at: now,
@@ -77,6 +167,64 @@ mod tests {
         assert!(store.providers[0].is_default);
     }
 
+    #[test]
+    fn test_provider_masked() {
+        let mut provider = ProviderStore::default().providers.remove(0);
+        provider.access_token = "sk-abcdefghijklmnop".to_string();
+        provider.api_key = Some("short".to_string());
+
+        let masked = provider.masked();
+        assert_eq!(masked.access_token, "sk-a...mnop");
+        assert_eq!(masked.api_key.as_deref(), Some("****"));
+        assert_eq!(masked.id, provider.id);
+    }
+
+    #[test]
+    fn test_custom_headers_masked_and_round_trip() {
+        let mut provider = ProviderStore::default().providers.remove(0);
+        provider.custom_headers = BTreeMap::from([
+            ("X-Org-Id".to_string(), "org-123456789".to_string()),
+            ("X-Relay-Key".to_string(), "relay-secret-value".to_string()),
+        ]);
+
+        let masked = provider.masked();
+        assert_eq!(masked.custom_headers["X-Org-Id"], "org-123456789");
+        assert_eq!(masked.custom_headers["X-Relay-Key"], "rela...alue");
+
+        let json = serde_json::to_string(&provider).unwrap();
+        let deserialized: Provider = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.custom_headers, provider.custom_headers);
+
+        // 旧数据没有 custom_headers 字段，空表也不写出
+        provider.custom_headers.clear();
+        let json = serde_json::to_value(&provider).unwrap();
+        assert!(json.get("custom_headers").is_none());
+        let deserialized: Provider = serde_json::from_value(json).unwrap();
+        assert!(deserialized.custom_headers.is_empty());
+    }
+
+    #[test]
+    fn test_validate_custom_headers() {
+        let mut provider = ProviderStore::default().providers.remove(0);
+        let check = |provider: &Provider| provider.validate_custom_headers().map_err(|e| e.key);
+
+        provider.custom_headers = BTreeMap::from([("X-Org-Id".to_string(), "42".to_string())]);
+        assert_eq!(check(&provider), Ok(()));
+
+        provider.custom_headers = BTreeMap::from([("X Org".to_string(), "42".to_string())]);
+        assert_eq!(check(&provider), Err("provider.header_name_invalid"));
+
+        provider.custom_headers = BTreeMap::from([("Host".to_string(), "evil".to_string())]);
+        assert_eq!(check(&provider), Err("provider.header_reserved"));
+
+        provider.custom_headers = BTreeMap::from([("content-length".to_string(), "1".to_string())]);
+        assert_eq!(check(&provider), Err("provider.header_reserved"));
+
+        provider.custom_headers =
+            BTreeMap::from([("X-Org-Id".to_string(), "a\r\nInjected: 1".to_string())]);
+        assert_eq!(check(&provider), Err("provider.header_value_invalid"));
+    }
+
     #[test]
     fn test_provider_serialization() {
         let provider = Provider {
@@ -86,6 +234,9 @@ mod tests {
             api_address: Some("https://api.test.com".to_string()),
             user_id: "12345".to_string(),
             access_token: "token123".to_string(),
+            api_key: None,
+            balance_template: Default::default(),
+            custom_headers: BTreeMap::new(),
             username: Some("testuser".to_string()),
             is_default: false,
             created_at: 1234567890,
@@ -2,6 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 /// 单个工具的透明代理配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +26,255 @@ pub struct ToolProxyConfig {
     /// 启动代理前激活的 Profile 名称（用于关闭时还原）
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub original_active_profile: Option<String>,
+    /// 凭证注入配置（由代理注入绑定供应商的真实凭证）
+    #[serde(default)]
+    pub credential_injection: CredentialInjectionConfig,
+    /// 上游限流/过载时的重试策略（默认关闭）
+    #[serde(default)]
+    pub retry_policy: RetryPolicyConfig,
+    /// 按供应商覆盖的重试策略（key 为供应商 ID 或上游主机名）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub provider_retry_overrides: HashMap<String, RetryPolicyConfig>,
+    /// 允许转发的路径前缀白名单，其余请求直接返回 403
+    #[serde(default = "default_allowed_path_prefixes")]
+    pub allowed_path_prefixes: Vec<String>,
+    /// 工具配置已指向本地代理时的记录（用于还原）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_routing: Option<ToolRoutingState>,
+    /// 并发连接与上游请求上限
+    #[serde(default)]
+    pub limits: ConcurrencyLimits,
+    /// 是否为健康检查请求（`/__duckcoding/health`）输出访问日志
+    #[serde(default)]
+    pub log_health_checks: bool,
+    /// 停止或重启监听时，等待进行中请求（含流式响应）完成的最长时间（秒）
+    #[serde(default = "default_drain_timeout_secs")]
+    pub drain_timeout_secs: u64,
+    /// 上游请求超时
+    #[serde(default)]
+    pub timeouts: UpstreamTimeouts,
+    /// 是否提供 Prometheus 指标端点（`/__duckcoding/metrics`）
+    #[serde(default = "default_metrics_endpoint_enabled")]
+    pub metrics_endpoint_enabled: bool,
+    /// 按供应商开启的协议转换（key 为供应商 ID 或上游主机名）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub provider_translations: HashMap<String, ApiTranslation>,
+}
+
+/// 上游协议转换模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiTranslation {
+    /// 不转换，原样转发
+    #[default]
+    None,
+    /// Anthropic Messages 请求转换为 OpenAI Chat Completions（供应商仅提供 OpenAI 兼容接口）
+    AnthropicToOpenaiChat,
+}
+
+/// 上游请求分阶段超时（秒，0 表示不限制）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpstreamTimeouts {
+    /// 建立连接
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_secs: u64,
+    /// 发出请求到收到响应头
+    #[serde(default = "default_first_byte_timeout_secs")]
+    pub first_byte_secs: u64,
+    /// 非流式请求总时长
+    #[serde(default = "default_total_timeout_secs")]
+    pub total_secs: u64,
+    /// 流式响应相邻分块的最长间隔
+    #[serde(default = "default_stream_idle_timeout_secs")]
+    pub stream_idle_secs: u64,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_first_byte_timeout_secs() -> u64 {
+    60
+}
+
+fn default_total_timeout_secs() -> u64 {
+    600
+}
+
+fn default_stream_idle_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for UpstreamTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: default_connect_timeout_secs(),
+            first_byte_secs: default_first_byte_timeout_secs(),
+            total_secs: default_total_timeout_secs(),
+            stream_idle_secs: default_stream_idle_timeout_secs(),
+        }
+    }
+}
+
+fn default_drain_timeout_secs() -> u64 {
+    30
+}
+
+fn default_metrics_endpoint_enabled() -> bool {
+    true
+}
+
+/// 并发上限（0 表示不限制）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcurrencyLimits {
+    /// 最大并发连接数
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+    /// 最大进行中的上游请求数（流式请求在流结束前一直占用）
+    #[serde(default = "default_max_in_flight_requests")]
+    pub max_in_flight_requests: usize,
+    /// 拒绝时返回的 Retry-After（秒）
+    #[serde(default = "default_limit_retry_after_secs")]
+    pub retry_after_secs: u64,
+}
+
+fn default_max_connections() -> usize {
+    256
+}
+
+fn default_max_in_flight_requests() -> usize {
+    64
+}
+
+fn default_limit_retry_after_secs() -> u64 {
+    1
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        Self {
+            max_connections: default_max_connections(),
+            max_in_flight_requests: default_max_in_flight_requests(),
+            retry_after_secs: default_limit_retry_after_secs(),
+        }
+    }
+}
+
+/// 工具已接入本地代理的记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolRoutingState {
+    /// 写入工具配置的代理地址
+    pub proxy_url: String,
+    pub applied_at: DateTime<Utc>,
+    /// 修改前的原始值（`None` 表示原本不存在该项）
+    pub originals: BTreeMap<String, Option<String>>,
+}
+
+/// 默认端点白名单（覆盖 Claude Code / Codex / Gemini CLI 的 API 调用）
+pub const DEFAULT_ALLOWED_PATH_PREFIXES: &[&str] = &[
+    "/v1/messages",
+    "/v1/chat/completions",
+    "/v1/models",
+    "/v1/responses",
+    // Gemini CLI 使用 v1beta 接口
+    "/v1beta/models",
+];
+
+fn default_allowed_path_prefixes() -> Vec<String> {
+    DEFAULT_ALLOWED_PATH_PREFIXES
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+
+/// 同供应商重试策略
+///
+/// 仅在上游返回 429 / 529（overloaded_error）/ 503 且尚未向客户端发送任何字节时生效。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicyConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 最大重试次数（不含首次请求）
+    #[serde(default = "default_retry_max_retries")]
+    pub max_retries: u32,
+    /// 指数退避基础延迟（毫秒）
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    /// 单次等待上限（毫秒），同时约束 Retry-After
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// 允许重试的请求体大小上限（字节）
+    #[serde(default = "default_retry_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+fn default_retry_max_retries() -> u32 {
+    2
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    10_000
+}
+
+fn default_retry_max_body_bytes() -> usize {
+    4 * 1024 * 1024
+}
+
+impl Default for RetryPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_retries: default_retry_max_retries(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            max_body_bytes: default_retry_max_body_bytes(),
+        }
+    }
+}
+
+/// 凭证注入配置
+///
+/// 启用后，匹配的请求会移除客户端携带的 Authorization / x-api-key，
+/// 改用绑定供应商的 API Key 转发，本地工具只需配置占位密钥。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CredentialInjectionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 绑定的供应商 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// 匹配的路径前缀（为空时使用工具默认前缀）
+    #[serde(default)]
+    pub path_prefixes: Vec<String>,
+    /// 客户端识别 header（命中即注入，与路径匹配为"或"关系）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_header: Option<ClientHeaderMatch>,
+}
+
+/// 客户端识别 header 匹配规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientHeaderMatch {
+    /// header 名称（大小写不敏感）
+    pub name: String,
+    /// 期望值（为空时仅要求 header 存在）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl CredentialInjectionConfig {
+    /// 工具默认的注入路径前缀
+    pub fn default_path_prefixes(tool_id: &str) -> &'static [&'static str] {
+        match tool_id {
+            "claude-code" => &["/v1/messages"],
+            "codex" => &["/v1/chat/completions", "/v1/responses"],
+            "gemini-cli" => &["/v1beta/models", "/v1/models"],
+            _ => &[],
+        }
+    }
 }
 
 impl ToolProxyConfig {
@@ -41,9 +291,33 @@ impl ToolProxyConfig {
             session_endpoint_config_enabled: false,
             auto_start: false,
             original_active_profile: None,
+            credential_injection: CredentialInjectionConfig::default(),
+            retry_policy: RetryPolicyConfig::default(),
+            provider_retry_overrides: HashMap::new(),
+            allowed_path_prefixes: default_allowed_path_prefixes(),
+            tool_routing: None,
+            limits: ConcurrencyLimits::default(),
+            log_health_checks: false,
+            drain_timeout_secs: default_drain_timeout_secs(),
+            timeouts: UpstreamTimeouts::default(),
+            metrics_endpoint_enabled: default_metrics_endpoint_enabled(),
+            provider_translations: HashMap::new(),
         }
     }
 
+    /// 路径是否在端点白名单内
+    ///
+    /// 按路径段匹配：`/v1/models` 允许 `/v1/models/xxx`，但不允许 `/v1/models-admin`。
+    /// 白名单为空时拒绝所有请求。
+    pub fn is_path_allowed(&self, path: &str) -> bool {
+        self.allowed_path_prefixes.iter().any(|prefix| {
+            let prefix = prefix.trim_end_matches('/');
+            !prefix.is_empty()
+                && path.starts_with(prefix)
+                && matches!(path.as_bytes().get(prefix.len()), None | Some(b'/'))
+        })
+    }
+
     /// 默认端口配置
     pub fn default_port(tool_id: &str) -> u16 {
         match tool_id {
@@ -55,25 +329,67 @@ impl ToolProxyConfig {
     }
 }
 
+/// proxy.json 当前结构版本
+///
+/// - v1：无 `schema_version` 字段（2.1.0 及之前）
+/// - v2：新增 `schema_version`，各工具配置显式写出带默认值的字段
+///
+/// 仅在已有数据需要转换时提升版本；新增带 `#[serde(default)]` 的字段不需要迁移，
+/// 也不提升版本，以免旧版本应用无法读取。
+pub const PROXY_STORE_SCHEMA_VERSION: u32 = 2;
+
+fn default_schema_version() -> u32 {
+    PROXY_STORE_SCHEMA_VERSION
+}
+
 /// proxy.json 顶层结构
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyStore {
+    /// 结构版本（用于迁移，与应用版本号 `version` 无关）
+    #[serde(default = "default_schema_version")]
+    pub schema_version: u32,
     pub version: String,
     #[serde(rename = "claude-code")]
     pub claude_code: ToolProxyConfig,
     pub codex: ToolProxyConfig,
     #[serde(rename = "gemini-cli")]
     pub gemini_cli: ToolProxyConfig,
+    /// 命名代理配置档（key 为工具 ID，value 为 名称 → 配置档）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, BTreeMap<String, ProxyProfile>>,
+    /// 各工具当前激活的配置档名称
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub active_profiles: HashMap<String, String>,
     pub metadata: ProxyMetadata,
 }
 
+/// 命名代理配置档（某个工具代理配置的快照）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyProfile {
+    pub config: ToolProxyConfig,
+    pub saved_at: DateTime<Utc>,
+}
+
+/// 代理配置档摘要（用于列表展示）
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyProfileInfo {
+    pub name: String,
+    pub port: u16,
+    pub allow_public: bool,
+    pub saved_at: DateTime<Utc>,
+    pub active: bool,
+}
+
 impl ProxyStore {
     pub fn new() -> Self {
         Self {
+            schema_version: PROXY_STORE_SCHEMA_VERSION,
             version: "2.1.0".to_string(),
             claude_code: ToolProxyConfig::new(8787),
             codex: ToolProxyConfig::new(8788),
             gemini_cli: ToolProxyConfig::new(8789),
+            profiles: HashMap::new(),
+            active_profiles: HashMap::new(),
             metadata: ProxyMetadata {
                 last_updated: Utc::now(),
             },
@@ -122,3 +438,41 @@ impl Default for ProxyStore {
 pub struct ProxyMetadata {
     pub last_updated: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_allowlist_keeps_clients_working() {
+        let config = ToolProxyConfig::new(8787);
+        assert!(config.is_path_allowed("/v1/messages"));
+        assert!(config.is_path_allowed("/v1/messages/count_tokens"));
+        assert!(config.is_path_allowed("/v1/chat/completions"));
+        assert!(config.is_path_allowed("/v1/responses"));
+        assert!(config.is_path_allowed("/v1/models"));
+        assert!(config.is_path_allowed("/v1beta/models/gemini-2.5-pro:streamGenerateContent"));
+
+        assert!(!config.is_path_allowed("/"));
+        assert!(!config.is_path_allowed("/admin"));
+        assert!(!config.is_path_allowed("/v1/models-admin"));
+    }
+
+    #[test]
+    fn test_allowlist_missing_in_old_config_uses_default() {
+        let config: ToolProxyConfig =
+            serde_json::from_str(r#"{"enabled": true, "port": 8787}"#).unwrap();
+        assert_eq!(
+            config.allowed_path_prefixes,
+            default_allowed_path_prefixes()
+        );
+
+        let mut config = config;
+        config.allowed_path_prefixes = vec!["/custom/".to_string()];
+        assert!(config.is_path_allowed("/custom/path"));
+        assert!(!config.is_path_allowed("/v1/messages"));
+
+        config.allowed_path_prefixes.clear();
+        assert!(!config.is_path_allowed("/v1/messages"));
+    }
+}
@@ -0,0 +1,156 @@
+// State Backup Models
+//
+// 完整状态备份的设置（保存在全局配置中）与备份、恢复结果
+
+use serde::{Deserialize, Serialize};
+
+/// 默认保留的备份份数
+pub const DEFAULT_BACKUP_RETENTION: u32 = 7;
+
+/// 自动备份频率
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupSchedule {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl BackupSchedule {
+    /// 两次自动备份的间隔（秒），关闭时为 None
+    pub fn interval_secs(self) -> Option<i64> {
+        match self {
+            Self::Off => None,
+            Self::Daily => Some(24 * 60 * 60),
+            Self::Weekly => Some(7 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// 备份内容组成
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupComponent {
+    /// 供应商（providers.json，含 API Key）
+    Providers,
+    /// 工具实例数据库（tools.db）
+    InstanceDb,
+    /// 仪表板状态（dashboard.json）
+    Dashboard,
+    /// 透明代理配置（proxy.json，含代理密钥）
+    ProxyConfig,
+    /// Profile 与激活状态（profiles.json、active.json，含 API Key）
+    Profiles,
+}
+
+impl BackupComponent {
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Providers,
+            Self::InstanceDb,
+            Self::Dashboard,
+            Self::ProxyConfig,
+            Self::Profiles,
+        ]
+    }
+
+    /// 是否包含密钥（包含时备份必须加密）
+    pub fn contains_secrets(self) -> bool {
+        matches!(self, Self::Providers | Self::ProxyConfig | Self::Profiles)
+    }
+
+    /// 组成对应的配置目录下的 JSON 文件（实例数据库为空）
+    pub fn files(self) -> &'static [&'static str] {
+        match self {
+            Self::Providers => &["providers.json"],
+            Self::InstanceDb => &[],
+            Self::Dashboard => &["dashboard.json"],
+            Self::ProxyConfig => &["proxy.json"],
+            Self::Profiles => &["profiles.json", "active.json"],
+        }
+    }
+}
+
+/// 完整状态备份设置（保存在全局配置中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBackupSettings {
+    /// 自动备份频率（默认关闭）
+    #[serde(default)]
+    pub schedule: BackupSchedule,
+    /// 备份目录（如同步盘中的目录）
+    #[serde(default)]
+    pub target_dir: Option<String>,
+    /// 保留的备份份数
+    #[serde(default = "default_backup_retention")]
+    pub retention: u32,
+    /// 备份的内容
+    #[serde(default = "BackupComponent::all")]
+    pub components: Vec<BackupComponent>,
+    /// 加密口令（备份包含密钥时必填）
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+fn default_backup_retention() -> u32 {
+    DEFAULT_BACKUP_RETENTION
+}
+
+impl Default for StateBackupSettings {
+    fn default() -> Self {
+        Self {
+            schedule: BackupSchedule::Off,
+            target_dir: None,
+            retention: DEFAULT_BACKUP_RETENTION,
+            components: BackupComponent::all(),
+            passphrase: None,
+        }
+    }
+}
+
+impl StateBackupSettings {
+    /// 所选内容是否包含密钥
+    pub fn includes_secrets(&self) -> bool {
+        self.components.iter().any(|c| c.contains_secrets())
+    }
+
+    /// 非空的加密口令
+    pub fn passphrase(&self) -> Option<&str> {
+        self.passphrase.as_deref().filter(|p| !p.is_empty())
+    }
+}
+
+/// 单个组成的备份或恢复结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentResult {
+    pub component: BackupComponent,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 一次备份的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRunResult {
+    /// 生成的备份文件
+    pub path: String,
+    pub encrypted: bool,
+    pub created_at: i64,
+    pub size: u64,
+    pub components: Vec<ComponentResult>,
+    /// 按保留份数删除的旧备份
+    pub pruned: Vec<String>,
+}
+
+/// 一次完整恢复的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// 备份创建时间
+    pub created_at: i64,
+    /// 创建备份的 DuckCoding 版本
+    pub app_version: String,
+    /// 恢复前自动生成的安全快照（可用于撤销本次恢复）
+    pub safety_snapshot: Option<String>,
+    pub components: Vec<ComponentResult>,
+    /// 所有组成均恢复成功
+    pub success: bool,
+}
@@ -0,0 +1,242 @@
+// Telemetry Models
+//
+// 匿名使用统计数据模型：设置保存在全局配置中，计数与发送历史保存在 telemetry.json
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 默认上报地址
+pub const DEFAULT_TELEMETRY_ENDPOINT: &str = "https://mirror.duckcoding.com/api/v1/telemetry";
+
+/// 上报格式版本
+pub const TELEMETRY_SCHEMA_VERSION: u32 = 1;
+
+/// 匿名使用统计设置（保存在全局配置中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetrySettings {
+    /// 是否开启（默认关闭，需用户主动同意）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 是否已在引导中询问过用户
+    #[serde(default)]
+    pub prompted: bool,
+    /// 上报地址
+    #[serde(default = "default_telemetry_endpoint")]
+    pub endpoint: String,
+}
+
+fn default_telemetry_endpoint() -> String {
+    DEFAULT_TELEMETRY_ENDPOINT.to_string()
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            prompted: false,
+            endpoint: default_telemetry_endpoint(),
+        }
+    }
+}
+
+/// 允许统计的事件（只记录功能名称，不带任何参数）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    /// 启动透明代理
+    ProxyStarted,
+    /// 直接将供应商配置应用到工具（切换 Profile）
+    ProfileApplied,
+    /// 一键配置工具
+    ToolSetup,
+    /// 检测本地工具
+    LocalToolsDetected,
+    /// 添加 WSL 工具实例
+    WslInstanceAdded,
+    /// 添加 SSH 工具实例
+    SshInstanceAdded,
+    /// 安装工具
+    ToolInstalled,
+    /// 更新工具实例
+    ToolUpdated,
+    /// 导出诊断包
+    DiagnosticsExported,
+}
+
+impl TelemetryEvent {
+    /// 所有允许统计的事件
+    pub const ALL: &'static [TelemetryEvent] = &[
+        TelemetryEvent::ProxyStarted,
+        TelemetryEvent::ProfileApplied,
+        TelemetryEvent::ToolSetup,
+        TelemetryEvent::LocalToolsDetected,
+        TelemetryEvent::WslInstanceAdded,
+        TelemetryEvent::SshInstanceAdded,
+        TelemetryEvent::ToolInstalled,
+        TelemetryEvent::ToolUpdated,
+        TelemetryEvent::DiagnosticsExported,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TelemetryEvent::ProxyStarted => "proxy_started",
+            TelemetryEvent::ProfileApplied => "profile_applied",
+            TelemetryEvent::ToolSetup => "tool_setup",
+            TelemetryEvent::LocalToolsDetected => "local_tools_detected",
+            TelemetryEvent::WslInstanceAdded => "wsl_instance_added",
+            TelemetryEvent::SshInstanceAdded => "ssh_instance_added",
+            TelemetryEvent::ToolInstalled => "tool_installed",
+            TelemetryEvent::ToolUpdated => "tool_updated",
+            TelemetryEvent::DiagnosticsExported => "diagnostics_exported",
+        }
+    }
+}
+
+/// 一次上报的完整内容（预览与实际发送使用同一结构）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryPing {
+    pub schema_version: u32,
+    pub app_version: String,
+    /// 操作系统（windows / macos / linux）
+    pub os: String,
+    /// 统计区间（Unix 秒）
+    pub period_start: i64,
+    pub period_end: i64,
+    /// 事件名 → 次数
+    pub counters: BTreeMap<String, u64>,
+}
+
+/// 一条已发送记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetrySendRecord {
+    /// 发送时间（Unix 秒）
+    pub sent_at: i64,
+    pub endpoint: String,
+    pub ping: TelemetryPing,
+}
+
+/// 本地统计状态（telemetry.json）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryState {
+    /// 待发送的计数（事件名 → 次数）
+    #[serde(default)]
+    pub queued: BTreeMap<String, u64>,
+    /// 待发送计数的起始时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queued_since: Option<i64>,
+    /// 最近一次发送成功的时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sent_at: Option<i64>,
+    /// 全部发送历史（按时间升序）
+    #[serde(default)]
+    pub history: Vec<TelemetrySendRecord>,
+}
+
+impl TelemetryState {
+    /// 事件计数加一
+    pub fn increment(&mut self, event: TelemetryEvent, now: i64) {
+        self.queued_since.get_or_insert(now);
+        *self.queued.entry(event.as_str().to_string()).or_insert(0) += 1;
+    }
+
+    /// 清空待发送的计数
+    pub fn clear_queue(&mut self) {
+        self.queued.clear();
+        self.queued_since = None;
+    }
+
+    /// 记录发送成功：扣除已发送的计数（发送期间新增的计数保留到下次）
+    pub fn mark_sent(&mut self, record: TelemetrySendRecord) {
+        for (event, count) in &record.ping.counters {
+            if let Some(queued) = self.queued.get_mut(event) {
+                *queued = queued.saturating_sub(*count);
+            }
+        }
+        self.queued.retain(|_, count| *count > 0);
+        self.queued_since = if self.queued.is_empty() {
+            None
+        } else {
+            Some(record.sent_at)
+        };
+        self.last_sent_at = Some(record.sent_at);
+        self.history.push(record);
+    }
+
+    /// 距上次发送是否已满 `interval_secs`（从未发送视为已满）
+    pub fn is_due(&self, now: i64, interval_secs: i64) -> bool {
+        self.last_sent_at
+            .is_none_or(|last| now.saturating_sub(last) >= interval_secs)
+    }
+}
+
+/// 统计状态总览（供设置页查看）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryStatus {
+    pub settings: TelemetrySettings,
+    /// 允许统计的事件名
+    pub allowed_events: Vec<String>,
+    pub queued: BTreeMap<String, u64>,
+    pub last_sent_at: Option<i64>,
+    pub history: Vec<TelemetrySendRecord>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(sent_at: i64, counters: &[(&str, u64)]) -> TelemetrySendRecord {
+        TelemetrySendRecord {
+            sent_at,
+            endpoint: DEFAULT_TELEMETRY_ENDPOINT.to_string(),
+            ping: TelemetryPing {
+                schema_version: TELEMETRY_SCHEMA_VERSION,
+                app_version: "1.0.0".to_string(),
+                os: "linux".to_string(),
+                period_start: 0,
+                period_end: sent_at,
+                counters: counters
+                    .iter()
+                    .map(|(event, count)| (event.to_string(), *count))
+                    .collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_mark_sent_keeps_events_recorded_while_sending() {
+        let mut state = TelemetryState::default();
+        state.increment(TelemetryEvent::ProxyStarted, 10);
+        state.increment(TelemetryEvent::ProxyStarted, 20);
+        state.increment(TelemetryEvent::ToolUpdated, 30);
+
+        // 预览时只有 1 次 proxy_started，之后又新增了 1 次
+        state.mark_sent(record(100, &[("proxy_started", 1), ("tool_updated", 1)]));
+        assert_eq!(state.queued.get("proxy_started"), Some(&1));
+        assert!(!state.queued.contains_key("tool_updated"));
+        assert_eq!(state.queued_since, Some(100));
+        assert_eq!(state.last_sent_at, Some(100));
+        assert_eq!(state.history.len(), 1);
+    }
+
+    #[test]
+    fn test_is_due_at_most_daily() {
+        let mut state = TelemetryState::default();
+        assert!(state.is_due(0, 86_400));
+        state.mark_sent(record(1_000, &[]));
+        assert!(!state.is_due(1_000 + 86_399, 86_400));
+        assert!(state.is_due(1_000 + 86_400, 86_400));
+    }
+
+    #[test]
+    fn test_event_names_are_unique() {
+        let mut names: Vec<&str> = TelemetryEvent::ALL.iter().map(|e| e.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), TelemetryEvent::ALL.len());
+        // serde 名称与 as_str 一致
+        assert_eq!(
+            serde_json::to_string(&TelemetryEvent::WslInstanceAdded).unwrap(),
+            "\"wsl_instance_added\""
+        );
+    }
+}
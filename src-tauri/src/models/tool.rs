@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// 工具状态
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ToolStatus {
     pub id: String,
     pub name: String,
@@ -10,13 +12,48 @@ pub struct ToolStatus {
     pub version: Option<String>,
 }
 
+/// 工具状态缓存设置（保存在全局配置中）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolStatusCacheSettings {
+    /// 缓存有效期（秒），超过后重新检测该工具
+    #[serde(default = "default_tool_status_ttl_secs")]
+    pub ttl_secs: u64,
+    /// 过期时先返回旧值，并在后台重新检测（stale-while-revalidate）
+    #[serde(default = "default_stale_while_revalidate")]
+    pub stale_while_revalidate: bool,
+}
+
+fn default_tool_status_ttl_secs() -> u64 {
+    600
+}
+
+fn default_stale_while_revalidate() -> bool {
+    true
+}
+
+impl ToolStatusCacheSettings {
+    pub fn ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ttl_secs)
+    }
+}
+
+impl Default for ToolStatusCacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_tool_status_ttl_secs(),
+            stale_while_revalidate: default_stale_while_revalidate(),
+        }
+    }
+}
+
 /// 工具定义
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
     pub id: String,
     pub name: String,
     pub group_name: String,
-    pub npm_package: String,
+    /// 各安装方式下的包坐标
+    pub package_coordinates: PackageCoordinates,
     pub check_command: String,
     pub config_dir: PathBuf,
     pub config_file: String,
@@ -37,10 +74,58 @@ pub struct EnvVars {
 pub enum InstallMethod {
     Official, // 官方脚本
     Npm,      // npm install
-    Brew,     // Homebrew (macOS)
+    Brew,     // Homebrew (macOS / Linuxbrew)
     Other,    // 其他（不支持APP内快捷更新）
 }
 
+/// 工具在各安装渠道中的包坐标
+///
+/// 未提供的渠道为 None。全局配置中的覆盖（见 `GlobalConfig::package_overrides`）
+/// 只需填写要替换的字段。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageCoordinates {
+    /// npm 包名（如 "@openai/codex"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub npm: Option<String>,
+    /// Homebrew formula / cask 名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brew: Option<String>,
+    /// winget 包 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winget: Option<String>,
+    /// 官方安装脚本在镜像站上的路径标识（如 "claude-code"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official: Option<String>,
+}
+
+impl PackageCoordinates {
+    /// 指定安装方式对应的坐标
+    pub fn for_method(&self, method: &InstallMethod) -> Option<&str> {
+        match method {
+            InstallMethod::Npm => self.npm.as_deref(),
+            InstallMethod::Brew => self.brew.as_deref(),
+            InstallMethod::Official => self.official.as_deref(),
+            InstallMethod::Other => None,
+        }
+    }
+
+    /// 用 `overrides` 中已填写（非空）的字段替换当前值
+    pub fn merged(&self, overrides: &PackageCoordinates) -> PackageCoordinates {
+        fn pick(base: &Option<String>, over: &Option<String>) -> Option<String> {
+            match over.as_deref().map(str::trim) {
+                Some(value) if !value.is_empty() => Some(value.to_string()),
+                _ => base.clone(),
+            }
+        }
+        PackageCoordinates {
+            npm: pick(&self.npm, &overrides.npm),
+            brew: pick(&self.brew, &overrides.brew),
+            winget: pick(&self.winget, &overrides.winget),
+            official: pick(&self.official, &overrides.official),
+        }
+    }
+}
+
 impl Tool {
     /// 获取所有工具
     pub fn all() -> Vec<Tool> {
@@ -60,7 +145,12 @@ impl Tool {
             id: "claude-code".to_string(),
             name: "Claude Code".to_string(),
             group_name: "Claude Code 专用分组".to_string(),
-            npm_package: "@anthropic-ai/claude-code".to_string(),
+            package_coordinates: PackageCoordinates {
+                npm: Some("@anthropic-ai/claude-code".to_string()),
+                brew: None,
+                winget: None,
+                official: Some("claude-code".to_string()),
+            },
             check_command: "claude --version".to_string(),
             config_dir: home_dir.join(".claude"),
             config_file: "settings.json".to_string(),
@@ -80,7 +170,12 @@ impl Tool {
             id: "codex".to_string(),
             name: "CodeX".to_string(),
             group_name: "CodeX 专用分组".to_string(),
-            npm_package: "@openai/codex".to_string(),
+            package_coordinates: PackageCoordinates {
+                npm: Some("@openai/codex".to_string()),
+                brew: Some("codex".to_string()),
+                winget: None,
+                official: None,
+            },
             check_command: "codex --version".to_string(),
             config_dir: home_dir.join(".codex"),
             config_file: "config.toml".to_string(),
@@ -100,7 +195,12 @@ impl Tool {
             id: "gemini-cli".to_string(),
             name: "Gemini CLI".to_string(),
             group_name: "Gemini CLI 专用分组".to_string(),
-            npm_package: "@google/gemini-cli".to_string(),
+            package_coordinates: PackageCoordinates {
+                npm: Some("@google/gemini-cli".to_string()),
+                brew: None,
+                winget: None,
+                official: None,
+            },
             check_command: "gemini --version".to_string(),
             config_dir: home_dir.join(".gemini"),
             config_file: "settings.json".to_string(),
@@ -112,6 +212,17 @@ impl Tool {
         }
     }
 
+    /// npm 包名（不支持 npm 安装的工具为 None）
+    pub fn npm_package(&self) -> Option<&str> {
+        self.package_coordinates.npm.as_deref()
+    }
+
+    /// 叠加用户配置的包坐标覆盖
+    pub fn with_package_override(mut self, overrides: &PackageCoordinates) -> Tool {
+        self.package_coordinates = self.package_coordinates.merged(overrides);
+        self
+    }
+
     /// 获取可用的安装方法
     pub fn available_install_methods(&self) -> Vec<InstallMethod> {
         let mut methods = vec![];
@@ -123,7 +234,7 @@ impl Tool {
             }
             "codex" => {
                 methods.push(InstallMethod::Official);
-                if cfg!(target_os = "macos") {
+                if !cfg!(target_os = "windows") {
                     methods.push(InstallMethod::Brew);
                 }
                 methods.push(InstallMethod::Npm);
@@ -159,6 +270,32 @@ impl Tool {
         }
     }
 
+    /// 可改写配置目录的环境变量（如 Claude Code 的 `CLAUDE_CONFIG_DIR`）
+    pub fn config_dir_env_var(&self) -> Option<&'static str> {
+        match self.id.as_str() {
+            "claude-code" => Some("CLAUDE_CONFIG_DIR"),
+            "codex" => Some("CODEX_HOME"),
+            _ => None,
+        }
+    }
+
+    /// 叠加环境变量覆盖后的配置目录
+    ///
+    /// 优先取实例的环境变量覆盖，其次取进程环境，均未设置时使用内置目录
+    pub fn resolved_config_dir(&self, env_overrides: &BTreeMap<String, String>) -> PathBuf {
+        let Some(var) = self.config_dir_env_var() else {
+            return self.config_dir.clone();
+        };
+        env_overrides
+            .get(var)
+            .cloned()
+            .or_else(|| std::env::var(var).ok())
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.config_dir.clone())
+    }
+
     /// 获取备份配置路径
     pub fn backup_path(&self, profile_name: &str) -> PathBuf {
         let ext = std::path::Path::new(&self.config_file)
@@ -180,6 +317,35 @@ impl Tool {
     }
 }
 
+/// 工具定义载荷的版本号，字段发生不兼容变更时递增
+pub const TOOL_DEFINITIONS_VERSION: u32 = 1;
+
+/// 返回前端的工具定义（引导向导等界面完全依据该载荷渲染）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinitions {
+    pub version: u32,
+    pub tools: Vec<ToolDefinition>,
+}
+
+/// 单个工具的完整元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    #[serde(flatten)]
+    pub tool: Tool,
+    /// 是否为内置工具（否则来自 Detector 注册表）
+    pub builtin: bool,
+    /// 叠加环境变量覆盖后的配置目录
+    pub resolved_config_dir: PathBuf,
+    /// 叠加环境变量覆盖后的配置文件路径
+    pub resolved_config_path: PathBuf,
+    /// 当前平台支持的安装方式
+    pub install_methods: Vec<InstallMethod>,
+    /// 推荐的安装方式（当前平台不支持任何方式时为 None）
+    pub recommended_install_method: Option<InstallMethod>,
+    /// 当前环境能否找到该工具的可执行文件
+    pub detectable: bool,
+}
+
 /// Provider 配置
 pub const DUCKCODING_BASE_URL: &str = "https://jp.duckcoding.com";
 
@@ -219,6 +385,19 @@ impl ToolType {
     }
 }
 
+/// SSH 认证方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshAuthMethod {
+    /// ssh-agent 或默认密钥（配置了 key_path 时一并尝试）
+    #[default]
+    Agent,
+    /// 指定密钥文件（可带口令）
+    Key,
+    /// 密码
+    Password,
+}
+
 /// SSH 连接配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SSHConfig {
@@ -232,6 +411,18 @@ pub struct SSHConfig {
     pub user: String,
     /// SSH 密钥路径（可选）
     pub key_path: Option<String>,
+    /// 认证方式
+    #[serde(default)]
+    pub auth_method: SshAuthMethod,
+    /// 密码（仅密码认证）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// 密钥口令（仅密钥认证，可选）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+    /// 跳板机（可多级嵌套；仅支持 ssh-agent 或无口令密钥认证）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump_host: Option<Box<SSHConfig>>,
 }
 
 /// 工具实例（具体环境中的安装）
@@ -257,6 +448,9 @@ pub struct ToolInstance {
     pub installer_path: Option<String>,
     /// WSL发行版名称（仅WSL类型使用）
     pub wsl_distro: Option<String>,
+    /// 安装路径的 Windows 表示（仅WSL类型使用，如 \\wsl.localhost\Ubuntu\usr\bin\claude）
+    #[serde(default)]
+    pub windows_install_path: Option<String>,
     /// SSH配置（仅SSH类型使用）
     pub ssh_config: Option<SSHConfig>,
     /// 是否为内置实例（内置的本地工具实例）
@@ -265,9 +459,140 @@ pub struct ToolInstance {
     pub created_at: i64,
     /// 更新时间（Unix timestamp）
     pub updated_at: i64,
+    /// 用户填写的标签（用于区分同一工具的多个实例）
+    #[serde(default)]
+    pub label: Option<String>,
+    /// 用户备注
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// 执行该实例的二进制（版本检查、重新验证、健康检查）时注入的环境变量
+    #[serde(default)]
+    pub env_overrides: BTreeMap<String, String>,
+    /// npm 安装实例所属的 Node.js 运行时（按全局 prefix 匹配安装路径，返回前端时计算，不持久化）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_runtime: Option<NodeRuntime>,
+}
+
+/// Node.js 运行时的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeRuntimeSource {
+    /// PATH 中找到、不属于任何版本管理器的 node
+    Path,
+    Nvm,
+    Fnm,
+    Volta,
+    Asdf,
+    /// 系统或 Homebrew 安装（如 /usr/bin、C:\Program Files\nodejs）
+    System,
+}
+
+impl NodeRuntimeSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Path => "path",
+            Self::Nvm => "nvm",
+            Self::Fnm => "fnm",
+            Self::Volta => "volta",
+            Self::Asdf => "asdf",
+            Self::System => "system",
+        }
+    }
+}
+
+/// 本机的一个 Node.js 运行时
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeRuntime {
+    /// node 可执行文件路径
+    pub node_path: String,
+    pub source: NodeRuntimeSource,
+    /// `node --version`（如 v20.11.0）
+    pub version: Option<String>,
+    /// 同目录 npm 的 `npm prefix -g`（npm 不可用时为空）
+    pub global_prefix: Option<String>,
+    /// 是否为 PATH 中第一个 node（终端中 `node` 实际使用的运行时）
+    #[serde(default)]
+    pub active: bool,
+}
+
+/// 敏感环境变量（名称含 key、token 等）的值脱敏
+pub fn mask_env_overrides(env: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    env.iter()
+        .map(|(key, value)| {
+            let value = if super::provider::is_secret_name(key) {
+                super::provider::mask_secret(value)
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
+
+/// 合并前端提交的环境变量覆盖：敏感变量提交的是当前值的脱敏形式时保留原值
+pub fn restore_masked_env(
+    current: &BTreeMap<String, String>,
+    submitted: BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    submitted
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match current.get(&key) {
+                Some(original)
+                    if super::provider::is_secret_name(&key)
+                        && value == super::provider::mask_secret(original) =>
+                {
+                    original.clone()
+                }
+                _ => value,
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+/// 将标识片段规范为 ID 可用的形式（小写字母、数字与 `-`）
+fn sanitize_id_part(raw: &str) -> String {
+    let mut id = String::with_capacity(raw.len());
+    for c in raw.to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+        } else if !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_matches('-').to_string()
 }
 
 impl ToolInstance {
+    /// 返回脱敏副本（敏感环境变量的值只保留首尾，用于返回前端）
+    pub fn masked(&self) -> Self {
+        Self {
+            env_overrides: mask_env_overrides(&self.env_overrides),
+            ..self.clone()
+        }
+    }
+
+    /// SSH 实例的稳定 ID：由连接目标（用户、主机、端口）决定，与显示名称无关
+    pub fn ssh_instance_id(base_id: &str, ssh_config: &SSHConfig) -> String {
+        format!(
+            "{}-ssh-{}",
+            base_id,
+            sanitize_id_part(&format!(
+                "{}-{}-{}",
+                ssh_config.user, ssh_config.host, ssh_config.port
+            ))
+        )
+    }
+
+    /// 手动添加的本地实例的稳定 ID：由安装路径决定
+    pub fn manual_local_instance_id(base_id: &str, install_path: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(install_path.as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+        format!("{}-local-{}", base_id, &digest[..12])
+    }
+
     /// 从基础工具创建本地实例
     pub fn from_tool_local(
         tool: &Tool,
@@ -288,10 +613,15 @@ impl ToolInstance {
             install_path,
             installer_path: None,
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: true,
             created_at: now,
             updated_at: now,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         }
     }
 
@@ -320,10 +650,15 @@ impl ToolInstance {
             install_path,
             installer_path: None,
             wsl_distro: Some(distro_name),
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: false,
             created_at: now,
             updated_at: now,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         }
     }
 
@@ -337,14 +672,9 @@ impl ToolInstance {
         install_path: Option<String>,
     ) -> Self {
         let now = chrono::Utc::now().timestamp();
-        let ssh_display_name = ssh_config.display_name.clone();
 
         ToolInstance {
-            instance_id: format!(
-                "{}-ssh-{}",
-                base_id,
-                ssh_display_name.to_lowercase().replace(' ', "-")
-            ),
+            instance_id: Self::ssh_instance_id(&base_id, &ssh_config),
             base_id,
             tool_name,
             tool_type: ToolType::SSH,
@@ -354,10 +684,15 @@ impl ToolInstance {
             install_path,
             installer_path: None,
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: Some(ssh_config),
             is_builtin: false,
             created_at: now,
             updated_at: now,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         }
     }
 }
@@ -373,4 +708,10 @@ pub struct UpdateResult {
     pub mirror_version: Option<String>, // 镜像实际可安装的版本
     pub mirror_is_stale: Option<bool>,  // 镜像是否滞后
     pub tool_id: Option<String>,        // 工具ID，用于批量检查时识别工具
+    #[serde(default)]
+    pub skipped: bool, // 最新版本已被用户跳过（定时检查不再提醒）
+    #[serde(default)]
+    pub download_size: Option<u64>, // 更新包下载大小（字节，无法获取时为 None）
+    #[serde(default)]
+    pub duration_ms: Option<u64>, // 更新实际耗时（仅更新完成后返回）
 }
@@ -12,6 +12,22 @@ pub struct UpdateInfo {
     pub release_notes: Option<String>,
     pub file_size: Option<u64>,
     pub required: bool, // 是否为强制更新
+    #[serde(default)]
+    pub skipped: bool, // 最新版本已被用户跳过
+}
+
+/// 应用自身的版本检查结果（来自 GitHub Releases）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppUpdateCheck {
+    pub current: String,
+    pub latest: String,
+    pub release_notes: Option<String>,
+    /// 发布页地址（未启用 tauri-updater，由用户在发布页下载）
+    pub download_url: Option<String>,
+    pub has_update: bool,
+    /// 最新版本已被用户跳过（定时检查不再提醒）
+    #[serde(default)]
+    pub skipped: bool,
 }
 
 /// 更新状态
@@ -58,6 +74,9 @@ pub struct PlatformInfo {
     pub is_windows: bool,
     pub is_macos: bool,
     pub is_linux: bool,
+    /// 下载用平台标识（如 linux-x64-musl）
+    #[serde(default)]
+    pub platform_id: String,
 }
 
 /// 包格式信息
@@ -0,0 +1,298 @@
+// Balance Adapters - 供应商余额响应适配
+//
+// 按供应商的余额接口模板（BalanceTemplate）把不同格式的响应统一为 Balance：
+// - NEW API：`{success, data:{quota, used_quota}}`，额度单位
+// - One API：`{balance:"12.34"}`，字符串或数字，美元
+// - Anthropic：组织花费报表，`data[].results[].amount` 为美分字符串，只有已用金额
+//
+// 识别不了的响应不报错，而是保留原始 JSON 并标记为未解析。
+
+use crate::models::{Balance, BalanceTemplate, BalanceUnit, ModelUsage, Provider};
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One API 余额接口路径
+pub const ONE_API_BALANCE_PATH: &str = "/api/user/balance";
+
+/// Anthropic 组织花费报表路径
+pub const ANTHROPIC_COST_REPORT_PATH: &str = "/v1/organizations/cost_report";
+
+/// Anthropic 官方 API 地址（供应商未配置 api_address 时使用）
+pub const ANTHROPIC_API_BASE: &str = "https://api.anthropic.com";
+
+/// 供应商是否配置了查询余额所需的凭证
+pub fn has_balance_credentials(provider: &Provider) -> bool {
+    let has_token = !provider.access_token.trim().is_empty();
+    match provider.balance_template {
+        BalanceTemplate::NewApi => has_token && !provider.user_id.trim().is_empty(),
+        BalanceTemplate::OneApi | BalanceTemplate::AnthropicUsage => has_token,
+    }
+}
+
+/// 把响应解析为统一的 Balance
+///
+/// 仅当响应明确表示失败（如 NEW API 的 `success:false`）时返回错误；
+/// 格式无法识别时返回 `parsed == false` 的 Balance。
+pub fn parse_balance(
+    template: BalanceTemplate,
+    provider_id: &str,
+    response: &Value,
+    fetched_at: i64,
+) -> Result<Balance> {
+    let amounts = match template {
+        BalanceTemplate::NewApi => parse_new_api(response)?,
+        BalanceTemplate::OneApi => parse_one_api(response),
+        BalanceTemplate::AnthropicUsage => parse_anthropic_usage(response),
+    };
+
+    let balance = match amounts {
+        Some((remaining, used, unit)) => Balance {
+            provider_id: provider_id.to_string(),
+            remaining,
+            used,
+            unit,
+            raw: Some(response.clone()),
+            parsed: true,
+            fetched_at,
+        },
+        None => {
+            tracing::warn!(
+                provider_id,
+                ?template,
+                "无法识别余额响应格式，仅保留原始响应"
+            );
+            Balance {
+                provider_id: provider_id.to_string(),
+                remaining: None,
+                used: None,
+                unit: BalanceUnit::Unknown,
+                raw: Some(response.clone()),
+                parsed: false,
+                fetched_at,
+            }
+        }
+    };
+    Ok(balance)
+}
+
+type Amounts = Option<(Option<f64>, Option<f64>, BalanceUnit)>;
+
+/// 数字或数字字符串
+fn as_amount(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_new_api(response: &Value) -> Result<Amounts> {
+    if response.get("success").and_then(Value::as_bool) == Some(false) {
+        let message = response
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("未知错误");
+        return Err(anyhow!("查询余额失败: {}", message));
+    }
+
+    let data = response.get("data").unwrap_or(response);
+    let Some(quota) = data.get("quota").and_then(as_amount) else {
+        return Ok(None);
+    };
+    let used = data.get("used_quota").and_then(as_amount);
+    Ok(Some((Some(quota), used, BalanceUnit::Quota)))
+}
+
+fn parse_one_api(response: &Value) -> Amounts {
+    let data = response
+        .get("data")
+        .filter(|d| d.is_object())
+        .unwrap_or(response);
+    let remaining = data.get("balance").and_then(as_amount)?;
+    let used = ["used", "used_balance"]
+        .iter()
+        .find_map(|key| data.get(*key).and_then(as_amount));
+    Some((Some(remaining), used, BalanceUnit::Usd))
+}
+
+fn parse_anthropic_usage(response: &Value) -> Amounts {
+    let buckets = response.get("data")?.as_array()?;
+    let mut cents = 0.0;
+    for bucket in buckets {
+        for result in bucket.get("results")?.as_array()? {
+            // 只统计美元金额，其他币种无法与余额比较
+            let currency = result.get("currency").and_then(Value::as_str);
+            if currency.is_some_and(|c| !c.eq_ignore_ascii_case("USD")) {
+                continue;
+            }
+            cents += result.get("amount").and_then(as_amount)?;
+        }
+    }
+    // 官方 API 不提供剩余额度
+    Some((None, Some(cents / 100.0), BalanceUnit::Usd))
+}
+
+/// 解析 NEW API `/api/data/self` 的逐小时用量，按模型汇总
+///
+/// 返回按花费降序的列表；格式无法识别时返回 None
+pub fn parse_new_api_usage(response: &Value) -> Option<Vec<ModelUsage>> {
+    if response.get("success").and_then(Value::as_bool) == Some(false) {
+        return None;
+    }
+    let rows = response.get("data")?.as_array()?;
+
+    let mut by_model: HashMap<String, (u64, u64, f64)> = HashMap::new();
+    for row in rows {
+        let model = row.get("model_name").and_then(Value::as_str)?;
+        let entry = by_model.entry(model.to_string()).or_default();
+        entry.0 += row.get("count").and_then(Value::as_u64).unwrap_or(0);
+        entry.1 += row.get("token_used").and_then(Value::as_u64).unwrap_or(0);
+        entry.2 += row.get("quota").and_then(as_amount).unwrap_or(0.0);
+    }
+
+    let total: f64 = by_model.values().map(|(_, _, cost)| cost).sum();
+    let mut models: Vec<ModelUsage> = by_model
+        .into_iter()
+        .map(|(model, (requests, tokens, cost))| ModelUsage {
+            model,
+            requests,
+            tokens,
+            cost,
+            unit: BalanceUnit::Quota,
+            share: if total > 0.0 { cost / total } else { 0.0 },
+        })
+        .collect();
+    models.sort_by(|a, b| {
+        b.cost
+            .partial_cmp(&a.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.model.cmp(&b.model))
+    });
+    Some(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(template: BalanceTemplate, response: Value) -> Balance {
+        parse_balance(template, "test", &response, 0).unwrap()
+    }
+
+    #[test]
+    fn test_new_api_adapter() {
+        let fixture = json!({
+            "success": true,
+            "message": "",
+            "data": {
+                "id": 1,
+                "username": "duck",
+                "quota": 5_000_000,
+                "used_quota": 250_000,
+                "request_count": 42
+            }
+        });
+        let balance = parse(BalanceTemplate::NewApi, fixture);
+        assert!(balance.parsed);
+        assert_eq!(balance.unit, BalanceUnit::Quota);
+        assert_eq!(balance.remaining, Some(5_000_000.0));
+        assert_eq!(balance.remaining_usd(), Some(10.0));
+        assert_eq!(balance.used_usd(), Some(0.5));
+        assert!(balance.raw.is_some());
+
+        let failed = json!({ "success": false, "message": "无权进行此操作" });
+        assert!(parse_balance(BalanceTemplate::NewApi, "test", &failed, 0)
+            .unwrap_err()
+            .to_string()
+            .contains("无权进行此操作"));
+    }
+
+    #[test]
+    fn test_one_api_adapter() {
+        let balance = parse(BalanceTemplate::OneApi, json!({ "balance": "12.34" }));
+        assert!(balance.parsed);
+        assert_eq!(balance.unit, BalanceUnit::Usd);
+        assert_eq!(balance.remaining, Some(12.34));
+        assert_eq!(balance.used, None);
+
+        let nested = parse(
+            BalanceTemplate::OneApi,
+            json!({ "data": { "balance": 3.5, "used": "1.25" } }),
+        );
+        assert_eq!(nested.remaining, Some(3.5));
+        assert_eq!(nested.used, Some(1.25));
+    }
+
+    #[test]
+    fn test_anthropic_usage_adapter() {
+        let fixture = json!({
+            "data": [
+                {
+                    "starting_at": "2025-01-01T00:00:00Z",
+                    "ending_at": "2025-01-02T00:00:00Z",
+                    "results": [
+                        { "currency": "USD", "amount": "1234.5", "cost_type": "tokens" },
+                        { "currency": "USD", "amount": "100", "cost_type": "web_search" }
+                    ]
+                },
+                {
+                    "starting_at": "2025-01-02T00:00:00Z",
+                    "ending_at": "2025-01-03T00:00:00Z",
+                    "results": []
+                }
+            ],
+            "has_more": false,
+            "next_page": null
+        });
+        let balance = parse(BalanceTemplate::AnthropicUsage, fixture);
+        assert!(balance.parsed);
+        assert_eq!(balance.remaining, None);
+        assert_eq!(balance.used, Some(13.345));
+        assert_eq!(balance.remaining_usd(), None);
+    }
+
+    #[test]
+    fn test_unknown_shape_falls_back_to_raw() {
+        let response = json!({ "credits": { "left": 7 } });
+        for template in [
+            BalanceTemplate::NewApi,
+            BalanceTemplate::OneApi,
+            BalanceTemplate::AnthropicUsage,
+        ] {
+            let balance = parse(template, response.clone());
+            assert!(!balance.parsed);
+            assert_eq!(balance.unit, BalanceUnit::Unknown);
+            assert_eq!(balance.remaining, None);
+            assert_eq!(balance.raw.as_ref(), Some(&response));
+        }
+    }
+
+    #[test]
+    fn test_new_api_usage_breakdown() {
+        let fixture = json!({
+            "success": true,
+            "message": "",
+            "data": [
+                { "model_name": "claude-sonnet-4", "created_at": 1735689600, "count": 10, "token_used": 40000, "quota": 400000 },
+                { "model_name": "gpt-4o-mini", "created_at": 1735689600, "count": 5, "token_used": 8000, "quota": 100000 },
+                { "model_name": "claude-sonnet-4", "created_at": 1735693200, "count": 2, "token_used": 10000, "quota": 0 }
+            ]
+        });
+        let models = parse_new_api_usage(&fixture).unwrap();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].model, "claude-sonnet-4");
+        assert_eq!(models[0].requests, 12);
+        assert_eq!(models[0].tokens, 50000);
+        assert_eq!(models[0].share, 0.8);
+        assert_eq!(models[1].share, 0.2);
+
+        assert_eq!(
+            parse_new_api_usage(&json!({ "data": [] })),
+            Some(Vec::new())
+        );
+        assert!(parse_new_api_usage(&json!({ "success": false })).is_none());
+        assert!(parse_new_api_usage(&json!({ "data": {} })).is_none());
+    }
+}
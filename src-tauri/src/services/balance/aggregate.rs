@@ -0,0 +1,160 @@
+// Balance Aggregate - 跨供应商余额汇总
+//
+// 只读取内存缓存中的最新余额，不发起任何请求（刷新由后台轮询负责）。
+
+use super::adapters::has_balance_credentials;
+use super::provider_balance::cached_balance;
+use crate::models::{AggregateBalance, Balance, BalanceExclusionReason, ExcludedBalance, Provider};
+
+/// 汇总所有供应商的缓存余额
+pub fn aggregate_cached_balances(providers: &[Provider]) -> AggregateBalance {
+    aggregate_balances(providers, cached_balance)
+}
+
+/// 汇总余额：可换算为美元的剩余额度求和，其余供应商列出排除原因
+fn aggregate_balances(
+    providers: &[Provider],
+    lookup: impl Fn(&str) -> Option<Balance>,
+) -> AggregateBalance {
+    let mut aggregate = AggregateBalance {
+        total_remaining_usd: 0.0,
+        included: Vec::new(),
+        excluded: Vec::new(),
+        oldest_fetched_at: None,
+    };
+
+    for provider in providers {
+        let mut exclude = |reason| {
+            aggregate.excluded.push(ExcludedBalance {
+                provider_id: provider.id.clone(),
+                reason,
+            })
+        };
+
+        if !has_balance_credentials(provider) {
+            exclude(BalanceExclusionReason::MissingCredentials);
+            continue;
+        }
+        let Some(balance) = lookup(&provider.id) else {
+            exclude(BalanceExclusionReason::NotFetched);
+            continue;
+        };
+        if !balance.parsed {
+            exclude(BalanceExclusionReason::Unparsed);
+            continue;
+        }
+        if balance.remaining.is_none() {
+            exclude(BalanceExclusionReason::NoRemaining);
+            continue;
+        }
+        let Some(remaining_usd) = balance.remaining_usd() else {
+            exclude(BalanceExclusionReason::UnknownUnit);
+            continue;
+        };
+
+        aggregate.total_remaining_usd += remaining_usd;
+        aggregate.included.push(provider.id.clone());
+        aggregate.oldest_fetched_at = Some(
+            aggregate
+                .oldest_fetched_at
+                .map_or(balance.fetched_at, |oldest| oldest.min(balance.fetched_at)),
+        );
+    }
+
+    aggregate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BalanceTemplate, BalanceUnit};
+    use std::collections::HashMap;
+
+    fn provider(id: &str, template: BalanceTemplate, token: &str) -> Provider {
+        Provider {
+            id: id.to_string(),
+            name: id.to_string(),
+            website_url: format!("https://{id}.example.com"),
+            api_address: None,
+            user_id: "1".to_string(),
+            access_token: token.to_string(),
+            api_key: None,
+            balance_template: template,
+            custom_headers: Default::default(),
+            username: None,
+            is_default: false,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn balance(id: &str, remaining: Option<f64>, unit: BalanceUnit, fetched_at: i64) -> Balance {
+        Balance {
+            provider_id: id.to_string(),
+            remaining,
+            used: None,
+            unit,
+            raw: None,
+            parsed: unit != BalanceUnit::Unknown,
+            fetched_at,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_converts_and_excludes() {
+        let providers = vec![
+            provider("relay", BalanceTemplate::NewApi, "t"),
+            provider("oneapi", BalanceTemplate::OneApi, "t"),
+            provider("anthropic", BalanceTemplate::AnthropicUsage, "t"),
+            provider("odd", BalanceTemplate::OneApi, "t"),
+            provider("fresh", BalanceTemplate::OneApi, "t"),
+            provider("nokey", BalanceTemplate::NewApi, ""),
+        ];
+        let cache: HashMap<&str, Balance> = [
+            (
+                "relay",
+                balance("relay", Some(5_000_000.0), BalanceUnit::Quota, 2_000),
+            ),
+            (
+                "oneapi",
+                balance("oneapi", Some(2.5), BalanceUnit::Usd, 1_000),
+            ),
+            (
+                "anthropic",
+                balance("anthropic", None, BalanceUnit::Usd, 500),
+            ),
+            ("odd", balance("odd", None, BalanceUnit::Unknown, 100)),
+        ]
+        .into_iter()
+        .collect();
+
+        let aggregate = aggregate_balances(&providers, |id| cache.get(id).cloned());
+
+        assert_eq!(aggregate.total_remaining_usd, 12.5);
+        assert_eq!(aggregate.included, vec!["relay", "oneapi"]);
+        // 只统计计入合计的余额
+        assert_eq!(aggregate.oldest_fetched_at, Some(1_000));
+
+        let reasons: Vec<_> = aggregate
+            .excluded
+            .iter()
+            .map(|e| (e.provider_id.as_str(), e.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("anthropic", BalanceExclusionReason::NoRemaining),
+                ("odd", BalanceExclusionReason::Unparsed),
+                ("fresh", BalanceExclusionReason::NotFetched),
+                ("nokey", BalanceExclusionReason::MissingCredentials),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_empty() {
+        let aggregate = aggregate_balances(&[], |_| None);
+        assert_eq!(aggregate.total_remaining_usd, 0.0);
+        assert_eq!(aggregate.oldest_fetched_at, None);
+    }
+}
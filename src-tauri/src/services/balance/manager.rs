@@ -3,25 +3,29 @@
 // 提供余额监控配置的 CRUD 操作，使用 DataManager 统一文件管理
 
 use crate::data::DataManager;
-use crate::models::{BalanceConfig, BalanceStore};
+use crate::models::{Balance, BalanceConfig, BalanceHistoryStore, BalanceStore};
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 
+/// 每个供应商最多保留的余额历史条数（30 分钟间隔约 15 天）
+pub const MAX_BALANCE_HISTORY: usize = 720;
+
 /// 余额监控管理器
 pub struct BalanceManager {
     data_manager: DataManager,
     file_path: PathBuf,
+    history_path: PathBuf,
 }
 
 impl BalanceManager {
     /// 创建新的 BalanceManager 实例
     pub fn new() -> Result<Self> {
-        let home_dir = dirs::home_dir().context("无法获取用户主目录")?;
-        let file_path = home_dir.join(".duckcoding").join("balance.json");
+        let duckcoding_dir = crate::utils::config::config_dir().map_err(|e| anyhow::anyhow!(e))?;
 
         Ok(Self {
             data_manager: DataManager::new(),
-            file_path,
+            file_path: duckcoding_dir.join("balance.json"),
+            history_path: duckcoding_dir.join("balance_history.json"),
         })
     }
 
@@ -148,6 +152,53 @@ impl BalanceManager {
         Ok(())
     }
 
+    /// 加载余额历史
+    fn load_history_store(&self) -> Result<BalanceHistoryStore> {
+        if !self.history_path.exists() {
+            return Ok(BalanceHistoryStore::default());
+        }
+
+        let value = self
+            .data_manager
+            .json()
+            .read(&self.history_path)
+            .context("读取 balance_history.json 失败")?;
+
+        serde_json::from_value(value).context("解析 balance_history.json 失败")
+    }
+
+    /// 追加一条余额记录
+    ///
+    /// 每个供应商仅保留最近 `MAX_BALANCE_HISTORY` 条
+    pub fn append_history(&self, balance: Balance) -> Result<()> {
+        let mut store = self.load_history_store()?;
+
+        let entries = store
+            .entries
+            .entry(balance.provider_id.clone())
+            .or_default();
+        entries.push(balance);
+        if entries.len() > MAX_BALANCE_HISTORY {
+            let excess = entries.len() - MAX_BALANCE_HISTORY;
+            entries.drain(..excess);
+        }
+
+        let value = serde_json::to_value(&store).context("序列化余额历史失败")?;
+        self.data_manager
+            .json()
+            .write(&self.history_path, &value)
+            .context("保存 balance_history.json 失败")
+    }
+
+    /// 获取供应商的余额历史（按时间升序）
+    pub fn history(&self, provider_id: &str) -> Result<Vec<Balance>> {
+        Ok(self
+            .load_history_store()?
+            .entries
+            .remove(provider_id)
+            .unwrap_or_default())
+    }
+
     /// 获取文件路径（用于测试）
     #[cfg(test)]
     pub fn file_path(&self) -> &PathBuf {
@@ -174,6 +225,7 @@ mod tests {
         let manager = BalanceManager {
             data_manager: DataManager::new(),
             file_path,
+            history_path: temp_dir.path().join("balance_history.json"),
         };
 
         (manager, temp_dir)
@@ -316,4 +368,28 @@ mod tests {
         let store = manager.load_store().unwrap();
         assert_eq!(store.configs.len(), 3);
     }
+
+    #[test]
+    fn test_balance_history_capped() {
+        let (manager, _temp) = create_test_manager();
+
+        for i in 0..(MAX_BALANCE_HISTORY + 5) {
+            manager
+                .append_history(Balance {
+                    provider_id: "duckcoding".to_string(),
+                    remaining: Some(i as f64),
+                    used: None,
+                    unit: Default::default(),
+                    raw: None,
+                    parsed: true,
+                    fetched_at: i as i64,
+                })
+                .unwrap();
+        }
+
+        let history = manager.history("duckcoding").unwrap();
+        assert_eq!(history.len(), MAX_BALANCE_HISTORY);
+        assert_eq!(history[0].fetched_at, 5);
+        assert!(manager.history("other").unwrap().is_empty());
+    }
 }
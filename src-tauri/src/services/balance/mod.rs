@@ -1,7 +1,15 @@
 // Balance Service Module
 //
-// 余额监控配置管理服务
+// 余额监控配置管理、供应商余额查询与后台轮询服务
 
+mod adapters;
+mod aggregate;
 mod manager;
+mod poller;
+mod provider_balance;
 
-pub use manager::BalanceManager;
+pub use adapters::{has_balance_credentials, parse_balance, parse_new_api_usage};
+pub use aggregate::aggregate_cached_balances;
+pub use manager::{BalanceManager, MAX_BALANCE_HISTORY};
+pub use poller::{BalancePoller, BalanceUpdatedEvent, PROVIDER_BALANCE_UPDATED_EVENT};
+pub use provider_balance::{cached_balance, get_provider_balance, get_provider_balance_detail};
@@ -0,0 +1,192 @@
+// Balance Poller - 供应商余额后台轮询
+//
+// 按全局配置 `balance_poll` 的间隔轮询所有已配置凭证的供应商余额：
+// - 每轮开始前重新读取配置，关闭后只休眠不请求
+// - 网络不可达时（镜像站探测失败）暂停本轮
+// - 同一供应商的查询不会重叠，上一次未完成时跳过
+
+use super::adapters::has_balance_credentials;
+use super::provider_balance::get_provider_balance;
+use crate::models::{Balance, BalancePollSettings, Provider};
+use crate::services::provider_manager::ProviderManager;
+use crate::services::tool::VersionService;
+use crate::utils::config::read_global_config;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 余额更新事件名（前端监听）
+pub const PROVIDER_BALANCE_UPDATED_EVENT: &str = "provider-balance-updated";
+
+/// 轮询关闭时重新检查配置的间隔
+const DISABLED_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 离线时重新探测的间隔
+const OFFLINE_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 余额更新事件
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceUpdatedEvent {
+    pub provider_id: String,
+    pub balance: Balance,
+}
+
+/// 供应商余额轮询器
+pub struct BalancePoller {
+    in_flight: Mutex<HashSet<String>>,
+    events: broadcast::Sender<BalanceUpdatedEvent>,
+}
+
+/// 在途标记，离开作用域时自动释放
+struct InFlightGuard<'a> {
+    poller: &'a BalancePoller,
+    provider_id: String,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.poller
+            .in_flight
+            .lock()
+            .unwrap()
+            .remove(&self.provider_id);
+    }
+}
+
+impl BalancePoller {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
+        Self {
+            in_flight: Mutex::new(HashSet::new()),
+            events,
+        }
+    }
+
+    /// 订阅余额更新事件
+    pub fn subscribe(&self) -> broadcast::Receiver<BalanceUpdatedEvent> {
+        self.events.subscribe()
+    }
+
+    /// 标记供应商查询开始，已在查询中时返回 None
+    fn try_begin(&self, provider_id: &str) -> Option<InFlightGuard<'_>> {
+        if !self
+            .in_flight
+            .lock()
+            .unwrap()
+            .insert(provider_id.to_string())
+        {
+            return None;
+        }
+        Some(InFlightGuard {
+            poller: self,
+            provider_id: provider_id.to_string(),
+        })
+    }
+
+    fn settings() -> BalancePollSettings {
+        match read_global_config() {
+            Ok(Some(config)) => config.balance_poll,
+            Ok(None) => BalancePollSettings::default(),
+            Err(e) => {
+                tracing::warn!(error = %e, "读取余额轮询配置失败，使用默认值");
+                BalancePollSettings::default()
+            }
+        }
+    }
+
+    /// 轮询主循环（不会返回）
+    pub async fn run(self: Arc<Self>) {
+        let version_service = VersionService::new();
+        loop {
+            let settings = Self::settings();
+            if !settings.enabled || settings.interval_mins == 0 {
+                tokio::time::sleep(DISABLED_RECHECK_INTERVAL).await;
+                continue;
+            }
+
+            if !version_service.probe_connectivity().await {
+                tracing::debug!("网络不可达，暂停余额轮询");
+                tokio::time::sleep(OFFLINE_RETRY_INTERVAL).await;
+                continue;
+            }
+
+            let providers = match ProviderManager::new().and_then(|m| m.list_providers()) {
+                Ok(providers) => providers,
+                Err(e) => {
+                    tracing::warn!(error = ?e, "加载供应商列表失败，跳过本轮余额轮询");
+                    Vec::new()
+                }
+            };
+            let interval = Duration::from_secs(u64::from(settings.interval_mins) * 60);
+            self.poll_once(providers, interval).await;
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// 轮询一轮：并发查询所有已配置凭证的供应商
+    ///
+    /// `max_age` 内已有缓存的供应商直接使用缓存，不重复请求
+    pub async fn poll_once(self: &Arc<Self>, providers: Vec<Provider>, max_age: Duration) {
+        let tasks = providers
+            .into_iter()
+            .filter(has_balance_credentials)
+            .map(|provider| {
+                let poller = Arc::clone(self);
+                tokio::spawn(async move { poller.poll_provider(provider, max_age).await })
+            })
+            .collect::<Vec<_>>();
+
+        for task in tasks {
+            if let Err(e) = task.await {
+                tracing::warn!(error = ?e, "余额轮询任务异常退出");
+            }
+        }
+    }
+
+    async fn poll_provider(&self, provider: Provider, max_age: Duration) {
+        let Some(_guard) = self.try_begin(&provider.id) else {
+            tracing::debug!(provider_id = %provider.id, "上一次余额查询尚未完成，跳过");
+            return;
+        };
+
+        match get_provider_balance(&provider, max_age).await {
+            Ok(balance) => {
+                // 没有订阅者时 send 返回错误，可以忽略
+                let _ = self.events.send(BalanceUpdatedEvent {
+                    provider_id: provider.id.clone(),
+                    balance,
+                });
+            }
+            Err(e) => {
+                tracing::warn!(provider_id = %provider.id, error = ?e, "轮询供应商余额失败");
+            }
+        }
+    }
+}
+
+impl Default for BalancePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_guard_prevents_overlap() {
+        let poller = BalancePoller::new();
+
+        let guard = poller.try_begin("duckcoding").expect("首次应成功");
+        assert!(poller.try_begin("duckcoding").is_none());
+        // 不同供应商互不影响
+        assert!(poller.try_begin("other").is_some());
+
+        drop(guard);
+        assert!(poller.try_begin("duckcoding").is_some());
+    }
+}
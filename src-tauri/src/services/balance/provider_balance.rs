@@ -0,0 +1,235 @@
+// Provider Balance - 供应商余额查询与缓存
+//
+// 按供应商的余额接口模板请求余额接口，经 adapters 统一为 Balance 后缓存在内存中，
+// 每次实际查询成功后追加到余额历史（balance_history.json）
+
+use super::adapters::{
+    has_balance_credentials, parse_balance, parse_new_api_usage, ANTHROPIC_API_BASE,
+    ANTHROPIC_COST_REPORT_PATH, ONE_API_BALANCE_PATH,
+};
+use super::manager::BalanceManager;
+use crate::http_client::merge_custom_headers;
+use crate::models::{Balance, BalanceDetail, BalanceTemplate, ModelUsage, Provider};
+use crate::services::new_api::NewApiClient;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 余额接口请求超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 内存中的余额缓存（按供应商 ID）
+static BALANCE_CACHE: Lazy<Mutex<HashMap<String, Balance>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 读取缓存的余额（不发起请求）
+pub fn cached_balance(provider_id: &str) -> Option<Balance> {
+    BALANCE_CACHE.lock().unwrap().get(provider_id).cloned()
+}
+
+/// 缓存是否仍在有效期内
+fn is_fresh(balance: &Balance, max_age: Duration, now_ms: i64) -> bool {
+    now_ms.saturating_sub(balance.fetched_at) < max_age.as_millis() as i64
+}
+
+/// 当前计费周期的起始时间（本月第一天，UTC）
+fn billing_period_start() -> DateTime<Utc> {
+    let now = Utc::now();
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// 以 GET 请求余额接口并返回 JSON 响应
+///
+/// 供应商的自定义请求头合并在 `headers` 之后（不覆盖凭证请求头）
+async fn get_json(url: &str, provider: &Provider, headers: &[(&str, &str)]) -> Result<Value> {
+    let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(
+            HeaderName::from_bytes(name.as_bytes())?,
+            HeaderValue::from_str(value).map_err(|_| anyhow!("请求头 {} 包含非法字符", name))?,
+        );
+    }
+    merge_custom_headers(&mut header_map, &provider.custom_headers);
+    let response = client
+        .get(url)
+        .timeout(FETCH_TIMEOUT)
+        .headers(header_map)
+        .send()
+        .await
+        .map_err(|e| anyhow!("请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "API 请求失败，状态码: {}",
+            response.status().as_u16()
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| anyhow!("解析响应失败: {}", e))
+}
+
+/// 按余额接口模板请求原始响应
+async fn fetch_raw(provider: &Provider) -> Result<Value> {
+    match provider.balance_template {
+        BalanceTemplate::NewApi => NewApiClient::new(provider.clone())?.get_user_self().await,
+        BalanceTemplate::OneApi => {
+            let url = format!(
+                "{}{}",
+                provider.website_url.trim_end_matches('/'),
+                ONE_API_BALANCE_PATH
+            );
+            let auth = format!("Bearer {}", provider.access_token);
+            get_json(&url, provider, &[("Authorization", auth.as_str())]).await
+        }
+        BalanceTemplate::AnthropicUsage => {
+            let base = provider
+                .api_address
+                .as_deref()
+                .filter(|a| !a.trim().is_empty())
+                .unwrap_or(ANTHROPIC_API_BASE)
+                .trim_end_matches('/');
+            let url = format!(
+                "{}{}?starting_at={}",
+                base,
+                ANTHROPIC_COST_REPORT_PATH,
+                billing_period_start().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            );
+            get_json(
+                &url,
+                provider,
+                &[
+                    ("x-api-key", provider.access_token.as_str()),
+                    ("anthropic-version", "2023-06-01"),
+                ],
+            )
+            .await
+        }
+    }
+}
+
+/// 查询供应商余额
+///
+/// 缓存未超过 `max_age` 时直接返回缓存；否则实时查询并更新缓存与历史。
+/// 写入历史失败只记录日志，不影响返回结果。
+pub async fn get_provider_balance(provider: &Provider, max_age: Duration) -> Result<Balance> {
+    if let Some(cached) = cached_balance(&provider.id) {
+        if is_fresh(&cached, max_age, Utc::now().timestamp_millis()) {
+            return Ok(cached);
+        }
+    }
+
+    if !has_balance_credentials(provider) {
+        return Err(anyhow!("供应商 {} 未配置查询余额所需的凭证", provider.id));
+    }
+
+    let response = fetch_raw(provider).await?;
+    let balance = parse_balance(
+        provider.balance_template,
+        &provider.id,
+        &response,
+        Utc::now().timestamp_millis(),
+    )?;
+
+    BALANCE_CACHE
+        .lock()
+        .unwrap()
+        .insert(provider.id.clone(), balance.clone());
+
+    // 历史只记录金额，不保存原始响应
+    let entry = Balance {
+        raw: None,
+        ..balance.clone()
+    };
+    if let Err(e) = BalanceManager::new().and_then(|m| m.append_history(entry)) {
+        tracing::warn!(provider_id = %provider.id, error = ?e, "写入余额历史失败");
+    }
+
+    Ok(balance)
+}
+
+/// 请求当前计费周期内按模型汇总的用量
+async fn fetch_model_usage(
+    provider: &Provider,
+    period_start: DateTime<Utc>,
+) -> Result<Vec<ModelUsage>> {
+    match provider.balance_template {
+        BalanceTemplate::NewApi => {
+            let response = NewApiClient::new(provider.clone())?
+                .get_user_quota_data(period_start.timestamp(), Utc::now().timestamp())
+                .await?;
+            parse_new_api_usage(&response).ok_or_else(|| anyhow!("无法识别用量统计响应格式"))
+        }
+        BalanceTemplate::OneApi | BalanceTemplate::AnthropicUsage => Ok(Vec::new()),
+    }
+}
+
+/// 查询供应商余额详情
+///
+/// 余额摘要与 `get_provider_balance` 一致（复用缓存）；模板声明了用量统计接口时，
+/// 额外汇总本计费周期内各模型的用量。明细获取失败不影响摘要。
+pub async fn get_provider_balance_detail(
+    provider: &Provider,
+    max_age: Duration,
+) -> Result<BalanceDetail> {
+    let summary = get_provider_balance(provider, max_age).await?;
+    let period_start = billing_period_start();
+
+    let (models, models_error) = if provider.balance_template.has_usage_stats() {
+        match fetch_model_usage(provider, period_start).await {
+            Ok(models) => (models, None),
+            Err(e) => {
+                tracing::warn!(provider_id = %provider.id, error = ?e, "获取模型用量明细失败");
+                (Vec::new(), Some(e.to_string()))
+            }
+        }
+    } else {
+        (Vec::new(), None)
+    };
+
+    Ok(BalanceDetail {
+        summary,
+        period_start: period_start.timestamp_millis(),
+        models,
+        models_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_freshness() {
+        let balance = Balance {
+            provider_id: "duckcoding".to_string(),
+            remaining: Some(1.0),
+            used: None,
+            unit: Default::default(),
+            raw: None,
+            parsed: true,
+            fetched_at: 1_000_000,
+        };
+        let max_age = Duration::from_secs(60);
+        assert!(is_fresh(&balance, max_age, 1_000_000 + 59_000));
+        assert!(!is_fresh(&balance, max_age, 1_000_000 + 60_000));
+        assert!(!is_fresh(&balance, Duration::ZERO, 1_000_000));
+    }
+
+    #[test]
+    fn test_billing_period_start() {
+        let start = billing_period_start();
+        assert_eq!(start.day(), 1);
+        assert!(start <= Utc::now());
+    }
+}
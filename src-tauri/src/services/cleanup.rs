@@ -0,0 +1,393 @@
+// Cleanup Service
+//
+// 卸载前清理 DuckCoding 管理的状态：按所选范围生成清理计划，
+// 破坏性范围需要携带与计划匹配的确认令牌才会执行
+
+use crate::models::provider::Provider;
+use crate::models::proxy_config::ToolProxyConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// 清理范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupScope {
+    /// 还原被代理接入 / 代理 Profile 改动的工具配置
+    ToolConfigs,
+    /// 停止并禁用透明代理
+    Proxy,
+    /// 删除自定义供应商（破坏性）
+    Providers,
+    /// 删除工具实例数据库（破坏性）
+    InstanceDb,
+    /// 删除整个应用配置目录（破坏性）
+    AppConfigDir,
+}
+
+impl CleanupScope {
+    /// 是否为破坏性范围（需要确认令牌）
+    pub fn is_destructive(self) -> bool {
+        matches!(
+            self,
+            Self::Providers | Self::InstanceDb | Self::AppConfigDir
+        )
+    }
+}
+
+/// 清理动作（按执行顺序排列）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanupAction {
+    /// 停止运行中的代理
+    StopProxy { tool_id: String },
+    /// 按记录的原始值还原工具配置中的代理地址
+    RestoreToolRouting { tool_id: String },
+    /// 切回启动代理前激活的 Profile
+    RestoreActiveProfile { tool_id: String, profile: String },
+    /// 禁用代理及其自动启动
+    DisableProxy { tool_id: String },
+    /// 删除供应商
+    DeleteProvider { provider_id: String, name: String },
+    /// 删除工具实例数据库
+    DeleteInstanceDb { path: PathBuf },
+    /// 删除应用配置目录
+    RemoveConfigDir { path: PathBuf },
+}
+
+impl CleanupAction {
+    pub fn scope(&self) -> CleanupScope {
+        match self {
+            Self::StopProxy { .. } | Self::DisableProxy { .. } => CleanupScope::Proxy,
+            Self::RestoreToolRouting { .. } | Self::RestoreActiveProfile { .. } => {
+                CleanupScope::ToolConfigs
+            }
+            Self::DeleteProvider { .. } => CleanupScope::Providers,
+            Self::DeleteInstanceDb { .. } => CleanupScope::InstanceDb,
+            Self::RemoveConfigDir { .. } => CleanupScope::AppConfigDir,
+        }
+    }
+
+    /// 动作对象（工具 ID、供应商 ID 或路径）
+    pub fn target(&self) -> String {
+        match self {
+            Self::StopProxy { tool_id }
+            | Self::RestoreToolRouting { tool_id }
+            | Self::RestoreActiveProfile { tool_id, .. }
+            | Self::DisableProxy { tool_id } => tool_id.clone(),
+            Self::DeleteProvider { provider_id, .. } => provider_id.clone(),
+            Self::DeleteInstanceDb { path } | Self::RemoveConfigDir { path } => {
+                path.to_string_lossy().into_owned()
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::StopProxy { tool_id } => format!("停止 {tool_id} 透明代理"),
+            Self::RestoreToolRouting { tool_id } => {
+                format!("还原 {tool_id} 接入代理前的原始配置")
+            }
+            Self::RestoreActiveProfile { tool_id, profile } => {
+                format!("将 {tool_id} 切回 Profile {profile}")
+            }
+            Self::DisableProxy { tool_id } => format!("禁用 {tool_id} 透明代理及自动启动"),
+            Self::DeleteProvider { provider_id, name } => {
+                format!("删除供应商 {name}（{provider_id}）")
+            }
+            Self::DeleteInstanceDb { path } => format!("删除工具实例数据库 {}", path.display()),
+            Self::RemoveConfigDir { path } => format!("删除配置目录 {}", path.display()),
+        }
+    }
+}
+
+/// 生成清理计划所需的当前状态
+#[derive(Debug, Clone, Default)]
+pub struct CleanupInputs {
+    /// 各工具的代理配置
+    pub proxy_configs: Vec<(String, ToolProxyConfig)>,
+    /// 运行中的代理
+    pub running_proxies: BTreeSet<String>,
+    pub providers: Vec<Provider>,
+    pub instance_db: PathBuf,
+    pub config_dir: PathBuf,
+}
+
+/// 按所选范围生成清理计划（只包含确实会产生改动的动作）
+pub fn plan(scopes: &[CleanupScope], inputs: &CleanupInputs) -> Vec<CleanupAction> {
+    let scopes: BTreeSet<CleanupScope> = scopes.iter().copied().collect();
+    let mut actions = Vec::new();
+
+    if scopes.contains(&CleanupScope::Proxy) {
+        for tool_id in &inputs.running_proxies {
+            actions.push(CleanupAction::StopProxy {
+                tool_id: tool_id.clone(),
+            });
+        }
+    }
+    if scopes.contains(&CleanupScope::ToolConfigs) {
+        for (tool_id, config) in &inputs.proxy_configs {
+            if config.tool_routing.is_some() {
+                actions.push(CleanupAction::RestoreToolRouting {
+                    tool_id: tool_id.clone(),
+                });
+            }
+            if let Some(profile) = &config.original_active_profile {
+                actions.push(CleanupAction::RestoreActiveProfile {
+                    tool_id: tool_id.clone(),
+                    profile: profile.clone(),
+                });
+            }
+        }
+    }
+    if scopes.contains(&CleanupScope::Proxy) {
+        for (tool_id, config) in &inputs.proxy_configs {
+            if config.enabled || config.auto_start {
+                actions.push(CleanupAction::DisableProxy {
+                    tool_id: tool_id.clone(),
+                });
+            }
+        }
+    }
+    // 删除配置目录已包含供应商与实例数据库，不再单独列出
+    let removes_config_dir =
+        scopes.contains(&CleanupScope::AppConfigDir) && inputs.config_dir.exists();
+    if scopes.contains(&CleanupScope::Providers) && !removes_config_dir {
+        // 默认供应商不可删除
+        for provider in inputs.providers.iter().filter(|p| !p.is_default) {
+            actions.push(CleanupAction::DeleteProvider {
+                provider_id: provider.id.clone(),
+                name: provider.name.clone(),
+            });
+        }
+    }
+    if scopes.contains(&CleanupScope::InstanceDb)
+        && !removes_config_dir
+        && inputs.instance_db.exists()
+    {
+        actions.push(CleanupAction::DeleteInstanceDb {
+            path: inputs.instance_db.clone(),
+        });
+    }
+    if removes_config_dir {
+        actions.push(CleanupAction::RemoveConfigDir {
+            path: inputs.config_dir.clone(),
+        });
+    }
+    actions
+}
+
+/// 计划中破坏性动作的确认令牌（无破坏性动作时为 `None`）
+///
+/// 令牌由动作内容计算，计划变化（如新增供应商）后旧令牌自动失效。
+pub fn confirmation_token(actions: &[CleanupAction]) -> Option<String> {
+    let mut hasher = Sha256::new();
+    let mut any = false;
+    for action in actions.iter().filter(|a| a.scope().is_destructive()) {
+        any = true;
+        hasher.update(action.describe().as_bytes());
+        hasher.update(b"\n");
+    }
+    any.then(|| format!("{:x}", hasher.finalize())[..12].to_string())
+}
+
+/// 清理项状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupStatus {
+    /// 预演模式下将要执行
+    Planned,
+    Done,
+    Failed,
+}
+
+/// 清理报告中的单项
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CleanupItem {
+    pub scope: CleanupScope,
+    pub target: String,
+    pub action: String,
+    pub status: CleanupStatus,
+    pub message: String,
+}
+
+/// 清理报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CleanupReport {
+    pub dry_run: bool,
+    pub items: Vec<CleanupItem>,
+    /// 执行破坏性范围所需的确认令牌（仅预演时返回）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation_token: Option<String>,
+    /// 所有项均成功
+    pub success: bool,
+}
+
+impl CleanupReport {
+    /// 预演报告：列出全部计划动作
+    pub fn dry_run(actions: &[CleanupAction]) -> Self {
+        Self {
+            dry_run: true,
+            items: actions
+                .iter()
+                .map(|action| item(action, CleanupStatus::Planned, String::new()))
+                .collect(),
+            confirmation_token: confirmation_token(actions),
+            success: true,
+        }
+    }
+
+    pub fn record(&mut self, action: &CleanupAction, result: Result<()>) {
+        let (status, message) = match result {
+            Ok(()) => (CleanupStatus::Done, String::new()),
+            Err(e) => (CleanupStatus::Failed, format!("{e:#}")),
+        };
+        self.items.push(item(action, status, message));
+    }
+
+    pub fn finish(mut self) -> Self {
+        self.success = self
+            .items
+            .iter()
+            .all(|item| item.status != CleanupStatus::Failed);
+        self
+    }
+}
+
+fn item(action: &CleanupAction, status: CleanupStatus, message: String) -> CleanupItem {
+    CleanupItem {
+        scope: action.scope(),
+        target: action.target(),
+        action: action.describe(),
+        status,
+        message,
+    }
+}
+
+/// 删除文件或目录（不存在时视为成功）
+pub fn remove_path(path: &Path) -> Result<()> {
+    let result = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+    match result {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("删除失败: {path:?}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::provider::ProviderStore;
+    use tempfile::TempDir;
+
+    fn inputs(temp_dir: &TempDir) -> CleanupInputs {
+        let mut routed = ToolProxyConfig::new(8787);
+        routed.enabled = true;
+        routed.original_active_profile = Some("work".to_string());
+        routed.tool_routing = Some(crate::models::proxy_config::ToolRoutingState {
+            proxy_url: "http://127.0.0.1:8787".to_string(),
+            applied_at: chrono::Utc::now(),
+            originals: Default::default(),
+        });
+        let mut providers = ProviderStore::default().providers;
+        let mut custom = providers[0].clone();
+        custom.id = "relay".to_string();
+        custom.name = "Relay".to_string();
+        custom.is_default = false;
+        providers.push(custom);
+
+        let instance_db = temp_dir.path().join("tools.db");
+        std::fs::write(&instance_db, "{}").unwrap();
+        CleanupInputs {
+            proxy_configs: vec![
+                ("claude-code".to_string(), routed),
+                ("codex".to_string(), ToolProxyConfig::new(8788)),
+            ],
+            running_proxies: ["claude-code".to_string()].into_iter().collect(),
+            providers,
+            instance_db,
+            config_dir: temp_dir.path().to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn test_plan_orders_actions_and_skips_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let actions = plan(
+            &[
+                CleanupScope::ToolConfigs,
+                CleanupScope::Proxy,
+                CleanupScope::Providers,
+            ],
+            &inputs(&temp_dir),
+        );
+        let described: Vec<String> = actions.iter().map(CleanupAction::describe).collect();
+        assert_eq!(
+            described,
+            vec![
+                "停止 claude-code 透明代理",
+                "还原 claude-code 接入代理前的原始配置",
+                "将 claude-code 切回 Profile work",
+                "禁用 claude-code 透明代理及自动启动",
+                "删除供应商 Relay（relay）",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_dir_removal_supersedes_file_deletes() {
+        let temp_dir = TempDir::new().unwrap();
+        let actions = plan(
+            &[
+                CleanupScope::Providers,
+                CleanupScope::InstanceDb,
+                CleanupScope::AppConfigDir,
+            ],
+            &inputs(&temp_dir),
+        );
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].scope(), CleanupScope::AppConfigDir);
+    }
+
+    #[test]
+    fn test_confirmation_token_tracks_destructive_actions() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut inputs = inputs(&temp_dir);
+        let safe = plan(&[CleanupScope::ToolConfigs], &inputs);
+        assert!(confirmation_token(&safe).is_none());
+
+        let destructive = plan(&[CleanupScope::Providers], &inputs);
+        let token = confirmation_token(&destructive).unwrap();
+        assert_eq!(token.len(), 12);
+        assert_eq!(confirmation_token(&destructive), Some(token.clone()));
+
+        inputs.providers[1].name = "Renamed".to_string();
+        let changed = plan(&[CleanupScope::Providers], &inputs);
+        assert_ne!(confirmation_token(&changed), Some(token));
+    }
+
+    #[test]
+    fn test_report_and_remove_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("config");
+        std::fs::create_dir_all(dir.join("logs")).unwrap();
+        let action = CleanupAction::RemoveConfigDir { path: dir.clone() };
+
+        let mut report = CleanupReport::default();
+        report.record(&action, remove_path(&dir));
+        report.record(&action, Err(anyhow::anyhow!("busy")));
+        let report = report.finish();
+
+        assert!(!dir.exists());
+        assert!(remove_path(&dir).is_ok());
+        assert_eq!(report.items[0].status, CleanupStatus::Done);
+        assert_eq!(report.items[1].message, "busy");
+        assert!(!report.success);
+    }
+}
@@ -1,6 +1,7 @@
 //! Claude Code 配置管理模块
 
 use super::types::ClaudeSettingsPayload;
+use super::write_ledger::record_write;
 use super::ToolConfigManager;
 use crate::data::DataManager;
 use crate::models::Tool;
@@ -102,18 +103,22 @@ pub fn save_claude_settings(settings: &Value, extra_config: Option<&Value>) -> R
 
     let manager = DataManager::new();
     manager
-        .json_uncached()
-        .write(&config_path, settings)
+        .with_file_lock(&config_path, || {
+            manager.json_uncached().write(&config_path, settings)
+        })
         .context("写入 Claude Code 配置失败")?;
+    record_write(&config_path, "save_settings");
 
     if let Some(extra) = extra_config {
         if !extra.is_object() {
             anyhow::bail!("Claude Code config.json 必须是 JSON 对象");
         }
         manager
-            .json_uncached()
-            .write(&extra_config_path, extra)
+            .with_file_lock(&extra_config_path, || {
+                manager.json_uncached().write(&extra_config_path, extra)
+            })
             .context("写入 Claude Code config.json 失败")?;
+        record_write(&extra_config_path, "save_settings");
     }
 
     Ok(())
@@ -2,6 +2,7 @@
 
 use super::types::CodexSettingsPayload;
 use super::utils::merge_toml_tables;
+use super::write_ledger::record_write;
 use super::ToolConfigManager;
 use crate::data::DataManager;
 use crate::models::Tool;
@@ -45,7 +46,8 @@ pub fn read_codex_settings() -> Result<CodexSettingsPayload> {
     let tool = Tool::codex();
     let config_path = tool.config_dir.join(&tool.config_file);
     let auth_path = tool.config_dir.join("auth.json");
-    let manager = DataManager::new();
+    // 使用全局实例，读取与保存共享 TOML 文档缓存
+    let manager = DataManager::global();
 
     let config_value = if config_path.exists() {
         let doc = manager
@@ -92,53 +94,47 @@ pub fn save_codex_settings(config: &Value, auth_token: Option<String>) -> Result
     let tool = Tool::codex();
     let config_path = tool.config_dir.join(&tool.config_file);
     let auth_path = tool.config_dir.join("auth.json");
-    let manager = DataManager::new();
+    // 使用全局实例，读取与保存共享 TOML 文档缓存
+    let manager = DataManager::global();
 
     fs::create_dir_all(&tool.config_dir).context("创建 Codex 配置目录失败")?;
 
-    // 读取现有 TOML 文档以保留注释和格式
-    let mut existing_doc = if config_path.exists() {
-        manager
-            .toml()
-            .read_document(&config_path)
-            .context("读取 Codex config.toml 失败")?
-    } else {
-        DocumentMut::new()
-    };
-
     // 将新配置序列化为 TOML 并解析
     let new_toml_string = toml::to_string(config).context("序列化 Codex config 失败")?;
     let new_doc = new_toml_string
         .parse::<DocumentMut>()
         .map_err(|err| anyhow!("解析待写入 Codex 配置失败: {err}"))?;
 
-    // 合并配置，保留注释
-    merge_toml_tables(existing_doc.as_table_mut(), new_doc.as_table());
-
+    // 加锁读取现有 TOML 文档并合并，保留注释和格式
     manager
-        .toml()
-        .write(&config_path, &existing_doc)
+        .read_modify_write_toml(&config_path, |existing_doc| {
+            merge_toml_tables(existing_doc.as_table_mut(), new_doc.as_table());
+            Ok(())
+        })
         .context("写入 Codex config.toml 失败")?;
+    record_write(&config_path, "save_settings");
 
     // 保存认证令牌
     if let Some(token) = auth_token {
-        let mut auth_data = if auth_path.exists() {
-            manager
-                .json_uncached()
-                .read(&auth_path)
-                .unwrap_or(Value::Object(Map::new()))
-        } else {
-            Value::Object(Map::new())
-        };
-
-        if let Value::Object(ref mut obj) = auth_data {
-            obj.insert("OPENAI_API_KEY".to_string(), Value::String(token));
-        }
-
         manager
-            .json_uncached()
-            .write(&auth_path, &auth_data)
+            .with_file_lock(&auth_path, || {
+                let mut auth_data = if auth_path.exists() {
+                    manager
+                        .json_uncached()
+                        .read(&auth_path)
+                        .unwrap_or(Value::Object(Map::new()))
+                } else {
+                    Value::Object(Map::new())
+                };
+
+                if let Value::Object(ref mut obj) = auth_data {
+                    obj.insert("OPENAI_API_KEY".to_string(), Value::String(token));
+                }
+
+                manager.json_uncached().write(&auth_path, &auth_data)
+            })
             .context("写入 Codex auth.json 失败")?;
+        record_write(&auth_path, "save_settings");
     }
 
     Ok(())
@@ -0,0 +1,892 @@
+//! 工具实际生效配置的解析
+//!
+//! 工具会从多处读取同一配置项，用户经常遇到「Base URL 明明改了却不生效」。
+//! 这里按各工具自身的优先级规则重现最终取值，并标注每个值来自哪里：
+//! - Claude Code：托管设置 > 环境变量 > 项目本地设置 > 项目设置 > 用户设置（均为 `env` 块）
+//! - Codex：`config.toml` 中 `profile` 选择的 `model_provider`，密钥来自 provider 的 `env_key` 或 `auth.json`
+//! - Gemini CLI：环境变量 > 最先找到的 `.env`（只加载一个）> 项目 / 用户 `settings.json`
+//!
+//! 结果只覆盖 DuckCoding 管理的配置项，并与当前应用的配置（Profile 或透明代理接入）比较。
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::data::DataManager;
+use crate::models::provider::{is_secret_name, mask_secret};
+use crate::models::Tool;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::utils::captured_shell_env;
+
+const CLAUDE_KEYS: &[&str] = &[
+    "ANTHROPIC_BASE_URL",
+    "ANTHROPIC_AUTH_TOKEN",
+    "ANTHROPIC_API_KEY",
+];
+const GEMINI_ENV_KEYS: &[&str] = &["GOOGLE_GEMINI_BASE_URL", "GEMINI_API_KEY"];
+const GEMINI_MODEL: &str = "GEMINI_MODEL";
+/// Codex 未配置 `model_providers` 时使用的内置 provider
+const CODEX_BUILTIN_PROVIDER: &str = "openai";
+const CODEX_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// 配置值的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// 企业托管设置（managed-settings.json）
+    Managed,
+    /// 启动工具时的环境变量
+    Environment,
+    /// 项目的 `.claude/settings.local.json`
+    ProjectLocalSettings,
+    /// 项目的 `settings.json`
+    ProjectSettings,
+    /// 用户目录下的 `settings.json`
+    UserSettings,
+    ConfigToml,
+    AuthJson,
+    EnvFile,
+    /// 工具内置的默认值
+    Default,
+}
+
+/// 某个来源中的取值
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueOrigin {
+    pub source: ConfigSource,
+    /// 来源文件（环境变量与默认值为空）
+    pub path: Option<String>,
+    /// 来源中的字段（如 `env.ANTHROPIC_BASE_URL`、`model_providers.relay.base_url`）
+    pub field: String,
+    /// 取值（敏感项已脱敏）
+    pub value: String,
+}
+
+/// 一个配置项的最终取值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveValue {
+    pub key: String,
+    /// 生效的取值（所有来源均未设置时为空）
+    pub effective: Option<ValueOrigin>,
+    /// 同样设置了该项但未生效的来源（按优先级从高到低）
+    pub overridden: Vec<ValueOrigin>,
+    /// DuckCoding 应用的值（敏感项已脱敏）
+    pub expected: Option<String>,
+    /// 生效值与应用的值不一致
+    pub mismatch: bool,
+}
+
+/// 工具实际生效的配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    pub tool_id: String,
+    pub project_dir: Option<String>,
+    /// 当前应用的 Profile
+    pub applied_profile: Option<String>,
+    /// 工具已接入透明代理（应用的值为代理地址）
+    pub via_proxy: bool,
+    pub values: Vec<EffectiveValue>,
+}
+
+/// 解析所需的文件位置与进程环境
+#[derive(Debug, Clone, Default)]
+pub struct ResolveContext {
+    /// 工具的用户配置目录（如 `~/.claude`）
+    pub config_dir: PathBuf,
+    /// 在哪个项目目录中启动工具
+    pub project_dir: Option<PathBuf>,
+    pub home_dir: Option<PathBuf>,
+    /// Claude Code 托管设置文件
+    pub managed_settings: Option<PathBuf>,
+    /// 启动工具时的环境变量
+    pub env: HashMap<String, String>,
+}
+
+impl ResolveContext {
+    /// 本机环境：进程环境叠加登录 shell 环境（终端中启动工具时看到的环境）
+    pub fn current(tool: &Tool, project_dir: Option<PathBuf>) -> Self {
+        let mut env: HashMap<String, String> = std::env::vars().collect();
+        if let Some(shell_env) = captured_shell_env() {
+            env.extend(shell_env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Self {
+            config_dir: tool.config_dir.clone(),
+            project_dir,
+            home_dir: dirs::home_dir(),
+            managed_settings: Some(claude_managed_settings_path()),
+            env,
+        }
+    }
+}
+
+/// Claude Code 托管设置文件的位置
+fn claude_managed_settings_path() -> PathBuf {
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+    } else if cfg!(windows) {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json")
+    } else {
+        PathBuf::from("/etc/claude-code/managed-settings.json")
+    }
+}
+
+/// 一个配置项在各来源中的取值
+struct KeyOrigins {
+    key: String,
+    /// 工具实际读取的来源（按优先级从高到低，第一个即生效值）
+    active: Vec<ValueOrigin>,
+    /// 工具不会读取的来源（如未被加载的 `.env`），用于说明为何未生效
+    unused: Vec<ValueOrigin>,
+}
+
+impl KeyOrigins {
+    fn new(key: &str, active: Vec<ValueOrigin>) -> Self {
+        Self {
+            key: key.to_string(),
+            active,
+            unused: Vec::new(),
+        }
+    }
+}
+
+/// DuckCoding 当前应用到工具的配置
+#[derive(Debug, Clone, Default)]
+pub struct AppliedValues {
+    pub profile: Option<String>,
+    pub via_proxy: bool,
+    /// 配置项 → 应用的值（未脱敏）
+    pub values: BTreeMap<String, String>,
+}
+
+/// 读取当前应用的配置：接入透明代理时为代理地址（与本地密钥），否则为激活的 Profile
+pub fn applied_values(tool_id: &str) -> Result<AppliedValues> {
+    let profile_manager = ProfileManager::new()?;
+    let mut applied = AppliedValues {
+        profile: profile_manager.get_active_profile_name(tool_id)?,
+        ..Default::default()
+    };
+    let (base_url_key, api_key_key) = match tool_id {
+        "claude-code" => ("ANTHROPIC_BASE_URL", "ANTHROPIC_AUTH_TOKEN"),
+        "codex" => ("base_url", "api_key"),
+        "gemini-cli" => ("GOOGLE_GEMINI_BASE_URL", "GEMINI_API_KEY"),
+        _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+    };
+
+    let proxy = ProxyConfigManager::new()?.get_config(tool_id)?;
+    if let Some((routing, proxy)) = proxy
+        .as_ref()
+        .and_then(|proxy| proxy.tool_routing.as_ref().map(|routing| (routing, proxy)))
+    {
+        applied.via_proxy = true;
+        let base_url = if tool_id == "codex" {
+            format!("{}/v1", routing.proxy_url)
+        } else {
+            routing.proxy_url.clone()
+        };
+        applied.values.insert(base_url_key.to_string(), base_url);
+        if let Some(key) = &proxy.local_api_key {
+            applied.values.insert(api_key_key.to_string(), key.clone());
+        }
+        return Ok(applied);
+    }
+
+    let Some(name) = applied.profile.clone() else {
+        return Ok(applied);
+    };
+    let (api_key, base_url) = match tool_id {
+        "claude-code" => {
+            let profile = profile_manager.get_claude_profile(&name)?;
+            (profile.api_key, profile.base_url)
+        }
+        "codex" => {
+            let profile = profile_manager.get_codex_profile(&name)?;
+            // 与应用 Profile 时一致：model_provider 为 Profile 名称，Base URL 以 /v1 结尾
+            applied
+                .values
+                .insert("model_provider".to_string(), name.clone());
+            applied
+                .values
+                .insert("wire_api".to_string(), profile.wire_api);
+            let base_url = profile.base_url.trim_end_matches('/');
+            let base_url = if base_url.ends_with("/v1") {
+                base_url.to_string()
+            } else {
+                format!("{base_url}/v1")
+            };
+            (profile.api_key, base_url)
+        }
+        _ => {
+            let profile = profile_manager.get_gemini_profile(&name)?;
+            if let Some(model) = profile.model {
+                applied.values.insert(GEMINI_MODEL.to_string(), model);
+            }
+            (profile.api_key, profile.base_url)
+        }
+    };
+    applied.values.insert(base_url_key.to_string(), base_url);
+    applied.values.insert(api_key_key.to_string(), api_key);
+    Ok(applied)
+}
+
+/// 按工具的优先级规则解析生效配置，并与 `applied` 比较
+pub fn resolve(
+    tool_id: &str,
+    context: &ResolveContext,
+    applied: &AppliedValues,
+) -> Result<EffectiveConfig> {
+    let origins = match tool_id {
+        "claude-code" => claude_origins(context),
+        "codex" => codex_origins(context),
+        "gemini-cli" => gemini_origins(context),
+        _ => return Err(anyhow!("不支持的工具: {}", tool_id)),
+    };
+    let values = origins
+        .into_iter()
+        .map(|origins| {
+            let expected = applied.values.get(&origins.key).map(String::as_str);
+            effective_value(origins, expected)
+        })
+        .collect();
+    Ok(EffectiveConfig {
+        tool_id: tool_id.to_string(),
+        project_dir: context
+            .project_dir
+            .as_ref()
+            .map(|dir| dir.to_string_lossy().to_string()),
+        applied_profile: applied.profile.clone(),
+        via_proxy: applied.via_proxy,
+        values,
+    })
+}
+
+/// 取优先级最高的来源为生效值；比较后再对敏感项脱敏
+fn effective_value(origins: KeyOrigins, expected: Option<&str>) -> EffectiveValue {
+    let KeyOrigins {
+        key,
+        active,
+        unused,
+    } = origins;
+    let mismatch = expected.is_some_and(|expected| {
+        active
+            .first()
+            .is_none_or(|origin| !same_value(&origin.value, expected))
+    });
+    let secret = is_secret_name(&key);
+    let mask = |value: &str| {
+        if secret {
+            mask_secret(value)
+        } else {
+            value.to_string()
+        }
+    };
+    let mask_origin = |origin: ValueOrigin| ValueOrigin {
+        value: mask(&origin.value),
+        ..origin
+    };
+    let mut active = active.into_iter().map(mask_origin);
+    EffectiveValue {
+        effective: active.next(),
+        overridden: active.chain(unused.into_iter().map(mask_origin)).collect(),
+        expected: expected.map(&mask),
+        mismatch,
+        key,
+    }
+}
+
+/// 比较时忽略首尾空白与 URL 结尾的 `/`
+fn same_value(a: &str, b: &str) -> bool {
+    a.trim().trim_end_matches('/') == b.trim().trim_end_matches('/')
+}
+
+fn path_string(path: &Path) -> Option<String> {
+    Some(path.to_string_lossy().to_string())
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    if !path.is_file() {
+        return None;
+    }
+    DataManager::new()
+        .json_uncached()
+        .read(path)
+        .map_err(|e| tracing::warn!(path = %path.display(), error = %e, "读取配置文件失败"))
+        .ok()
+}
+
+fn read_env_file(path: &Path) -> Option<HashMap<String, String>> {
+    if !path.is_file() {
+        return None;
+    }
+    DataManager::new()
+        .env()
+        .read(path)
+        .map_err(|e| tracing::warn!(path = %path.display(), error = %e, "读取 .env 失败"))
+        .ok()
+}
+
+fn env_origin(context: &ResolveContext, name: &str) -> Option<ValueOrigin> {
+    context
+        .env
+        .get(name)
+        .filter(|value| !value.is_empty())
+        .map(|value| ValueOrigin {
+            source: ConfigSource::Environment,
+            path: None,
+            field: name.to_string(),
+            value: value.clone(),
+        })
+}
+
+// ==================== Claude Code ====================
+
+fn claude_origins(context: &ResolveContext) -> Vec<KeyOrigins> {
+    let mut layers: Vec<(ConfigSource, PathBuf)> = Vec::new();
+    if let Some(managed) = &context.managed_settings {
+        layers.push((ConfigSource::Managed, managed.clone()));
+    }
+    let mut settings_layers = Vec::new();
+    // 在用户目录中启动时项目设置即用户设置，不重复计算
+    if let Some(project) = context
+        .project_dir
+        .as_ref()
+        .map(|dir| dir.join(".claude"))
+        .filter(|dir| *dir != context.config_dir)
+    {
+        settings_layers.push((
+            ConfigSource::ProjectLocalSettings,
+            project.join("settings.local.json"),
+        ));
+        settings_layers.push((ConfigSource::ProjectSettings, project.join("settings.json")));
+    }
+    settings_layers.push((
+        ConfigSource::UserSettings,
+        context.config_dir.join("settings.json"),
+    ));
+
+    let read_layers = |layers: &[(ConfigSource, PathBuf)]| -> Vec<(ConfigSource, PathBuf, Value)> {
+        layers
+            .iter()
+            .filter_map(|(source, path)| {
+                read_json(path).map(|settings| (*source, path.clone(), settings))
+            })
+            .collect()
+    };
+    let managed = read_layers(&layers);
+    let settings = read_layers(&settings_layers);
+
+    let from_settings = |(source, path, settings): &(ConfigSource, PathBuf, Value), key: &str| {
+        settings
+            .get("env")
+            .and_then(|env| env.get(key))
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .map(|value| ValueOrigin {
+                source: *source,
+                path: path_string(path),
+                field: format!("env.{key}"),
+                value: value.to_string(),
+            })
+    };
+
+    CLAUDE_KEYS
+        .iter()
+        .map(|&key| {
+            let origins = managed
+                .iter()
+                .filter_map(|layer| from_settings(layer, key))
+                .chain(env_origin(context, key))
+                .chain(
+                    settings
+                        .iter()
+                        .filter_map(|layer| from_settings(layer, key)),
+                )
+                .collect();
+            KeyOrigins::new(key, origins)
+        })
+        .collect()
+}
+
+// ==================== Codex ====================
+
+fn codex_origins(context: &ResolveContext) -> Vec<KeyOrigins> {
+    let config_path = context.config_dir.join("config.toml");
+    let auth_path = context.config_dir.join("auth.json");
+    let config = if config_path.is_file() {
+        DataManager::new()
+            .toml()
+            .read(&config_path)
+            .map_err(|e| tracing::warn!(error = %e, "读取 Codex config.toml 失败"))
+            .ok()
+    } else {
+        None
+    };
+    let config_origin = |field: String, value: &str| ValueOrigin {
+        source: ConfigSource::ConfigToml,
+        path: path_string(&config_path),
+        field,
+        value: value.to_string(),
+    };
+    let get_str = |path: &[&str]| -> Option<String> {
+        let mut value = config.as_ref()?;
+        for part in path {
+            value = value.get(part)?;
+        }
+        value.as_str().map(str::to_string)
+    };
+
+    // profile 选择的 model_provider 优先于顶层的 model_provider
+    let profile = get_str(&["profile"]);
+    let mut provider_origins = Vec::new();
+    if let Some(profile) = &profile {
+        if let Some(provider) = get_str(&["profiles", profile.as_str(), "model_provider"]) {
+            provider_origins.push(config_origin(
+                format!("profiles.{profile}.model_provider"),
+                &provider,
+            ));
+        }
+    }
+    if let Some(provider) = get_str(&["model_provider"]) {
+        provider_origins.push(config_origin("model_provider".to_string(), &provider));
+    }
+    if provider_origins.is_empty() {
+        provider_origins.push(ValueOrigin {
+            source: ConfigSource::Default,
+            path: None,
+            field: "model_provider".to_string(),
+            value: CODEX_BUILTIN_PROVIDER.to_string(),
+        });
+    }
+    let provider = provider_origins[0].value.clone();
+    let provider_field = |name: &str| get_str(&["model_providers", provider.as_str(), name]);
+    let provider_defined = config
+        .as_ref()
+        .and_then(|config| config.get("model_providers"))
+        .and_then(|providers| providers.get(&provider))
+        .is_some();
+
+    let mut base_url = Vec::new();
+    if let Some(url) = provider_field("base_url") {
+        base_url.push(config_origin(
+            format!("model_providers.{provider}.base_url"),
+            &url,
+        ));
+    } else if !provider_defined && provider == CODEX_BUILTIN_PROVIDER {
+        base_url.extend(env_origin(context, "OPENAI_BASE_URL"));
+        base_url.push(ValueOrigin {
+            source: ConfigSource::Default,
+            path: None,
+            field: "base_url".to_string(),
+            value: CODEX_DEFAULT_BASE_URL.to_string(),
+        });
+    }
+
+    let wire_api = provider_field("wire_api")
+        .map(|wire_api| config_origin(format!("model_providers.{provider}.wire_api"), &wire_api))
+        .into_iter()
+        .collect();
+
+    // provider 声明了 env_key 时从该环境变量读取密钥，否则使用 auth.json
+    let api_key = match provider_field("env_key") {
+        Some(env_key) => env_origin(context, &env_key).into_iter().collect(),
+        None => read_json(&auth_path)
+            .as_ref()
+            .and_then(|auth| auth.get("OPENAI_API_KEY"))
+            .and_then(Value::as_str)
+            .filter(|key| !key.is_empty())
+            .map(|key| ValueOrigin {
+                source: ConfigSource::AuthJson,
+                path: path_string(&auth_path),
+                field: "OPENAI_API_KEY".to_string(),
+                value: key.to_string(),
+            })
+            .into_iter()
+            .collect(),
+    };
+
+    let profile = profile
+        .map(|profile| config_origin("profile".to_string(), &profile))
+        .into_iter()
+        .collect();
+    vec![
+        KeyOrigins::new("profile", profile),
+        KeyOrigins::new("model_provider", provider_origins),
+        KeyOrigins::new("base_url", base_url),
+        KeyOrigins::new("wire_api", wire_api),
+        KeyOrigins::new("api_key", api_key),
+    ]
+}
+
+// ==================== Gemini CLI ====================
+
+/// Gemini CLI 查找 `.env` 的顺序：从项目目录向上逐级查找 `.gemini/.env` 与 `.env`，
+/// 最后是用户目录下的 `.gemini/.env` 与 `.env`；只加载第一个存在的文件
+fn gemini_env_files(context: &ResolveContext) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    if let Some(project) = &context.project_dir {
+        for dir in project.ancestors() {
+            files.push(dir.join(".gemini").join(".env"));
+            files.push(dir.join(".env"));
+        }
+    }
+    files.push(context.config_dir.join(".env"));
+    if let Some(home) = &context.home_dir {
+        files.push(home.join(".env"));
+    }
+    let mut seen = std::collections::HashSet::new();
+    files
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+fn gemini_origins(context: &ResolveContext) -> Vec<KeyOrigins> {
+    let env_files: Vec<(PathBuf, HashMap<String, String>)> = gemini_env_files(context)
+        .into_iter()
+        .filter_map(|path| read_env_file(&path).map(|pairs| (path, pairs)))
+        .collect();
+    let from_env_file = |(path, pairs): &(PathBuf, HashMap<String, String>), key: &str| {
+        pairs
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .map(|value| ValueOrigin {
+                source: ConfigSource::EnvFile,
+                path: path_string(path),
+                field: key.to_string(),
+                value: value.clone(),
+            })
+    };
+    // 已存在的环境变量不会被 .env 覆盖；未加载的 .env 排在最后，便于说明为何未生效
+    let (loaded, ignored) = env_files.split_at(env_files.len().min(1));
+    let env_chain = |key: &str| KeyOrigins {
+        key: key.to_string(),
+        active: env_origin(context, key)
+            .into_iter()
+            .chain(loaded.iter().filter_map(|file| from_env_file(file, key)))
+            .collect(),
+        unused: ignored
+            .iter()
+            .filter_map(|file| from_env_file(file, key))
+            .collect(),
+    };
+
+    let mut values: Vec<KeyOrigins> = GEMINI_ENV_KEYS.iter().map(|&key| env_chain(key)).collect();
+
+    // 模型：GEMINI_MODEL（含 .env）优先于项目与用户 settings.json
+    let mut settings_files = Vec::new();
+    if let Some(project) = context
+        .project_dir
+        .as_ref()
+        .map(|dir| dir.join(".gemini"))
+        .filter(|dir| *dir != context.config_dir)
+    {
+        settings_files.push((ConfigSource::ProjectSettings, project.join("settings.json")));
+    }
+    settings_files.push((
+        ConfigSource::UserSettings,
+        context.config_dir.join("settings.json"),
+    ));
+    let mut model = env_chain(GEMINI_MODEL);
+    for (source, path) in settings_files {
+        let Some(settings) = read_json(&path) else {
+            continue;
+        };
+        // 新版为 model.name，旧版为字符串 model
+        let found = match settings.get("model") {
+            Some(Value::String(name)) => Some(("model", name.clone())),
+            Some(model) => model
+                .get("name")
+                .and_then(Value::as_str)
+                .map(|name| ("model.name", name.to_string())),
+            None => None,
+        };
+        if let Some((field, name)) = found.filter(|(_, name)| !name.is_empty()) {
+            model.active.push(ValueOrigin {
+                source,
+                path: path_string(&path),
+                field: field.to_string(),
+                value: name,
+            });
+        }
+    }
+    values.push(model);
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn applied(values: &[(&str, &str)]) -> AppliedValues {
+        AppliedValues {
+            profile: Some("team".to_string()),
+            via_proxy: false,
+            values: values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    fn value<'a>(config: &'a EffectiveConfig, key: &str) -> &'a EffectiveValue {
+        config.values.iter().find(|v| v.key == key).unwrap()
+    }
+
+    fn sources(value: &EffectiveValue) -> Vec<ConfigSource> {
+        value
+            .effective
+            .iter()
+            .chain(&value.overridden)
+            .map(|origin| origin.source)
+            .collect()
+    }
+
+    #[test]
+    fn test_claude_precedence() {
+        let temp = TempDir::new().unwrap();
+        let user = temp.path().join("home/.claude");
+        let project = temp.path().join("work/app");
+        write(
+            &user.join("settings.json"),
+            r#"{"env":{"ANTHROPIC_BASE_URL":"https://relay.example.com","ANTHROPIC_AUTH_TOKEN":"sk-user-token-123456"}}"#,
+        );
+        write(
+            &project.join(".claude/settings.json"),
+            r#"{"env":{"ANTHROPIC_BASE_URL":"https://project.example.com"}}"#,
+        );
+        write(
+            &project.join(".claude/settings.local.json"),
+            r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-local-token-abcdef"}}"#,
+        );
+        let mut context = ResolveContext {
+            config_dir: user.clone(),
+            project_dir: Some(project.clone()),
+            managed_settings: Some(temp.path().join("managed-settings.json")),
+            ..Default::default()
+        };
+        let expected = applied(&[
+            ("ANTHROPIC_BASE_URL", "https://relay.example.com/"),
+            ("ANTHROPIC_AUTH_TOKEN", "sk-user-token-123456"),
+        ]);
+
+        let config = resolve("claude-code", &context, &expected).unwrap();
+        let base_url = value(&config, "ANTHROPIC_BASE_URL");
+        assert_eq!(
+            sources(base_url),
+            vec![ConfigSource::ProjectSettings, ConfigSource::UserSettings]
+        );
+        assert_eq!(
+            base_url.effective.as_ref().unwrap().value,
+            "https://project.example.com"
+        );
+        assert_eq!(
+            base_url.effective.as_ref().unwrap().field,
+            "env.ANTHROPIC_BASE_URL"
+        );
+        assert!(base_url.mismatch);
+
+        // 密钥脱敏，比较使用原值
+        let token = value(&config, "ANTHROPIC_AUTH_TOKEN");
+        let effective = token.effective.as_ref().unwrap();
+        assert_eq!(effective.source, ConfigSource::ProjectLocalSettings);
+        assert_eq!(effective.value, "sk-l...cdef");
+        assert_eq!(token.expected.as_deref(), Some("sk-u...3456"));
+        assert!(token.mismatch);
+        assert!(value(&config, "ANTHROPIC_API_KEY").effective.is_none());
+        assert!(!value(&config, "ANTHROPIC_API_KEY").mismatch);
+
+        // 环境变量优先于项目设置，托管设置优先于一切
+        context.env.insert(
+            "ANTHROPIC_BASE_URL".to_string(),
+            "https://relay.example.com".to_string(),
+        );
+        let config = resolve("claude-code", &context, &expected).unwrap();
+        let base_url = value(&config, "ANTHROPIC_BASE_URL");
+        assert_eq!(
+            sources(base_url),
+            vec![
+                ConfigSource::Environment,
+                ConfigSource::ProjectSettings,
+                ConfigSource::UserSettings
+            ]
+        );
+        assert!(!base_url.mismatch);
+
+        write(
+            &temp.path().join("managed-settings.json"),
+            r#"{"env":{"ANTHROPIC_BASE_URL":"https://gateway.corp.example.com"}}"#,
+        );
+        let config = resolve("claude-code", &context, &expected).unwrap();
+        let base_url = value(&config, "ANTHROPIC_BASE_URL");
+        assert_eq!(sources(base_url)[0], ConfigSource::Managed);
+        assert!(base_url.mismatch);
+    }
+
+    #[test]
+    fn test_codex_profile_and_env_key() {
+        let temp = TempDir::new().unwrap();
+        let codex = temp.path().join(".codex");
+        write(
+            &codex.join("config.toml"),
+            r#"
+model_provider = "relay"
+profile = "work"
+
+[profiles.work]
+model_provider = "corp"
+
+[model_providers.relay]
+base_url = "https://relay.example.com/v1"
+wire_api = "responses"
+
+[model_providers.corp]
+base_url = "https://corp.example.com/v1"
+wire_api = "chat"
+env_key = "CORP_API_KEY"
+"#,
+        );
+        write(
+            &codex.join("auth.json"),
+            r#"{"OPENAI_API_KEY":"sk-auth-json-key-0000"}"#,
+        );
+        let mut context = ResolveContext {
+            config_dir: codex.clone(),
+            ..Default::default()
+        };
+        context.env.insert(
+            "CORP_API_KEY".to_string(),
+            "sk-corp-env-key-9999".to_string(),
+        );
+        let expected = applied(&[
+            ("model_provider", "relay"),
+            ("base_url", "https://relay.example.com/v1"),
+            ("api_key", "sk-auth-json-key-0000"),
+        ]);
+
+        let config = resolve("codex", &context, &expected).unwrap();
+        let provider = value(&config, "model_provider");
+        assert_eq!(
+            provider.effective.as_ref().unwrap().field,
+            "profiles.work.model_provider"
+        );
+        assert_eq!(provider.effective.as_ref().unwrap().value, "corp");
+        assert_eq!(provider.overridden[0].value, "relay");
+        assert!(provider.mismatch);
+
+        let base_url = value(&config, "base_url");
+        assert_eq!(
+            base_url.effective.as_ref().unwrap().field,
+            "model_providers.corp.base_url"
+        );
+        assert!(base_url.mismatch);
+
+        let api_key = value(&config, "api_key");
+        let effective = api_key.effective.as_ref().unwrap();
+        assert_eq!(effective.source, ConfigSource::Environment);
+        assert_eq!(effective.field, "CORP_API_KEY");
+        assert_eq!(effective.value, "sk-c...9999");
+
+        // 去掉 profile 后回到顶层 model_provider，密钥来自 auth.json
+        write(
+            &codex.join("config.toml"),
+            r#"
+model_provider = "relay"
+
+[model_providers.relay]
+base_url = "https://relay.example.com/v1/"
+"#,
+        );
+        let config = resolve("codex", &context, &expected).unwrap();
+        assert!(value(&config, "profile").effective.is_none());
+        assert!(!value(&config, "model_provider").mismatch);
+        assert!(!value(&config, "base_url").mismatch);
+        let api_key = value(&config, "api_key");
+        assert_eq!(sources(api_key), vec![ConfigSource::AuthJson]);
+        assert!(!api_key.mismatch);
+    }
+
+    #[test]
+    fn test_codex_builtin_provider() {
+        let temp = TempDir::new().unwrap();
+        let mut context = ResolveContext {
+            config_dir: temp.path().join(".codex"),
+            ..Default::default()
+        };
+        context.env.insert(
+            "OPENAI_BASE_URL".to_string(),
+            "https://mirror.example.com/v1".to_string(),
+        );
+        let config = resolve("codex", &context, &AppliedValues::default()).unwrap();
+        assert_eq!(
+            sources(value(&config, "model_provider")),
+            vec![ConfigSource::Default]
+        );
+        assert_eq!(
+            sources(value(&config, "base_url")),
+            vec![ConfigSource::Environment, ConfigSource::Default]
+        );
+    }
+
+    #[test]
+    fn test_gemini_first_env_file_wins() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let gemini = home.join(".gemini");
+        let project = temp.path().join("work/app");
+        write(
+            &gemini.join(".env"),
+            "GEMINI_API_KEY=sk-gemini-user-key-1111\nGOOGLE_GEMINI_BASE_URL=https://relay.example.com\n",
+        );
+        write(
+            &gemini.join("settings.json"),
+            r#"{"model":{"name":"gemini-2.5-pro"}}"#,
+        );
+        write(
+            &project.join(".gemini/settings.json"),
+            r#"{"model":"gemini-2.5-flash"}"#,
+        );
+        // 项目的 .env 先被找到，用户目录下的 .env 不再加载
+        write(&temp.path().join("work/.env"), "DEBUG=1\n");
+
+        let context = ResolveContext {
+            config_dir: gemini.clone(),
+            project_dir: Some(project.clone()),
+            home_dir: Some(home.clone()),
+            ..Default::default()
+        };
+        let expected = applied(&[
+            ("GOOGLE_GEMINI_BASE_URL", "https://relay.example.com"),
+            ("GEMINI_API_KEY", "sk-gemini-user-key-1111"),
+        ]);
+        let config = resolve("gemini-cli", &context, &expected).unwrap();
+        let base_url = value(&config, "GOOGLE_GEMINI_BASE_URL");
+        assert!(base_url.effective.is_none());
+        assert_eq!(base_url.overridden[0].source, ConfigSource::EnvFile);
+        assert!(base_url.mismatch);
+
+        let model = value(&config, GEMINI_MODEL);
+        assert_eq!(
+            sources(model),
+            vec![ConfigSource::ProjectSettings, ConfigSource::UserSettings]
+        );
+        assert_eq!(model.effective.as_ref().unwrap().value, "gemini-2.5-flash");
+        assert_eq!(model.overridden[0].field, "model.name");
+
+        // 没有项目 .env 时加载用户目录下的 .env
+        std::fs::remove_file(temp.path().join("work/.env")).unwrap();
+        let config = resolve("gemini-cli", &context, &expected).unwrap();
+        let api_key = value(&config, "GEMINI_API_KEY");
+        assert_eq!(sources(api_key), vec![ConfigSource::EnvFile]);
+        assert!(!api_key.mismatch);
+        assert!(!value(&config, "GOOGLE_GEMINI_BASE_URL").mismatch);
+    }
+}
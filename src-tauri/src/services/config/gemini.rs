@@ -1,6 +1,7 @@
 //! Gemini CLI 配置管理模块
 
 use super::types::{GeminiEnvPayload, GeminiSettingsPayload};
+use super::write_ledger::record_write;
 use super::ToolConfigManager;
 use crate::data::DataManager;
 use crate::models::Tool;
@@ -84,22 +85,28 @@ pub fn save_gemini_settings(settings: &Value, env: &GeminiEnvPayload) -> Result<
     fs::create_dir_all(config_dir).context("创建 Gemini CLI 配置目录失败")?;
 
     manager
-        .json_uncached()
-        .write(&settings_path, settings)
+        .with_file_lock(&settings_path, || {
+            manager.json_uncached().write(&settings_path, settings)
+        })
         .context("写入 Gemini CLI 配置失败")?;
-
-    let mut env_pairs = read_env_pairs(&env_path)?;
-    env_pairs.insert("GEMINI_API_KEY".to_string(), env.api_key.clone());
-    env_pairs.insert("GOOGLE_GEMINI_BASE_URL".to_string(), env.base_url.clone());
-    env_pairs.insert(
-        "GEMINI_MODEL".to_string(),
-        if env.model.trim().is_empty() {
-            "gemini-2.5-pro".to_string()
-        } else {
-            env.model.clone()
-        },
-    );
-    write_env_pairs(&env_path, &env_pairs).context("写入 Gemini CLI .env 失败")?;
+    record_write(&settings_path, "save_settings");
+
+    // .env 读-改-写期间持有文件锁
+    manager.with_file_lock(&env_path, || -> Result<()> {
+        let mut env_pairs = read_env_pairs(&env_path)?;
+        env_pairs.insert("GEMINI_API_KEY".to_string(), env.api_key.clone());
+        env_pairs.insert("GOOGLE_GEMINI_BASE_URL".to_string(), env.base_url.clone());
+        env_pairs.insert(
+            "GEMINI_MODEL".to_string(),
+            if env.model.trim().is_empty() {
+                "gemini-2.5-pro".to_string()
+            } else {
+                env.model.clone()
+            },
+        );
+        write_env_pairs(&env_path, &env_pairs).context("写入 Gemini CLI .env 失败")
+    })?;
+    record_write(&env_path, "save_settings");
 
     Ok(())
 }
@@ -8,8 +8,10 @@
 //! - `utils`: 工具函数（TOML 合并等）
 //! - `claude`: Claude Code 配置管理
 //! - `codex`: Codex 配置管理
+//! - `effective`: 按工具优先级规则解析实际生效的配置及其来源
 //! - `gemini`: Gemini CLI 配置管理
 //! - `watcher`: 外部变更检测与文件监听
+//! - `write_ledger`: DuckCoding 写入配置文件的记录（区分自身写入与外部修改）
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -18,10 +20,12 @@ use serde_json::Value;
 // 模块声明
 pub mod claude;
 pub mod codex;
+pub mod effective;
 pub mod gemini;
 pub mod types;
 pub mod utils;
 pub mod watcher;
+pub mod write_ledger;
 
 // 重导出类型
 pub use types::*;
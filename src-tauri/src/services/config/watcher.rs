@@ -5,6 +5,8 @@
 //! - `NotifyWatcherManager`: 基于 OS 通知的实时监听（性能更优）
 
 use super::types::{ExternalConfigChange, ImportExternalChangeResult};
+use super::write_ledger;
+use crate::data::DataManager;
 use crate::models::Tool;
 use crate::services::profile_manager::ProfileManager;
 use anyhow::{anyhow, Result};
@@ -87,6 +89,15 @@ pub(crate) fn compute_native_checksum(tool: &Tool) -> Option<String> {
     }
 }
 
+/// 工具现有的配置文件是否都与 DuckCoding 最后一次写入一致
+fn written_by_us(tool: &Tool) -> bool {
+    let existing: Vec<PathBuf> = config_paths(tool)
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+    !existing.is_empty() && existing.iter().all(|path| write_ledger::is_own_write(path))
+}
+
 // ========== 外部变更检测与管理 ==========
 
 /// 将外部修改导入为 Profile
@@ -160,7 +171,10 @@ pub fn detect_external_changes() -> Result<Vec<ExternalConfigChange>> {
         let active = active_opt.ok_or_else(|| anyhow!("工具 {} 无激活 Profile", tool.id))?;
         let last_checksum = active.native_checksum.clone();
 
-        if last_checksum.as_ref() != current_checksum.as_ref() {
+        if last_checksum.as_ref() != current_checksum.as_ref() && written_by_us(&tool) {
+            // 内容均为 DuckCoding 最后一次写入的结果：只刷新校验和，不视为外部修改
+            profile_manager.update_active_sync_state(&tool.id, current_checksum, false)?;
+        } else if last_checksum.as_ref() != current_checksum.as_ref() {
             // 标记脏，但保留旧 checksum 以便前端确认后再更新
             profile_manager.mark_active_dirty(&tool.id, true)?;
 
@@ -214,8 +228,9 @@ pub fn mark_external_change(
 
     let last_checksum = active_opt.as_ref().and_then(|a| a.native_checksum.clone());
 
-    // 若与当前记录的 checksum 一致，则视为内部写入，保持非脏状态
-    let checksum_changed = last_checksum.as_ref() != checksum.as_ref();
+    // 若与当前记录的 checksum 一致，或写入记录表明是 DuckCoding 自己写入的，则视为内部写入
+    let checksum_changed =
+        last_checksum.as_ref() != checksum.as_ref() && !write_ledger::is_own_write(&path);
 
     // 更新 checksum 和 dirty 状态
     profile_manager.update_active_sync_state(&tool.id, checksum.clone(), checksum_changed)?;
@@ -295,6 +310,7 @@ impl ConfigWatcher {
 
                     if stable_checksum.is_some() && stable_checksum != last_checksum {
                         last_checksum = stable_checksum.clone();
+                        DataManager::global().invalidate(&watch_path);
                         let change = FileChangeEvent {
                             tool_id: tool_id.clone(),
                             path: watch_path.clone(),
@@ -359,6 +375,8 @@ impl NotifyWatcherManager {
                                 return;
                             }
                             last_checksum = checksum.clone();
+                            // 外部修改后丢弃该文件的读取缓存
+                            DataManager::global().invalidate(&path_for_cb);
 
                             match mark_external_change(
                                 &tool_for_state,
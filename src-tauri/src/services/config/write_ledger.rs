@@ -0,0 +1,235 @@
+//! 配置文件写入记录（write ledger）
+//!
+//! DuckCoding 每次写入工具配置文件后，把路径、内容哈希、时间与触发操作追加到
+//! `write_ledger.json`（最多保留 `MAX_LEDGER_ENTRIES` 条）。据此可以判断文件的当前内容
+//! 是否仍是我们最后一次写入的结果：
+//! - 外部变更监听跳过自己写入引起的文件事件
+//! - `get_file_provenance` 向用户展示“由 DuckCoding 修改”还是“被外部修改”
+//! - 差异预览以最后一次写入的哈希作为对比基准
+
+use crate::data::DataManager;
+use crate::utils::config::config_dir;
+use crate::utils::file_helpers::file_checksum;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 最多保留的写入记录条数（超出时丢弃最早的记录）
+pub const MAX_LEDGER_ENTRIES: usize = 500;
+
+/// 进程内写锁（文件锁之外，保证同一进程内的读-改-写串行）
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一次写入记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub path: String,
+    /// 写入后文件内容的 SHA256
+    pub sha256: String,
+    /// 写入时间（Unix 时间戳）
+    pub written_at: i64,
+    /// 触发写入的操作（如 `apply_profile`、`save_settings`）
+    pub action: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LedgerStore {
+    /// 按时间升序
+    #[serde(default)]
+    entries: Vec<LedgerEntry>,
+}
+
+/// 文件来源判定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvenanceStatus {
+    /// 当前内容与 DuckCoding 最后一次写入一致
+    DuckCoding,
+    /// DuckCoding 写入后文件又被修改
+    ModifiedExternally,
+    /// DuckCoding 从未写入过该文件
+    Unknown,
+    /// 文件不存在
+    Missing,
+}
+
+/// 配置文件来源
+#[derive(Debug, Clone, Serialize)]
+pub struct FileProvenance {
+    pub path: String,
+    pub status: ProvenanceStatus,
+    /// 当前内容的 SHA256（文件不存在时为 None）
+    pub current_sha256: Option<String>,
+    /// DuckCoding 最后一次写入该文件的记录
+    pub last_write: Option<LedgerEntry>,
+}
+
+/// 写入记录文件名（位于 DuckCoding 配置目录）
+pub const LEDGER_FILE: &str = "write_ledger.json";
+
+/// 写入记录管理器
+pub struct WriteLedger {
+    data_manager: DataManager,
+    ledger_path: PathBuf,
+}
+
+impl WriteLedger {
+    pub fn new() -> Result<Self> {
+        let ledger_path = config_dir()
+            .map_err(|e| anyhow::anyhow!("获取配置目录失败: {}", e))?
+            .join(LEDGER_FILE);
+        Ok(Self::at(ledger_path))
+    }
+
+    /// 使用指定文件保存记录（测试用）
+    pub fn at(ledger_path: PathBuf) -> Self {
+        Self {
+            data_manager: DataManager::new(),
+            ledger_path,
+        }
+    }
+
+    fn load(&self) -> Result<LedgerStore> {
+        if !self.ledger_path.exists() {
+            return Ok(LedgerStore::default());
+        }
+        let value = self.data_manager.json_uncached().read(&self.ledger_path)?;
+        serde_json::from_value(value).context("解析 write_ledger.json 失败")
+    }
+
+    /// 记录一次写入（按文件的当前内容计算哈希）
+    pub fn record(&self, path: &Path, action: &str) -> Result<LedgerEntry> {
+        let entry = LedgerEntry {
+            path: normalize(path),
+            sha256: file_checksum(path)?,
+            written_at: chrono::Utc::now().timestamp(),
+            action: action.to_string(),
+        };
+
+        let _guard = WRITE_LOCK.lock().unwrap();
+        self.data_manager
+            .with_file_lock(&self.ledger_path, || -> Result<()> {
+                let mut store = self.load()?;
+                store.entries.push(entry.clone());
+                if store.entries.len() > MAX_LEDGER_ENTRIES {
+                    let overflow = store.entries.len() - MAX_LEDGER_ENTRIES;
+                    store.entries.drain(..overflow);
+                }
+                let value = serde_json::to_value(&store).context("序列化写入记录失败")?;
+                self.data_manager
+                    .json_uncached()
+                    .write(&self.ledger_path, &value)?;
+                Ok(())
+            })?;
+        Ok(entry)
+    }
+
+    /// 该文件最后一次由 DuckCoding 写入的记录
+    pub fn last_write(&self, path: &Path) -> Result<Option<LedgerEntry>> {
+        let key = normalize(path);
+        Ok(self
+            .load()?
+            .entries
+            .into_iter()
+            .rev()
+            .find(|entry| entry.path == key))
+    }
+
+    /// 比较文件当前内容与最后一次写入记录
+    pub fn provenance(&self, path: &Path) -> Result<FileProvenance> {
+        let last_write = self.last_write(path)?;
+        let current_sha256 = path.exists().then(|| file_checksum(path)).transpose()?;
+        let status = match (&current_sha256, &last_write) {
+            (None, _) => ProvenanceStatus::Missing,
+            (Some(_), None) => ProvenanceStatus::Unknown,
+            (Some(current), Some(entry)) if *current == entry.sha256 => {
+                ProvenanceStatus::DuckCoding
+            }
+            (Some(_), Some(_)) => ProvenanceStatus::ModifiedExternally,
+        };
+        Ok(FileProvenance {
+            path: path.to_string_lossy().to_string(),
+            status,
+            current_sha256,
+            last_write,
+        })
+    }
+}
+
+/// 统一路径写法，避免同一文件因 `..`、符号链接等记录成不同的 key
+fn normalize(path: &Path) -> String {
+    path.canonicalize()
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// 记录一次配置写入（失败只记日志，不影响写入本身）
+pub fn record_write(path: &Path, action: &str) {
+    if let Err(e) = WriteLedger::new().and_then(|ledger| ledger.record(path, action)) {
+        tracing::warn!(path = ?path, action, error = ?e, "记录配置写入失败");
+    }
+}
+
+/// 文件当前内容是否为 DuckCoding 最后一次写入的结果
+pub fn is_own_write(path: &Path) -> bool {
+    WriteLedger::new()
+        .and_then(|ledger| ledger.provenance(path))
+        .map(|provenance| provenance.status == ProvenanceStatus::DuckCoding)
+        .unwrap_or(false)
+}
+
+/// 查询配置文件的来源
+pub fn get_file_provenance(path: &Path) -> Result<FileProvenance> {
+    WriteLedger::new()?.provenance(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_provenance_tracks_own_and_external_writes() {
+        let dir = TempDir::new().unwrap();
+        let ledger = WriteLedger::at(dir.path().join("write_ledger.json"));
+        let config = dir.path().join("settings.json");
+
+        assert_eq!(
+            ledger.provenance(&config).unwrap().status,
+            ProvenanceStatus::Missing
+        );
+
+        fs::write(&config, r#"{"env":{}}"#).unwrap();
+        assert_eq!(
+            ledger.provenance(&config).unwrap().status,
+            ProvenanceStatus::Unknown
+        );
+
+        ledger.record(&config, "apply_profile").unwrap();
+        let provenance = ledger.provenance(&config).unwrap();
+        assert_eq!(provenance.status, ProvenanceStatus::DuckCoding);
+        assert_eq!(provenance.last_write.unwrap().action, "apply_profile");
+
+        fs::write(&config, r#"{"env":{"EDITED":"1"}}"#).unwrap();
+        assert_eq!(
+            ledger.provenance(&config).unwrap().status,
+            ProvenanceStatus::ModifiedExternally
+        );
+    }
+
+    #[test]
+    fn test_ledger_is_capped() {
+        let dir = TempDir::new().unwrap();
+        let ledger = WriteLedger::at(dir.path().join("write_ledger.json"));
+        let config = dir.path().join("settings.json");
+        fs::write(&config, "{}").unwrap();
+
+        for _ in 0..MAX_LEDGER_ENTRIES + 5 {
+            ledger.record(&config, "save_settings").unwrap();
+        }
+        assert_eq!(ledger.load().unwrap().entries.len(), MAX_LEDGER_ENTRIES);
+    }
+}
@@ -2,22 +2,204 @@
 //
 // 仪表板状态管理服务
 
-use crate::data::DataManager;
-use crate::models::dashboard::DashboardStore;
+use crate::data::{BackupPolicy, DataManager};
+use crate::models::dashboard::{
+    ActivityEntry, ActivityKind, DashboardSelectionCleared, DashboardStore, LaunchPreferences,
+    DASHBOARD_STORE_VERSION, MAX_ACTIVITY_ENTRIES,
+};
 use crate::utils::config::config_dir;
-use anyhow::Result;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tokio::sync::broadcast;
+
+/// 清理失效选择后发送的事件（负载为 `DashboardSelectionCleared`）
+pub const DASHBOARD_SELECTION_CLEARED_EVENT: &str = "dashboard-selection-cleared";
+
+/// 仪表板存储写入后发送的事件（负载为 `DashboardChangedEvent`）
+pub const DASHBOARD_CHANGED_EVENT: &str = "dashboard-changed";
+
+/// 仪表板存储变化通知（写入 dashboard.json 后发送）
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardChangedEvent {
+    pub updated_at: i64,
+}
+
+static CHANGES: Lazy<broadcast::Sender<DashboardChangedEvent>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// 订阅仪表板存储变化
+pub fn subscribe_dashboard_changes() -> broadcast::Receiver<DashboardChangedEvent> {
+    CHANGES.subscribe()
+}
+
+/// 单步迁移：将 JSON 从版本 N 升级到 N+1
+type MigrationStep = fn(&mut Value) -> Result<()>;
+
+/// 迁移步骤表，`MIGRATIONS[i]` 负责 v{i+1} → v{i+2}
+const MIGRATIONS: &[MigrationStep] = &[
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+];
+
+/// 读取 JSON 中的数据版本（缺失视为 v1）
+fn store_version_of(value: &Value) -> Result<u32> {
+    match value.get("version") {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| anyhow::anyhow!("dashboard.json 的 version 无效: {v}")),
+    }
+}
+
+/// 将 dashboard.json 逐步升级到当前版本
+///
+/// 返回迁移前的版本；版本高于当前应用支持的版本时拒绝加载，避免覆盖新版本写入的数据。
+pub fn migrate_dashboard_store_value(value: &mut Value) -> Result<u32> {
+    let from = store_version_of(value)?;
+    if from > DASHBOARD_STORE_VERSION {
+        anyhow::bail!(
+            "dashboard.json 的版本为 v{from}，当前应用仅支持到 v{DASHBOARD_STORE_VERSION}。\
+             该文件可能由更新版本的 DuckCoding 写入，请升级应用后重试（文件未做任何修改）"
+        );
+    }
+
+    for version in from..DASHBOARD_STORE_VERSION {
+        let step = MIGRATIONS[(version - 1) as usize];
+        step(value)
+            .with_context(|| format!("dashboard.json 迁移 v{version} → v{} 失败", version + 1))?;
+        value["version"] = Value::from(version + 1);
+    }
+    Ok(from)
+}
+
+/// v1 → v2：新增 `tool_provider_bindings`
+///
+/// v1 只有一个全局的 `selected_provider_id`，对所有已选择实例的工具生效；
+/// 迁移时将其绑定到这些工具上，保持升级前后各工具使用的供应商不变。
+fn migrate_v1_to_v2(value: &mut Value) -> Result<()> {
+    let root = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("dashboard.json 顶层不是对象"))?;
+
+    let mut bindings = serde_json::Map::new();
+    if let Some(provider_id) = root.get("selected_provider_id").and_then(Value::as_str) {
+        if let Some(selections) = root
+            .get("tool_instance_selections")
+            .and_then(Value::as_object)
+        {
+            for tool_id in selections.keys() {
+                bindings.insert(tool_id.clone(), Value::from(provider_id));
+            }
+        }
+    }
+    root.insert(
+        "tool_provider_bindings".to_string(),
+        Value::Object(bindings),
+    );
+    Ok(())
+}
+
+/// v2 → v3：新增 `activity`（默认为空，无需转换数据）
+fn migrate_v2_to_v3(_value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+/// v3 → v4：新增 `pinned_provider_ids`（默认为空，无需转换数据）
+fn migrate_v3_to_v4(_value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+/// v4 → v5：新增 `launch_preferences`（默认为空，无需转换数据）
+fn migrate_v4_to_v5(_value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+/// 按置顶顺序拆分供应商列表，返回 `(置顶, 其余)`
+///
+/// 置顶列表中不存在的供应商 ID 直接忽略，其余供应商保持原顺序。
+pub fn partition_pinned<T>(
+    items: Vec<T>,
+    pinned_ids: &[String],
+    id_of: impl Fn(&T) -> &str,
+) -> (Vec<T>, Vec<T>) {
+    let mut pinned: Vec<(usize, T)> = Vec::new();
+    let mut others = Vec::new();
+    for item in items {
+        match pinned_ids.iter().position(|id| id == id_of(&item)) {
+            Some(rank) => pinned.push((rank, item)),
+            None => others.push(item),
+        }
+    }
+    pinned.sort_by_key(|(rank, _)| *rank);
+    (pinned.into_iter().map(|(_, item)| item).collect(), others)
+}
+
+/// 记录一条活动（失败只记日志，不影响调用方的主流程）
+pub fn record_activity(kind: ActivityKind, subject: &str, detail: Option<String>) {
+    let result = DashboardManager::new()
+        .and_then(|manager| manager.append_activity(ActivityEntry::new(kind, subject, detail)));
+    if let Err(e) = result {
+        tracing::warn!(error = ?e, ?kind, subject, "记录活动失败");
+    }
+}
+
+/// 迁移前备份路径：dashboard.json → dashboard.json.v{N}.bak
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}.bak"));
+    path.with_file_name(name)
+}
+
+/// 文件状态戳（修改时间 + 长度），用于判断缓存是否仍与磁盘一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// 缓存的存储及读取时的文件状态
+#[derive(Debug, Clone)]
+struct CachedStore {
+    store: DashboardStore,
+    stamp: Option<FileStamp>,
+}
+
+type SharedCache = Arc<Mutex<Option<CachedStore>>>;
+
+/// 进程内共享的缓存（同时作为写锁）
+///
+/// 各个 State 各自构造 DashboardManager，共享同一把锁才能保证并发的读-改-写不丢失更新。
+static SHARED_CACHE: OnceCell<SharedCache> = OnceCell::new();
 
 /// 仪表板状态管理器
 pub struct DashboardManager {
     data_manager: Arc<DataManager>,
     store_path: PathBuf,
-    cache: Arc<Mutex<Option<DashboardStore>>>,
+    cache: SharedCache,
 }
 
 impl DashboardManager {
-    /// 创建新的 DashboardManager 实例
+    /// 创建新的 DashboardManager 实例（所有实例共享同一缓存与写锁）
     pub fn new() -> Result<Self> {
         let data_manager = Arc::new(DataManager::new());
         let store_path = config_dir()
@@ -27,48 +209,109 @@ impl DashboardManager {
         Ok(Self {
             data_manager,
             store_path,
-            cache: Arc::new(Mutex::new(None)),
+            cache: SHARED_CACHE
+                .get_or_init(|| Arc::new(Mutex::new(None)))
+                .clone(),
         })
     }
 
-    /// 读取存储（带缓存）
+    /// 读取存储（带缓存，文件被外部修改时重新读取）
     pub fn load_store(&self) -> Result<DashboardStore> {
-        // 检查缓存
-        if let Some(cached) = self.cache.lock().unwrap().as_ref() {
-            return Ok(cached.clone());
+        let mut cache = self.cache.lock().unwrap();
+        self.load_locked(&mut cache)
+    }
+
+    /// 在持有缓存锁的情况下读取存储
+    fn load_locked(&self, cache: &mut Option<CachedStore>) -> Result<DashboardStore> {
+        let stamp = FileStamp::of(&self.store_path);
+
+        // 缓存仅在文件状态未变化时可信
+        if let Some(cached) = cache.as_ref() {
+            if stamp.is_some() && cached.stamp == stamp {
+                return Ok(cached.store.clone());
+            }
         }
 
         // 文件不存在则返回默认值
-        if !self.store_path.exists() {
+        if stamp.is_none() {
             tracing::warn!("dashboard.json 不存在，返回默认配置");
             let default_store = DashboardStore::default();
             // 初次创建时保存默认配置
-            let _ = self.save_store(&default_store);
+            let _ = self.save_locked(cache, &default_store);
             return Ok(default_store);
         }
 
-        // 从文件读取
-        let json_value = self.data_manager.json().read(&self.store_path)?;
+        // 从文件读取，旧版本先逐步迁移
+        let mut json_value = self.data_manager.json().read(&self.store_path)?;
+        let from = migrate_dashboard_store_value(&mut json_value)?;
         let store: DashboardStore = serde_json::from_value(json_value)
             .map_err(|e| anyhow::anyhow!("反序列化 DashboardStore 失败: {}", e))?;
 
+        if from < DASHBOARD_STORE_VERSION {
+            let backup = backup_path(&self.store_path, from);
+            std::fs::copy(&self.store_path, &backup)
+                .with_context(|| format!("备份迁移前的 dashboard.json 失败: {backup:?}"))?;
+            self.save_locked(cache, &store)?;
+            tracing::info!(
+                from,
+                to = DASHBOARD_STORE_VERSION,
+                backup = ?backup,
+                "dashboard.json 已迁移到新版本"
+            );
+            return Ok(store);
+        }
+
         // 更新缓存
-        *self.cache.lock().unwrap() = Some(store.clone());
+        *cache = Some(CachedStore {
+            store: store.clone(),
+            stamp,
+        });
 
         Ok(store)
     }
 
-    /// 保存存储
-    fn save_store(&self, store: &DashboardStore) -> Result<()> {
+    /// 在持有缓存锁的情况下保存存储
+    fn save_locked(&self, cache: &mut Option<CachedStore>, store: &DashboardStore) -> Result<()> {
         let json_value = serde_json::to_value(store)
             .map_err(|e| anyhow::anyhow!("序列化 DashboardStore 失败: {}", e))?;
-        self.data_manager
-            .json()
-            .write(&self.store_path, &json_value)?;
-        *self.cache.lock().unwrap() = Some(store.clone());
+        self.data_manager.write_json_with_backup(
+            &self.store_path,
+            &json_value,
+            &BackupPolicy::beside(&self.store_path),
+        )?;
+        *cache = Some(CachedStore {
+            store: store.clone(),
+            stamp: FileStamp::of(&self.store_path),
+        });
         Ok(())
     }
 
+    /// 读-改-写存储（全程持有进程内锁与文件锁，有变化时才写入并刷新 `updated_at`）
+    fn update_store<T>(&self, f: impl FnOnce(&mut DashboardStore) -> T) -> Result<T> {
+        let mut cache = self.cache.lock().unwrap();
+        let mut changed = None;
+        let result = self.data_manager.with_file_lock(&self.store_path, || {
+            // 文件状态戳保证其他进程写入后会重新读取
+            let original = self.load_locked(&mut cache)?;
+            let mut store = original.clone();
+
+            let result = f(&mut store);
+            if store != original {
+                store.updated_at = chrono::Utc::now().timestamp();
+                self.save_locked(&mut cache, &store)?;
+                changed = Some(store.updated_at);
+            }
+            Ok(result)
+        })?;
+        drop(cache);
+
+        // 写入落盘并释放锁后再通知，监听方重新读取即可看到新数据
+        if let Some(updated_at) = changed {
+            let _ = CHANGES.send(DashboardChangedEvent { updated_at });
+        }
+        Ok(result)
+    }
+
     /// 获取工具实例选择
     pub fn get_tool_instance_selection(&self, tool_id: &str) -> Result<Option<String>> {
         Ok(self
@@ -80,13 +323,9 @@ impl DashboardManager {
 
     /// 设置工具实例选择
     pub fn set_tool_instance_selection(&self, tool_id: String, instance_id: String) -> Result<()> {
-        let mut store = self.load_store()?;
-
-        store.tool_instance_selections.insert(tool_id, instance_id);
-        store.updated_at = chrono::Utc::now().timestamp();
-
-        self.save_store(&store)?;
-        Ok(())
+        self.update_store(|store| {
+            store.tool_instance_selections.insert(tool_id, instance_id);
+        })
     }
 
     /// 获取最后选中的供应商 ID
@@ -96,16 +335,211 @@ impl DashboardManager {
 
     /// 设置最后选中的供应商 ID
     pub fn set_selected_provider_id(&self, provider_id: Option<String>) -> Result<()> {
-        let mut store = self.load_store()?;
+        self.update_store(|store| {
+            store.selected_provider_id = provider_id;
+        })
+    }
 
-        store.selected_provider_id = provider_id;
-        store.updated_at = chrono::Utc::now().timestamp();
+    /// 获取工具绑定的供应商 ID
+    pub fn get_tool_provider_binding(&self, tool_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .load_store()?
+            .tool_provider_bindings
+            .get(tool_id)
+            .cloned())
+    }
 
-        self.save_store(&store)?;
-        Ok(())
+    /// 设置工具绑定的供应商 ID（`None` 表示解除绑定）
+    pub fn set_tool_provider_binding(
+        &self,
+        tool_id: String,
+        provider_id: Option<String>,
+    ) -> Result<()> {
+        self.update_store(|store| match provider_id {
+            Some(provider_id) => {
+                store.tool_provider_bindings.insert(tool_id, provider_id);
+            }
+            None => {
+                store.tool_provider_bindings.remove(&tool_id);
+            }
+        })
+    }
+
+    /// 获取实例的启动偏好
+    pub fn get_launch_preferences(&self, instance_id: &str) -> Result<Option<LaunchPreferences>> {
+        Ok(self
+            .load_store()?
+            .launch_preferences
+            .get(instance_id)
+            .cloned())
+    }
+
+    /// 设置实例的启动偏好
+    ///
+    /// `check_cwd` 为 true 时要求工作目录在本机存在；WSL/SSH 实例的路径无法在本机校验，由调用方关闭。
+    pub fn set_launch_preferences(
+        &self,
+        instance_id: &str,
+        preferences: LaunchPreferences,
+        check_cwd: bool,
+    ) -> Result<()> {
+        preferences.validate().map_err(|e| anyhow::anyhow!(e))?;
+        if check_cwd {
+            if let Some(cwd) = preferences.cwd.as_deref() {
+                if !Path::new(cwd).is_dir() {
+                    anyhow::bail!("工作目录不存在: {}", cwd);
+                }
+            }
+        }
+
+        self.update_store(|store| {
+            store
+                .launch_preferences
+                .insert(instance_id.to_string(), preferences);
+        })
+    }
+
+    /// 获取置顶供应商 ID（按置顶顺序）
+    pub fn pinned_provider_ids(&self) -> Result<Vec<String>> {
+        Ok(self.load_store()?.pinned_provider_ids)
+    }
+
+    /// 置顶供应商（已置顶时保持原位置）
+    ///
+    /// 调用方需确认供应商存在。
+    pub fn pin_provider(&self, provider_id: &str) -> Result<Vec<String>> {
+        self.update_store(|store| {
+            if !store.pinned_provider_ids.iter().any(|id| id == provider_id) {
+                store.pinned_provider_ids.push(provider_id.to_string());
+            }
+            store.pinned_provider_ids.clone()
+        })
+    }
+
+    /// 取消置顶供应商（未置顶时无操作）
+    pub fn unpin_provider(&self, provider_id: &str) -> Result<Vec<String>> {
+        self.update_store(|store| {
+            store.pinned_provider_ids.retain(|id| id != provider_id);
+            store.pinned_provider_ids.clone()
+        })
+    }
+
+    /// 将实例选择与启动偏好中的旧实例 ID 改写为新 ID（实例 ID 迁移后调用）
+    ///
+    /// 返回改写的条目数；新 ID 已有启动偏好时保留新 ID 的记录。
+    pub fn rename_instance_ids(&self, aliases: &HashMap<String, String>) -> Result<usize> {
+        if aliases.is_empty() {
+            return Ok(0);
+        }
+        self.update_store(|store| {
+            let mut renamed = 0;
+            for instance_id in store.tool_instance_selections.values_mut() {
+                if let Some(new_id) = aliases.get(instance_id) {
+                    *instance_id = new_id.clone();
+                    renamed += 1;
+                }
+            }
+            for (old_id, new_id) in aliases {
+                if let Some(preferences) = store.launch_preferences.remove(old_id) {
+                    store
+                        .launch_preferences
+                        .entry(new_id.clone())
+                        .or_insert(preferences);
+                    renamed += 1;
+                }
+            }
+            renamed
+        })
+    }
+
+    /// 清理指向已删除实例或供应商的选择
+    ///
+    /// 移除失效的 `tool_instance_selections` / `tool_provider_bindings` / `pinned_provider_ids`
+    /// 条目，`selected_provider_id` 失效时置空。没有需要清理的内容时不写文件。
+    pub fn validate_and_clean(
+        &self,
+        known_instance_ids: &HashSet<String>,
+        known_provider_ids: &HashSet<String>,
+    ) -> Result<DashboardSelectionCleared> {
+        let cleared = self.update_store(|store| {
+            let mut cleared = DashboardSelectionCleared::default();
+
+            store
+                .tool_instance_selections
+                .retain(|tool_id, instance_id| {
+                    let keep = known_instance_ids.contains(instance_id);
+                    if !keep {
+                        cleared
+                            .removed_instance_selections
+                            .insert(tool_id.clone(), instance_id.clone());
+                    }
+                    keep
+                });
+            store.tool_provider_bindings.retain(|tool_id, provider_id| {
+                let keep = known_provider_ids.contains(provider_id);
+                if !keep {
+                    cleared
+                        .removed_provider_bindings
+                        .insert(tool_id.clone(), provider_id.clone());
+                }
+                keep
+            });
+            if store
+                .selected_provider_id
+                .as_ref()
+                .is_some_and(|id| !known_provider_ids.contains(id))
+            {
+                cleared.cleared_provider_id = store.selected_provider_id.take();
+            }
+            store.pinned_provider_ids.retain(|id| {
+                let keep = known_provider_ids.contains(id);
+                if !keep {
+                    cleared.removed_pinned_provider_ids.push(id.clone());
+                }
+                keep
+            });
+            store.launch_preferences.retain(|instance_id, _| {
+                let keep = known_instance_ids.contains(instance_id);
+                if !keep {
+                    cleared.removed_launch_preferences.push(instance_id.clone());
+                }
+                keep
+            });
+            cleared
+        })?;
+
+        if !cleared.is_empty() {
+            tracing::info!(?cleared, "已清理 dashboard.json 中失效的选择");
+        }
+        Ok(cleared)
+    }
+
+    /// 追加活动记录（超出上限时丢弃最早的记录）
+    pub fn append_activity(&self, entry: ActivityEntry) -> Result<()> {
+        self.update_store(|store| {
+            store.activity.push(entry);
+            let overflow = store.activity.len().saturating_sub(MAX_ACTIVITY_ENTRIES);
+            store.activity.drain(..overflow);
+        })
+    }
+
+    /// 获取最近的活动记录（按时间倒序）
+    pub fn recent_activity(&self, limit: usize) -> Result<Vec<ActivityEntry>> {
+        Ok(self
+            .load_store()?
+            .activity
+            .into_iter()
+            .rev()
+            .take(limit)
+            .collect())
+    }
+
+    /// 清空活动记录
+    pub fn clear_activity(&self) -> Result<()> {
+        self.update_store(|store| store.activity.clear())
     }
 
-    /// 清除缓存（用于测试或强制刷新）
+    /// 清除缓存（强制下次从磁盘读取）
     pub fn clear_cache(&self) {
         *self.cache.lock().unwrap() = None;
     }
@@ -120,6 +554,483 @@ impl Default for DashboardManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tempfile::TempDir;
+
+    /// 引入版本迁移前写出的 dashboard.json（v1）
+    const V1_FIXTURE: &str = r#"{
+  "version": 1,
+  "tool_instance_selections": {
+    "claude-code": "claude-code-local",
+    "codex": "codex-wsl-Ubuntu"
+  },
+  "selected_provider_id": "duckcoding",
+  "updated_at": 1735689600
+}"#;
+
+    /// 引入供应商绑定后的 dashboard.json（v2）
+    const V2_FIXTURE: &str = r#"{
+  "version": 2,
+  "tool_instance_selections": {
+    "claude-code": "claude-code-local"
+  },
+  "selected_provider_id": "duckcoding",
+  "tool_provider_bindings": {
+    "claude-code": "relay"
+  },
+  "updated_at": 1740000000
+}"#;
+
+    /// 引入活动记录后的 dashboard.json（v3）
+    const V3_FIXTURE: &str = r#"{
+  "version": 3,
+  "tool_instance_selections": {
+    "codex": "codex-local"
+  },
+  "selected_provider_id": null,
+  "tool_provider_bindings": {},
+  "activity": [
+    {
+      "timestamp": 1760000000,
+      "kind": "tool_updated",
+      "subject": "codex-local",
+      "detail": "0.66.0"
+    }
+  ],
+  "updated_at": 1760000000
+}"#;
+
+    /// 引入置顶供应商后的 dashboard.json（v4）
+    const V4_FIXTURE: &str = r#"{
+  "version": 4,
+  "tool_instance_selections": {},
+  "selected_provider_id": "duckcoding",
+  "tool_provider_bindings": {},
+  "activity": [],
+  "pinned_provider_ids": ["relay", "duckcoding"],
+  "updated_at": 1770000000
+}"#;
+
+    /// 当前版本（v5）的 dashboard.json
+    const V5_FIXTURE: &str = r#"{
+  "version": 5,
+  "tool_instance_selections": {
+    "claude-code": "claude-code-local"
+  },
+  "selected_provider_id": "duckcoding",
+  "tool_provider_bindings": {},
+  "activity": [],
+  "pinned_provider_ids": ["duckcoding"],
+  "launch_preferences": {
+    "claude-code-local": {
+      "cwd": "/home/dev/project",
+      "extra_args": ["--verbose"],
+      "env_overrides": {"DEBUG": "1"}
+    }
+  },
+  "updated_at": 1780000000
+}"#;
+
+    fn create_test_manager(temp_dir: &TempDir) -> DashboardManager {
+        DashboardManager {
+            data_manager: Arc::new(DataManager::new()),
+            store_path: temp_dir.path().join("dashboard.json"),
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_value() {
+        let mut value: Value = serde_json::from_str(V1_FIXTURE).unwrap();
+        assert_eq!(migrate_dashboard_store_value(&mut value).unwrap(), 1);
+
+        assert_eq!(value["version"], DASHBOARD_STORE_VERSION);
+        assert_eq!(value["tool_provider_bindings"]["claude-code"], "duckcoding");
+        assert_eq!(value["tool_provider_bindings"]["codex"], "duckcoding");
+        assert_eq!(value["selected_provider_id"], "duckcoding");
+
+        // 未选择供应商时不产生绑定
+        let mut value = serde_json::json!({
+            "version": 1,
+            "tool_instance_selections": {"claude-code": "claude-code-local"},
+            "selected_provider_id": null,
+            "updated_at": 0
+        });
+        migrate_dashboard_store_value(&mut value).unwrap();
+        assert_eq!(value["tool_provider_bindings"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_migrate_v2_value() {
+        let mut value: Value = serde_json::from_str(V2_FIXTURE).unwrap();
+        assert_eq!(migrate_dashboard_store_value(&mut value).unwrap(), 2);
+        assert_eq!(value["version"], DASHBOARD_STORE_VERSION);
+        assert_eq!(value["tool_provider_bindings"]["claude-code"], "relay");
+
+        let store: DashboardStore = serde_json::from_value(value).unwrap();
+        assert!(store.activity.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_v3_value() {
+        let mut value: Value = serde_json::from_str(V3_FIXTURE).unwrap();
+        assert_eq!(migrate_dashboard_store_value(&mut value).unwrap(), 3);
+        assert_eq!(value["version"], DASHBOARD_STORE_VERSION);
+
+        let store: DashboardStore = serde_json::from_value(value).unwrap();
+        assert_eq!(store.activity.len(), 1);
+        assert!(store.pinned_provider_ids.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_v4_value() {
+        let mut value: Value = serde_json::from_str(V4_FIXTURE).unwrap();
+        assert_eq!(migrate_dashboard_store_value(&mut value).unwrap(), 4);
+        assert_eq!(value["version"], DASHBOARD_STORE_VERSION);
+
+        let store: DashboardStore = serde_json::from_value(value).unwrap();
+        assert_eq!(store.pinned_provider_ids, vec!["relay", "duckcoding"]);
+        assert!(store.launch_preferences.is_empty());
+    }
+
+    #[test]
+    fn test_current_version_untouched() {
+        let mut value: Value = serde_json::from_str(V5_FIXTURE).unwrap();
+        let original = value.clone();
+        assert_eq!(
+            migrate_dashboard_store_value(&mut value).unwrap(),
+            DASHBOARD_STORE_VERSION
+        );
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn test_load_migrates_and_backs_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        std::fs::write(&manager.store_path, V1_FIXTURE).unwrap();
+
+        let store = manager.load_store().unwrap();
+        assert_eq!(store.version, DASHBOARD_STORE_VERSION);
+        assert_eq!(
+            store
+                .tool_provider_bindings
+                .get("codex")
+                .map(String::as_str),
+            Some("duckcoding")
+        );
+
+        let backup =
+            std::fs::read_to_string(temp_dir.path().join("dashboard.json.v1.bak")).unwrap();
+        assert_eq!(backup, V1_FIXTURE);
+
+        let on_disk: Value =
+            serde_json::from_str(&std::fs::read_to_string(&manager.store_path).unwrap()).unwrap();
+        assert_eq!(on_disk["version"], DASHBOARD_STORE_VERSION);
+    }
+
+    #[test]
+    fn test_future_version_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        let future = r#"{"version": 99, "tool_instance_selections": {}, "updated_at": 0}"#;
+        std::fs::write(&manager.store_path, future).unwrap();
+
+        let err = manager.load_store().unwrap_err();
+        assert!(err.to_string().contains("v99"));
+        // 文件保持原样，不被默认值覆盖
+        assert_eq!(
+            std::fs::read_to_string(&manager.store_path).unwrap(),
+            future
+        );
+    }
+
+    #[test]
+    fn test_tool_provider_binding() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+
+        manager
+            .set_tool_provider_binding("codex".to_string(), Some("relay".to_string()))
+            .unwrap();
+        assert_eq!(
+            manager.get_tool_provider_binding("codex").unwrap(),
+            Some("relay".to_string())
+        );
+
+        manager
+            .set_tool_provider_binding("codex".to_string(), None)
+            .unwrap();
+        assert_eq!(manager.get_tool_provider_binding("codex").unwrap(), None);
+    }
+
+    #[test]
+    fn test_rename_instance_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        manager
+            .set_tool_instance_selection("codex".to_string(), "codex-ssh-dev".to_string())
+            .unwrap();
+        manager
+            .set_tool_instance_selection("claude-code".to_string(), "claude-code-local".to_string())
+            .unwrap();
+
+        let aliases: HashMap<String, String> = [(
+            "codex-ssh-dev".to_string(),
+            "codex-ssh-duck-dev-example-com-22".to_string(),
+        )]
+        .into();
+        assert_eq!(manager.rename_instance_ids(&aliases).unwrap(), 1);
+        assert_eq!(
+            manager
+                .get_tool_instance_selection("codex")
+                .unwrap()
+                .as_deref(),
+            Some("codex-ssh-duck-dev-example-com-22")
+        );
+        assert_eq!(
+            manager
+                .get_tool_instance_selection("claude-code")
+                .unwrap()
+                .as_deref(),
+            Some("claude-code-local")
+        );
+        // 再次执行没有需要改写的条目
+        assert_eq!(manager.rename_instance_ids(&aliases).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_validate_and_clean_with_populated_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        std::fs::write(&manager.store_path, V2_FIXTURE).unwrap();
+        manager
+            .set_tool_instance_selection("codex".to_string(), "codex-ssh-dev".to_string())
+            .unwrap();
+        // 此时缓存已填充
+        assert!(manager.cache.lock().unwrap().is_some());
+
+        let instances: HashSet<String> = ["claude-code-local".to_string()].into();
+        let providers: HashSet<String> = ["duckcoding".to_string()].into();
+        let cleared = manager.validate_and_clean(&instances, &providers).unwrap();
+
+        assert_eq!(
+            cleared
+                .removed_instance_selections
+                .get("codex")
+                .map(String::as_str),
+            Some("codex-ssh-dev")
+        );
+        assert_eq!(
+            cleared
+                .removed_provider_bindings
+                .get("claude-code")
+                .map(String::as_str),
+            Some("relay")
+        );
+        assert_eq!(cleared.cleared_provider_id, None);
+
+        // 缓存与文件均已更新
+        assert_eq!(manager.get_tool_instance_selection("codex").unwrap(), None);
+        assert_eq!(
+            manager.get_tool_instance_selection("claude-code").unwrap(),
+            Some("claude-code-local".to_string())
+        );
+        manager.clear_cache();
+        assert_eq!(manager.get_tool_instance_selection("codex").unwrap(), None);
+        assert_eq!(
+            manager.get_tool_provider_binding("claude-code").unwrap(),
+            None
+        );
+
+        // 再次清理无变化
+        assert!(manager
+            .validate_and_clean(&instances, &providers)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_and_clean_stale_provider() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        std::fs::write(&manager.store_path, V2_FIXTURE).unwrap();
+        manager.load_store().unwrap();
+
+        let instances: HashSet<String> = ["claude-code-local".to_string()].into();
+        let cleared = manager
+            .validate_and_clean(&instances, &HashSet::new())
+            .unwrap();
+        assert_eq!(cleared.cleared_provider_id.as_deref(), Some("duckcoding"));
+        assert_eq!(manager.get_selected_provider_id().unwrap(), None);
+    }
+
+    #[test]
+    fn test_cache_reloads_after_external_write() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        manager
+            .set_selected_provider_id(Some("duckcoding".to_string()))
+            .unwrap();
+        assert!(manager.cache.lock().unwrap().is_some());
+
+        // 其他进程（或旧版本应用）直接改写文件
+        std::fs::write(&manager.store_path, V2_FIXTURE).unwrap();
+
+        assert_eq!(
+            manager.get_tool_provider_binding("claude-code").unwrap(),
+            Some("relay".to_string())
+        );
+    }
+
+    #[test]
+    fn test_concurrent_updates_not_lost() {
+        let temp_dir = TempDir::new().unwrap();
+        let shared: SharedCache = Arc::new(Mutex::new(None));
+        // 模拟两个 State 各自构造的管理器
+        let managers: Vec<Arc<DashboardManager>> = (0..2)
+            .map(|_| {
+                Arc::new(DashboardManager {
+                    data_manager: Arc::new(DataManager::new()),
+                    store_path: temp_dir.path().join("dashboard.json"),
+                    cache: shared.clone(),
+                })
+            })
+            .collect();
+
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let manager = managers[t % 2].clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        manager
+                            .set_tool_instance_selection(
+                                format!("tool-{t}-{i}"),
+                                format!("instance-{t}-{i}"),
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        managers[0].clear_cache();
+        let store = managers[0].load_store().unwrap();
+        assert_eq!(store.tool_instance_selections.len(), 200);
+        assert_eq!(
+            store
+                .tool_instance_selections
+                .get("tool-7-24")
+                .map(String::as_str),
+            Some("instance-7-24")
+        );
+    }
+
+    #[test]
+    fn test_activity_ring() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        std::fs::write(&manager.store_path, V3_FIXTURE).unwrap();
+
+        for i in 0..MAX_ACTIVITY_ENTRIES {
+            manager
+                .append_activity(ActivityEntry::new(
+                    ActivityKind::ProxyStarted,
+                    "claude-code",
+                    Some(i.to_string()),
+                ))
+                .unwrap();
+        }
+
+        // 最早的一条（来自文件）已被挤出
+        let all = manager.recent_activity(usize::MAX).unwrap();
+        assert_eq!(all.len(), MAX_ACTIVITY_ENTRIES);
+        assert!(all.iter().all(|e| e.kind == ActivityKind::ProxyStarted));
+
+        let recent = manager.recent_activity(2).unwrap();
+        assert_eq!(recent[0].detail.as_deref(), Some("99"));
+        assert_eq!(recent[1].detail.as_deref(), Some("98"));
+
+        manager.clear_activity().unwrap();
+        assert!(manager.recent_activity(10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pin_and_unpin_provider() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+
+        manager.pin_provider("relay").unwrap();
+        manager.pin_provider("duckcoding").unwrap();
+        // 重复置顶保持原位置
+        assert_eq!(
+            manager.pin_provider("relay").unwrap(),
+            vec!["relay".to_string(), "duckcoding".to_string()]
+        );
+
+        assert_eq!(
+            manager.unpin_provider("relay").unwrap(),
+            vec!["duckcoding".to_string()]
+        );
+
+        // 删除供应商后清理置顶
+        let cleared = manager
+            .validate_and_clean(&HashSet::new(), &HashSet::new())
+            .unwrap();
+        assert_eq!(cleared.removed_pinned_provider_ids, vec!["duckcoding"]);
+        assert!(manager.pinned_provider_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_launch_preferences() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        let cwd = temp_dir.path().to_string_lossy().to_string();
+
+        let prefs = LaunchPreferences {
+            cwd: Some(cwd.clone()),
+            extra_args: vec!["--verbose".to_string()],
+            env_overrides: Default::default(),
+            node_runtime: None,
+        };
+        manager
+            .set_launch_preferences("claude-code-local", prefs.clone(), true)
+            .unwrap();
+        assert_eq!(
+            manager.get_launch_preferences("claude-code-local").unwrap(),
+            Some(prefs)
+        );
+
+        // 本机不存在的目录被拒绝，远程实例跳过校验
+        let missing = LaunchPreferences {
+            cwd: Some(format!("{cwd}/missing")),
+            ..Default::default()
+        };
+        assert!(manager
+            .set_launch_preferences("claude-code-local", missing.clone(), true)
+            .is_err());
+        manager
+            .set_launch_preferences("codex-wsl-Ubuntu", missing, false)
+            .unwrap();
+
+        // 删除实例后清理
+        let instances: HashSet<String> = ["claude-code-local".to_string()].into();
+        let cleared = manager
+            .validate_and_clean(&instances, &HashSet::new())
+            .unwrap();
+        assert_eq!(cleared.removed_launch_preferences, vec!["codex-wsl-Ubuntu"]);
+    }
+
+    #[test]
+    fn test_partition_pinned() {
+        let items = vec!["a", "b", "c", "d"];
+        let pinned = vec!["c".to_string(), "missing".to_string(), "a".to_string()];
+        let (pinned, others) = partition_pinned(items, &pinned, |s| *s);
+        assert_eq!(pinned, vec!["c", "a"]);
+        assert_eq!(others, vec!["b", "d"]);
+    }
 
     #[test]
     fn test_dashboard_manager_creation() {
@@ -131,7 +1042,7 @@ mod tests {
     fn test_load_default_store() {
         let manager = DashboardManager::new().unwrap();
         let store = manager.load_store().unwrap();
-        assert_eq!(store.version, 1);
+        assert_eq!(store.version, DASHBOARD_STORE_VERSION);
         assert!(store.tool_instance_selections.is_empty());
         assert!(store.selected_provider_id.is_none());
     }
@@ -0,0 +1,446 @@
+//! 诊断包导出
+//!
+//! 将排查问题常用的信息打包为一个 zip：
+//! - `system.json`：应用版本、平台信息、日志文件路径
+//! - `path.txt`：工具检测实际搜索的 PATH
+//! - `config/*.json`：dashboard / providers / proxy / tools（检测结果）的脱敏副本
+//! - `logs/install.log`：最近的安装、更新相关日志
+//! - `logs/app-tail.log`：应用日志尾部（包含命令执行记录）
+//! - `logs/proxy-access.log`：代理访问日志尾部
+//!
+//! 所有文件都会经过密钥脱敏，写入归档前再做一次 `sk-` / `Bearer` 残留扫描，
+//! 扫描不通过则拒绝生成诊断包。
+//!
+//! 归档采用不压缩（stored）的 zip 格式，避免引入额外依赖。
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::proxy::export::{export_dir, redact_secrets};
+use crate::core::log_viewer::log_files;
+use crate::models::telemetry::TelemetryEvent;
+use crate::services::telemetry::record_event;
+use crate::services::tool::ToolInstanceDB;
+
+/// 参与导出的最近日志文件数
+const RECENT_LOG_FILES: usize = 2;
+
+/// 应用日志尾部行数
+const APP_LOG_TAIL_LINES: usize = 2000;
+
+/// 安装日志、访问日志尾部行数
+const FILTERED_LOG_TAIL_LINES: usize = 500;
+
+/// 代理访问日志标识（与 proxy_instance 中的 tracing 消息一致）
+const ACCESS_LOG_MARKER: &str = "代理访问日志";
+
+/// 安装、更新相关日志关键字
+const INSTALL_LOG_KEYWORDS: &[&str] = &["安装", "更新", "install", "update", "npm"];
+
+/// 导出的配置文件（文件名即归档内名称）
+const CONFIG_FILES: &[&str] = &["dashboard.json", "providers.json", "proxy.json"];
+
+/// 字段名包含以下片段时整体替换为占位符
+const SECRET_KEY_FRAGMENTS: &[&str] = &[
+    "token",
+    "key",
+    "secret",
+    "password",
+    "authorization",
+    "cookie",
+];
+
+/// 脱敏占位符
+const REDACTED: &str = "***";
+
+/// 脱敏后仍不允许出现的模式
+static LEAK_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [r"sk-[A-Za-z0-9_\-]{4,}", r"(?i)bearer\s+[A-Za-z0-9_\-\.=]"]
+        .iter()
+        .map(|p| Regex::new(p).expect("残留扫描正则无效"))
+        .collect()
+});
+
+/// 脱敏后仍残留的敏感模式（用于导出前的最终检查）
+pub(crate) fn leaked_pattern(content: &str) -> Option<&'static Regex> {
+    LEAK_PATTERNS.iter().find(|p| p.is_match(content))
+}
+
+/// 诊断包输入（由命令层收集运行时信息）
+#[derive(Debug, Clone)]
+pub struct DiagnosticsInput {
+    pub app_version: String,
+    pub effective_path: Vec<String>,
+    pub config_dir: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+#[derive(Serialize)]
+struct SystemInfo<'a> {
+    app_version: &'a str,
+    os: &'static str,
+    arch: &'static str,
+    family: &'static str,
+    generated_at: String,
+    log_dir: String,
+    log_files: Vec<String>,
+}
+
+/// 生成诊断包，写入 `~/.duckcoding/exports/`，返回文件路径
+pub fn export_diagnostics(input: &DiagnosticsInput) -> Result<PathBuf> {
+    let name = format!(
+        "duckcoding-diagnostics-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = export_dir()?.join(name);
+    write_diagnostics(input, &path)?;
+    record_event(TelemetryEvent::DiagnosticsExported);
+    Ok(path)
+}
+
+fn write_diagnostics(input: &DiagnosticsInput, output: &Path) -> Result<()> {
+    let entries = collect_entries(input)?;
+
+    for (name, content) in &entries {
+        if let Some(pattern) = leaked_pattern(content) {
+            anyhow::bail!("诊断包文件 {name} 脱敏后仍匹配敏感模式 {pattern}，已取消导出");
+        }
+    }
+
+    let file = fs::File::create(output).with_context(|| format!("创建诊断包失败: {output:?}"))?;
+    let mut zip = StoredZipWriter::new(std::io::BufWriter::new(file));
+    for (name, content) in &entries {
+        zip.add(name, content.as_bytes())?;
+    }
+    zip.finish()?.flush()?;
+    Ok(())
+}
+
+/// 收集诊断包内容（已脱敏）
+fn collect_entries(input: &DiagnosticsInput) -> Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+
+    let system = SystemInfo {
+        app_version: &input.app_version,
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        family: std::env::consts::FAMILY,
+        generated_at: Local::now().to_rfc3339(),
+        log_dir: input.log_dir.display().to_string(),
+        log_files: log_files(&input.log_dir)
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect(),
+    };
+    entries.push((
+        "system.json".to_string(),
+        serde_json::to_string_pretty(&system).context("序列化系统信息失败")?,
+    ));
+    entries.push((
+        "path.txt".to_string(),
+        redact_secrets(&input.effective_path.join("\n")),
+    ));
+
+    for file in CONFIG_FILES {
+        let path = input.config_dir.join(file);
+        if !path.exists() {
+            continue;
+        }
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("读取配置文件失败: {path:?}"))?;
+        entries.push((format!("config/{file}"), redact_config(&raw)));
+    }
+
+    // 工具实例存放在 SQLite 中，导出为 JSON 便于阅读
+    let tools_db = input.config_dir.join("tools.db");
+    if tools_db.exists() {
+        let instances = ToolInstanceDB::open(&tools_db)?.get_all_instances()?;
+        let raw = serde_json::to_string(&instances).context("序列化工具实例失败")?;
+        entries.push(("config/tools.json".to_string(), redact_config(&raw)));
+    }
+
+    let lines = recent_log_lines(&input.log_dir);
+    let tail = |filter: &dyn Fn(&str) -> bool, limit: usize| {
+        let selected: Vec<&String> = lines.iter().filter(|line| filter(line)).collect();
+        let start = selected.len().saturating_sub(limit);
+        selected[start..]
+            .iter()
+            .map(|line| redact_secrets(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    entries.push((
+        "logs/install.log".to_string(),
+        tail(
+            &|line| {
+                let lower = line.to_lowercase();
+                INSTALL_LOG_KEYWORDS.iter().any(|kw| lower.contains(kw))
+            },
+            FILTERED_LOG_TAIL_LINES,
+        ),
+    ));
+    entries.push((
+        "logs/app-tail.log".to_string(),
+        tail(&|_| true, APP_LOG_TAIL_LINES),
+    ));
+    entries.push((
+        "logs/proxy-access.log".to_string(),
+        tail(
+            &|line| line.contains(ACCESS_LOG_MARKER),
+            FILTERED_LOG_TAIL_LINES,
+        ),
+    ));
+
+    Ok(entries)
+}
+
+/// 脱敏配置文件：JSON 按字段名替换密钥，再统一做文本脱敏；非 JSON 仅做文本脱敏
+fn redact_config(raw: &str) -> String {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            let text = serde_json::to_string_pretty(&value).unwrap_or_default();
+            redact_secrets(&text)
+        }
+        Err(_) => redact_secrets(raw),
+    }
+}
+
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let lower = key.to_lowercase();
+                let sensitive = SECRET_KEY_FRAGMENTS
+                    .iter()
+                    .any(|fragment| lower.contains(fragment));
+                if sensitive && (child.is_string() || child.is_number()) {
+                    *child = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// 读取最近几个滚动日志文件的全部行（按时间顺序）
+fn recent_log_lines(log_dir: &Path) -> Vec<String> {
+    let files = log_files(log_dir);
+    let start = files.len().saturating_sub(RECENT_LOG_FILES);
+
+    files[start..]
+        .iter()
+        .filter_map(|file| fs::read(file).ok())
+        .flat_map(|bytes| {
+            // 日志中可能混有非 UTF-8 内容，逐行容错
+            String::from_utf8_lossy(&bytes)
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// ==================== 最小 zip 写入器 ====================
+
+/// CRC-32（IEEE）查找表
+static CRC32_TABLE: Lazy<[u32; 256]> = Lazy::new(|| {
+    let mut table = [0u32; 256];
+    for (i, slot) in table.iter_mut().enumerate() {
+        let mut crc = i as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+        *slot = crc;
+    }
+    table
+});
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// 仅支持不压缩条目的 zip 写入器（单文件不超过 4 GiB）
+struct StoredZipWriter<W: Write> {
+    writer: W,
+    offset: u32,
+    central: Vec<u8>,
+    count: u16,
+}
+
+impl<W: Write> StoredZipWriter<W> {
+    /// 通用标志位：文件名使用 UTF-8
+    const FLAG_UTF8: u16 = 0x0800;
+    /// DOS 日期 1980-01-01
+    const DOS_DATE: u16 = 0x0021;
+
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            central: Vec::new(),
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<()> {
+        let crc = crc32(data);
+        let size = u32::try_from(data.len()).context("诊断包单个文件过大")?;
+        let name_len = u16::try_from(name.len()).context("诊断包文件名过长")?;
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        local.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        local.extend_from_slice(&Self::FLAG_UTF8.to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes()); // stored
+        local.extend_from_slice(&0u16.to_le_bytes()); // time
+        local.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        local.extend_from_slice(&crc.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&name_len.to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes()); // extra
+        local.extend_from_slice(name.as_bytes());
+
+        self.central
+            .extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version made by
+        self.central.extend_from_slice(&20u16.to_le_bytes()); // version needed
+        self.central
+            .extend_from_slice(&Self::FLAG_UTF8.to_le_bytes());
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // stored
+        self.central.extend_from_slice(&0u16.to_le_bytes()); // time
+        self.central
+            .extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        self.central.extend_from_slice(&crc.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central.extend_from_slice(&size.to_le_bytes());
+        self.central.extend_from_slice(&name_len.to_le_bytes());
+        self.central.extend_from_slice(&[0u8; 12]); // extra, comment, disk, attrs
+        self.central.extend_from_slice(&self.offset.to_le_bytes());
+        self.central.extend_from_slice(name.as_bytes());
+
+        self.writer.write_all(&local)?;
+        self.writer.write_all(data)?;
+        self.offset = self
+            .offset
+            .checked_add(local.len() as u32 + size)
+            .context("诊断包过大")?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<W> {
+        let central_len = u32::try_from(self.central.len()).context("诊断包目录过大")?;
+        self.writer.write_all(&self.central)?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        end.extend_from_slice(&[0u8; 4]); // disk numbers
+        end.extend_from_slice(&self.count.to_le_bytes());
+        end.extend_from_slice(&self.count.to_le_bytes());
+        end.extend_from_slice(&central_len.to_le_bytes());
+        end.extend_from_slice(&self.offset.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment
+        self.writer.write_all(&end)?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PLANTED_KEY: &str = "sk-plantedSecretValue1234567890";
+    const PLANTED_BEARER: &str = "plantedBearerToken.abc";
+
+    fn planted_input(dir: &Path) -> DiagnosticsInput {
+        let config_dir = dir.join("config");
+        let log_dir = dir.join("logs");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::create_dir_all(&log_dir).unwrap();
+
+        fs::write(
+            config_dir.join("providers.json"),
+            format!(
+                r#"{{"providers":[{{"name":"relay","api_key":"{PLANTED_KEY}","note":"plain"}}]}}"#
+            ),
+        )
+        .unwrap();
+        fs::write(
+            config_dir.join("proxy.json"),
+            r#"{"proxy_configs":{"claude-code":{"local_api_key":"raw-local-secret"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            log_dir.join("duckcoding.2024-01-01"),
+            format!(
+                "开始安装 claude-code\n代理访问日志 Authorization: Bearer {PLANTED_BEARER}\n调用 {PLANTED_KEY}\n"
+            ),
+        )
+        .unwrap();
+
+        DiagnosticsInput {
+            app_version: "1.2.3".to_string(),
+            effective_path: vec!["/usr/bin".to_string()],
+            config_dir,
+            log_dir,
+        }
+    }
+
+    #[test]
+    fn test_planted_secrets_never_reach_archive() {
+        let dir = TempDir::new().unwrap();
+        let input = planted_input(dir.path());
+        let output = dir.path().join("diag.zip");
+        write_diagnostics(&input, &output).unwrap();
+
+        // stored 归档中文件内容以明文存放，可直接按字节检查
+        let bytes = fs::read(&output).unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(!text.contains(PLANTED_KEY));
+        assert!(!text.contains("plantedSecretValue"));
+        assert!(!text.contains(PLANTED_BEARER));
+        assert!(!text.contains("raw-local-secret"));
+        assert!(text.contains("开始安装 claude-code"));
+        assert!(text.contains("\"note\": \"plain\""));
+        assert!(text.contains("duckcoding.2024-01-01"));
+    }
+
+    #[test]
+    fn test_archive_layout() {
+        let dir = TempDir::new().unwrap();
+        let input = planted_input(dir.path());
+        let output = dir.path().join("diag.zip");
+        write_diagnostics(&input, &output).unwrap();
+
+        let bytes = fs::read(&output).unwrap();
+        assert_eq!(&bytes[..4], &0x0403_4b50u32.to_le_bytes());
+        let end = &bytes[bytes.len() - 22..];
+        assert_eq!(&end[..4], &0x0605_4b50u32.to_le_bytes());
+        // system.json + path.txt + 2 个配置 + 3 个日志
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 7);
+    }
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
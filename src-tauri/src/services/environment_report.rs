@@ -0,0 +1,664 @@
+//! 环境报告导出
+//!
+//! 汇总本机「装了哪些 AI CLI、什么版本、用哪个供应商」，供团队统一环境或写入 Wiki：
+//! - 工具实例（按工具分组）：环境类型、版本、安装方式、路径（用户目录替换为 `~`）、npm 实例所属的 Node.js
+//! - 本机的 Node.js 运行时：来源、版本、npm 全局目录
+//! - 各工具的供应商绑定：当前 Profile、Profile 来源供应商、代理凭证注入供应商（仅名称）
+//! - 透明代理开关与端口、应用版本
+//!
+//! 报告不含生成时间，条目按固定顺序排列，Markdown 输出可直接提交并比较差异。
+//! 输出统一经过密钥脱敏，写入前再做一次残留扫描（与诊断包共用）。
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::diagnostics::leaked_pattern;
+use super::profile_manager::{ActiveStore, ProfileSource, ProfilesStore};
+use super::proxy::export::redact_secrets;
+use crate::models::proxy_config::ProxyStore;
+use crate::models::{InstallMethod, NodeRuntime, Provider, Tool, ToolInstance, ToolType};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::provider_manager::ProviderManager;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::tool::node_env::{describe, owning_runtime};
+use crate::services::tool::ToolInstanceDB;
+
+/// 常见用户目录前缀（Linux / macOS / root / Windows），用于远程与 WSL 路径的匿名化
+static HOME_PREFIX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(/home/[^/]+|/Users/[^/]+|/root|[a-z]:[\\/]Users[\\/][^\\/]+)([\\/].*)?$")
+        .expect("用户目录正则无效")
+});
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl ReportFormat {
+    /// 按文件扩展名决定格式（`.json` 为 JSON，其余为 Markdown）
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// 环境报告
+#[derive(Debug, Clone, Serialize)]
+pub struct EnvironmentReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// 工具 ID → 实例列表
+    pub tools: BTreeMap<String, Vec<ToolReportEntry>>,
+    pub node_runtimes: Vec<NodeRuntimeReportEntry>,
+    pub bindings: Vec<ProviderBinding>,
+    pub proxies: Vec<ProxyReportEntry>,
+}
+
+/// 一个工具实例
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReportEntry {
+    pub tool_name: String,
+    /// Local / WSL / SSH
+    pub tool_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wsl_distro: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub installed: bool,
+    pub version: Option<String>,
+    pub install_method: Option<String>,
+    /// 安装路径（用户目录替换为 `~`）
+    pub install_path: Option<String>,
+    /// npm 安装的本地实例所属的 Node.js 运行时（如 `v20.11.0 (nvm)`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_runtime: Option<String>,
+}
+
+/// 一个 Node.js 运行时
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeRuntimeReportEntry {
+    pub source: String,
+    pub version: Option<String>,
+    /// node 路径（用户目录替换为 `~`）
+    pub node_path: String,
+    /// npm 全局目录（用户目录替换为 `~`）
+    pub global_prefix: Option<String>,
+    /// 是否为 PATH 中第一个 node
+    pub active: bool,
+}
+
+/// 工具的供应商绑定（仅名称，不含任何凭证）
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderBinding {
+    pub tool_id: String,
+    /// 当前激活的 Profile
+    pub active_profile: Option<String>,
+    /// Profile 导入自的供应商（自定义 Profile 为空）
+    pub provider: Option<String>,
+    /// 透明代理凭证注入绑定的供应商
+    pub proxy_provider: Option<String>,
+}
+
+/// 工具的透明代理设置
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyReportEntry {
+    pub tool_id: String,
+    pub enabled: bool,
+    pub port: u16,
+}
+
+/// 生成报告所需的原始数据
+pub struct ReportSources {
+    pub app_version: String,
+    pub instances: Vec<ToolInstance>,
+    pub profiles: ProfilesStore,
+    pub active: ActiveStore,
+    pub proxy: ProxyStore,
+    pub providers: Vec<Provider>,
+    pub node_runtimes: Vec<NodeRuntime>,
+    pub home_dir: Option<PathBuf>,
+}
+
+impl ReportSources {
+    /// 从本机配置读取（Node.js 运行时需异步扫描，由调用方传入）
+    pub fn collect(app_version: &str, node_runtimes: Vec<NodeRuntime>) -> Result<Self> {
+        let profile_manager = ProfileManager::new()?;
+        Ok(Self {
+            app_version: app_version.to_string(),
+            instances: ToolInstanceDB::new()?.get_all_instances()?,
+            profiles: profile_manager.load_profiles_store()?,
+            active: profile_manager.load_active_store()?,
+            proxy: ProxyConfigManager::new()?.load_proxy_store()?,
+            providers: ProviderManager::new()?.list_providers()?,
+            node_runtimes,
+            home_dir: dirs::home_dir(),
+        })
+    }
+}
+
+/// 导出环境报告到指定路径，返回写入的路径
+pub fn export_environment_report(
+    app_version: &str,
+    format: ReportFormat,
+    output: &Path,
+    node_runtimes: Vec<NodeRuntime>,
+) -> Result<PathBuf> {
+    let report = build_report(&ReportSources::collect(app_version, node_runtimes)?);
+    write_report(&report, format, output)?;
+    Ok(output.to_path_buf())
+}
+
+fn write_report(report: &EnvironmentReport, format: ReportFormat, output: &Path) -> Result<()> {
+    let content = render(report, format)?;
+    if let Some(pattern) = leaked_pattern(&content) {
+        anyhow::bail!("环境报告脱敏后仍匹配敏感模式 {pattern}，已取消导出");
+    }
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("创建目录失败: {parent:?}"))?;
+    }
+    fs::write(output, content).with_context(|| format!("写入环境报告失败: {output:?}"))
+}
+
+/// 渲染报告（已脱敏）
+pub fn render(report: &EnvironmentReport, format: ReportFormat) -> Result<String> {
+    let content = match format {
+        ReportFormat::Markdown => render_markdown(report),
+        ReportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(report).context("序列化环境报告失败")?;
+            json.push('\n');
+            json
+        }
+    };
+    Ok(redact_secrets(&content))
+}
+
+/// 由原始数据构建报告（不读取任何文件）
+pub fn build_report(sources: &ReportSources) -> EnvironmentReport {
+    let home = sources.home_dir.as_deref();
+    let is_windows = cfg!(windows);
+
+    let mut tools: BTreeMap<String, Vec<(String, ToolReportEntry)>> = BTreeMap::new();
+    for instance in &sources.instances {
+        let entry = ToolReportEntry {
+            tool_name: instance.tool_name.clone(),
+            tool_type: instance.tool_type.as_str().to_string(),
+            wsl_distro: instance.wsl_distro.clone(),
+            label: instance.label.clone(),
+            installed: instance.installed,
+            version: instance.version.clone(),
+            install_method: instance.install_method.as_ref().map(method_name),
+            install_path: instance
+                .install_path
+                .as_deref()
+                .map(|path| anonymize_path(path, home)),
+            node_runtime: instance
+                .install_path
+                .as_deref()
+                .filter(|_| {
+                    instance.tool_type == ToolType::Local
+                        && instance.install_method == Some(InstallMethod::Npm)
+                })
+                .and_then(|path| owning_runtime(path, &sources.node_runtimes, is_windows))
+                .map(describe),
+        };
+        let sort_key = format!(
+            "{}/{}",
+            type_rank(&instance.tool_type),
+            instance.instance_id
+        );
+        tools
+            .entry(instance.base_id.clone())
+            .or_default()
+            .push((sort_key, entry));
+    }
+    let tools = tools
+        .into_iter()
+        .map(|(tool_id, mut entries)| {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            (tool_id, entries.into_iter().map(|(_, e)| e).collect())
+        })
+        .collect();
+
+    let node_runtimes = sources
+        .node_runtimes
+        .iter()
+        .map(|runtime| NodeRuntimeReportEntry {
+            source: runtime.source.as_str().to_string(),
+            version: runtime.version.clone(),
+            node_path: anonymize_path(&runtime.node_path, home),
+            global_prefix: runtime
+                .global_prefix
+                .as_deref()
+                .map(|prefix| anonymize_path(prefix, home)),
+            active: runtime.active,
+        })
+        .collect();
+
+    let tool_ids: Vec<String> = Tool::all().into_iter().map(|tool| tool.id).collect();
+
+    let bindings = tool_ids
+        .iter()
+        .map(|tool_id| {
+            let active_profile = sources
+                .active
+                .get_active(tool_id)
+                .map(|active| active.profile.clone());
+            let provider = active_profile.as_deref().and_then(|name| {
+                match profile_source(&sources.profiles, tool_id, name)? {
+                    ProfileSource::ImportedFromProvider { provider_name, .. } => {
+                        Some(provider_name.clone())
+                    }
+                    ProfileSource::Custom => None,
+                }
+            });
+            let proxy_provider = sources
+                .proxy
+                .get_config(tool_id)
+                .map(|config| &config.credential_injection)
+                .filter(|injection| injection.enabled)
+                .and_then(|injection| injection.provider_id.as_deref())
+                .map(|id| {
+                    sources
+                        .providers
+                        .iter()
+                        .find(|provider| provider.id == id)
+                        .map(|provider| provider.name.clone())
+                        .unwrap_or_else(|| id.to_string())
+                });
+            ProviderBinding {
+                tool_id: tool_id.clone(),
+                active_profile,
+                provider,
+                proxy_provider,
+            }
+        })
+        .collect();
+
+    let proxies = tool_ids
+        .iter()
+        .filter_map(|tool_id| {
+            let config = sources.proxy.get_config(tool_id)?;
+            Some(ProxyReportEntry {
+                tool_id: tool_id.clone(),
+                enabled: config.enabled,
+                port: config.port,
+            })
+        })
+        .collect();
+
+    EnvironmentReport {
+        app_version: sources.app_version.clone(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        tools,
+        node_runtimes,
+        bindings,
+        proxies,
+    }
+}
+
+fn profile_source<'a>(
+    store: &'a ProfilesStore,
+    tool_id: &str,
+    name: &str,
+) -> Option<&'a ProfileSource> {
+    match tool_id {
+        "claude-code" => store.claude_code.get(name).map(|p| &p.source),
+        "codex" => store.codex.get(name).map(|p| &p.source),
+        "gemini-cli" => store.gemini_cli.get(name).map(|p| &p.source),
+        _ => None,
+    }
+}
+
+fn type_rank(tool_type: &ToolType) -> u8 {
+    match tool_type {
+        ToolType::Local => 0,
+        ToolType::WSL => 1,
+        ToolType::SSH => 2,
+    }
+}
+
+fn method_name(method: &InstallMethod) -> String {
+    match method {
+        InstallMethod::Official => "official",
+        InstallMethod::Npm => "npm",
+        InstallMethod::Brew => "brew",
+        InstallMethod::Other => "other",
+    }
+    .to_string()
+}
+
+/// 将路径中的用户目录替换为 `~`（本机用户目录优先，其次是常见的用户目录前缀）
+fn anonymize_path(path: &str, home: Option<&Path>) -> String {
+    if let Some(home) = home.map(|h| h.to_string_lossy()).filter(|h| !h.is_empty()) {
+        if let Some(rest) = path.strip_prefix(&*home) {
+            if rest.is_empty() || rest.starts_with(['/', '\\']) {
+                return format!("~{rest}");
+            }
+        }
+    }
+    HOME_PREFIX.replace(path, "~$2").into_owned()
+}
+
+/// Markdown 表格单元格（空值显示为 `-`）
+fn cell(value: Option<&str>) -> String {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(v) => v.replace('|', "\\|").replace(['\r', '\n'], " "),
+        None => "-".to_string(),
+    }
+}
+
+fn render_markdown(report: &EnvironmentReport) -> String {
+    let mut md = String::from("# DuckCoding 环境报告\n\n");
+    md.push_str(&format!("- 应用版本：{}\n", report.app_version));
+    md.push_str(&format!("- 系统：{} ({})\n", report.os, report.arch));
+
+    md.push_str("\n## 工具\n");
+    if report.tools.is_empty() {
+        md.push_str("\n未检测到工具。\n");
+    }
+    for (tool_id, entries) in &report.tools {
+        md.push_str(&format!("\n### {tool_id}\n\n"));
+        md.push_str("| 环境 | 已安装 | 版本 | 安装方式 | 路径 | Node.js |\n");
+        md.push_str("| --- | --- | --- | --- | --- | --- |\n");
+        for entry in entries {
+            let mut environment = entry.tool_type.clone();
+            if let Some(distro) = &entry.wsl_distro {
+                environment.push_str(&format!(" ({distro})"));
+            }
+            if let Some(label) = &entry.label {
+                environment.push_str(&format!(" · {label}"));
+            }
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} |\n",
+                cell(Some(&environment)),
+                if entry.installed { "是" } else { "否" },
+                cell(entry.version.as_deref()),
+                cell(entry.install_method.as_deref()),
+                cell(entry.install_path.as_deref()),
+                cell(entry.node_runtime.as_deref()),
+            ));
+        }
+    }
+
+    md.push_str("\n## Node.js\n\n");
+    if report.node_runtimes.is_empty() {
+        md.push_str("未检测到 Node.js。\n");
+    } else {
+        md.push_str("| 来源 | 版本 | 路径 | npm 全局目录 | 当前使用 |\n");
+        md.push_str("| --- | --- | --- | --- | --- |\n");
+        for runtime in &report.node_runtimes {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                runtime.source,
+                cell(runtime.version.as_deref()),
+                cell(Some(&runtime.node_path)),
+                cell(runtime.global_prefix.as_deref()),
+                if runtime.active { "是" } else { "-" },
+            ));
+        }
+    }
+
+    md.push_str("\n## 供应商\n\n");
+    md.push_str("| 工具 | 当前 Profile | Profile 供应商 | 代理注入供应商 |\n");
+    md.push_str("| --- | --- | --- | --- |\n");
+    for binding in &report.bindings {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            binding.tool_id,
+            cell(binding.active_profile.as_deref()),
+            cell(binding.provider.as_deref()),
+            cell(binding.proxy_provider.as_deref()),
+        ));
+    }
+
+    md.push_str("\n## 透明代理\n\n");
+    md.push_str("| 工具 | 状态 | 端口 |\n");
+    md.push_str("| --- | --- | --- |\n");
+    for proxy in &report.proxies {
+        md.push_str(&format!(
+            "| {} | {} | {} |\n",
+            proxy.tool_id,
+            if proxy.enabled { "开启" } else { "关闭" },
+            proxy.port
+        ));
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::NodeRuntimeSource;
+    use crate::services::profile_manager::{ClaudeProfile, CodexProfile};
+    use chrono::Utc;
+    use tempfile::TempDir;
+
+    const PLANTED_KEY: &str = "sk-plantedSecretValue1234567890";
+    const PLANTED_TOKEN: &str = "plantedAccessToken987654321";
+    const PLANTED_LOCAL_KEY: &str = "raw-local-proxy-secret";
+
+    fn instance(id: &str, base_id: &str, tool_type: ToolType, path: &str) -> ToolInstance {
+        ToolInstance {
+            instance_id: id.to_string(),
+            base_id: base_id.to_string(),
+            tool_name: base_id.to_string(),
+            tool_type,
+            install_method: Some(InstallMethod::Npm),
+            installed: true,
+            version: Some("1.0.0".to_string()),
+            install_path: Some(path.to_string()),
+            installer_path: Some("/home/alice/.nvm/bin/npm".to_string()),
+            wsl_distro: None,
+            windows_install_path: None,
+            ssh_config: None,
+            is_builtin: false,
+            created_at: 0,
+            updated_at: 0,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
+        }
+    }
+
+    fn planted_sources() -> ReportSources {
+        let mut profiles = ProfilesStore::new();
+        profiles.claude_code.insert(
+            "team".to_string(),
+            ClaudeProfile {
+                api_key: PLANTED_KEY.to_string(),
+                base_url: "https://relay.example.com".to_string(),
+                source: ProfileSource::ImportedFromProvider {
+                    provider_id: "relay".to_string(),
+                    provider_name: "Relay".to_string(),
+                    remote_token_id: 1,
+                    remote_token_name: "default".to_string(),
+                    group: "default".to_string(),
+                    imported_at: 0,
+                },
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_settings: None,
+                raw_config_json: None,
+            },
+        );
+        profiles.codex.insert(
+            "mine".to_string(),
+            CodexProfile {
+                api_key: PLANTED_KEY.to_string(),
+                base_url: "https://api.example.com".to_string(),
+                wire_api: "responses".to_string(),
+                source: ProfileSource::Custom,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+                raw_config_toml: None,
+                raw_auth_json: None,
+            },
+        );
+        let mut active = ActiveStore::new();
+        active.set_active("claude-code", "team".to_string());
+        active.set_active("codex", "mine".to_string());
+
+        let mut proxy = ProxyStore::new();
+        proxy.claude_code.enabled = true;
+        proxy.claude_code.local_api_key = Some(PLANTED_LOCAL_KEY.to_string());
+        proxy.claude_code.real_api_key = Some(PLANTED_KEY.to_string());
+        proxy.claude_code.credential_injection.enabled = true;
+        proxy.claude_code.credential_injection.provider_id = Some("relay".to_string());
+
+        let provider: Provider = serde_json::from_value(serde_json::json!({
+            "id": "relay",
+            "name": "Relay",
+            "website_url": "https://relay.example.com",
+            "user_id": "42",
+            "access_token": PLANTED_TOKEN,
+            "api_key": PLANTED_KEY,
+            "is_default": false,
+            "created_at": 0,
+            "updated_at": 0
+        }))
+        .unwrap();
+
+        ReportSources {
+            app_version: "1.2.3".to_string(),
+            instances: vec![
+                instance(
+                    "codex-ssh-1",
+                    "codex",
+                    ToolType::SSH,
+                    "/home/deploy/.local/bin/codex",
+                ),
+                instance(
+                    "claude-code-local-1",
+                    "claude-code",
+                    ToolType::Local,
+                    "/home/alice/.nvm/bin/claude",
+                ),
+                instance(
+                    "codex-local-1",
+                    "codex",
+                    ToolType::Local,
+                    r"C:\Users\alice\AppData\Roaming\npm\codex.cmd",
+                ),
+            ],
+            profiles,
+            active,
+            proxy,
+            providers: vec![provider],
+            node_runtimes: vec![NodeRuntime {
+                node_path: "/home/alice/.nvm/bin/node".to_string(),
+                source: NodeRuntimeSource::Nvm,
+                version: Some("v20.11.0".to_string()),
+                global_prefix: Some("/home/alice/.nvm".to_string()),
+                active: true,
+            }],
+            home_dir: Some(PathBuf::from("/home/alice")),
+        }
+    }
+
+    #[test]
+    fn test_report_contains_no_secret_material() {
+        let report = build_report(&planted_sources());
+        let dir = TempDir::new().unwrap();
+        for format in [ReportFormat::Markdown, ReportFormat::Json] {
+            let output = dir.path().join("report");
+            write_report(&report, format, &output).unwrap();
+            let text = fs::read_to_string(&output).unwrap();
+
+            assert!(!text.contains(PLANTED_KEY));
+            assert!(!text.contains("plantedSecretValue"));
+            assert!(!text.contains(PLANTED_TOKEN));
+            assert!(!text.contains(PLANTED_LOCAL_KEY));
+            assert!(!text.contains("alice"));
+            assert!(!text.contains("deploy"));
+            assert!(text.contains("Relay"));
+            assert!(text.contains("~/.nvm/bin/claude"));
+        }
+    }
+
+    #[test]
+    fn test_markdown_is_stable() {
+        let sources = planted_sources();
+        let first = render(&build_report(&sources), ReportFormat::Markdown).unwrap();
+
+        // 实例顺序不影响输出
+        let mut shuffled = planted_sources();
+        shuffled.instances.reverse();
+        let second = render(&build_report(&shuffled), ReportFormat::Markdown).unwrap();
+        assert_eq!(first, second);
+
+        let claude = first.find("### claude-code").unwrap();
+        let codex = first.find("### codex").unwrap();
+        assert!(claude < codex);
+        // 同一工具内本地实例在前
+        let local = first.find(r"~\AppData\Roaming\npm\codex.cmd").unwrap();
+        let ssh = first.find("~/.local/bin/codex").unwrap();
+        assert!(local < ssh);
+
+        // npm 实例按全局目录匹配到所属的 Node.js（SSH 实例不匹配）
+        assert!(first.contains("| ~/.nvm/bin/claude | v20.11.0 (nvm) |"));
+        assert!(first.contains("| ~/.local/bin/codex | - |"));
+        assert!(first.contains("| nvm | v20.11.0 | ~/.nvm/bin/node | ~/.nvm | 是 |"));
+
+        assert!(first.contains("| claude-code | team | Relay | Relay |"));
+        assert!(first.contains("| codex | mine | - | - |"));
+        assert!(first.contains("| claude-code | 开启 | 8787 |"));
+        assert!(first.contains("| gemini-cli | 关闭 | 8789 |"));
+    }
+
+    #[test]
+    fn test_anonymize_path() {
+        let home = Path::new("/home/alice");
+        assert_eq!(
+            anonymize_path("/home/alice/.npm/bin/claude", Some(home)),
+            "~/.npm/bin/claude"
+        );
+        assert_eq!(
+            anonymize_path("/home/alicex/bin/claude", Some(home)),
+            "~/bin/claude"
+        );
+        assert_eq!(
+            anonymize_path("/Users/bob/.local/bin/codex", None),
+            "~/.local/bin/codex"
+        );
+        assert_eq!(anonymize_path("/root/bin/gemini", None), "~/bin/gemini");
+        assert_eq!(
+            anonymize_path("/rootfs/bin/gemini", None),
+            "/rootfs/bin/gemini"
+        );
+        assert_eq!(
+            anonymize_path("/usr/local/bin/claude", Some(home)),
+            "/usr/local/bin/claude"
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("/tmp/env.JSON")),
+            ReportFormat::Json
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("/tmp/env.md")),
+            ReportFormat::Markdown
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("/tmp/env")),
+            ReportFormat::Markdown
+        );
+    }
+}
@@ -191,6 +191,19 @@ impl MigrationManager {
                 external_poll_interval_ms: 5000,
                 single_instance_enabled: true,
                 startup_enabled: false,
+                pricing: Default::default(),
+                balance_poll: Default::default(),
+                shell_env_capture_enabled: true,
+                durable_writes_enabled: true,
+                tool_status_cache: Default::default(),
+                offline_mode: false,
+                github_token: None,
+                skipped_versions: Default::default(),
+                backend_locale: Default::default(),
+                telemetry: Default::default(),
+                package_overrides: Default::default(),
+                extra_path_entries: Vec::new(),
+                state_backup: Default::default(),
             });
 
         config.version = Some(new_version.to_string());
@@ -1,35 +1,36 @@
-// SQLite → JSON 迁移
+// 旧版实例数据库迁移
 //
-// 将 tool_instances.db 迁移到 tools.json
+// 将旧版 tool_instances.db 中的工具实例导入 tools.db（ToolInstanceDB）
 
 use crate::services::migration_manager::migration_trait::{Migration, MigrationResult};
 use crate::services::tool::ToolInstanceDB;
 use anyhow::Result;
 use async_trait::async_trait;
 
-/// SQLite → JSON 迁移（目标版本 1.4.0）
-pub struct SqliteToJsonMigration;
+/// 旧版实例数据库迁移（目标版本 1.4.0）
+pub struct LegacyInstanceDbMigration;
 
-impl Default for SqliteToJsonMigration {
+impl Default for LegacyInstanceDbMigration {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl SqliteToJsonMigration {
+impl LegacyInstanceDbMigration {
     pub fn new() -> Self {
         Self
     }
 }
 
 #[async_trait]
-impl Migration for SqliteToJsonMigration {
+impl Migration for LegacyInstanceDbMigration {
     fn id(&self) -> &str {
+        // 沿用早期（迁移到 tools.json 时）的 ID，已执行过的记录保持有效
         "sqlite_to_json_v1"
     }
 
     fn name(&self) -> &str {
-        "SQLite → JSON 迁移"
+        "tool_instances.db → tools.db 迁移"
     }
 
     fn target_version(&self) -> &str {
@@ -37,7 +38,7 @@ impl Migration for SqliteToJsonMigration {
     }
 
     async fn execute(&self) -> Result<MigrationResult> {
-        tracing::info!("开始执行 SQLite → JSON 迁移");
+        tracing::info!("开始执行 tool_instances.db → tools.db 迁移");
 
         // 调用 ToolInstanceDB 的迁移方法
         let db = ToolInstanceDB::new()?;
@@ -50,14 +51,14 @@ impl Migration for SqliteToJsonMigration {
         Ok(MigrationResult {
             migration_id: self.id().to_string(),
             success: true,
-            message: format!("成功迁移 {} 个工具实例到 tools.json", count),
+            message: format!("成功迁移 {} 个工具实例到 tools.db", count),
             records_migrated: count,
             duration_secs: 0.0, // 由 MigrationManager 填充
         })
     }
 
     async fn rollback(&self) -> Result<()> {
-        // SQLite → JSON 迁移支持回滚
+        // 回滚时恢复迁移前备份的旧数据库
         tracing::warn!("回滚迁移：恢复 tool_instances.db");
 
         let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?;
@@ -244,12 +244,14 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
         .as_object()
         .ok_or_else(|| anyhow::anyhow!("配置不是对象"))?;
 
+    let port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16;
+
     Ok(ToolProxyConfig {
         enabled: obj
             .get("enabled")
             .and_then(|v| v.as_bool())
             .unwrap_or(false),
-        port: obj.get("port").and_then(|v| v.as_u64()).unwrap_or(8787) as u16,
+        port,
         local_api_key: obj
             .get("local_api_key")
             .and_then(|v| v.as_str())
@@ -282,5 +284,6 @@ fn parse_old_config(value: &Value) -> Result<ToolProxyConfig> {
             .get("original_active_profile")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string()),
+        ..ToolProxyConfig::new(port)
     })
 }
@@ -9,8 +9,9 @@ mod migrations;
 pub use manager::MigrationManager;
 pub use migration_trait::{Migration, MigrationResult};
 pub use migrations::{
-    BalanceLocalstorageToJsonMigration, GlobalConfigToProvidersMigration, ProfileV2Migration,
-    ProxyConfigMigration, ProxyConfigSplitMigration, SessionConfigMigration, SqliteToJsonMigration,
+    BalanceLocalstorageToJsonMigration, GlobalConfigToProvidersMigration,
+    LegacyInstanceDbMigration, ProfileV2Migration, ProxyConfigMigration, ProxyConfigSplitMigration,
+    SessionConfigMigration,
 };
 
 use std::sync::Arc;
@@ -18,7 +19,7 @@ use std::sync::Arc;
 /// 创建并初始化迁移管理器
 ///
 /// 自动注册所有迁移（按版本号执行）：
-/// - SqliteToJsonMigration (1.4.0) - SQLite → JSON 迁移
+/// - LegacyInstanceDbMigration (1.4.0) - 旧版 tool_instances.db 导入 tools.db
 /// - ProxyConfigMigration (1.4.0) - Proxy 配置重构
 /// - SessionConfigMigration (1.4.0) - Session 配置拆分
 /// - ProfileV2Migration (1.4.0) - Profile v2.0 双文件系统迁移
@@ -29,7 +30,7 @@ pub fn create_migration_manager() -> MigrationManager {
     let mut manager = MigrationManager::new();
 
     // 注册所有迁移（按目标版本号自动排序执行）
-    manager.register(Arc::new(SqliteToJsonMigration::new()));
+    manager.register(Arc::new(LegacyInstanceDbMigration::new()));
     manager.register(Arc::new(ProxyConfigMigration::new()));
     manager.register(Arc::new(SessionConfigMigration::new()));
     manager.register(Arc::new(ProfileV2Migration::new()));
@@ -9,19 +9,47 @@
 // - migration_manager: 统一迁移管理（新）
 // - balance: 余额监控配置管理
 // - provider_manager: 供应商配置管理
+// - provider_import: 从其他切换工具导入供应商
+// - provider_readiness: 启动前验证工具绑定的供应商
+// - provider_benchmark: 供应商延迟测速
+// - provider_trial: 供应商限时试用（到期自动恢复配置）
 // - new_api: NEW API 客户端服务
+// - pricing: 模型定价与花费估算
+// - diagnostics: 诊断包导出
+// - environment_report: 环境报告导出（工具、供应商绑定、代理设置）
+// - onboarding_manager: 新手引导状态
+// - tool_setup: 一键配置工具的报告与配置回滚
+// - cleanup: 卸载前清理 DuckCoding 管理的状态
+// - selfcheck: 启动自检（前置条件检查清单）
+// - state_backup: 完整状态备份（定时写入用户选择的目录，可加密）与恢复
+// - telemetry: 匿名使用统计（默认关闭）
+// - trash: 回收站（删除的供应商与工具实例保留 30 天）
 
 pub mod balance;
+pub mod cleanup; // 卸载前清理
 pub mod config;
 pub mod dashboard_manager; // 仪表板状态管理
+pub mod diagnostics; // 诊断包导出
+pub mod environment_report; // 环境报告导出
 pub mod migration_manager;
 pub mod new_api; // NEW API 客户端
+pub mod onboarding_manager; // 新手引导状态管理
+pub mod pricing; // 模型定价与花费估算
 pub mod profile_manager; // Profile管理（v2.1）
+pub mod provider_benchmark; // 供应商延迟测速
+pub mod provider_import; // 从其他切换工具导入供应商
 pub mod provider_manager; // 供应商配置管理
+pub mod provider_readiness; // 绑定供应商的就绪检查
+pub mod provider_trial; // 供应商限时试用
 pub mod proxy;
 pub mod proxy_config_manager; // 透明代理配置管理（v2.1）
+pub mod selfcheck; // 启动自检
 pub mod session;
+pub mod state_backup; // 完整状态备份
+pub mod telemetry; // 匿名使用统计
 pub mod tool;
+pub mod tool_setup; // 一键配置工具
+pub mod trash; // 回收站
 pub mod update;
 
 // 重新导出服务
@@ -30,6 +58,7 @@ pub use config::types::*; // 仅导出类型
 pub use dashboard_manager::DashboardManager;
 pub use migration_manager::{create_migration_manager, MigrationManager};
 pub use new_api::NewApiClient;
+pub use onboarding_manager::OnboardingManager;
 pub use profile_manager::{
     ActiveStore, ClaudeProfile, CodexProfile, GeminiProfile, ProfileDescriptor, ProfileManager,
     ProfileSource, ProfilesStore,
@@ -38,6 +67,7 @@ pub use provider_manager::ProviderManager;
 pub use proxy::*;
 // session 模块：明确导出避免 db 名称冲突
 pub use session::{manager::SESSION_MANAGER, models::*};
+pub use telemetry::TelemetryManager;
 // tool 模块：导出主要服务类和子模块
 pub use tool::{
     db::ToolInstanceDB, downloader, downloader::FileDownloader, installer,
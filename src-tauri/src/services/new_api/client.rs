@@ -2,6 +2,7 @@
 //
 // NEW API 客户端服务，用于与供应商的 API 交互
 
+use crate::http_client::merge_custom_headers;
 use crate::models::provider::Provider;
 use crate::models::remote_token::{
     CreateRemoteTokenRequest, NewApiResponse, RemoteToken, RemoteTokenGroup, RemoteTokenGroupInfo,
@@ -46,6 +47,7 @@ impl NewApiClient {
         );
         headers.insert("New-Api-User", self.provider.user_id.parse().unwrap());
         headers.insert("Content-Type", "application/json".parse().unwrap());
+        merge_custom_headers(&mut headers, &self.provider.custom_headers);
         headers
     }
 
@@ -92,6 +94,63 @@ impl NewApiClient {
         Ok(tokens)
     }
 
+    /// 获取当前用户信息（`/api/user/self` 原始响应，含额度字段）
+    pub async fn get_user_self(&self) -> Result<serde_json::Value> {
+        let url = format!("{}/api/user/self", self.base_url());
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.build_headers())
+            .send()
+            .await
+            .map_err(|e| anyhow!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "API 请求失败，状态码: {}",
+                response.status().as_u16()
+            ));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("解析响应失败: {}", e))
+    }
+
+    /// 获取当前用户按模型、按小时汇总的用量（`/api/data/self`，时间戳为秒）
+    pub async fn get_user_quota_data(
+        &self,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Result<serde_json::Value> {
+        let url = format!(
+            "{}/api/data/self?start_timestamp={}&end_timestamp={}",
+            self.base_url(),
+            start_timestamp,
+            end_timestamp
+        );
+        let response = self
+            .client
+            .get(&url)
+            .headers(self.build_headers())
+            .send()
+            .await
+            .map_err(|e| anyhow!("请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "API 请求失败，状态码: {}",
+                response.status().as_u16()
+            ));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("解析响应失败: {}", e))
+    }
+
     /// 获取所有令牌分组
     pub async fn list_groups(&self) -> Result<Vec<RemoteTokenGroup>> {
         let url = format!("{}/api/user/self/groups", self.base_url());
@@ -335,6 +394,9 @@ mod tests {
             api_address: None,
             user_id: "123".to_string(),
             access_token: "token123".to_string(),
+            api_key: None,
+            balance_template: Default::default(),
+            custom_headers: Default::default(),
             username: None,
             is_default: false,
             created_at: 0,
@@ -354,6 +416,9 @@ mod tests {
             api_address: None,
             user_id: "123".to_string(),
             access_token: "token123".to_string(),
+            api_key: None,
+            balance_template: Default::default(),
+            custom_headers: Default::default(),
             username: None,
             is_default: false,
             created_at: 0,
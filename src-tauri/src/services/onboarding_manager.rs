@@ -0,0 +1,151 @@
+// Onboarding Manager Service
+//
+// 新手引导状态管理服务（onboarding.json）
+//
+// 引导进度以后端为准：清空 WebView 数据不会让引导从头开始，
+// 检测工具、应用供应商配置等后端操作也会直接记录对应步骤。
+
+use crate::data::DataManager;
+use crate::models::onboarding::{OnboardingState, STEP_PROVIDER_APPLIED, STEP_TOOLS_DETECTED};
+use crate::utils::config::config_dir;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// 进程内写锁（文件锁之外，保证同一进程内的读-改-写串行）
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 记录工具检测完成（失败只记日志，不影响调用方的主流程）
+pub fn record_tools_detected<'a>(installed_tool_ids: impl IntoIterator<Item = &'a str>) {
+    let result = OnboardingManager::new().and_then(|manager| {
+        manager.update(|state, now| {
+            state.mark_completed(STEP_TOOLS_DETECTED, now);
+            state.add_chosen_tools(installed_tool_ids);
+        })
+    });
+    if let Err(e) = result {
+        tracing::warn!(error = ?e, "记录引导步骤失败: {}", STEP_TOOLS_DETECTED);
+    }
+}
+
+/// 记录供应商配置已应用到工具（失败只记日志，不影响调用方的主流程）
+pub fn record_provider_applied(tool_id: &str) {
+    let result = OnboardingManager::new().and_then(|manager| {
+        manager.update(|state, now| {
+            state.mark_completed(STEP_PROVIDER_APPLIED, now);
+            state.add_chosen_tools([tool_id]);
+        })
+    });
+    if let Err(e) = result {
+        tracing::warn!(error = ?e, tool_id, "记录引导步骤失败: {}", STEP_PROVIDER_APPLIED);
+    }
+}
+
+/// 新手引导状态管理器
+pub struct OnboardingManager {
+    data_manager: DataManager,
+    state_path: PathBuf,
+}
+
+impl OnboardingManager {
+    pub fn new() -> Result<Self> {
+        let state_path = config_dir()
+            .map_err(|e| anyhow::anyhow!("获取配置目录失败: {}", e))?
+            .join("onboarding.json");
+        Ok(Self {
+            data_manager: DataManager::new(),
+            state_path,
+        })
+    }
+
+    /// 读取引导状态（文件不存在时返回默认值）
+    pub fn load(&self) -> Result<OnboardingState> {
+        if !self.state_path.exists() {
+            return Ok(OnboardingState::default());
+        }
+        let value = self.data_manager.json_uncached().read(&self.state_path)?;
+        serde_json::from_value(value).context("解析 onboarding.json 失败")
+    }
+
+    /// 标记步骤完成或跳过
+    pub fn mark_step(&self, step: &str, skipped: bool) -> Result<OnboardingState> {
+        let step = step.trim();
+        if step.is_empty() {
+            anyhow::bail!("引导步骤不能为空");
+        }
+        self.update(|state, now| {
+            if skipped {
+                state.mark_skipped(step, now);
+            } else {
+                state.mark_completed(step, now);
+            }
+        })
+    }
+
+    /// 清空引导状态
+    pub fn reset(&self) -> Result<()> {
+        let _guard = WRITE_LOCK.lock().unwrap();
+        if self.state_path.exists() {
+            std::fs::remove_file(&self.state_path)
+                .with_context(|| format!("删除引导状态失败: {:?}", self.state_path))?;
+        }
+        Ok(())
+    }
+
+    /// 读-改-写引导状态（闭包的第二个参数为当前时间）
+    fn update(&self, f: impl FnOnce(&mut OnboardingState, i64)) -> Result<OnboardingState> {
+        let _guard = WRITE_LOCK.lock().unwrap();
+        self.data_manager
+            .with_file_lock(&self.state_path, || -> Result<OnboardingState> {
+                let mut state = self.load()?;
+                f(&mut state, chrono::Utc::now().timestamp());
+                let value = serde_json::to_value(&state).context("序列化引导状态失败")?;
+                self.data_manager
+                    .json_uncached()
+                    .write(&self.state_path, &value)?;
+                Ok(state)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_manager(temp_dir: &TempDir) -> OnboardingManager {
+        OnboardingManager {
+            data_manager: DataManager::new(),
+            state_path: temp_dir.path().join("onboarding.json"),
+        }
+    }
+
+    #[test]
+    fn test_state_survives_reload_and_reset() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        assert_eq!(manager.load().unwrap(), OnboardingState::default());
+
+        manager.mark_step("welcome", false).unwrap();
+        manager.mark_step("proxy", true).unwrap();
+        manager
+            .update(|state, now| state.mark_completed(STEP_TOOLS_DETECTED, now))
+            .unwrap();
+
+        let reloaded = create_test_manager(&temp_dir).load().unwrap();
+        assert!(reloaded.is_completed("welcome"));
+        assert!(reloaded.is_completed(STEP_TOOLS_DETECTED));
+        assert_eq!(reloaded.skipped_steps, vec!["proxy".to_string()]);
+        assert_eq!(reloaded.pending_steps(), vec![STEP_PROVIDER_APPLIED]);
+
+        manager.reset().unwrap();
+        assert_eq!(manager.load().unwrap(), OnboardingState::default());
+    }
+
+    #[test]
+    fn test_mark_step_rejects_empty_step() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        assert!(manager.mark_step("  ", false).is_err());
+    }
+}
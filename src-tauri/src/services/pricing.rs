@@ -0,0 +1,229 @@
+// Pricing Service
+//
+// 按模型单价估算 token 用量对应的花费
+
+use crate::models::pricing::{ModelRate, PricingSettings, PricingTable};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 内置默认定价表
+static DEFAULT_PRICING: Lazy<PricingTable> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../../resources/model_pricing.json"))
+        .expect("内置定价表格式错误")
+});
+
+/// 内置默认定价表
+pub fn default_pricing_table() -> &'static PricingTable {
+    &DEFAULT_PRICING
+}
+
+/// 单价来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateSource {
+    ProviderOverride,
+    ModelOverride,
+    Builtin,
+    /// 未知模型，使用默认单价
+    Default,
+}
+
+/// 单次花费估算
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CostEstimate {
+    pub cost_usd: f64,
+    /// 使用了默认单价（模型未在定价表中）
+    pub estimated: bool,
+}
+
+/// 按天汇总的花费
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyCost {
+    /// 日期（YYYY-MM-DD）
+    pub date: String,
+    pub tokens: u64,
+    pub cost_usd: f64,
+    /// 当日存在使用默认单价估算的记录
+    pub estimated: bool,
+}
+
+/// 定价解析器：用户覆盖 > 内置定价表 > 默认单价
+pub struct PricingResolver<'a> {
+    table: &'a PricingTable,
+    settings: &'a PricingSettings,
+}
+
+impl<'a> PricingResolver<'a> {
+    pub fn new(table: &'a PricingTable, settings: &'a PricingSettings) -> Self {
+        Self { table, settings }
+    }
+
+    /// 使用内置定价表
+    pub fn with_defaults(settings: &'a PricingSettings) -> Self {
+        Self::new(default_pricing_table(), settings)
+    }
+
+    /// 查询模型单价
+    pub fn rate_for(&self, provider_id: Option<&str>, model: &str) -> (ModelRate, RateSource) {
+        if let Some(rate) = provider_id
+            .and_then(|id| self.settings.provider_overrides.get(id))
+            .and_then(|models| match_model(models, model))
+        {
+            return (rate, RateSource::ProviderOverride);
+        }
+        if let Some(rate) = match_model(&self.settings.model_overrides, model) {
+            return (rate, RateSource::ModelOverride);
+        }
+        if let Some(rate) = match_model(&self.table.models, model) {
+            return (rate, RateSource::Builtin);
+        }
+        (
+            self.settings
+                .default_rate
+                .unwrap_or(self.table.default_rate),
+            RateSource::Default,
+        )
+    }
+
+    /// 估算花费
+    pub fn estimate(
+        &self,
+        provider_id: Option<&str>,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> CostEstimate {
+        let (rate, source) = self.rate_for(provider_id, model);
+        CostEstimate {
+            cost_usd: (input_tokens as f64 * rate.input_per_mtok
+                + output_tokens as f64 * rate.output_per_mtok)
+                / 1_000_000.0,
+            estimated: source == RateSource::Default,
+        }
+    }
+}
+
+/// 按最长前缀匹配模型名（忽略大小写与 `vendor/` 前缀）
+///
+/// 前缀必须在非字母数字处结束：`gpt-4o` 匹配 `gpt-4o-2024-08-06`，`gpt-4` 不匹配 `gpt-4o`。
+fn match_model(models: &BTreeMap<String, ModelRate>, model: &str) -> Option<ModelRate> {
+    let model = model
+        .rsplit('/')
+        .next()
+        .unwrap_or(model)
+        .to_ascii_lowercase();
+    models
+        .iter()
+        .filter(|(prefix, _)| {
+            let prefix = prefix.to_ascii_lowercase();
+            model.starts_with(&prefix)
+                && model[prefix.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| !c.is_ascii_alphanumeric())
+        })
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, rate)| *rate)
+}
+
+/// 按天汇总花费
+///
+/// `records` 为 (Unix 时间戳秒, token 数, 花费估算)，`utc_offset_secs` 为统计所用时区偏移。
+pub fn aggregate_daily<I>(records: I, utc_offset_secs: i64) -> Vec<DailyCost>
+where
+    I: IntoIterator<Item = (i64, u64, CostEstimate)>,
+{
+    let mut days: BTreeMap<String, DailyCost> = BTreeMap::new();
+    for (timestamp, tokens, estimate) in records {
+        let date = chrono::DateTime::from_timestamp(timestamp + utc_offset_secs, 0)
+            .map(|dt| dt.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        let day = days.entry(date.clone()).or_insert_with(|| DailyCost {
+            date,
+            tokens: 0,
+            cost_usd: 0.0,
+            estimated: false,
+        });
+        day.tokens += tokens;
+        day.cost_usd += estimate.cost_usd;
+        day.estimated |= estimate.estimated;
+    }
+    days.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_table_parses() {
+        let table = default_pricing_table();
+        assert!(table.models.contains_key("claude-sonnet-4"));
+        assert!(table.default_rate.input_per_mtok > 0.0);
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let settings = PricingSettings::default();
+        let resolver = PricingResolver::with_defaults(&settings);
+
+        let (rate, source) = resolver.rate_for(None, "gpt-4o-mini-2024-07-18");
+        assert_eq!(source, RateSource::Builtin);
+        assert_eq!(rate.input_per_mtok, 0.15);
+
+        let (rate, _) = resolver.rate_for(None, "claude-sonnet-4-5-20250929");
+        assert_eq!(rate.output_per_mtok, 15.0);
+
+        let (_, source) = resolver.rate_for(None, "some-unknown-model");
+        assert_eq!(source, RateSource::Default);
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let cheap = ModelRate {
+            input_per_mtok: 0.5,
+            output_per_mtok: 1.0,
+        };
+        let mut settings = PricingSettings::default();
+        settings
+            .provider_overrides
+            .entry("relay".to_string())
+            .or_default()
+            .insert("claude-sonnet-4".to_string(), cheap);
+        let resolver = PricingResolver::with_defaults(&settings);
+
+        let (rate, source) = resolver.rate_for(Some("relay"), "claude-sonnet-4-20250514");
+        assert_eq!((rate, source), (cheap, RateSource::ProviderOverride));
+
+        let (_, source) = resolver.rate_for(Some("other"), "claude-sonnet-4-20250514");
+        assert_eq!(source, RateSource::Builtin);
+    }
+
+    #[test]
+    fn test_estimate_and_daily_aggregate() {
+        let settings = PricingSettings::default();
+        let resolver = PricingResolver::with_defaults(&settings);
+
+        let known = resolver.estimate(None, "claude-sonnet-4", 1_000_000, 100_000);
+        assert!((known.cost_usd - 4.5).abs() < 1e-9);
+        assert!(!known.estimated);
+
+        let unknown = resolver.estimate(None, "mystery", 1_000_000, 0);
+        assert!(unknown.estimated);
+
+        // 2024-01-01 00:00:00 UTC 与 2024-01-01 20:00:00 UTC（+8 时区下为次日）
+        let days = aggregate_daily(
+            [
+                (1_704_067_200, 1_100_000, known),
+                (1_704_139_200, 1_000_000, unknown),
+            ],
+            8 * 3600,
+        );
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, "2024-01-01");
+        assert!(!days[0].estimated);
+        assert_eq!(days[1].date, "2024-01-02");
+        assert!(days[1].estimated);
+    }
+}
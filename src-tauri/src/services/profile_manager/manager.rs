@@ -2,10 +2,13 @@
 
 use super::types::*;
 use crate::data::DataManager;
+use crate::models::dashboard::ActivityKind;
+use crate::models::telemetry::TelemetryEvent;
+use crate::services::dashboard_manager::record_activity;
+use crate::services::onboarding_manager::record_provider_applied;
+use crate::services::telemetry::record_event;
 use anyhow::{anyhow, Context, Result};
 use chrono::Utc;
-use fs2::FileExt;
-use std::fs::File;
 use std::path::PathBuf;
 
 /// 系统保留的 Profile 名称前缀
@@ -30,9 +33,7 @@ pub struct ProfileManager {
 
 impl ProfileManager {
     pub fn new() -> Result<Self> {
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow!("无法获取用户主目录"))?;
-        let duckcoding_dir = home_dir.join(".duckcoding");
-        std::fs::create_dir_all(&duckcoding_dir)?;
+        let duckcoding_dir = crate::utils::config::config_dir().map_err(|e| anyhow!(e))?;
 
         Ok(Self {
             data_manager: DataManager::new(),
@@ -41,6 +42,11 @@ impl ProfileManager {
         })
     }
 
+    /// Profile 存储文件（profiles.json、active.json）
+    pub fn store_files(&self) -> Vec<PathBuf> {
+        vec![self.profiles_path.clone(), self.active_path.clone()]
+    }
+
     pub fn load_profiles_store(&self) -> Result<ProfilesStore> {
         if !self.profiles_path.exists() {
             return Ok(ProfilesStore::new());
@@ -50,20 +56,11 @@ impl ProfileManager {
     }
 
     pub fn save_profiles_store(&self, store: &ProfilesStore) -> Result<()> {
-        // 创建锁文件（与 profiles.json 同目录）
-        let lock_path = self.profiles_path.with_extension("lock");
-        let lock_file = File::create(&lock_path).context("创建锁文件失败")?;
-
-        // 获取排他锁（阻塞等待其他写操作完成）
-        lock_file.lock_exclusive().context("获取文件锁失败")?;
-
-        // 执行写入（受锁保护）
+        // 在 profiles.json.lock 上加锁后写入（等待超时返回「文件被其他进程锁定」）
         let value = serde_json::to_value(store)?;
-        self.data_manager
-            .json()
-            .write(&self.profiles_path, &value)?;
-
-        // 锁在 lock_file drop 时自动释放
+        self.data_manager.with_file_lock(&self.profiles_path, || {
+            self.data_manager.json().write(&self.profiles_path, &value)
+        })?;
         Ok(())
     }
 
@@ -76,18 +73,11 @@ impl ProfileManager {
     }
 
     pub fn save_active_store(&self, store: &ActiveStore) -> Result<()> {
-        // 创建锁文件（与 active.json 同目录）
-        let lock_path = self.active_path.with_extension("lock");
-        let lock_file = File::create(&lock_path).context("创建锁文件失败")?;
-
-        // 获取排他锁（阻塞等待其他写操作完成）
-        lock_file.lock_exclusive().context("获取文件锁失败")?;
-
-        // 执行写入（受锁保护）
+        // 在 active.json.lock 上加锁后写入
         let value = serde_json::to_value(store)?;
-        self.data_manager.json().write(&self.active_path, &value)?;
-
-        // 锁在 lock_file drop 时自动释放
+        self.data_manager.with_file_lock(&self.active_path, || {
+            self.data_manager.json().write(&self.active_path, &value)
+        })?;
         Ok(())
     }
 
@@ -411,6 +401,13 @@ impl ProfileManager {
         // 应用到原生配置文件
         self.apply_to_native(tool_id, profile_name)?;
 
+        record_activity(
+            ActivityKind::ProfileActivated,
+            tool_id,
+            Some(profile_name.to_string()),
+        );
+        record_provider_applied(tool_id);
+        record_event(TelemetryEvent::ProfileApplied);
         Ok(())
     }
 
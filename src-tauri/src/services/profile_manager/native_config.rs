@@ -3,10 +3,14 @@
 use super::types::*;
 use crate::data::DataManager;
 use crate::models::tool::Tool;
+use crate::services::config::write_ledger::record_write;
 use anyhow::{anyhow, Result};
 use serde_json::{Map, Value};
 use toml_edit;
 
+/// 写入记录中应用 Profile 的操作名称
+const APPLY_ACTION: &str = "apply_profile";
+
 impl super::manager::ProfileManager {
     /// 将 Profile 应用到原生配置文件
     pub fn apply_profile_to_native(&self, tool_id: &str, profile_name: &str) -> Result<()> {
@@ -91,6 +95,7 @@ fn apply_claude_native(tool: &Tool, profile: &ClaudeProfile) -> Result<()> {
     );
 
     manager.json_uncached().write(&settings_path, &settings)?;
+    record_write(&settings_path, APPLY_ACTION);
     Ok(())
 }
 
@@ -204,6 +209,7 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
     }
 
     manager.toml().write(&config_path, &doc)?;
+    record_write(&config_path, APPLY_ACTION);
 
     // 应用 auth.json
     let mut auth = if auth_path.exists() {
@@ -219,6 +225,7 @@ fn apply_codex_native(tool: &Tool, profile: &CodexProfile, provider_name: &str)
             Value::String(profile.api_key.clone()),
         );
     manager.json_uncached().write(&auth_path, &auth)?;
+    record_write(&auth_path, APPLY_ACTION);
 
     Ok(())
 }
@@ -278,6 +285,7 @@ fn apply_gemini_native(tool: &Tool, profile: &GeminiProfile) -> Result<()> {
     if let Some(ref model) = profile.model {
         manager.env().set(&env_path, "GEMINI_MODEL", model)?;
     }
+    record_write(&env_path, APPLY_ACTION);
 
     Ok(())
 }
@@ -0,0 +1,461 @@
+// Provider Benchmark Service
+//
+// 批量测量供应商延迟（测速），帮助在长时间会话前挑选最快的中转站。
+// 每一轮对所有供应商并发发起一次轻量请求（模型列表或 1 token 补全），各轮依次执行；
+// 单个请求受 `REQUEST_TIMEOUT` 限制，整次测速受全局时间预算限制，预算耗尽时中止仍未返回的请求。
+//
+// 测速请求直接发往供应商、不经过透明代理，因此不会计入代理的用量与花费统计；
+// 请求附带 `BENCHMARK_HEADER`，便于供应商侧区分测速流量。
+// 每个供应商最近一次的测速结果保存在 provider_benchmarks.json，供健康状态旁展示。
+
+use crate::data::DataManager;
+use crate::http_client::merge_custom_headers;
+use crate::models::provider::Provider;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// 测速请求的标记请求头
+pub const BENCHMARK_HEADER: &str = "X-DuckCoding-Purpose";
+
+/// 标记请求头的值
+const BENCHMARK_HEADER_VALUE: &str = "benchmark";
+
+/// 单个请求的时限
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 整次测速的默认时间预算
+pub const DEFAULT_BUDGET: Duration = Duration::from_secs(60);
+
+/// 每个供应商最多采样次数
+pub const MAX_SAMPLES: u32 = 20;
+
+/// 测速请求类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkProbe {
+    /// GET /v1/models（不消耗额度）
+    #[default]
+    Models,
+    /// POST /v1/chat/completions，max_tokens = 1
+    Completion,
+}
+
+/// 测速参数
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BenchmarkOptions {
+    #[serde(default)]
+    pub probe: BenchmarkProbe,
+    /// 补全请求使用的模型（`Completion` 时必填）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 全局时间预算（毫秒），缺省为 `DEFAULT_BUDGET`
+    #[serde(default)]
+    pub budget_ms: Option<u64>,
+}
+
+/// 单次采样结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Sample {
+    /// 成功，耗时（毫秒）
+    Ok(u64),
+    /// 失败（HTTP 错误状态或网络错误）
+    Failed(String),
+    /// 超过单个请求时限
+    Timeout,
+    /// 全局预算耗尽被中止
+    Aborted,
+}
+
+/// 单个供应商的测速结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderBenchmark {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub probe: BenchmarkProbe,
+    /// 发起的采样次数（不含因预算耗尽未发起的轮次）
+    pub samples: u32,
+    pub successes: u32,
+    /// 失败次数（含超时，不含被中止的请求）
+    pub errors: u32,
+    pub timeouts: u32,
+    pub aborted: u32,
+    pub min_ms: Option<u64>,
+    pub median_ms: Option<u64>,
+    pub p95_ms: Option<u64>,
+    /// 最近一次失败原因
+    pub last_error: Option<String>,
+    /// 测速时间（Unix 时间戳）
+    pub benchmarked_at: i64,
+}
+
+/// 测速对比结果（按中位延迟升序，没有成功采样的排在最后）
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    pub results: Vec<ProviderBenchmark>,
+    /// 是否因全局预算耗尽而提前结束
+    pub budget_exceeded: bool,
+    pub elapsed_ms: u64,
+}
+
+/// 测速对所有供应商，`samples` 会限制在 1..=`MAX_SAMPLES`
+pub async fn benchmark_providers(
+    providers: &[Provider],
+    samples: u32,
+    options: &BenchmarkOptions,
+) -> Result<BenchmarkReport> {
+    if options.probe == BenchmarkProbe::Completion
+        && options.model.as_deref().is_none_or(|m| m.trim().is_empty())
+    {
+        return Err(anyhow!("补全测速需要指定模型"));
+    }
+    let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+    let budget = options
+        .budget_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_BUDGET);
+
+    let client = &client;
+    let report = run_benchmark(
+        providers,
+        samples,
+        options.probe,
+        budget,
+        |provider, limit| async move { probe_once(client, &provider, options, limit).await },
+    )
+    .await;
+
+    if let Err(e) = BenchmarkStore::new().and_then(|store| store.save_results(&report.results)) {
+        tracing::warn!(error = %e, "保存测速结果失败");
+    }
+    Ok(report)
+}
+
+/// 最近一次测速结果（按供应商 ID）
+pub fn last_benchmarks() -> Result<HashMap<String, ProviderBenchmark>> {
+    BenchmarkStore::new()?.load()
+}
+
+/// 按轮次执行测速：每轮所有供应商并发，轮与轮之间依次执行
+///
+/// `probe` 收到供应商与本次请求可用的时限（单个请求时限与剩余预算中的较小者）
+async fn run_benchmark<F, Fut>(
+    providers: &[Provider],
+    samples: u32,
+    probe_kind: BenchmarkProbe,
+    budget: Duration,
+    probe: F,
+) -> BenchmarkReport
+where
+    F: Fn(Provider, Duration) -> Fut,
+    Fut: Future<Output = Sample>,
+{
+    let started = Instant::now();
+    let deadline = started + budget;
+    let samples = samples.clamp(1, MAX_SAMPLES);
+    let mut collected: Vec<Vec<Sample>> = vec![Vec::new(); providers.len()];
+    let mut budget_exceeded = false;
+
+    for _ in 0..samples {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            budget_exceeded = true;
+            break;
+        }
+        let round = futures_util::future::join_all(providers.iter().map(|provider| {
+            let limit = remaining.min(REQUEST_TIMEOUT);
+            let aborts_budget = limit == remaining;
+            let fut = probe(provider.clone(), limit);
+            async move {
+                match tokio::time::timeout(limit, fut).await {
+                    Ok(sample) => sample,
+                    Err(_) if aborts_budget => Sample::Aborted,
+                    Err(_) => Sample::Timeout,
+                }
+            }
+        }))
+        .await;
+        for (history, sample) in collected.iter_mut().zip(round) {
+            if sample == Sample::Aborted {
+                budget_exceeded = true;
+            }
+            history.push(sample);
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut results: Vec<ProviderBenchmark> = providers
+        .iter()
+        .zip(&collected)
+        .map(|(provider, samples)| summarize(provider, probe_kind, samples, now))
+        .collect();
+    results.sort_by_key(|result| (result.median_ms.is_none(), result.median_ms));
+
+    BenchmarkReport {
+        results,
+        budget_exceeded,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// 汇总单个供应商的采样
+fn summarize(
+    provider: &Provider,
+    probe: BenchmarkProbe,
+    samples: &[Sample],
+    now: i64,
+) -> ProviderBenchmark {
+    let mut latencies: Vec<u64> = samples
+        .iter()
+        .filter_map(|sample| match sample {
+            Sample::Ok(ms) => Some(*ms),
+            _ => None,
+        })
+        .collect();
+    latencies.sort_unstable();
+
+    let count = |f: fn(&Sample) -> bool| samples.iter().filter(|s| f(s)).count() as u32;
+    let last_error = samples.iter().rev().find_map(|sample| match sample {
+        Sample::Failed(error) => Some(error.clone()),
+        Sample::Timeout => Some(format!("请求超时（{} 秒）", REQUEST_TIMEOUT.as_secs())),
+        _ => None,
+    });
+
+    ProviderBenchmark {
+        provider_id: provider.id.clone(),
+        provider_name: provider.name.clone(),
+        probe,
+        samples: samples.len() as u32,
+        successes: latencies.len() as u32,
+        errors: count(|s| matches!(s, Sample::Failed(_) | Sample::Timeout)),
+        timeouts: count(|s| matches!(s, Sample::Timeout)),
+        aborted: count(|s| matches!(s, Sample::Aborted)),
+        min_ms: latencies.first().copied(),
+        median_ms: percentile(&latencies, 50),
+        p95_ms: percentile(&latencies, 95),
+        last_error,
+        benchmarked_at: now,
+    }
+}
+
+/// 最近秩法取百分位（`sorted` 须已升序）
+fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// 供应商的 API 基础地址（优先 api_address），统一以 `/v1` 结尾
+fn api_base(provider: &Provider) -> String {
+    let base = provider
+        .api_address
+        .as_deref()
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or(&provider.website_url)
+        .trim()
+        .trim_end_matches('/');
+    if base.ends_with("/v1") {
+        base.to_string()
+    } else {
+        format!("{base}/v1")
+    }
+}
+
+/// 发起一次测速请求（任何 2xx 响应视为成功）
+async fn probe_once(
+    client: &reqwest::Client,
+    provider: &Provider,
+    options: &BenchmarkOptions,
+    limit: Duration,
+) -> Sample {
+    let Some(api_key) = provider.api_key.as_deref().filter(|k| !k.is_empty()) else {
+        return Sample::Failed("未配置 API Key".to_string());
+    };
+    let mut headers = HeaderMap::new();
+    match HeaderValue::from_str(&format!("Bearer {api_key}")) {
+        Ok(value) => headers.insert("Authorization", value),
+        Err(_) => return Sample::Failed("API Key 包含非法字符".to_string()),
+    };
+    headers.insert(
+        BENCHMARK_HEADER,
+        HeaderValue::from_static(BENCHMARK_HEADER_VALUE),
+    );
+    merge_custom_headers(&mut headers, &provider.custom_headers);
+
+    let base = api_base(provider);
+    let request = match options.probe {
+        BenchmarkProbe::Models => client.get(format!("{base}/models")),
+        BenchmarkProbe::Completion => {
+            client
+                .post(format!("{base}/chat/completions"))
+                .json(&serde_json::json!({
+                    "model": options.model.as_deref().unwrap_or_default().trim(),
+                    "max_tokens": 1,
+                    "stream": false,
+                    "messages": [{ "role": "user", "content": "ping" }],
+                }))
+        }
+    };
+
+    let started = Instant::now();
+    match request.headers(headers).timeout(limit).send().await {
+        Ok(response) if response.status().is_success() => {
+            Sample::Ok(started.elapsed().as_millis() as u64)
+        }
+        Ok(response) => Sample::Failed(format!("状态码 {}", response.status().as_u16())),
+        Err(e) if e.is_timeout() => Sample::Timeout,
+        Err(e) => Sample::Failed(format!("请求失败: {e}")),
+    }
+}
+
+/// 最近一次测速结果的存储（provider_benchmarks.json）
+struct BenchmarkStore {
+    data_manager: DataManager,
+    file_path: PathBuf,
+}
+
+impl BenchmarkStore {
+    fn new() -> Result<Self> {
+        let dir = config_dir().map_err(|e| anyhow!(e))?;
+        Ok(Self::at(dir.join("provider_benchmarks.json")))
+    }
+
+    fn at(file_path: PathBuf) -> Self {
+        Self {
+            data_manager: DataManager::new(),
+            file_path,
+        }
+    }
+
+    fn load(&self) -> Result<HashMap<String, ProviderBenchmark>> {
+        if !self.file_path.exists() {
+            return Ok(HashMap::new());
+        }
+        let value = self
+            .data_manager
+            .json()
+            .read(&self.file_path)
+            .context("读取 provider_benchmarks.json 失败")?;
+        serde_json::from_value(value).context("解析 provider_benchmarks.json 失败")
+    }
+
+    /// 合并保存：只替换本次测速涉及的供应商
+    fn save_results(&self, results: &[ProviderBenchmark]) -> Result<()> {
+        let mut stored = self.load().unwrap_or_default();
+        for result in results {
+            stored.insert(result.provider_id.clone(), result.clone());
+        }
+        let value = serde_json::to_value(&stored).context("序列化测速结果失败")?;
+        self.data_manager
+            .json()
+            .write(&self.file_path, &value)
+            .context("保存 provider_benchmarks.json 失败")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::provider::ProviderStore;
+
+    fn provider(id: &str) -> Provider {
+        let mut provider = ProviderStore::default().providers[0].clone();
+        provider.id = id.to_string();
+        provider.name = id.to_uppercase();
+        provider
+    }
+
+    #[test]
+    fn test_percentile_and_summary() {
+        assert_eq!(percentile(&[], 50), None);
+        assert_eq!(percentile(&[7], 95), Some(7));
+        let sorted: Vec<u64> = (1..=20).collect();
+        assert_eq!(percentile(&sorted, 50), Some(10));
+        assert_eq!(percentile(&sorted, 95), Some(19));
+
+        let samples = [
+            Sample::Ok(300),
+            Sample::Failed("状态码 502".to_string()),
+            Sample::Ok(100),
+            Sample::Timeout,
+            Sample::Ok(200),
+            Sample::Aborted,
+        ];
+        let result = summarize(&provider("relay"), BenchmarkProbe::Models, &samples, 0);
+        assert_eq!(result.samples, 6);
+        assert_eq!(result.successes, 3);
+        assert_eq!((result.errors, result.timeouts, result.aborted), (2, 1, 1));
+        assert_eq!(result.min_ms, Some(100));
+        assert_eq!(result.median_ms, Some(200));
+        assert_eq!(result.p95_ms, Some(300));
+        assert!(result.last_error.unwrap().contains("超时"));
+    }
+
+    #[tokio::test]
+    async fn test_rounds_sorted_and_budget_aborts_stragglers() {
+        let providers = [provider("slow"), provider("fast"), provider("down")];
+        let report = run_benchmark(
+            &providers,
+            3,
+            BenchmarkProbe::Models,
+            Duration::from_millis(200),
+            |provider, _limit| {
+                let id = provider.id.clone();
+                async move {
+                    match id.as_str() {
+                        "fast" => Sample::Ok(10),
+                        "down" => Sample::Failed("状态码 503".to_string()),
+                        _ => {
+                            tokio::time::sleep(Duration::from_secs(5)).await;
+                            Sample::Ok(5000)
+                        }
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert!(report.budget_exceeded);
+        assert!(report.elapsed_ms < 2000, "预算耗尽后应立即结束");
+        let ids: Vec<&str> = report
+            .results
+            .iter()
+            .map(|r| r.provider_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["fast", "slow", "down"]);
+        // 首轮即耗尽预算，后续轮次不再发起
+        let slow = &report.results[1];
+        assert_eq!((slow.samples, slow.aborted, slow.errors), (1, 1, 0));
+        assert_eq!(report.results[2].errors, 1);
+    }
+
+    #[test]
+    fn test_api_base_and_store_merge() {
+        let mut p = provider("relay");
+        p.api_address = Some("https://relay.example.com/v1/".to_string());
+        assert_eq!(api_base(&p), "https://relay.example.com/v1");
+        p.api_address = None;
+        p.website_url = "https://relay.example.com".to_string();
+        assert_eq!(api_base(&p), "https://relay.example.com/v1");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = BenchmarkStore::at(dir.path().join("provider_benchmarks.json"));
+        let first = summarize(&provider("a"), BenchmarkProbe::Models, &[Sample::Ok(5)], 1);
+        let second = summarize(&provider("b"), BenchmarkProbe::Models, &[Sample::Ok(9)], 1);
+        store.save_results(&[first, second]).unwrap();
+        let updated = summarize(&provider("a"), BenchmarkProbe::Models, &[Sample::Ok(7)], 2);
+        store.save_results(&[updated]).unwrap();
+
+        let stored = store.load().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored["a"].median_ms, Some(7));
+        assert_eq!(stored["b"].median_ms, Some(9));
+    }
+}
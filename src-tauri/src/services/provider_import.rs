@@ -0,0 +1,588 @@
+// Provider Import Service
+//
+// 从其他切换工具（cc-switch、claude-code-router）的配置中发现供应商
+//
+// 只负责解析并生成供应商草稿与建议绑定，不写入任何数据；
+// 用户确认后由命令层创建供应商并设置绑定。
+
+use crate::models::provider::Provider;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// 配置来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// cc-switch（`~/.cc-switch/config.json`）
+    CcSwitch,
+    /// claude-code-router（`~/.claude-code-router/config.json`）
+    ClaudeCodeRouter,
+}
+
+/// 发现的供应商（草稿，尚未写入）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredProvider {
+    pub source: ImportSource,
+    /// 来源配置文件
+    pub source_path: String,
+    /// 供应商草稿
+    pub provider: Provider,
+    /// 建议绑定到该供应商的工具 ID
+    pub suggested_tools: Vec<String>,
+    /// 已存在相同 API 地址的供应商 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub existing_provider_id: Option<String>,
+    /// 解析说明（如被忽略的字段）
+    pub notes: Vec<String>,
+}
+
+/// 发现结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderDiscovery {
+    pub providers: Vec<DiscoveredProvider>,
+    /// 已检查且存在的配置文件
+    pub scanned_files: Vec<String>,
+    /// 整体说明（如配置文件无法解析）
+    pub notes: Vec<String>,
+}
+
+/// 已知的配置位置
+pub fn known_sources() -> Vec<(ImportSource, PathBuf)> {
+    let mut sources = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        sources.push((ImportSource::CcSwitch, home.join(".cc-switch/config.json")));
+        sources.push((
+            ImportSource::ClaudeCodeRouter,
+            home.join(".claude-code-router/config.json"),
+        ));
+    }
+    if let Some(config_dir) = dirs::config_dir() {
+        sources.push((
+            ImportSource::CcSwitch,
+            config_dir.join("cc-switch/config.json"),
+        ));
+    }
+    sources
+}
+
+/// 扫描已知位置并生成供应商草稿（`existing` 用于标记已存在的供应商）
+pub fn discover_providers(existing: &[Provider]) -> ProviderDiscovery {
+    discover_from(&known_sources(), existing)
+}
+
+/// 扫描指定位置（不存在的文件直接跳过）
+pub fn discover_from(
+    sources: &[(ImportSource, PathBuf)],
+    existing: &[Provider],
+) -> ProviderDiscovery {
+    let mut discovery = ProviderDiscovery::default();
+    let mut seen = BTreeSet::new();
+    for (source, path) in sources {
+        if !path.is_file() || !seen.insert(path.clone()) {
+            continue;
+        }
+        discovery
+            .scanned_files
+            .push(path.to_string_lossy().into_owned());
+        match parse_file(*source, path) {
+            Ok(found) => discovery.providers.extend(found),
+            Err(e) => discovery
+                .notes
+                .push(format!("无法解析 {}: {e:#}", path.display())),
+        }
+    }
+
+    for item in &mut discovery.providers {
+        item.existing_provider_id = existing
+            .iter()
+            .find(|p| same_endpoint(p, &item.provider))
+            .map(|p| p.id.clone());
+    }
+    discovery
+}
+
+fn parse_file(source: ImportSource, path: &Path) -> Result<Vec<DiscoveredProvider>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("读取配置失败: {path:?}"))?;
+    let json: Value = serde_json::from_str(&content).context("JSON 格式无效")?;
+    let source_path = path.to_string_lossy().into_owned();
+    let mut found = match source {
+        ImportSource::CcSwitch => parse_cc_switch(&json)?,
+        ImportSource::ClaudeCodeRouter => parse_claude_code_router(&json)?,
+    };
+    for item in &mut found {
+        item.source_path = source_path.clone();
+    }
+    Ok(found)
+}
+
+/// cc-switch 中各应用对应的工具
+const CC_SWITCH_APPS: [(&str, &str); 3] = [
+    ("claude", "claude-code"),
+    ("codex", "codex"),
+    ("gemini", "gemini-cli"),
+];
+
+/// cc-switch 供应商条目中已识别的字段
+const CC_SWITCH_KNOWN_FIELDS: [&str; 6] = [
+    "id",
+    "name",
+    "settingsConfig",
+    "websiteUrl",
+    "createdAt",
+    "category",
+];
+
+/// 解析 cc-switch 配置
+///
+/// 新版按应用分组：`{"claude": {"providers": {...}, "current": "id"}, "codex": {...}}`；
+/// 旧版只有 Claude：`{"providers": {...}, "current": "id"}`。
+/// 各应用当前选中的供应商作为该工具的建议绑定。
+pub fn parse_cc_switch(json: &Value) -> Result<Vec<DiscoveredProvider>> {
+    let root = json.as_object().context("配置根节点不是对象")?;
+    let apps: Vec<(&str, &Map<String, Value>)> = if root.contains_key("providers") {
+        vec![("claude-code", root)]
+    } else {
+        CC_SWITCH_APPS
+            .iter()
+            .filter_map(|(app, tool)| {
+                root.get(*app)
+                    .and_then(Value::as_object)
+                    .map(|v| (*tool, v))
+            })
+            .collect()
+    };
+
+    let mut found: Vec<DiscoveredProvider> = Vec::new();
+    for (tool_id, app) in apps {
+        let current = app.get("current").and_then(Value::as_str);
+        let Some(providers) = app.get("providers").and_then(Value::as_object) else {
+            continue;
+        };
+        for (key, entry) in providers {
+            let Some(entry) = entry.as_object() else {
+                continue;
+            };
+            let name = entry
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or(key.as_str());
+            let mut notes = unknown_fields(entry, &CC_SWITCH_KNOWN_FIELDS);
+            let settings = entry.get("settingsConfig").unwrap_or(&Value::Null);
+            let (base_url, api_key) = cc_switch_endpoint(tool_id, settings, &mut notes);
+            let Some(base_url) = base_url else {
+                notes.push("未找到 API 地址，已跳过".to_string());
+                tracing::debug!(provider = %name, ?notes, "跳过 cc-switch 供应商");
+                continue;
+            };
+            let base_url = base_url.trim_end_matches('/').to_string();
+            let website = entry.get("websiteUrl").and_then(Value::as_str);
+            let is_current = current == Some(key.as_str());
+
+            // 同一供应商在多个应用中出现时合并
+            if let Some(item) = found
+                .iter_mut()
+                .find(|item| item.provider.api_address.as_deref() == Some(base_url.as_str()))
+            {
+                if is_current {
+                    item.suggested_tools.push(tool_id.to_string());
+                }
+                if item.provider.api_key.is_none() {
+                    item.provider.api_key = api_key;
+                }
+                item.notes.extend(notes);
+                continue;
+            }
+            found.push(DiscoveredProvider {
+                source: ImportSource::CcSwitch,
+                source_path: String::new(),
+                provider: draft_provider(name, &base_url, website, api_key),
+                suggested_tools: if is_current {
+                    vec![tool_id.to_string()]
+                } else {
+                    Vec::new()
+                },
+                existing_provider_id: None,
+                notes,
+            });
+        }
+    }
+    Ok(found)
+}
+
+/// 从 cc-switch 的 `settingsConfig` 中提取 API 地址与密钥
+fn cc_switch_endpoint(
+    tool_id: &str,
+    settings: &Value,
+    notes: &mut Vec<String>,
+) -> (Option<String>, Option<String>) {
+    let env = settings.get("env");
+    let env_str = |key: &str| {
+        env.and_then(|env| env.get(key))
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    match tool_id {
+        "claude-code" => (
+            env_str("ANTHROPIC_BASE_URL"),
+            env_str("ANTHROPIC_AUTH_TOKEN").or_else(|| env_str("ANTHROPIC_API_KEY")),
+        ),
+        "gemini-cli" => (env_str("GOOGLE_GEMINI_BASE_URL"), env_str("GEMINI_API_KEY")),
+        _ => {
+            let api_key = settings
+                .pointer("/auth/OPENAI_API_KEY")
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string);
+            let config = settings.get("config").and_then(Value::as_str).unwrap_or("");
+            let base_url = match codex_base_url(config) {
+                Ok(url) => url,
+                Err(e) => {
+                    notes.push(format!("Codex config.toml 无法解析: {e}"));
+                    None
+                }
+            };
+            (base_url, api_key)
+        }
+    }
+}
+
+/// 读取 Codex `config.toml` 中当前 `model_provider` 的 `base_url`
+fn codex_base_url(config: &str) -> Result<Option<String>> {
+    if config.trim().is_empty() {
+        return Ok(None);
+    }
+    let doc: toml::Table = toml::from_str(config)?;
+    let providers = doc.get("model_providers").and_then(toml::Value::as_table);
+    let selected = doc
+        .get("model_provider")
+        .and_then(toml::Value::as_str)
+        .and_then(|name| providers.and_then(|p| p.get(name)))
+        .or_else(|| providers.and_then(|p| p.values().next()));
+    Ok(selected
+        .and_then(|p| p.get("base_url"))
+        .and_then(toml::Value::as_str)
+        .map(str::to_string))
+}
+
+/// claude-code-router 供应商条目中已识别的字段
+const CCR_KNOWN_FIELDS: [&str; 3] = ["name", "api_base_url", "api_key"];
+
+/// 解析 claude-code-router 配置
+///
+/// 格式：`{"Providers": [{"name", "api_base_url", "api_key", "models", "transformer"}],
+/// "Router": {"default": "provider,model"}}`，`Router.default` 指向的供应商作为 Claude Code 的建议绑定。
+pub fn parse_claude_code_router(json: &Value) -> Result<Vec<DiscoveredProvider>> {
+    let root = json.as_object().context("配置根节点不是对象")?;
+    let providers = root
+        .get("Providers")
+        .or_else(|| root.get("providers"))
+        .and_then(Value::as_array)
+        .context("未找到 Providers 列表")?;
+    let default_provider = root
+        .get("Router")
+        .and_then(|router| router.get("default"))
+        .and_then(Value::as_str)
+        .and_then(|route| route.split(',').next())
+        .map(str::trim);
+
+    let mut found = Vec::new();
+    for entry in providers.iter().filter_map(Value::as_object) {
+        let Some(name) = entry.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some(base_url) = entry
+            .get("api_base_url")
+            .and_then(Value::as_str)
+            .filter(|url| !url.trim().is_empty())
+        else {
+            continue;
+        };
+        let api_key = entry
+            .get("api_key")
+            .and_then(Value::as_str)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string);
+        found.push(DiscoveredProvider {
+            source: ImportSource::ClaudeCodeRouter,
+            source_path: String::new(),
+            provider: draft_provider(name, &endpoint_base(base_url), None, api_key),
+            suggested_tools: if default_provider == Some(name) {
+                vec!["claude-code".to_string()]
+            } else {
+                Vec::new()
+            },
+            existing_provider_id: None,
+            notes: unknown_fields(entry, &CCR_KNOWN_FIELDS),
+        });
+    }
+    Ok(found)
+}
+
+/// claude-code-router 记录的是完整端点（如 `.../v1/chat/completions`），取其服务地址
+fn endpoint_base(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    match url.find("/v1/") {
+        Some(idx) => url[..idx].to_string(),
+        None => url.trim_end_matches("/v1").to_string(),
+    }
+}
+
+fn unknown_fields(entry: &Map<String, Value>, known: &[&str]) -> Vec<String> {
+    let ignored: Vec<&str> = entry
+        .keys()
+        .map(String::as_str)
+        .filter(|key| !known.contains(key))
+        .collect();
+    if ignored.is_empty() {
+        Vec::new()
+    } else {
+        vec![format!("已忽略未识别字段: {}", ignored.join(", "))]
+    }
+}
+
+fn draft_provider(
+    name: &str,
+    base_url: &str,
+    website: Option<&str>,
+    api_key: Option<String>,
+) -> Provider {
+    let base_url = base_url.trim().trim_end_matches('/').to_string();
+    let website_url = website
+        .map(str::to_string)
+        .or_else(|| {
+            url::Url::parse(&base_url)
+                .ok()
+                .map(|u| u.origin().ascii_serialization())
+        })
+        .unwrap_or_else(|| base_url.clone());
+    Provider {
+        id: slugify(name),
+        name: name.to_string(),
+        website_url,
+        api_address: Some(base_url),
+        user_id: String::new(),
+        access_token: String::new(),
+        api_key,
+        balance_template: Default::default(),
+        custom_headers: Default::default(),
+        username: None,
+        is_default: false,
+        created_at: 0,
+        updated_at: 0,
+    }
+}
+
+/// 由名称生成供应商 ID（小写字母、数字与连字符）
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-').to_string();
+    if slug.is_empty() {
+        "imported".to_string()
+    } else {
+        slug
+    }
+}
+
+fn same_endpoint(existing: &Provider, draft: &Provider) -> bool {
+    let normalize = |url: &str| url.trim().trim_end_matches('/').to_ascii_lowercase();
+    let Some(draft_url) = draft.api_address.as_deref().map(normalize) else {
+        return false;
+    };
+    existing
+        .api_address
+        .as_deref()
+        .into_iter()
+        .chain(std::iter::once(existing.website_url.as_str()))
+        .any(|url| normalize(url) == draft_url)
+}
+
+/// 用户确认导入的条目
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfirmedImport {
+    /// 供应商草稿（可能已被用户修改）
+    pub provider: Provider,
+    /// 需要绑定的工具 ID
+    #[serde(default)]
+    pub bind_tools: Vec<String>,
+    /// 使用已存在的供应商（只设置绑定，不创建）
+    #[serde(default)]
+    pub existing_provider_id: Option<String>,
+}
+
+/// 导入产生的工具绑定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportedBinding {
+    pub tool_id: String,
+    pub provider_id: String,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderImportResult {
+    pub created: Vec<Provider>,
+    pub bindings: Vec<ImportedBinding>,
+    /// 失败的条目说明（不影响其余条目）
+    pub failures: Vec<String>,
+}
+
+/// 在已有 ID 中为草稿生成不冲突的 ID（`name`、`name-2`、`name-3`...）
+pub fn unique_provider_id(base: &str, taken: &[String]) -> String {
+    if !taken.iter().any(|id| id == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|id| !taken.contains(id))
+        .unwrap_or_else(|| base.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_cc_switch_grouped_config() {
+        let config = json!({
+            "claude": {
+                "current": "duck",
+                "providers": {
+                    "duck": {
+                        "id": "duck",
+                        "name": "Duck Relay",
+                        "websiteUrl": "https://duck.example.com",
+                        "settingsConfig": {"env": {
+                            "ANTHROPIC_BASE_URL": "https://api.duck.example.com/",
+                            "ANTHROPIC_AUTH_TOKEN": "sk-claude"
+                        }},
+                        "meta": {"icon": "duck"}
+                    },
+                    "official": {"name": "Official", "settingsConfig": {"env": {}}}
+                }
+            },
+            "codex": {
+                "current": "relay",
+                "providers": {
+                    "relay": {
+                        "name": "Duck Relay",
+                        "settingsConfig": {
+                            "auth": {"OPENAI_API_KEY": "sk-codex"},
+                            "config": "model_provider = \"duck\"\n[model_providers.duck]\nbase_url = \"https://api.duck.example.com\"\n"
+                        }
+                    }
+                }
+            }
+        });
+
+        let found = parse_cc_switch(&config).unwrap();
+        assert_eq!(found.len(), 1);
+        let item = &found[0];
+        assert_eq!(item.provider.id, "duck-relay");
+        assert_eq!(item.provider.website_url, "https://duck.example.com");
+        assert_eq!(
+            item.provider.api_address.as_deref(),
+            Some("https://api.duck.example.com")
+        );
+        assert_eq!(item.provider.api_key.as_deref(), Some("sk-claude"));
+        assert_eq!(item.suggested_tools, vec!["claude-code", "codex"]);
+        assert!(item.notes.iter().any(|note| note.contains("meta")));
+    }
+
+    #[test]
+    fn test_parse_cc_switch_legacy_config() {
+        let config = json!({
+            "current": "a",
+            "providers": {"a": {"name": "A", "settingsConfig": {"env": {
+                "ANTHROPIC_BASE_URL": "https://a.example.com/api"
+            }}}}
+        });
+        let found = parse_cc_switch(&config).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].provider.website_url, "https://a.example.com");
+        assert_eq!(found[0].suggested_tools, vec!["claude-code"]);
+    }
+
+    #[test]
+    fn test_parse_claude_code_router_config() {
+        let config = json!({
+            "Providers": [
+                {
+                    "name": "openrouter",
+                    "api_base_url": "https://openrouter.ai/api/v1/chat/completions",
+                    "api_key": "sk-or",
+                    "models": ["anthropic/claude-sonnet-4"],
+                    "transformer": {"use": ["openrouter"]}
+                },
+                {"name": "ollama", "api_base_url": "http://localhost:11434/v1/chat/completions"}
+            ],
+            "Router": {"default": "openrouter,anthropic/claude-sonnet-4"}
+        });
+        let found = parse_claude_code_router(&config).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].provider.api_address.as_deref(),
+            Some("https://openrouter.ai/api")
+        );
+        assert_eq!(found[0].suggested_tools, vec!["claude-code"]);
+        assert!(found[0].notes[0].contains("models"));
+        assert!(found[1].suggested_tools.is_empty());
+        assert!(found[1].provider.api_key.is_none());
+    }
+
+    #[test]
+    fn test_discover_marks_existing_and_reports_invalid_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let ccr = temp_dir.path().join("ccr.json");
+        let broken = temp_dir.path().join("cc-switch.json");
+        std::fs::write(
+            &ccr,
+            r#"{"Providers":[{"name":"Duck","api_base_url":"https://jp.duckcoding.com/v1/chat/completions"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(&broken, "not json").unwrap();
+
+        let existing = crate::models::provider::ProviderStore::default().providers;
+        let discovery = discover_from(
+            &[
+                (ImportSource::ClaudeCodeRouter, ccr.clone()),
+                (ImportSource::CcSwitch, broken),
+                (ImportSource::CcSwitch, temp_dir.path().join("missing.json")),
+            ],
+            &existing,
+        );
+        assert_eq!(discovery.scanned_files.len(), 2);
+        assert_eq!(discovery.notes.len(), 1);
+        assert_eq!(discovery.providers.len(), 1);
+        assert_eq!(
+            discovery.providers[0].existing_provider_id.as_deref(),
+            Some("duckcoding")
+        );
+        assert_eq!(
+            discovery.providers[0].source_path,
+            ccr.to_string_lossy().as_ref()
+        );
+    }
+
+    #[test]
+    fn test_unique_provider_id() {
+        let taken = vec!["duck".to_string(), "duck-2".to_string()];
+        assert_eq!(unique_provider_id("duck", &taken), "duck-3");
+        assert_eq!(unique_provider_id("goose", &taken), "goose");
+        assert_eq!(slugify("  Duck Relay (JP) "), "duck-relay-jp");
+        assert_eq!(slugify("中转"), "imported");
+    }
+}
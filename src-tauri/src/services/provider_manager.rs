@@ -2,12 +2,64 @@
 //
 // 供应商配置管理服务
 
-use crate::data::DataManager;
+use crate::core::error::AppError;
+use crate::data::{BackupPolicy, DataManager};
+use crate::http_client::merge_custom_headers;
 use crate::models::provider::{Provider, ProviderStore};
+use crate::services::trash::unique_id;
 use crate::utils::config::config_dir;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// 供应商存储写入后发送的事件（负载为 `ProvidersChangedEvent`）
+pub const PROVIDERS_CHANGED_EVENT: &str = "providers-changed";
+
+/// 供应商存储变化通知（写入 providers.json 后发送）
+#[derive(Debug, Clone, Serialize)]
+pub struct ProvidersChangedEvent {
+    pub updated_at: i64,
+}
+
+/// 进程内共享的变化通知（各个 State 各自构造 ProviderManager）
+static CHANGES: Lazy<broadcast::Sender<ProvidersChangedEvent>> =
+    Lazy::new(|| broadcast::channel(16).0);
+
+/// 订阅供应商存储变化
+pub fn subscribe_provider_changes() -> broadcast::Receiver<ProvidersChangedEvent> {
+    CHANGES.subscribe()
+}
+
+/// 进程内共享的供应商列表快照（代理按请求查询，避免每次请求读取磁盘）
+static SHARED_PROVIDERS: Lazy<Mutex<Option<Arc<Vec<Provider>>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 快照失效计数（读取期间发生失效时不保存读取结果）
+static SHARED_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 读取共享的供应商列表（首次读取或失效后才访问磁盘）
+pub fn shared_providers() -> Result<Arc<Vec<Provider>>> {
+    if let Some(providers) = SHARED_PROVIDERS.lock().unwrap().as_ref() {
+        return Ok(providers.clone());
+    }
+    let generation = SHARED_GENERATION.load(Ordering::SeqCst);
+    let providers = Arc::new(ProviderManager::new()?.list_providers()?);
+    let mut shared = SHARED_PROVIDERS.lock().unwrap();
+    if SHARED_GENERATION.load(Ordering::SeqCst) == generation {
+        *shared = Some(providers.clone());
+    }
+    Ok(providers)
+}
+
+/// 使共享的供应商列表失效（providers.json 被写入或外部修改后调用）
+pub fn invalidate_shared_providers() {
+    let mut shared = SHARED_PROVIDERS.lock().unwrap();
+    SHARED_GENERATION.fetch_add(1, Ordering::SeqCst);
+    *shared = None;
+}
 
 /// 供应商管理器
 pub struct ProviderManager {
@@ -55,13 +107,40 @@ impl ProviderManager {
         Ok(store)
     }
 
-    /// 保存存储
+    /// 加锁读-改-写存储
+    ///
+    /// 持有 providers.json 的文件锁期间直接从磁盘读取，避免覆盖其他进程的修改
+    fn update_store<T>(&self, f: impl FnOnce(&mut ProviderStore) -> Result<T>) -> Result<T> {
+        let mut updated_at = 0;
+        let result = self.data_manager.with_file_lock(&self.store_path, || {
+            let mut store = if self.store_path.exists() {
+                let json_value = self.data_manager.json().read(&self.store_path)?;
+                serde_json::from_value(json_value)
+                    .map_err(|e| anyhow::anyhow!("反序列化 ProviderStore 失败: {}", e))?
+            } else {
+                ProviderStore::default()
+            };
+            let result = f(&mut store)?;
+            self.save_store(&store)?;
+            updated_at = store.updated_at;
+            Ok(result)
+        })?;
+
+        // 写入落盘并释放文件锁后再通知，监听方重新读取即可看到新数据
+        invalidate_shared_providers();
+        let _ = CHANGES.send(ProvidersChangedEvent { updated_at });
+        Ok(result)
+    }
+
+    /// 保存存储（调用方需持有文件锁）
     fn save_store(&self, store: &ProviderStore) -> Result<()> {
         let json_value = serde_json::to_value(store)
             .map_err(|e| anyhow::anyhow!("序列化 ProviderStore 失败: {}", e))?;
-        self.data_manager
-            .json()
-            .write(&self.store_path, &json_value)?;
+        self.data_manager.write_json_with_backup(
+            &self.store_path,
+            &json_value,
+            &BackupPolicy::beside(&self.store_path),
+        )?;
         *self.cache.lock().unwrap() = Some(store.clone());
         Ok(())
     }
@@ -73,65 +152,93 @@ impl ProviderManager {
 
     /// 创建供应商
     pub fn create_provider(&self, mut provider: Provider) -> Result<Provider> {
-        let mut store = self.load_store()?;
-
-        // 检查 ID 冲突
-        if store.providers.iter().any(|p| p.id == provider.id) {
-            return Err(anyhow!("供应商 ID 已存在: {}", provider.id));
-        }
+        provider.validate_custom_headers()?;
+        self.update_store(|store| {
+            // 检查 ID 冲突
+            if store.providers.iter().any(|p| p.id == provider.id) {
+                return Err(AppError::ProviderAlreadyExists {
+                    id: provider.id.clone(),
+                }
+                .into());
+            }
 
-        let now = chrono::Utc::now().timestamp();
-        provider.created_at = now;
-        provider.updated_at = now;
+            let now = chrono::Utc::now().timestamp();
+            provider.created_at = now;
+            provider.updated_at = now;
 
-        store.providers.push(provider.clone());
-        store.updated_at = now;
-
-        self.save_store(&store)?;
-        Ok(provider)
+            store.providers.push(provider.clone());
+            store.updated_at = now;
+            Ok(provider)
+        })
     }
 
     /// 更新供应商
     pub fn update_provider(&self, id: &str, updated: Provider) -> Result<Provider> {
-        let mut store = self.load_store()?;
+        updated.validate_custom_headers()?;
+        self.update_store(|store| {
+            let provider = store
+                .providers
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| AppError::ProviderNotFound { id: id.to_string() })?;
 
-        let provider = store
-            .providers
-            .iter_mut()
-            .find(|p| p.id == id)
-            .ok_or_else(|| anyhow!("供应商不存在: {}", id))?;
+            provider.name = updated.name;
+            provider.website_url = updated.website_url;
+            provider.api_address = updated.api_address;
+            provider.user_id = updated.user_id;
+            provider.access_token = updated.access_token;
+            provider.api_key = updated.api_key;
+            provider.custom_headers = updated.custom_headers;
+            provider.username = updated.username;
+            provider.updated_at = chrono::Utc::now().timestamp();
 
-        provider.name = updated.name;
-        provider.website_url = updated.website_url;
-        provider.api_address = updated.api_address;
-        provider.user_id = updated.user_id;
-        provider.access_token = updated.access_token;
-        provider.username = updated.username;
-        provider.updated_at = chrono::Utc::now().timestamp();
+            let updated_at = provider.updated_at;
+            let result = provider.clone();
 
-        let updated_at = provider.updated_at;
-        let result = provider.clone();
+            store.updated_at = updated_at;
+            Ok(result)
+        })
+    }
 
-        store.updated_at = updated_at;
-        self.save_store(&store)?;
+    /// 删除供应商，返回被删除的记录（不存在时为 None）
+    pub fn delete_provider(&self, id: &str) -> Result<Option<Provider>> {
+        self.update_store(|store| {
+            // 不允许删除默认供应商
+            if store.providers.iter().any(|p| p.id == id && p.is_default) {
+                return Err(anyhow!("无法删除默认供应商"));
+            }
 
-        Ok(result)
+            let removed = store.providers.iter().position(|p| p.id == id);
+            let removed = removed.map(|index| store.providers.remove(index));
+            store.updated_at = chrono::Utc::now().timestamp();
+            Ok(removed)
+        })
     }
 
-    /// 删除供应商
-    pub fn delete_provider(&self, id: &str) -> Result<()> {
-        let mut store = self.load_store()?;
-
-        // 不允许删除默认供应商
-        if store.providers.iter().any(|p| p.id == id && p.is_default) {
-            return Err(anyhow!("无法删除默认供应商"));
-        }
+    /// 从回收站恢复供应商；ID 已被占用时生成新 ID，返回恢复后的供应商
+    pub fn restore_provider(&self, mut provider: Provider) -> Result<Provider> {
+        provider.validate_custom_headers()?;
+        self.update_store(|store| {
+            provider.id = unique_id(&provider.id, |id| {
+                Ok(store.providers.iter().any(|p| p.id == id))
+            })?;
+            // 默认供应商不可删除，恢复的记录一律作为普通供应商
+            provider.is_default = false;
+            provider.updated_at = chrono::Utc::now().timestamp();
 
-        store.providers.retain(|p| p.id != id);
-        store.updated_at = chrono::Utc::now().timestamp();
-        self.save_store(&store)?;
+            store.providers.push(provider.clone());
+            store.updated_at = provider.updated_at;
+            Ok(provider)
+        })
+    }
 
-        Ok(())
+    /// 按 ID 获取供应商
+    pub fn get_provider(&self, id: &str) -> Result<Option<Provider>> {
+        Ok(self
+            .load_store()?
+            .providers
+            .into_iter()
+            .find(|p| p.id == id))
     }
 
     /// 清除缓存（用于测试或强制刷新）
@@ -146,6 +253,107 @@ impl Default for ProviderManager {
     }
 }
 
+/// 供应商验证结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderValidation {
+    pub success: bool,
+    pub username: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ProviderValidation {
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            username: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// 验证供应商配置（请求 `{website_url}/api/user/self` 检查访问令牌）
+///
+/// 配置缺失或 API 拒绝时返回 `success: false`；仅网络层失败返回错误。
+pub async fn validate_provider(provider: &Provider) -> Result<ProviderValidation> {
+    use reqwest::Client;
+    use std::time::Duration;
+
+    // 基础验证
+    if provider.website_url.is_empty() {
+        return Ok(ProviderValidation::failed("官网地址不能为空"));
+    }
+    if provider.user_id.is_empty() {
+        return Ok(ProviderValidation::failed("用户 ID 不能为空"));
+    }
+    if provider.access_token.is_empty() {
+        return Ok(ProviderValidation::failed("访问令牌不能为空"));
+    }
+
+    let api_url = format!(
+        "{}/api/user/self",
+        provider.website_url.trim_end_matches('/')
+    );
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .context("创建 HTTP 客户端失败")?;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        "Authorization",
+        format!("Bearer {}", provider.access_token)
+            .parse()
+            .context("访问令牌包含非法字符")?,
+    );
+    headers.insert(
+        "New-Api-User",
+        provider.user_id.parse().context("用户 ID 包含非法字符")?,
+    );
+    merge_custom_headers(&mut headers, &provider.custom_headers);
+    let response = client
+        .get(&api_url)
+        .headers(headers)
+        .send()
+        .await
+        .context("API 请求失败")?;
+
+    if !response.status().is_success() {
+        return Ok(ProviderValidation::failed(format!(
+            "API 验证失败，状态码: {}",
+            response.status().as_u16()
+        )));
+    }
+
+    let json = match response.json::<serde_json::Value>().await {
+        Ok(json) => json,
+        Err(e) => return Ok(ProviderValidation::failed(format!("API 响应格式错误: {e}"))),
+    };
+    // 没有 success 字段时默认为 true（兼容不同 API）
+    let api_success = json
+        .get("success")
+        .and_then(|s| s.as_bool())
+        .unwrap_or(true);
+    if !api_success {
+        let error_msg = json
+            .get("message")
+            .and_then(|m| m.as_str())
+            .unwrap_or("API 验证失败");
+        return Ok(ProviderValidation::failed(error_msg));
+    }
+
+    // 用户名位于 data.username 或 username 字段
+    let username = json
+        .get("data")
+        .and_then(|data| data.get("username"))
+        .or_else(|| json.get("username"))
+        .and_then(|u| u.as_str())
+        .map(|s| s.to_string());
+    Ok(ProviderValidation {
+        success: true,
+        username,
+        error: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
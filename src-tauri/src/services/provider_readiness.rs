@@ -0,0 +1,363 @@
+// Provider Readiness Service
+//
+// 启动工具前预先验证所绑定的供应商（访问令牌是否仍然有效），避免会话开始后才发现令牌过期。
+// 验证结果按供应商缓存（供应商被编辑后 `updated_at` 变化即失效），缓存未过期时不发起网络请求；
+// 所有供应商并发验证且各自限时，整次调用最坏约 `PER_PROVIDER_TIMEOUT`。
+
+use crate::models::provider::Provider;
+use crate::services::provider_manager::{validate_provider, ProviderValidation};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 缓存的验证结果在此时长内直接复用
+pub const VALIDATION_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// 单个供应商的验证时限
+pub const PER_PROVIDER_TIMEOUT: Duration = Duration::from_millis(2800);
+
+/// 工具的供应商就绪状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessStatus {
+    /// 供应商验证通过
+    Ready,
+    /// 供应商拒绝了凭证（令牌过期、被禁用等）
+    Rejected,
+    /// 网络请求失败
+    Unreachable,
+    /// 验证超时
+    Timeout,
+    /// 缺少官网地址 / 用户 ID / 访问令牌，无法验证
+    Unverifiable,
+    /// 绑定的供应商已不存在
+    ProviderMissing,
+}
+
+/// 修复提示（前端据此跳转）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReadinessHint {
+    /// 重新验证供应商
+    RevalidateProvider {
+        provider_id: String,
+        provider_name: String,
+    },
+    /// 重新为工具绑定供应商
+    RebindProvider { tool_id: String },
+}
+
+/// 单个工具的就绪结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReadiness {
+    pub tool_id: String,
+    pub provider_id: String,
+    pub provider_name: Option<String>,
+    pub status: ReadinessStatus,
+    /// 失败时供应商返回的错误详情
+    pub error: Option<String>,
+    pub hint: Option<ReadinessHint>,
+    /// 验证时间（Unix 时间戳）
+    pub checked_at: i64,
+    /// 是否来自缓存
+    pub cached: bool,
+}
+
+impl ToolReadiness {
+    pub fn is_ready(&self) -> bool {
+        self.status == ReadinessStatus::Ready
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedValidation {
+    provider_updated_at: i64,
+    checked_at: i64,
+    validation: ProviderValidation,
+}
+
+/// 供应商验证结果缓存（按供应商 ID）
+#[derive(Default)]
+pub struct ValidationCache {
+    entries: Mutex<HashMap<String, CachedValidation>>,
+}
+
+impl ValidationCache {
+    /// 未过期且供应商未被编辑时返回缓存的结果与验证时间
+    fn get(&self, provider: &Provider, now: i64) -> Option<(ProviderValidation, i64)> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(&provider.id)?;
+        let fresh = entry.provider_updated_at == provider.updated_at
+            && now - entry.checked_at < VALIDATION_CACHE_TTL.as_secs() as i64;
+        fresh.then(|| (entry.validation.clone(), entry.checked_at))
+    }
+
+    fn insert(&self, provider: &Provider, validation: ProviderValidation, now: i64) {
+        self.entries.lock().unwrap().insert(
+            provider.id.clone(),
+            CachedValidation {
+                provider_updated_at: provider.updated_at,
+                checked_at: now,
+                validation,
+            },
+        );
+    }
+}
+
+/// 全局验证缓存
+static VALIDATION_CACHE: Lazy<ValidationCache> = Lazy::new(ValidationCache::default);
+
+/// 单个供应商的验证结果
+#[derive(Debug, Clone)]
+struct ProviderCheck {
+    status: ReadinessStatus,
+    error: Option<String>,
+    checked_at: i64,
+    cached: bool,
+}
+
+fn is_verifiable(provider: &Provider) -> bool {
+    !provider.website_url.is_empty()
+        && !provider.user_id.is_empty()
+        && !provider.access_token.is_empty()
+}
+
+/// 验证供应商（优先使用缓存；网络失败与超时不写入缓存，下次重试）
+async fn check_provider(cache: &ValidationCache, provider: &Provider) -> ProviderCheck {
+    let now = chrono::Utc::now().timestamp();
+    if !is_verifiable(provider) {
+        return ProviderCheck {
+            status: ReadinessStatus::Unverifiable,
+            error: None,
+            checked_at: now,
+            cached: false,
+        };
+    }
+    if let Some((validation, checked_at)) = cache.get(provider, now) {
+        return ProviderCheck {
+            status: status_of(&validation),
+            error: validation.error,
+            checked_at,
+            cached: true,
+        };
+    }
+
+    let (status, error) =
+        match tokio::time::timeout(PER_PROVIDER_TIMEOUT, validate_provider(provider)).await {
+            Ok(Ok(validation)) => {
+                cache.insert(provider, validation.clone(), now);
+                (status_of(&validation), validation.error)
+            }
+            Ok(Err(e)) => (ReadinessStatus::Unreachable, Some(format!("{e:#}"))),
+            Err(_) => (
+                ReadinessStatus::Timeout,
+                Some(format!(
+                    "验证超时（{} 秒）",
+                    PER_PROVIDER_TIMEOUT.as_secs_f32()
+                )),
+            ),
+        };
+    ProviderCheck {
+        status,
+        error,
+        checked_at: now,
+        cached: false,
+    }
+}
+
+fn status_of(validation: &ProviderValidation) -> ReadinessStatus {
+    if validation.success {
+        ReadinessStatus::Ready
+    } else {
+        ReadinessStatus::Rejected
+    }
+}
+
+/// 验证各工具绑定的供应商，返回按工具 ID 排序的就绪结果
+///
+/// `bindings` 为工具 ID → 供应商 ID。多个工具绑定同一供应商时只验证一次。
+pub async fn validate_active_bindings(
+    bindings: &HashMap<String, String>,
+    providers: &[Provider],
+) -> Vec<ToolReadiness> {
+    validate_with_cache(&VALIDATION_CACHE, bindings, providers).await
+}
+
+async fn validate_with_cache(
+    cache: &ValidationCache,
+    bindings: &HashMap<String, String>,
+    providers: &[Provider],
+) -> Vec<ToolReadiness> {
+    let by_id: HashMap<&str, &Provider> = providers.iter().map(|p| (p.id.as_str(), p)).collect();
+    let mut bound: Vec<&Provider> = bindings
+        .values()
+        .filter_map(|id| by_id.get(id.as_str()).copied())
+        .collect();
+    bound.sort_by(|a, b| a.id.cmp(&b.id));
+    bound.dedup_by(|a, b| a.id == b.id);
+
+    let checks = futures_util::future::join_all(bound.iter().map(|provider| async move {
+        (provider.id.as_str(), check_provider(cache, provider).await)
+    }))
+    .await;
+    let checks: HashMap<&str, ProviderCheck> = checks.into_iter().collect();
+
+    let sorted: BTreeMap<&String, &String> = bindings.iter().collect();
+    sorted
+        .into_iter()
+        .map(|(tool_id, provider_id)| {
+            let provider = by_id.get(provider_id.as_str());
+            let check = checks.get(provider_id.as_str());
+            let (status, error, checked_at, cached) = match check {
+                Some(check) => (
+                    check.status,
+                    check.error.clone(),
+                    check.checked_at,
+                    check.cached,
+                ),
+                None => (
+                    ReadinessStatus::ProviderMissing,
+                    Some(format!("供应商 {provider_id} 不存在")),
+                    chrono::Utc::now().timestamp(),
+                    false,
+                ),
+            };
+            let hint = match (status, provider) {
+                (ReadinessStatus::Ready, _) => None,
+                (ReadinessStatus::ProviderMissing, _) => Some(ReadinessHint::RebindProvider {
+                    tool_id: tool_id.clone(),
+                }),
+                (_, Some(provider)) => Some(ReadinessHint::RevalidateProvider {
+                    provider_id: provider.id.clone(),
+                    provider_name: provider.name.clone(),
+                }),
+                (_, None) => None,
+            };
+            ToolReadiness {
+                tool_id: tool_id.clone(),
+                provider_id: provider_id.clone(),
+                provider_name: provider.map(|p| p.name.clone()),
+                status,
+                error,
+                hint,
+                checked_at,
+                cached,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(id: &str, token: &str) -> Provider {
+        let mut provider = crate::models::provider::ProviderStore::default().providers[0].clone();
+        provider.id = id.to_string();
+        provider.name = id.to_uppercase();
+        provider.user_id = "1".to_string();
+        provider.access_token = token.to_string();
+        provider
+    }
+
+    fn bindings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(tool, provider)| (tool.to_string(), provider.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_uses_cached_validation_without_network() {
+        let cache = ValidationCache::default();
+        let ok = provider("ok", "token-ok");
+        let expired = provider("expired", "token-expired");
+        let now = chrono::Utc::now().timestamp();
+        cache.insert(
+            &ok,
+            ProviderValidation {
+                success: true,
+                username: None,
+                error: None,
+            },
+            now,
+        );
+        cache.insert(
+            &expired,
+            ProviderValidation {
+                success: false,
+                username: None,
+                error: Some("令牌已过期".to_string()),
+            },
+            now,
+        );
+
+        let result = validate_with_cache(
+            &cache,
+            &bindings(&[
+                ("claude-code", "ok"),
+                ("codex", "expired"),
+                ("gemini-cli", "ok"),
+            ]),
+            &[ok, expired],
+        )
+        .await;
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].tool_id, "claude-code");
+        assert!(result[0].is_ready() && result[0].cached);
+        assert_eq!(result[1].status, ReadinessStatus::Rejected);
+        assert_eq!(result[1].error.as_deref(), Some("令牌已过期"));
+        assert_eq!(
+            result[1].hint,
+            Some(ReadinessHint::RevalidateProvider {
+                provider_id: "expired".to_string(),
+                provider_name: "EXPIRED".to_string(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_and_unverifiable_providers() {
+        let cache = ValidationCache::default();
+        let api_key_only = provider("api-key-only", "");
+
+        let result = validate_with_cache(
+            &cache,
+            &bindings(&[("claude-code", "gone"), ("codex", "api-key-only")]),
+            &[api_key_only],
+        )
+        .await;
+
+        assert_eq!(result[0].status, ReadinessStatus::ProviderMissing);
+        assert_eq!(
+            result[0].hint,
+            Some(ReadinessHint::RebindProvider {
+                tool_id: "claude-code".to_string()
+            })
+        );
+        assert_eq!(result[1].status, ReadinessStatus::Unverifiable);
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_edit_and_age() {
+        let cache = ValidationCache::default();
+        let mut p = provider("p", "token");
+        let validation = ProviderValidation {
+            success: true,
+            username: None,
+            error: None,
+        };
+        let now = chrono::Utc::now().timestamp();
+        cache.insert(&p, validation, now);
+        assert!(cache.get(&p, now).is_some());
+        assert!(cache
+            .get(&p, now + VALIDATION_CACHE_TTL.as_secs() as i64)
+            .is_none());
+
+        p.updated_at += 1;
+        assert!(cache.get(&p, now).is_none());
+    }
+}
@@ -0,0 +1,692 @@
+// Provider Trial Service
+//
+// 限时试用供应商：通过一键配置的同一条应用路径（`apply_provider_profile`）写入配置，
+// 应用前记录 Profile 存储与原生配置文件的原始内容，到期后自动恢复。
+//
+// - 试用记录保存在 provider_trials.json，应用重启后继续计时；关闭期间到期的试用在下次启动时补做恢复
+// - 到期前 `WARNING_BEFORE_SECS` 发出一次提醒事件
+// - 恢复前对照写入记录（write ledger）检查文件：试用期间被手动修改过的配置不会被覆盖，
+//   此时跳过恢复并发出警告事件，由用户自行处理
+// - 恢复成功（或跳过）后才移除试用记录；恢复失败的试用保留在记录中，间隔 `REVERT_RETRY_SECS` 后重试
+
+use crate::data::DataManager;
+use crate::models::provider::Provider;
+use crate::models::Tool;
+use crate::services::config::write_ledger::{ProvenanceStatus, WriteLedger};
+use crate::services::profile_manager::ProfileManager;
+use crate::services::tool_setup::{
+    apply_provider_profile, native_config_files, restore_file, ConfigSnapshot, SetupOptions,
+};
+use crate::utils::config::config_dir;
+use crate::utils::file_helpers::file_checksum;
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 试用事件名（前端监听）
+pub const PROVIDER_TRIAL_EVENT: &str = "provider-trial";
+
+/// 到期前多久发出提醒
+pub const WARNING_BEFORE_SECS: i64 = 5 * 60;
+
+/// 试用时长上限（分钟）
+pub const MAX_TRIAL_MINUTES: u32 = 24 * 60;
+
+/// 检查到期试用的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 恢复失败后的重试间隔（秒）
+const REVERT_RETRY_SECS: i64 = 60;
+
+/// 恢复配置时写入记录使用的操作名
+const REVERT_ACTION: &str = "provider_trial_revert";
+
+/// 全局试用管理器
+pub static PROVIDER_TRIALS: Lazy<ProviderTrialManager> = Lazy::new(ProviderTrialManager::new);
+
+/// 试用涉及的一个文件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrialFile {
+    pub path: PathBuf,
+    /// 试用前的内容（`None` 表示原本不存在，恢复时删除）
+    pub original: Option<String>,
+    /// 应用试用配置后的 SHA256（`None` 表示应用后文件不存在）
+    pub applied_sha256: Option<String>,
+}
+
+/// 一次进行中的试用（持久化记录）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderTrial {
+    pub id: String,
+    pub tool_id: String,
+    pub provider_id: String,
+    /// 开始时间（Unix 时间戳）
+    pub started_at: i64,
+    /// 到期时间（Unix 时间戳）
+    pub ends_at: i64,
+    /// 是否已发出到期提醒
+    #[serde(default)]
+    pub warned: bool,
+    /// 恢复失败后下次重试的时间（Unix 时间戳）
+    #[serde(default)]
+    pub retry_at: Option<i64>,
+    pub files: Vec<TrialFile>,
+}
+
+/// 试用概要（发给前端，不含文件原始内容）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProviderTrialInfo {
+    pub id: String,
+    pub tool_id: String,
+    pub provider_id: String,
+    pub started_at: i64,
+    pub ends_at: i64,
+    pub warned: bool,
+}
+
+impl ProviderTrial {
+    pub fn info(&self) -> ProviderTrialInfo {
+        ProviderTrialInfo {
+            id: self.id.clone(),
+            tool_id: self.tool_id.clone(),
+            provider_id: self.provider_id.clone(),
+            started_at: self.started_at,
+            ends_at: self.ends_at,
+            warned: self.warned,
+        }
+    }
+}
+
+/// 试用事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialEventKind {
+    /// 已应用试用配置
+    Applied,
+    /// 即将到期
+    Warning,
+    /// 已恢复试用前的配置
+    Reverted,
+    /// 配置在试用期间被修改，跳过恢复
+    RevertSkipped,
+    /// 恢复失败
+    RevertFailed,
+    /// 用户结束试用并保留当前配置
+    Kept,
+}
+
+/// `provider-trial` 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderTrialEvent {
+    pub kind: TrialEventKind,
+    pub trial: ProviderTrialInfo,
+    /// 试用期间被修改的文件（`RevertSkipped` 时非空）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub modified_files: Vec<String>,
+    pub message: String,
+}
+
+impl ProviderTrialEvent {
+    fn new(kind: TrialEventKind, trial: &ProviderTrial, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            trial: trial.info(),
+            modified_files: Vec::new(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrialStore {
+    #[serde(default)]
+    trials: Vec<ProviderTrial>,
+    /// 最近分配的试用序号（结束的试用会被移除，序号保证 ID 不会复用）
+    #[serde(default)]
+    last_seq: u64,
+}
+
+impl TrialStore {
+    /// 分配试用 ID：同一秒内取消后重新开始的试用也不会与之前的 ID 重复
+    fn allocate_id(&mut self, tool_id: &str, started_at: i64) -> String {
+        self.last_seq += 1;
+        format!("{}-{}-{}", tool_id, started_at, self.last_seq)
+    }
+}
+
+/// 供应商试用管理器
+pub struct ProviderTrialManager {
+    data_manager: DataManager,
+    /// 试用记录文件（`None` 表示无法确定配置目录）
+    store_path: Option<PathBuf>,
+    /// 写入记录文件（`None` 时使用默认位置）
+    ledger_path: Option<PathBuf>,
+    /// 进程内锁，保证读-改-写串行
+    lock: Mutex<()>,
+    events: broadcast::Sender<ProviderTrialEvent>,
+}
+
+impl ProviderTrialManager {
+    fn new() -> Self {
+        let store_path = match config_dir() {
+            Ok(dir) => Some(dir.join("provider_trials.json")),
+            Err(e) => {
+                tracing::warn!(error = %e, "获取配置目录失败，供应商试用不可用");
+                None
+            }
+        };
+        Self::with_paths(store_path, None)
+    }
+
+    /// 使用指定文件保存试用记录与写入记录（测试用）
+    pub fn at(store_path: PathBuf, ledger_path: PathBuf) -> Self {
+        Self::with_paths(Some(store_path), Some(ledger_path))
+    }
+
+    fn with_paths(store_path: Option<PathBuf>, ledger_path: Option<PathBuf>) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self {
+            data_manager: DataManager::new(),
+            store_path,
+            ledger_path,
+            lock: Mutex::new(()),
+            events,
+        }
+    }
+
+    /// 订阅试用事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ProviderTrialEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: &ProviderTrialEvent) {
+        // 没有订阅者时 send 返回错误，可以忽略
+        let _ = self.events.send(event.clone());
+    }
+
+    fn store_path(&self) -> Result<&Path> {
+        self.store_path
+            .as_deref()
+            .ok_or_else(|| anyhow!("获取配置目录失败"))
+    }
+
+    fn ledger(&self) -> Result<WriteLedger> {
+        match &self.ledger_path {
+            Some(path) => Ok(WriteLedger::at(path.clone())),
+            None => WriteLedger::new(),
+        }
+    }
+
+    fn load(&self) -> Result<TrialStore> {
+        let path = self.store_path()?;
+        if !path.exists() {
+            return Ok(TrialStore::default());
+        }
+        let value = self.data_manager.json_uncached().read(path)?;
+        serde_json::from_value(value).context("解析 provider_trials.json 失败")
+    }
+
+    fn save(&self, store: &TrialStore) -> Result<()> {
+        let value = serde_json::to_value(store).context("序列化试用记录失败")?;
+        self.data_manager
+            .json_uncached()
+            .write(self.store_path()?, &value)
+            .context("保存 provider_trials.json 失败")
+    }
+
+    /// 进行中的试用
+    pub fn active_trials(&self) -> Result<Vec<ProviderTrial>> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.load()?.trials)
+    }
+
+    /// 开始试用：记录原始配置后应用供应商，`minutes` 分钟后自动恢复
+    ///
+    /// 同一工具同时只能有一个试用；应用失败时恢复原始配置并返回错误。
+    pub fn start(
+        &self,
+        manager: &ProfileManager,
+        tool: &Tool,
+        provider: &Provider,
+        minutes: u32,
+    ) -> Result<ProviderTrial> {
+        if minutes == 0 || minutes > MAX_TRIAL_MINUTES {
+            return Err(anyhow!(
+                "试用时长必须在 1 到 {} 分钟之间",
+                MAX_TRIAL_MINUTES
+            ));
+        }
+
+        let _guard = self.lock.lock().unwrap();
+        let mut store = self.load()?;
+        if store.trials.iter().any(|trial| trial.tool_id == tool.id) {
+            return Err(anyhow!("{} 已有进行中的供应商试用", tool.name));
+        }
+
+        let mut paths = manager.store_files();
+        paths.extend(native_config_files(tool));
+        let snapshot = ConfigSnapshot::capture(&paths)?;
+        let originals = snapshot
+            .files()
+            .iter()
+            .map(|(path, content)| {
+                let original = content
+                    .clone()
+                    .map(String::from_utf8)
+                    .transpose()
+                    .map_err(|_| anyhow!("配置文件不是 UTF-8 文本: {path:?}"))?;
+                Ok((path.clone(), original))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Err(e) =
+            apply_provider_profile(manager, &tool.id, provider, &SetupOptions::default())
+        {
+            if let Err(restore_err) = snapshot.restore() {
+                tracing::error!(tool = %tool.id, error = ?restore_err, "试用配置应用失败后恢复失败");
+            }
+            return Err(e);
+        }
+
+        let files = originals
+            .into_iter()
+            .map(|(path, original)| {
+                let applied_sha256 = current_sha256(&path)?;
+                Ok(TrialFile {
+                    path,
+                    original,
+                    applied_sha256,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let started_at = chrono::Utc::now().timestamp();
+        let trial = ProviderTrial {
+            id: store.allocate_id(&tool.id, started_at),
+            tool_id: tool.id.clone(),
+            provider_id: provider.id.clone(),
+            started_at,
+            ends_at: started_at + i64::from(minutes) * 60,
+            warned: false,
+            retry_at: None,
+            files,
+        };
+        store.trials.push(trial.clone());
+        if let Err(e) = self.save(&store) {
+            // 没有记录就无法按时恢复，直接撤销本次应用
+            if let Err(restore_err) = snapshot.restore() {
+                tracing::error!(tool = %tool.id, error = ?restore_err, "保存试用记录失败后恢复失败");
+            }
+            return Err(e);
+        }
+
+        tracing::info!(
+            tool = %tool.id,
+            provider = %provider.id,
+            minutes,
+            "已开始供应商试用"
+        );
+        self.emit(&ProviderTrialEvent::new(
+            TrialEventKind::Applied,
+            &trial,
+            format!("已开始试用供应商 {}（{} 分钟）", provider.name, minutes),
+        ));
+        Ok(trial)
+    }
+
+    /// 提前结束试用：`keep_config` 为 true 时保留当前配置，否则立即恢复
+    pub fn cancel(&self, trial_id: &str, keep_config: bool) -> Result<ProviderTrialEvent> {
+        let _guard = self.lock.lock().unwrap();
+        let mut store = self.load()?;
+        let index = store
+            .trials
+            .iter()
+            .position(|trial| trial.id == trial_id)
+            .ok_or_else(|| anyhow!("试用不存在: {}", trial_id))?;
+
+        let event = if keep_config {
+            let trial = &store.trials[index];
+            tracing::info!(tool = %trial.tool_id, provider = %trial.provider_id, "已结束试用并保留配置");
+            ProviderTrialEvent::new(TrialEventKind::Kept, trial, "已结束试用，保留当前配置")
+        } else {
+            self.revert(&store.trials[index])
+        };
+        if event.kind == TrialEventKind::RevertFailed {
+            // 保留记录并视为已到期，由后台检查继续重试
+            let now = chrono::Utc::now().timestamp();
+            let trial = &mut store.trials[index];
+            trial.ends_at = trial.ends_at.min(now);
+            trial.retry_at = Some(now + REVERT_RETRY_SECS);
+        } else {
+            store.trials.remove(index);
+        }
+        self.save(&store)?;
+        self.emit(&event);
+        Ok(event)
+    }
+
+    /// 处理到期与即将到期的试用，返回本次产生的事件
+    pub fn tick(&self, now: i64) -> Result<Vec<ProviderTrialEvent>> {
+        let _guard = self.lock.lock().unwrap();
+        let mut store = self.load()?;
+        let mut events = Vec::new();
+        let mut remaining = Vec::with_capacity(store.trials.len());
+        for mut trial in store.trials.drain(..) {
+            if trial.ends_at > now {
+                if !trial.warned && trial.ends_at - now <= WARNING_BEFORE_SECS {
+                    trial.warned = true;
+                    let minutes = ((trial.ends_at - now) + 59) / 60;
+                    events.push(ProviderTrialEvent::new(
+                        TrialEventKind::Warning,
+                        &trial,
+                        format!("供应商试用将在 {minutes} 分钟后结束并恢复原配置"),
+                    ));
+                }
+                remaining.push(trial);
+                continue;
+            }
+            if trial.retry_at.is_some_and(|retry_at| retry_at > now) {
+                remaining.push(trial);
+                continue;
+            }
+
+            // 先恢复再移除记录：恢复失败（或恢复途中退出）时试用仍在记录中，之后会重试
+            let event = self.revert(&trial);
+            if event.kind == TrialEventKind::RevertFailed {
+                trial.retry_at = Some(now + REVERT_RETRY_SECS);
+                remaining.push(trial);
+            }
+            events.push(event);
+        }
+        store.trials = remaining;
+        if !events.is_empty() {
+            self.save(&store)?;
+        }
+
+        for event in &events {
+            self.emit(event);
+        }
+        Ok(events)
+    }
+
+    /// 后台循环：定期处理到期试用（不会返回）
+    pub async fn run(&'static self) {
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) = self.tick(now) {
+                tracing::warn!(error = ?e, "检查供应商试用失败");
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+
+    /// 恢复试用前的配置；有文件在试用期间被修改时跳过
+    fn revert(&self, trial: &ProviderTrial) -> ProviderTrialEvent {
+        let modified = match self.modified_files(trial) {
+            Ok(modified) => modified,
+            Err(e) => {
+                tracing::error!(tool = %trial.tool_id, error = ?e, "检查试用配置失败");
+                return ProviderTrialEvent::new(
+                    TrialEventKind::RevertFailed,
+                    trial,
+                    format!("检查配置失败，未恢复: {e:#}"),
+                );
+            }
+        };
+        if !modified.is_empty() {
+            tracing::warn!(
+                tool = %trial.tool_id,
+                files = ?modified,
+                "试用期间配置被修改，跳过恢复"
+            );
+            return ProviderTrialEvent {
+                modified_files: modified,
+                ..ProviderTrialEvent::new(
+                    TrialEventKind::RevertSkipped,
+                    trial,
+                    "试用期间配置被修改，未自动恢复，请手动检查",
+                )
+            };
+        }
+
+        for file in &trial.files {
+            if let Err(e) = restore_file(&file.path, file.original.as_deref().map(str::as_bytes)) {
+                tracing::error!(tool = %trial.tool_id, error = ?e, "恢复试用前配置失败");
+                return ProviderTrialEvent::new(
+                    TrialEventKind::RevertFailed,
+                    trial,
+                    format!("恢复配置失败: {e:#}"),
+                );
+            }
+            if file.original.is_some() {
+                // 记录失败只记日志，不影响恢复本身
+                if let Err(e) = self
+                    .ledger()
+                    .and_then(|ledger| ledger.record(&file.path, REVERT_ACTION))
+                {
+                    tracing::warn!(path = ?file.path, error = %e, "记录配置写入失败");
+                }
+            }
+        }
+        tracing::info!(tool = %trial.tool_id, provider = %trial.provider_id, "供应商试用结束，已恢复配置");
+        ProviderTrialEvent::new(TrialEventKind::Reverted, trial, "试用结束，已恢复原配置")
+    }
+
+    /// 试用期间被修改的文件：当前内容与应用试用配置后的哈希不一致
+    /// （无论是手动编辑，还是在 DuckCoding 中切换了配置）
+    fn modified_files(&self, trial: &ProviderTrial) -> Result<Vec<String>> {
+        let ledger = self.ledger()?;
+        let mut modified = Vec::new();
+        for file in &trial.files {
+            // 上次恢复中途失败时已写回的文件不算修改
+            if is_restored(file) {
+                continue;
+            }
+            let provenance = ledger.provenance(&file.path)?;
+            if provenance.current_sha256 != file.applied_sha256 {
+                if provenance.status == ProvenanceStatus::ModifiedExternally {
+                    tracing::debug!(path = ?file.path, "试用期间配置被外部修改");
+                }
+                modified.push(file.path.to_string_lossy().into_owned());
+            }
+        }
+        Ok(modified)
+    }
+}
+
+/// 文件是否已是试用前的内容
+fn is_restored(file: &TrialFile) -> bool {
+    match &file.original {
+        Some(original) => {
+            std::fs::read(&file.path).is_ok_and(|content| content == original.as_bytes())
+        }
+        None => !file.path.exists(),
+    }
+}
+
+fn current_sha256(path: &Path) -> Result<Option<String>> {
+    path.exists().then(|| file_checksum(path)).transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn setup(dir: &TempDir, ends_at: i64) -> (ProviderTrialManager, PathBuf, PathBuf) {
+        let manager = ProviderTrialManager::at(
+            dir.path().join("provider_trials.json"),
+            dir.path().join("write_ledger.json"),
+        );
+        let settings = dir.path().join("settings.json");
+        let created = dir.path().join("auth.json");
+        std::fs::write(&settings, "{\"trial\":true}").unwrap();
+        std::fs::write(&created, "{}").unwrap();
+
+        let trial = ProviderTrial {
+            id: "t1".to_string(),
+            tool_id: "codex".to_string(),
+            provider_id: "duckcoding".to_string(),
+            started_at: 0,
+            ends_at,
+            warned: false,
+            retry_at: None,
+            files: vec![
+                TrialFile {
+                    path: settings.clone(),
+                    original: Some("{\"old\":true}".to_string()),
+                    applied_sha256: current_sha256(&settings).unwrap(),
+                },
+                TrialFile {
+                    path: created.clone(),
+                    original: None,
+                    applied_sha256: current_sha256(&created).unwrap(),
+                },
+            ],
+        };
+        manager
+            .save(&TrialStore {
+                trials: vec![trial],
+                ..Default::default()
+            })
+            .unwrap();
+        (manager, settings, created)
+    }
+
+    #[test]
+    fn test_allocated_ids_are_unique() {
+        let mut store = TrialStore::default();
+        let first = store.allocate_id("codex", 1_000);
+        let second = store.allocate_id("codex", 1_000);
+        assert_ne!(first, second);
+        assert!(second.starts_with("codex-1000-"));
+
+        // 序号随记录保存，重启后继续递增
+        let restored: TrialStore =
+            serde_json::from_str(&serde_json::to_string(&store).unwrap()).unwrap();
+        assert_eq!(restored.last_seq, 2);
+    }
+
+    #[test]
+    fn test_tick_warns_then_reverts() {
+        let dir = TempDir::new().unwrap();
+        let (manager, settings, created) = setup(&dir, 1_000);
+
+        assert!(manager.tick(0).unwrap().is_empty());
+
+        let events = manager.tick(1_000 - WARNING_BEFORE_SECS).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].kind, TrialEventKind::Warning);
+        // 只提醒一次
+        assert!(manager.tick(1_000 - 60).unwrap().is_empty());
+
+        // 关闭期间已到期：下一次检查时补做恢复
+        let events = manager.tick(5_000).unwrap();
+        assert_eq!(events[0].kind, TrialEventKind::Reverted);
+        assert_eq!(
+            std::fs::read_to_string(&settings).unwrap(),
+            "{\"old\":true}"
+        );
+        assert!(!created.exists());
+        assert!(manager.active_trials().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_revert_skipped_when_modified_during_trial() {
+        let dir = TempDir::new().unwrap();
+        let (manager, settings, _) = setup(&dir, 1_000);
+        std::fs::write(&settings, "{\"manual\":true}").unwrap();
+
+        let events = manager.tick(1_000).unwrap();
+        assert_eq!(events[0].kind, TrialEventKind::RevertSkipped);
+        assert_eq!(
+            events[0].modified_files,
+            vec![settings.to_string_lossy().into_owned()]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&settings).unwrap(),
+            "{\"manual\":true}"
+        );
+        assert!(manager.active_trials().unwrap().is_empty());
+    }
+
+    /// 给 t1 追加一个无法写回的文件（父路径是普通文件），返回阻挡用的文件
+    fn add_blocked_file(dir: &TempDir, manager: &ProviderTrialManager) -> (PathBuf, PathBuf) {
+        let blocker = dir.path().join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let blocked = blocker.join("config.toml");
+        let mut store = manager.load().unwrap();
+        store.trials[0].files.push(TrialFile {
+            path: blocked.clone(),
+            original: Some("model = \"old\"".to_string()),
+            applied_sha256: None,
+        });
+        manager.save(&store).unwrap();
+        (blocker, blocked)
+    }
+
+    #[test]
+    fn test_failed_revert_is_kept_and_retried() {
+        let dir = TempDir::new().unwrap();
+        let (manager, settings, created) = setup(&dir, 1_000);
+        let (blocker, blocked) = add_blocked_file(&dir, &manager);
+
+        let events = manager.tick(1_000).unwrap();
+        assert_eq!(events[0].kind, TrialEventKind::RevertFailed);
+        let trials = manager.active_trials().unwrap();
+        assert_eq!(trials.len(), 1);
+        assert_eq!(trials[0].retry_at, Some(1_000 + REVERT_RETRY_SECS));
+        // 等待重试间隔
+        assert!(manager.tick(1_000 + 1).unwrap().is_empty());
+
+        // 已写回的文件不算试用期间的修改，重试时完成剩余恢复
+        std::fs::remove_file(&blocker).unwrap();
+        let events = manager.tick(1_000 + REVERT_RETRY_SECS).unwrap();
+        assert_eq!(events[0].kind, TrialEventKind::Reverted);
+        assert_eq!(
+            std::fs::read_to_string(&settings).unwrap(),
+            "{\"old\":true}"
+        );
+        assert!(!created.exists());
+        assert_eq!(
+            std::fs::read_to_string(&blocked).unwrap(),
+            "model = \"old\""
+        );
+        assert!(manager.active_trials().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_cancel_is_retried_by_tick() {
+        let dir = TempDir::new().unwrap();
+        let (manager, _, _) = setup(&dir, i64::MAX);
+        let (blocker, blocked) = add_blocked_file(&dir, &manager);
+
+        let event = manager.cancel("t1", false).unwrap();
+        assert_eq!(event.kind, TrialEventKind::RevertFailed);
+        let trial = manager.active_trials().unwrap().remove(0);
+        let retry_at = trial.retry_at.unwrap();
+
+        std::fs::remove_file(&blocker).unwrap();
+        let events = manager.tick(retry_at).unwrap();
+        assert_eq!(events[0].kind, TrialEventKind::Reverted);
+        assert!(blocked.exists());
+        assert!(manager.active_trials().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_keeps_or_reverts() {
+        let dir = TempDir::new().unwrap();
+        let (manager, settings, _) = setup(&dir, 1_000);
+        let event = manager.cancel("t1", true).unwrap();
+        assert_eq!(event.kind, TrialEventKind::Kept);
+        assert_eq!(
+            std::fs::read_to_string(&settings).unwrap(),
+            "{\"trial\":true}"
+        );
+        assert!(manager.cancel("t1", false).is_err());
+    }
+}
@@ -0,0 +1,376 @@
+//! 代理请求/响应调试捕获
+//!
+//! 默认关闭，开启后在限定时间内将最近 K 条请求/响应保存在内存中：
+//! - 敏感 headers 脱敏（Authorization、x-api-key 等）
+//! - 请求体/响应体按上限截断
+//! - SSE 流式响应在流结束时拼接为完整内容
+//!
+//! 捕获内容只保存在内存，仅在用户显式导出时写入磁盘，导出同样经过脱敏。
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use hyper::HeaderMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::data::DataManager;
+
+/// 默认保留的最大捕获条数
+pub const DEFAULT_MAX_EXCHANGES: usize = 50;
+
+/// 默认单个 body 的最大捕获字节数
+pub const DEFAULT_MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// 需要脱敏的 header 名称（小写）
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "api-key",
+    "x-goog-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+const REDACTED: &str = "***REDACTED***";
+
+/// 全局捕获存储
+pub static CAPTURE_STORE: Lazy<CaptureStore> = Lazy::new(CaptureStore::new);
+
+/// 单条捕获的请求/响应
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedExchange {
+    pub id: u64,
+    pub tool_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    /// 所属代理会话（见 `sessions` 模块）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: String,
+    pub request_truncated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: String,
+    pub response_truncated: bool,
+    pub streaming: bool,
+    pub duration_ms: u64,
+}
+
+/// 捕获状态快照（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureSnapshot {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub max_exchanges: usize,
+    pub max_body_bytes: usize,
+    pub exchanges: Vec<CapturedExchange>,
+}
+
+struct CaptureState {
+    expires_at: Option<DateTime<Utc>>,
+    max_exchanges: usize,
+    max_body_bytes: usize,
+    next_id: u64,
+    exchanges: VecDeque<CapturedExchange>,
+}
+
+/// 捕获存储（线程安全）
+pub struct CaptureStore {
+    state: Mutex<CaptureState>,
+}
+
+impl CaptureStore {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(CaptureState {
+                expires_at: None,
+                max_exchanges: DEFAULT_MAX_EXCHANGES,
+                max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+                next_id: 1,
+                exchanges: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// 开启捕获，`minutes` 分钟后自动失效
+    pub fn enable(
+        &self,
+        minutes: u32,
+        max_exchanges: Option<usize>,
+        max_body_bytes: Option<usize>,
+    ) -> DateTime<Utc> {
+        let mut state = self.state.lock().unwrap();
+        let expires_at = Utc::now() + ChronoDuration::minutes(i64::from(minutes.max(1)));
+        state.expires_at = Some(expires_at);
+        if let Some(max) = max_exchanges {
+            state.max_exchanges = max.max(1);
+        }
+        if let Some(max) = max_body_bytes {
+            state.max_body_bytes = max.max(1);
+        }
+        while state.exchanges.len() > state.max_exchanges {
+            state.exchanges.pop_front();
+        }
+        tracing::info!(expires_at = %expires_at, "代理调试捕获已开启");
+        expires_at
+    }
+
+    /// 立即关闭捕获（保留已捕获内容）
+    pub fn disable(&self) {
+        self.state.lock().unwrap().expires_at = None;
+    }
+
+    /// 当前是否处于捕获窗口内
+    pub fn is_enabled(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .expires_at
+            .is_some_and(|at| Utc::now() < at)
+    }
+
+    /// 清空已捕获内容
+    pub fn clear(&self) {
+        self.state.lock().unwrap().exchanges.clear();
+    }
+
+    /// 获取当前捕获快照
+    pub fn snapshot(&self) -> CaptureSnapshot {
+        let state = self.state.lock().unwrap();
+        let enabled = state.expires_at.is_some_and(|at| Utc::now() < at);
+        CaptureSnapshot {
+            enabled,
+            expires_at: state.expires_at.filter(|_| enabled),
+            max_exchanges: state.max_exchanges,
+            max_body_bytes: state.max_body_bytes,
+            exchanges: state.exchanges.iter().cloned().collect(),
+        }
+    }
+
+    /// 导出捕获内容到指定 JSON 文件（内容已在捕获时脱敏）
+    pub fn export(&self, path: &Path) -> anyhow::Result<usize> {
+        let exchanges = self.snapshot().exchanges;
+        let count = exchanges.len();
+        let value = serde_json::to_value(&exchanges)?;
+        DataManager::new().json_uncached().write(path, &value)?;
+        tracing::info!(path = ?path, count, "已导出代理调试捕获");
+        Ok(count)
+    }
+
+    /// 开始记录一次请求，未开启捕获时返回 `None`
+    pub fn begin(
+        &'static self,
+        tool_id: &str,
+        method: &str,
+        path: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Option<ExchangeRecorder> {
+        let max_body_bytes = {
+            let mut state = self.state.lock().unwrap();
+            match state.expires_at {
+                Some(at) if Utc::now() < at => {}
+                Some(_) => {
+                    // 已过期，自动关闭
+                    state.expires_at = None;
+                    return None;
+                }
+                None => return None,
+            }
+            state.max_body_bytes
+        };
+
+        let (request_body, request_truncated) = truncate_body(body, max_body_bytes);
+        Some(ExchangeRecorder {
+            store: self,
+            started_at: Instant::now(),
+            max_body_bytes,
+            exchange: Some(CapturedExchange {
+                id: 0,
+                tool_id: tool_id.to_string(),
+                timestamp: Utc::now(),
+                method: method.to_string(),
+                path: path.to_string(),
+                session_id: None,
+                request_headers: redact_headers(headers),
+                request_body,
+                request_truncated,
+                status: None,
+                response_headers: Vec::new(),
+                response_body: String::new(),
+                response_truncated: false,
+                streaming: false,
+                duration_ms: 0,
+            }),
+            response_buffer: Vec::new(),
+        })
+    }
+
+    fn push(&self, mut exchange: CapturedExchange) {
+        let mut state = self.state.lock().unwrap();
+        exchange.id = state.next_id;
+        state.next_id += 1;
+        state.exchanges.push_back(exchange);
+        while state.exchanges.len() > state.max_exchanges {
+            state.exchanges.pop_front();
+        }
+    }
+}
+
+impl Default for CaptureStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 单次请求的捕获记录器
+///
+/// 在 drop 时写入存储，流式响应在流结束（或客户端断开）时自动完成记录。
+pub struct ExchangeRecorder {
+    store: &'static CaptureStore,
+    started_at: Instant,
+    max_body_bytes: usize,
+    exchange: Option<CapturedExchange>,
+    response_buffer: Vec<u8>,
+}
+
+impl ExchangeRecorder {
+    /// 记录所属会话
+    pub fn set_session_id(&mut self, session_id: &str) {
+        if let Some(exchange) = self.exchange.as_mut() {
+            exchange.session_id = Some(session_id.to_string());
+        }
+    }
+
+    /// 记录响应状态和 headers
+    pub fn set_response_head(&mut self, status: u16, headers: &HeaderMap, streaming: bool) {
+        if let Some(exchange) = self.exchange.as_mut() {
+            exchange.status = Some(status);
+            exchange.response_headers = redact_headers(headers);
+            exchange.streaming = streaming;
+        }
+    }
+
+    /// 追加响应体片段（超出上限的部分丢弃）
+    pub fn append_response(&mut self, chunk: &[u8]) {
+        let remaining = self
+            .max_body_bytes
+            .saturating_sub(self.response_buffer.len());
+        if chunk.len() > remaining {
+            if let Some(exchange) = self.exchange.as_mut() {
+                exchange.response_truncated = true;
+            }
+        }
+        self.response_buffer
+            .extend_from_slice(&chunk[..chunk.len().min(remaining)]);
+    }
+}
+
+impl Drop for ExchangeRecorder {
+    fn drop(&mut self) {
+        if let Some(mut exchange) = self.exchange.take() {
+            exchange.response_body = String::from_utf8_lossy(&self.response_buffer).into_owned();
+            exchange.duration_ms = self.started_at.elapsed().as_millis() as u64;
+            self.store.push(exchange);
+        }
+    }
+}
+
+/// 脱敏 headers
+fn redact_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let name = name.as_str().to_string();
+            let value = if SENSITIVE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+/// 截断 body 并转换为字符串
+fn truncate_body(body: &[u8], max: usize) -> (String, bool) {
+    let truncated = body.len() > max;
+    let slice = &body[..body.len().min(max)];
+    (String::from_utf8_lossy(slice).into_owned(), truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_STORE: Lazy<CaptureStore> = Lazy::new(CaptureStore::new);
+
+    #[test]
+    fn test_redact_sensitive_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer sk-secret".parse().unwrap());
+        headers.insert("x-api-key", "sk-secret".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+
+        let redacted = redact_headers(&headers);
+        for (name, value) in &redacted {
+            if name == "content-type" {
+                assert_eq!(value, "application/json");
+            } else {
+                assert_eq!(value, REDACTED);
+            }
+        }
+    }
+
+    #[test]
+    fn test_capture_lifecycle() {
+        let store: &'static CaptureStore = &TEST_STORE;
+        assert!(store
+            .begin(
+                "claude-code",
+                "POST",
+                "/v1/messages",
+                &HeaderMap::new(),
+                b"{}"
+            )
+            .is_none());
+
+        store.enable(5, Some(2), Some(8));
+        for i in 0..3 {
+            let mut recorder = store
+                .begin(
+                    "claude-code",
+                    "POST",
+                    "/v1/messages",
+                    &HeaderMap::new(),
+                    b"0123456789",
+                )
+                .unwrap();
+            recorder.set_response_head(200, &HeaderMap::new(), true);
+            recorder.append_response(format!("chunk{i}-").as_bytes());
+            recorder.append_response(b"tail");
+        }
+
+        let snapshot = store.snapshot();
+        assert!(snapshot.enabled);
+        assert_eq!(snapshot.exchanges.len(), 2);
+        let last = snapshot.exchanges.last().unwrap();
+        assert_eq!(last.request_body, "01234567");
+        assert!(last.request_truncated);
+        assert_eq!(last.response_body, "chunk2-t");
+        assert!(last.response_truncated);
+
+        store.clear();
+        store.disable();
+        assert!(store.snapshot().exchanges.is_empty());
+        assert!(!store.is_enabled());
+    }
+}
@@ -0,0 +1,361 @@
+//! 代理凭证注入
+//!
+//! 根据工具的 `CredentialInjectionConfig` 判断请求是否需要注入凭证，
+//! 并从绑定的供应商中解析出真实的 Base URL 与 API Key。
+//! 请求携带 `X-DuckCoding-Provider` 时，仅该请求改用指定供应商的凭证。
+
+use hyper::HeaderMap;
+use std::collections::BTreeMap;
+
+use crate::models::provider::Provider;
+use crate::models::proxy_config::{CredentialInjectionConfig, ToolProxyConfig};
+
+/// 单次请求指定供应商的请求头（转发前移除）
+pub const PROVIDER_OVERRIDE_HEADER: &str = "x-duckcoding-provider";
+
+/// 注入使用的上游凭证
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedCredential {
+    pub provider_id: String,
+    pub base_url: String,
+    pub api_key: String,
+    /// 供应商的自定义请求头（与凭证一并附加到上游请求）
+    pub headers: BTreeMap<String, String>,
+}
+
+/// 凭证注入判定结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InjectionOutcome {
+    /// 未启用或请求不匹配，按原有配置转发
+    NotApplicable,
+    /// 使用绑定供应商的凭证
+    Injected(InjectedCredential),
+    /// 配置错误（如未绑定供应商），需拒绝请求而不是转发占位密钥
+    Misconfigured(String),
+}
+
+/// 单次请求供应商覆盖判定结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderOverride {
+    /// 请求未携带覆盖请求头
+    Absent,
+    /// 使用请求头指定供应商的凭证
+    Override(InjectedCredential),
+    /// 指定的供应商不存在或不可用（返回 400）
+    Invalid(String),
+}
+
+/// 判断请求是否命中注入规则
+pub fn matches_request(
+    injection: &CredentialInjectionConfig,
+    tool_id: &str,
+    path: &str,
+    headers: &HeaderMap,
+) -> bool {
+    let path_matched = if injection.path_prefixes.is_empty() {
+        CredentialInjectionConfig::default_path_prefixes(tool_id)
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    } else {
+        injection
+            .path_prefixes
+            .iter()
+            .any(|prefix| path.starts_with(prefix.as_str()))
+    };
+
+    let header_matched = injection.client_header.as_ref().is_some_and(|rule| {
+        headers
+            .get(rule.name.as_str())
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|actual| match &rule.value {
+                Some(expected) => actual.eq_ignore_ascii_case(expected),
+                None => true,
+            })
+    });
+
+    path_matched || header_matched
+}
+
+/// 解析请求应使用的注入凭证
+///
+/// `providers` 仅在注入启用时才会被读取，调用方可按需懒加载。
+pub fn resolve_injection<F>(
+    config: &ToolProxyConfig,
+    tool_id: &str,
+    path: &str,
+    headers: &HeaderMap,
+    load_providers: F,
+) -> InjectionOutcome
+where
+    F: FnOnce() -> anyhow::Result<Vec<Provider>>,
+{
+    let injection = &config.credential_injection;
+    if !injection.enabled || !matches_request(injection, tool_id, path, headers) {
+        return InjectionOutcome::NotApplicable;
+    }
+
+    let Some(provider_id) = injection.provider_id.as_deref() else {
+        return InjectionOutcome::Misconfigured(format!(
+            "{tool_id} 已启用凭证注入，但未绑定供应商"
+        ));
+    };
+
+    let providers = match load_providers() {
+        Ok(providers) => providers,
+        Err(e) => return InjectionOutcome::Misconfigured(format!("读取供应商配置失败: {e}")),
+    };
+
+    let Some(provider) = providers.into_iter().find(|p| p.id == provider_id) else {
+        return InjectionOutcome::Misconfigured(format!("绑定的供应商不存在: {provider_id}"));
+    };
+
+    match credential_from_provider(provider) {
+        Ok(credential) => InjectionOutcome::Injected(credential),
+        Err(reason) => InjectionOutcome::Misconfigured(reason),
+    }
+}
+
+/// 解析 `X-DuckCoding-Provider` 请求头指定的供应商凭证
+///
+/// 供应商必须存在且已配置 API Key（未配置视为未启用代理调用）。
+pub fn resolve_provider_override<F>(headers: &HeaderMap, load_providers: F) -> ProviderOverride
+where
+    F: FnOnce() -> anyhow::Result<Vec<Provider>>,
+{
+    let Some(value) = headers.get(PROVIDER_OVERRIDE_HEADER) else {
+        return ProviderOverride::Absent;
+    };
+
+    let provider_id = match value.to_str().map(str::trim) {
+        Ok(id) if !id.is_empty() => id,
+        _ => {
+            return ProviderOverride::Invalid(format!("{PROVIDER_OVERRIDE_HEADER} 请求头的值无效"))
+        }
+    };
+
+    let providers = match load_providers() {
+        Ok(providers) => providers,
+        Err(e) => return ProviderOverride::Invalid(format!("读取供应商配置失败: {e}")),
+    };
+
+    let Some(provider) = providers.into_iter().find(|p| p.id == provider_id) else {
+        return ProviderOverride::Invalid(format!("供应商不存在: {provider_id}"));
+    };
+
+    match credential_from_provider(provider) {
+        Ok(credential) => ProviderOverride::Override(credential),
+        Err(reason) => ProviderOverride::Invalid(format!("供应商 {provider_id} 不可用: {reason}")),
+    }
+}
+
+/// 从供应商配置中提取上游凭证
+pub fn credential_from_provider(provider: Provider) -> Result<InjectedCredential, String> {
+    let api_key = match provider.api_key.as_deref().map(str::trim) {
+        Some(key) if !key.is_empty() => key.to_string(),
+        _ => return Err(format!("供应商 {} 未配置 API Key", provider.name)),
+    };
+
+    let base_url = provider
+        .api_address
+        .clone()
+        .filter(|addr| !addr.trim().is_empty())
+        .unwrap_or(provider.website_url);
+
+    Ok(InjectedCredential {
+        provider_id: provider.id,
+        base_url: base_url.trim_end_matches('/').to_string(),
+        api_key,
+        headers: provider.custom_headers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::proxy_config::ClientHeaderMatch;
+
+    fn provider(id: &str, api_key: Option<&str>) -> Provider {
+        Provider {
+            id: id.to_string(),
+            name: id.to_string(),
+            website_url: "https://relay.example.com".to_string(),
+            api_address: Some("https://api.relay.example.com/".to_string()),
+            user_id: String::new(),
+            access_token: String::new(),
+            api_key: api_key.map(str::to_string),
+            balance_template: Default::default(),
+            custom_headers: Default::default(),
+            username: None,
+            is_default: false,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn config_with(injection: CredentialInjectionConfig) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(8787);
+        config.credential_injection = injection;
+        config
+    }
+
+    #[test]
+    fn test_disabled_injection_is_not_applicable() {
+        let config = ToolProxyConfig::new(8787);
+        let outcome = resolve_injection(
+            &config,
+            "claude-code",
+            "/v1/messages",
+            &HeaderMap::new(),
+            || panic!("不应加载供应商"),
+        );
+        assert_eq!(outcome, InjectionOutcome::NotApplicable);
+    }
+
+    #[test]
+    fn test_inject_bound_provider_credential() {
+        let config = config_with(CredentialInjectionConfig {
+            enabled: true,
+            provider_id: Some("relay".to_string()),
+            ..Default::default()
+        });
+
+        let outcome = resolve_injection(
+            &config,
+            "claude-code",
+            "/v1/messages",
+            &HeaderMap::new(),
+            || Ok(vec![provider("relay", Some("sk-real"))]),
+        );
+
+        assert_eq!(
+            outcome,
+            InjectionOutcome::Injected(InjectedCredential {
+                provider_id: "relay".to_string(),
+                base_url: "https://api.relay.example.com".to_string(),
+                api_key: "sk-real".to_string(),
+                headers: BTreeMap::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_binding_is_misconfigured() {
+        let config = config_with(CredentialInjectionConfig {
+            enabled: true,
+            ..Default::default()
+        });
+
+        let outcome = resolve_injection(
+            &config,
+            "claude-code",
+            "/v1/messages",
+            &HeaderMap::new(),
+            || Ok(vec![]),
+        );
+        assert!(matches!(outcome, InjectionOutcome::Misconfigured(_)));
+
+        let config = config_with(CredentialInjectionConfig {
+            enabled: true,
+            provider_id: Some("relay".to_string()),
+            ..Default::default()
+        });
+        let outcome = resolve_injection(
+            &config,
+            "claude-code",
+            "/v1/messages",
+            &HeaderMap::new(),
+            || Ok(vec![provider("relay", None)]),
+        );
+        assert!(matches!(outcome, InjectionOutcome::Misconfigured(_)));
+    }
+
+    #[test]
+    fn test_match_by_path_or_client_header() {
+        let injection = CredentialInjectionConfig {
+            enabled: true,
+            client_header: Some(ClientHeaderMatch {
+                name: "x-duckcoding-client".to_string(),
+                value: Some("claude-code".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let empty = HeaderMap::new();
+        assert!(matches_request(
+            &injection,
+            "claude-code",
+            "/v1/messages",
+            &empty
+        ));
+        assert!(!matches_request(
+            &injection,
+            "claude-code",
+            "/v1/chat/completions",
+            &empty
+        ));
+        assert!(matches_request(
+            &injection,
+            "codex",
+            "/v1/chat/completions",
+            &empty
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-duckcoding-client", "claude-code".parse().unwrap());
+        assert!(matches_request(
+            &injection,
+            "claude-code",
+            "/other",
+            &headers
+        ));
+    }
+
+    #[test]
+    fn test_provider_override_header() {
+        let providers = || {
+            Ok(vec![
+                provider("relay-a", Some("sk-a")),
+                provider("relay-b", None),
+            ])
+        };
+
+        assert_eq!(
+            resolve_provider_override(&HeaderMap::new(), || panic!("不应加载供应商")),
+            ProviderOverride::Absent
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(PROVIDER_OVERRIDE_HEADER, "relay-a".parse().unwrap());
+        match resolve_provider_override(&headers, providers) {
+            ProviderOverride::Override(credential) => {
+                assert_eq!(credential.provider_id, "relay-a");
+                assert_eq!(credential.api_key, "sk-a");
+            }
+            other => panic!("unexpected outcome: {other:?}"),
+        }
+
+        headers.insert(PROVIDER_OVERRIDE_HEADER, "relay-b".parse().unwrap());
+        assert!(matches!(
+            resolve_provider_override(&headers, providers),
+            ProviderOverride::Invalid(_)
+        ));
+
+        headers.insert(PROVIDER_OVERRIDE_HEADER, "missing".parse().unwrap());
+        assert!(matches!(
+            resolve_provider_override(&headers, providers),
+            ProviderOverride::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn test_credential_carries_custom_headers() {
+        let mut relay = provider("relay", Some("sk-real"));
+        relay.custom_headers = BTreeMap::from([("X-Org-Id".to_string(), "org-1".to_string())]);
+
+        let credential = credential_from_provider(relay).unwrap();
+        assert_eq!(
+            credential.headers.get("X-Org-Id").map(String::as_str),
+            Some("org-1")
+        );
+    }
+}
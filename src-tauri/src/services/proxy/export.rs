@@ -0,0 +1,272 @@
+//! 代理用量与访问日志导出
+//!
+//! - 用量：按天、供应商、端点汇总的请求量，导出为 CSV 或 JSON
+//! - 访问日志：从按天滚动的日志文件中提取「代理访问日志」记录，合并为单个文件
+//!
+//! 导出文件写入 `~/.duckcoding/exports/`，内容统一经过密钥脱敏。
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::metrics::DailyUsageRow;
+use crate::utils::config::config_dir;
+
+/// 访问日志记录的消息标识（与 proxy_instance 中的 tracing 消息一致）
+const ACCESS_LOG_MARKER: &str = "代理访问日志";
+
+/// 滚动日志文件名前缀（tracing_appender::rolling::daily）
+const LOG_FILE_PREFIX: &str = "duckcoding.";
+
+/// 脱敏占位符
+const REDACTED: &str = "***";
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// 导出日期范围（本地日期，含首尾）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl ExportRange {
+    pub fn validate(&self) -> Result<()> {
+        if self.from > self.to {
+            anyhow::bail!(
+                "导出范围无效：开始日期 {} 晚于结束日期 {}",
+                self.from,
+                self.to
+            );
+        }
+        Ok(())
+    }
+
+    fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.from && date <= self.to
+    }
+
+    fn file_suffix(&self) -> String {
+        format!("{}_{}", self.from, self.to)
+    }
+}
+
+/// 密钥模式：`sk-` 开头的 API Key、Bearer Token、`*_key` / `*token` 字段值
+static SECRET_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"sk-[A-Za-z0-9_\-]{8,}",
+        r"(?i)(bearer\s+)[A-Za-z0-9_\-\.=]+",
+        r#"(?i)("?(?:api_key|apikey|access_token|auth_token|x-api-key|authorization)"?\s*[:=]\s*"?)[^",\s}]+"#,
+    ]
+    .iter()
+    .map(|p| Regex::new(p).expect("脱敏正则无效"))
+    .collect()
+});
+
+/// 脱敏文本中的密钥
+pub fn redact_secrets(text: &str) -> String {
+    let mut output = text.to_string();
+    for (i, pattern) in SECRET_PATTERNS.iter().enumerate() {
+        output = if i == 0 {
+            pattern.replace_all(&output, REDACTED).into_owned()
+        } else {
+            pattern
+                .replace_all(&output, format!("${{1}}{REDACTED}"))
+                .into_owned()
+        };
+    }
+    output
+}
+
+/// 导出目录
+pub(crate) fn export_dir() -> Result<PathBuf> {
+    let dir = config_dir()
+        .map_err(|e| anyhow::anyhow!(e))?
+        .join("exports");
+    fs::create_dir_all(&dir).context("创建导出目录失败")?;
+    Ok(dir)
+}
+
+/// CSV 字段转义
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 渲染用量 CSV
+pub fn render_usage_csv(rows: &[DailyUsageRow]) -> String {
+    let mut csv = String::from("date,provider,endpoint,request_count,error_count,avg_ms\n");
+    for row in rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            row.date,
+            csv_field(&redact_secrets(&row.provider)),
+            csv_field(&row.endpoint),
+            row.request_count,
+            row.error_count,
+            row.avg_ms
+        ));
+    }
+    csv
+}
+
+/// 导出用量汇总，返回文件路径
+pub fn export_usage(
+    rows: &[DailyUsageRow],
+    range: ExportRange,
+    format: ExportFormat,
+) -> Result<PathBuf> {
+    range.validate()?;
+    let (content, ext) = match format {
+        ExportFormat::Csv => (render_usage_csv(rows), "csv"),
+        ExportFormat::Json => {
+            let json = serde_json::to_string_pretty(rows).context("序列化用量数据失败")?;
+            (redact_secrets(&json), "json")
+        }
+    };
+
+    let path = export_dir()?.join(format!("proxy-usage-{}.{ext}", range.file_suffix()));
+    fs::write(&path, content).with_context(|| format!("写入导出文件失败: {path:?}"))?;
+    Ok(path)
+}
+
+/// 列出范围内的滚动日志文件（按日期排序）
+fn rotated_log_files(log_dir: &Path, range: ExportRange) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<(NaiveDate, PathBuf)> = fs::read_dir(log_dir)
+        .with_context(|| format!("读取日志目录失败: {log_dir:?}"))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let date =
+                NaiveDate::parse_from_str(name.strip_prefix(LOG_FILE_PREFIX)?, "%Y-%m-%d").ok()?;
+            range.contains(date).then(|| (date, entry.path()))
+        })
+        .collect();
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// 从日志目录中提取访问日志并合并为单个文件，返回文件路径
+pub fn export_access_log(log_dir: &Path, range: ExportRange, json_lines: bool) -> Result<PathBuf> {
+    range.validate()?;
+    let ext = if json_lines { "jsonl" } else { "log" };
+    let path = export_dir()?.join(format!("proxy-access-{}.{ext}", range.file_suffix()));
+    write_access_log(log_dir, range, &path)?;
+    Ok(path)
+}
+
+fn write_access_log(log_dir: &Path, range: ExportRange, output: &Path) -> Result<usize> {
+    let mut writer =
+        fs::File::create(output).with_context(|| format!("创建导出文件失败: {output:?}"))?;
+    let mut written = 0;
+
+    for file in rotated_log_files(log_dir, range)? {
+        let reader = BufReader::new(
+            fs::File::open(&file).with_context(|| format!("读取日志文件失败: {file:?}"))?,
+        );
+        // 日志中可能混有非 UTF-8 内容，逐行容错
+        for line in reader.split(b'\n').map_while(|line| line.ok()) {
+            let line = String::from_utf8_lossy(&line);
+            if !line.contains(ACCESS_LOG_MARKER) {
+                continue;
+            }
+            writeln!(writer, "{}", redact_secrets(line.trim_end()))?;
+            written += 1;
+        }
+    }
+
+    writer.flush()?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let line = r#"{"api_key":"abc123","msg":"Bearer tok.en-1 and sk-abcdefghijkl"}"#;
+        let redacted = redact_secrets(line);
+        assert!(!redacted.contains("abc123"));
+        assert!(!redacted.contains("tok.en-1"));
+        assert!(!redacted.contains("sk-abcdefghijkl"));
+        assert!(redacted.contains("Bearer ***"));
+    }
+
+    #[test]
+    fn test_usage_csv() {
+        let rows = vec![DailyUsageRow {
+            date: "2024-01-01".to_string(),
+            provider: "relay,cn".to_string(),
+            endpoint: "messages".to_string(),
+            request_count: 3,
+            error_count: 1,
+            avg_ms: 120,
+        }];
+        let csv = render_usage_csv(&rows);
+        assert_eq!(
+            csv,
+            "date,provider,endpoint,request_count,error_count,avg_ms\n\
+             2024-01-01,\"relay,cn\",messages,3,1,120\n"
+        );
+    }
+
+    #[test]
+    fn test_access_log_spans_rotated_files() {
+        let dir = TempDir::new().unwrap();
+        let logs = dir.path();
+        fs::write(
+            logs.join("duckcoding.2024-01-01"),
+            "代理访问日志 day1 sk-abcdefghijkl\nother line\n",
+        )
+        .unwrap();
+        fs::write(logs.join("duckcoding.2024-01-02"), "代理访问日志 day2\n").unwrap();
+        fs::write(
+            logs.join("duckcoding.2024-01-05"),
+            "代理访问日志 out of range\n",
+        )
+        .unwrap();
+        fs::write(logs.join("unrelated.txt"), "代理访问日志 ignored\n").unwrap();
+
+        let range = ExportRange {
+            from: date("2024-01-01"),
+            to: date("2024-01-03"),
+        };
+        let output = dir.path().join("out.log");
+        let written = write_access_log(logs, range, &output).unwrap();
+        assert_eq!(written, 2);
+
+        let content = fs::read_to_string(&output).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines, vec!["代理访问日志 day1 ***", "代理访问日志 day2"]);
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        let range = ExportRange {
+            from: date("2024-01-02"),
+            to: date("2024-01-01"),
+        };
+        assert!(range.validate().is_err());
+    }
+}
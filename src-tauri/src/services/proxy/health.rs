@@ -0,0 +1,227 @@
+//! 代理自身提供的健康检查端点
+//!
+//! `GET /__duckcoding/health` 由代理直接响应，不经过端点白名单与本地密钥校验。
+//! `/__duckcoding` 为保留前缀，该前缀下的任何请求都不会被转发到上游。
+
+use bytes::Bytes;
+use hyper::{Method, Response, StatusCode};
+use serde::Serialize;
+use std::time::Duration;
+
+use super::metrics;
+use super::utils::body::{box_body, BoxBody};
+use crate::models::proxy_config::ToolProxyConfig;
+
+/// 保留路径前缀
+pub const RESERVED_PREFIX: &str = "/__duckcoding";
+
+/// 健康检查路径
+pub const HEALTH_PATH: &str = "/__duckcoding/health";
+
+/// 上游探测超时
+const UPSTREAM_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 健康检查响应
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// `ok` / `degraded`（上游探测失败）
+    pub status: &'static str,
+    pub uptime_s: u64,
+    pub active_provider: Option<String>,
+    pub version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream: Option<UpstreamProbe>,
+}
+
+/// 上游可达性探测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UpstreamProbe {
+    pub reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 是否为保留路径（永不转发）
+pub fn is_reserved_path(path: &str) -> bool {
+    path.strip_prefix(RESERVED_PREFIX)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// 当前生效的上游供应商标签
+pub fn active_provider(config: &ToolProxyConfig) -> Option<String> {
+    let injection = &config.credential_injection;
+    if injection.enabled {
+        if let Some(provider_id) = &injection.provider_id {
+            return Some(provider_id.clone());
+        }
+    }
+    config
+        .real_base_url
+        .as_deref()
+        .map(metrics::provider_label_from_url)
+}
+
+/// 查询参数中是否要求探测上游（`upstream=1` / `upstream=true`）
+fn wants_upstream_probe(query: Option<&str>) -> bool {
+    query.is_some_and(|q| {
+        q.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .any(|(k, v)| k == "upstream" && matches!(v, "1" | "true"))
+    })
+}
+
+/// 轻量探测上游是否可达（任何 HTTP 响应都视为可达）
+async fn probe_upstream(base_url: &str) -> UpstreamProbe {
+    let started_at = std::time::Instant::now();
+    let result = match reqwest::Client::builder()
+        .timeout(UPSTREAM_PROBE_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client.head(base_url).send().await,
+        Err(e) => Err(e),
+    };
+    let latency_ms = started_at.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(res) => UpstreamProbe {
+            reachable: true,
+            status: Some(res.status().as_u16()),
+            latency_ms,
+            error: None,
+        },
+        Err(e) => UpstreamProbe {
+            reachable: false,
+            status: None,
+            latency_ms,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// 处理保留前缀下的请求
+pub async fn respond(
+    method: &Method,
+    path: &str,
+    query: Option<&str>,
+    config: &ToolProxyConfig,
+    base_url: Option<&str>,
+    uptime: Duration,
+) -> Response<BoxBody> {
+    if path != HEALTH_PATH || method != Method::GET {
+        return json_response(
+            StatusCode::NOT_FOUND,
+            serde_json::json!({
+                "error": "NOT_FOUND",
+                "message": format!("未知的代理内部路径: {path}"),
+            })
+            .to_string(),
+        );
+    }
+
+    let upstream = if wants_upstream_probe(query) {
+        Some(match base_url {
+            Some(url) => probe_upstream(url).await,
+            None => UpstreamProbe {
+                reachable: false,
+                status: None,
+                latency_ms: 0,
+                error: Some("未配置上游地址".to_string()),
+            },
+        })
+    } else {
+        None
+    };
+
+    let report = HealthReport {
+        status: if upstream.as_ref().is_some_and(|u| !u.reachable) {
+            "degraded"
+        } else {
+            "ok"
+        },
+        uptime_s: uptime.as_secs(),
+        active_provider: active_provider(config),
+        version: env!("CARGO_PKG_VERSION"),
+        upstream,
+    };
+
+    json_response(
+        StatusCode::OK,
+        serde_json::to_string(&report).unwrap_or_default(),
+    )
+}
+
+fn json_response(status: StatusCode, body: String) -> Response<BoxBody> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .header("cache-control", "no-store")
+        .body(box_body(http_body_util::Full::new(Bytes::from(body))))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_path() {
+        assert!(is_reserved_path("/__duckcoding"));
+        assert!(is_reserved_path("/__duckcoding/health"));
+        assert!(is_reserved_path("/__duckcoding/anything/else"));
+        assert!(!is_reserved_path("/__duckcodingx"));
+        assert!(!is_reserved_path("/v1/__duckcoding/health"));
+    }
+
+    #[test]
+    fn test_upstream_query() {
+        assert!(wants_upstream_probe(Some("upstream=1")));
+        assert!(wants_upstream_probe(Some("a=b&upstream=true")));
+        assert!(!wants_upstream_probe(Some("upstream=0")));
+        assert!(!wants_upstream_probe(None));
+    }
+
+    #[test]
+    fn test_active_provider() {
+        let mut config = ToolProxyConfig::new(8787);
+        assert_eq!(active_provider(&config), None);
+
+        config.real_base_url = Some("https://api.anthropic.com".to_string());
+        assert_eq!(
+            active_provider(&config).as_deref(),
+            Some("api.anthropic.com")
+        );
+
+        config.credential_injection.enabled = true;
+        config.credential_injection.provider_id = Some("relay".to_string());
+        assert_eq!(active_provider(&config).as_deref(), Some("relay"));
+    }
+
+    #[tokio::test]
+    async fn test_health_response() {
+        let config = ToolProxyConfig::new(8787);
+        let res = respond(
+            &Method::GET,
+            HEALTH_PATH,
+            None,
+            &config,
+            None,
+            Duration::from_secs(42),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = respond(
+            &Method::POST,
+            HEALTH_PATH,
+            None,
+            &config,
+            None,
+            Duration::ZERO,
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
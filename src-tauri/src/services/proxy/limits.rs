@@ -0,0 +1,141 @@
+//! 代理并发限制
+//!
+//! 分别限制并发连接数与进行中的上游请求数，超出上限时立即返回 503 + Retry-After，
+//! 不做排队。上限每次从配置读取，修改配置后立即生效；流式响应在流结束时才释放名额。
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::models::proxy_config::ConcurrencyLimits;
+
+/// 当前使用情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ProxyUtilization {
+    pub active_connections: usize,
+    pub max_connections: usize,
+    pub in_flight_requests: usize,
+    pub max_in_flight_requests: usize,
+    /// 启动以来因超出上限被拒绝的次数
+    pub rejected: u64,
+}
+
+/// 并发计数器（单个代理实例共享）
+#[derive(Debug, Default)]
+pub struct ProxyLimiter {
+    connections: AtomicUsize,
+    in_flight: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+/// 占用的名额，drop 时释放
+#[derive(Debug)]
+pub struct LimitGuard {
+    limiter: Arc<ProxyLimiter>,
+    kind: LimitKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum LimitKind {
+    Connection,
+    InFlight,
+}
+
+impl ProxyLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn counter(&self, kind: LimitKind) -> &AtomicUsize {
+        match kind {
+            LimitKind::Connection => &self.connections,
+            LimitKind::InFlight => &self.in_flight,
+        }
+    }
+
+    fn try_acquire(self: &Arc<Self>, kind: LimitKind, max: usize) -> Option<LimitGuard> {
+        let counter = self.counter(kind);
+        // max 为 0 表示不限制
+        let acquired = counter
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+                (max == 0 || current < max).then_some(current + 1)
+            })
+            .is_ok();
+
+        if acquired {
+            Some(LimitGuard {
+                limiter: Arc::clone(self),
+                kind,
+            })
+        } else {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+
+    /// 尝试占用一个连接名额
+    pub fn try_acquire_connection(self: &Arc<Self>, max: usize) -> Option<LimitGuard> {
+        self.try_acquire(LimitKind::Connection, max)
+    }
+
+    /// 尝试占用一个上游请求名额
+    pub fn try_acquire_in_flight(self: &Arc<Self>, max: usize) -> Option<LimitGuard> {
+        self.try_acquire(LimitKind::InFlight, max)
+    }
+
+    /// 按当前配置生成使用情况快照
+    pub fn utilization(&self, limits: &ConcurrencyLimits) -> ProxyUtilization {
+        ProxyUtilization {
+            active_connections: self.connections.load(Ordering::Acquire),
+            max_connections: limits.max_connections,
+            in_flight_requests: self.in_flight.load(Ordering::Acquire),
+            max_in_flight_requests: limits.max_in_flight_requests,
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for LimitGuard {
+    fn drop(&mut self) {
+        self.limiter
+            .counter(self.kind)
+            .fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_limit_and_release() {
+        let limiter = Arc::new(ProxyLimiter::new());
+        let first = limiter.try_acquire_in_flight(2).unwrap();
+        let _second = limiter.try_acquire_in_flight(2).unwrap();
+        assert!(limiter.try_acquire_in_flight(2).is_none());
+
+        drop(first);
+        assert!(limiter.try_acquire_in_flight(2).is_some());
+
+        let limits = ConcurrencyLimits {
+            max_in_flight_requests: 2,
+            ..Default::default()
+        };
+        let utilization = limiter.utilization(&limits);
+        assert_eq!(utilization.in_flight_requests, 1);
+        assert_eq!(utilization.rejected, 1);
+    }
+
+    #[test]
+    fn test_zero_means_unlimited_and_limit_is_hot_reloadable() {
+        let limiter = Arc::new(ProxyLimiter::new());
+        let guards: Vec<_> = (0..10)
+            .map(|_| limiter.try_acquire_connection(0).unwrap())
+            .collect();
+
+        // 调低上限后新连接立即被拒绝，已有连接不受影响
+        assert!(limiter.try_acquire_connection(5).is_none());
+        drop(guards);
+        assert!(limiter.try_acquire_connection(5).is_some());
+    }
+}
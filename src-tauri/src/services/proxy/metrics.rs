@@ -0,0 +1,787 @@
+//! 透明代理请求指标
+//!
+//! 按「供应商 + 端点类别」维护固定分桶的延迟直方图：
+//! - 以 5 分钟为一个时间窗口，最多保留 24 小时（288 个窗口）
+//! - 每个窗口的序列数量有上限，超出部分归入 `other`，内存占用与流量无关
+//! - 定期持久化到 `~/.duckcoding/proxy_metrics.json`，重启后恢复当天数据
+//! - 另维护进程启动以来的累计计数，供 Prometheus 抓取（单调递增，不随窗口过期）
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::limits::ProxyUtilization;
+use super::usage::TokenUsage;
+use crate::data::{DataManager, Durability};
+use crate::utils::config::config_dir;
+
+/// 延迟分桶上界（毫秒），最后一个桶为溢出桶
+pub(super) const LATENCY_BUCKETS_MS: &[u64] = &[
+    50, 100, 200, 300, 500, 750, 1_000, 1_500, 2_000, 3_000, 5_000, 7_500, 10_000, 15_000, 20_000,
+    30_000, 60_000,
+];
+
+/// 时间窗口长度（秒）
+const WINDOW_SECS: i64 = 300;
+
+/// 最多保留的窗口数（24 小时）
+const MAX_WINDOWS: usize = 288;
+
+/// 每个窗口最多保留的序列数
+const MAX_SERIES_PER_WINDOW: usize = 64;
+
+/// 持久化间隔
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 超出序列上限时使用的归并标签
+const OVERFLOW_LABEL: &str = "other";
+
+/// 全局代理指标
+pub static PROXY_METRICS: Lazy<ProxyMetrics> = Lazy::new(ProxyMetrics::new);
+
+/// 端点类别
+pub fn endpoint_class(path: &str) -> &'static str {
+    if path.starts_with("/v1/messages") {
+        "messages"
+    } else if path.starts_with("/v1/chat/completions") {
+        "chat_completions"
+    } else if path.starts_with("/v1/responses") {
+        "responses"
+    } else if path.contains(":generateContent") || path.contains(":streamGenerateContent") {
+        "generate_content"
+    } else if path.starts_with("/v1/models") || path.starts_with("/v1beta/models") {
+        "models"
+    } else {
+        "other"
+    }
+}
+
+/// 查询时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MetricsRange {
+    #[default]
+    #[serde(rename = "1h")]
+    LastHour,
+    #[serde(rename = "6h")]
+    Last6Hours,
+    #[serde(rename = "24h")]
+    Last24Hours,
+}
+
+impl MetricsRange {
+    pub(super) fn seconds(self) -> i64 {
+        match self {
+            Self::LastHour => 3_600,
+            Self::Last6Hours => 6 * 3_600,
+            Self::Last24Hours => 24 * 3_600,
+        }
+    }
+}
+
+/// 固定分桶延迟直方图
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(super) struct LatencyHistogram {
+    pub(super) buckets: Vec<u64>,
+    pub(super) count: u64,
+    pub(super) errors: u64,
+    pub(super) sum_ms: u64,
+    pub(super) status_codes: BTreeMap<u16, u64>,
+    /// 上游响应中报告的 token 用量
+    #[serde(default)]
+    pub(super) input_tokens: u64,
+    #[serde(default)]
+    pub(super) output_tokens: u64,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency_ms: u64, status: u16) {
+        if self.buckets.len() != LATENCY_BUCKETS_MS.len() + 1 {
+            self.buckets = vec![0; LATENCY_BUCKETS_MS.len() + 1];
+        }
+        let index = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| latency_ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[index] += 1;
+        self.count += 1;
+        self.sum_ms = self.sum_ms.saturating_add(latency_ms);
+        if status >= 400 {
+            self.errors += 1;
+        }
+        *self.status_codes.entry(status).or_insert(0) += 1;
+    }
+
+    fn merge(&mut self, other: &LatencyHistogram) {
+        if self.buckets.len() < other.buckets.len() {
+            self.buckets.resize(other.buckets.len(), 0);
+        }
+        for (i, v) in other.buckets.iter().enumerate() {
+            self.buckets[i] += v;
+        }
+        self.count += other.count;
+        self.errors += other.errors;
+        self.sum_ms = self.sum_ms.saturating_add(other.sum_ms);
+        for (code, v) in &other.status_codes {
+            *self.status_codes.entry(*code).or_insert(0) += v;
+        }
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+    }
+
+    fn record_tokens(&mut self, usage: TokenUsage) {
+        self.input_tokens = self.input_tokens.saturating_add(usage.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(usage.output_tokens);
+    }
+
+    /// 估算百分位（返回所在分桶的上界，溢出桶返回最大上界）
+    fn percentile(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = ((self.count as f64) * p).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, v) in self.buckets.iter().enumerate() {
+            seen += v;
+            if seen >= target {
+                return LATENCY_BUCKETS_MS
+                    .get(i)
+                    .copied()
+                    .unwrap_or(*LATENCY_BUCKETS_MS.last().unwrap());
+            }
+        }
+        *LATENCY_BUCKETS_MS.last().unwrap()
+    }
+}
+
+/// 单个时间窗口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MetricsWindow {
+    /// 窗口起始时间（Unix 秒，按 WINDOW_SECS 对齐）
+    start: i64,
+    series: Vec<SeriesEntry>,
+    /// 被端点白名单拒绝的请求数（按工具）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    blocked: BTreeMap<String, u64>,
+    /// 因并发上限被拒绝的请求数（按工具）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    rejected: BTreeMap<String, u64>,
+    /// 上游超时次数（按超时阶段）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    timeouts: BTreeMap<String, u64>,
+    /// 上游重试次数（按供应商）
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    retries: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SeriesEntry {
+    provider: String,
+    endpoint: String,
+    histogram: LatencyHistogram,
+}
+
+impl MetricsWindow {
+    fn new(start: i64) -> Self {
+        Self {
+            start,
+            series: Vec::new(),
+            blocked: BTreeMap::new(),
+            rejected: BTreeMap::new(),
+            timeouts: BTreeMap::new(),
+            retries: BTreeMap::new(),
+        }
+    }
+
+    fn entry(&mut self, provider: &str, endpoint: &str) -> &mut LatencyHistogram {
+        let index = match self
+            .series
+            .iter()
+            .position(|s| s.provider == provider && s.endpoint == endpoint)
+        {
+            Some(i) => i,
+            None if self.series.len() < MAX_SERIES_PER_WINDOW => {
+                self.series.push(SeriesEntry {
+                    provider: provider.to_string(),
+                    endpoint: endpoint.to_string(),
+                    histogram: LatencyHistogram::default(),
+                });
+                self.series.len() - 1
+            }
+            None => {
+                // 超出上限，归并到 other 序列
+                match self
+                    .series
+                    .iter()
+                    .position(|s| s.provider == OVERFLOW_LABEL && s.endpoint == OVERFLOW_LABEL)
+                {
+                    Some(i) => i,
+                    None => {
+                        let last = self.series.len() - 1;
+                        self.series[last] = SeriesEntry {
+                            provider: OVERFLOW_LABEL.to_string(),
+                            endpoint: OVERFLOW_LABEL.to_string(),
+                            histogram: self.series[last].histogram.clone(),
+                        };
+                        last
+                    }
+                }
+            }
+        };
+        &mut self.series[index].histogram
+    }
+}
+
+/// 进程启动以来的累计计数
+///
+/// 序列键同样受 `MAX_SERIES_PER_WINDOW` 限制，超出部分归入 `other`。
+#[derive(Debug, Clone, Default)]
+pub(super) struct CumulativeTotals {
+    /// (供应商, 端点类别) → 延迟直方图
+    pub(super) series: BTreeMap<(String, String), LatencyHistogram>,
+    /// 被端点白名单拒绝的请求数（按工具）
+    pub(super) blocked: BTreeMap<String, u64>,
+    /// 因并发上限被拒绝的请求数（按工具）
+    pub(super) rejected: BTreeMap<String, u64>,
+    /// 上游超时次数（按阶段）
+    pub(super) timeouts: BTreeMap<String, u64>,
+    /// 上游重试次数（按供应商）
+    pub(super) retries: BTreeMap<String, u64>,
+}
+
+impl CumulativeTotals {
+    fn series_entry(&mut self, provider: &str, endpoint: &str) -> &mut LatencyHistogram {
+        let key = (provider.to_string(), endpoint.to_string());
+        let key = if self.series.contains_key(&key) || self.series.len() < MAX_SERIES_PER_WINDOW {
+            key
+        } else {
+            (OVERFLOW_LABEL.to_string(), OVERFLOW_LABEL.to_string())
+        };
+        self.series.entry(key).or_default()
+    }
+}
+
+/// 按标签计数（标签数量超出上限时归入 `other`）
+fn bump(map: &mut BTreeMap<String, u64>, label: &str) {
+    let key = if map.contains_key(label) || map.len() < MAX_SERIES_PER_WINDOW {
+        label
+    } else {
+        OVERFLOW_LABEL
+    };
+    *map.entry(key.to_string()).or_insert(0) += 1;
+}
+
+/// 持久化文件结构
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MetricsSnapshotFile {
+    windows: Vec<MetricsWindow>,
+}
+
+/// 单条指标序列统计
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSeries {
+    pub provider: String,
+    pub endpoint: String,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub error_rate: f64,
+    pub avg_ms: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub status_codes: BTreeMap<u16, u64>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// 指标查询结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyMetricsReport {
+    pub range: MetricsRange,
+    pub generated_at: i64,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    /// 被端点白名单拒绝的请求数
+    pub blocked_requests: u64,
+    /// 因并发上限被拒绝的请求数
+    pub rejected_requests: u64,
+    /// 上游超时次数（按阶段：connect / first_byte / total / stream_idle）
+    pub upstream_timeouts: BTreeMap<String, u64>,
+    /// 因限流/过载/超时而重试的次数
+    pub upstream_retries: u64,
+    /// 各工具代理当前的并发使用情况（由命令层填充）
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub utilization: BTreeMap<String, ProxyUtilization>,
+    pub series: Vec<MetricsSeries>,
+}
+
+/// 按天、供应商、端点汇总的请求量（用于导出）
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailyUsageRow {
+    /// 本地日期（YYYY-MM-DD）
+    pub date: String,
+    pub provider: String,
+    pub endpoint: String,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub avg_ms: u64,
+}
+
+/// 代理指标收集器
+pub struct ProxyMetrics {
+    windows: Mutex<VecDeque<MetricsWindow>>,
+    totals: Mutex<CumulativeTotals>,
+}
+
+impl ProxyMetrics {
+    pub fn new() -> Self {
+        Self {
+            windows: Mutex::new(VecDeque::new()),
+            totals: Mutex::new(CumulativeTotals::default()),
+        }
+    }
+
+    fn with_current_window<T>(&self, now: i64, f: impl FnOnce(&mut MetricsWindow) -> T) -> T {
+        let start = now - now.rem_euclid(WINDOW_SECS);
+        let mut windows = self.windows.lock().unwrap();
+        if windows.back().map(|w| w.start) != Some(start) {
+            windows.push_back(MetricsWindow::new(start));
+            while windows.len() > MAX_WINDOWS {
+                windows.pop_front();
+            }
+        }
+        f(windows.back_mut().unwrap())
+    }
+
+    /// 记录一次已转发到上游的请求
+    pub fn record(&self, provider: &str, path: &str, latency: Duration, status: u16) {
+        self.record_at(
+            Utc::now().timestamp(),
+            provider,
+            endpoint_class(path),
+            latency.as_millis() as u64,
+            status,
+        );
+    }
+
+    fn record_at(&self, now: i64, provider: &str, endpoint: &str, latency_ms: u64, status: u16) {
+        self.with_current_window(now, |window| {
+            window.entry(provider, endpoint).record(latency_ms, status);
+        });
+        self.totals
+            .lock()
+            .unwrap()
+            .series_entry(provider, endpoint)
+            .record(latency_ms, status);
+    }
+
+    /// 记录一次被端点白名单拒绝的请求
+    pub fn record_blocked(&self, tool_id: &str) {
+        self.with_current_window(Utc::now().timestamp(), |window| {
+            *window.blocked.entry(tool_id.to_string()).or_insert(0) += 1;
+        });
+        bump(&mut self.totals.lock().unwrap().blocked, tool_id);
+    }
+
+    /// 记录一次因并发上限被拒绝的请求
+    pub fn record_rejected(&self, tool_id: &str) {
+        self.with_current_window(Utc::now().timestamp(), |window| {
+            *window.rejected.entry(tool_id.to_string()).or_insert(0) += 1;
+        });
+        bump(&mut self.totals.lock().unwrap().rejected, tool_id);
+    }
+
+    /// 记录一次上游超时
+    pub fn record_timeout(&self, phase: &str) {
+        self.with_current_window(Utc::now().timestamp(), |window| {
+            *window.timeouts.entry(phase.to_string()).or_insert(0) += 1;
+        });
+        bump(&mut self.totals.lock().unwrap().timeouts, phase);
+    }
+
+    /// 记录一次上游重试
+    pub fn record_retry(&self, provider: &str) {
+        self.with_current_window(Utc::now().timestamp(), |window| {
+            *window.retries.entry(provider.to_string()).or_insert(0) += 1;
+        });
+        bump(&mut self.totals.lock().unwrap().retries, provider);
+    }
+
+    /// 记录上游响应报告的 token 用量
+    pub fn record_tokens(&self, provider: &str, path: &str, usage: TokenUsage) {
+        self.record_tokens_at(
+            Utc::now().timestamp(),
+            provider,
+            endpoint_class(path),
+            usage,
+        );
+    }
+
+    fn record_tokens_at(&self, now: i64, provider: &str, endpoint: &str, usage: TokenUsage) {
+        if usage.is_empty() {
+            return;
+        }
+        self.with_current_window(now, |window| {
+            window.entry(provider, endpoint).record_tokens(usage);
+        });
+        self.totals
+            .lock()
+            .unwrap()
+            .series_entry(provider, endpoint)
+            .record_tokens(usage);
+    }
+
+    /// 进程启动以来的累计计数快照
+    pub(super) fn totals(&self) -> CumulativeTotals {
+        self.totals.lock().unwrap().clone()
+    }
+
+    /// 指定工具在范围内被白名单拒绝的请求数
+    pub fn blocked_count(&self, tool_id: &str, range: MetricsRange) -> u64 {
+        let since = Utc::now().timestamp() - range.seconds();
+        self.windows
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|w| w.start + WINDOW_SECS > since)
+            .filter_map(|w| w.blocked.get(tool_id))
+            .sum()
+    }
+
+    /// 查询指定范围内的指标
+    pub fn report(&self, range: MetricsRange) -> ProxyMetricsReport {
+        self.report_at(Utc::now().timestamp(), range)
+    }
+
+    fn report_at(&self, now: i64, range: MetricsRange) -> ProxyMetricsReport {
+        let since = now - range.seconds();
+        let windows = self.windows.lock().unwrap();
+
+        let mut merged: BTreeMap<(String, String), LatencyHistogram> = BTreeMap::new();
+        let mut blocked_requests = 0;
+        let mut rejected_requests = 0;
+        let mut upstream_timeouts: BTreeMap<String, u64> = BTreeMap::new();
+        let mut upstream_retries = 0;
+        for window in windows.iter().filter(|w| w.start + WINDOW_SECS > since) {
+            blocked_requests += window.blocked.values().sum::<u64>();
+            rejected_requests += window.rejected.values().sum::<u64>();
+            upstream_retries += window.retries.values().sum::<u64>();
+            for (phase, count) in &window.timeouts {
+                *upstream_timeouts.entry(phase.clone()).or_insert(0) += count;
+            }
+            for entry in &window.series {
+                merged
+                    .entry((entry.provider.clone(), entry.endpoint.clone()))
+                    .or_default()
+                    .merge(&entry.histogram);
+            }
+        }
+
+        let series: Vec<MetricsSeries> = merged
+            .into_iter()
+            .map(|((provider, endpoint), h)| MetricsSeries {
+                provider,
+                endpoint,
+                request_count: h.count,
+                error_count: h.errors,
+                error_rate: if h.count == 0 {
+                    0.0
+                } else {
+                    h.errors as f64 / h.count as f64
+                },
+                avg_ms: if h.count == 0 { 0 } else { h.sum_ms / h.count },
+                p50_ms: h.percentile(0.50),
+                p95_ms: h.percentile(0.95),
+                status_codes: h.status_codes,
+                input_tokens: h.input_tokens,
+                output_tokens: h.output_tokens,
+            })
+            .collect();
+
+        ProxyMetricsReport {
+            range,
+            generated_at: now,
+            total_requests: series.iter().map(|s| s.request_count).sum(),
+            total_errors: series.iter().map(|s| s.error_count).sum(),
+            blocked_requests,
+            rejected_requests,
+            upstream_timeouts,
+            upstream_retries,
+            utilization: BTreeMap::new(),
+            series,
+        }
+    }
+
+    /// 按本地日期汇总 `[from, to]` 范围内的请求量（仅包含内存中保留的 24 小时数据）
+    pub fn daily_usage(&self, from: NaiveDate, to: NaiveDate) -> Vec<DailyUsageRow> {
+        self.daily_usage_by(from, to, |ts| {
+            chrono::Local
+                .timestamp_opt(ts, 0)
+                .single()
+                .map(|dt| dt.date_naive())
+        })
+    }
+
+    fn daily_usage_by(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        to_date: impl Fn(i64) -> Option<NaiveDate>,
+    ) -> Vec<DailyUsageRow> {
+        let windows = self.windows.lock().unwrap();
+        let mut merged: BTreeMap<(NaiveDate, String, String), LatencyHistogram> = BTreeMap::new();
+        for window in windows.iter() {
+            let Some(date) = to_date(window.start) else {
+                continue;
+            };
+            if date < from || date > to {
+                continue;
+            }
+            for entry in &window.series {
+                merged
+                    .entry((date, entry.provider.clone(), entry.endpoint.clone()))
+                    .or_default()
+                    .merge(&entry.histogram);
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|((date, provider, endpoint), h)| DailyUsageRow {
+                date: date.format("%Y-%m-%d").to_string(),
+                provider,
+                endpoint,
+                request_count: h.count,
+                error_count: h.errors,
+                avg_ms: if h.count == 0 { 0 } else { h.sum_ms / h.count },
+            })
+            .collect()
+    }
+
+    fn snapshot_path() -> anyhow::Result<PathBuf> {
+        Ok(config_dir()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .join("proxy_metrics.json"))
+    }
+
+    /// 从磁盘恢复 24 小时内的指标
+    pub fn load_persisted(&self) -> anyhow::Result<()> {
+        let path = Self::snapshot_path()?;
+        if !path.exists() {
+            return Ok(());
+        }
+        let value = DataManager::new().json_uncached().read(&path)?;
+        let file: MetricsSnapshotFile = serde_json::from_value(value)?;
+        let cutoff = Utc::now().timestamp() - MetricsRange::Last24Hours.seconds();
+
+        let mut windows = self.windows.lock().unwrap();
+        let mut restored: VecDeque<MetricsWindow> = file
+            .windows
+            .into_iter()
+            .filter(|w| w.start > cutoff)
+            .collect();
+        // 内存中已有的窗口（启动后新产生的数据）优先
+        for window in windows.drain(..) {
+            restored.retain(|w| w.start != window.start);
+            restored.push_back(window);
+        }
+        restored.make_contiguous().sort_by_key(|w| w.start);
+        while restored.len() > MAX_WINDOWS {
+            restored.pop_front();
+        }
+        *windows = restored;
+        Ok(())
+    }
+
+    /// 将当前指标写入磁盘
+    pub fn persist(&self) -> anyhow::Result<()> {
+        let file = MetricsSnapshotFile {
+            windows: self.windows.lock().unwrap().iter().cloned().collect(),
+        };
+        let value = serde_json::to_value(&file)?;
+        // 快照每分钟写入一次，丢失最近一次可接受，跳过 fsync
+        DataManager::new()
+            .with_durability(Durability::Relaxed)
+            .json_uncached()
+            .write(&Self::snapshot_path()?, &value)?;
+        Ok(())
+    }
+
+    /// 恢复历史指标并启动定期持久化任务
+    pub fn start_persistence(&'static self) {
+        if let Err(e) = self.load_persisted() {
+            tracing::warn!(error = ?e, "恢复代理指标失败");
+        }
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(PERSIST_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.persist() {
+                    tracing::warn!(error = ?e, "持久化代理指标失败");
+                }
+            }
+        });
+    }
+}
+
+impl Default for ProxyMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 从 Base URL 提取主机名作为供应商标签
+pub fn provider_label_from_url(base_url: &str) -> String {
+    url::Url::parse(base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| base_url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_class() {
+        assert_eq!(endpoint_class("/v1/messages"), "messages");
+        assert_eq!(endpoint_class("/v1/chat/completions"), "chat_completions");
+        assert_eq!(
+            endpoint_class("/v1beta/models/gemini-pro:streamGenerateContent"),
+            "generate_content"
+        );
+        assert_eq!(endpoint_class("/foo"), "other");
+    }
+
+    #[test]
+    fn test_percentiles_and_error_rate() {
+        let metrics = ProxyMetrics::new();
+        let now = 1_700_000_000;
+        for _ in 0..90 {
+            metrics.record_at(now, "relay", "messages", 80, 200);
+        }
+        for _ in 0..10 {
+            metrics.record_at(now, "relay", "messages", 4_000, 529);
+        }
+
+        let report = metrics.report_at(now, MetricsRange::LastHour);
+        assert_eq!(report.total_requests, 100);
+        let series = &report.series[0];
+        assert_eq!(series.p50_ms, 100);
+        assert_eq!(series.p95_ms, 5_000);
+        assert_eq!(series.error_count, 10);
+        assert!((series.error_rate - 0.1).abs() < f64::EPSILON);
+        assert_eq!(series.status_codes.get(&529), Some(&10));
+    }
+
+    #[test]
+    fn test_range_excludes_old_windows() {
+        let metrics = ProxyMetrics::new();
+        let now = 1_700_000_000;
+        metrics.record_at(now - 7_200, "relay", "messages", 100, 200);
+        metrics.record_at(now, "relay", "messages", 100, 200);
+
+        assert_eq!(
+            metrics
+                .report_at(now, MetricsRange::LastHour)
+                .total_requests,
+            1
+        );
+        assert_eq!(
+            metrics
+                .report_at(now, MetricsRange::Last24Hours)
+                .total_requests,
+            2
+        );
+    }
+
+    #[test]
+    fn test_series_are_bounded() {
+        let metrics = ProxyMetrics::new();
+        let now = 1_700_000_000;
+        for i in 0..(MAX_SERIES_PER_WINDOW + 20) {
+            metrics.record_at(now, &format!("provider-{i}"), "messages", 100, 200);
+        }
+
+        let report = metrics.report_at(now, MetricsRange::LastHour);
+        assert_eq!(report.series.len(), MAX_SERIES_PER_WINDOW);
+        assert_eq!(report.total_requests, (MAX_SERIES_PER_WINDOW + 20) as u64);
+    }
+
+    #[test]
+    fn test_blocked_requests_are_counted() {
+        let metrics = ProxyMetrics::new();
+        metrics.record_blocked("claude-code");
+        metrics.record_blocked("claude-code");
+        metrics.record_blocked("codex");
+
+        assert_eq!(
+            metrics.blocked_count("claude-code", MetricsRange::LastHour),
+            2
+        );
+        assert_eq!(
+            metrics.blocked_count("gemini-cli", MetricsRange::LastHour),
+            0
+        );
+        let report = metrics.report(MetricsRange::LastHour);
+        assert_eq!(report.blocked_requests, 3);
+        assert_eq!(report.total_requests, 0);
+    }
+
+    #[test]
+    fn test_tokens_and_totals() {
+        let metrics = ProxyMetrics::new();
+        let now = 1_700_000_000;
+        metrics.record_at(now - 7_200, "relay", "messages", 100, 200);
+        metrics.record_at(now, "relay", "messages", 100, 429);
+        metrics.record_tokens_at(
+            now,
+            "relay",
+            "messages",
+            TokenUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+            },
+        );
+        metrics.record_retry("relay");
+
+        let report = metrics.report_at(now, MetricsRange::LastHour);
+        assert_eq!(report.series[0].input_tokens, 10);
+        assert_eq!(report.series[0].output_tokens, 5);
+        assert_eq!(report.upstream_retries, 1);
+
+        // 累计计数不受查询范围影响
+        let totals = metrics.totals();
+        let relay = &totals.series[&("relay".to_string(), "messages".to_string())];
+        assert_eq!(relay.count, 2);
+        assert_eq!(relay.status_codes.get(&429), Some(&1));
+        assert_eq!(totals.retries.get("relay"), Some(&1));
+    }
+
+    #[test]
+    fn test_daily_usage_groups_by_date() {
+        let metrics = ProxyMetrics::new();
+        // 2024-01-01 00:00 UTC 与 2024-01-02 00:00 UTC
+        metrics.record_at(1_704_067_200, "relay", "messages", 100, 200);
+        metrics.record_at(1_704_067_200 + 600, "relay", "messages", 300, 500);
+        metrics.record_at(1_704_153_600, "relay", "messages", 100, 200);
+
+        let utc_date = |ts: i64| chrono::DateTime::from_timestamp(ts, 0).map(|dt| dt.date_naive());
+        let day1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+
+        let rows = metrics.daily_usage_by(day1, day2, utc_date);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].date, "2024-01-01");
+        assert_eq!(rows[0].request_count, 2);
+        assert_eq!(rows[0].error_count, 1);
+        assert_eq!(rows[0].avg_ms, 200);
+
+        let rows = metrics.daily_usage_by(day2, day2, utc_date);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].date, "2024-01-02");
+    }
+}
@@ -2,11 +2,26 @@
 //
 // 包含代理配置、透明代理等功能
 
+pub mod capture; // 调试捕获（仅内存）
 pub mod config; // 代理配置辅助模块
+pub mod credentials; // 凭证注入
+pub mod export; // 用量与访问日志导出
 pub mod headers;
+pub mod health; // 代理自身的健康检查端点
+pub mod limits; // 并发连接/上游请求上限
+pub mod metrics; // 请求延迟与错误率指标
+pub mod prometheus; // Prometheus 指标端点
 pub mod proxy_instance;
 pub mod proxy_manager;
 pub mod proxy_service;
+pub mod retry; // 上游限流/过载重试
+pub mod sessions; // 请求的会话关联与统计
+pub mod supervisor; // 监听任务守护与自动重启
+pub mod timeouts; // 上游分阶段超时
+pub mod tool_routing; // 一键将工具指向本地代理
+pub mod translation; // Anthropic → OpenAI 协议转换
+pub mod upstream_errors; // 上游错误响应规范化
+pub mod usage; // 上游响应 token 用量提取
 pub mod utils;
 
 pub use headers::{create_request_processor, ProcessedRequest, RequestProcessor};
@@ -16,3 +31,4 @@ pub use headers::create_headers_processor;
 pub use proxy_instance::ProxyInstance;
 pub use proxy_manager::ProxyManager;
 pub use proxy_service::ProxyService;
+pub use supervisor::{ProxyRunState, ProxyStatusEvent, PROXY_CRASHED_EVENT, PROXY_STATUS_EVENT};
@@ -0,0 +1,272 @@
+//! Prometheus 指标端点
+//!
+//! `GET /__duckcoding/metrics` 以 Prometheus 文本格式输出代理指标，数据来自与
+//! `get_proxy_metrics` 相同的指标收集器（进程启动以来的累计值），不会请求上游。
+//! 标签仅包含供应商 ID、端点类别、状态码等有限取值，不包含完整路径。
+//!
+//! 累计计数在所有工具的代理间共享，并发使用情况（连接数、进行中请求数）仅为当前监听端口。
+
+use bytes::Bytes;
+use hyper::{Response, StatusCode};
+use std::fmt::Write;
+
+use super::limits::ProxyUtilization;
+use super::metrics::{CumulativeTotals, LATENCY_BUCKETS_MS, PROXY_METRICS};
+use super::utils::body::{box_body, BoxBody};
+
+/// 指标路径
+pub const METRICS_PATH: &str = "/__duckcoding/metrics";
+
+/// Prometheus 文本格式 Content-Type
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 输出当前指标
+pub fn respond(tool_id: &str, utilization: &ProxyUtilization) -> Response<BoxBody> {
+    let body = render(&PROXY_METRICS.totals(), tool_id, utilization);
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", CONTENT_TYPE)
+        .header("cache-control", "no-store")
+        .body(box_body(http_body_util::Full::new(Bytes::from(body))))
+        .unwrap()
+}
+
+/// 转义标签值
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn labeled_counter(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    label: &str,
+    values: &std::collections::BTreeMap<String, u64>,
+) {
+    header(out, name, "counter", help);
+    for (value, count) in values {
+        let _ = writeln!(out, "{name}{{{label}=\"{}\"}} {count}", escape(value));
+    }
+}
+
+/// 渲染 Prometheus 文本格式
+pub(super) fn render(
+    totals: &CumulativeTotals,
+    tool_id: &str,
+    utilization: &ProxyUtilization,
+) -> String {
+    let mut out = String::new();
+
+    header(
+        &mut out,
+        "duckcoding_proxy_requests_total",
+        "counter",
+        "Requests forwarded upstream by provider, endpoint class and status code.",
+    );
+    for ((provider, endpoint), h) in &totals.series {
+        for (status, count) in &h.status_codes {
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_requests_total{{provider=\"{}\",endpoint=\"{}\",status=\"{status}\"}} {count}",
+                escape(provider),
+                escape(endpoint)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "duckcoding_proxy_request_duration_seconds",
+        "histogram",
+        "Time to upstream response headers.",
+    );
+    for ((provider, endpoint), h) in &totals.series {
+        let labels = format!(
+            "provider=\"{}\",endpoint=\"{}\"",
+            escape(provider),
+            escape(endpoint)
+        );
+        let mut cumulative = 0;
+        for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            cumulative += h.buckets.get(i).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_request_duration_seconds_bucket{{{labels},le=\"{}\"}} {cumulative}",
+                *bound as f64 / 1000.0
+            );
+        }
+        let _ = writeln!(
+            out,
+            "duckcoding_proxy_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+            h.count
+        );
+        let _ = writeln!(
+            out,
+            "duckcoding_proxy_request_duration_seconds_sum{{{labels}}} {}",
+            h.sum_ms as f64 / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "duckcoding_proxy_request_duration_seconds_count{{{labels}}} {}",
+            h.count
+        );
+    }
+
+    header(
+        &mut out,
+        "duckcoding_proxy_tokens_total",
+        "counter",
+        "Token usage reported by upstream responses.",
+    );
+    for ((provider, endpoint), h) in &totals.series {
+        for (direction, count) in [("input", h.input_tokens), ("output", h.output_tokens)] {
+            let _ = writeln!(
+                out,
+                "duckcoding_proxy_tokens_total{{provider=\"{}\",endpoint=\"{}\",direction=\"{direction}\"}} {count}",
+                escape(provider),
+                escape(endpoint)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "duckcoding_proxy_upstream_rate_limited_total",
+        "counter",
+        "Upstream responses with status 429.",
+    );
+    for ((provider, endpoint), h) in &totals.series {
+        let count = h.status_codes.get(&429).copied().unwrap_or(0);
+        let _ = writeln!(
+            out,
+            "duckcoding_proxy_upstream_rate_limited_total{{provider=\"{}\",endpoint=\"{}\"}} {count}",
+            escape(provider),
+            escape(endpoint)
+        );
+    }
+
+    labeled_counter(
+        &mut out,
+        "duckcoding_proxy_upstream_retries_total",
+        "Upstream retries after rate limiting, overload or timeouts.",
+        "provider",
+        &totals.retries,
+    );
+    labeled_counter(
+        &mut out,
+        "duckcoding_proxy_upstream_timeouts_total",
+        "Upstream timeouts by phase.",
+        "phase",
+        &totals.timeouts,
+    );
+    labeled_counter(
+        &mut out,
+        "duckcoding_proxy_rejected_requests_total",
+        "Requests rejected by the proxy concurrency limits.",
+        "tool",
+        &totals.rejected,
+    );
+    labeled_counter(
+        &mut out,
+        "duckcoding_proxy_blocked_requests_total",
+        "Requests rejected by the endpoint allowlist.",
+        "tool",
+        &totals.blocked,
+    );
+
+    let tool = escape(tool_id);
+    header(
+        &mut out,
+        "duckcoding_proxy_active_connections",
+        "gauge",
+        "Open client connections on this listener.",
+    );
+    let _ = writeln!(
+        out,
+        "duckcoding_proxy_active_connections{{tool=\"{tool}\"}} {}",
+        utilization.active_connections
+    );
+    header(
+        &mut out,
+        "duckcoding_proxy_in_flight_requests",
+        "gauge",
+        "Upstream requests currently in flight on this listener.",
+    );
+    let _ = writeln!(
+        out,
+        "duckcoding_proxy_in_flight_requests{{tool=\"{tool}\"}} {}",
+        utilization.in_flight_requests
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::proxy::metrics::ProxyMetrics;
+    use crate::services::proxy::usage::TokenUsage;
+    use std::time::Duration;
+
+    #[test]
+    fn test_render_exposition_format() {
+        let metrics = ProxyMetrics::new();
+        metrics.record("relay", "/v1/messages", Duration::from_millis(80), 200);
+        metrics.record(
+            "relay",
+            "/v1/messages?beta=1",
+            Duration::from_millis(2_500),
+            429,
+        );
+        metrics.record_tokens(
+            "relay",
+            "/v1/messages",
+            TokenUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+            },
+        );
+        metrics.record_retry("relay");
+        metrics.record_timeout("first_byte");
+
+        let utilization = ProxyUtilization {
+            active_connections: 2,
+            max_connections: 0,
+            in_flight_requests: 1,
+            max_in_flight_requests: 0,
+            rejected: 0,
+        };
+        let text = render(&metrics.totals(), "claude-code", &utilization);
+
+        assert!(text.contains(
+            r#"duckcoding_proxy_requests_total{provider="relay",endpoint="messages",status="429"} 1"#
+        ));
+        assert!(text.contains(
+            r#"duckcoding_proxy_request_duration_seconds_bucket{provider="relay",endpoint="messages",le="0.1"} 1"#
+        ));
+        assert!(text.contains(
+            r#"duckcoding_proxy_request_duration_seconds_bucket{provider="relay",endpoint="messages",le="+Inf"} 2"#
+        ));
+        assert!(text.contains(
+            r#"duckcoding_proxy_tokens_total{provider="relay",endpoint="messages",direction="input"} 100"#
+        ));
+        assert!(text.contains(r#"duckcoding_proxy_upstream_retries_total{provider="relay"} 1"#));
+        assert!(text.contains(r#"duckcoding_proxy_upstream_timeouts_total{phase="first_byte"} 1"#));
+        assert!(text.contains(r#"duckcoding_proxy_active_connections{tool="claude-code"} 2"#));
+        // 标签中不包含完整路径
+        assert!(!text.contains("beta=1"));
+    }
+
+    #[test]
+    fn test_escape_label_values() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -4,9 +4,12 @@
 // - HTTP 服务器的启动和停止
 // - 请求的接收和转发
 // - Headers 处理的协调
+// - 监听任务异常退出后的自动重启
 
 use anyhow::{Context, Result};
 use bytes::Bytes;
+use chrono::Utc;
+use futures_util::Stream;
 use http_body_util::BodyExt;
 use hyper::body::{Frame, Incoming};
 use hyper::server::conn::http1;
@@ -15,14 +18,32 @@ use hyper::{Method, Request, Response, StatusCode};
 use hyper_util::rt::TokioIo;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::net::TcpListener;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio_util::sync::CancellationToken;
 
+use super::capture::CAPTURE_STORE;
+use super::credentials::{self, InjectionOutcome, ProviderOverride};
 use super::headers::RequestProcessor;
+use super::health;
+use super::limits::{ProxyLimiter, ProxyUtilization};
+use super::metrics::{self, PROXY_METRICS};
+use super::prometheus;
+use super::retry;
+use super::sessions::{self, PROXY_SESSIONS};
+use super::supervisor::{panic_message, ProxyRunState, ProxyStatusEvent, RestartPolicy};
+use super::timeouts::{self, TimeoutPhase, UpstreamFailure};
+use super::translation;
+use super::upstream_errors::{self, Normalized};
+use super::usage::{self, SseUsageScanner};
 use super::utils::body::{box_body, BoxBody};
 use super::utils::{error_responses, loop_detector};
+use crate::http_client::merge_custom_headers;
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::provider_manager::shared_providers;
 
 /// 单个代理实例
 pub struct ProxyInstance {
@@ -30,6 +51,11 @@ pub struct ProxyInstance {
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
     server_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    shutdown: Arc<RwLock<Option<CancellationToken>>>,
+    state: Arc<RwLock<ProxyRunState>>,
+    events: broadcast::Sender<ProxyStatusEvent>,
+    restart_policy: RestartPolicy,
+    limiter: Arc<ProxyLimiter>,
 }
 
 impl ProxyInstance {
@@ -38,21 +64,40 @@ impl ProxyInstance {
         tool_id: String,
         config: ToolProxyConfig,
         processor: Box<dyn RequestProcessor>,
+    ) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self::with_events(tool_id, config, processor, events)
+    }
+
+    /// 创建代理实例，并将状态变化发送到指定事件通道
+    pub fn with_events(
+        tool_id: String,
+        config: ToolProxyConfig,
+        processor: Box<dyn RequestProcessor>,
+        events: broadcast::Sender<ProxyStatusEvent>,
     ) -> Self {
         Self {
             tool_id,
             config: Arc::new(RwLock::new(config)),
             processor: Arc::from(processor),
             server_handle: Arc::new(RwLock::new(None)),
+            shutdown: Arc::new(RwLock::new(None)),
+            state: Arc::new(RwLock::new(ProxyRunState::Stopped)),
+            events,
+            restart_policy: RestartPolicy::default(),
+            limiter: Arc::new(ProxyLimiter::new()),
         }
     }
 
     /// 启动代理服务
+    ///
+    /// 首次绑定失败直接返回错误；绑定成功后由守护任务负责
+    /// 在监听任务异常退出时按指数退避重启。
     pub async fn start(&self) -> Result<()> {
         // 检查是否已经在运行
         {
             let handle = self.server_handle.read().await;
-            if handle.is_some() {
+            if handle.is_some() && self.state.read().await.is_active() {
                 anyhow::bail!("代理实例已在运行");
             }
         }
@@ -85,65 +130,56 @@ impl ProxyInstance {
             "透明代理启动成功"
         );
 
-        let config_clone = Arc::clone(&self.config);
-        let processor_clone = Arc::clone(&self.processor);
-        let port = config.port;
-        let tool_id = self.tool_id.clone();
-
-        // 启动服务器
-        let handle = tokio::spawn(async move {
-            loop {
-                match listener.accept().await {
-                    Ok((stream, _addr)) => {
-                        let config = Arc::clone(&config_clone);
-                        let processor = Arc::clone(&processor_clone);
-                        let tool_id_inner = tool_id.clone();
-                        let tool_id_for_error = tool_id.clone();
-
-                        tokio::spawn(async move {
-                            let io = TokioIo::new(stream);
-                            let service = service_fn(move |req| {
-                                let config = Arc::clone(&config);
-                                let processor = Arc::clone(&processor);
-                                let tool_id = tool_id_inner.clone();
-                                async move {
-                                    handle_request(req, config, processor, port, &tool_id).await
-                                }
-                            });
-
-                            if let Err(err) =
-                                http1::Builder::new().serve_connection(io, service).await
-                            {
-                                tracing::error!(
-                                    tool_id = %tool_id_for_error,
-                                    error = ?err,
-                                    "处理连接失败"
-                                );
-                            }
-                        });
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            tool_id = %tool_id,
-                            error = ?e,
-                            "接受连接失败"
-                        );
-                    }
-                }
-            }
-        });
+        let token = CancellationToken::new();
+        let ctx = ListenerContext {
+            tool_id: self.tool_id.clone(),
+            config: Arc::clone(&self.config),
+            processor: Arc::clone(&self.processor),
+            limiter: Arc::clone(&self.limiter),
+            port: config.port,
+            started_at: Instant::now(),
+        };
+
+        set_state(
+            &self.state,
+            &self.events,
+            &self.tool_id,
+            ProxyRunState::Running,
+            None,
+        )
+        .await;
+
+        // 启动守护任务
+        let handle = tokio::spawn(supervise(
+            ctx,
+            addr,
+            listener,
+            token.clone(),
+            Arc::clone(&self.state),
+            self.events.clone(),
+            self.restart_policy.clone(),
+        ));
 
         // 保存服务器句柄
         {
             let mut h = self.server_handle.write().await;
             *h = Some(handle);
+            let mut s = self.shutdown.write().await;
+            *s = Some(token);
         }
 
         Ok(())
     }
 
     /// 停止代理服务
+    ///
+    /// 显式停止会同时关闭守护任务，本次会话内不再自动重启。
+    /// 已建立的连接不再接受新请求，进行中的响应最多等待 `drain_timeout_secs` 后关闭。
     pub async fn stop(&self) -> Result<()> {
+        if let Some(token) = self.shutdown.write().await.take() {
+            token.cancel();
+        }
+
         let handle = {
             let mut h = self.server_handle.write().await;
             h.take()
@@ -154,6 +190,15 @@ impl ProxyInstance {
             tracing::info!(tool_id = %self.tool_id, "透明代理已停止");
         }
 
+        set_state(
+            &self.state,
+            &self.events,
+            &self.tool_id,
+            ProxyRunState::Stopped,
+            None,
+        )
+        .await;
+
         Ok(())
     }
 
@@ -164,13 +209,36 @@ impl ProxyInstance {
         false // 临时实现，将在异步上下文中使用 try_read
     }
 
-    /// 异步检查是否运行
+    /// 异步检查是否运行（重启中也视为运行）
     pub async fn is_running_async(&self) -> bool {
         let handle = self.server_handle.read().await;
-        handle.is_some()
+        handle.is_some() && self.state.read().await.is_active()
+    }
+
+    /// 获取当前运行状态
+    pub async fn state(&self) -> ProxyRunState {
+        self.state.read().await.clone()
+    }
+
+    /// 获取监听端口
+    pub async fn port(&self) -> u16 {
+        self.config.read().await.port
+    }
+
+    /// 获取并发使用情况
+    pub async fn utilization(&self) -> ProxyUtilization {
+        self.limiter.utilization(&self.config.read().await.limits)
+    }
+
+    /// 获取当前配置快照
+    pub async fn config(&self) -> ToolProxyConfig {
+        self.config.read().await.clone()
     }
 
     /// 更新配置（无需重启）
+    ///
+    /// 每个请求开始时读取配置快照，进行中的请求（含流式响应）继续使用旧配置，
+    /// 之后的新请求立即使用新配置。
     pub async fn update_config(&self, new_config: ToolProxyConfig) -> Result<()> {
         let mut config = self.config.write().await;
         *config = new_config;
@@ -179,15 +247,274 @@ impl ProxyInstance {
     }
 }
 
+/// 监听任务所需的共享上下文
+#[derive(Clone)]
+struct ListenerContext {
+    tool_id: String,
+    config: Arc<RwLock<ToolProxyConfig>>,
+    processor: Arc<dyn RequestProcessor>,
+    limiter: Arc<ProxyLimiter>,
+    port: u16,
+    /// 监听开始时间（健康检查的 uptime）
+    started_at: Instant,
+}
+
+/// 更新状态并广播事件
+async fn set_state(
+    state: &RwLock<ProxyRunState>,
+    events: &broadcast::Sender<ProxyStatusEvent>,
+    tool_id: &str,
+    new_state: ProxyRunState,
+    message: Option<String>,
+) {
+    {
+        let mut guard = state.write().await;
+        if *guard == new_state && message.is_none() {
+            return;
+        }
+        *guard = new_state.clone();
+    }
+    // 没有订阅者时发送失败属于正常情况
+    let _ = events.send(ProxyStatusEvent {
+        tool_id: tool_id.to_string(),
+        state: new_state,
+        message,
+    });
+}
+
+/// 守护任务：运行监听循环，异常退出时按策略重启
+async fn supervise(
+    ctx: ListenerContext,
+    addr: SocketAddr,
+    listener: TcpListener,
+    token: CancellationToken,
+    state: Arc<RwLock<ProxyRunState>>,
+    events: broadcast::Sender<ProxyStatusEvent>,
+    policy: RestartPolicy,
+) {
+    let mut listener = Some(listener);
+    let mut attempt: u32 = 0;
+
+    loop {
+        let reason = match listener.take() {
+            Some(l) => {
+                let started_at = Instant::now();
+                let task = tokio::spawn(accept_loop(l, ctx.clone(), token.clone()));
+                let result = tokio::select! {
+                    res = task => res,
+                    _ = token.cancelled() => return,
+                };
+
+                if token.is_cancelled() {
+                    return;
+                }
+                if started_at.elapsed() >= policy.reset_after {
+                    attempt = 0;
+                }
+
+                match result {
+                    Ok(()) => "监听任务意外退出".to_string(),
+                    Err(e) if e.is_panic() => {
+                        format!("监听任务 panic: {}", panic_message(e.into_panic()))
+                    }
+                    Err(_) => return,
+                }
+            }
+            None => match TcpListener::bind(addr).await {
+                Ok(l) => {
+                    tracing::info!(tool_id = %ctx.tool_id, addr = %addr, "透明代理已重启");
+                    set_state(&state, &events, &ctx.tool_id, ProxyRunState::Running, None).await;
+                    listener = Some(l);
+                    continue;
+                }
+                Err(e) => format!("重新绑定端口 {} 失败: {e}", ctx.port),
+            },
+        };
+
+        attempt += 1;
+        tracing::error!(
+            tool_id = %ctx.tool_id,
+            attempt = attempt,
+            reason = %reason,
+            "透明代理监听任务终止"
+        );
+
+        if attempt > policy.max_restarts {
+            tracing::error!(
+                tool_id = %ctx.tool_id,
+                max_restarts = policy.max_restarts,
+                "透明代理重启次数耗尽，停止守护"
+            );
+            set_state(
+                &state,
+                &events,
+                &ctx.tool_id,
+                ProxyRunState::Crashed {
+                    reason: reason.clone(),
+                },
+                Some(reason),
+            )
+            .await;
+            return;
+        }
+
+        set_state(
+            &state,
+            &events,
+            &ctx.tool_id,
+            ProxyRunState::Restarting { attempt },
+            Some(reason),
+        )
+        .await;
+
+        tokio::select! {
+            _ = tokio::time::sleep(policy.delay_for(attempt)) => {}
+            _ = token.cancelled() => return,
+        }
+    }
+}
+
+/// 接受连接的主循环
+async fn accept_loop(listener: TcpListener, ctx: ListenerContext, token: CancellationToken) {
+    loop {
+        let accepted = tokio::select! {
+            res = listener.accept() => res,
+            _ = token.cancelled() => return,
+        };
+
+        match accepted {
+            Ok((stream, peer_addr)) => {
+                let limits = ctx.config.read().await.limits.clone();
+
+                // 超出连接上限：返回 503 后关闭连接，不进入正常处理
+                let Some(connection_guard) =
+                    ctx.limiter.try_acquire_connection(limits.max_connections)
+                else {
+                    tracing::warn!(
+                        tool_id = %ctx.tool_id,
+                        max_connections = limits.max_connections,
+                        "并发连接已达上限，拒绝新连接"
+                    );
+                    PROXY_METRICS.record_rejected(&ctx.tool_id);
+                    let tool_id = ctx.tool_id.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |_req| {
+                            let response = error_responses::proxy_overloaded(
+                                &tool_id,
+                                limits.retry_after_secs,
+                            );
+                            async move { Ok::<_, Infallible>(response) }
+                        });
+                        let _ = http1::Builder::new()
+                            .keep_alive(false)
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                    continue;
+                };
+
+                let config = Arc::clone(&ctx.config);
+                let processor = Arc::clone(&ctx.processor);
+                let limiter = Arc::clone(&ctx.limiter);
+                let tool_id_inner = ctx.tool_id.clone();
+                let tool_id_for_error = ctx.tool_id.clone();
+                let drain_config = Arc::clone(&ctx.config);
+                let port = ctx.port;
+                let listening_since = ctx.started_at;
+                let token = token.clone();
+
+                tokio::spawn(async move {
+                    // 连接名额在连接关闭时释放
+                    let _connection_guard = connection_guard;
+                    let io = TokioIo::new(stream);
+                    let service = service_fn(move |req| {
+                        let config = Arc::clone(&config);
+                        let processor = Arc::clone(&processor);
+                        let limiter = Arc::clone(&limiter);
+                        let tool_id = tool_id_inner.clone();
+                        async move {
+                            handle_request(
+                                req,
+                                config,
+                                processor,
+                                limiter,
+                                port,
+                                listening_since,
+                                peer_addr,
+                                &tool_id,
+                            )
+                            .await
+                        }
+                    });
+
+                    let conn = http1::Builder::new().serve_connection(io, service);
+                    tokio::pin!(conn);
+
+                    let result = tokio::select! {
+                        res = conn.as_mut() => res,
+                        _ = token.cancelled() => {
+                            // 监听停止/重启：不再接受新请求，进行中的响应（含流式）继续完成
+                            conn.as_mut().graceful_shutdown();
+                            let drain_timeout = Duration::from_secs(
+                                drain_config.read().await.drain_timeout_secs,
+                            );
+                            match tokio::time::timeout(drain_timeout, conn.as_mut()).await {
+                                Ok(res) => res,
+                                Err(_) => {
+                                    tracing::warn!(
+                                        tool_id = %tool_id_for_error,
+                                        drain_timeout_secs = drain_timeout.as_secs(),
+                                        "连接排空超时，强制关闭"
+                                    );
+                                    Ok(())
+                                }
+                            }
+                        }
+                    };
+
+                    if let Err(err) = result {
+                        tracing::error!(
+                            tool_id = %tool_id_for_error,
+                            error = ?err,
+                            "处理连接失败"
+                        );
+                    }
+                });
+            }
+            Err(e) => {
+                tracing::error!(
+                    tool_id = %ctx.tool_id,
+                    error = ?e,
+                    "接受连接失败"
+                );
+            }
+        }
+    }
+}
+
 /// 处理单个请求
 async fn handle_request(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    limiter: Arc<ProxyLimiter>,
     own_port: u16,
+    listening_since: Instant,
+    peer_addr: SocketAddr,
     tool_id: &str,
 ) -> Result<Response<BoxBody>, Infallible> {
-    match handle_request_inner(req, config, processor, own_port, tool_id).await {
+    match handle_request_inner(
+        req,
+        config,
+        processor,
+        limiter,
+        own_port,
+        listening_since,
+        peer_addr,
+        tool_id,
+    )
+    .await
+    {
         Ok(res) => Ok(res),
         Err(e) => {
             tracing::error!(
@@ -200,55 +527,172 @@ async fn handle_request(
     }
 }
 
+/// 请求携带的本地 API Key（`Authorization: Bearer` 或 `x-api-key`）
+fn provided_local_key(headers: &hyper::HeaderMap) -> &str {
+    let auth_header = headers
+        .get("authorization")
+        .or_else(|| headers.get("x-api-key"))
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if let Some(stripped) = auth_header.strip_prefix("Bearer ") {
+        stripped
+    } else if let Some(stripped) = auth_header.strip_prefix("x-api-key ") {
+        stripped
+    } else {
+        auth_header
+    }
+}
+
 async fn handle_request_inner(
     req: Request<Incoming>,
     config: Arc<RwLock<ToolProxyConfig>>,
     processor: Arc<dyn RequestProcessor>,
+    limiter: Arc<ProxyLimiter>,
     own_port: u16,
+    listening_since: Instant,
+    peer_addr: SocketAddr,
     tool_id: &str,
 ) -> Result<Response<BoxBody>> {
-    // 获取配置
-    let proxy_config = {
-        let cfg = config.read().await;
-        if cfg.real_api_key.is_none() || cfg.real_base_url.is_none() {
-            return Ok(error_responses::configuration_missing(tool_id));
+    let started_at = Instant::now();
+    let path = req.uri().path().to_string();
+
+    // 凭证注入判定（命中时使用绑定供应商的凭证）
+    let (proxy_config, injected) = {
+        let cfg = config.read().await.clone();
+
+        // 保留前缀由代理自身响应，不受白名单与本地密钥限制，且永不转发上游
+        if health::is_reserved_path(&path) {
+            let res = if path == prometheus::METRICS_PATH
+                && req.method() == Method::GET
+                && cfg.metrics_endpoint_enabled
+            {
+                prometheus::respond(tool_id, &limiter.utilization(&cfg.limits))
+            } else {
+                health::respond(
+                    req.method(),
+                    &path,
+                    req.uri().query(),
+                    &cfg,
+                    cfg.real_base_url.as_deref(),
+                    listening_since.elapsed(),
+                )
+                .await
+            };
+            if cfg.log_health_checks {
+                tracing::info!(
+                    tool_id = %tool_id,
+                    method = %req.method(),
+                    path = %path,
+                    status = res.status().as_u16(),
+                    latency_ms = started_at.elapsed().as_millis() as u64,
+                    "代理访问日志"
+                );
+            }
+            return Ok(res);
         }
-        cfg.clone()
-    };
 
-    // 验证本地 API Key
-    let auth_header = req
-        .headers()
-        .get("authorization")
-        .or_else(|| req.headers().get("x-api-key"))
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
+        // 端点白名单：代理持有真实凭证，只转发预期的 AI API 调用
+        if !cfg.is_path_allowed(&path) {
+            PROXY_METRICS.record_blocked(tool_id);
+            tracing::warn!(
+                tool_id = %tool_id,
+                method = %req.method(),
+                path = %path,
+                "请求路径不在端点白名单内，已拒绝"
+            );
+            return Ok(error_responses::endpoint_not_allowed(tool_id, &path));
+        }
 
-    let provided_key = if let Some(stripped) = auth_header.strip_prefix("Bearer ") {
-        stripped
-    } else if let Some(stripped) = auth_header.strip_prefix("x-api-key ") {
-        stripped
-    } else {
-        auth_header
-    };
+        // 验证本地 API Key（先于供应商解析，未认证的请求无法探测供应商 ID）
+        if let Some(local_key) = &cfg.local_api_key {
+            if provided_local_key(req.headers()) != local_key.as_str() {
+                return Ok(error_responses::unauthorized());
+            }
+        }
 
-    if let Some(local_key) = &proxy_config.local_api_key {
-        if provided_key != local_key {
-            return Ok(error_responses::unauthorized());
+        // 单次请求指定供应商（X-DuckCoding-Provider）优先于工具级凭证注入
+        let overridden = credentials::resolve_provider_override(req.headers(), || {
+            Ok(shared_providers()?.to_vec())
+        });
+        match overridden {
+            ProviderOverride::Override(credential) => (cfg, Some(credential)),
+            ProviderOverride::Invalid(reason) => {
+                tracing::warn!(tool_id = %tool_id, reason = %reason, "请求指定的供应商无效");
+                return Ok(error_responses::invalid_provider_override(tool_id, &reason));
+            }
+            ProviderOverride::Absent => {
+                let outcome =
+                    credentials::resolve_injection(&cfg, tool_id, &path, req.headers(), || {
+                        Ok(shared_providers()?.to_vec())
+                    });
+                match outcome {
+                    InjectionOutcome::Injected(credential) => (cfg, Some(credential)),
+                    InjectionOutcome::Misconfigured(reason) => {
+                        tracing::warn!(tool_id = %tool_id, reason = %reason, "凭证注入配置错误");
+                        return Ok(error_responses::credential_injection_failed(
+                            tool_id, &reason,
+                        ));
+                    }
+                    InjectionOutcome::NotApplicable => {
+                        if cfg.real_api_key.is_none() || cfg.real_base_url.is_none() {
+                            return Ok(error_responses::configuration_missing(tool_id));
+                        }
+                        (cfg, None)
+                    }
+                }
+            }
         }
-    }
+    };
+
+    // 上游请求并发上限（在读取请求体之前判断，超限请求不占用内存）
+    let limits = &proxy_config.limits;
+    let Some(in_flight_guard) = limiter.try_acquire_in_flight(limits.max_in_flight_requests) else {
+        tracing::warn!(
+            tool_id = %tool_id,
+            max_in_flight_requests = limits.max_in_flight_requests,
+            "进行中的上游请求已达上限，拒绝请求"
+        );
+        PROXY_METRICS.record_rejected(tool_id);
+        return Ok(error_responses::proxy_overloaded(
+            tool_id,
+            limits.retry_after_secs,
+        ));
+    };
 
     // 提取请求信息（先借用，避免与后续的 collect 冲突）
-    let path = req.uri().path().to_string();
     let query = req.uri().query().map(|s| s.to_string());
     let method = req.method().clone();
-    let headers = req.headers().clone();
+    let mut headers = req.headers().clone();
+    // 覆盖请求头仅供代理使用，不转发上游
+    headers.remove(credentials::PROVIDER_OVERRIDE_HEADER);
+
+    // 会话关联：访问日志、调试捕获与会话统计共用同一会话 ID
+    let session = sessions::session_for_request(tool_id, peer_addr.port(), &headers, Utc::now());
+    let user_agent = headers
+        .get(hyper::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let (base, api_key) = match &injected {
+        Some(credential) => (credential.base_url.as_str(), credential.api_key.as_str()),
+        None => (
+            proxy_config
+                .real_base_url
+                .as_deref()
+                .unwrap()
+                .trim_end_matches('/'),
+            proxy_config.real_api_key.as_deref().unwrap(),
+        ),
+    };
 
-    let base = proxy_config
-        .real_base_url
-        .as_ref()
-        .unwrap()
-        .trim_end_matches('/');
+    if let Some(credential) = &injected {
+        tracing::debug!(
+            tool_id = %tool_id,
+            provider_id = %credential.provider_id,
+            "已注入供应商凭证"
+        );
+    }
 
     // 读取请求体（消费 req）
     let body_bytes = if method != Method::GET && method != Method::HEAD {
@@ -257,11 +701,17 @@ async fn handle_request_inner(
         Bytes::new()
     };
 
+    // 调试捕获（未开启时为 None）
+    let mut recorder = CAPTURE_STORE.begin(tool_id, method.as_str(), &path, &headers, &body_bytes);
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.set_session_id(&session.id);
+    }
+
     // 使用 RequestProcessor 统一处理请求（URL + headers + body）
-    let processed = processor
+    let mut processed = processor
         .process_outgoing_request(
             base,
-            proxy_config.real_api_key.as_ref().unwrap(),
+            api_key,
             &path,
             query.as_deref(),
             &headers,
@@ -269,35 +719,180 @@ async fn handle_request_inner(
         )
         .await
         .context("处理出站请求失败")?;
+    // 供应商自定义请求头（不覆盖处理器写入的凭证）
+    if let Some(credential) = &injected {
+        merge_custom_headers(&mut processed.headers, &credential.headers);
+    }
 
     // 回环检测
     if loop_detector::is_proxy_loop(&processed.target_url, own_port) {
         return Ok(error_responses::proxy_loop_detected(tool_id));
     }
 
+    // 指标标签：优先使用注入的供应商 ID，否则使用目标主机名
+    let provider_label = match &injected {
+        Some(credential) => credential.provider_id.clone(),
+        None => metrics::provider_label_from_url(&processed.target_url),
+    };
+
+    // 协议转换：供应商仅提供 OpenAI 兼容接口时转换 Anthropic Messages 请求
+    let translation = match translation::resolve(&proxy_config, &provider_label, &method, &path) {
+        Some(_) => match translation::translate_request(&mut processed) {
+            Ok(context) => Some(context),
+            Err(reason) => {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    provider = %provider_label,
+                    reason = %reason,
+                    "协议转换失败，拒绝转发"
+                );
+                return Ok(error_responses::translation_failed(
+                    tool_id,
+                    StatusCode::BAD_REQUEST,
+                    &reason,
+                ));
+            }
+        },
+        None => None,
+    };
+
     tracing::debug!(
         tool_id = %tool_id,
         method = %method,
         path = %path,
         target_url = %processed.target_url,
+        translated = translation.is_some(),
         "代理请求"
     );
 
-    // 构建上游请求（使用处理后的信息）
-    let mut reqwest_builder = reqwest::Client::new().request(method.clone(), &processed.target_url);
+    // 构建上游请求（使用处理后的信息），重试时重新构建
+    let timeouts_config = proxy_config.timeouts.clone();
+    let client = timeouts::build_client(&timeouts_config).context("创建上游 HTTP 客户端失败")?;
+    let build_request = || {
+        let mut reqwest_builder = client.request(method.clone(), &processed.target_url);
 
-    // 应用处理后的 headers
-    for (name, value) in processed.headers.iter() {
-        reqwest_builder = reqwest_builder.header(name, value);
-    }
+        // 应用处理后的 headers
+        for (name, value) in processed.headers.iter() {
+            reqwest_builder = reqwest_builder.header(name, value);
+        }
 
-    // 添加请求体
-    if !processed.body.is_empty() {
-        reqwest_builder = reqwest_builder.body(processed.body.to_vec());
-    }
+        // 添加请求体
+        if !processed.body.is_empty() {
+            reqwest_builder = reqwest_builder.body(processed.body.to_vec());
+        }
+        reqwest_builder
+    };
+
+    // 重试策略：请求体已完整缓冲且未超出上限时才允许重放
+    let policy = retry::resolve_policy(&proxy_config, &provider_label).clone();
+    let retry_enabled = retry::can_retry(&policy, processed.body.len());
+
+    // 发送请求（限流/过载时在向客户端写出任何数据前重试）
+    let mut retries: u32 = 0;
+    let upstream_res = loop {
+        let res = match timeouts::send(build_request(), &timeouts_config).await {
+            Ok(res) => res,
+            // 连接/首字节超时：尚未向客户端写出数据，可按重试策略重新请求
+            Err(UpstreamFailure::Timeout(phase)) => {
+                PROXY_METRICS.record_timeout(phase.as_str());
+                if retry_enabled && retries < policy.max_retries {
+                    retries += 1;
+                    PROXY_METRICS.record_retry(&provider_label);
+                    let delay = retry::retry_delay(&policy, retries, None);
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        provider = %provider_label,
+                        phase = phase.as_str(),
+                        attempt = retries,
+                        max_retries = policy.max_retries,
+                        delay_ms = delay.as_millis() as u64,
+                        "上游超时，准备重试"
+                    );
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
 
-    // 发送请求
-    let upstream_res = reqwest_builder.send().await.context("上游请求失败")?;
+                PROXY_METRICS.record(
+                    &provider_label,
+                    &path,
+                    started_at.elapsed(),
+                    StatusCode::GATEWAY_TIMEOUT.as_u16(),
+                );
+                PROXY_SESSIONS.record_request(
+                    &session,
+                    tool_id,
+                    user_agent.as_deref(),
+                    StatusCode::GATEWAY_TIMEOUT.as_u16(),
+                );
+                tracing::info!(
+                    tool_id = %tool_id,
+                    method = %method,
+                    path = %path,
+                    provider = %provider_label,
+                    session = %session.id,
+                    retries,
+                    outcome = "upstream_timeout",
+                    phase = phase.as_str(),
+                    latency_ms = started_at.elapsed().as_millis() as u64,
+                    "代理访问日志"
+                );
+                return Ok(error_responses::upstream_timeout(
+                    tool_id,
+                    phase.as_str(),
+                    phase.limit_secs(&timeouts_config),
+                ));
+            }
+            Err(UpstreamFailure::Request(e)) => {
+                PROXY_METRICS.record(
+                    &provider_label,
+                    &path,
+                    started_at.elapsed(),
+                    StatusCode::BAD_GATEWAY.as_u16(),
+                );
+                PROXY_SESSIONS.record_request(
+                    &session,
+                    tool_id,
+                    user_agent.as_deref(),
+                    StatusCode::BAD_GATEWAY.as_u16(),
+                );
+                tracing::info!(
+                    tool_id = %tool_id,
+                    method = %method,
+                    path = %path,
+                    provider = %provider_label,
+                    session = %session.id,
+                    retries,
+                    outcome = "upstream_error",
+                    latency_ms = started_at.elapsed().as_millis() as u64,
+                    "代理访问日志"
+                );
+                return Err(anyhow::Error::new(e).context("上游请求失败"));
+            }
+        };
+
+        let upstream_status = res.status().as_u16();
+        if !retry_enabled
+            || !retry::is_retryable_status(upstream_status)
+            || retries >= policy.max_retries
+        {
+            break res;
+        }
+
+        retries += 1;
+        PROXY_METRICS.record_retry(&provider_label);
+        let delay = retry::retry_delay(&policy, retries, retry::parse_retry_after(res.headers()));
+        tracing::warn!(
+            tool_id = %tool_id,
+            provider = %provider_label,
+            status = upstream_status,
+            attempt = retries,
+            max_retries = policy.max_retries,
+            delay_ms = delay.as_millis() as u64,
+            "上游限流/过载，准备重试"
+        );
+        drop(res);
+        tokio::time::sleep(delay).await;
+    };
 
     // 构建响应
     let status = StatusCode::from_u16(upstream_res.status().as_u16())
@@ -311,6 +906,39 @@ async fn handle_request_inner(
         .map(|v| v.contains("text/event-stream"))
         .unwrap_or(false);
 
+    // 记录延迟（首字节时间）与状态码
+    PROXY_METRICS.record(
+        &provider_label,
+        &path,
+        started_at.elapsed(),
+        status.as_u16(),
+    );
+    PROXY_SESSIONS.record_request(&session, tool_id, user_agent.as_deref(), status.as_u16());
+
+    // 访问日志：重试耗尽后（当前无故障转移）直接返回最后一次上游响应
+    tracing::info!(
+        tool_id = %tool_id,
+        method = %method,
+        path = %path,
+        provider = %provider_label,
+        session = %session.id,
+        status = status.as_u16(),
+        retries,
+        outcome = if retries > 0 && retry::is_retryable_status(status.as_u16()) {
+            "retries_exhausted"
+        } else if retries > 0 {
+            "retried"
+        } else {
+            "ok"
+        },
+        latency_ms = started_at.elapsed().as_millis() as u64,
+        "代理访问日志"
+    );
+
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.set_response_head(status.as_u16(), upstream_res.headers(), is_sse);
+    }
+
     let mut response = Response::builder().status(status);
 
     // 复制响应 headers
@@ -322,20 +950,295 @@ async fn handle_request_inner(
         tracing::debug!(tool_id = %tool_id, "SSE 流式响应");
         use futures_util::StreamExt;
 
-        let stream = upstream_res.bytes_stream();
-        let mapped_stream = stream.map(|result| {
-            result
-                .map(Frame::data)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        // 分块间隔超过空闲超时则中断流（响应头已发出，无法再返回 504）
+        let idle_tool_id = tool_id.to_string();
+        let stream = timeouts::with_idle_timeout(
+            upstream_res.bytes_stream(),
+            timeouts_config.stream_idle_secs,
+            move || {
+                PROXY_METRICS.record_timeout(TimeoutPhase::StreamIdle.as_str());
+                tracing::warn!(tool_id = %idle_tool_id, "上游流式响应空闲超时，已中断");
+            },
+        );
+        let stream: Pin<Box<dyn Stream<Item = Result<Bytes, StreamError>> + Send>> =
+            match &translation {
+                Some(context) => Box::pin(translation::translate_sse_stream(
+                    stream,
+                    context.model.clone(),
+                )),
+                None => Box::pin(stream),
+            };
+        // recorder 与请求名额随流一起释放，流结束时完成捕获并归还名额
+        let mut usage_tracker = StreamUsageTracker::new(&provider_label, &path, &session.id);
+        let mapped_stream = stream.map(move |result| {
+            let _ = &in_flight_guard;
+            if let Ok(chunk) = &result {
+                usage_tracker.scanner.feed(chunk);
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.append_response(chunk);
+                }
+            }
+            result.map(Frame::data)
         });
 
         let body = http_body_util::StreamBody::new(mapped_stream);
         Ok(response.body(box_body(body)).unwrap())
     } else {
-        // 普通响应
-        let body_bytes = upstream_res.bytes().await.context("读取响应体失败")?;
+        // 普通响应（受总时长限制）
+        let body_bytes = match timeouts::remaining_total(&timeouts_config, started_at.elapsed()) {
+            Some(remaining) => match tokio::time::timeout(remaining, upstream_res.bytes()).await {
+                Ok(bytes) => bytes.context("读取响应体失败")?,
+                Err(_) => {
+                    PROXY_METRICS.record_timeout(TimeoutPhase::Total.as_str());
+                    tracing::warn!(
+                        tool_id = %tool_id,
+                        provider = %provider_label,
+                        total_secs = timeouts_config.total_secs,
+                        "读取上游响应体超时"
+                    );
+                    return Ok(error_responses::upstream_timeout(
+                        tool_id,
+                        TimeoutPhase::Total.as_str(),
+                        timeouts_config.total_secs,
+                    ));
+                }
+            },
+            None => upstream_res.bytes().await.context("读取响应体失败")?,
+        };
+        drop(in_flight_guard);
+        if let Some(mut recorder) = recorder {
+            recorder.append_response(&body_bytes);
+        }
+        if status.is_success() {
+            if let Some(usage) = usage::from_body(&body_bytes) {
+                PROXY_METRICS.record_tokens(&provider_label, &path, usage);
+                PROXY_SESSIONS.record_tokens(&session.id, usage);
+            }
+        }
+
+        // 协议转换：将 OpenAI 响应转换回 Anthropic 格式（错误响应交由下方规范化处理）
+        let body_bytes = match &translation {
+            Some(context) if status.is_success() => {
+                match translation::translate_response(&body_bytes, context) {
+                    Ok(translated) => {
+                        set_json_content_type(&mut response);
+                        translated
+                    }
+                    Err(reason) => {
+                        tracing::warn!(
+                            tool_id = %tool_id,
+                            provider = %provider_label,
+                            reason = %reason,
+                            "上游响应协议转换失败"
+                        );
+                        return Ok(error_responses::translation_failed(
+                            tool_id,
+                            StatusCode::BAD_GATEWAY,
+                            &reason,
+                        ));
+                    }
+                }
+            }
+            _ => body_bytes,
+        };
+
+        // 非标准的上游错误（HTML、纯文本等）包装为客户端期望的错误格式
+        let header_str = |name: &str| {
+            response
+                .headers_ref()
+                .and_then(|h| h.get(name))
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let normalized = upstream_errors::normalize(
+            upstream_errors::ErrorSchema::for_request(tool_id, &path),
+            status.as_u16(),
+            header_str("content-type").as_deref(),
+            header_str("content-encoding").as_deref(),
+            &body_bytes,
+        );
+        let body_bytes = match normalized {
+            Normalized::Untouched => body_bytes,
+            Normalized::FixContentType => {
+                set_json_content_type(&mut response);
+                body_bytes
+            }
+            Normalized::Rewritten(rewritten) => {
+                tracing::warn!(
+                    tool_id = %tool_id,
+                    provider = %provider_label,
+                    status = status.as_u16(),
+                    "上游返回非标准错误响应，已规范化"
+                );
+                set_json_content_type(&mut response);
+                rewritten
+            }
+        };
         Ok(response
             .body(box_body(http_body_util::Full::new(body_bytes)))
             .unwrap())
     }
 }
+
+/// 转发给客户端的流式响应错误类型
+type StreamError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 流式响应的 token 用量统计（流结束或客户端断开时记录）
+struct StreamUsageTracker {
+    provider: String,
+    path: String,
+    session_id: String,
+    scanner: SseUsageScanner,
+}
+
+impl StreamUsageTracker {
+    fn new(provider: &str, path: &str, session_id: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            path: path.to_string(),
+            session_id: session_id.to_string(),
+            scanner: SseUsageScanner::new(),
+        }
+    }
+}
+
+impl Drop for StreamUsageTracker {
+    fn drop(&mut self) {
+        let usage = self.scanner.usage();
+        PROXY_METRICS.record_tokens(&self.provider, &self.path, usage);
+        PROXY_SESSIONS.record_tokens(&self.session_id, usage);
+    }
+}
+
+/// 替换响应的 Content-Type 为 JSON（响应体被改写时同时移除原 Content-Length）
+fn set_json_content_type(response: &mut hyper::http::response::Builder) {
+    if let Some(headers) = response.headers_mut() {
+        headers.remove(hyper::header::CONTENT_LENGTH);
+        headers.insert(
+            hyper::header::CONTENT_TYPE,
+            hyper::header::HeaderValue::from_static("application/json"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::proxy::headers::ProcessedRequest;
+    use async_trait::async_trait;
+    use hyper::HeaderMap;
+    use reqwest::header::HeaderMap as ReqwestHeaderMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::oneshot;
+
+    /// 直接拼接 base_url + path 的测试处理器
+    #[derive(Debug)]
+    struct PassthroughProcessor;
+
+    #[async_trait]
+    impl RequestProcessor for PassthroughProcessor {
+        fn tool_id(&self) -> &str {
+            "claude-code"
+        }
+
+        async fn process_outgoing_request(
+            &self,
+            base_url: &str,
+            _api_key: &str,
+            path: &str,
+            _query: Option<&str>,
+            _original_headers: &HeaderMap,
+            body: &[u8],
+        ) -> Result<ProcessedRequest> {
+            Ok(ProcessedRequest {
+                target_url: format!("{}{path}", base_url.trim_end_matches('/')),
+                headers: ReqwestHeaderMap::new(),
+                body: Bytes::copy_from_slice(body),
+            })
+        }
+    }
+
+    /// 启动只响应一次的模拟上游：先发送 `first`，收到 `release` 后再发送 `rest`
+    async fn spawn_upstream(
+        first: &'static str,
+        rest: &'static str,
+        release: Option<oneshot::Receiver<()>>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+
+            let chunk = |data: &str| format!("{:x}\r\n{data}\r\n", data.len());
+            let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\
+                        transfer-encoding: chunked\r\n\r\n";
+            stream
+                .write_all(format!("{head}{}", chunk(first)).as_bytes())
+                .await
+                .unwrap();
+            if let Some(release) = release {
+                let _ = release.await;
+            }
+            stream
+                .write_all(format!("{}0\r\n\r\n", chunk(rest)).as_bytes())
+                .await
+                .unwrap();
+        });
+        format!("http://{addr}")
+    }
+
+    fn test_config(port: u16, base_url: &str) -> ToolProxyConfig {
+        let mut config = ToolProxyConfig::new(port);
+        config.enabled = true;
+        config.real_api_key = Some("sk-test".to_string());
+        config.real_base_url = Some(base_url.to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn test_config_swap_keeps_running_stream_on_old_provider() {
+        let (release_tx, release_rx) = oneshot::channel();
+        let upstream_a = spawn_upstream("a1", "a2", Some(release_rx)).await;
+        let upstream_b = spawn_upstream("b1", "b2", None).await;
+
+        let port = {
+            let probe = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            probe.local_addr().unwrap().port()
+        };
+        let instance = ProxyInstance::new(
+            "claude-code".to_string(),
+            test_config(port, &upstream_a),
+            Box::new(PassthroughProcessor),
+        );
+        instance.start().await.unwrap();
+
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let url = format!("http://127.0.0.1:{port}/v1/messages");
+
+        // 流式请求已开始并收到首个分块
+        let mut running = client.get(&url).send().await.unwrap();
+        assert_eq!(running.chunk().await.unwrap().unwrap(), "a1");
+
+        // 流进行中切换供应商
+        instance
+            .update_config(test_config(port, &upstream_b))
+            .await
+            .unwrap();
+
+        // 新请求使用新供应商
+        let body = client.get(&url).send().await.unwrap().text().await.unwrap();
+        assert_eq!(body, "b1b2");
+
+        // 进行中的流继续从旧供应商完成
+        release_tx.send(()).unwrap();
+        let mut rest = Vec::new();
+        while let Some(chunk) = running.chunk().await.unwrap() {
+            rest.extend_from_slice(&chunk);
+        }
+        assert_eq!(rest, b"a2");
+
+        instance.stop().await.unwrap();
+    }
+}
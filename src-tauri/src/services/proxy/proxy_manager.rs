@@ -4,29 +4,44 @@
 // - 启动和停止指定工具的代理
 // - 管理所有代理实例的状态
 // - 确保端口不冲突
+// - 广播代理状态变化事件（启动、重启、崩溃、停止）
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 
 use super::headers::create_request_processor;
+use super::limits::ProxyUtilization;
 use super::proxy_instance::ProxyInstance;
+use super::supervisor::{ProxyRunState, ProxyStatusEvent};
+use crate::models::dashboard::ActivityKind;
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::models::telemetry::TelemetryEvent;
+use crate::services::dashboard_manager::record_activity;
+use crate::services::telemetry::record_event;
 
 /// 代理管理器
 pub struct ProxyManager {
     instances: Arc<RwLock<HashMap<String, ProxyInstance>>>,
+    events: broadcast::Sender<ProxyStatusEvent>,
 }
 
 impl ProxyManager {
     /// 创建新的代理管理器
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(64);
         Self {
             instances: Arc::new(RwLock::new(HashMap::new())),
+            events,
         }
     }
 
+    /// 订阅代理状态变化事件
+    pub fn subscribe(&self) -> broadcast::Receiver<ProxyStatusEvent> {
+        self.events.subscribe()
+    }
+
     /// 启动指定工具的代理
     ///
     /// # 参数
@@ -61,8 +76,11 @@ impl ProxyManager {
         // 创建 RequestProcessor
         let processor = create_request_processor(tool_id).context("创建请求处理器失败")?;
 
+        let port = config.port;
+
         // 创建并启动代理实例
-        let instance = ProxyInstance::new(tool_id.to_string(), config, processor);
+        let instance =
+            ProxyInstance::with_events(tool_id.to_string(), config, processor, self.events.clone());
         instance
             .start()
             .await
@@ -74,6 +92,12 @@ impl ProxyManager {
             instances.insert(tool_id.to_string(), instance);
         }
 
+        record_activity(
+            ActivityKind::ProxyStarted,
+            tool_id,
+            Some(format!("端口 {port}")),
+        );
+        record_event(TelemetryEvent::ProxyStarted);
         Ok(())
     }
 
@@ -93,6 +117,7 @@ impl ProxyManager {
                 .stop()
                 .await
                 .context(format!("停止 {tool_id} 代理失败"))?;
+            record_activity(ActivityKind::ProxyStopped, tool_id, None);
         } else {
             tracing::warn!(tool_id = %tool_id, "代理未运行或不存在");
         }
@@ -151,6 +176,30 @@ impl ProxyManager {
         status_map
     }
 
+    /// 获取所有已创建代理实例的运行状态（包含重启中、已崩溃）
+    pub async fn get_all_states(&self) -> HashMap<String, ProxyRunState> {
+        let instances = self.instances.read().await;
+        let mut state_map = HashMap::new();
+
+        for (tool_id, instance) in instances.iter() {
+            state_map.insert(tool_id.clone(), instance.state().await);
+        }
+
+        state_map
+    }
+
+    /// 获取所有已创建代理实例的并发使用情况
+    pub async fn get_all_utilization(&self) -> HashMap<String, ProxyUtilization> {
+        let instances = self.instances.read().await;
+        let mut utilization_map = HashMap::new();
+
+        for (tool_id, instance) in instances.iter() {
+            utilization_map.insert(tool_id.clone(), instance.utilization().await);
+        }
+
+        utilization_map
+    }
+
     /// 更新指定工具的代理配置（无需重启）
     pub async fn update_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<()> {
         let instances = self.instances.read().await;
@@ -166,6 +215,41 @@ impl ProxyManager {
 
         Ok(())
     }
+
+    /// 热切换运行中代理的配置
+    ///
+    /// 端口或监听地址变化时重启监听，否则原地更新（不中断进行中的请求）。
+    /// 代理未运行时不做任何操作。返回是否重启了监听。
+    pub async fn reload_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<bool> {
+        let current = {
+            let instances = self.instances.read().await;
+            match instances.get(tool_id) {
+                Some(instance) if instance.is_running_async().await => instance.config().await,
+                _ => return Ok(false),
+            }
+        };
+
+        if current.port == config.port && current.allow_public == config.allow_public {
+            self.update_config(tool_id, config).await?;
+            return Ok(false);
+        }
+
+        tracing::info!(
+            tool_id = %tool_id,
+            old_port = current.port,
+            new_port = config.port,
+            "监听地址变化，重启代理"
+        );
+        self.stop_proxy(tool_id).await?;
+        if let Err(e) = self.start_proxy(tool_id, config).await {
+            // 新地址启动失败（如端口占用），回退到原配置
+            if let Err(rollback_err) = self.start_proxy(tool_id, current).await {
+                tracing::error!(tool_id = %tool_id, error = ?rollback_err, "回退代理配置失败");
+            }
+            return Err(e);
+        }
+        Ok(true)
+    }
 }
 
 impl Default for ProxyManager {
@@ -193,5 +277,26 @@ mod tests {
         assert!(status.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_stop_disables_supervisor() {
+        let manager = ProxyManager::new();
+        let mut events = manager.subscribe();
+
+        let config = ToolProxyConfig::new(0);
+        manager.start_proxy("claude-code", config).await.unwrap();
+        assert_eq!(
+            manager.get_all_states().await.get("claude-code"),
+            Some(&ProxyRunState::Running)
+        );
+
+        manager.stop_proxy("claude-code").await.unwrap();
+        assert!(manager.get_all_states().await.is_empty());
+
+        let first = events.recv().await.unwrap();
+        assert_eq!(first.state, ProxyRunState::Running);
+        let second = events.recv().await.unwrap();
+        assert_eq!(second.state, ProxyRunState::Stopped);
+    }
+
     // 更多测试需要 mock 或集成测试环境
 }
@@ -0,0 +1,124 @@
+//! 上游限流/过载重试
+//!
+//! 提供可重试状态判断、Retry-After 解析和退避计算，
+//! 由 `proxy_instance` 在收到上游响应、尚未向客户端写出数据前使用。
+
+use hyper::HeaderMap;
+use std::time::Duration;
+
+use crate::models::proxy_config::{RetryPolicyConfig, ToolProxyConfig};
+
+/// 是否为可重试状态码
+///
+/// - 429：限流
+/// - 529：Anthropic `overloaded_error`
+/// - 503：中转站常用的过载状态
+pub fn is_retryable_status(status: u16) -> bool {
+    matches!(status, 429 | 503 | 529)
+}
+
+/// 解析 Retry-After（支持秒数与 HTTP-date 两种格式）
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get("retry-after")?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// 选择适用于指定供应商的重试策略（供应商覆盖优先）
+pub fn resolve_policy<'a>(config: &'a ToolProxyConfig, provider: &str) -> &'a RetryPolicyConfig {
+    config
+        .provider_retry_overrides
+        .get(provider)
+        .unwrap_or(&config.retry_policy)
+}
+
+/// 计算第 `attempt` 次重试前的等待时间（从 1 开始）
+///
+/// 优先使用 Retry-After，否则按指数退避；均受 `max_delay_ms` 约束。
+pub fn retry_delay(
+    policy: &RetryPolicyConfig,
+    attempt: u32,
+    retry_after: Option<Duration>,
+) -> Duration {
+    let max = Duration::from_millis(policy.max_delay_ms);
+    let backoff = Duration::from_millis(policy.base_delay_ms)
+        .saturating_mul(1u32 << attempt.saturating_sub(1).min(16));
+    retry_after.unwrap_or(backoff).min(max)
+}
+
+/// 请求是否满足重试前提（策略启用且请求体已缓冲并在上限内）
+pub fn can_retry(policy: &RetryPolicyConfig, body_len: usize) -> bool {
+    policy.enabled && policy.max_retries > 0 && body_len <= policy.max_body_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(429));
+        assert!(is_retryable_status(529));
+        assert!(is_retryable_status(503));
+        assert!(!is_retryable_status(500));
+        assert!(!is_retryable_status(200));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert("retry-after", "3".parse().unwrap());
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(3)));
+
+        headers.insert(
+            "retry-after",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicyConfig {
+            enabled: true,
+            base_delay_ms: 500,
+            max_delay_ms: 3_000,
+            ..Default::default()
+        };
+        assert_eq!(retry_delay(&policy, 1, None), Duration::from_millis(500));
+        assert_eq!(retry_delay(&policy, 3, None), Duration::from_secs(2));
+        assert_eq!(retry_delay(&policy, 5, None), Duration::from_secs(3));
+        assert_eq!(
+            retry_delay(&policy, 1, Some(Duration::from_secs(60))),
+            Duration::from_secs(3)
+        );
+    }
+
+    #[test]
+    fn test_resolve_policy_and_body_cap() {
+        let mut config = ToolProxyConfig::new(8787);
+        config.retry_policy.enabled = true;
+        config.provider_retry_overrides.insert(
+            "strict-relay".to_string(),
+            RetryPolicyConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        );
+
+        assert!(resolve_policy(&config, "api.anthropic.com").enabled);
+        assert!(!resolve_policy(&config, "strict-relay").enabled);
+
+        let policy = &config.retry_policy;
+        assert!(can_retry(policy, 1024));
+        assert!(!can_retry(policy, policy.max_body_bytes + 1));
+    }
+}
@@ -0,0 +1,333 @@
+//! 代理请求的会话关联
+//!
+//! 为每个代理请求分配会话 ID，用于在访问日志与调试捕获中归组同一会话的请求：
+//! - 请求携带 `X-Session-Id` 时直接使用（按工具隔离）
+//! - 否则由「工具 + 客户端端口 + User-Agent + 30 分钟时间桶」哈希得到
+//!
+//! 推导规则刻意保守：不同工具、不同连接或不同客户端的请求不会被归入同一会话，
+//! 宁可把一个长会话拆成多段，也不把无关请求合并。会话统计只保存在内存中。
+
+use chrono::{DateTime, Utc};
+use hyper::HeaderMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::metrics::MetricsRange;
+use super::usage::TokenUsage;
+
+/// 客户端显式指定会话 ID 的请求头
+pub const SESSION_HEADER: &str = "x-session-id";
+
+/// 推导会话 ID 时使用的时间桶长度（秒）
+const SESSION_BUCKET_SECS: i64 = 30 * 60;
+
+/// 客户端指定的会话 ID 最大长度
+const MAX_SESSION_ID_LEN: usize = 128;
+
+/// 最多保留的会话数（超出时淘汰最久未活动的会话）
+const MAX_SESSIONS: usize = 500;
+
+/// 会话统计保留时长（秒）
+const SESSION_RETENTION_SECS: i64 = 24 * 3600;
+
+/// 全局会话统计
+pub static PROXY_SESSIONS: Lazy<SessionTracker> = Lazy::new(SessionTracker::new);
+
+/// 会话 ID 来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSource {
+    /// 请求头 `X-Session-Id`
+    Header,
+    /// 按客户端连接特征推导
+    Derived,
+}
+
+/// 单次请求的会话标识
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionKey {
+    pub id: String,
+    pub source: SessionSource,
+}
+
+/// 为请求分配会话 ID
+pub fn session_for_request(
+    tool_id: &str,
+    client_port: u16,
+    headers: &HeaderMap,
+    now: DateTime<Utc>,
+) -> SessionKey {
+    let header_value = headers
+        .get(SESSION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && v.len() <= MAX_SESSION_ID_LEN);
+    if let Some(value) = header_value {
+        // 按工具加前缀，避免不同工具恰好使用相同 ID 时被合并
+        return SessionKey {
+            id: format!("{tool_id}:{value}"),
+            source: SessionSource::Header,
+        };
+    }
+
+    let user_agent = headers
+        .get(hyper::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    let bucket = now.timestamp().div_euclid(SESSION_BUCKET_SECS);
+
+    let mut hasher = Sha256::new();
+    hasher.update(tool_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(client_port.to_be_bytes());
+    hasher.update(user_agent.as_bytes());
+    hasher.update([0]);
+    hasher.update(bucket.to_be_bytes());
+    let digest = hasher.finalize();
+    let short: String = digest[..6].iter().map(|b| format!("{b:02x}")).collect();
+
+    SessionKey {
+        id: format!("{tool_id}:{short}"),
+        source: SessionSource::Derived,
+    }
+}
+
+/// 会话统计（返回给前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxySession {
+    pub session_id: String,
+    pub tool_id: String,
+    pub source: SessionSource,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub request_count: u64,
+    pub error_count: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+#[derive(Debug, Clone)]
+struct SessionStats {
+    tool_id: String,
+    source: SessionSource,
+    user_agent: Option<String>,
+    first_seen: DateTime<Utc>,
+    last_seen: DateTime<Utc>,
+    request_count: u64,
+    error_count: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// 会话统计存储
+pub struct SessionTracker {
+    sessions: Mutex<HashMap<String, SessionStats>>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 记录一次请求完成
+    pub fn record_request(
+        &self,
+        key: &SessionKey,
+        tool_id: &str,
+        user_agent: Option<&str>,
+        status: u16,
+    ) {
+        self.record_request_at(Utc::now(), key, tool_id, user_agent, status);
+    }
+
+    fn record_request_at(
+        &self,
+        now: DateTime<Utc>,
+        key: &SessionKey,
+        tool_id: &str,
+        user_agent: Option<&str>,
+        status: u16,
+    ) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let stats = sessions
+            .entry(key.id.clone())
+            .or_insert_with(|| SessionStats {
+                tool_id: tool_id.to_string(),
+                source: key.source,
+                user_agent: user_agent.map(str::to_string),
+                first_seen: now,
+                last_seen: now,
+                request_count: 0,
+                error_count: 0,
+                input_tokens: 0,
+                output_tokens: 0,
+            });
+        stats.last_seen = stats.last_seen.max(now);
+        stats.request_count += 1;
+        if status >= 400 {
+            stats.error_count += 1;
+        }
+        Self::evict(&mut sessions, now);
+    }
+
+    /// 累加会话的 token 用量
+    pub fn record_tokens(&self, session_id: &str, usage: TokenUsage) {
+        if let Some(stats) = self.sessions.lock().unwrap().get_mut(session_id) {
+            stats.input_tokens = stats.input_tokens.saturating_add(usage.input_tokens);
+            stats.output_tokens = stats.output_tokens.saturating_add(usage.output_tokens);
+        }
+    }
+
+    fn evict(sessions: &mut HashMap<String, SessionStats>, now: DateTime<Utc>) {
+        let cutoff = now - chrono::Duration::seconds(SESSION_RETENTION_SECS);
+        sessions.retain(|_, s| s.last_seen > cutoff);
+        while sessions.len() > MAX_SESSIONS {
+            let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, s)| s.last_seen)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            sessions.remove(&oldest);
+        }
+    }
+
+    /// 查询范围内活跃过的会话（按最近活动时间倒序）
+    pub fn sessions(&self, range: MetricsRange) -> Vec<ProxySession> {
+        self.sessions_at(Utc::now(), range)
+    }
+
+    fn sessions_at(&self, now: DateTime<Utc>, range: MetricsRange) -> Vec<ProxySession> {
+        let since = now - chrono::Duration::seconds(range.seconds());
+        let mut result: Vec<ProxySession> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, s)| s.last_seen > since)
+            .map(|(id, s)| ProxySession {
+                session_id: id.clone(),
+                tool_id: s.tool_id.clone(),
+                source: s.source,
+                user_agent: s.user_agent.clone(),
+                first_seen: s.first_seen,
+                last_seen: s.last_seen,
+                duration_ms: (s.last_seen - s.first_seen).num_milliseconds().max(0) as u64,
+                request_count: s.request_count,
+                error_count: s.error_count,
+                input_tokens: s.input_tokens,
+                output_tokens: s.output_tokens,
+                total_tokens: s.input_tokens.saturating_add(s.output_tokens),
+            })
+            .collect();
+        result.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        result
+    }
+}
+
+impl Default for SessionTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_header_session_id_is_scoped_by_tool() {
+        let now = Utc::now();
+        let h = headers(&[("x-session-id", "abc")]);
+        let claude = session_for_request("claude-code", 50000, &h, now);
+        let codex = session_for_request("codex", 50000, &h, now);
+        assert_eq!(claude.source, SessionSource::Header);
+        assert_eq!(claude.id, "claude-code:abc");
+        assert_ne!(claude.id, codex.id);
+    }
+
+    #[test]
+    fn test_derived_session_is_conservative() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let ua = headers(&[("user-agent", "claude-cli/1.0")]);
+        let base = session_for_request("claude-code", 50000, &ua, now);
+        assert_eq!(base.source, SessionSource::Derived);
+
+        // 同一连接、同一客户端、同一时间桶内归为同一会话
+        let same = session_for_request(
+            "claude-code",
+            50000,
+            &ua,
+            now + chrono::Duration::seconds(60),
+        );
+        assert_eq!(base, same);
+
+        // 任一特征不同都视为不同会话
+        assert_ne!(base, session_for_request("codex", 50000, &ua, now));
+        assert_ne!(base, session_for_request("claude-code", 50001, &ua, now));
+        let other_ua = headers(&[("user-agent", "curl/8.0")]);
+        assert_ne!(
+            base,
+            session_for_request("claude-code", 50000, &other_ua, now)
+        );
+        assert_ne!(
+            base,
+            session_for_request("claude-code", 50000, &ua, now + chrono::Duration::hours(1))
+        );
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let tracker = SessionTracker::new();
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let key = SessionKey {
+            id: "claude-code:abc".to_string(),
+            source: SessionSource::Header,
+        };
+        tracker.record_request_at(now, &key, "claude-code", Some("claude-cli"), 200);
+        tracker.record_request_at(
+            now + chrono::Duration::seconds(90),
+            &key,
+            "claude-code",
+            Some("claude-cli"),
+            529,
+        );
+        tracker.record_tokens(
+            &key.id,
+            TokenUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+            },
+        );
+
+        let sessions =
+            tracker.sessions_at(now + chrono::Duration::seconds(120), MetricsRange::LastHour);
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.request_count, 2);
+        assert_eq!(session.error_count, 1);
+        assert_eq!(session.total_tokens, 120);
+        assert_eq!(session.duration_ms, 90_000);
+
+        let later = tracker.sessions_at(now + chrono::Duration::hours(3), MetricsRange::LastHour);
+        assert!(later.is_empty());
+    }
+}
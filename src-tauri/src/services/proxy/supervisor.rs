@@ -0,0 +1,128 @@
+//! 代理监听任务守护
+//!
+//! 监听任务异常退出（panic 或意外结束）时按指数退避自动重启，
+//! 超过重启上限后标记为崩溃并通过事件通知前端。
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 代理状态变化事件名称（前端监听）
+pub const PROXY_STATUS_EVENT: &str = "proxy-status-changed";
+
+/// 代理崩溃事件名称（重启次数耗尽后发送）
+pub const PROXY_CRASHED_EVENT: &str = "proxy-crashed";
+
+/// 代理运行状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ProxyRunState {
+    /// 未运行
+    Stopped,
+    /// 正常运行
+    Running,
+    /// 监听任务异常退出，等待重启
+    Restarting { attempt: u32 },
+    /// 重启次数耗尽，代理已停止
+    Crashed { reason: String },
+}
+
+impl ProxyRunState {
+    /// 是否处于活动状态（运行中或正在重启）
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Running | Self::Restarting { .. })
+    }
+}
+
+/// 代理状态变化事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyStatusEvent {
+    pub tool_id: String,
+    #[serde(flatten)]
+    pub state: ProxyRunState,
+    /// 附加说明（如退出原因）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 重启策略
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    /// 最大连续重启次数
+    pub max_restarts: u32,
+    /// 首次重启延迟
+    pub base_delay: Duration,
+    /// 最大重启延迟
+    pub max_delay: Duration,
+    /// 监听任务稳定运行超过该时长后重置重启计数
+    pub reset_after: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            reset_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// 计算第 `attempt` 次重启前的等待时间（从 1 开始，指数退避）
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        self.base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay)
+    }
+}
+
+/// 从 panic 负载中提取可读信息
+pub fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知 panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_exponential_backoff() {
+        let policy = RestartPolicy::default();
+        assert_eq!(policy.delay_for(1), Duration::from_millis(500));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(20), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_state_serialization() {
+        let event = ProxyStatusEvent {
+            tool_id: "claude-code".to_string(),
+            state: ProxyRunState::Restarting { attempt: 2 },
+            message: None,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["tool_id"], "claude-code");
+        assert_eq!(json["state"], "restarting");
+        assert_eq!(json["attempt"], 2);
+
+        assert!(ProxyRunState::Running.is_active());
+        assert!(!ProxyRunState::Stopped.is_active());
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new("boom");
+        assert_eq!(panic_message(payload), "boom");
+        let payload: Box<dyn std::any::Any + Send> = Box::new(String::from("bang"));
+        assert_eq!(panic_message(payload), "bang");
+    }
+}
@@ -0,0 +1,204 @@
+//! 上游请求超时
+//!
+//! 分阶段限制上游耗时，避免失效的中转站让客户端连接无限挂起：
+//! - 连接超时：建立 TCP/TLS 连接
+//! - 首字节超时：发出请求到收到响应头
+//! - 总时长：非流式响应从请求开始到读完响应体
+//! - 流空闲超时：流式响应相邻两个分块之间的间隔
+//!
+//! 超时值每次请求从配置读取，修改后立即生效；0 表示不限制。
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use std::time::Duration;
+
+use crate::models::proxy_config::UpstreamTimeouts;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 超时阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutPhase {
+    Connect,
+    FirstByte,
+    Total,
+    StreamIdle,
+}
+
+impl TimeoutPhase {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::FirstByte => "first_byte",
+            Self::Total => "total",
+            Self::StreamIdle => "stream_idle",
+        }
+    }
+
+    /// 该阶段配置的超时秒数
+    pub fn limit_secs(self, timeouts: &UpstreamTimeouts) -> u64 {
+        match self {
+            Self::Connect => timeouts.connect_secs,
+            Self::FirstByte => timeouts.first_byte_secs,
+            Self::Total => timeouts.total_secs,
+            Self::StreamIdle => timeouts.stream_idle_secs,
+        }
+    }
+}
+
+/// 上游请求失败原因
+#[derive(Debug)]
+pub enum UpstreamFailure {
+    Timeout(TimeoutPhase),
+    Request(reqwest::Error),
+}
+
+/// 流式响应空闲超时错误（响应头已发出，只能中断流）
+#[derive(Debug)]
+pub struct StreamIdleTimeout {
+    pub idle_secs: u64,
+}
+
+impl std::fmt::Display for StreamIdleTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "上游流式响应超过 {} 秒无数据", self.idle_secs)
+    }
+}
+
+impl std::error::Error for StreamIdleTimeout {}
+
+fn limit(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// 按配置创建上游 HTTP 客户端（设置连接超时）
+pub fn build_client(timeouts: &UpstreamTimeouts) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(connect) = limit(timeouts.connect_secs) {
+        builder = builder.connect_timeout(connect);
+    }
+    builder.build()
+}
+
+/// 发送请求并等待响应头（首字节超时）
+pub async fn send(
+    request: reqwest::RequestBuilder,
+    timeouts: &UpstreamTimeouts,
+) -> Result<reqwest::Response, UpstreamFailure> {
+    let result = match limit(timeouts.first_byte_secs) {
+        Some(first_byte) => tokio::time::timeout(first_byte, request.send())
+            .await
+            .map_err(|_| UpstreamFailure::Timeout(TimeoutPhase::FirstByte))?,
+        None => request.send().await,
+    };
+
+    result.map_err(|e| {
+        if e.is_connect() && e.is_timeout() {
+            UpstreamFailure::Timeout(TimeoutPhase::Connect)
+        } else {
+            UpstreamFailure::Request(e)
+        }
+    })
+}
+
+/// 非流式响应剩余可用时间（`elapsed` 为请求开始至今的耗时）
+pub fn remaining_total(timeouts: &UpstreamTimeouts, elapsed: Duration) -> Option<Duration> {
+    limit(timeouts.total_secs).map(|total| total.saturating_sub(elapsed))
+}
+
+/// 为流式响应添加分块间空闲超时，超时后输出一个错误并结束流
+pub fn with_idle_timeout<S, E, F>(
+    stream: S,
+    idle_secs: u64,
+    on_timeout: F,
+) -> impl Stream<Item = Result<Bytes, BoxError>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Into<BoxError>,
+    F: FnOnce() + Send + 'static,
+{
+    let idle = limit(idle_secs);
+    let state = (Box::pin(stream), Some(on_timeout), false);
+
+    futures_util::stream::unfold(
+        state,
+        move |(mut stream, mut on_timeout, finished)| async move {
+            if finished {
+                return None;
+            }
+            let next = match idle {
+                Some(idle) => match tokio::time::timeout(idle, stream.next()).await {
+                    Ok(next) => next,
+                    Err(_) => {
+                        if let Some(callback) = on_timeout.take() {
+                            callback();
+                        }
+                        let err: BoxError = Box::new(StreamIdleTimeout { idle_secs });
+                        return Some((Err(err), (stream, on_timeout, true)));
+                    }
+                },
+                None => stream.next().await,
+            };
+            next.map(|item| (item.map_err(Into::into), (stream, on_timeout, false)))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_zero_disables_limit() {
+        let timeouts = UpstreamTimeouts {
+            total_secs: 0,
+            ..Default::default()
+        };
+        assert_eq!(remaining_total(&timeouts, Duration::from_secs(999)), None);
+
+        let timeouts = UpstreamTimeouts {
+            total_secs: 10,
+            ..Default::default()
+        };
+        assert_eq!(
+            remaining_total(&timeouts, Duration::from_secs(4)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            remaining_total(&timeouts, Duration::from_secs(20)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_idle_timeout() {
+        let fired = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&fired);
+
+        let source = futures_util::stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from("a"))])
+            .chain(futures_util::stream::pending());
+        let items: Vec<_> = with_idle_timeout(source, 1, move || {
+            flag.store(true, Ordering::SeqCst);
+        })
+        .collect()
+        .await;
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].as_ref().unwrap(), &Bytes::from("a"));
+        assert!(items[1].is_err());
+        assert!(fired.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_stream_without_idle_limit_passes_through() {
+        let source = futures_util::stream::iter(vec![
+            Ok::<_, std::io::Error>(Bytes::from("a")),
+            Ok(Bytes::from("b")),
+        ]);
+        let items: Vec<_> = with_idle_timeout(source, 0, || {}).collect().await;
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.is_ok()));
+    }
+}
@@ -0,0 +1,412 @@
+//! 一键将工具指向本地透明代理
+//!
+//! 直接修改工具原生配置中的 Base URL（以及启用本地密钥时的 API Key），
+//! 修改前的原始值记录在 `ToolProxyConfig::tool_routing` 中，还原时按原样写回：
+//! - Claude Code：`settings.json` 的 `env.ANTHROPIC_BASE_URL` / `env.ANTHROPIC_AUTH_TOKEN`
+//! - Codex：`config.toml` 中当前 `model_provider` 的 `base_url`，以及 `auth.json` 的 `OPENAI_API_KEY`
+//! - Gemini CLI：`.env` 的 `GOOGLE_GEMINI_BASE_URL` / `GEMINI_API_KEY`
+
+use anyhow::{anyhow, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::ProxyManager;
+use crate::data::DataManager;
+use crate::models::proxy_config::{ToolProxyConfig, ToolRoutingState};
+use crate::models::Tool;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+
+/// 配置项 → 值（`None` 表示删除该项）
+pub type SettingValues = BTreeMap<String, Option<String>>;
+
+const CODEX_API_KEY: &str = "OPENAI_API_KEY";
+
+/// 将工具指向本地代理
+///
+/// 代理未运行或未绑定上游供应商时拒绝执行；重复执行只更新代理地址，保留最初的原始值。
+pub async fn configure_tool_for_proxy(
+    manager: &ProxyManager,
+    tool_id: &str,
+) -> Result<ToolRoutingState> {
+    if !manager.is_running(tool_id).await {
+        anyhow::bail!("{tool_id} 透明代理未运行，请先启动代理");
+    }
+
+    let proxy_mgr = ProxyConfigManager::new()?;
+    let mut config = proxy_mgr
+        .get_config(tool_id)?
+        .ok_or_else(|| anyhow!("未找到 {tool_id} 的代理配置"))?;
+    if !has_bound_provider(&config) {
+        anyhow::bail!(
+            "{tool_id} 透明代理未绑定上游供应商，请先配置凭证注入或真实 API Key/Base URL"
+        );
+    }
+
+    let proxy_url = format!("http://127.0.0.1:{}", config.port);
+    let tool = tool_by_id(tool_id)?;
+    let values = proxy_settings(
+        tool_id,
+        &tool.config_dir,
+        &proxy_url,
+        config.local_api_key.as_deref(),
+    )?;
+    let previous = swap_settings(tool_id, &tool.config_dir, &values)?;
+
+    // 已接入时保留最初的原始值，仅补充新出现的配置项
+    let mut originals = config
+        .tool_routing
+        .take()
+        .map(|state| state.originals)
+        .unwrap_or_default();
+    for (key, value) in previous {
+        originals.entry(key).or_insert(value);
+    }
+
+    let state = ToolRoutingState {
+        proxy_url,
+        applied_at: chrono::Utc::now(),
+        originals,
+    };
+    config.tool_routing = Some(state.clone());
+    if let Err(e) = proxy_mgr.update_config(tool_id, config) {
+        // 记录失败时回滚工具配置，避免丢失原始值
+        if let Err(rollback_err) = swap_settings(tool_id, &tool.config_dir, &state.originals) {
+            tracing::error!(tool_id = %tool_id, error = ?rollback_err, "回滚工具配置失败");
+        }
+        return Err(e.context("保存代理接入记录失败"));
+    }
+
+    tracing::info!(tool_id = %tool_id, proxy_url = %state.proxy_url, "工具已指向本地代理");
+    Ok(state)
+}
+
+/// 还原工具接入代理前的原始配置
+pub fn unconfigure_tool_for_proxy(tool_id: &str) -> Result<()> {
+    let proxy_mgr = ProxyConfigManager::new()?;
+    let mut config = proxy_mgr
+        .get_config(tool_id)?
+        .ok_or_else(|| anyhow!("未找到 {tool_id} 的代理配置"))?;
+    let state = config
+        .tool_routing
+        .take()
+        .ok_or_else(|| anyhow!("{tool_id} 未通过代理接入，无需还原"))?;
+
+    let tool = tool_by_id(tool_id)?;
+    swap_settings(tool_id, &tool.config_dir, &state.originals)?;
+    proxy_mgr.update_config(tool_id, config)?;
+
+    tracing::info!(tool_id = %tool_id, "已还原工具的原始配置");
+    Ok(())
+}
+
+/// 是否已绑定上游供应商（凭证注入或真实凭证二选一）
+pub fn has_bound_provider(config: &ToolProxyConfig) -> bool {
+    let injection = &config.credential_injection;
+    (injection.enabled && injection.provider_id.is_some())
+        || (config.real_api_key.is_some() && config.real_base_url.is_some())
+}
+
+fn tool_by_id(tool_id: &str) -> Result<Tool> {
+    match tool_id {
+        "claude-code" => Ok(Tool::claude_code()),
+        "codex" => Ok(Tool::codex()),
+        "gemini-cli" => Ok(Tool::gemini_cli()),
+        _ => Err(anyhow!("不支持的工具: {tool_id}")),
+    }
+}
+
+/// 工具接入代理需要写入的配置项
+fn proxy_settings(
+    tool_id: &str,
+    config_dir: &Path,
+    proxy_url: &str,
+    local_api_key: Option<&str>,
+) -> Result<SettingValues> {
+    let mut values = SettingValues::new();
+    let key = local_api_key.map(str::to_string);
+    match tool_id {
+        "claude-code" => {
+            values.insert(
+                "ANTHROPIC_BASE_URL".to_string(),
+                Some(proxy_url.to_string()),
+            );
+            if key.is_some() {
+                values.insert("ANTHROPIC_AUTH_TOKEN".to_string(), key);
+            }
+        }
+        "codex" => {
+            let provider = codex_active_provider(config_dir)?;
+            values.insert(
+                format!("model_providers.{provider}.base_url"),
+                Some(format!("{proxy_url}/v1")),
+            );
+            if key.is_some() {
+                values.insert(CODEX_API_KEY.to_string(), key);
+            }
+        }
+        "gemini-cli" => {
+            values.insert(
+                "GOOGLE_GEMINI_BASE_URL".to_string(),
+                Some(proxy_url.to_string()),
+            );
+            if key.is_some() {
+                values.insert("GEMINI_API_KEY".to_string(), key);
+            }
+        }
+        _ => anyhow::bail!("不支持的工具: {tool_id}"),
+    }
+    Ok(values)
+}
+
+/// 写入配置项并返回修改前的值
+fn swap_settings(
+    tool_id: &str,
+    config_dir: &Path,
+    values: &SettingValues,
+) -> Result<SettingValues> {
+    match tool_id {
+        "claude-code" => swap_claude(config_dir, values),
+        "codex" => swap_codex(config_dir, values),
+        "gemini-cli" => swap_gemini(config_dir, values),
+        _ => Err(anyhow!("不支持的工具: {tool_id}")),
+    }
+}
+
+/// 按值更新 JSON 对象中的字符串项，返回旧值
+fn swap_json_entries(obj: &mut Map<String, Value>, values: &SettingValues) -> SettingValues {
+    values
+        .iter()
+        .map(|(key, value)| {
+            let previous = match value {
+                Some(v) => obj.insert(key.clone(), Value::String(v.clone())),
+                None => obj.remove(key),
+            };
+            let previous = previous.and_then(|v| v.as_str().map(str::to_string));
+            (key.clone(), previous)
+        })
+        .collect()
+}
+
+// ==================== Claude Code ====================
+
+fn swap_claude(config_dir: &Path, values: &SettingValues) -> Result<SettingValues> {
+    let manager = DataManager::new();
+    let settings_path = config_dir.join("settings.json");
+
+    let mut settings: Value = if settings_path.exists() {
+        manager.json_uncached().read(&settings_path)?
+    } else {
+        serde_json::json!({})
+    };
+
+    let env = settings
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Claude 配置格式错误：settings 不是对象"))?
+        .entry("env")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("Claude 配置 env 字段格式错误"))?;
+    let previous = swap_json_entries(env, values);
+
+    std::fs::create_dir_all(config_dir).context("创建 Claude Code 配置目录失败")?;
+    manager.json_uncached().write(&settings_path, &settings)?;
+    Ok(previous)
+}
+
+// ==================== Codex ====================
+
+fn codex_active_provider(config_dir: &Path) -> Result<String> {
+    let config_path = config_dir.join("config.toml");
+    if !config_path.exists() {
+        anyhow::bail!("Codex 尚未配置 config.toml，请先配置供应商");
+    }
+    let doc = DataManager::new().toml().read_document(&config_path)?;
+    let provider = doc
+        .get("model_provider")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow!("Codex 配置缺少 model_provider"))?;
+    let has_table = doc
+        .get("model_providers")
+        .and_then(|v| v.as_table())
+        .is_some_and(|t| t.contains_key(provider));
+    if !has_table {
+        anyhow::bail!("Codex 配置缺少 model_providers.{provider}");
+    }
+    Ok(provider.to_string())
+}
+
+fn swap_codex(config_dir: &Path, values: &SettingValues) -> Result<SettingValues> {
+    let manager = DataManager::new();
+    let mut previous = SettingValues::new();
+
+    let provider_values: Vec<(&str, &Option<String>)> = values
+        .iter()
+        .filter_map(|(key, value)| {
+            key.strip_prefix("model_providers.")
+                .and_then(|rest| rest.strip_suffix(".base_url"))
+                .map(|provider| (provider, value))
+        })
+        .collect();
+
+    if !provider_values.is_empty() {
+        let config_path = config_dir.join("config.toml");
+        let mut doc = manager.toml().read_document(&config_path)?;
+        for (provider, value) in provider_values {
+            let table = doc
+                .get_mut("model_providers")
+                .and_then(|item| item.as_table_mut())
+                .and_then(|t| t.get_mut(provider))
+                .and_then(|item| item.as_table_mut())
+                .ok_or_else(|| anyhow!("Codex 配置缺少 model_providers.{provider}"))?;
+            let old = match value {
+                Some(url) => table.insert("base_url", toml_edit::value(url.as_str())),
+                None => table.remove("base_url"),
+            };
+            previous.insert(
+                format!("model_providers.{provider}.base_url"),
+                old.and_then(|item| item.as_str().map(str::to_string)),
+            );
+        }
+        manager.toml().write(&config_path, &doc)?;
+    }
+
+    if let Some(value) = values.get(CODEX_API_KEY) {
+        let auth_path = config_dir.join("auth.json");
+        let mut auth = if auth_path.exists() {
+            manager.json_uncached().read(&auth_path)?
+        } else {
+            serde_json::json!({})
+        };
+        let obj = auth
+            .as_object_mut()
+            .ok_or_else(|| anyhow!("auth.json 格式错误：不是对象"))?;
+        let single = SettingValues::from([(CODEX_API_KEY.to_string(), value.clone())]);
+        previous.extend(swap_json_entries(obj, &single));
+        manager.json_uncached().write(&auth_path, &auth)?;
+    }
+
+    Ok(previous)
+}
+
+// ==================== Gemini CLI ====================
+
+fn swap_gemini(config_dir: &Path, values: &SettingValues) -> Result<SettingValues> {
+    let manager = DataManager::new();
+    let env_path = config_dir.join(".env");
+    let existing = if env_path.exists() {
+        manager.env().read(&env_path)?
+    } else {
+        Default::default()
+    };
+
+    let mut previous = SettingValues::new();
+    for (key, value) in values {
+        previous.insert(key.clone(), existing.get(key).cloned());
+        match value {
+            Some(v) => manager.env().set(&env_path, key, v)?,
+            None if existing.contains_key(key) => manager.env().delete(&env_path, key)?,
+            None => {}
+        }
+    }
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_claude_swap_and_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("settings.json"),
+            r#"{"env":{"ANTHROPIC_BASE_URL":"https://relay.example.com"},"model":"opus"}"#,
+        )
+        .unwrap();
+
+        let values = proxy_settings(
+            "claude-code",
+            dir,
+            "http://127.0.0.1:8787",
+            Some("dc-local"),
+        )
+        .unwrap();
+        let originals = swap_settings("claude-code", dir, &values).unwrap();
+        assert_eq!(
+            originals.get("ANTHROPIC_BASE_URL"),
+            Some(&Some("https://relay.example.com".to_string()))
+        );
+        assert_eq!(originals.get("ANTHROPIC_AUTH_TOKEN"), Some(&None));
+
+        swap_settings("claude-code", dir, &originals).unwrap();
+        let restored: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("settings.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            restored,
+            serde_json::json!({
+                "env": {"ANTHROPIC_BASE_URL": "https://relay.example.com"},
+                "model": "opus"
+            })
+        );
+    }
+
+    #[test]
+    fn test_codex_swap_uses_active_provider() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("config.toml"),
+            "model_provider = \"relay\"\n\n[model_providers.relay]\nname = \"relay\"\nbase_url = \"https://relay.example.com/v1\"\n",
+        )
+        .unwrap();
+
+        let values = proxy_settings("codex", dir, "http://127.0.0.1:8788", None).unwrap();
+        assert_eq!(
+            values.get("model_providers.relay.base_url"),
+            Some(&Some("http://127.0.0.1:8788/v1".to_string()))
+        );
+
+        let originals = swap_settings("codex", dir, &values).unwrap();
+        let content = std::fs::read_to_string(dir.join("config.toml")).unwrap();
+        assert!(content.contains("http://127.0.0.1:8788/v1"));
+
+        swap_settings("codex", dir, &originals).unwrap();
+        let content = std::fs::read_to_string(dir.join("config.toml")).unwrap();
+        assert!(content.contains("https://relay.example.com/v1"));
+        assert!(!dir.join("auth.json").exists());
+    }
+
+    #[test]
+    fn test_gemini_swap_removes_added_keys_on_restore() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::write(dir.join(".env"), "GEMINI_MODEL=gemini-2.5-pro\n").unwrap();
+
+        let values =
+            proxy_settings("gemini-cli", dir, "http://127.0.0.1:8789", Some("dc-local")).unwrap();
+        let originals = swap_settings("gemini-cli", dir, &values).unwrap();
+        assert!(originals.values().all(Option::is_none));
+
+        swap_settings("gemini-cli", dir, &originals).unwrap();
+        let env = DataManager::new().env().read(&dir.join(".env")).unwrap();
+        assert_eq!(env.len(), 1);
+        assert_eq!(
+            env.get("GEMINI_MODEL").map(String::as_str),
+            Some("gemini-2.5-pro")
+        );
+    }
+
+    #[test]
+    fn test_bound_provider_required() {
+        let mut config = ToolProxyConfig::new(8787);
+        assert!(!has_bound_provider(&config));
+
+        config.credential_injection.enabled = true;
+        config.credential_injection.provider_id = Some("relay".to_string());
+        assert!(has_bound_provider(&config));
+    }
+}
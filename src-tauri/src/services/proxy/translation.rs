@@ -0,0 +1,957 @@
+//! Anthropic Messages → OpenAI Chat Completions 协议转换
+//!
+//! 供应商只提供 OpenAI 兼容接口时，可为其开启转换模式，让 Claude Code 通过代理使用：
+//! - 请求：`/v1/messages` 改写为 `/v1/chat/completions`，转换 system、messages、tools、max_tokens 等字段
+//! - 响应：非流式响应转换为 Anthropic message；流式响应将 `chat.completion.chunk`
+//!   转换为 `message_start` / `content_block_delta` / `message_stop` 等事件
+//!
+//! 无法等价转换的内容（文档块、内置工具、tool_result 中的图片等）直接返回 400 说明原因，
+//! 不做有损转换；thinking 块在 OpenAI 接口中没有对应概念，转换时丢弃。
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use hyper::Method;
+use reqwest::header::HeaderValue;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+use super::headers::ProcessedRequest;
+use crate::models::proxy_config::{ApiTranslation, ToolProxyConfig};
+
+const MESSAGES_SUFFIX: &str = "/messages";
+const CHAT_COMPLETIONS_SUFFIX: &str = "/chat/completions";
+
+/// 转换后不再适用的请求头
+const DROPPED_HEADERS: &[&str] = &[
+    "content-length",
+    "accept-encoding",
+    "anthropic-version",
+    "anthropic-beta",
+];
+
+/// 请求转换后保留的上下文（用于转换响应）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationContext {
+    /// 客户端请求的模型名（响应中原样返回）
+    pub model: String,
+    pub stream: bool,
+}
+
+/// 查询当前请求适用的转换模式（仅 POST Messages 请求）
+pub fn resolve(
+    config: &ToolProxyConfig,
+    provider: &str,
+    method: &Method,
+    path: &str,
+) -> Option<ApiTranslation> {
+    let mode = config
+        .provider_translations
+        .get(provider)
+        .copied()
+        .unwrap_or_default();
+    (mode != ApiTranslation::None && method == Method::POST && path.contains(MESSAGES_SUFFIX))
+        .then_some(mode)
+}
+
+/// 转换出站请求（改写 URL、请求头与请求体），失败时返回面向用户的原因
+pub fn translate_request(processed: &mut ProcessedRequest) -> Result<TranslationContext, String> {
+    let mut url =
+        url::Url::parse(&processed.target_url).map_err(|e| format!("无效的上游地址: {e}"))?;
+    let path = url.path().to_string();
+    let Some(prefix) = path.strip_suffix(MESSAGES_SUFFIX) else {
+        return Err(format!(
+            "协议转换模式下不支持该端点: {path}（仅支持 /v1/messages）"
+        ));
+    };
+    url.set_path(&format!("{prefix}{CHAT_COMPLETIONS_SUFFIX}"));
+    url.set_query(None);
+
+    let request: Value = serde_json::from_slice(&processed.body)
+        .map_err(|e| format!("请求体不是有效的 JSON: {e}"))?;
+    let (body, context) = anthropic_to_openai_request(&request)?;
+
+    processed.target_url = url.to_string();
+    processed.body = Bytes::from(body.to_string());
+    for name in DROPPED_HEADERS {
+        processed.headers.remove(*name);
+    }
+    processed.headers.insert(
+        reqwest::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    Ok(context)
+}
+
+fn unsupported_block(kind: &str) -> String {
+    format!(
+        "OpenAI 兼容接口不支持 `{kind}` 类型的内容块，请关闭该供应商的协议转换或改用原生 Anthropic 接口"
+    )
+}
+
+fn block_type(block: &Value) -> &str {
+    block.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+fn block_text(block: &Value) -> &str {
+    block.get("text").and_then(Value::as_str).unwrap_or("")
+}
+
+/// Anthropic 请求体 → OpenAI 请求体
+fn anthropic_to_openai_request(request: &Value) -> Result<(Value, TranslationContext), String> {
+    let obj = request.as_object().ok_or("请求体必须是 JSON 对象")?;
+    let model = obj
+        .get("model")
+        .and_then(Value::as_str)
+        .ok_or("请求缺少 model 字段")?
+        .to_string();
+    let stream = obj.get("stream").and_then(Value::as_bool).unwrap_or(false);
+
+    let mut messages = Vec::new();
+    if let Some(system) = obj.get("system") {
+        let text = system_text(system)?;
+        if !text.is_empty() {
+            messages.push(json!({ "role": "system", "content": text }));
+        }
+    }
+    for message in obj
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or("请求缺少 messages 字段")?
+    {
+        convert_message(message, &mut messages)?;
+    }
+
+    let mut out = Map::new();
+    out.insert("model".into(), json!(model));
+    out.insert("messages".into(), Value::Array(messages));
+    for key in ["max_tokens", "temperature", "top_p"] {
+        if let Some(value) = obj.get(key) {
+            out.insert(key.into(), value.clone());
+        }
+    }
+    if let Some(stop) = obj.get("stop_sequences") {
+        out.insert("stop".into(), stop.clone());
+    }
+    if stream {
+        out.insert("stream".into(), json!(true));
+        out.insert("stream_options".into(), json!({ "include_usage": true }));
+    }
+    if let Some(tools) = obj.get("tools").and_then(Value::as_array) {
+        if !tools.is_empty() {
+            let tools = tools
+                .iter()
+                .map(convert_tool)
+                .collect::<Result<Vec<_>, _>>()?;
+            out.insert("tools".into(), Value::Array(tools));
+        }
+    }
+    if let Some(choice) = obj.get("tool_choice") {
+        convert_tool_choice(choice, &mut out)?;
+    }
+    if let Some(user) = request.pointer("/metadata/user_id").and_then(Value::as_str) {
+        out.insert("user".into(), json!(user));
+    }
+
+    Ok((Value::Object(out), TranslationContext { model, stream }))
+}
+
+fn system_text(system: &Value) -> Result<String, String> {
+    match system {
+        Value::String(text) => Ok(text.clone()),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(|block| match block_type(block) {
+                "text" => Ok(block_text(block)),
+                other => Err(unsupported_block(other)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|parts| parts.join("\n\n")),
+        _ => Err("system 字段格式无效".to_string()),
+    }
+}
+
+fn convert_message(message: &Value, out: &mut Vec<Value>) -> Result<(), String> {
+    let role = message
+        .get("role")
+        .and_then(Value::as_str)
+        .ok_or("消息缺少 role 字段")?;
+    let blocks = match message.get("content") {
+        Some(Value::String(text)) => vec![json!({ "type": "text", "text": text })],
+        Some(Value::Array(blocks)) => blocks.clone(),
+        _ => return Err("消息 content 字段格式无效".to_string()),
+    };
+    match role {
+        "user" => convert_user_blocks(&blocks, out),
+        "assistant" => convert_assistant_blocks(&blocks, out),
+        other => Err(format!("不支持的消息角色: {other}")),
+    }
+}
+
+fn convert_user_blocks(blocks: &[Value], out: &mut Vec<Value>) -> Result<(), String> {
+    let mut parts = Vec::new();
+    for block in blocks {
+        match block_type(block) {
+            "text" => parts.push(json!({ "type": "text", "text": block_text(block) })),
+            "image" => parts.push(image_part(block)?),
+            // tool_result 转为独立的 tool 消息，紧跟在上一条 assistant 的 tool_calls 之后
+            "tool_result" => out.push(tool_result_message(block)?),
+            "thinking" | "redacted_thinking" => {}
+            other => return Err(unsupported_block(other)),
+        }
+    }
+    if parts.is_empty() {
+        return Ok(());
+    }
+
+    // 纯文本时使用字符串形式，兼容不支持多段 content 的中转站
+    let content = if parts.iter().all(|p| p["type"] == "text") {
+        Value::String(
+            parts
+                .iter()
+                .filter_map(|p| p["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )
+    } else {
+        Value::Array(parts)
+    };
+    out.push(json!({ "role": "user", "content": content }));
+    Ok(())
+}
+
+fn image_part(block: &Value) -> Result<Value, String> {
+    let source = block.get("source").ok_or("image 块缺少 source 字段")?;
+    let url = match source.get("type").and_then(Value::as_str) {
+        Some("base64") => format!(
+            "data:{};base64,{}",
+            source
+                .get("media_type")
+                .and_then(Value::as_str)
+                .unwrap_or("image/png"),
+            source.get("data").and_then(Value::as_str).unwrap_or("")
+        ),
+        Some("url") => source
+            .get("url")
+            .and_then(Value::as_str)
+            .unwrap_or("")
+            .to_string(),
+        other => {
+            return Err(format!(
+                "不支持的图片来源类型: {}",
+                other.unwrap_or("unknown")
+            ))
+        }
+    };
+    Ok(json!({ "type": "image_url", "image_url": { "url": url } }))
+}
+
+fn tool_result_message(block: &Value) -> Result<Value, String> {
+    let tool_call_id = block
+        .get("tool_use_id")
+        .and_then(Value::as_str)
+        .ok_or("tool_result 块缺少 tool_use_id 字段")?;
+    let text = match block.get("content") {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .map(|part| match block_type(part) {
+                "text" => Ok(block_text(part)),
+                "image" => Err(
+                    "OpenAI 兼容接口的 tool 消息不支持图片，无法转换包含图片的 tool_result"
+                        .to_string(),
+                ),
+                other => Err(unsupported_block(other)),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n\n"),
+        Some(_) => return Err("tool_result 块的 content 字段格式无效".to_string()),
+    };
+    let is_error = block
+        .get("is_error")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let content = if is_error {
+        format!("Error: {text}")
+    } else {
+        text
+    };
+    Ok(json!({ "role": "tool", "tool_call_id": tool_call_id, "content": content }))
+}
+
+fn convert_assistant_blocks(blocks: &[Value], out: &mut Vec<Value>) -> Result<(), String> {
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block_type(block) {
+            "text" => text.push_str(block_text(block)),
+            "tool_use" => {
+                let id = block
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .ok_or("tool_use 块缺少 id 字段")?;
+                let name = block
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or("tool_use 块缺少 name 字段")?;
+                let input = block.get("input").cloned().unwrap_or_else(|| json!({}));
+                tool_calls.push(json!({
+                    "id": id,
+                    "type": "function",
+                    "function": { "name": name, "arguments": input.to_string() },
+                }));
+            }
+            "thinking" | "redacted_thinking" => {}
+            other => return Err(unsupported_block(other)),
+        }
+    }
+    if text.is_empty() && tool_calls.is_empty() {
+        return Ok(());
+    }
+
+    let content = if text.is_empty() {
+        Value::Null
+    } else {
+        Value::String(text)
+    };
+    let mut message = json!({ "role": "assistant", "content": content });
+    if !tool_calls.is_empty() {
+        message["tool_calls"] = Value::Array(tool_calls);
+    }
+    out.push(message);
+    Ok(())
+}
+
+fn convert_tool(tool: &Value) -> Result<Value, String> {
+    let name = tool
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("工具定义缺少 name 字段")?;
+    // 内置工具（web_search、computer 等）带版本化的 type 且没有 input_schema
+    let Some(schema) = tool.get("input_schema") else {
+        let kind = tool.get("type").and_then(Value::as_str).unwrap_or(name);
+        return Err(format!(
+            "OpenAI 兼容接口不支持 Anthropic 内置工具 `{kind}`，仅支持自定义函数工具"
+        ));
+    };
+
+    let mut function = json!({ "name": name, "parameters": schema });
+    if let Some(description) = tool.get("description") {
+        function["description"] = description.clone();
+    }
+    Ok(json!({ "type": "function", "function": function }))
+}
+
+fn convert_tool_choice(choice: &Value, out: &mut Map<String, Value>) -> Result<(), String> {
+    let mapped = match choice.get("type").and_then(Value::as_str) {
+        Some("auto") => json!("auto"),
+        Some("any") => json!("required"),
+        Some("none") => json!("none"),
+        Some("tool") => {
+            let name = choice
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or("tool_choice 缺少 name 字段")?;
+            json!({ "type": "function", "function": { "name": name } })
+        }
+        other => {
+            return Err(format!(
+                "不支持的 tool_choice 类型: {}",
+                other.unwrap_or("unknown")
+            ))
+        }
+    };
+    out.insert("tool_choice".into(), mapped);
+    if choice
+        .get("disable_parallel_tool_use")
+        .and_then(Value::as_bool)
+        == Some(true)
+    {
+        out.insert("parallel_tool_calls".into(), json!(false));
+    }
+    Ok(())
+}
+
+/// OpenAI finish_reason → Anthropic stop_reason
+fn stop_reason(finish_reason: Option<&str>) -> &'static str {
+    match finish_reason {
+        Some("length") => "max_tokens",
+        Some("tool_calls") | Some("function_call") => "tool_use",
+        _ => "end_turn",
+    }
+}
+
+fn message_id(id: Option<&str>) -> String {
+    format!("msg_{}", id.unwrap_or("duckcoding"))
+}
+
+fn usage_tokens(usage: Option<&Value>) -> (u64, u64) {
+    let get = |key: &str| {
+        usage
+            .and_then(|u| u.get(key))
+            .and_then(Value::as_u64)
+            .unwrap_or(0)
+    };
+    (get("prompt_tokens"), get("completion_tokens"))
+}
+
+/// 转换非流式响应体
+pub fn translate_response(body: &[u8], context: &TranslationContext) -> Result<Bytes, String> {
+    let response: Value =
+        serde_json::from_slice(body).map_err(|e| format!("上游响应不是有效的 JSON: {e}"))?;
+    let choice = response
+        .pointer("/choices/0")
+        .ok_or("上游响应缺少 choices 字段")?;
+    let message = choice.get("message").ok_or("上游响应缺少 message 字段")?;
+
+    let mut content = Vec::new();
+    if let Some(text) = message
+        .get("content")
+        .and_then(Value::as_str)
+        .filter(|t| !t.is_empty())
+    {
+        content.push(json!({ "type": "text", "text": text }));
+    }
+    if let Some(calls) = message.get("tool_calls").and_then(Value::as_array) {
+        for (i, call) in calls.iter().enumerate() {
+            let arguments = call
+                .pointer("/function/arguments")
+                .and_then(Value::as_str)
+                .filter(|a| !a.trim().is_empty())
+                .unwrap_or("{}");
+            let input: Value = serde_json::from_str(arguments)
+                .map_err(|e| format!("上游工具调用参数不是有效的 JSON: {e}"))?;
+            let id = call
+                .get("id")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("toolu_{i}"));
+            let name = call
+                .pointer("/function/name")
+                .and_then(Value::as_str)
+                .unwrap_or("");
+            content.push(json!({ "type": "tool_use", "id": id, "name": name, "input": input }));
+        }
+    }
+
+    let (input_tokens, output_tokens) = usage_tokens(response.get("usage"));
+    let translated = json!({
+        "id": message_id(response.get("id").and_then(Value::as_str)),
+        "type": "message",
+        "role": "assistant",
+        "model": context.model,
+        "content": content,
+        "stop_reason": stop_reason(choice.get("finish_reason").and_then(Value::as_str)),
+        "stop_sequence": null,
+        "usage": { "input_tokens": input_tokens, "output_tokens": output_tokens },
+    });
+    Ok(Bytes::from(translated.to_string()))
+}
+
+/// 当前打开的内容块（值为 Anthropic 内容块索引）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OpenBlock {
+    Text(usize),
+    Tool(usize),
+}
+
+/// 流式响应转换器：逐块输入 OpenAI SSE，输出 Anthropic SSE
+#[derive(Debug)]
+pub struct StreamTranslator {
+    model: String,
+    buffer: Vec<u8>,
+    started: bool,
+    finished: bool,
+    open: Option<OpenBlock>,
+    next_index: usize,
+    /// OpenAI tool_calls 索引 → Anthropic 内容块索引
+    tool_blocks: HashMap<u64, usize>,
+    stop_reason: Option<&'static str>,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+fn emit(out: &mut Vec<u8>, event: &str, data: Value) {
+    out.extend_from_slice(format!("event: {event}\ndata: {data}\n\n").as_bytes());
+}
+
+impl StreamTranslator {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            buffer: Vec::new(),
+            started: false,
+            finished: false,
+            open: None,
+            next_index: 0,
+            tool_blocks: HashMap::new(),
+            stop_reason: None,
+            input_tokens: 0,
+            output_tokens: 0,
+        }
+    }
+
+    /// 输入一个上游分块，返回转换后的输出（可能为空）
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<u8> {
+        self.buffer.extend_from_slice(chunk);
+        let mut out = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.finish_into(&mut out);
+            } else if let Ok(value) = serde_json::from_str::<Value>(data) {
+                self.handle_chunk(&value, &mut out);
+            }
+        }
+        out
+    }
+
+    /// 上游流结束，补齐未发送的结束事件
+    pub fn finish(mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.finish_into(&mut out);
+        out
+    }
+
+    fn alloc_index(&mut self) -> usize {
+        let index = self.next_index;
+        self.next_index += 1;
+        index
+    }
+
+    fn ensure_started(&mut self, id: Option<&str>, out: &mut Vec<u8>) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        emit(
+            out,
+            "message_start",
+            json!({
+                "type": "message_start",
+                "message": {
+                    "id": message_id(id),
+                    "type": "message",
+                    "role": "assistant",
+                    "model": self.model,
+                    "content": [],
+                    "stop_reason": null,
+                    "stop_sequence": null,
+                    "usage": { "input_tokens": 0, "output_tokens": 0 },
+                },
+            }),
+        );
+    }
+
+    fn close_block(&mut self, out: &mut Vec<u8>) {
+        if let Some(block) = self.open.take() {
+            let (OpenBlock::Text(index) | OpenBlock::Tool(index)) = block;
+            emit(
+                out,
+                "content_block_stop",
+                json!({ "type": "content_block_stop", "index": index }),
+            );
+        }
+    }
+
+    fn handle_chunk(&mut self, value: &Value, out: &mut Vec<u8>) {
+        if self.finished {
+            return;
+        }
+        if let Some(error) = value.get("error") {
+            let message = error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("上游流式响应返回错误");
+            emit(
+                out,
+                "error",
+                json!({ "type": "error", "error": { "type": "api_error", "message": message } }),
+            );
+            return;
+        }
+
+        self.ensure_started(value.get("id").and_then(Value::as_str), out);
+        if let Some(usage) = value.get("usage").filter(|u| u.is_object()) {
+            (self.input_tokens, self.output_tokens) = usage_tokens(Some(usage));
+        }
+        let Some(choice) = value.pointer("/choices/0") else {
+            return;
+        };
+        let delta = choice.get("delta");
+
+        if let Some(text) = delta
+            .and_then(|d| d.get("content"))
+            .and_then(Value::as_str)
+            .filter(|t| !t.is_empty())
+        {
+            let index = match self.open {
+                Some(OpenBlock::Text(index)) => index,
+                _ => {
+                    self.close_block(out);
+                    let index = self.alloc_index();
+                    self.open = Some(OpenBlock::Text(index));
+                    emit(
+                        out,
+                        "content_block_start",
+                        json!({
+                            "type": "content_block_start",
+                            "index": index,
+                            "content_block": { "type": "text", "text": "" },
+                        }),
+                    );
+                    index
+                }
+            };
+            emit(
+                out,
+                "content_block_delta",
+                json!({
+                    "type": "content_block_delta",
+                    "index": index,
+                    "delta": { "type": "text_delta", "text": text },
+                }),
+            );
+        }
+
+        if let Some(calls) = delta
+            .and_then(|d| d.get("tool_calls"))
+            .and_then(Value::as_array)
+        {
+            for call in calls {
+                let call_index = call.get("index").and_then(Value::as_u64).unwrap_or(0);
+                let index = match self.tool_blocks.get(&call_index) {
+                    Some(&index) => index,
+                    None => {
+                        self.close_block(out);
+                        let index = self.alloc_index();
+                        self.tool_blocks.insert(call_index, index);
+                        self.open = Some(OpenBlock::Tool(index));
+                        let id = call
+                            .get("id")
+                            .and_then(Value::as_str)
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("toolu_{index}"));
+                        let name = call
+                            .pointer("/function/name")
+                            .and_then(Value::as_str)
+                            .unwrap_or("");
+                        emit(
+                            out,
+                            "content_block_start",
+                            json!({
+                                "type": "content_block_start",
+                                "index": index,
+                                "content_block": { "type": "tool_use", "id": id, "name": name, "input": {} },
+                            }),
+                        );
+                        index
+                    }
+                };
+                if let Some(arguments) = call
+                    .pointer("/function/arguments")
+                    .and_then(Value::as_str)
+                    .filter(|a| !a.is_empty())
+                {
+                    emit(
+                        out,
+                        "content_block_delta",
+                        json!({
+                            "type": "content_block_delta",
+                            "index": index,
+                            "delta": { "type": "input_json_delta", "partial_json": arguments },
+                        }),
+                    );
+                }
+            }
+        }
+
+        if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+            self.stop_reason = Some(stop_reason(Some(reason)));
+        }
+    }
+
+    fn finish_into(&mut self, out: &mut Vec<u8>) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.ensure_started(None, out);
+        self.close_block(out);
+        emit(
+            out,
+            "message_delta",
+            json!({
+                "type": "message_delta",
+                "delta": {
+                    "stop_reason": self.stop_reason.unwrap_or("end_turn"),
+                    "stop_sequence": null,
+                },
+                "usage": {
+                    "input_tokens": self.input_tokens,
+                    "output_tokens": self.output_tokens,
+                },
+            }),
+        );
+        emit(out, "message_stop", json!({ "type": "message_stop" }));
+    }
+}
+
+/// 将上游 OpenAI SSE 流转换为 Anthropic SSE 流
+pub fn translate_sse_stream<S, E>(
+    stream: S,
+    model: String,
+) -> impl Stream<Item = Result<Bytes, E>> + Send
+where
+    S: Stream<Item = Result<Bytes, E>> + Send + 'static,
+    E: Send + 'static,
+{
+    let state = (Box::pin(stream), Some(StreamTranslator::new(model)));
+    futures_util::stream::unfold(state, |(mut stream, translator)| async move {
+        let mut translator = translator?;
+        loop {
+            match stream.next().await {
+                Some(Ok(chunk)) => {
+                    let out = translator.feed(&chunk);
+                    if !out.is_empty() {
+                        return Some((Ok(Bytes::from(out)), (stream, Some(translator))));
+                    }
+                }
+                Some(Err(e)) => return Some((Err(e), (stream, Some(translator)))),
+                None => {
+                    let out = translator.finish();
+                    return Some((Ok(Bytes::from(out)), (stream, None)));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+
+    fn processed(url: &str, body: Value) -> ProcessedRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-length", HeaderValue::from_static("999"));
+        headers.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
+        ProcessedRequest {
+            target_url: url.to_string(),
+            headers,
+            body: Bytes::from(body.to_string()),
+        }
+    }
+
+    fn translated_body(request: &ProcessedRequest) -> Value {
+        serde_json::from_slice(&request.body).unwrap()
+    }
+
+    #[test]
+    fn test_resolve_only_for_configured_provider() {
+        let mut config = ToolProxyConfig::new(8787);
+        config
+            .provider_translations
+            .insert("relay".to_string(), ApiTranslation::AnthropicToOpenaiChat);
+
+        assert_eq!(
+            resolve(&config, "relay", &Method::POST, "/v1/messages"),
+            Some(ApiTranslation::AnthropicToOpenaiChat)
+        );
+        assert_eq!(resolve(&config, "relay", &Method::GET, "/v1/models"), None);
+        assert_eq!(
+            resolve(&config, "other", &Method::POST, "/v1/messages"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_translate_request_with_tools() {
+        let mut request = processed(
+            "https://relay.example.com/v1/messages?beta=true",
+            json!({
+                "model": "claude-sonnet-4",
+                "max_tokens": 1024,
+                "stream": true,
+                "system": [{ "type": "text", "text": "You are helpful." }],
+                "tools": [{
+                    "name": "read_file",
+                    "description": "Read a file",
+                    "input_schema": { "type": "object", "properties": { "path": { "type": "string" } } },
+                }],
+                "tool_choice": { "type": "any" },
+                "messages": [
+                    { "role": "user", "content": "Read main.rs" },
+                    { "role": "assistant", "content": [
+                        { "type": "thinking", "thinking": "..." },
+                        { "type": "text", "text": "Sure." },
+                        { "type": "tool_use", "id": "toolu_1", "name": "read_file", "input": { "path": "main.rs" } },
+                    ]},
+                    { "role": "user", "content": [
+                        { "type": "tool_result", "tool_use_id": "toolu_1", "content": "fn main() {}" },
+                        { "type": "text", "text": "Explain it" },
+                    ]},
+                ],
+            }),
+        );
+
+        let context = translate_request(&mut request).unwrap();
+        assert_eq!(
+            context,
+            TranslationContext {
+                model: "claude-sonnet-4".to_string(),
+                stream: true
+            }
+        );
+        assert_eq!(
+            request.target_url,
+            "https://relay.example.com/v1/chat/completions"
+        );
+        assert!(request.headers.get("content-length").is_none());
+        assert!(request.headers.get("anthropic-version").is_none());
+
+        let body = translated_body(&request);
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+        assert_eq!(body["tool_choice"], "required");
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages[1]["content"], "Read main.rs");
+        assert_eq!(messages[2]["content"], "Sure.");
+        assert_eq!(
+            messages[2]["tool_calls"][0]["function"]["arguments"],
+            r#"{"path":"main.rs"}"#
+        );
+        assert_eq!(messages[3]["role"], "tool");
+        assert_eq!(messages[3]["tool_call_id"], "toolu_1");
+        assert_eq!(messages[4]["content"], "Explain it");
+    }
+
+    #[test]
+    fn test_unsupported_features_are_rejected() {
+        let mut request = processed(
+            "https://relay.example.com/v1/messages/count_tokens",
+            json!({ "model": "m", "messages": [] }),
+        );
+        assert!(translate_request(&mut request)
+            .unwrap_err()
+            .contains("count_tokens"));
+
+        let mut request = processed(
+            "https://relay.example.com/v1/messages",
+            json!({
+                "model": "m",
+                "messages": [{ "role": "user", "content": [{ "type": "document", "source": {} }] }],
+            }),
+        );
+        assert!(translate_request(&mut request)
+            .unwrap_err()
+            .contains("document"));
+
+        let mut request = processed(
+            "https://relay.example.com/v1/messages",
+            json!({
+                "model": "m",
+                "messages": [],
+                "tools": [{ "type": "web_search_20250305", "name": "web_search" }],
+            }),
+        );
+        assert!(translate_request(&mut request)
+            .unwrap_err()
+            .contains("web_search_20250305"));
+
+        // 失败时不修改原请求
+        assert_eq!(request.target_url, "https://relay.example.com/v1/messages");
+    }
+
+    #[test]
+    fn test_translate_response() {
+        let context = TranslationContext {
+            model: "claude-sonnet-4".to_string(),
+            stream: false,
+        };
+        let body = json!({
+            "id": "chatcmpl-1",
+            "choices": [{
+                "message": {
+                    "content": "Done",
+                    "tool_calls": [{ "id": "call_1", "function": { "name": "ls", "arguments": "{\"dir\":\".\"}" } }],
+                },
+                "finish_reason": "tool_calls",
+            }],
+            "usage": { "prompt_tokens": 11, "completion_tokens": 7 },
+        });
+        let translated: Value = serde_json::from_slice(
+            &translate_response(body.to_string().as_bytes(), &context).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(translated["type"], "message");
+        assert_eq!(translated["model"], "claude-sonnet-4");
+        assert_eq!(translated["stop_reason"], "tool_use");
+        assert_eq!(translated["content"][0]["text"], "Done");
+        assert_eq!(translated["content"][1]["type"], "tool_use");
+        assert_eq!(translated["content"][1]["input"]["dir"], ".");
+        assert_eq!(translated["usage"]["input_tokens"], 11);
+    }
+
+    fn events(output: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(output)
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_translation() {
+        let upstream = concat!(
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"function\":{\"name\":\"ls\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{}\"}}]}}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"id\":\"c1\",\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":9}}\n\n",
+            "data: [DONE]\n\n",
+        );
+
+        let mut translator = StreamTranslator::new("claude-sonnet-4");
+        let mut output = Vec::new();
+        // 分块边界落在任意位置
+        for chunk in upstream.as_bytes().chunks(13) {
+            output.extend(translator.feed(chunk));
+        }
+        output.extend(translator.finish());
+
+        let events = events(&output);
+        let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                "message_start",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_delta",
+                "content_block_stop",
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_delta",
+                "message_stop",
+            ]
+        );
+        assert_eq!(events[2]["delta"]["text"], "Hel");
+        assert_eq!(events[5]["content_block"]["name"], "ls");
+        assert_eq!(events[5]["index"], 1);
+        assert_eq!(events[6]["delta"]["partial_json"], "{}");
+        assert_eq!(events[8]["delta"]["stop_reason"], "tool_use");
+        assert_eq!(events[8]["usage"]["output_tokens"], 9);
+    }
+}
@@ -0,0 +1,277 @@
+//! 上游错误响应规范化
+//!
+//! 部分中转站在出错时返回 HTML 页面或纯文本，客户端解析 JSON 失败后只能提示解析错误，
+//! 看不到真实原因。这里按请求形态把这类响应包装成客户端期望的错误格式：
+//! - Anthropic：`{"type":"error","error":{"type":...,"message":...}}`
+//! - OpenAI：`{"error":{"message":...,"type":...,"code":...}}`
+//!
+//! 保留原始状态码，并附带截断后的上游响应体便于排查；格式正确的上游错误原样透传。
+
+use bytes::Bytes;
+use serde_json::Value;
+
+/// 附带的上游响应体最大长度（字节）
+const UPSTREAM_BODY_LIMIT: usize = 2048;
+
+/// 客户端期望的错误格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorSchema {
+    Anthropic,
+    OpenAi,
+}
+
+impl ErrorSchema {
+    /// 根据请求路径判断错误格式，无法判断时按工具默认格式
+    pub fn for_request(tool_id: &str, path: &str) -> Self {
+        if path.ends_with("/messages") || path.contains("/messages/") {
+            Self::Anthropic
+        } else if path.contains("/chat/completions")
+            || path.contains("/responses")
+            || path.contains("/completions")
+        {
+            Self::OpenAi
+        } else if tool_id == "claude-code" {
+            Self::Anthropic
+        } else {
+            Self::OpenAi
+        }
+    }
+
+    /// 响应体是否已是该格式的错误
+    fn is_well_formed(self, value: &Value) -> bool {
+        let has_message = |error: &Value| error.get("message").is_some_and(Value::is_string);
+        match self {
+            Self::Anthropic => {
+                value.get("type").and_then(Value::as_str) == Some("error")
+                    && value.get("error").is_some_and(has_message)
+            }
+            Self::OpenAi => value.get("error").is_some_and(has_message),
+        }
+    }
+}
+
+/// 规范化结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum Normalized {
+    /// 上游错误格式正确，原样透传
+    Untouched,
+    /// 响应体是正确格式的 JSON，仅 Content-Type 有误
+    FixContentType,
+    /// 响应体已包装为客户端期望的格式
+    Rewritten(Bytes),
+}
+
+/// 规范化上游错误响应
+///
+/// 仅处理 4xx/5xx 且未压缩的响应；`content_encoding` 非空时无法检查响应体，直接透传。
+pub fn normalize(
+    schema: ErrorSchema,
+    status: u16,
+    content_type: Option<&str>,
+    content_encoding: Option<&str>,
+    body: &[u8],
+) -> Normalized {
+    if status < 400 || content_encoding.is_some_and(|enc| !enc.eq_ignore_ascii_case("identity")) {
+        return Normalized::Untouched;
+    }
+
+    if let Ok(value) = serde_json::from_slice::<Value>(body) {
+        if schema.is_well_formed(&value) {
+            let is_json = content_type.is_some_and(|ct| ct.to_ascii_lowercase().contains("json"));
+            return if is_json {
+                Normalized::Untouched
+            } else {
+                Normalized::FixContentType
+            };
+        }
+    }
+
+    Normalized::Rewritten(Bytes::from(wrap(schema, status, body).to_string()))
+}
+
+/// 按状态码推断错误类型
+fn error_type(schema: ErrorSchema, status: u16) -> &'static str {
+    match (schema, status) {
+        (_, 400 | 422) => "invalid_request_error",
+        (_, 401) => "authentication_error",
+        (ErrorSchema::Anthropic, 403) => "permission_error",
+        (ErrorSchema::Anthropic, 404) => "not_found_error",
+        (ErrorSchema::Anthropic, 413) => "request_too_large",
+        (_, 429) => "rate_limit_error",
+        (ErrorSchema::Anthropic, 529) => "overloaded_error",
+        (ErrorSchema::Anthropic, _) => "api_error",
+        (ErrorSchema::OpenAi, 400..=499) => "invalid_request_error",
+        (ErrorSchema::OpenAi, _) => "server_error",
+    }
+}
+
+/// 截断上游响应体（按 UTF-8 字符边界）
+fn truncate_body(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let text = text.trim();
+    if text.len() <= UPSTREAM_BODY_LIMIT {
+        return text.to_string();
+    }
+    let mut end = UPSTREAM_BODY_LIMIT;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}…(已截断)", &text[..end])
+}
+
+fn wrap(schema: ErrorSchema, status: u16, body: &[u8]) -> Value {
+    let upstream_body = truncate_body(body);
+    let message = if upstream_body.is_empty() {
+        format!("上游返回 HTTP {status}（响应体为空）")
+    } else {
+        format!("上游返回 HTTP {status}（非标准错误响应）")
+    };
+    let kind = error_type(schema, status);
+
+    match schema {
+        ErrorSchema::Anthropic => serde_json::json!({
+            "type": "error",
+            "error": {
+                "type": kind,
+                "message": message,
+                "upstream_status": status,
+                "upstream_body": upstream_body,
+            },
+        }),
+        ErrorSchema::OpenAi => serde_json::json!({
+            "error": {
+                "message": message,
+                "type": kind,
+                "param": null,
+                "code": status.to_string(),
+                "upstream_body": upstream_body,
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rewritten(result: Normalized) -> Value {
+        match result {
+            Normalized::Rewritten(body) => serde_json::from_slice(&body).unwrap(),
+            other => panic!("expected rewritten body, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_schema_for_request() {
+        assert_eq!(
+            ErrorSchema::for_request("claude-code", "/v1/messages"),
+            ErrorSchema::Anthropic
+        );
+        assert_eq!(
+            ErrorSchema::for_request("codex", "/v1/responses"),
+            ErrorSchema::OpenAi
+        );
+        assert_eq!(
+            ErrorSchema::for_request("claude-code", "/v1/chat/completions"),
+            ErrorSchema::OpenAi
+        );
+        assert_eq!(
+            ErrorSchema::for_request("claude-code", "/v1/models"),
+            ErrorSchema::Anthropic
+        );
+    }
+
+    #[test]
+    fn test_html_body_wrapped() {
+        let html = b"<html><body><h1>502 Bad Gateway</h1></body></html>";
+        let value = rewritten(normalize(
+            ErrorSchema::Anthropic,
+            502,
+            Some("text/html"),
+            None,
+            html,
+        ));
+        assert_eq!(value["type"], "error");
+        assert_eq!(value["error"]["type"], "api_error");
+        assert_eq!(value["error"]["upstream_status"], 502);
+        assert!(value["error"]["upstream_body"]
+            .as_str()
+            .unwrap()
+            .contains("502 Bad Gateway"));
+
+        let value = rewritten(normalize(
+            ErrorSchema::OpenAi,
+            502,
+            Some("text/html"),
+            None,
+            html,
+        ));
+        assert_eq!(value["error"]["type"], "server_error");
+        assert!(value["error"]["message"].is_string());
+    }
+
+    #[test]
+    fn test_empty_body_wrapped() {
+        let value = rewritten(normalize(ErrorSchema::Anthropic, 429, None, None, b""));
+        assert_eq!(value["error"]["type"], "rate_limit_error");
+        assert_eq!(value["error"]["upstream_body"], "");
+    }
+
+    #[test]
+    fn test_wrong_content_type_json() {
+        let body =
+            br#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        assert_eq!(
+            normalize(ErrorSchema::Anthropic, 529, Some("text/plain"), None, body),
+            Normalized::FixContentType
+        );
+
+        // JSON 但不是期望的错误格式
+        let value = rewritten(normalize(
+            ErrorSchema::Anthropic,
+            500,
+            Some("text/plain"),
+            None,
+            br#"{"msg":"boom"}"#,
+        ));
+        assert_eq!(value["error"]["upstream_body"], r#"{"msg":"boom"}"#);
+    }
+
+    #[test]
+    fn test_well_formed_and_success_untouched() {
+        let body = br#"{"error":{"message":"bad key","type":"invalid_request_error"}}"#;
+        assert_eq!(
+            normalize(
+                ErrorSchema::OpenAi,
+                401,
+                Some("application/json"),
+                None,
+                body
+            ),
+            Normalized::Untouched
+        );
+        assert_eq!(
+            normalize(ErrorSchema::OpenAi, 200, Some("text/html"), None, b"<html>"),
+            Normalized::Untouched
+        );
+        assert_eq!(
+            normalize(ErrorSchema::OpenAi, 502, None, Some("gzip"), b"\x1f\x8b"),
+            Normalized::Untouched
+        );
+    }
+
+    #[test]
+    fn test_long_body_truncated() {
+        let body = "错".repeat(2000);
+        let value = rewritten(normalize(
+            ErrorSchema::Anthropic,
+            500,
+            Some("text/plain"),
+            None,
+            body.as_bytes(),
+        ));
+        let upstream = value["error"]["upstream_body"].as_str().unwrap();
+        assert!(upstream.ends_with("…(已截断)"));
+        assert!(upstream.len() < body.len());
+    }
+}
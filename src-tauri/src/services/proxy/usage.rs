@@ -0,0 +1,170 @@
+//! 从上游响应中提取 token 用量
+//!
+//! 兼容三类响应格式：
+//! - Anthropic：`usage.input_tokens` / `usage.output_tokens`（流式在 `message_start` 与 `message_delta` 中）
+//! - OpenAI：`usage.prompt_tokens` / `usage.completion_tokens`，Responses API 为 `response.usage`
+//! - Gemini：`usageMetadata.promptTokenCount` / `usageMetadata.candidatesTokenCount`
+
+use serde_json::Value;
+
+/// SSE 单行缓冲上限，超出时丢弃（避免异常上游撑爆内存）
+const MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// token 用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens == 0 && self.output_tokens == 0
+    }
+
+    /// 流式事件中的用量为累计值，取各字段最大值合并
+    fn merge_max(&mut self, other: TokenUsage) {
+        self.input_tokens = self.input_tokens.max(other.input_tokens);
+        self.output_tokens = self.output_tokens.max(other.output_tokens);
+    }
+}
+
+fn first_u64(object: &Value, keys: &[&str]) -> u64 {
+    keys.iter()
+        .find_map(|key| object.get(*key).and_then(Value::as_u64))
+        .unwrap_or(0)
+}
+
+/// 从单个 JSON 响应或事件中提取用量
+pub fn from_json(value: &Value) -> Option<TokenUsage> {
+    let usage = [
+        value.get("usage"),
+        value.pointer("/message/usage"),
+        value.pointer("/response/usage"),
+        value.get("usageMetadata"),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|u| u.is_object())?;
+
+    // Anthropic 的缓存读写 token 单独计数，一并计入输入
+    let input = first_u64(
+        usage,
+        &["input_tokens", "prompt_tokens", "promptTokenCount"],
+    ) + first_u64(usage, &["cache_creation_input_tokens"])
+        + first_u64(usage, &["cache_read_input_tokens"]);
+    let output = first_u64(
+        usage,
+        &["output_tokens", "completion_tokens", "candidatesTokenCount"],
+    );
+
+    Some(TokenUsage {
+        input_tokens: input,
+        output_tokens: output,
+    })
+}
+
+/// 从非流式响应体中提取用量
+pub fn from_body(body: &[u8]) -> Option<TokenUsage> {
+    serde_json::from_slice::<Value>(body)
+        .ok()
+        .as_ref()
+        .and_then(from_json)
+}
+
+/// 增量解析 SSE 流中的用量（分块可能在任意位置截断）
+#[derive(Debug, Default)]
+pub struct SseUsageScanner {
+    buffer: Vec<u8>,
+    usage: TokenUsage,
+}
+
+impl SseUsageScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.buffer.extend_from_slice(chunk);
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            self.scan_line(&line);
+        }
+        if self.buffer.len() > MAX_LINE_BYTES {
+            self.buffer.clear();
+        }
+    }
+
+    fn scan_line(&mut self, line: &[u8]) {
+        let Ok(line) = std::str::from_utf8(line) else {
+            return;
+        };
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            return;
+        };
+        if let Some(usage) = serde_json::from_str::<Value>(data.trim())
+            .ok()
+            .as_ref()
+            .and_then(from_json)
+        {
+            self.usage.merge_max(usage);
+        }
+    }
+
+    pub fn usage(&self) -> TokenUsage {
+        self.usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_streaming_formats() {
+        let anthropic =
+            br#"{"usage":{"input_tokens":10,"cache_read_input_tokens":5,"output_tokens":7}}"#;
+        assert_eq!(
+            from_body(anthropic),
+            Some(TokenUsage {
+                input_tokens: 15,
+                output_tokens: 7
+            })
+        );
+
+        let openai = br#"{"usage":{"prompt_tokens":3,"completion_tokens":4}}"#;
+        assert_eq!(
+            from_body(openai),
+            Some(TokenUsage {
+                input_tokens: 3,
+                output_tokens: 4
+            })
+        );
+
+        let gemini = br#"{"usageMetadata":{"promptTokenCount":8,"candidatesTokenCount":2}}"#;
+        assert_eq!(from_body(gemini).unwrap().output_tokens, 2);
+
+        assert_eq!(from_body(b"<html>"), None);
+    }
+
+    #[test]
+    fn test_sse_scanner_handles_split_chunks() {
+        let stream = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":12,\"output_tokens\":1}}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":30}}\n\n",
+        );
+        let mut scanner = SseUsageScanner::new();
+        for chunk in stream.as_bytes().chunks(7) {
+            scanner.feed(chunk);
+        }
+        assert_eq!(
+            scanner.usage(),
+            TokenUsage {
+                input_tokens: 12,
+                output_tokens: 30
+            }
+        );
+    }
+}
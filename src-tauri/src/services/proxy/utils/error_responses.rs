@@ -37,6 +37,38 @@ pub fn proxy_loop_detected(tool_id: &str) -> Response<BoxBody> {
         .unwrap()
 }
 
+/// 凭证注入配置错误（拒绝转发占位密钥）
+pub fn credential_injection_failed(tool_id: &str, reason: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "CREDENTIAL_INJECTION_FAILED",
+        "message": format!("{tool_id} 透明代理无法注入供应商凭证"),
+        "details": reason,
+    });
+    Response::builder()
+        .status(StatusCode::UNAUTHORIZED)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 请求头指定的供应商无效
+pub fn invalid_provider_override(tool_id: &str, reason: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "INVALID_PROVIDER_OVERRIDE",
+        "message": format!("{tool_id} 透明代理无法使用请求指定的供应商"),
+        "details": reason,
+    });
+    Response::builder()
+        .status(StatusCode::BAD_REQUEST)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
 /// 未授权错误
 pub fn unauthorized() -> Response<BoxBody> {
     Response::builder()
@@ -47,6 +79,81 @@ pub fn unauthorized() -> Response<BoxBody> {
         .unwrap()
 }
 
+/// 请求路径不在端点白名单内
+pub fn endpoint_not_allowed(tool_id: &str, path: &str) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "ENDPOINT_NOT_ALLOWED",
+        "message": format!("{tool_id} 透明代理拒绝转发该路径: {path}"),
+        "path": path,
+        "details": "仅允许转发端点白名单中的 AI API 请求，可在代理设置中调整白名单",
+    });
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 超出并发上限
+pub fn proxy_overloaded(tool_id: &str, retry_after_secs: u64) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "PROXY_OVERLOADED",
+        "message": format!("{tool_id} 透明代理并发已达上限"),
+        "details": "请稍后重试，或在代理设置中调整并发上限",
+    });
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header("content-type", "application/json")
+        .header("retry-after", retry_after_secs.to_string())
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 上游超时（`phase` 为超时阶段）
+pub fn upstream_timeout(tool_id: &str, phase: &str, timeout_secs: u64) -> Response<BoxBody> {
+    let body = serde_json::json!({
+        "error": "UPSTREAM_TIMEOUT",
+        "message": format!("{tool_id} 透明代理等待上游响应超时"),
+        "phase": phase,
+        "timeout_secs": timeout_secs,
+        "details": "上游供应商响应过慢或不可达，可在代理设置中调整超时时间",
+    });
+    Response::builder()
+        .status(StatusCode::GATEWAY_TIMEOUT)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
+/// 协议转换失败（使用 Anthropic 错误格式，Claude Code 可直接展示原因）
+pub fn translation_failed(tool_id: &str, status: StatusCode, reason: &str) -> Response<BoxBody> {
+    let error_type = if status == StatusCode::BAD_REQUEST {
+        "invalid_request_error"
+    } else {
+        "api_error"
+    };
+    let body = serde_json::json!({
+        "type": "error",
+        "error": {
+            "type": error_type,
+            "message": format!("{tool_id} 透明代理协议转换失败: {reason}"),
+        },
+    });
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(box_body(http_body_util::Full::new(Bytes::from(
+            body.to_string(),
+        ))))
+        .unwrap()
+}
+
 /// 内部错误
 pub fn internal_error(message: &str) -> Response<BoxBody> {
     Response::builder()
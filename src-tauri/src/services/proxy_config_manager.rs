@@ -1,10 +1,71 @@
 //! 透明代理配置管理器
 
 use crate::data::DataManager;
-use crate::models::proxy_config::ProxyStore;
 use crate::models::proxy_config::ToolProxyConfig;
+use crate::models::proxy_config::{
+    ProxyProfile, ProxyProfileInfo, ProxyStore, PROXY_STORE_SCHEMA_VERSION,
+};
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// 单步迁移：将 JSON 从 schema N 升级到 N+1
+type MigrationStep = fn(&mut Value) -> Result<()>;
+
+/// 迁移步骤表，`MIGRATIONS[i]` 负责 v{i+1} → v{i+2}
+const MIGRATIONS: &[MigrationStep] = &[migrate_v1_to_v2];
+
+const TOOL_KEYS: &[&str] = &["claude-code", "codex", "gemini-cli"];
+
+/// 读取 JSON 中的结构版本（缺失视为 v1）
+fn schema_version_of(value: &Value) -> Result<u32> {
+    match value.get("schema_version") {
+        None => Ok(1),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| anyhow::anyhow!("proxy.json 的 schema_version 无效: {v}")),
+    }
+}
+
+/// 将 proxy.json 逐步升级到当前结构版本
+///
+/// 返回迁移前的版本；版本高于当前应用支持的版本时拒绝加载，避免覆盖新版本写入的数据。
+pub fn migrate_proxy_store_value(value: &mut Value) -> Result<u32> {
+    let from = schema_version_of(value)?;
+    if from > PROXY_STORE_SCHEMA_VERSION {
+        anyhow::bail!(
+            "proxy.json 的结构版本为 v{from}，当前应用仅支持到 v{PROXY_STORE_SCHEMA_VERSION}。\
+             该文件可能由更新版本的 DuckCoding 写入，请升级应用后重试（文件未做任何修改）"
+        );
+    }
+
+    for version in from..PROXY_STORE_SCHEMA_VERSION {
+        let step = MIGRATIONS[(version - 1) as usize];
+        step(value)
+            .with_context(|| format!("proxy.json 迁移 v{version} → v{} 失败", version + 1))?;
+        value["schema_version"] = Value::from(version + 1);
+    }
+    Ok(from)
+}
+
+/// v1 → v2：写出 schema_version，并补全各工具配置中依赖 serde 默认值的字段
+fn migrate_v1_to_v2(value: &mut Value) -> Result<()> {
+    let root = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow::anyhow!("proxy.json 顶层不是对象"))?;
+
+    for key in TOOL_KEYS {
+        let Some(raw) = root.get(*key) else {
+            continue;
+        };
+        let config: ToolProxyConfig = serde_json::from_value(raw.clone())
+            .with_context(|| format!("解析 {key} 的代理配置失败"))?;
+        root.insert(key.to_string(), serde_json::to_value(config)?);
+    }
+    Ok(())
+}
 
 pub struct ProxyConfigManager {
     data_manager: DataManager,
@@ -13,9 +74,7 @@ pub struct ProxyConfigManager {
 
 impl ProxyConfigManager {
     pub fn new() -> Result<Self> {
-        let home_dir = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?;
-        let duckcoding_dir = home_dir.join(".duckcoding");
-        std::fs::create_dir_all(&duckcoding_dir)?;
+        let duckcoding_dir = crate::utils::config::config_dir().map_err(|e| anyhow::anyhow!(e))?;
 
         Ok(Self {
             data_manager: DataManager::new(),
@@ -29,13 +88,30 @@ impl ProxyConfigManager {
             return Ok(ProxyStore::new());
         }
 
-        let value = self
+        let mut value = self
             .data_manager
             .json()
             .read(&self.proxy_path)
             .context("读取 proxy.json 失败")?;
 
-        serde_json::from_value(value).context("反序列化 ProxyStore 失败")
+        let from = migrate_proxy_store_value(&mut value)?;
+        let store: ProxyStore =
+            serde_json::from_value(value).context("反序列化 ProxyStore 失败")?;
+
+        if from < PROXY_STORE_SCHEMA_VERSION {
+            let backup = backup_path(&self.proxy_path, from);
+            std::fs::copy(&self.proxy_path, &backup)
+                .with_context(|| format!("备份迁移前的 proxy.json 失败: {backup:?}"))?;
+            self.save_proxy_store(&store)?;
+            tracing::info!(
+                from,
+                to = PROXY_STORE_SCHEMA_VERSION,
+                backup = ?backup,
+                "proxy.json 已迁移到新结构版本"
+            );
+        }
+
+        Ok(store)
     }
 
     /// 保存 proxy.json
@@ -47,6 +123,16 @@ impl ProxyConfigManager {
             .map_err(Into::into)
     }
 
+    /// 加锁读-改-写 proxy.json（其他进程持锁超时时返回错误）
+    fn update_store<T>(&self, f: impl FnOnce(&mut ProxyStore) -> Result<T>) -> Result<T> {
+        self.data_manager.with_file_lock(&self.proxy_path, || {
+            let mut store = self.load_proxy_store()?;
+            let result = f(&mut store)?;
+            self.save_proxy_store(&store)?;
+            Ok(result)
+        })
+    }
+
     /// 获取指定工具的代理配置
     pub fn get_config(&self, tool_id: &str) -> Result<Option<ToolProxyConfig>> {
         let store = self.load_proxy_store()?;
@@ -55,23 +141,144 @@ impl ProxyConfigManager {
 
     /// 更新指定工具的代理配置
     pub fn update_config(&self, tool_id: &str, config: ToolProxyConfig) -> Result<()> {
-        let mut store = self.load_proxy_store()?;
-        store.update_config(tool_id, config);
-        self.save_proxy_store(&store)
+        self.update_store(|store| {
+            store.update_config(tool_id, config);
+            Ok(())
+        })
     }
 
     /// 删除指定工具的代理配置（重置为默认）
     pub fn reset_config(&self, tool_id: &str) -> Result<()> {
-        let mut store = self.load_proxy_store()?;
         let default_port = ToolProxyConfig::default_port(tool_id);
-        store.update_config(tool_id, ToolProxyConfig::new(default_port));
-        self.save_proxy_store(&store)
+        self.update_store(|store| {
+            store.update_config(tool_id, ToolProxyConfig::new(default_port));
+            Ok(())
+        })
     }
 
     /// 获取所有工具的配置
     pub fn get_all_configs(&self) -> Result<ProxyStore> {
         self.load_proxy_store()
     }
+
+    /// 将工具当前配置保存为命名配置档（同名覆盖），并标记为当前激活
+    pub fn save_profile(&self, tool_id: &str, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("配置档名称不能为空");
+        }
+
+        self.update_store(|store| {
+            let mut config = store
+                .get_config(tool_id)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("不支持的工具: {tool_id}"))?;
+            // 运行期状态不属于配置档
+            config.original_active_profile = None;
+            config.tool_routing = None;
+
+            store
+                .profiles
+                .entry(tool_id.to_string())
+                .or_default()
+                .insert(
+                    name.to_string(),
+                    ProxyProfile {
+                        config,
+                        saved_at: chrono::Utc::now(),
+                    },
+                );
+            store
+                .active_profiles
+                .insert(tool_id.to_string(), name.to_string());
+            Ok(())
+        })
+    }
+
+    /// 列出工具的所有配置档
+    pub fn list_profiles(&self, tool_id: &str) -> Result<Vec<ProxyProfileInfo>> {
+        let store = self.load_proxy_store()?;
+        let active = store.active_profiles.get(tool_id);
+        Ok(store
+            .profiles
+            .get(tool_id)
+            .map(|profiles| {
+                profiles
+                    .iter()
+                    .map(|(name, profile)| ProxyProfileInfo {
+                        name: name.clone(),
+                        port: profile.config.port,
+                        allow_public: profile.config.allow_public,
+                        saved_at: profile.saved_at,
+                        active: active == Some(name),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// 激活配置档，写入工具配置并返回新配置
+    pub fn activate_profile(&self, tool_id: &str, name: &str) -> Result<ToolProxyConfig> {
+        self.update_store(|store| {
+            let current = store
+                .get_config(tool_id)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("不支持的工具: {tool_id}"))?;
+            let profile = store
+                .profiles
+                .get(tool_id)
+                .and_then(|profiles| profiles.get(name))
+                .ok_or_else(|| anyhow::anyhow!("配置档不存在: {name}"))?;
+
+            let mut config = profile.config.clone();
+            // 保留运行期状态，确保停止代理时仍能还原原始 Profile
+            config.original_active_profile = current.original_active_profile;
+            config.tool_routing = current.tool_routing;
+
+            store.update_config(tool_id, config.clone());
+            store
+                .active_profiles
+                .insert(tool_id.to_string(), name.to_string());
+            Ok(config)
+        })
+    }
+
+    /// 删除配置档（删除当前激活的配置档不影响工具配置本身）
+    pub fn delete_profile(&self, tool_id: &str, name: &str) -> Result<()> {
+        self.update_store(|store| {
+            let removed = store
+                .profiles
+                .get_mut(tool_id)
+                .and_then(|profiles| profiles.remove(name))
+                .is_some();
+            if !removed {
+                anyhow::bail!("配置档不存在: {name}");
+            }
+            if store.profiles.get(tool_id).is_some_and(|p| p.is_empty()) {
+                store.profiles.remove(tool_id);
+            }
+            if store.active_profiles.get(tool_id).map(String::as_str) == Some(name) {
+                store.active_profiles.remove(tool_id);
+            }
+            Ok(())
+        })
+    }
+
+    /// 获取工具当前激活的配置档名称
+    pub fn active_profile(&self, tool_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .load_proxy_store()?
+            .active_profiles
+            .get(tool_id)
+            .cloned())
+    }
+}
+
+/// 迁移前备份路径：proxy.json → proxy.json.v{N}.bak
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{version}.bak"));
+    path.with_file_name(name)
 }
 
 impl Default for ProxyConfigManager {
@@ -79,3 +286,146 @@ impl Default for ProxyConfigManager {
         Self::new().expect("创建 ProxyConfigManager 失败")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// 2.1.0 版本写出的 proxy.json（无 schema_version）
+    const V1_FIXTURE: &str = r#"{
+  "version": "2.1.0",
+  "claude-code": {
+    "enabled": true,
+    "port": 8787,
+    "local_api_key": "dc-local",
+    "real_api_key": "sk-real",
+    "real_base_url": "https://api.anthropic.com",
+    "real_profile_name": "work",
+    "allow_public": false,
+    "session_endpoint_config_enabled": true,
+    "auto_start": true
+  },
+  "codex": {
+    "enabled": false,
+    "port": 8788,
+    "allow_public": false,
+    "session_endpoint_config_enabled": false,
+    "auto_start": false
+  },
+  "gemini-cli": {
+    "enabled": false,
+    "port": 8789,
+    "allow_public": false,
+    "session_endpoint_config_enabled": false,
+    "auto_start": false
+  },
+  "metadata": {
+    "last_updated": "2025-06-01T08:00:00Z"
+  }
+}"#;
+
+    fn create_test_manager(temp_dir: &TempDir) -> ProxyConfigManager {
+        ProxyConfigManager {
+            data_manager: DataManager::new(),
+            proxy_path: temp_dir.path().join("proxy.json"),
+        }
+    }
+
+    #[test]
+    fn test_migrate_v1_value() {
+        let mut value: Value = serde_json::from_str(V1_FIXTURE).unwrap();
+        assert_eq!(migrate_proxy_store_value(&mut value).unwrap(), 1);
+
+        assert_eq!(value["schema_version"], PROXY_STORE_SCHEMA_VERSION);
+        let claude = &value["claude-code"];
+        assert_eq!(claude["real_api_key"], "sk-real");
+        assert_eq!(claude["auto_start"], true);
+        assert!(claude["allowed_path_prefixes"].is_array());
+        assert!(claude["retry_policy"].is_object());
+        assert!(claude["credential_injection"].is_object());
+
+        // 已是当前版本时不做修改
+        let migrated = value.clone();
+        assert_eq!(
+            migrate_proxy_store_value(&mut value).unwrap(),
+            PROXY_STORE_SCHEMA_VERSION
+        );
+        assert_eq!(value, migrated);
+    }
+
+    #[test]
+    fn test_load_migrates_and_backs_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        std::fs::write(&manager.proxy_path, V1_FIXTURE).unwrap();
+
+        let store = manager.load_proxy_store().unwrap();
+        assert_eq!(store.schema_version, PROXY_STORE_SCHEMA_VERSION);
+        assert_eq!(store.claude_code.real_profile_name.as_deref(), Some("work"));
+        assert!(store.claude_code.auto_start);
+
+        let backup = std::fs::read_to_string(temp_dir.path().join("proxy.json.v1.bak")).unwrap();
+        assert_eq!(backup, V1_FIXTURE);
+
+        let on_disk: Value =
+            serde_json::from_str(&std::fs::read_to_string(&manager.proxy_path).unwrap()).unwrap();
+        assert_eq!(on_disk["schema_version"], PROXY_STORE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_profile_save_activate_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+
+        let mut home = ToolProxyConfig::new(8787);
+        home.real_base_url = Some("https://api.anthropic.com".to_string());
+        manager.update_config("claude-code", home).unwrap();
+        manager.save_profile("claude-code", "home").unwrap();
+
+        let mut office = ToolProxyConfig::new(9787);
+        office.real_base_url = Some("https://relay.corp.example.com".to_string());
+        office.allowed_path_prefixes = vec!["/v1/messages".to_string()];
+        manager.update_config("claude-code", office).unwrap();
+        manager.save_profile("claude-code", "office").unwrap();
+
+        let profiles = manager.list_profiles("claude-code").unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert!(profiles.iter().any(|p| p.name == "office" && p.active));
+        assert!(manager.list_profiles("codex").unwrap().is_empty());
+
+        let config = manager.activate_profile("claude-code", "home").unwrap();
+        assert_eq!(config.port, 8787);
+        let stored = manager.get_config("claude-code").unwrap().unwrap();
+        assert_eq!(
+            stored.real_base_url.as_deref(),
+            Some("https://api.anthropic.com")
+        );
+        assert_eq!(
+            manager.active_profile("claude-code").unwrap().as_deref(),
+            Some("home")
+        );
+
+        manager.delete_profile("claude-code", "home").unwrap();
+        assert_eq!(manager.active_profile("claude-code").unwrap(), None);
+        assert!(manager.activate_profile("claude-code", "home").is_err());
+        assert!(manager.save_profile("claude-code", "  ").is_err());
+    }
+
+    #[test]
+    fn test_reject_newer_schema_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        let mut value: Value = serde_json::from_str(V1_FIXTURE).unwrap();
+        value["schema_version"] = Value::from(PROXY_STORE_SCHEMA_VERSION + 1);
+        let content = serde_json::to_string_pretty(&value).unwrap();
+        std::fs::write(&manager.proxy_path, &content).unwrap();
+
+        let err = manager.load_proxy_store().unwrap_err();
+        assert!(err.to_string().contains("请升级应用"));
+        assert_eq!(
+            std::fs::read_to_string(&manager.proxy_path).unwrap(),
+            content
+        );
+    }
+}
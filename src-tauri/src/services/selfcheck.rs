@@ -0,0 +1,569 @@
+// Startup Self-check Service
+//
+// 启动自检：集中检查新用户最常遇到的前置条件（配置目录不可写、实例数据库损坏、
+// 缺少 Node.js、WSL 安装不完整、代理端口被占用、凭证文件权限过宽、配置目录位于网络共享），
+// 返回前端可直接渲染为检查清单的结果。每项检查独立限时并发执行，
+// 任何检查失败或超时都只体现在结果中，不会阻止应用进入界面。
+
+use crate::data::network_fs::{self, NetworkMount};
+use crate::models::proxy_config::ToolProxyConfig;
+use crate::services::proxy::health::HEALTH_PATH;
+use crate::services::proxy_config_manager::ProxyConfigManager;
+use crate::services::tool::ToolInstanceDB;
+use crate::utils::config::config_dir;
+use crate::utils::{CommandExecutor, WSLExecutor};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::future::Future;
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 启动自检完成后发送的事件（负载为 `Vec<SelfCheckItem>`）
+pub const SELFCHECK_COMPLETED_EVENT: &str = "startup-selfcheck-completed";
+
+/// 单项检查的时限
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 配置目录位于网络共享时单项检查的时限
+pub const NETWORK_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 已提示过的网络共享挂载点（每个挂载点只提示一次）
+const NETWORK_NOTICE_FILE: &str = "network_share_notice.json";
+
+const PROXY_TOOLS: [&str; 3] = ["claude-code", "codex", "gemini-cli"];
+
+/// 检查项
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfCheck {
+    /// 配置目录可写
+    ConfigDirWritable,
+    /// 工具实例数据库可以打开
+    InstanceDb,
+    /// Node.js 与 npm（npm 安装方式需要）
+    NodeEnvironment,
+    /// WSL 可用（仅 Windows）
+    Wsl,
+    /// 开启自启动的代理端口未被其他程序占用
+    ProxyPorts,
+    /// 保存供应商凭证的文件可读且权限不过宽
+    SecretStore,
+    /// 配置目录不在网络共享上（漫游配置文件）
+    NetworkShare,
+}
+
+/// 检查结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    /// 不影响启动，但相关功能不可用
+    Warn,
+    Fail,
+    /// 不适用（如非 Windows 平台的 WSL 检查）
+    Skipped,
+}
+
+/// 修复建议（前端据此展示对应的操作指引）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemediationCode {
+    FixConfigDirPermissions,
+    RepairInstanceDb,
+    InstallNode,
+    InstallNpm,
+    RepairWsl,
+    InstallWslDistro,
+    ChangeProxyPort,
+    RestrictSecretFilePermissions,
+    RepairProviderStore,
+    /// 配置目录位于网络共享：读写较慢且不支持文件监听，建议改用本地目录
+    UseLocalConfigDir,
+    /// 检查超时，稍后重试
+    RetryCheck,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfCheckItem {
+    pub check: SelfCheck,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub remediation_code: Option<RemediationCode>,
+    /// 检查耗时（毫秒）
+    pub duration_ms: u64,
+}
+
+/// 最近一次自检结果（启动时在后台执行，前端可能晚于事件加载）
+static LAST_RESULT: Lazy<Mutex<Option<Vec<SelfCheckItem>>>> = Lazy::new(|| Mutex::new(None));
+
+/// 最近一次自检结果（尚未完成时为 None）
+pub fn last_selfcheck() -> Option<Vec<SelfCheckItem>> {
+    LAST_RESULT.lock().unwrap().clone()
+}
+
+struct Outcome {
+    status: CheckStatus,
+    detail: String,
+    remediation_code: Option<RemediationCode>,
+}
+
+impl Outcome {
+    fn pass(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            remediation_code: None,
+        }
+    }
+
+    fn skipped(detail: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            detail: detail.into(),
+            remediation_code: None,
+        }
+    }
+
+    fn warn(detail: impl Into<String>, code: RemediationCode) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            remediation_code: Some(code),
+        }
+    }
+
+    fn fail(detail: impl Into<String>, code: RemediationCode) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            remediation_code: Some(code),
+        }
+    }
+}
+
+/// 执行全部检查并记录结果
+///
+/// `running_proxies` 为当前由本进程运行的代理所属工具 ID，这些工具的端口不再检查。
+pub async fn run_startup_selfcheck(running_proxies: &[String]) -> Vec<SelfCheckItem> {
+    let running: Vec<String> = running_proxies.to_vec();
+    let (config_dir, instance_db, node, wsl, ports, secrets, network) = tokio::join!(
+        timed(SelfCheck::ConfigDirWritable, blocking(check_config_dir)),
+        timed(SelfCheck::InstanceDb, blocking(check_instance_db)),
+        timed(SelfCheck::NodeEnvironment, check_node_environment()),
+        timed(SelfCheck::Wsl, blocking(check_wsl)),
+        timed(SelfCheck::ProxyPorts, check_proxy_ports(running)),
+        timed(SelfCheck::SecretStore, blocking(check_secret_store)),
+        timed(SelfCheck::NetworkShare, blocking(check_network_share)),
+    );
+    let items = vec![config_dir, instance_db, node, wsl, ports, secrets, network];
+
+    for item in &items {
+        if matches!(item.status, CheckStatus::Warn | CheckStatus::Fail) {
+            tracing::warn!(check = ?item.check, status = ?item.status, detail = %item.detail, "启动自检未通过");
+        }
+    }
+    *LAST_RESULT.lock().unwrap() = Some(items.clone());
+    items
+}
+
+/// 限时执行单项检查（超时记为 Warn，不阻塞其余检查）
+async fn timed(check: SelfCheck, future: impl Future<Output = Outcome>) -> SelfCheckItem {
+    let limit = if network_fs::network_backed() {
+        NETWORK_CHECK_TIMEOUT
+    } else {
+        CHECK_TIMEOUT
+    };
+    timed_within(check, limit, future).await
+}
+
+async fn timed_within(
+    check: SelfCheck,
+    limit: Duration,
+    future: impl Future<Output = Outcome>,
+) -> SelfCheckItem {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(limit, future)
+        .await
+        .unwrap_or_else(|_| {
+            Outcome::warn(
+                format!("检查超时（{} 毫秒）", limit.as_millis()),
+                RemediationCode::RetryCheck,
+            )
+        });
+    SelfCheckItem {
+        check,
+        status: outcome.status,
+        detail: outcome.detail,
+        remediation_code: outcome.remediation_code,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// 在阻塞线程池中执行同步检查
+async fn blocking(f: impl FnOnce() -> Outcome + Send + 'static) -> Outcome {
+    tokio::task::spawn_blocking(f).await.unwrap_or_else(|e| {
+        Outcome::warn(format!("检查异常退出: {e}"), RemediationCode::RetryCheck)
+    })
+}
+
+fn check_config_dir() -> Outcome {
+    match config_dir() {
+        Ok(dir) => check_dir_writable(&dir),
+        Err(e) => Outcome::fail(
+            format!("无法创建配置目录: {e}"),
+            RemediationCode::FixConfigDirPermissions,
+        ),
+    }
+}
+
+/// 写入并删除一个探测文件
+fn check_dir_writable(dir: &Path) -> Outcome {
+    let probe = dir.join(format!(".selfcheck-{}", std::process::id()));
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            Outcome::pass(dir.to_string_lossy())
+        }
+        Err(e) => Outcome::fail(
+            format!("{} 不可写: {e}", dir.display()),
+            RemediationCode::FixConfigDirPermissions,
+        ),
+    }
+}
+
+fn check_instance_db() -> Outcome {
+    match ToolInstanceDB::new().and_then(|db| db.get_all_instances().map(|all| all.len())) {
+        Ok(count) => Outcome::pass(format!("{count} 个工具实例")),
+        Err(e) => Outcome::fail(format!("{e:#}"), RemediationCode::RepairInstanceDb),
+    }
+}
+
+async fn check_node_environment() -> Outcome {
+    let executor = CommandExecutor::new().with_timeout(CHECK_TIMEOUT);
+    let (node, npm) = tokio::join!(
+        executor.execute_async("node --version"),
+        executor.execute_async("npm --version"),
+    );
+    match (node.success, npm.success) {
+        (true, true) => Outcome::pass(format!(
+            "Node.js {}，npm {}",
+            node.stdout.trim(),
+            npm.stdout.trim()
+        )),
+        (true, false) => Outcome::warn(
+            format!(
+                "已找到 Node.js {}，但未找到 npm，无法使用 npm 安装工具",
+                node.stdout.trim()
+            ),
+            RemediationCode::InstallNpm,
+        ),
+        (false, _) => Outcome::warn(
+            "未找到 Node.js，无法使用 npm 安装工具（官方安装方式不受影响）",
+            RemediationCode::InstallNode,
+        ),
+    }
+}
+
+fn check_wsl() -> Outcome {
+    if !cfg!(target_os = "windows") {
+        return Outcome::skipped("仅 Windows 需要检查");
+    }
+    if WSLExecutor::is_available() {
+        return match WSLExecutor::list_distributions() {
+            Ok(distros) if !distros.is_empty() => {
+                Outcome::pass(format!("已安装发行版: {}", distros.join(", ")))
+            }
+            Ok(_) => Outcome::warn(
+                "WSL 已启用但没有安装任何发行版",
+                RemediationCode::InstallWslDistro,
+            ),
+            Err(e) => Outcome::warn(format!("{e:#}"), RemediationCode::RepairWsl),
+        };
+    }
+    // wsl.exe 存在但 `wsl --status` 失败，通常是功能未启用或内核未安装
+    if CommandExecutor::new().command_exists("wsl.exe") {
+        Outcome::warn(
+            "找到 wsl.exe 但 WSL 无法运行（可能未启用虚拟机平台或未安装内核）",
+            RemediationCode::RepairWsl,
+        )
+    } else {
+        Outcome::skipped("未安装 WSL")
+    }
+}
+
+async fn check_proxy_ports(running: Vec<String>) -> Outcome {
+    let store = match ProxyConfigManager::new().and_then(|mgr| mgr.load_proxy_store()) {
+        Ok(store) => store,
+        Err(e) => {
+            return Outcome::warn(
+                format!("读取代理配置失败: {e:#}"),
+                RemediationCode::RetryCheck,
+            )
+        }
+    };
+    let configs: Vec<(String, ToolProxyConfig)> = PROXY_TOOLS
+        .iter()
+        .filter(|tool_id| !running.iter().any(|id| id == *tool_id))
+        .filter_map(|tool_id| {
+            store
+                .get_config(tool_id)
+                .map(|config| (tool_id.to_string(), config.clone()))
+        })
+        .collect();
+    check_ports(&configs).await
+}
+
+/// 检查开启自启动的代理端口
+///
+/// 只探测连接而不绑定端口，避免与正在启动的代理抢占；端口上响应 DuckCoding 健康检查时
+/// 视为本应用（或另一个 DuckCoding 进程）的代理。
+async fn check_ports(configs: &[(String, ToolProxyConfig)]) -> Outcome {
+    let auto_start: Vec<&(String, ToolProxyConfig)> = configs
+        .iter()
+        .filter(|(_, config)| config.enabled && config.auto_start)
+        .collect();
+    if auto_start.is_empty() {
+        return Outcome::skipped("没有开启自启动的代理");
+    }
+
+    let mut occupied = Vec::new();
+    for (tool_id, config) in auto_start {
+        let port = config.port;
+        let in_use = tokio::task::spawn_blocking(move || {
+            let addr = SocketAddr::from(([127, 0, 0, 1], port));
+            TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok()
+        })
+        .await
+        .unwrap_or(false);
+        if in_use && !is_duckcoding_proxy(port).await {
+            occupied.push(format!("{tool_id}: {port}"));
+        }
+    }
+
+    if occupied.is_empty() {
+        Outcome::pass("代理端口可用")
+    } else {
+        Outcome::fail(
+            format!("端口已被其他程序占用（{}）", occupied.join("，")),
+            RemediationCode::ChangeProxyPort,
+        )
+    }
+}
+
+async fn is_duckcoding_proxy(port: u16) -> bool {
+    let Ok(client) = reqwest::Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(1))
+        .build()
+    else {
+        return false;
+    };
+    client
+        .get(format!("http://127.0.0.1:{port}{HEALTH_PATH}"))
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+fn check_secret_store() -> Outcome {
+    match config_dir() {
+        Ok(dir) => check_secret_file(&dir.join("providers.json")),
+        Err(e) => Outcome::warn(
+            format!("无法定位配置目录: {e}"),
+            RemediationCode::FixConfigDirPermissions,
+        ),
+    }
+}
+
+/// 供应商凭证保存在 providers.json 中：要求能解析，Unix 上不允许其他用户读取
+fn check_secret_file(path: &Path) -> Outcome {
+    if !path.exists() {
+        return Outcome::pass("尚未保存供应商凭证");
+    }
+    let parsed = std::fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(serde_json::from_str::<serde_json::Value>(&content)?));
+    if let Err(e) = parsed {
+        return Outcome::fail(
+            format!("{} 无法读取: {e}", path.display()),
+            RemediationCode::RepairProviderStore,
+        );
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(meta) = path.metadata() {
+            let mode = meta.permissions().mode() & 0o777;
+            if mode & 0o077 != 0 {
+                return Outcome::warn(
+                    format!("{} 的权限为 {mode:o}，其他用户可读取凭证", path.display()),
+                    RemediationCode::RestrictSecretFilePermissions,
+                );
+            }
+        }
+    }
+    Outcome::pass(path.to_string_lossy())
+}
+
+fn check_network_share() -> Outcome {
+    let mounts = network_fs::network_mounts();
+    if mounts.is_empty() {
+        return Outcome::pass("配置目录位于本地磁盘");
+    }
+    let latency = mounts
+        .iter()
+        .filter(|mount| mount.path.is_dir())
+        .filter_map(|mount| network_fs::measure_latency(&mount.path).ok())
+        .max();
+    match config_dir() {
+        Ok(dir) => network_share_outcome(mounts, latency, &dir.join(NETWORK_NOTICE_FILE)),
+        Err(e) => Outcome::warn(
+            format!("无法定位配置目录: {e}"),
+            RemediationCode::FixConfigDirPermissions,
+        ),
+    }
+}
+
+/// 首次发现某个网络挂载点时给出警告，之后只在结果中说明（记录在 `notice_file`）
+fn network_share_outcome(
+    mounts: &[NetworkMount],
+    latency: Option<Duration>,
+    notice_file: &Path,
+) -> Outcome {
+    let mut detail = mounts
+        .iter()
+        .map(|mount| {
+            format!(
+                "{} 位于网络共享（{}，{}）",
+                mount.path.display(),
+                mount.fs_type,
+                mount.mount_point
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("；");
+    if let Some(latency) = latency {
+        detail.push_str(&format!("，访问延迟约 {} 毫秒", latency.as_millis()));
+    }
+    detail.push_str("。已停用配置文件监听，外部修改需手动刷新");
+
+    let mut notified: Vec<String> = std::fs::read_to_string(notice_file)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let new_mounts: Vec<&NetworkMount> = mounts
+        .iter()
+        .filter(|mount| !notified.contains(&mount.mount_point))
+        .collect();
+    if new_mounts.is_empty() {
+        return Outcome::pass(detail);
+    }
+
+    notified.extend(new_mounts.iter().map(|mount| mount.mount_point.clone()));
+    notified.sort();
+    notified.dedup();
+    let written = serde_json::to_string(&notified)
+        .map_err(std::io::Error::other)
+        .and_then(|content| std::fs::write(notice_file, content));
+    if let Err(e) = written {
+        tracing::warn!(path = ?notice_file, error = ?e, "记录网络共享提示失败");
+    }
+    Outcome::warn(detail, RemediationCode::UseLocalConfigDir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn proxy_config(port: u16, auto_start: bool) -> ToolProxyConfig {
+        let mut config = crate::models::proxy_config::ProxyStore::new().claude_code;
+        config.enabled = true;
+        config.auto_start = auto_start;
+        config.port = port;
+        config
+    }
+
+    #[test]
+    fn test_dir_writable() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(check_dir_writable(dir.path()).status, CheckStatus::Pass);
+        assert_eq!(
+            check_dir_writable(&dir.path().join("missing")).status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[tokio::test]
+    async fn test_occupied_port_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // 保持监听但不响应 HTTP，健康检查失败即视为被其他程序占用
+        let outcome = check_ports(&[("claude-code".to_string(), proxy_config(port, true))]).await;
+        assert_eq!(outcome.status, CheckStatus::Fail);
+        assert_eq!(
+            outcome.remediation_code,
+            Some(RemediationCode::ChangeProxyPort)
+        );
+
+        drop(listener);
+        let outcome = check_ports(&[("claude-code".to_string(), proxy_config(port, false))]).await;
+        assert_eq!(outcome.status, CheckStatus::Skipped);
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out() {
+        let item = timed_within(SelfCheck::Wsl, Duration::from_millis(20), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Outcome::pass("unreachable")
+        })
+        .await;
+        assert_eq!(item.status, CheckStatus::Warn);
+        assert_eq!(item.remediation_code, Some(RemediationCode::RetryCheck));
+    }
+
+    #[test]
+    fn test_network_share_warns_once_per_mount() {
+        let dir = TempDir::new().unwrap();
+        let notice = dir.path().join(NETWORK_NOTICE_FILE);
+        let mounts = vec![NetworkMount {
+            path: dir.path().join(".claude"),
+            mount_point: "/home/alice".to_string(),
+            fs_type: "cifs".to_string(),
+        }];
+
+        let first = network_share_outcome(&mounts, Some(Duration::from_millis(1800)), &notice);
+        assert_eq!(first.status, CheckStatus::Warn);
+        assert_eq!(
+            first.remediation_code,
+            Some(RemediationCode::UseLocalConfigDir)
+        );
+        assert!(first.detail.contains("1800 毫秒"));
+
+        let second = network_share_outcome(&mounts, None, &notice);
+        assert_eq!(second.status, CheckStatus::Pass);
+        assert!(second.detail.contains("cifs"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_secret_file_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("providers.json");
+        assert_eq!(check_secret_file(&path).status, CheckStatus::Pass);
+
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(check_secret_file(&path).status, CheckStatus::Warn);
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(check_secret_file(&path).status, CheckStatus::Pass);
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(check_secret_file(&path).status, CheckStatus::Fail);
+    }
+}
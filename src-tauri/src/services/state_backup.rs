@@ -0,0 +1,725 @@
+// State Backup Service
+//
+// 完整状态备份：把供应商、工具实例数据库、仪表板、透明代理配置与 Profile 打包成单个带版本号的
+// 备份文件，写入用户选择的目录（如同步盘），按保留份数轮转。备份包含密钥时必须设置口令，
+// 正文以 AES-256-GCM 加密（密钥由口令经 PBKDF2-SHA256 派生）。
+//
+// 文件格式：`MAGIC` | 格式版本（u32 LE）| 标志（u8，bit0 = 加密）
+//           | [PBKDF2 迭代次数（u32 LE）| salt | nonce] | 正文（`BackupArchive` 的 JSON）
+//
+// 恢复前先把当前状态保存为安全快照（配置目录下的 backups/pre-restore-*.dcbackup），再逐项恢复，
+// 单项失败不影响其余各项。备份结果（自动与手动）都通过 `STATE_BACKUP_EVENT` 通知前端，失败不会被静默跳过。
+
+use crate::data::backup::{self, BackupPolicy, DEFAULT_BACKUP_KEEP};
+use crate::data::{path_policy, DataManager, Durability};
+use crate::models::{
+    BackupComponent, BackupRunResult, ComponentResult, RestoreReport, StateBackupSettings,
+    ToolInstance,
+};
+use crate::services::config::write_ledger::{WriteLedger, LEDGER_FILE};
+use crate::services::provider_manager::invalidate_shared_providers;
+use crate::services::tool::ToolInstanceDB;
+use crate::utils::config::{config_dir, read_global_config};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// 备份结果事件名（前端监听并弹出通知）
+pub const STATE_BACKUP_EVENT: &str = "state-backup";
+
+/// 备份文件扩展名
+pub const ARCHIVE_EXTENSION: &str = "dcbackup";
+
+/// 备份文件格式版本
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// 自动备份的文件名前缀
+const ARCHIVE_PREFIX: &str = "duckcoding-backup-";
+
+/// 恢复前安全快照的文件名前缀
+const SAFETY_PREFIX: &str = "pre-restore-";
+
+/// 恢复写入在写入记录中的操作名
+const RESTORE_LEDGER_ACTION: &str = "state_backup_restore";
+
+const MAGIC: &[u8; 8] = b"DCBACKUP";
+const FLAG_ENCRYPTED: u8 = 1;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// 读取备份时接受的迭代次数范围（迭代次数来自文件头，过大的值会让恢复长时间卡住）
+const MIN_PBKDF2_ITERATIONS: u32 = 100_000;
+const MAX_PBKDF2_ITERATIONS: u32 = PBKDF2_ITERATIONS * 10;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 文件名中的时间戳格式（字典序即时间顺序）
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3f";
+
+/// 检查是否需要自动备份的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// 自动备份失败后，至少间隔多久再重试（避免反复通知）
+const RETRY_AFTER_FAILURE_SECS: i64 = 60 * 60;
+
+/// 全局备份服务
+pub static STATE_BACKUP: Lazy<BackupService> = Lazy::new(BackupService::new);
+
+/// 备份文件正文
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupArchive {
+    format_version: u32,
+    app_version: String,
+    created_at: i64,
+    components: Vec<ArchivedComponent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchivedComponent {
+    component: BackupComponent,
+    /// 文件名 → 内容（备份时不存在的文件不记录）
+    #[serde(default)]
+    files: BTreeMap<String, Value>,
+    /// 工具实例（仅 `InstanceDb`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    instances: Option<Vec<ToolInstance>>,
+}
+
+/// 备份事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupEventKind {
+    Succeeded,
+    Failed,
+}
+
+/// `state-backup` 事件负载
+#[derive(Debug, Clone, Serialize)]
+pub struct StateBackupEvent {
+    pub kind: BackupEventKind,
+    /// 是否为定时自动备份
+    pub automatic: bool,
+    /// 生成的备份文件（成功时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub message: String,
+}
+
+/// 完整状态备份服务
+pub struct BackupService {
+    /// DuckCoding 配置目录（`None` 表示无法确定）
+    data_dir: Option<PathBuf>,
+    data_manager: DataManager,
+    /// 备份与恢复互斥
+    lock: Mutex<()>,
+    /// 最近一次自动备份失败的时间
+    last_failure_at: Mutex<Option<i64>>,
+    events: broadcast::Sender<StateBackupEvent>,
+}
+
+impl BackupService {
+    fn new() -> Self {
+        let data_dir = match config_dir() {
+            Ok(dir) => Some(dir),
+            Err(e) => {
+                tracing::warn!(error = %e, "获取配置目录失败，完整状态备份不可用");
+                None
+            }
+        };
+        Self::with_dir(data_dir)
+    }
+
+    /// 备份与恢复指定目录下的状态（测试用）
+    pub fn at(data_dir: PathBuf) -> Self {
+        Self::with_dir(Some(data_dir))
+    }
+
+    fn with_dir(data_dir: Option<PathBuf>) -> Self {
+        let (events, _) = broadcast::channel(16);
+        Self {
+            data_dir,
+            data_manager: DataManager::new(),
+            lock: Mutex::new(()),
+            last_failure_at: Mutex::new(None),
+            events,
+        }
+    }
+
+    /// 订阅备份结果事件
+    pub fn subscribe(&self) -> broadcast::Receiver<StateBackupEvent> {
+        self.events.subscribe()
+    }
+
+    fn data_dir(&self) -> Result<&Path> {
+        self.data_dir
+            .as_deref()
+            .ok_or_else(|| anyhow!("获取配置目录失败"))
+    }
+
+    /// 全局配置中的备份设置（读取失败时视为默认设置，即关闭）
+    pub fn settings() -> StateBackupSettings {
+        match read_global_config() {
+            Ok(Some(config)) => config.state_backup,
+            Ok(None) => StateBackupSettings::default(),
+            Err(e) => {
+                tracing::warn!(error = %e, "读取备份设置失败，使用默认值");
+                StateBackupSettings::default()
+            }
+        }
+    }
+
+    /// 立即备份，并通过事件通知结果
+    pub fn run_backup_now(
+        &self,
+        settings: &StateBackupSettings,
+        automatic: bool,
+    ) -> Result<BackupRunResult> {
+        let result = self.backup(settings);
+        let event = match &result {
+            Ok(run) => {
+                tracing::info!(path = %run.path, automatic, "完整状态备份完成");
+                StateBackupEvent {
+                    kind: BackupEventKind::Succeeded,
+                    automatic,
+                    path: Some(run.path.clone()),
+                    message: format!("已备份到 {}", run.path),
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, automatic, "完整状态备份失败");
+                StateBackupEvent {
+                    kind: BackupEventKind::Failed,
+                    automatic,
+                    path: None,
+                    message: format!("备份失败: {e:#}"),
+                }
+            }
+        };
+        // 没有订阅者时 send 返回错误，可以忽略
+        let _ = self.events.send(event);
+        result
+    }
+
+    fn backup(&self, settings: &StateBackupSettings) -> Result<BackupRunResult> {
+        validate_settings(settings)?;
+        let target_dir = settings
+            .target_dir
+            .as_deref()
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("未设置备份目录"))?;
+
+        let _guard = self.lock.lock().unwrap();
+        let (archive, components) = self.collect(&settings.components)?;
+        let passphrase = settings.passphrase();
+        let content = seal(&archive, passphrase, PBKDF2_ITERATIONS)?;
+
+        std::fs::create_dir_all(&target_dir)
+            .with_context(|| format!("创建备份目录失败: {target_dir:?}"))?;
+        let path = archive_path(&target_dir, ARCHIVE_PREFIX);
+        backup::atomic_write(&path, &content, Durability::Durable)
+            .with_context(|| format!("写入备份失败: {path:?}"))?;
+        let pruned = prune(&target_dir, ARCHIVE_PREFIX, settings.retention as usize)?;
+
+        Ok(BackupRunResult {
+            path: path.to_string_lossy().into_owned(),
+            encrypted: passphrase.is_some(),
+            created_at: archive.created_at,
+            size: content.len() as u64,
+            components,
+            pruned,
+        })
+    }
+
+    /// 读取各组成的当前内容（任一组成失败时整个备份失败）
+    fn collect(
+        &self,
+        components: &[BackupComponent],
+    ) -> Result<(BackupArchive, Vec<ComponentResult>)> {
+        let dir = self.data_dir()?;
+        let mut archived = Vec::with_capacity(components.len());
+        let mut results = Vec::with_capacity(components.len());
+        for &component in components {
+            let mut entry = ArchivedComponent {
+                component,
+                files: BTreeMap::new(),
+                instances: None,
+            };
+            for name in component.files() {
+                let path = dir.join(name);
+                if path.exists() {
+                    let value = self
+                        .data_manager
+                        .json_uncached()
+                        .read(&path)
+                        .with_context(|| format!("读取 {name} 失败"))?;
+                    entry.files.insert(name.to_string(), value);
+                }
+            }
+            let message = if component == BackupComponent::InstanceDb {
+                let instances = ToolInstanceDB::open(&dir.join("tools.db"))
+                    .and_then(|db| db.get_all_instances())
+                    .context("导出工具实例失败")?;
+                let message = format!("{} 个工具实例", instances.len());
+                entry.instances = Some(instances);
+                message
+            } else {
+                format!("{} 个文件", entry.files.len())
+            };
+            results.push(ComponentResult {
+                component,
+                success: true,
+                message,
+            });
+            archived.push(entry);
+        }
+
+        let archive = BackupArchive {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+            components: archived,
+        };
+        Ok((archive, results))
+    }
+
+    /// 从备份文件恢复全部内容，返回每个组成的结果
+    ///
+    /// 恢复前把当前状态保存为安全快照；快照失败时不做任何恢复。
+    pub fn restore_full_backup(
+        &self,
+        path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<RestoreReport> {
+        let content = std::fs::read(path).with_context(|| format!("读取备份失败: {path:?}"))?;
+        let archive = open(&content, passphrase)?;
+
+        let _guard = self.lock.lock().unwrap();
+        let safety_snapshot = self
+            .safety_snapshot(passphrase)
+            .context("恢复前保存安全快照失败，未做任何恢复")?;
+
+        let components: Vec<ComponentResult> = archive
+            .components
+            .iter()
+            .map(|entry| match self.restore_component(entry) {
+                Ok(message) => ComponentResult {
+                    component: entry.component,
+                    success: true,
+                    message,
+                },
+                Err(e) => {
+                    tracing::error!(component = ?entry.component, error = ?e, "恢复备份组成失败");
+                    ComponentResult {
+                        component: entry.component,
+                        success: false,
+                        message: format!("{e:#}"),
+                    }
+                }
+            })
+            .collect();
+
+        let success = components.iter().all(|c| c.success);
+        tracing::info!(path = ?path, success, "完整状态恢复完成");
+        Ok(RestoreReport {
+            created_at: archive.created_at,
+            app_version: archive.app_version,
+            safety_snapshot: Some(safety_snapshot.to_string_lossy().into_owned()),
+            components,
+            success,
+        })
+    }
+
+    /// 把当前全部状态保存到配置目录下的 backups/（有口令时加密）
+    ///
+    /// 未提供口令时快照不加密：它与明文保存密钥的配置文件位于同一目录。
+    fn safety_snapshot(&self, passphrase: Option<&str>) -> Result<PathBuf> {
+        let dir = self.data_dir()?.join(backup::BACKUP_DIR_NAME);
+        let (archive, _) = self.collect(&BackupComponent::all())?;
+        let content = seal(&archive, passphrase, PBKDF2_ITERATIONS)?;
+        std::fs::create_dir_all(&dir).with_context(|| format!("创建目录失败: {dir:?}"))?;
+        let path = archive_path(&dir, SAFETY_PREFIX);
+        backup::atomic_write(&path, &content, Durability::Durable)?;
+        prune(&dir, SAFETY_PREFIX, DEFAULT_BACKUP_KEEP)?;
+        Ok(path)
+    }
+
+    fn restore_component(&self, entry: &ArchivedComponent) -> Result<String> {
+        let dir = self.data_dir()?;
+        if entry.component == BackupComponent::InstanceDb {
+            let instances = entry.instances.as_deref().unwrap_or_default();
+            ToolInstanceDB::open(&dir.join("tools.db"))?.replace_all_instances(instances)?;
+            return Ok(format!("已恢复 {} 个工具实例", instances.len()));
+        }
+
+        // 只写入该组成的已知文件，忽略备份中的其他文件名；
+        // 与各管理器的保存一致：持有文件锁写入、覆盖前备份旧文件，并记录写入来源
+        let ledger = WriteLedger::at(dir.join(LEDGER_FILE));
+        let mut restored = 0;
+        for name in entry.component.files() {
+            if let Some(value) = entry.files.get(*name) {
+                let path = dir.join(name);
+                self.data_manager
+                    .with_file_lock(&path, || {
+                        self.data_manager.write_json_with_backup(
+                            &path,
+                            value,
+                            &BackupPolicy::beside(&path),
+                        )
+                    })
+                    .with_context(|| format!("写入 {name} 失败"))?;
+                DataManager::global().invalidate(&path);
+                if let Err(e) = ledger.record(&path, RESTORE_LEDGER_ACTION) {
+                    tracing::warn!(path = ?path, error = ?e, "记录恢复写入失败");
+                }
+                restored += 1;
+            }
+        }
+        if entry.component == BackupComponent::Providers {
+            invalidate_shared_providers();
+        }
+        Ok(format!("已恢复 {restored} 个文件"))
+    }
+
+    /// 后台循环：按设置的频率自动备份（不会返回）
+    pub async fn run(&'static self) {
+        loop {
+            let settings = Self::settings();
+            if self.backup_due(&settings, chrono::Utc::now().timestamp()) {
+                let result =
+                    tokio::task::spawn_blocking(move || self.run_backup_now(&settings, true)).await;
+                let failed = !matches!(result, Ok(Ok(_)));
+                *self.last_failure_at.lock().unwrap() =
+                    failed.then(|| chrono::Utc::now().timestamp());
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    }
+
+    /// 距离上次备份已超过设置的间隔（失败后一小时内不重试）
+    fn backup_due(&self, settings: &StateBackupSettings, now: i64) -> bool {
+        let (Some(interval), Some(target_dir)) =
+            (settings.schedule.interval_secs(), &settings.target_dir)
+        else {
+            return false;
+        };
+        if let Some(failed_at) = *self.last_failure_at.lock().unwrap() {
+            if now - failed_at < RETRY_AFTER_FAILURE_SECS {
+                return false;
+            }
+        }
+        latest_archive_time(Path::new(target_dir), ARCHIVE_PREFIX)
+            .is_none_or(|last| now - last >= interval)
+    }
+}
+
+/// 校验备份设置：包含密钥时必须设置口令
+pub fn validate_settings(settings: &StateBackupSettings) -> Result<()> {
+    if settings.components.is_empty() {
+        return Err(anyhow!("至少选择一项备份内容"));
+    }
+    if settings.retention == 0 {
+        return Err(anyhow!("保留份数至少为 1"));
+    }
+    if settings.includes_secrets() && settings.passphrase().is_none() {
+        return Err(anyhow!("备份内容包含密钥，必须设置加密口令"));
+    }
+    if let Some(dir) = &settings.target_dir {
+        path_policy::check_user_chosen(Path::new(dir))
+            .map_err(|e| anyhow!("备份目录无效: {}", e))?;
+    }
+    Ok(())
+}
+
+fn archive_path(dir: &Path, prefix: &str) -> PathBuf {
+    let stamp = chrono::Utc::now().format(TIMESTAMP_FORMAT).to_string();
+    // 同一毫秒内多次备份时追加序号，保证名称唯一且有序
+    let mut path = dir.join(format!("{prefix}{stamp}.{ARCHIVE_EXTENSION}"));
+    let mut seq = 1;
+    while path.exists() {
+        path = dir.join(format!("{prefix}{stamp}_{seq:03}.{ARCHIVE_EXTENSION}"));
+        seq += 1;
+    }
+    path
+}
+
+/// 目录中以 `prefix` 开头的备份文件（最新的在前）
+fn list_archives(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let suffix = format!(".{ARCHIVE_EXTENSION}");
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut archives: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(prefix) && name.ends_with(&suffix))
+        })
+        .collect();
+    archives.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+    archives
+}
+
+/// 只保留最新的 `keep` 份备份，返回删除的文件
+fn prune(dir: &Path, prefix: &str, keep: usize) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for stale in list_archives(dir, prefix).into_iter().skip(keep) {
+        std::fs::remove_file(&stale).with_context(|| format!("删除旧备份失败: {stale:?}"))?;
+        removed.push(stale.to_string_lossy().into_owned());
+    }
+    Ok(removed)
+}
+
+/// 最新一份备份的修改时间（Unix 时间戳）
+fn latest_archive_time(dir: &Path, prefix: &str) -> Option<i64> {
+    let latest = list_archives(dir, prefix).into_iter().next()?;
+    let modified = std::fs::metadata(latest).ok()?.modified().ok()?;
+    Some(chrono::DateTime::<chrono::Utc>::from(modified).timestamp())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Key<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key.into()
+}
+
+/// 序列化备份正文并写入文件头，有口令时加密
+fn seal(archive: &BackupArchive, passphrase: Option<&str>, iterations: u32) -> Result<Vec<u8>> {
+    let body = serde_json::to_vec(archive).context("序列化备份失败")?;
+    let mut out = Vec::with_capacity(body.len() + 64);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&ARCHIVE_FORMAT_VERSION.to_le_bytes());
+
+    let Some(passphrase) = passphrase else {
+        out.push(0);
+        out.extend_from_slice(&body);
+        return Ok(out);
+    };
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let cipher = Aes256Gcm::new(&derive_key(passphrase, &salt, iterations));
+    let ciphertext = cipher
+        .encrypt(&nonce, body.as_slice())
+        .map_err(|_| anyhow!("加密备份失败"))?;
+
+    out.push(FLAG_ENCRYPTED);
+    out.extend_from_slice(&iterations.to_le_bytes());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(nonce.as_slice());
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// 解析备份文件（加密时需要口令）
+fn open(content: &[u8], passphrase: Option<&str>) -> Result<BackupArchive> {
+    let rest = content
+        .strip_prefix(MAGIC.as_slice())
+        .ok_or_else(|| anyhow!("不是 DuckCoding 备份文件"))?;
+    let (version, rest) = split_u32(rest)?;
+    if version > ARCHIVE_FORMAT_VERSION {
+        return Err(anyhow!(
+            "备份格式版本 {} 高于当前支持的版本 {}，请升级 DuckCoding",
+            version,
+            ARCHIVE_FORMAT_VERSION
+        ));
+    }
+    let (&flags, rest) = rest
+        .split_first()
+        .ok_or_else(|| anyhow!("备份文件已损坏"))?;
+
+    let body = if flags & FLAG_ENCRYPTED == 0 {
+        rest.to_vec()
+    } else {
+        let passphrase = passphrase
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow!("备份已加密，请输入口令"))?;
+        let (iterations, rest) = split_u32(rest)?;
+        if !(MIN_PBKDF2_ITERATIONS..=MAX_PBKDF2_ITERATIONS).contains(&iterations) {
+            return Err(anyhow!(
+                "备份文件的密钥派生迭代次数 {} 不在允许范围（{}–{}）内，文件可能已损坏",
+                iterations,
+                MIN_PBKDF2_ITERATIONS,
+                MAX_PBKDF2_ITERATIONS
+            ));
+        }
+        if rest.len() < SALT_LEN + NONCE_LEN {
+            return Err(anyhow!("备份文件已损坏"));
+        }
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        Aes256Gcm::new(&derive_key(passphrase, salt, iterations))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("口令错误或备份文件已损坏"))?
+    };
+    serde_json::from_slice(&body).context("解析备份内容失败")
+}
+
+fn split_u32(bytes: &[u8]) -> Result<(u32, &[u8])> {
+    if bytes.len() < 4 {
+        return Err(anyhow!("备份文件已损坏"));
+    }
+    let (head, rest) = bytes.split_at(4);
+    Ok((u32::from_le_bytes(head.try_into()?), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BackupSchedule;
+    use crate::services::config::write_ledger::ProvenanceStatus;
+    use tempfile::TempDir;
+
+    fn archive() -> BackupArchive {
+        BackupArchive {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            app_version: "1.0.0".to_string(),
+            created_at: 42,
+            components: vec![ArchivedComponent {
+                component: BackupComponent::Providers,
+                files: [(
+                    "providers.json".to_string(),
+                    serde_json::json!({ "providers": [{ "api_key": "sk-secret" }] }),
+                )]
+                .into(),
+                instances: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_encrypted_archive_roundtrip() {
+        let sealed = seal(&archive(), Some("correct horse"), MIN_PBKDF2_ITERATIONS).unwrap();
+        assert!(sealed.starts_with(MAGIC));
+        // 加密后正文中不含明文密钥
+        assert!(!sealed.windows(9).any(|w| w == b"sk-secret"));
+
+        let opened = open(&sealed, Some("correct horse")).unwrap();
+        assert_eq!(opened.created_at, 42);
+        assert_eq!(
+            opened.components[0].files["providers.json"]["providers"][0]["api_key"],
+            "sk-secret"
+        );
+
+        assert!(open(&sealed, Some("wrong")).is_err());
+        assert!(open(&sealed, None).is_err());
+        assert!(open(b"not a backup", None).is_err());
+    }
+
+    #[test]
+    fn test_rejects_out_of_range_iterations() {
+        let header = |iterations: u32| {
+            let mut content = MAGIC.to_vec();
+            content.extend_from_slice(&ARCHIVE_FORMAT_VERSION.to_le_bytes());
+            content.push(FLAG_ENCRYPTED);
+            content.extend_from_slice(&iterations.to_le_bytes());
+            content.extend_from_slice(&[0u8; SALT_LEN + NONCE_LEN + 16]);
+            content
+        };
+
+        // 在派生密钥前拒绝，不会长时间计算
+        for iterations in [u32::MAX, MAX_PBKDF2_ITERATIONS + 1, 1_000] {
+            let err = open(&header(iterations), Some("pass")).unwrap_err();
+            assert!(err.to_string().contains("迭代次数"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_secrets_require_passphrase() {
+        let mut settings = StateBackupSettings {
+            target_dir: Some(std::env::temp_dir().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        assert!(validate_settings(&settings).is_err());
+
+        settings.components = vec![BackupComponent::Dashboard, BackupComponent::InstanceDb];
+        assert!(validate_settings(&settings).is_ok());
+
+        settings.components = BackupComponent::all();
+        settings.passphrase = Some("pass".to_string());
+        assert!(validate_settings(&settings).is_ok());
+    }
+
+    #[test]
+    fn test_restore_files_with_safety_snapshot() {
+        let data = TempDir::new().unwrap();
+        let target = TempDir::new().unwrap();
+        std::fs::write(data.path().join("dashboard.json"), r#"{"version":1}"#).unwrap();
+
+        let service = BackupService::at(data.path().to_path_buf());
+        let settings = StateBackupSettings {
+            target_dir: Some(target.path().to_string_lossy().into_owned()),
+            components: vec![BackupComponent::Dashboard],
+            retention: 2,
+            ..Default::default()
+        };
+        let run = service.run_backup_now(&settings, false).unwrap();
+        assert!(!run.encrypted);
+        assert_eq!(run.components[0].message, "1 个文件");
+
+        std::fs::write(data.path().join("dashboard.json"), r#"{"version":2}"#).unwrap();
+        let report = service
+            .restore_full_backup(Path::new(&run.path), None)
+            .unwrap();
+        assert!(report.success);
+        let restored: Value = serde_json::from_str(
+            &std::fs::read_to_string(data.path().join("dashboard.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(restored["version"], 1);
+        assert!(Path::new(&report.safety_snapshot.unwrap()).exists());
+
+        // 恢复前的文件留有备份，写入记录中登记了恢复
+        let dashboard = data.path().join("dashboard.json");
+        let backups = DataManager::new()
+            .list_backups(&dashboard, &BackupPolicy::beside(&dashboard))
+            .unwrap();
+        assert!(!backups.is_empty());
+        let ledger = WriteLedger::at(data.path().join(LEDGER_FILE));
+        assert_eq!(
+            ledger.last_write(&dashboard).unwrap().unwrap().action,
+            RESTORE_LEDGER_ACTION
+        );
+        assert_eq!(
+            ledger.provenance(&dashboard).unwrap().status,
+            ProvenanceStatus::DuckCoding
+        );
+
+        // 超出保留份数的旧备份被删除
+        service.run_backup_now(&settings, false).unwrap();
+        let latest = service.run_backup_now(&settings, false).unwrap();
+        assert_eq!(latest.pruned.len(), 1);
+        assert_eq!(list_archives(target.path(), ARCHIVE_PREFIX).len(), 2);
+    }
+
+    #[test]
+    fn test_backup_due() {
+        let target = TempDir::new().unwrap();
+        let service = BackupService::at(target.path().to_path_buf());
+        let mut settings = StateBackupSettings {
+            target_dir: Some(target.path().to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+        let now = chrono::Utc::now().timestamp();
+        assert!(!service.backup_due(&settings, now));
+
+        settings.schedule = BackupSchedule::Daily;
+        assert!(service.backup_due(&settings, now));
+
+        std::fs::write(archive_path(target.path(), ARCHIVE_PREFIX), b"x").unwrap();
+        assert!(!service.backup_due(&settings, now));
+        assert!(service.backup_due(&settings, now + 24 * 60 * 60));
+
+        *service.last_failure_at.lock().unwrap() = Some(now + 24 * 60 * 60);
+        assert!(!service.backup_due(&settings, now + 24 * 60 * 60 + 60));
+    }
+}
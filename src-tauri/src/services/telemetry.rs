@@ -0,0 +1,321 @@
+// Telemetry Service
+//
+// 匿名使用统计（默认关闭，用户在引导中同意后才开启）：
+// - 只对固定的事件列表计数（见 TelemetryEvent），不记录参数、路径或供应商信息
+// - 计数保存在本地 telemetry.json，每天最多发送一次；发送内容与预览使用同一构建逻辑
+// - 关闭时删除尚未发送的计数，发送历史保留供用户查看
+
+use crate::core::i18n::LocalizedError;
+use crate::data::DataManager;
+use crate::http_client::build_client;
+use crate::models::telemetry::{
+    TelemetryEvent, TelemetryPing, TelemetrySendRecord, TelemetrySettings, TelemetryState,
+    TelemetryStatus, TELEMETRY_SCHEMA_VERSION,
+};
+use crate::utils::config::{config_dir, read_global_config, write_global_config};
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 两次发送的最小间隔（秒）
+pub const SEND_INTERVAL_SECS: i64 = 24 * 60 * 60;
+
+/// 启动后首次检查前的等待时间
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// 检查是否需要发送的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 发送请求超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 进程内写锁（文件锁之外，保证同一进程内的读-改-写串行）
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 记录一次功能使用（未开启时不记录；失败只记日志，不影响调用方的主流程）
+pub fn record_event(event: TelemetryEvent) {
+    if !TelemetryManager::settings().enabled {
+        return;
+    }
+    if let Err(e) = TelemetryManager::new().and_then(|manager| manager.record(event)) {
+        tracing::warn!(error = ?e, event = event.as_str(), "记录使用统计失败");
+    }
+}
+
+/// 后台发送循环（不会返回）：每小时检查一次，满足间隔且有待发送计数时发送
+pub async fn run_telemetry_loop() {
+    tokio::time::sleep(STARTUP_DELAY).await;
+    loop {
+        match TelemetryManager::new() {
+            Ok(manager) => match manager.send_if_due().await {
+                Ok(Some(record)) => tracing::info!(
+                    events = record.ping.counters.len(),
+                    endpoint = %record.endpoint,
+                    "已发送匿名使用统计"
+                ),
+                Ok(None) => {}
+                Err(e) => tracing::warn!(error = ?e, "发送匿名使用统计失败，稍后重试"),
+            },
+            Err(e) => tracing::warn!(error = ?e, "初始化使用统计失败"),
+        }
+        tokio::time::sleep(CHECK_INTERVAL).await;
+    }
+}
+
+/// 匿名使用统计管理器
+pub struct TelemetryManager {
+    data_manager: DataManager,
+    state_path: PathBuf,
+}
+
+impl TelemetryManager {
+    pub fn new() -> Result<Self> {
+        let state_path = config_dir()
+            .map_err(|e| anyhow::anyhow!("获取配置目录失败: {}", e))?
+            .join("telemetry.json");
+        Ok(Self {
+            data_manager: DataManager::new(),
+            state_path,
+        })
+    }
+
+    /// 当前设置（读取失败时按关闭处理）
+    pub fn settings() -> TelemetrySettings {
+        match read_global_config() {
+            Ok(Some(config)) => config.telemetry,
+            Ok(None) => TelemetrySettings::default(),
+            Err(e) => {
+                tracing::warn!(error = %e, "读取使用统计设置失败，按关闭处理");
+                TelemetrySettings::default()
+            }
+        }
+    }
+
+    /// 读取本地统计状态（文件不存在时返回默认值）
+    pub fn load(&self) -> Result<TelemetryState> {
+        if !self.state_path.exists() {
+            return Ok(TelemetryState::default());
+        }
+        let value = self.data_manager.json_uncached().read(&self.state_path)?;
+        serde_json::from_value(value).context("解析 telemetry.json 失败")
+    }
+
+    /// 设置、待发送计数与发送历史
+    pub fn status(&self) -> Result<TelemetryStatus> {
+        let state = self.load()?;
+        Ok(TelemetryStatus {
+            settings: Self::settings(),
+            allowed_events: TelemetryEvent::ALL
+                .iter()
+                .map(|event| event.as_str().to_string())
+                .collect(),
+            queued: state.queued,
+            last_sent_at: state.last_sent_at,
+            history: state.history,
+        })
+    }
+
+    /// 下一次发送的内容（当前待发送计数）
+    pub fn preview(&self) -> Result<TelemetryPing> {
+        Ok(build_ping(&self.load()?, chrono::Utc::now().timestamp()))
+    }
+
+    /// 事件计数加一
+    pub fn record(&self, event: TelemetryEvent) -> Result<()> {
+        self.update(|state, now| state.increment(event, now))?;
+        Ok(())
+    }
+
+    /// 开启或关闭统计（同时记为已询问）；关闭时删除待发送的计数
+    pub fn set_enabled(&self, enabled: bool) -> Result<TelemetryStatus> {
+        self.update_settings(|settings| {
+            settings.enabled = enabled;
+            settings.prompted = true;
+        })?;
+        if !enabled {
+            self.discard_queue()?;
+        }
+        self.status()
+    }
+
+    /// 设置上报地址（仅支持 http/https）
+    pub fn set_endpoint(&self, endpoint: &str) -> Result<TelemetryStatus> {
+        let endpoint = endpoint.trim();
+        let valid = url::Url::parse(endpoint)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or(false);
+        if !valid {
+            return Err(
+                LocalizedError::new("VALIDATION", "telemetry.invalid_endpoint")
+                    .arg("endpoint", endpoint)
+                    .into(),
+            );
+        }
+        self.update_settings(|settings| settings.endpoint = endpoint.to_string())?;
+        self.status()
+    }
+
+    /// 满足发送条件时发送一次（未开启、离线模式、未满间隔或没有计数时跳过）
+    pub async fn send_if_due(&self) -> Result<Option<TelemetrySendRecord>> {
+        let Some(config) = read_global_config().map_err(|e| anyhow::anyhow!(e))? else {
+            return Ok(None);
+        };
+        if !config.telemetry.enabled || config.offline_mode {
+            return Ok(None);
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let state = self.load()?;
+        if state.queued.is_empty() || !state.is_due(now, SEND_INTERVAL_SECS) {
+            return Ok(None);
+        }
+
+        let ping = build_ping(&state, now);
+        let endpoint = config.telemetry.endpoint;
+        build_client()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .post(&endpoint)
+            .timeout(SEND_TIMEOUT)
+            .json(&ping)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .with_context(|| format!("请求 {} 失败", endpoint))?;
+
+        let record = TelemetrySendRecord {
+            sent_at: now,
+            endpoint,
+            ping,
+        };
+        self.update(|state, _| state.mark_sent(record.clone()))?;
+        Ok(Some(record))
+    }
+
+    /// 删除待发送的计数（发送历史保留）
+    fn discard_queue(&self) -> Result<()> {
+        if self.state_path.exists() {
+            self.update(|state, _| state.clear_queue())?;
+        }
+        Ok(())
+    }
+
+    fn update_settings(&self, f: impl FnOnce(&mut TelemetrySettings)) -> Result<()> {
+        let mut config = read_global_config()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .ok_or_else(|| anyhow::anyhow!("配置文件不存在"))?;
+        f(&mut config.telemetry);
+        write_global_config(&config).map_err(|e| anyhow::anyhow!(e))
+    }
+
+    /// 读-改-写统计状态（闭包的第二个参数为当前时间）
+    fn update(&self, f: impl FnOnce(&mut TelemetryState, i64)) -> Result<TelemetryState> {
+        let _guard = WRITE_LOCK.lock().unwrap();
+        self.data_manager
+            .with_file_lock(&self.state_path, || -> Result<TelemetryState> {
+                let mut state = self.load()?;
+                f(&mut state, chrono::Utc::now().timestamp());
+                let value = serde_json::to_value(&state).context("序列化使用统计失败")?;
+                self.data_manager
+                    .json_uncached()
+                    .write(&self.state_path, &value)?;
+                Ok(state)
+            })
+    }
+}
+
+/// 由待发送计数构建上报内容（不含任何标识用户或设备的信息）
+fn build_ping(state: &TelemetryState, now: i64) -> TelemetryPing {
+    TelemetryPing {
+        schema_version: TELEMETRY_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        period_start: state.queued_since.unwrap_or(now),
+        period_end: now,
+        counters: state.queued.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_manager(temp_dir: &TempDir) -> TelemetryManager {
+        TelemetryManager {
+            data_manager: DataManager::new(),
+            state_path: temp_dir.path().join("telemetry.json"),
+        }
+    }
+
+    #[test]
+    fn test_record_and_preview() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        assert!(manager.preview().unwrap().counters.is_empty());
+
+        manager.record(TelemetryEvent::ProxyStarted).unwrap();
+        manager.record(TelemetryEvent::ProxyStarted).unwrap();
+        manager.record(TelemetryEvent::WslInstanceAdded).unwrap();
+
+        let ping = create_test_manager(&temp_dir).preview().unwrap();
+        assert_eq!(ping.counters.get("proxy_started"), Some(&2));
+        assert_eq!(ping.counters.get("wsl_instance_added"), Some(&1));
+        assert_eq!(ping.schema_version, TELEMETRY_SCHEMA_VERSION);
+        assert!(ping.period_start <= ping.period_end);
+
+        // 上报内容只包含固定字段
+        let value = serde_json::to_value(&ping).unwrap();
+        let mut keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                "app_version",
+                "counters",
+                "os",
+                "period_end",
+                "period_start",
+                "schema_version"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discard_queue_keeps_history() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        manager.record(TelemetryEvent::ToolInstalled).unwrap();
+        let ping = manager.preview().unwrap();
+        manager
+            .update(|state, now| {
+                state.mark_sent(TelemetrySendRecord {
+                    sent_at: now,
+                    endpoint: "https://example.com/telemetry".to_string(),
+                    ping,
+                })
+            })
+            .unwrap();
+        manager.record(TelemetryEvent::ToolUpdated).unwrap();
+
+        manager.discard_queue().unwrap();
+        let state = manager.load().unwrap();
+        assert!(state.queued.is_empty());
+        assert!(state.queued_since.is_none());
+        assert_eq!(state.history.len(), 1);
+        assert!(state.last_sent_at.is_some());
+    }
+
+    #[test]
+    fn test_set_endpoint_rejects_invalid_url() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = create_test_manager(&temp_dir);
+        for endpoint in ["", "not a url", "ftp://example.com/telemetry"] {
+            let err = manager.set_endpoint(endpoint).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<LocalizedError>().unwrap().code,
+                "VALIDATION"
+            );
+        }
+    }
+}
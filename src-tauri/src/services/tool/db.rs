@@ -1,246 +1,296 @@
-// Tool Instance DB - 工具实例存储管理（JSON 版本）
+// Tool Instance DB - 工具实例存储管理（SQLite 版本）
 //
-// 从 SQLite 迁移到 JSON 文件，支持版本控制和多端同步
+// 实例与版本历史存放在 tools.db，多步操作（删除旧实例 + 写入新实例）在同一事务内完成，
+// 进程中途退出不会留下半写的数据。旧版 tools.json 在首次打开时导入，并保留为 tools.json.backup。
+// 表结构按版本迁移（见 db_migrations），打开时自动升级到最新版本。
 
+use crate::core::i18n::LocalizedError;
+use crate::data::managers::SqliteManager;
 use crate::data::DataManager;
 use crate::models::{ToolInstance, ToolType};
-use crate::services::tool::tools_config::{
-    LocalToolInstance, SSHToolInstance, ToolsConfig, WSLToolInstance,
-};
+use crate::services::tool::db_migrations::{latest_schema_version, SCHEMA_MIGRATIONS};
+use crate::services::tool::tools_config::ToolsConfig;
 use anyhow::{Context, Result};
-use std::path::PathBuf;
+use rusqlite::{params, params_from_iter, Row, Transaction};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const COLUMNS: &str = "instance_id, base_id, tool_name, tool_type, install_method, installed, \
+     version, install_path, installer_path, wsl_distro, windows_install_path, ssh_config, \
+     is_builtin, created_at, updated_at, label, notes, env_overrides";
+
+const INSERT_SQL: &str = "INSERT INTO tool_instances (instance_id, base_id, tool_name, tool_type, \
+     install_method, installed, version, install_path, installer_path, wsl_distro, \
+     windows_install_path, ssh_config, is_builtin, created_at, updated_at, label, notes, \
+     env_overrides) \
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)";
+
+/// 按 instance_id 更新整条记录（参数顺序与 INSERT_SQL 相同，保留 created_at、is_builtin）
+const UPDATE_SQL: &str = "UPDATE tool_instances SET tool_name = ?3, install_method = ?5, \
+     installed = ?6, version = ?7, install_path = ?8, installer_path = ?9, wsl_distro = ?10, \
+     windows_install_path = ?11, ssh_config = ?12, updated_at = ?15, label = ?16, notes = ?17, \
+     env_overrides = ?18 \
+     WHERE instance_id = ?1 AND base_id = ?2 AND tool_type = ?4";
+
+/// 写入检测结果：已存在时只更新检测得到的字段（保留 created_at、is_builtin 及用户填写的 label、notes、env_overrides）
+const UPSERT_CONFLICT_SQL: &str = " ON CONFLICT(instance_id) DO UPDATE SET \
+     tool_name = excluded.tool_name, install_method = excluded.install_method, \
+     installed = excluded.installed, version = excluded.version, \
+     install_path = excluded.install_path, installer_path = excluded.installer_path, \
+     wsl_distro = excluded.wsl_distro, windows_install_path = excluded.windows_install_path, \
+     ssh_config = excluded.ssh_config, updated_at = excluded.updated_at";
+
+/// 版本历史记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRecord {
+    pub version: Option<String>,
+    /// 记录时间（Unix timestamp，取实例的 updated_at）
+    pub recorded_at: i64,
+}
 
-/// 工具实例数据库管理（JSON 存储）
+/// 工具实例数据库管理（SQLite 存储）
 pub struct ToolInstanceDB {
-    config_path: PathBuf,
-    data_manager: DataManager,
+    db_path: PathBuf,
+    db: Arc<SqliteManager>,
 }
 
 impl ToolInstanceDB {
     /// 创建新的数据库实例
     pub fn new() -> Result<Self> {
-        let home_dir = dirs::home_dir().context("无法获取用户主目录")?;
-        let duckcoding_dir = home_dir.join(".duckcoding");
-
-        // 确保目录存在
-        std::fs::create_dir_all(&duckcoding_dir).context("无法创建 .duckcoding 目录")?;
-
-        let config_path = duckcoding_dir.join("tools.json");
-        let data_manager = DataManager::new();
+        let duckcoding_dir = crate::utils::config::config_dir().map_err(|e| anyhow::anyhow!(e))?;
+        Self::open(&duckcoding_dir.join("tools.db"))
+    }
 
-        Ok(Self {
-            config_path,
-            data_manager,
-        })
+    /// 打开指定路径的数据库（同一进程内按路径复用连接）
+    pub fn open(db_path: &Path) -> Result<Self> {
+        let db = DataManager::global()
+            .sqlite(db_path)
+            .with_context(|| format!("打开工具实例数据库失败: {}", db_path.display()))?;
+        let instance_db = Self {
+            db_path: db_path.to_path_buf(),
+            db,
+        };
+        instance_db.init_tables()?;
+        Ok(instance_db)
     }
 
-    /// 初始化配置文件（如果不存在）
+    /// 初始化：执行结构迁移，并导入旧版 tools.json（如果存在）
+    ///
+    /// 打开时已自动调用，重复调用无副作用。
     pub fn init_tables(&self) -> Result<()> {
-        if !self.config_path.exists() {
-            tracing::info!("初始化 tools.json 配置文件");
-            let default_config = ToolsConfig::default();
-            self.save_config(&default_config)?;
+        self.migrate_schema()?;
+
+        let json_path = self.db_path.with_file_name("tools.json");
+        if json_path.exists() {
+            self.import_json_store(&json_path)?;
         }
         Ok(())
     }
 
-    /// 读取配置
-    fn load_config(&self) -> Result<ToolsConfig> {
-        let json_value = self
-            .data_manager
-            .json()
-            .read(&self.config_path)
-            .context("读取 tools.json 失败")?;
+    /// 从旧版 tools.json 导入实例（一次性迁移）
+    ///
+    /// 导入在单个事务内完成；成功后原文件重命名为 `tools.json.backup`，返回导入的实例数。
+    pub fn import_json_store(&self, json_path: &Path) -> Result<usize> {
+        let raw = std::fs::read_to_string(json_path)
+            .with_context(|| format!("读取 {} 失败", json_path.display()))?;
+        let config: ToolsConfig = serde_json::from_str(&raw).context("解析 tools.json 失败")?;
+        let instances = config.to_instances();
+
+        self.write(|tx| {
+            for instance in &instances {
+                upsert_in(tx, instance)?;
+            }
+            Ok(())
+        })
+        .context("导入 tools.json 失败")?;
+
+        let backup_path = json_path.with_extension("json.backup");
+        std::fs::rename(json_path, &backup_path)
+            .with_context(|| format!("备份 {} 失败", json_path.display()))?;
+        tracing::info!(
+            count = instances.len(),
+            backup = %backup_path.display(),
+            "已将 tools.json 导入工具实例数据库"
+        );
+        Ok(instances.len())
+    }
+
+    /// 当前结构版本（`PRAGMA user_version`）
+    pub fn schema_version(&self) -> Result<u32> {
+        self.write(|tx| Ok(tx.pragma_query_value(None, "user_version", |row| row.get(0))?))
+    }
+
+    /// 按版本执行待处理的结构迁移
+    ///
+    /// 版本高于当前支持的最新版本时拒绝打开（由更新版本的 DuckCoding 创建）；
+    /// 已有数据的数据库在迁移前先备份为 `tools.db.v{旧版本}.backup`。
+    fn migrate_schema(&self) -> Result<()> {
+        let current = self.schema_version()?;
+        let latest = latest_schema_version();
+        if current > latest {
+            return Err(LocalizedError::new("UNSUPPORTED", "tool.db.future_schema")
+                .arg("version", current.to_string())
+                .arg("supported", latest.to_string())
+                .into());
+        }
+
+        let pending: Vec<_> = SCHEMA_MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > current)
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if self.db.table_exists("tool_instances")? {
+            self.backup_before_migration(current)?;
+        }
 
-        // 将 JSON Value 转换为 ToolsConfig
-        serde_json::from_value(json_value).context("解析 tools.json 失败")
+        for migration in pending {
+            self.write(|tx| {
+                (migration.apply)(tx)?;
+                tx.pragma_update(None, "user_version", migration.version)?;
+                Ok(())
+            })
+            .with_context(|| {
+                format!(
+                    "工具实例数据库迁移失败: v{} {}",
+                    migration.version, migration.name
+                )
+            })?;
+            tracing::info!(
+                version = migration.version,
+                name = migration.name,
+                "已执行工具实例数据库迁移"
+            );
+        }
+        Ok(())
     }
 
-    /// 保存配置
-    fn save_config(&self, config: &ToolsConfig) -> Result<()> {
-        // 将 ToolsConfig 转换为 JSON Value
-        let json_value = serde_json::to_value(config).context("序列化 ToolsConfig 失败")?;
+    /// 迁移前备份数据库（`VACUUM INTO` 生成一致的副本）
+    fn backup_before_migration(&self, version: u32) -> Result<PathBuf> {
+        let backup_path = self.db_path.with_extension(format!("db.v{version}.backup"));
+        if backup_path.exists() {
+            std::fs::remove_file(&backup_path)
+                .with_context(|| format!("删除旧备份失败: {}", backup_path.display()))?;
+        }
+        let escaped = backup_path.to_string_lossy().replace('\'', "''");
+        self.db
+            .execute_raw(&format!("VACUUM INTO '{escaped}'"))
+            .with_context(|| format!("备份工具实例数据库失败: {}", backup_path.display()))?;
+        tracing::info!(backup = %backup_path.display(), "已在迁移前备份工具实例数据库");
+        Ok(backup_path)
+    }
 
-        self.data_manager
-            .json()
-            .write(&self.config_path, &json_value)
-            .context("保存 tools.json 失败")
+    /// 在事务内执行读写，任一步失败整体回滚
+    fn write<T>(&self, f: impl FnOnce(&Transaction) -> crate::data::Result<T>) -> Result<T> {
+        Ok(self.db.transaction(f)?)
     }
 
     /// 获取所有工具实例
     pub fn get_all_instances(&self) -> Result<Vec<ToolInstance>> {
-        let config = self.load_config()?;
-        Ok(config.to_instances())
+        self.write(|tx| query_instances(tx, "1 = 1", params![]))
     }
 
     /// 添加工具实例
     pub fn add_instance(&self, instance: &ToolInstance) -> Result<()> {
-        let mut config = self.load_config()?;
-
-        // 查找对应的 ToolGroup
-        let tool_group = config
-            .tools
-            .iter_mut()
-            .find(|g| g.id == instance.base_id)
-            .ok_or_else(|| anyhow::anyhow!("未找到工具分组: {}", instance.base_id))?;
-
-        // 根据类型添加到对应列表
-        match instance.tool_type {
-            ToolType::Local => {
-                tool_group.local_tools.push(LocalToolInstance {
-                    instance_id: instance.instance_id.clone(),
-                    installed: instance.installed,
-                    version: instance.version.clone(),
-                    install_path: instance.install_path.clone(),
-                    installer_path: instance.installer_path.clone(), // 新增
-                    install_method: instance.install_method.clone(),
-                    is_builtin: instance.is_builtin,
-                    created_at: instance.created_at,
-                    updated_at: instance.updated_at,
-                });
-            }
-            ToolType::WSL => {
-                if let Some(ref distro_name) = instance.wsl_distro {
-                    tool_group.wsl_tools.push(WSLToolInstance {
-                        instance_id: instance.instance_id.clone(),
-                        distro_name: distro_name.clone(),
-                        installed: instance.installed,
-                        version: instance.version.clone(),
-                        install_path: instance.install_path.clone(),
-                        install_method: instance.install_method.clone(),
-                        is_builtin: instance.is_builtin,
-                        created_at: instance.created_at,
-                        updated_at: instance.updated_at,
-                    });
-                }
-            }
-            ToolType::SSH => {
-                if let Some(ref ssh_config) = instance.ssh_config {
-                    tool_group.ssh_tools.push(SSHToolInstance {
-                        instance_id: instance.instance_id.clone(),
-                        ssh_config: ssh_config.clone(),
-                        installed: instance.installed,
-                        version: instance.version.clone(),
-                        install_path: instance.install_path.clone(),
-                        install_method: instance.install_method.clone(),
-                        is_builtin: instance.is_builtin,
-                        created_at: instance.created_at,
-                        updated_at: instance.updated_at,
-                    });
-                }
-            }
-        }
-
-        config.updated_at = chrono::Utc::now().to_rfc3339();
-        self.save_config(&config)?;
-        Ok(())
+        self.write(|tx| {
+            execute_instance(tx, INSERT_SQL, instance)?;
+            Ok(())
+        })
+        .with_context(|| format!("添加工具实例失败: {}", instance.instance_id))
     }
 
     /// 更新工具实例
     pub fn update_instance(&self, instance: &ToolInstance) -> Result<()> {
-        let mut config = self.load_config()?;
-
-        // 查找对应的 ToolGroup
-        let tool_group = config
-            .tools
-            .iter_mut()
-            .find(|g| g.id == instance.base_id)
-            .ok_or_else(|| anyhow::anyhow!("未找到工具分组: {}", instance.base_id))?;
-
-        // 根据类型更新
-        let updated = match instance.tool_type {
-            ToolType::Local => {
-                if let Some(local) = tool_group
-                    .local_tools
-                    .iter_mut()
-                    .find(|t| t.instance_id == instance.instance_id)
-                {
-                    local.installed = instance.installed;
-                    local.version = instance.version.clone();
-                    local.install_path = instance.install_path.clone();
-                    local.install_method = instance.install_method.clone();
-                    local.updated_at = instance.updated_at;
-                    true
-                } else {
-                    false
-                }
-            }
-            ToolType::WSL => {
-                if let Some(wsl) = tool_group
-                    .wsl_tools
-                    .iter_mut()
-                    .find(|t| t.instance_id == instance.instance_id)
-                {
-                    wsl.installed = instance.installed;
-                    wsl.version = instance.version.clone();
-                    wsl.install_path = instance.install_path.clone();
-                    wsl.install_method = instance.install_method.clone();
-                    wsl.updated_at = instance.updated_at;
-                    true
-                } else {
-                    false
-                }
-            }
-            ToolType::SSH => {
-                if let Some(ssh) = tool_group
-                    .ssh_tools
-                    .iter_mut()
-                    .find(|t| t.instance_id == instance.instance_id)
-                {
-                    ssh.installed = instance.installed;
-                    ssh.version = instance.version.clone();
-                    ssh.install_path = instance.install_path.clone();
-                    ssh.install_method = instance.install_method.clone();
-                    ssh.updated_at = instance.updated_at;
-                    true
-                } else {
-                    false
-                }
-            }
-        };
-
-        if !updated {
+        let updated = self.write(|tx| execute_instance(tx, UPDATE_SQL, instance))?;
+        if updated == 0 {
             return Err(anyhow::anyhow!("实例不存在: {}", instance.instance_id));
         }
-
-        config.updated_at = chrono::Utc::now().to_rfc3339();
-        self.save_config(&config)?;
         Ok(())
     }
 
     /// 删除工具实例
     pub fn delete_instance(&self, instance_id: &str) -> Result<()> {
-        let mut config = self.load_config()?;
-
-        let mut deleted = false;
-
-        for tool_group in &mut config.tools {
-            // 尝试从各个列表中删除
-            tool_group
-                .local_tools
-                .retain(|t| t.instance_id != instance_id);
-            tool_group
-                .wsl_tools
-                .retain(|t| t.instance_id != instance_id);
-            tool_group
-                .ssh_tools
-                .retain(|t| t.instance_id != instance_id);
-
-            deleted = true;
-        }
+        self.write(|tx| {
+            tx.execute(
+                "DELETE FROM tool_instances WHERE instance_id = ?1",
+                [instance_id],
+            )?;
+            Ok(())
+        })
+    }
 
-        if deleted {
-            config.updated_at = chrono::Utc::now().to_rfc3339();
-            self.save_config(&config)?;
-        }
+    /// 根据 instance_id 获取实例（迁移前的旧 ID 按别名解析）
+    pub fn get_instance(&self, instance_id: &str) -> Result<Option<ToolInstance>> {
+        let mut instances = self.write(|tx| {
+            query_instances(
+                tx,
+                "instance_id = COALESCE(
+                    (SELECT new_id FROM instance_id_aliases WHERE old_id = ?1), ?1)",
+                params![instance_id],
+            )
+        })?;
+        Ok(instances.pop())
+    }
 
+    /// 迁移改写过的实例 ID（key: 旧 ID，value: 新 ID）
+    pub fn instance_id_aliases(&self) -> Result<HashMap<String, String>> {
+        self.write(|tx| {
+            let mut stmt = tx.prepare("SELECT old_id, new_id FROM instance_id_aliases")?;
+            let aliases = stmt
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+            Ok(aliases)
+        })
+    }
+
+    /// 设置实例的标签与备注（空字符串视为清除）
+    pub fn set_instance_label(
+        &self,
+        instance_id: &str,
+        label: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<()> {
+        let normalize = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let updated = self.write(|tx| {
+            Ok(tx.execute(
+                "UPDATE tool_instances SET label = ?2, notes = ?3 WHERE instance_id = ?1",
+                params![instance_id, normalize(label), normalize(notes)],
+            )?)
+        })?;
+        if updated == 0 {
+            return Err(anyhow::anyhow!("实例不存在: {}", instance_id));
+        }
         Ok(())
     }
 
-    /// 根据 instance_id 获取实例
-    pub fn get_instance(&self, instance_id: &str) -> Result<Option<ToolInstance>> {
-        let instances = self.get_all_instances()?;
-        Ok(instances.into_iter().find(|i| i.instance_id == instance_id))
+    /// 设置实例的环境变量覆盖（空表示清除）
+    pub fn set_instance_env(
+        &self,
+        instance_id: &str,
+        env: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let raw = (!env.is_empty())
+            .then(|| serde_json::to_string(env))
+            .transpose()?;
+        let updated = self.write(|tx| {
+            Ok(tx.execute(
+                "UPDATE tool_instances SET env_overrides = ?2 WHERE instance_id = ?1",
+                params![instance_id, raw],
+            )?)
+        })?;
+        if updated == 0 {
+            return Err(anyhow::anyhow!("实例不存在: {}", instance_id));
+        }
+        Ok(())
     }
 
     /// 检查实例是否存在
@@ -250,33 +300,107 @@ impl ToolInstanceDB {
 
     /// 检查是否有本地工具实例（用于判断是否需要执行首次检测）
     pub fn has_local_tools(&self) -> Result<bool> {
-        let config = self.load_config()?;
-        let has_tools = config
-            .tools
-            .iter()
-            .any(|group| !group.local_tools.is_empty());
-        Ok(has_tools)
+        Ok(!self.get_local_instances()?.is_empty())
     }
 
     /// 更新或插入实例（upsert）
     pub fn upsert_instance(&self, instance: &ToolInstance) -> Result<()> {
-        if self.instance_exists(&instance.instance_id)? {
-            self.update_instance(instance)
-        } else {
-            self.add_instance(instance)
-        }
+        self.write(|tx| upsert_in(tx, instance))
     }
 
     /// 获取本地工具实例
     pub fn get_local_instances(&self) -> Result<Vec<ToolInstance>> {
-        let instances = self.get_all_instances()?;
-        Ok(instances
-            .into_iter()
-            .filter(|i| i.tool_type == ToolType::Local)
-            .collect())
+        self.write(|tx| query_instances(tx, "tool_type = ?1", params![ToolType::Local.as_str()]))
+    }
+
+    /// 用检测结果替换本地实例
+    ///
+    /// 删除 `base_id`（`None` 表示所有工具）下不在 `instances` 中的本地实例，再写入 `instances`；
+    /// 两步在同一事务内完成，中途失败时数据库保持原样。
+    pub fn replace_local_instances(
+        &self,
+        base_id: Option<&str>,
+        instances: &[ToolInstance],
+    ) -> Result<()> {
+        self.replace_local_instances_with(base_id, instances, || Ok(()))
+    }
+
+    /// `between` 在删除与写入之间执行（测试中用于模拟中途失败）
+    fn replace_local_instances_with(
+        &self,
+        base_id: Option<&str>,
+        instances: &[ToolInstance],
+        between: impl FnOnce() -> crate::data::Result<()>,
+    ) -> Result<()> {
+        self.write(|tx| {
+            let keep: Vec<&str> = instances
+                .iter()
+                .map(|instance| instance.instance_id.as_str())
+                .collect();
+            let placeholders = vec!["?"; keep.len()].join(", ");
+            let mut sql = "DELETE FROM tool_instances WHERE tool_type = 'Local'".to_string();
+            if base_id.is_some() {
+                sql.push_str(" AND base_id = ?");
+            }
+            if !keep.is_empty() {
+                sql.push_str(&format!(" AND instance_id NOT IN ({placeholders})"));
+            }
+            let deleted = tx.execute(&sql, params_from_iter(base_id.into_iter().chain(keep)))?;
+            if deleted > 0 {
+                tracing::info!(deleted, base_id = ?base_id, "已删除不存在的本地工具实例");
+            }
+
+            between()?;
+
+            for instance in instances {
+                upsert_in(tx, instance)?;
+            }
+            Ok(())
+        })
     }
 
-    /// 从 SQLite 迁移到 JSON（一次性迁移）
+    /// 清空所有实例与版本历史（卸载前清理）
+    pub fn clear(&self) -> Result<()> {
+        self.write(|tx| {
+            tx.execute_batch(
+                "DELETE FROM tool_instances; DELETE FROM version_history;
+                 DELETE FROM instance_id_aliases;",
+            )?;
+            Ok(())
+        })
+    }
+
+    /// 用 `instances` 整体替换所有实例（恢复完整备份），在同一事务内完成
+    pub fn replace_all_instances(&self, instances: &[ToolInstance]) -> Result<()> {
+        self.write(|tx| {
+            tx.execute("DELETE FROM tool_instances", [])?;
+            for instance in instances {
+                execute_instance(tx, INSERT_SQL, instance)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// 获取实例的版本历史（最新的在前）
+    pub fn get_version_history(&self, instance_id: &str) -> Result<Vec<VersionRecord>> {
+        self.write(|tx| {
+            let mut stmt = tx.prepare(
+                "SELECT version, recorded_at FROM version_history
+                 WHERE instance_id = ?1 ORDER BY id DESC",
+            )?;
+            let records = stmt
+                .query_map([instance_id], |row| {
+                    Ok(VersionRecord {
+                        version: row.get(0)?,
+                        recorded_at: row.get(1)?,
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok(records)
+        })
+    }
+
+    /// 从旧版 SQLite（tool_instances.db）迁移（一次性迁移）
     pub fn migrate_from_sqlite(&self) -> Result<()> {
         use rusqlite::Connection;
 
@@ -288,7 +412,7 @@ impl ToolInstanceDB {
             return Ok(());
         }
 
-        tracing::info!("开始从 SQLite 迁移到 JSON");
+        tracing::info!("开始从旧版 SQLite 迁移");
 
         let conn = Connection::open(&old_db_path)?;
 
@@ -313,6 +437,10 @@ impl ToolInstanceDB {
                     port: row.get::<_, i32>(10)? as u16,
                     user: row.get(11)?,
                     key_path: row.get(12)?,
+                    auth_method: Default::default(),
+                    password: None,
+                    passphrase: None,
+                    jump_host: None,
                 })
             } else {
                 None
@@ -329,10 +457,15 @@ impl ToolInstanceDB {
                 install_path: row.get(6)?,
                 installer_path: None, // 旧数据没有，需要后续检测
                 wsl_distro: row.get(7)?,
+                windows_install_path: None,
                 ssh_config,
                 is_builtin: is_builtin_int != 0,
                 created_at: row.get(14)?,
                 updated_at: row.get(15)?,
+                label: None,
+                notes: None,
+                env_overrides: Default::default(),
+                node_runtime: None,
             })
         })?;
 
@@ -340,13 +473,18 @@ impl ToolInstanceDB {
 
         tracing::info!("从 SQLite 读取到 {} 个实例", instances.len());
 
-        // 转换为 ToolsConfig 并保存
-        let config = ToolsConfig::from_instances(instances);
-        self.save_config(&config)?;
+        self.write(|tx| {
+            for instance in &instances {
+                upsert_in(tx, instance)?;
+            }
+            Ok(())
+        })?;
 
-        tracing::info!("迁移完成，已保存到 {}", self.config_path.display());
+        tracing::info!("迁移完成，已保存到 {}", self.db_path.display());
 
         // 备份旧数据库
+        drop(stmt);
+        drop(conn);
         let backup_path = old_db_path.with_extension("db.backup");
         std::fs::rename(&old_db_path, &backup_path)?;
         tracing::info!("旧数据库已备份到 {}", backup_path.display());
@@ -361,10 +499,142 @@ impl Default for ToolInstanceDB {
     }
 }
 
+/// 写入或更新单个实例（在调用方的事务内）
+fn upsert_in(tx: &Transaction, instance: &ToolInstance) -> crate::data::Result<()> {
+    execute_instance(tx, &format!("{INSERT_SQL}{UPSERT_CONFLICT_SQL}"), instance)?;
+    Ok(())
+}
+
+fn query_instances(
+    tx: &Transaction,
+    condition: &str,
+    params: &[&dyn rusqlite::ToSql],
+) -> crate::data::Result<Vec<ToolInstance>> {
+    let mut stmt = tx.prepare(&format!(
+        "SELECT {COLUMNS} FROM tool_instances WHERE {condition}
+         ORDER BY base_id, tool_type, instance_id"
+    ))?;
+    let instances = stmt
+        .query_map(params, instance_from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(instances)
+}
+
+/// 以实例字段作为参数（顺序同 INSERT_SQL）执行写入语句
+fn execute_instance(
+    tx: &Transaction,
+    sql: &str,
+    instance: &ToolInstance,
+) -> crate::data::Result<usize> {
+    let install_method = instance
+        .install_method
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    let ssh_config = instance
+        .ssh_config
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
+    let env_overrides = (!instance.env_overrides.is_empty())
+        .then(|| serde_json::to_string(&instance.env_overrides))
+        .transpose()?;
+    Ok(tx.execute(
+        sql,
+        params![
+            instance.instance_id,
+            instance.base_id,
+            instance.tool_name,
+            instance.tool_type.as_str(),
+            install_method,
+            instance.installed,
+            instance.version,
+            instance.install_path,
+            instance.installer_path,
+            instance.wsl_distro,
+            instance.windows_install_path,
+            ssh_config,
+            instance.is_builtin,
+            instance.created_at,
+            instance.updated_at,
+            instance.label,
+            instance.notes,
+            env_overrides,
+        ],
+    )?)
+}
+
+fn instance_from_row(row: &Row) -> rusqlite::Result<ToolInstance> {
+    let tool_type: String = row.get(3)?;
+    Ok(ToolInstance {
+        instance_id: row.get(0)?,
+        base_id: row.get(1)?,
+        tool_name: row.get(2)?,
+        tool_type: ToolType::parse(&tool_type).unwrap_or(ToolType::Local),
+        install_method: json_column(row, 4)?,
+        installed: row.get(5)?,
+        version: row.get(6)?,
+        install_path: row.get(7)?,
+        installer_path: row.get(8)?,
+        wsl_distro: row.get(9)?,
+        windows_install_path: row.get(10)?,
+        ssh_config: json_column(row, 11)?,
+        is_builtin: row.get(12)?,
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+        label: row.get(15)?,
+        notes: row.get(16)?,
+        env_overrides: json_column(row, 17)?.unwrap_or_default(),
+        node_runtime: None,
+    })
+}
+
+/// 读取以 JSON 文本存储的可选列
+fn json_column<T: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<Option<T>> {
+    let raw: Option<String> = row.get(idx)?;
+    raw.map(|text| {
+        serde_json::from_str(&text).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    })
+    .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::InstallMethod;
+    use tempfile::TempDir;
+
+    fn local_instance(base_id: &str, version: &str) -> ToolInstance {
+        ToolInstance {
+            instance_id: format!("{base_id}-local"),
+            base_id: base_id.to_string(),
+            tool_name: base_id.to_string(),
+            tool_type: ToolType::Local,
+            install_method: Some(InstallMethod::Npm),
+            installed: true,
+            version: Some(version.to_string()),
+            install_path: Some(format!("/usr/local/bin/{base_id}")),
+            installer_path: None,
+            wsl_distro: None,
+            windows_install_path: None,
+            ssh_config: None,
+            is_builtin: true,
+            created_at: 1733299200,
+            updated_at: 1733299200,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
+        }
+    }
+
+    fn temp_db() -> (TempDir, ToolInstanceDB) {
+        let temp_dir = TempDir::new().unwrap();
+        let db = ToolInstanceDB::open(&temp_dir.path().join("tools.db")).unwrap();
+        (temp_dir, db)
+    }
 
     #[test]
     fn test_db_creation() {
@@ -388,10 +658,15 @@ mod tests {
             install_path: Some("/usr/local/bin/test".to_string()),
             installer_path: Some("/usr/local/bin/npm".to_string()),
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: true,
             created_at: 1733299200,
             updated_at: 1733299200,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         };
 
         // 添加实例
@@ -411,4 +686,263 @@ mod tests {
         // 清理
         let _ = db.delete_instance("test-tool-local");
     }
+
+    #[test]
+    fn test_ssh_config_jump_host_serde_compat() {
+        // 旧版本写入的配置没有 jump_host 等字段
+        let legacy = r#"{"display_name":"dev","host":"dev.example.com","port":22,"user":"duck","key_path":null}"#;
+        let config: crate::models::SSHConfig = serde_json::from_str(legacy).unwrap();
+        assert!(config.jump_host.is_none());
+        // 没有跳板机时不写出该字段
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("jump_host"));
+
+        let mut target = config.clone();
+        target.host = "10.0.0.5".to_string();
+        target.jump_host = Some(Box::new(config));
+        let json = serde_json::to_string(&target).unwrap();
+        let loaded: crate::models::SSHConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.jump_host.unwrap().host, "dev.example.com");
+    }
+
+    #[test]
+    fn test_update_requires_existing_instance() {
+        let (_dir, db) = temp_db();
+        let mut instance = local_instance("codex", "0.1.0");
+        assert!(db.update_instance(&instance).is_err());
+
+        db.add_instance(&instance).unwrap();
+        instance.version = Some("0.2.0".to_string());
+        instance.is_builtin = false;
+        db.update_instance(&instance).unwrap();
+
+        let loaded = db.get_instance("codex-local").unwrap().unwrap();
+        assert_eq!(loaded.version.as_deref(), Some("0.2.0"));
+        // is_builtin、created_at 不随更新改变
+        assert!(loaded.is_builtin);
+        assert!(db.add_instance(&instance).is_err());
+    }
+
+    #[test]
+    fn test_replace_local_instances_is_atomic() {
+        let (_dir, db) = temp_db();
+        db.add_instance(&local_instance("claude-code", "1.0.0"))
+            .unwrap();
+        db.add_instance(&local_instance("codex", "0.1.0")).unwrap();
+
+        // 删除之后、写入之前失败：整个事务回滚，旧实例仍在
+        let result =
+            db.replace_local_instances_with(None, &[local_instance("codex", "0.2.0")], || {
+                Err(crate::data::DataError::Concurrency(
+                    "模拟中途退出".to_string(),
+                ))
+            });
+        assert!(result.is_err());
+        let ids: Vec<String> = db
+            .get_local_instances()
+            .unwrap()
+            .into_iter()
+            .map(|i| i.instance_id)
+            .collect();
+        assert_eq!(ids, vec!["claude-code-local", "codex-local"]);
+        assert_eq!(
+            db.get_instance("codex-local").unwrap().unwrap().version,
+            Some("0.1.0".to_string())
+        );
+
+        // 正常完成：未检测到的实例被删除，检测到的被更新
+        db.replace_local_instances(None, &[local_instance("codex", "0.2.0")])
+            .unwrap();
+        let instances = db.get_local_instances().unwrap();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].version.as_deref(), Some("0.2.0"));
+    }
+
+    #[test]
+    fn test_replace_local_instances_scoped_to_tool() {
+        let (_dir, db) = temp_db();
+        db.add_instance(&local_instance("claude-code", "1.0.0"))
+            .unwrap();
+        db.add_instance(&local_instance("codex", "0.1.0")).unwrap();
+
+        // 只替换 codex，未检测到时删除其本地实例，不影响其他工具
+        db.replace_local_instances(Some("codex"), &[]).unwrap();
+        assert!(!db.instance_exists("codex-local").unwrap());
+        assert!(db.instance_exists("claude-code-local").unwrap());
+    }
+
+    #[test]
+    fn test_version_history_records_changes() {
+        let (_dir, db) = temp_db();
+        let mut instance = local_instance("gemini-cli", "0.1.0");
+        db.upsert_instance(&instance).unwrap();
+        // 版本未变化不记录
+        instance.updated_at += 10;
+        db.upsert_instance(&instance).unwrap();
+        instance.version = Some("0.2.0".to_string());
+        instance.updated_at += 10;
+        db.upsert_instance(&instance).unwrap();
+
+        let history = db.get_version_history("gemini-cli-local").unwrap();
+        let versions: Vec<_> = history.iter().map(|r| r.version.as_deref()).collect();
+        assert_eq!(versions, vec![Some("0.2.0"), Some("0.1.0")]);
+        assert_eq!(history[0].recorded_at, instance.updated_at);
+    }
+
+    #[test]
+    fn test_import_json_store_keeps_backup() {
+        let (dir, db) = temp_db();
+        let config = ToolsConfig::from_instances(vec![local_instance("claude-code", "1.0.0")]);
+        let json_path = dir.path().join("tools.json");
+        std::fs::write(&json_path, serde_json::to_string(&config).unwrap()).unwrap();
+
+        db.init_tables().unwrap();
+        assert!(!json_path.exists());
+        assert!(dir.path().join("tools.json.backup").exists());
+        let loaded = db.get_instance("claude-code-local").unwrap().unwrap();
+        assert_eq!(loaded.version.as_deref(), Some("1.0.0"));
+        assert_eq!(loaded.install_method, Some(InstallMethod::Npm));
+
+        // 再次初始化不会重复导入
+        db.init_tables().unwrap();
+        assert_eq!(db.get_all_instances().unwrap().len(), 1);
+    }
+
+    /// 引入结构版本之前（user_version = 0）的 tools.db
+    const V0_SCHEMA_SQL: &str = r#"
+        CREATE TABLE tool_instances (
+            instance_id TEXT PRIMARY KEY, base_id TEXT NOT NULL, tool_name TEXT NOT NULL,
+            tool_type TEXT NOT NULL, install_method TEXT, installed INTEGER NOT NULL,
+            version TEXT, install_path TEXT, installer_path TEXT, wsl_distro TEXT,
+            windows_install_path TEXT, ssh_config TEXT, is_builtin INTEGER NOT NULL,
+            created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL
+        );
+        CREATE TABLE version_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, instance_id TEXT NOT NULL,
+            version TEXT, recorded_at INTEGER NOT NULL
+        );
+        INSERT INTO tool_instances VALUES
+            ('codex-ssh-dev', 'codex', 'CodeX', 'SSH', '"Npm"', 1, '0.1.0', '/usr/bin/codex',
+             NULL, NULL, NULL,
+             '{"display_name":"dev","host":"Dev.Example.com","port":22,"user":"duck","key_path":null}',
+             0, 1733299200, 1733299200),
+            ('codex-local-1733299200', 'codex', 'CodeX', 'Local', '"Npm"', 1, '0.2.0',
+             '/opt/codex/bin/codex', NULL, NULL, NULL, NULL, 0, 1733299200, 1733299200),
+            ('claude-code-local', 'claude-code', 'Claude Code', 'Local', '"Npm"', 1, '1.0.0',
+             '/usr/local/bin/claude', NULL, NULL, NULL, NULL, 1, 1733299200, 1733299200);
+        INSERT INTO version_history (instance_id, version, recorded_at) VALUES
+            ('codex-ssh-dev', '0.1.0', 1733299200);
+    "#;
+
+    #[test]
+    fn test_migrate_v0_database() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("tools.db");
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(V0_SCHEMA_SQL)
+            .unwrap();
+
+        let db = ToolInstanceDB::open(&db_path).unwrap();
+        assert_eq!(db.schema_version().unwrap(), latest_schema_version());
+        assert!(dir.path().join("tools.db.v0.backup").exists());
+
+        // SSH 实例按连接目标改用稳定 ID，版本历史随之迁移
+        let ssh_id = "codex-ssh-duck-dev-example-com-22";
+        let ssh = db.get_instance(ssh_id).unwrap().unwrap();
+        assert_eq!(ssh.ssh_config.unwrap().display_name, "dev");
+        assert_eq!(db.get_version_history(ssh_id).unwrap().len(), 1);
+        assert!(db.get_version_history("codex-ssh-dev").unwrap().is_empty());
+
+        // 手动添加的本地实例按路径生成 ID，内置实例保持不变
+        let local_id = ToolInstance::manual_local_instance_id("codex", "/opt/codex/bin/codex");
+        assert!(db.instance_exists(&local_id).unwrap());
+        assert!(db.instance_exists("claude-code-local").unwrap());
+
+        // 旧 ID 仍可解析
+        let aliases = db.instance_id_aliases().unwrap();
+        assert_eq!(aliases.len(), 2);
+        assert_eq!(aliases["codex-ssh-dev"], ssh_id);
+        assert_eq!(aliases["codex-local-1733299200"], local_id);
+        let resolved = db.get_instance("codex-ssh-dev").unwrap().unwrap();
+        assert_eq!(resolved.instance_id, ssh_id);
+
+        // 新增的 label、notes 列可用
+        db.set_instance_label(ssh_id, Some(" 开发机 "), Some(""))
+            .unwrap();
+        let labeled = db.get_instance(ssh_id).unwrap().unwrap();
+        assert_eq!(labeled.label.as_deref(), Some("开发机"));
+        assert!(labeled.notes.is_none());
+
+        // 再次迁移无副作用
+        db.init_tables().unwrap();
+        assert_eq!(db.get_all_instances().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_refuses_future_schema_version() {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("tools.db");
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .pragma_update(None, "user_version", latest_schema_version() + 1)
+            .unwrap();
+
+        let err = ToolInstanceDB::open(&db_path).err().unwrap();
+        let localized = err.downcast_ref::<LocalizedError>().unwrap();
+        assert_eq!(localized.code, "UNSUPPORTED");
+    }
+
+    #[test]
+    fn test_upsert_keeps_label_and_notes() {
+        let (_dir, db) = temp_db();
+        let mut instance = local_instance("codex", "0.1.0");
+        db.add_instance(&instance).unwrap();
+        db.set_instance_label("codex-local", Some("工作"), Some("公司项目"))
+            .unwrap();
+        assert!(db.set_instance_label("missing", Some("x"), None).is_err());
+
+        // 检测结果不带标签，写入时不覆盖用户填写的内容
+        instance.version = Some("0.2.0".to_string());
+        db.upsert_instance(&instance).unwrap();
+        let loaded = db.get_instance("codex-local").unwrap().unwrap();
+        assert_eq!(loaded.version.as_deref(), Some("0.2.0"));
+        assert_eq!(loaded.label.as_deref(), Some("工作"));
+        assert_eq!(loaded.notes.as_deref(), Some("公司项目"));
+    }
+
+    #[test]
+    fn test_env_overrides_round_trip() {
+        let (_dir, db) = temp_db();
+        let mut instance = local_instance("codex", "0.1.0");
+        db.add_instance(&instance).unwrap();
+        assert!(db
+            .get_instance("codex-local")
+            .unwrap()
+            .unwrap()
+            .env_overrides
+            .is_empty());
+
+        let env = BTreeMap::from([
+            (
+                "NODE_OPTIONS".to_string(),
+                "--max-old-space-size=8192".to_string(),
+            ),
+            ("CODEX_HOME".to_string(), "/data/codex".to_string()),
+        ]);
+        db.set_instance_env("codex-local", &env).unwrap();
+        assert!(db.set_instance_env("missing", &env).is_err());
+
+        // 检测结果写入时保留用户设置的环境变量
+        instance.version = Some("0.2.0".to_string());
+        db.upsert_instance(&instance).unwrap();
+        let loaded = db.get_instance("codex-local").unwrap().unwrap();
+        assert_eq!(loaded.env_overrides, env);
+
+        db.set_instance_env("codex-local", &BTreeMap::new())
+            .unwrap();
+        let loaded = db.get_instance("codex-local").unwrap().unwrap();
+        assert!(loaded.env_overrides.is_empty());
+    }
 }
@@ -0,0 +1,213 @@
+// Tool Instance DB 结构迁移
+//
+// 结构版本记录在 `PRAGMA user_version`（0 表示尚未记录版本）。迁移按版本号升序执行，
+// 每个迁移在独立事务内完成并写入新版本号；迁移必须可重复执行，以便多个进程同时打开时不出错。
+
+use crate::data::Result;
+use crate::models::{SSHConfig, ToolInstance};
+use rusqlite::{params, Transaction};
+
+/// 单个结构迁移
+pub(super) struct SchemaMigration {
+    /// 执行后的结构版本
+    pub version: u32,
+    pub name: &'static str,
+    pub apply: fn(&Transaction) -> Result<()>,
+}
+
+/// 所有结构迁移（按版本号升序）
+pub(super) const SCHEMA_MIGRATIONS: &[SchemaMigration] = &[
+    SchemaMigration {
+        version: 1,
+        name: "初始表结构与版本历史",
+        apply: create_base_tables,
+    },
+    SchemaMigration {
+        version: 2,
+        name: "稳定实例 ID",
+        apply: stabilize_instance_ids,
+    },
+    SchemaMigration {
+        version: 3,
+        name: "实例标签与备注",
+        apply: add_label_and_notes,
+    },
+    SchemaMigration {
+        version: 4,
+        name: "实例环境变量覆盖",
+        apply: add_env_overrides,
+    },
+];
+
+/// 当前支持的最新结构版本
+pub(super) fn latest_schema_version() -> u32 {
+    SCHEMA_MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// v1：实例表与版本历史（版本历史由触发器在版本号变化时写入）
+const BASE_SCHEMA_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS tool_instances (
+    instance_id TEXT PRIMARY KEY,
+    base_id TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    tool_type TEXT NOT NULL,
+    install_method TEXT,
+    installed INTEGER NOT NULL,
+    version TEXT,
+    install_path TEXT,
+    installer_path TEXT,
+    wsl_distro TEXT,
+    windows_install_path TEXT,
+    ssh_config TEXT,
+    is_builtin INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_tool_instances_base ON tool_instances(base_id, tool_type);
+
+CREATE TABLE IF NOT EXISTS version_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    instance_id TEXT NOT NULL,
+    version TEXT,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_version_history_instance ON version_history(instance_id, id);
+
+CREATE TRIGGER IF NOT EXISTS trg_version_history_insert
+AFTER INSERT ON tool_instances WHEN NEW.version IS NOT NULL
+BEGIN
+    INSERT INTO version_history (instance_id, version, recorded_at)
+    VALUES (NEW.instance_id, NEW.version, NEW.updated_at);
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_version_history_update
+AFTER UPDATE OF version ON tool_instances WHEN NEW.version IS NOT OLD.version
+BEGIN
+    INSERT INTO version_history (instance_id, version, recorded_at)
+    VALUES (NEW.instance_id, NEW.version, NEW.updated_at);
+END;
+"#;
+
+fn create_base_tables(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(BASE_SCHEMA_SQL)?;
+    Ok(())
+}
+
+/// v2：SSH 实例与手动添加的本地实例改用稳定 ID
+///
+/// 旧 ID 分别取自显示名称和添加时间，同一目标可能对应多个 ID。改写后的旧 ID 记录在
+/// `instance_id_aliases` 中，仍可按旧 ID 查到实例；新 ID 已被占用时保留旧 ID。
+fn stabilize_instance_ids(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE IF NOT EXISTS instance_id_aliases (
+            old_id TEXT PRIMARY KEY,
+            new_id TEXT NOT NULL
+        );",
+    )?;
+
+    let mut stmt = tx.prepare(
+        "SELECT instance_id, base_id, tool_type, install_path, ssh_config, is_builtin
+         FROM tool_instances",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, bool>(5)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    drop(stmt);
+
+    for (old_id, base_id, tool_type, install_path, ssh_config, is_builtin) in rows {
+        let new_id = match (tool_type.as_str(), install_path, ssh_config) {
+            ("SSH", _, Some(raw)) => match serde_json::from_str::<SSHConfig>(&raw) {
+                Ok(config) => ToolInstance::ssh_instance_id(&base_id, &config),
+                Err(e) => {
+                    tracing::warn!(instance_id = %old_id, error = %e, "SSH 配置无法解析，保留原 ID");
+                    continue;
+                }
+            },
+            ("Local", Some(path), _) if !is_builtin => {
+                ToolInstance::manual_local_instance_id(&base_id, &path)
+            }
+            _ => continue,
+        };
+        if new_id == old_id {
+            continue;
+        }
+
+        let taken: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM tool_instances WHERE instance_id = ?1",
+            [&new_id],
+            |row| row.get(0),
+        )?;
+        if taken > 0 {
+            tracing::warn!(old_id = %old_id, new_id = %new_id, "稳定 ID 已被占用，保留原 ID");
+            continue;
+        }
+
+        tx.execute(
+            "UPDATE tool_instances SET instance_id = ?2 WHERE instance_id = ?1",
+            params![old_id, new_id],
+        )?;
+        tx.execute(
+            "UPDATE version_history SET instance_id = ?2 WHERE instance_id = ?1",
+            params![old_id, new_id],
+        )?;
+        tx.execute(
+            "UPDATE instance_id_aliases SET new_id = ?2 WHERE new_id = ?1",
+            params![old_id, new_id],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO instance_id_aliases (old_id, new_id) VALUES (?1, ?2)",
+            params![old_id, new_id],
+        )?;
+        tracing::info!(old_id = %old_id, new_id = %new_id, "工具实例已改用稳定 ID");
+    }
+    Ok(())
+}
+
+/// v3：实例标签与备注（用户填写，检测结果写入时不覆盖）
+fn add_label_and_notes(tx: &Transaction) -> Result<()> {
+    for column in ["label", "notes"] {
+        if !has_column(tx, "tool_instances", column)? {
+            tx.execute_batch(&format!(
+                "ALTER TABLE tool_instances ADD COLUMN {column} TEXT"
+            ))?;
+        }
+    }
+    Ok(())
+}
+
+/// v4：实例环境变量覆盖（JSON 对象文本，用户填写，检测结果写入时不覆盖）
+fn add_env_overrides(tx: &Transaction) -> Result<()> {
+    if !has_column(tx, "tool_instances", "env_overrides")? {
+        tx.execute_batch("ALTER TABLE tool_instances ADD COLUMN env_overrides TEXT")?;
+    }
+    Ok(())
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = tx.prepare(&format!("PRAGMA table_info({table})"))?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(columns.iter().any(|name| name == column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<u32> = SCHEMA_MIGRATIONS.iter().map(|m| m.version).collect();
+        let expected: Vec<u32> = (1..=latest_schema_version()).collect();
+        assert_eq!(versions, expected);
+    }
+}
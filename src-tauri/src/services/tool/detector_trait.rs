@@ -4,13 +4,24 @@
 // 每个工具实现此 trait 以提供工具特定的逻辑
 
 use crate::data::DataManager;
-use crate::models::InstallMethod;
-use crate::utils::CommandExecutor;
+use crate::models::{InstallMethod, PackageCoordinates};
+use crate::services::tool::packages::package_coordinates;
+use crate::utils::{CommandRunner, PROBE_TIMEOUT};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
 use std::path::PathBuf;
 
+/// 版本检查时从子进程环境中移除的代理变量
+const PROXY_ENV_VARS: [&str; 6] = [
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+];
+
 /// 工具检测器 Trait
 ///
 /// 每个 AI 开发工具（Claude Code、CodeX、Gemini CLI）都实现此接口
@@ -31,8 +42,15 @@ pub trait ToolDetector: Send + Sync {
     /// 配置文件名（如 "settings.json"）
     fn config_file(&self) -> &str;
 
+    /// 包坐标（内置定义叠加用户配置的覆盖，见 `services::tool::packages`）
+    fn package_coordinates(&self) -> PackageCoordinates {
+        package_coordinates(self.tool_id())
+    }
+
     /// npm 包名（如 "@anthropic-ai/claude-code"）
-    fn npm_package(&self) -> &str;
+    fn npm_package(&self) -> String {
+        self.package_coordinates().npm.unwrap_or_default()
+    }
 
     /// 版本检查命令（如 "claude --version"）
     fn check_command(&self) -> &str;
@@ -47,7 +65,7 @@ pub trait ToolDetector: Send + Sync {
     /// 检测工具是否已安装
     ///
     /// 默认实现：执行 check_command 并判断是否成功
-    async fn is_installed(&self, executor: &CommandExecutor) -> bool {
+    async fn is_installed(&self, executor: &dyn CommandRunner) -> bool {
         let cmd = self.check_command().split_whitespace().next().unwrap_or("");
         if cmd.is_empty() {
             return false;
@@ -58,9 +76,15 @@ pub trait ToolDetector: Send + Sync {
     /// 获取已安装版本
     ///
     /// 默认实现：执行 check_command 并提取版本号
-    async fn get_version(&self, executor: &CommandExecutor) -> Option<String> {
+    async fn get_version(&self, executor: &dyn CommandRunner) -> Option<String> {
         let result = if self.use_proxy_for_version_check() {
-            executor.execute_async(self.check_command()).await
+            let mut parts = self.check_command().split_whitespace();
+            let program = parts.next()?;
+            let args: Vec<&str> = parts.collect();
+            executor
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_args_async(program, &args)
+                .await
         } else {
             self.execute_without_proxy(executor, self.check_command())
                 .await
@@ -76,15 +100,18 @@ pub trait ToolDetector: Send + Sync {
     /// 获取安装路径（如 /usr/local/bin/claude）
     ///
     /// 默认实现：使用 which/where 命令
-    async fn get_install_path(&self, executor: &CommandExecutor) -> Option<String> {
+    async fn get_install_path(&self, executor: &dyn CommandRunner) -> Option<String> {
         let cmd_name = self.check_command().split_whitespace().next()?;
 
         #[cfg(target_os = "windows")]
-        let which_cmd = format!("where {}", cmd_name);
+        let which = "where";
         #[cfg(not(target_os = "windows"))]
-        let which_cmd = format!("which {}", cmd_name);
+        let which = "which";
 
-        let result = executor.execute_async(&which_cmd).await;
+        let result = executor
+            .with_timeout(PROBE_TIMEOUT)
+            .execute_args_async(which, &[cmd_name])
+            .await;
         if result.success {
             let path = result.stdout.lines().next()?.trim();
             if !path.is_empty() {
@@ -97,7 +124,7 @@ pub trait ToolDetector: Send + Sync {
     /// 检测工具的安装方法（npm、Homebrew、官方脚本）
     ///
     /// 需要每个工具自己实现，因为检测逻辑不同
-    async fn detect_install_method(&self, executor: &CommandExecutor) -> Option<InstallMethod>;
+    async fn detect_install_method(&self, executor: &dyn CommandRunner) -> Option<InstallMethod>;
 
     // ==================== 安装逻辑 ====================
 
@@ -109,7 +136,7 @@ pub trait ToolDetector: Send + Sync {
     /// - force: 是否强制重新安装
     async fn install(
         &self,
-        executor: &CommandExecutor,
+        executor: &dyn CommandRunner,
         method: &InstallMethod,
         force: bool,
     ) -> Result<()>;
@@ -119,7 +146,7 @@ pub trait ToolDetector: Send + Sync {
     /// 参数：
     /// - executor: 命令执行器
     /// - force: 是否强制更新
-    async fn update(&self, executor: &CommandExecutor, force: bool) -> Result<()>;
+    async fn update(&self, executor: &dyn CommandRunner, force: bool) -> Result<()>;
 
     // ==================== 配置管理 ====================
 
@@ -140,87 +167,19 @@ pub trait ToolDetector: Send + Sync {
 
     /// 执行命令但不使用代理（用于版本检查）
     ///
-    /// 默认实现：移除所有代理环境变量
+    /// 默认实现：子进程移除所有代理环境变量
     async fn execute_without_proxy(
         &self,
-        _executor: &CommandExecutor,
+        executor: &dyn CommandRunner,
         command: &str,
     ) -> crate::utils::CommandResult {
-        use crate::utils::platform::PlatformInfo;
-        use std::process::Command;
-
-        #[cfg(target_os = "windows")]
-        use std::os::windows::process::CommandExt;
-
-        let command_str = command.to_string();
-        let platform = PlatformInfo::current();
-
-        tokio::task::spawn_blocking(move || {
-            let enhanced_path = platform.build_enhanced_path();
-
-            let output = if platform.is_windows {
-                #[cfg(target_os = "windows")]
-                {
-                    Command::new("cmd")
-                        .args(["/C", &command_str])
-                        .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                        .env("PATH", &enhanced_path)
-                        .env_remove("HTTP_PROXY")
-                        .env_remove("HTTPS_PROXY")
-                        .env_remove("ALL_PROXY")
-                        .env_remove("http_proxy")
-                        .env_remove("https_proxy")
-                        .env_remove("all_proxy")
-                        .output()
-                }
-                #[cfg(not(target_os = "windows"))]
-                {
-                    Command::new("cmd")
-                        .args(["/C", &command_str])
-                        .env("PATH", &enhanced_path)
-                        .env_remove("HTTP_PROXY")
-                        .env_remove("HTTPS_PROXY")
-                        .env_remove("ALL_PROXY")
-                        .env_remove("http_proxy")
-                        .env_remove("https_proxy")
-                        .env_remove("all_proxy")
-                        .output()
-                }
-            } else {
-                Command::new("sh")
-                    .args(["-c", &command_str])
-                    .env("PATH", &enhanced_path)
-                    .env_remove("HTTP_PROXY")
-                    .env_remove("HTTPS_PROXY")
-                    .env_remove("ALL_PROXY")
-                    .env_remove("http_proxy")
-                    .env_remove("https_proxy")
-                    .env_remove("all_proxy")
-                    .output()
-            };
-
-            match output {
-                Ok(output) => crate::utils::CommandResult {
-                    success: output.status.success(),
-                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                    exit_code: output.status.code(),
-                },
-                Err(e) => crate::utils::CommandResult {
-                    success: false,
-                    stdout: String::new(),
-                    stderr: e.to_string(),
-                    exit_code: None,
-                },
-            }
-        })
-        .await
-        .unwrap_or_else(|_| crate::utils::CommandResult {
-            success: false,
-            stdout: String::new(),
-            stderr: "执行失败".to_string(),
-            exit_code: None,
-        })
+        PROXY_ENV_VARS
+            .iter()
+            .fold(executor.with_timeout(PROBE_TIMEOUT), |executor, key| {
+                executor.env_remove(*key)
+            })
+            .execute_async(command)
+            .await
     }
 
     /// 默认版本号提取逻辑
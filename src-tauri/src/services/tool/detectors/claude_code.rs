@@ -6,7 +6,7 @@ use super::super::detector_trait::ToolDetector;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
-use crate::utils::CommandExecutor;
+use crate::utils::{CommandRunner, PlatformInfo, INSTALL_TIMEOUT, PROBE_TIMEOUT};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
@@ -15,6 +15,18 @@ use std::path::PathBuf;
 #[cfg(target_os = "windows")]
 use std::process::Command;
 
+/// 官方安装脚本提供二进制的平台
+const CLAUDE_OFFICIAL_PLATFORMS: &[&str] = &[
+    "darwin-arm64",
+    "darwin-x64",
+    "linux-x64",
+    "linux-arm64",
+    "linux-x64-musl",
+    "linux-arm64-musl",
+    "win32-x64",
+    "win32-arm64",
+];
+
 /// Claude Code 工具检测器
 pub struct ClaudeCodeDetector {
     config_dir: PathBuf,
@@ -67,10 +79,6 @@ impl ToolDetector for ClaudeCodeDetector {
         "settings.json"
     }
 
-    fn npm_package(&self) -> &str {
-        "@anthropic-ai/claude-code"
-    }
-
     fn check_command(&self) -> &str {
         "claude --version"
     }
@@ -82,7 +90,7 @@ impl ToolDetector for ClaudeCodeDetector {
 
     // ==================== 检测逻辑 ====================
 
-    async fn detect_install_method(&self, executor: &CommandExecutor) -> Option<InstallMethod> {
+    async fn detect_install_method(&self, executor: &dyn CommandRunner) -> Option<InstallMethod> {
         // 检查是否通过 npm 安装
         if executor.command_exists_async("npm").await {
             let stderr_redirect = if cfg!(windows) {
@@ -90,8 +98,11 @@ impl ToolDetector for ClaudeCodeDetector {
             } else {
                 "2>/dev/null"
             };
-            let cmd = format!("npm list -g @anthropic-ai/claude-code {stderr_redirect}");
-            let result = executor.execute_async(&cmd).await;
+            let cmd = format!("npm list -g {} {stderr_redirect}", self.npm_package());
+            let result = executor
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_async(&cmd)
+                .await;
             if result.success {
                 return Some(InstallMethod::Npm);
             }
@@ -105,7 +116,7 @@ impl ToolDetector for ClaudeCodeDetector {
 
     async fn install(
         &self,
-        executor: &CommandExecutor,
+        executor: &dyn CommandRunner,
         method: &InstallMethod,
         force: bool,
     ) -> Result<()> {
@@ -121,7 +132,7 @@ impl ToolDetector for ClaudeCodeDetector {
         }
     }
 
-    async fn update(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
+    async fn update(&self, executor: &dyn CommandRunner, force: bool) -> Result<()> {
         // 检测当前安装方法
         let method = self.detect_install_method(executor).await;
 
@@ -161,7 +172,10 @@ impl ToolDetector for ClaudeCodeDetector {
 
 impl ClaudeCodeDetector {
     /// 使用官方脚本安装（DuckCoding 镜像）
-    async fn install_official(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
+    async fn install_official(&self, executor: &dyn CommandRunner, force: bool) -> Result<()> {
+        // 不支持的平台直接报错，避免脚本下载时才以 404 失败
+        PlatformInfo::current().require_supported(CLAUDE_OFFICIAL_PLATFORMS)?;
+
         // 安装前先检查镜像状态
         if !force {
             let version_service = VersionService::new();
@@ -200,17 +214,20 @@ impl ClaudeCodeDetector {
             "curl -fsSL https://mirror.duckcoding.com/claude-code/install.sh | bash".to_string()
         };
 
-        let result = executor.execute_async(&command).await;
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ 官方脚本安装失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ 官方脚本安装失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
+    async fn install_npm(&self, executor: &dyn CommandRunner, force: bool) -> Result<()> {
         if !executor.command_exists_async("npm").await {
             anyhow::bail!("npm 未安装，请先安装 Node.js");
         }
@@ -229,32 +246,40 @@ impl ClaudeCodeDetector {
 
         let package_spec = match version_hint {
             Some(version) if !version.is_empty() => {
-                format!("@anthropic-ai/claude-code@{}", version)
+                format!("{}@{}", self.npm_package(), version)
             }
-            _ => "@anthropic-ai/claude-code@latest".to_string(),
+            _ => format!("{}@latest", self.npm_package()),
         };
 
         let command =
             format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 安装失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 npm 更新
-    async fn update_npm(&self, executor: &CommandExecutor) -> Result<()> {
-        let command =
-            "npm update -g @anthropic-ai/claude-code --registry https://registry.npmmirror.com";
-        let result = executor.execute_async(command).await;
+    async fn update_npm(&self, executor: &dyn CommandRunner) -> Result<()> {
+        let command = format!(
+            "npm update -g {} --registry https://registry.npmmirror.com",
+            self.npm_package()
+        );
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 更新失败\n\n{}", result.failure_detail())
         }
     }
 
@@ -284,4 +309,25 @@ mod tests {
         assert_eq!(detector.check_command(), "claude --version");
         assert!(!detector.use_proxy_for_version_check());
     }
+
+    #[tokio::test]
+    async fn test_version_and_path_with_mock() {
+        use crate::utils::MockExecutor;
+
+        let detector = ClaudeCodeDetector::new();
+        let mock = MockExecutor::new()
+            .with_command("claude", "/usr/local/bin/claude")
+            .on_success("claude --version", "2.0.61 (Claude Code)");
+
+        assert!(detector.is_installed(&mock).await);
+        assert_eq!(detector.get_version(&mock).await.as_deref(), Some("2.0.61"));
+        assert_eq!(
+            detector.get_install_path(&mock).await.as_deref(),
+            Some("/usr/local/bin/claude")
+        );
+
+        let missing = MockExecutor::new();
+        assert!(!detector.is_installed(&missing).await);
+        assert!(detector.get_version(&missing).await.is_none());
+    }
 }
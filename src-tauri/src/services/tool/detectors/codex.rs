@@ -6,7 +6,7 @@ use super::super::detector_trait::ToolDetector;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
-use crate::utils::CommandExecutor;
+use crate::utils::{CommandRunner, INSTALL_TIMEOUT, PROBE_TIMEOUT};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
@@ -52,10 +52,6 @@ impl ToolDetector for CodeXDetector {
         "config.toml"
     }
 
-    fn npm_package(&self) -> &str {
-        "@openai/codex"
-    }
-
     fn check_command(&self) -> &str {
         "codex --version"
     }
@@ -67,14 +63,18 @@ impl ToolDetector for CodeXDetector {
 
     // ==================== 检测逻辑 ====================
 
-    async fn detect_install_method(&self, executor: &CommandExecutor) -> Option<InstallMethod> {
-        // 1. 检查是否通过 Homebrew cask 安装
+    async fn detect_install_method(&self, executor: &dyn CommandRunner) -> Option<InstallMethod> {
+        // 1. 检查是否通过 Homebrew 安装（macOS 上为 cask；Linuxbrew 不支持 cask，只有 formula）
         if executor.command_exists_async("brew").await {
-            let result = executor
-                .execute_async("brew list --cask codex 2>/dev/null")
-                .await;
-            if result.success && result.stdout.contains("codex") {
-                return Some(InstallMethod::Brew);
+            let name = self.brew_cask();
+            for kind in ["--cask", "--formula"] {
+                let result = executor
+                    .with_timeout(PROBE_TIMEOUT)
+                    .execute_async(&format!("brew list {kind} {name} 2>/dev/null"))
+                    .await;
+                if result.success && result.stdout.contains(&name) {
+                    return Some(InstallMethod::Brew);
+                }
             }
         }
 
@@ -85,8 +85,11 @@ impl ToolDetector for CodeXDetector {
             } else {
                 "2>/dev/null"
             };
-            let cmd = format!("npm list -g @openai/codex {stderr_redirect}");
-            let result = executor.execute_async(&cmd).await;
+            let cmd = format!("npm list -g {} {stderr_redirect}", self.npm_package());
+            let result = executor
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_async(&cmd)
+                .await;
             if result.success {
                 return Some(InstallMethod::Npm);
             }
@@ -100,7 +103,7 @@ impl ToolDetector for CodeXDetector {
 
     async fn install(
         &self,
-        executor: &CommandExecutor,
+        executor: &dyn CommandRunner,
         method: &InstallMethod,
         force: bool,
     ) -> Result<()> {
@@ -116,7 +119,7 @@ impl ToolDetector for CodeXDetector {
         }
     }
 
-    async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
+    async fn update(&self, executor: &dyn CommandRunner, _force: bool) -> Result<()> {
         let method = self.detect_install_method(executor).await;
 
         match method {
@@ -187,7 +190,7 @@ impl ToolDetector for CodeXDetector {
 
 impl CodeXDetector {
     /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
+    async fn install_npm(&self, executor: &dyn CommandRunner, force: bool) -> Result<()> {
         if !executor.command_exists_async("npm").await {
             anyhow::bail!("npm 未安装");
         }
@@ -204,57 +207,74 @@ impl CodeXDetector {
         };
 
         let package_spec = match version_hint {
-            Some(version) if !version.is_empty() => format!("@openai/codex@{}", version),
-            _ => "@openai/codex@latest".to_string(),
+            Some(version) if !version.is_empty() => format!("{}@{}", self.npm_package(), version),
+            _ => format!("{}@latest", self.npm_package()),
         };
 
         let command =
             format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 安装失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 Homebrew 安装
-    async fn install_brew(&self, executor: &CommandExecutor) -> Result<()> {
-        if !cfg!(target_os = "macos") {
-            anyhow::bail!("❌ Homebrew 仅支持 macOS");
+    async fn install_brew(&self, executor: &dyn CommandRunner) -> Result<()> {
+        if cfg!(target_os = "windows") {
+            anyhow::bail!("❌ Homebrew 不支持 Windows");
         }
 
         if !executor.command_exists_async("brew").await {
             anyhow::bail!("❌ Homebrew 未安装");
         }
 
-        let command = "brew install --cask codex";
-        let result = executor.execute_async(command).await;
+        let command = format!("brew install {} {}", brew_kind(), self.brew_cask());
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ Homebrew 安装失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ Homebrew 安装失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 npm 更新
-    async fn update_npm(&self, executor: &CommandExecutor) -> Result<()> {
-        let command = "npm update -g @openai/codex --registry https://registry.npmmirror.com";
-        let result = executor.execute_async(command).await;
+    async fn update_npm(&self, executor: &dyn CommandRunner) -> Result<()> {
+        let command = format!(
+            "npm update -g {} --registry https://registry.npmmirror.com",
+            self.npm_package()
+        );
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 更新失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 Homebrew 更新
-    async fn update_brew(&self, executor: &CommandExecutor) -> Result<()> {
-        let command = "brew upgrade --cask codex";
-        let result = executor.execute_async(command).await;
+    async fn update_brew(&self, executor: &dyn CommandRunner) -> Result<()> {
+        let cask = self.brew_cask();
+        let kind = brew_kind();
+        let command = format!("brew upgrade {kind} {cask}");
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
@@ -265,8 +285,9 @@ impl CodeXDetector {
             if error_str.contains("Not upgrading") && error_str.contains("already installed") {
                 anyhow::bail!(
                     "⚠️ Homebrew版本滞后\n\n推荐切换到 npm 安装：\n\
-                     1. brew uninstall --cask codex\n\
-                     2. npm install -g @openai/codex --registry https://registry.npmmirror.com"
+                     1. brew uninstall {kind} {cask}\n\
+                     2. npm install -g {} --registry https://registry.npmmirror.com",
+                    self.npm_package()
                 );
             }
 
@@ -274,6 +295,13 @@ impl CodeXDetector {
         }
     }
 
+    /// Homebrew cask 名
+    fn brew_cask(&self) -> String {
+        self.package_coordinates()
+            .brew
+            .unwrap_or_else(|| self.tool_id().to_string())
+    }
+
     /// 转换为旧版 Tool 结构
     fn to_legacy_tool(&self) -> crate::models::Tool {
         crate::models::Tool::codex()
@@ -287,6 +315,15 @@ impl CodeXDetector {
     }
 }
 
+/// Homebrew 包类型参数：macOS 使用 cask，Linuxbrew 只支持 formula
+fn brew_kind() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "--cask"
+    } else {
+        "--formula"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,4 +336,40 @@ mod tests {
         assert_eq!(detector.npm_package(), "@openai/codex");
         assert!(detector.use_proxy_for_version_check());
     }
+
+    #[tokio::test]
+    async fn test_detect_install_method_with_mock() {
+        use crate::utils::{CommandResult, MockExecutor};
+
+        let detector = CodeXDetector::new();
+        let brew = MockExecutor::new()
+            .with_command("brew", "/opt/homebrew/bin/brew")
+            .on_success("brew list --cask codex*", "codex");
+        assert_eq!(
+            detector.detect_install_method(&brew).await,
+            Some(InstallMethod::Brew)
+        );
+
+        let npm = MockExecutor::new()
+            .with_command("npm", "/usr/local/bin/npm")
+            .on_success("npm list -g @openai/codex*", "└── @openai/codex@0.65.0");
+        assert_eq!(
+            detector.detect_install_method(&npm).await,
+            Some(InstallMethod::Npm)
+        );
+        assert!(!npm.called("brew list*"));
+
+        // Linuxbrew 没有 cask，按 formula 识别
+        let linuxbrew = MockExecutor::new()
+            .with_command("brew", "/home/linuxbrew/.linuxbrew/bin/brew")
+            .on("brew list --cask codex*", CommandResult::failure(1, ""))
+            .on_success(
+                "brew list --formula codex*",
+                "/home/linuxbrew/.linuxbrew/Cellar/codex/0.65.0/bin/codex",
+            );
+        assert_eq!(
+            detector.detect_install_method(&linuxbrew).await,
+            Some(InstallMethod::Brew)
+        );
+    }
 }
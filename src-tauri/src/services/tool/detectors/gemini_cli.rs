@@ -6,7 +6,7 @@ use super::super::detector_trait::ToolDetector;
 use crate::data::DataManager;
 use crate::models::InstallMethod;
 use crate::services::version::{VersionInfo, VersionService};
-use crate::utils::CommandExecutor;
+use crate::utils::{CommandRunner, INSTALL_TIMEOUT, PROBE_TIMEOUT};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde_json::Value;
@@ -52,10 +52,6 @@ impl ToolDetector for GeminiCLIDetector {
         "settings.json"
     }
 
-    fn npm_package(&self) -> &str {
-        "@google/gemini-cli"
-    }
-
     fn check_command(&self) -> &str {
         "gemini --version"
     }
@@ -67,7 +63,7 @@ impl ToolDetector for GeminiCLIDetector {
 
     // ==================== 检测逻辑 ====================
 
-    async fn detect_install_method(&self, executor: &CommandExecutor) -> Option<InstallMethod> {
+    async fn detect_install_method(&self, executor: &dyn CommandRunner) -> Option<InstallMethod> {
         // Gemini CLI 仅支持 npm 安装
         if executor.command_exists_async("npm").await {
             let stderr_redirect = if cfg!(windows) {
@@ -75,8 +71,11 @@ impl ToolDetector for GeminiCLIDetector {
             } else {
                 "2>/dev/null"
             };
-            let cmd = format!("npm list -g @google/gemini-cli {stderr_redirect}");
-            let result = executor.execute_async(&cmd).await;
+            let cmd = format!("npm list -g {} {stderr_redirect}", self.npm_package());
+            let result = executor
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_async(&cmd)
+                .await;
             if result.success {
                 return Some(InstallMethod::Npm);
             }
@@ -89,7 +88,7 @@ impl ToolDetector for GeminiCLIDetector {
 
     async fn install(
         &self,
-        executor: &CommandExecutor,
+        executor: &dyn CommandRunner,
         method: &InstallMethod,
         force: bool,
     ) -> Result<()> {
@@ -101,7 +100,7 @@ impl ToolDetector for GeminiCLIDetector {
         }
     }
 
-    async fn update(&self, executor: &CommandExecutor, _force: bool) -> Result<()> {
+    async fn update(&self, executor: &dyn CommandRunner, _force: bool) -> Result<()> {
         self.update_npm(executor).await
     }
 
@@ -128,7 +127,7 @@ impl ToolDetector for GeminiCLIDetector {
 
 impl GeminiCLIDetector {
     /// 使用 npm 安装
-    async fn install_npm(&self, executor: &CommandExecutor, force: bool) -> Result<()> {
+    async fn install_npm(&self, executor: &dyn CommandRunner, force: bool) -> Result<()> {
         if !executor.command_exists_async("npm").await {
             anyhow::bail!("npm 未安装");
         }
@@ -145,30 +144,39 @@ impl GeminiCLIDetector {
         };
 
         let package_spec = match version_hint {
-            Some(version) if !version.is_empty() => format!("@google/gemini-cli@{}", version),
-            _ => "@google/gemini-cli@latest".to_string(),
+            Some(version) if !version.is_empty() => format!("{}@{}", self.npm_package(), version),
+            _ => format!("{}@latest", self.npm_package()),
         };
 
         let command =
             format!("npm install -g {package_spec} --registry https://registry.npmmirror.com");
-        let result = executor.execute_async(&command).await;
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 安装失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 安装失败\n\n{}", result.failure_detail())
         }
     }
 
     /// 使用 npm 更新
-    async fn update_npm(&self, executor: &CommandExecutor) -> Result<()> {
-        let command = "npm update -g @google/gemini-cli --registry https://registry.npmmirror.com";
-        let result = executor.execute_async(command).await;
+    async fn update_npm(&self, executor: &dyn CommandRunner) -> Result<()> {
+        let command = format!(
+            "npm update -g {} --registry https://registry.npmmirror.com",
+            self.npm_package()
+        );
+        let result = executor
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute_async(&command)
+            .await;
 
         if result.success {
             Ok(())
         } else {
-            anyhow::bail!("❌ npm 更新失败\n\n{}", result.stderr)
+            anyhow::bail!("❌ npm 更新失败\n\n{}", result.failure_detail())
         }
     }
 
@@ -1,20 +1,34 @@
+use crate::models::dashboard::ActivityKind;
+use crate::models::telemetry::TelemetryEvent;
 use crate::models::{InstallMethod, Tool, ToolInstance, UpdateResult};
-use crate::services::tool::DetectorRegistry;
-use crate::utils::parse_version_string;
+use crate::services::dashboard_manager::record_activity;
+use crate::services::telemetry::record_event;
+use crate::services::tool::{resolve_tool, DetectorRegistry};
+use crate::services::VersionService;
+use crate::utils::{parse_version_string, CommandExecutor, CommandRunner, PROBE_TIMEOUT};
 use anyhow::Result;
-use tokio::time::{timeout, Duration};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// 通过安装器快捷更新的超时
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// 安装服务（新架构：委托给 Detector）
 pub struct InstallerService {
     detector_registry: DetectorRegistry,
-    command_executor: crate::utils::CommandExecutor,
+    command_executor: Arc<dyn CommandRunner>,
 }
 
 impl InstallerService {
     pub fn new() -> Self {
+        Self::with_runner(Arc::new(CommandExecutor::new()))
+    }
+
+    /// 使用指定的命令执行器（测试中传入 `MockExecutor`）
+    pub fn with_runner(command_executor: Arc<dyn CommandRunner>) -> Self {
         InstallerService {
             detector_registry: DetectorRegistry::new(),
-            command_executor: crate::utils::CommandExecutor::new(),
+            command_executor,
         }
     }
 
@@ -27,8 +41,16 @@ impl InstallerService {
 
         tracing::info!("使用 Detector 安装工具: {}", tool.name);
         detector
-            .install(&self.command_executor, method, force)
-            .await
+            .install(&*self.command_executor, method, force)
+            .await?;
+
+        record_activity(
+            ActivityKind::ToolInstalled,
+            &tool.id,
+            Some(format!("{method:?}").to_lowercase()),
+        );
+        record_event(TelemetryEvent::ToolInstalled);
+        Ok(())
     }
 
     /// 更新工具（委托给 Detector）
@@ -39,13 +61,13 @@ impl InstallerService {
             .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool.id))?;
 
         tracing::info!("使用 Detector 更新工具: {}", tool.name);
-        detector.update(&self.command_executor, force).await
+        detector.update(&*self.command_executor, force).await
     }
 
     /// 检查工具是否已安装（委托给 Detector）
     pub async fn is_installed(&self, tool: &Tool) -> bool {
         if let Some(detector) = self.detector_registry.get(&tool.id) {
-            detector.is_installed(&self.command_executor).await
+            detector.is_installed(&*self.command_executor).await
         } else {
             false
         }
@@ -54,7 +76,7 @@ impl InstallerService {
     /// 获取已安装版本（委托给 Detector）
     pub async fn get_installed_version(&self, tool: &Tool) -> Option<String> {
         if let Some(detector) = self.detector_registry.get(&tool.id) {
-            detector.get_version(&self.command_executor).await
+            detector.get_version(&*self.command_executor).await
         } else {
             None
         }
@@ -87,11 +109,14 @@ impl InstallerService {
             .ok_or_else(|| anyhow::anyhow!("该实例未配置安装方法，无法执行快捷更新"))?;
 
         // 2. 根据安装方法构建更新命令
-        let tool_obj = Tool::by_id(&instance.base_id).ok_or_else(|| anyhow::anyhow!("未知工具"))?;
+        let tool_obj =
+            resolve_tool(&instance.base_id).ok_or_else(|| anyhow::anyhow!("未知工具"))?;
 
         let update_cmd = match install_method {
             InstallMethod::Npm => {
-                let package_name = &tool_obj.npm_package;
+                let package_name = tool_obj
+                    .npm_package()
+                    .ok_or_else(|| anyhow::anyhow!("{} 不支持 npm 安装", tool_obj.name))?;
                 if force {
                     format!("{} install -g {} --force", installer_path, package_name)
                 } else {
@@ -99,8 +124,12 @@ impl InstallerService {
                 }
             }
             InstallMethod::Brew => {
-                let tool_id = &instance.base_id;
-                format!("{} upgrade {}", installer_path, tool_id)
+                let formula = tool_obj
+                    .package_coordinates
+                    .brew
+                    .as_deref()
+                    .unwrap_or(&instance.base_id);
+                format!("{} upgrade {}", installer_path, formula)
             }
             InstallMethod::Official => {
                 anyhow::bail!("官方安装方式暂不支持快捷更新，请手动重新安装");
@@ -110,27 +139,33 @@ impl InstallerService {
             }
         };
 
-        // 3. 执行更新命令（120秒超时）
+        // 3. 执行更新命令（120秒超时，超时后终止安装器进程树）
         tracing::info!("使用安装器 {} 执行更新: {}", installer_path, update_cmd);
 
-        let update_future = {
-            let executor = self.command_executor.clone();
-            let cmd = update_cmd.clone();
-            async move { executor.execute_async(&cmd).await }
-        };
-
-        let update_result = timeout(Duration::from_secs(120), update_future).await;
+        let started = Instant::now();
+        let result = self
+            .command_executor
+            .with_timeout(UPDATE_TIMEOUT)
+            .execute_async(&update_cmd)
+            .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
 
-        match update_result {
-            Ok(result) if result.success => {
+        match result {
+            result if result.timed_out => {
+                anyhow::bail!("更新超时（{}秒）", UPDATE_TIMEOUT.as_secs());
+            }
+            result if result.success => {
                 // 4. 更新成功，获取新版本
                 let install_path = instance
                     .install_path
                     .as_ref()
                     .ok_or_else(|| anyhow::anyhow!("实例缺少安装路径"))?;
 
-                let version_cmd = format!("{} --version", install_path);
-                let version_result = self.command_executor.execute_async(&version_cmd).await;
+                let version_result = self
+                    .command_executor
+                    .with_timeout(PROBE_TIMEOUT)
+                    .execute_args_async(install_path, &["--version"])
+                    .await;
 
                 let new_version = if version_result.success {
                     let raw = version_result.stdout.trim();
@@ -139,6 +174,15 @@ impl InstallerService {
                     None
                 };
 
+                // 下载大小：npm 包按版本查询（检查更新时已缓存）
+                let download_size = match (install_method, &new_version) {
+                    (InstallMethod::Npm, Some(version)) => match tool_obj.npm_package() {
+                        Some(package) => VersionService::npm_download_size(package, version).await,
+                        None => None,
+                    },
+                    _ => None,
+                };
+
                 Ok(UpdateResult {
                     success: true,
                     message: "✅ 更新成功！".to_string(),
@@ -148,19 +192,25 @@ impl InstallerService {
                     mirror_version: None,
                     mirror_is_stale: None,
                     tool_id: Some(instance.base_id.clone()),
+                    skipped: false,
+                    download_size,
+                    duration_ms: Some(duration_ms),
                 })
             }
-            Ok(result) => {
+            result => {
                 // 命令执行失败
+                let hints: String = result
+                    .issues
+                    .iter()
+                    .map(|issue| format!("\n\n💡 {}", issue.hint()))
+                    .collect();
                 anyhow::bail!(
-                    "更新失败\n\nstderr: {}\nstdout: {}",
+                    "更新失败\n\nstderr: {}\nstdout: {}{}",
                     result.stderr,
-                    result.stdout
+                    result.stdout,
+                    hints
                 );
             }
-            Err(_) => {
-                anyhow::bail!("更新超时（120秒）");
-            }
         }
     }
 }
@@ -203,10 +253,15 @@ mod tests {
             install_path: Some("/usr/local/bin/claude".to_string()),
             installer_path: None, // 缺少安装器路径
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: false,
             created_at: 0,
             updated_at: 0,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         };
 
         // 测试：缺少安装器路径应该失败
@@ -237,10 +292,15 @@ mod tests {
             install_path: Some("/usr/local/bin/claude".to_string()),
             installer_path: Some("/usr/bin/install".to_string()),
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: false,
             created_at: 0,
             updated_at: 0,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         };
 
         let result = service
@@ -264,10 +324,15 @@ mod tests {
             install_path: Some("/usr/local/bin/claude".to_string()),
             installer_path: Some("/usr/bin/install".to_string()),
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: false,
             created_at: 0,
             updated_at: 0,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         };
 
         let result = service
@@ -3,20 +3,31 @@
 // 包含工具的安装、版本检查、下载等功能
 
 pub mod db;
+mod db_migrations;
 pub mod detector_trait;
 pub mod detectors;
 pub mod downloader;
 pub mod installer;
+pub mod node_env;
+pub mod npm_prefix;
+pub mod packages;
+pub mod path_check;
 pub mod registry;
+pub mod status_cache;
 pub mod tools_config;
 pub mod version;
 
-pub use db::ToolInstanceDB;
+pub use db::{ToolInstanceDB, VersionRecord};
 pub use detector_trait::ToolDetector;
 pub use detectors::{ClaudeCodeDetector, CodeXDetector, DetectorRegistry, GeminiCLIDetector};
 pub use downloader::FileDownloader;
 pub use installer::InstallerService;
-pub use registry::ToolRegistry;
+pub use packages::{package_coordinates, resolve_all_tools, resolve_tool};
+pub use registry::{ToolRegistry, ToolStatusView};
+pub use status_cache::{
+    ToolDetectionState, ToolStatusCache, ToolStatusSnapshot, TOOL_DETECTION_CHANGED_EVENT,
+    TOOL_STATUS_CACHE, TOOL_STATUS_CHANGED_EVENT,
+};
 pub use tools_config::{
     LocalToolInstance, SSHToolInstance, ToolGroup, ToolsConfig, WSLToolInstance,
 };
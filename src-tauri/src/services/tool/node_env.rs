@@ -0,0 +1,616 @@
+// Node.js 运行时扫描
+//
+// 同一台机器上常同时存在多个 Node.js（系统自带、Homebrew、nvm / fnm / volta / asdf 安装的多个版本），
+// 每个运行时的 npm 有各自的全局目录。npm 安装的工具属于哪个运行时，决定了用哪个 npm 才能更新它：
+// 用另一个运行时的 npm 更新，新版本会装进另一棵全局目录，原来的工具保持旧版本。
+//
+// 扫描增强 PATH、各版本管理器的安装目录与系统目录中的 node，记录版本与 `npm prefix -g`，
+// 再按全局 prefix 匹配实例的安装路径得到其所属运行时。
+
+use crate::models::{InstallMethod, NodeRuntime, NodeRuntimeSource, ToolInstance};
+use crate::services::tool::npm_prefix::same_dir;
+use crate::utils::{homebrew_prefixes, CommandRunner, PlatformInfo, PROBE_TIMEOUT};
+use once_cell::sync::Lazy;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 扫描结果的缓存时间（每个运行时需要执行两次子进程）
+const CACHE_TTL: Duration = Duration::from_secs(300);
+
+static NODE_RUNTIMES: Lazy<Mutex<Option<(Instant, Vec<NodeRuntime>)>>> =
+    Lazy::new(|| Mutex::new(None));
+
+/// 一个版本管理器的安装布局
+#[derive(Debug, Clone)]
+struct NodeManager {
+    source: NodeRuntimeSource,
+    /// 根目录：PATH 中的 node（如 shim）位于其下时按该来源归类
+    root: PathBuf,
+    /// 存放各版本的目录
+    versions: PathBuf,
+    /// 版本目录内 node 所在的子目录
+    bin_subdir: &'static str,
+}
+
+/// 扫描 node 的位置
+#[derive(Debug, Clone, Default)]
+pub struct NodeSearchDirs {
+    managers: Vec<NodeManager>,
+    system: Vec<PathBuf>,
+}
+
+impl NodeSearchDirs {
+    /// 按环境变量与平台默认位置确定各版本管理器与系统目录
+    pub fn from_env(platform: &PlatformInfo) -> Self {
+        let env_dir = |key: &str| std::env::var_os(key).map(PathBuf::from);
+        let home = dirs::home_dir().unwrap_or_default();
+        let mut search = Self::default();
+
+        if platform.is_windows {
+            let app_data = env_dir("APPDATA").unwrap_or_else(|| home.join("AppData/Roaming"));
+            let local_app_data =
+                env_dir("LOCALAPPDATA").unwrap_or_else(|| home.join("AppData/Local"));
+            // nvm-windows 的版本目录直接位于 NVM_HOME 下
+            let nvm = env_dir("NVM_HOME").unwrap_or_else(|| app_data.join("nvm"));
+            search.add(NodeRuntimeSource::Nvm, nvm.clone(), nvm, "");
+            let fnm = env_dir("FNM_DIR").unwrap_or_else(|| app_data.join("fnm"));
+            search.add(
+                NodeRuntimeSource::Fnm,
+                fnm.clone(),
+                fnm.join("node-versions"),
+                "installation",
+            );
+            let volta = local_app_data.join("Volta");
+            search.add(
+                NodeRuntimeSource::Volta,
+                volta.clone(),
+                volta.join("tools/image/node"),
+                "",
+            );
+            search.system = [
+                "C:\\Program Files\\nodejs",
+                "C:\\Program Files (x86)\\nodejs",
+            ]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+            return search;
+        }
+
+        let nvm = env_dir("NVM_DIR").unwrap_or_else(|| home.join(".nvm"));
+        search.add(
+            NodeRuntimeSource::Nvm,
+            nvm.clone(),
+            nvm.join("versions/node"),
+            "bin",
+        );
+        let fnm = env_dir("FNM_DIR").unwrap_or_else(|| {
+            if platform.is_macos {
+                home.join("Library/Application Support/fnm")
+            } else {
+                env_dir("XDG_DATA_HOME")
+                    .unwrap_or_else(|| home.join(".local/share"))
+                    .join("fnm")
+            }
+        });
+        search.add(
+            NodeRuntimeSource::Fnm,
+            fnm.clone(),
+            fnm.join("node-versions"),
+            "installation/bin",
+        );
+        let volta = env_dir("VOLTA_HOME").unwrap_or_else(|| home.join(".volta"));
+        search.add(
+            NodeRuntimeSource::Volta,
+            volta.clone(),
+            volta.join("tools/image/node"),
+            "bin",
+        );
+        let asdf = env_dir("ASDF_DATA_DIR")
+            .or_else(|| env_dir("ASDF_DIR"))
+            .unwrap_or_else(|| home.join(".asdf"));
+        search.add(
+            NodeRuntimeSource::Asdf,
+            asdf.clone(),
+            asdf.join("installs/nodejs"),
+            "bin",
+        );
+
+        search.system = homebrew_prefixes()
+            .into_iter()
+            .map(|prefix| prefix.join("bin"))
+            .chain(
+                ["/usr/local/bin", "/usr/bin"]
+                    .into_iter()
+                    .map(PathBuf::from),
+            )
+            .collect();
+        search
+    }
+
+    fn add(
+        &mut self,
+        source: NodeRuntimeSource,
+        root: PathBuf,
+        versions: PathBuf,
+        bin_subdir: &'static str,
+    ) {
+        self.managers.push(NodeManager {
+            source,
+            root,
+            versions,
+            bin_subdir,
+        });
+    }
+
+    /// 目录中 node 的来源：版本管理器目录下按管理器归类，其次是系统目录，其余为 PATH
+    fn classify(&self, dir: &Path) -> NodeRuntimeSource {
+        if let Some(manager) = self
+            .managers
+            .iter()
+            .find(|manager| dir.starts_with(&manager.root))
+        {
+            return manager.source;
+        }
+        if self.system.iter().any(|system| system == dir) {
+            return NodeRuntimeSource::System;
+        }
+        NodeRuntimeSource::Path
+    }
+}
+
+/// 待探测的 node 可执行文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NodeCandidate {
+    path: PathBuf,
+    source: NodeRuntimeSource,
+    active: bool,
+}
+
+fn node_executable(is_windows: bool) -> &'static str {
+    if is_windows {
+        "node.exe"
+    } else {
+        "node"
+    }
+}
+
+fn npm_executable(is_windows: bool) -> &'static str {
+    if is_windows {
+        "npm.cmd"
+    } else {
+        "npm"
+    }
+}
+
+/// 枚举 node 可执行文件：PATH 顺序在前，其次是各版本管理器的已安装版本，最后是系统目录
+///
+/// 指向同一文件的路径（如 /bin/node 与 /usr/bin/node）只保留第一次出现的
+fn candidate_node_binaries(
+    dirs: &NodeSearchDirs,
+    path_entries: &[String],
+    is_windows: bool,
+) -> Vec<NodeCandidate> {
+    let executable = node_executable(is_windows);
+    let mut found: Vec<(PathBuf, NodeRuntimeSource)> = path_entries
+        .iter()
+        .map(PathBuf::from)
+        .map(|dir| {
+            let source = dirs.classify(&dir);
+            (dir.join(executable), source)
+        })
+        .collect();
+    for manager in &dirs.managers {
+        let Ok(entries) = std::fs::read_dir(&manager.versions) else {
+            continue;
+        };
+        let mut versions: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect();
+        versions.sort();
+        found.extend(versions.into_iter().map(|version| {
+            (
+                version.join(manager.bin_subdir).join(executable),
+                manager.source,
+            )
+        }));
+    }
+    found.extend(
+        dirs.system
+            .iter()
+            .map(|dir| (dir.join(executable), NodeRuntimeSource::System)),
+    );
+
+    let mut seen = HashSet::new();
+    let mut candidates: Vec<NodeCandidate> = found
+        .into_iter()
+        .filter(|(path, _)| path.is_file())
+        .filter(|(path, _)| {
+            seen.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+        })
+        .map(|(path, source)| NodeCandidate {
+            path,
+            source,
+            active: false,
+        })
+        .collect();
+    // PATH 中的第一个 node 即终端里实际使用的运行时
+    if let Some(first) = candidates.first_mut() {
+        let first_dir = first.path.parent().map(Path::to_path_buf);
+        first.active = path_entries
+            .iter()
+            .any(|entry| first_dir.as_deref() == Some(Path::new(entry)));
+    }
+    candidates
+}
+
+/// 执行 `node --version` 与同目录 npm 的 `npm prefix -g`
+///
+/// npm 脚本通过 `env node` 启动，执行时把该 node 所在目录放在 PATH 最前，确保使用同一个运行时
+async fn probe(
+    runner: &dyn CommandRunner,
+    platform: &PlatformInfo,
+    candidate: NodeCandidate,
+    path_entries: &[String],
+) -> NodeRuntime {
+    let node_path = candidate.path.to_string_lossy().to_string();
+    let bin_dir = candidate.path.parent().unwrap_or(Path::new(""));
+    let path = platform
+        .merge_path_entries(
+            std::iter::once(bin_dir.to_string_lossy().to_string())
+                .chain(path_entries.iter().cloned()),
+            false,
+        )
+        .join(platform.path_separator());
+    let runner = runner
+        .envs(&BTreeMap::from([("PATH".to_string(), path)]))
+        .with_timeout(PROBE_TIMEOUT);
+
+    let output = runner.execute_args_async(&node_path, &["--version"]).await;
+    let version = Some(output.stdout.trim())
+        .filter(|v| output.success && !v.is_empty())
+        .map(str::to_string);
+
+    let npm = bin_dir.join(npm_executable(platform.is_windows));
+    let global_prefix = if npm.is_file() {
+        let output = runner
+            .execute_args_async(&npm.to_string_lossy(), &["prefix", "-g"])
+            .await;
+        Some(output.stdout.trim())
+            .filter(|prefix| output.success && !prefix.is_empty())
+            .map(str::to_string)
+    } else {
+        None
+    };
+
+    NodeRuntime {
+        node_path,
+        source: candidate.source,
+        version,
+        global_prefix,
+        active: candidate.active,
+    }
+}
+
+/// 扫描本机的 Node.js 运行时（不使用缓存）
+pub async fn scan_node_runtimes(
+    runner: &dyn CommandRunner,
+    platform: &PlatformInfo,
+    dirs: &NodeSearchDirs,
+    path_entries: &[String],
+) -> Vec<NodeRuntime> {
+    let mut runtimes = Vec::new();
+    for candidate in candidate_node_binaries(dirs, path_entries, platform.is_windows) {
+        runtimes.push(probe(runner, platform, candidate, path_entries).await);
+    }
+    tracing::debug!(count = runtimes.len(), "Node.js 运行时扫描完成");
+    runtimes
+}
+
+/// 本机的 Node.js 运行时（结果缓存 5 分钟，`refresh` 为 true 时重新扫描）
+pub async fn node_runtimes(runner: &dyn CommandRunner, refresh: bool) -> Vec<NodeRuntime> {
+    if !refresh {
+        let cached = NODE_RUNTIMES.lock().unwrap();
+        if let Some((scanned_at, runtimes)) = cached.as_ref() {
+            if scanned_at.elapsed() < CACHE_TTL {
+                return runtimes.clone();
+            }
+        }
+    }
+
+    let platform = PlatformInfo::current();
+    let runtimes = scan_node_runtimes(
+        runner,
+        &platform,
+        &NodeSearchDirs::from_env(&platform),
+        &platform.enhanced_path_entries(true),
+    )
+    .await;
+    *NODE_RUNTIMES.lock().unwrap() = Some((Instant::now(), runtimes.clone()));
+    runtimes
+}
+
+/// `path` 是否为 `dir` 本身或位于其下（忽略结尾分隔符；Windows 上不区分大小写）
+fn path_within(path: &str, dir: &str, is_windows: bool) -> bool {
+    let dir = dir.trim_end_matches(['/', '\\']);
+    if dir.is_empty() {
+        return false;
+    }
+    let (path, dir) = if is_windows {
+        (path.to_lowercase(), dir.to_lowercase())
+    } else {
+        (path.to_string(), dir.to_string())
+    };
+    path.strip_prefix(&dir)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '\\']))
+}
+
+/// 安装路径所在全局目录所属的运行时（多个 prefix 匹配时取最长的，相同时取靠前的）
+///
+/// 同时比较安装路径解析符号链接后的位置（如 bin/claude → lib/node_modules/...）
+pub fn owning_runtime<'a>(
+    install_path: &str,
+    runtimes: &'a [NodeRuntime],
+    is_windows: bool,
+) -> Option<&'a NodeRuntime> {
+    let resolved = std::fs::canonicalize(install_path)
+        .ok()
+        .map(|path| path.to_string_lossy().to_string());
+    let mut best: Option<(&NodeRuntime, usize)> = None;
+    for runtime in runtimes {
+        let Some(prefix) = runtime.global_prefix.as_deref() else {
+            continue;
+        };
+        let matches = path_within(install_path, prefix, is_windows)
+            || resolved
+                .as_deref()
+                .is_some_and(|path| path_within(path, prefix, is_windows));
+        if matches && best.is_none_or(|(_, len)| prefix.len() > len) {
+            best = Some((runtime, prefix.len()));
+        }
+    }
+    best.map(|(runtime, _)| runtime)
+}
+
+/// npm 可执行文件所属的运行时（与 node 位于同一目录）
+pub fn installer_runtime<'a>(
+    installer_path: &str,
+    runtimes: &'a [NodeRuntime],
+    is_windows: bool,
+) -> Option<&'a NodeRuntime> {
+    let npm_dir = Path::new(installer_path).parent()?.to_string_lossy();
+    runtimes.iter().find(|runtime| {
+        Path::new(&runtime.node_path)
+            .parent()
+            .is_some_and(|dir| same_dir(&dir.to_string_lossy(), &npm_dir, is_windows))
+    })
+}
+
+/// npm 安装的本地实例的安装器属于另一个运行时时，返回提示信息
+pub fn runtime_mismatch(
+    instance: &ToolInstance,
+    runtimes: &[NodeRuntime],
+    is_windows: bool,
+) -> Option<String> {
+    if instance.install_method != Some(InstallMethod::Npm) {
+        return None;
+    }
+    let owner = owning_runtime(instance.install_path.as_deref()?, runtimes, is_windows)?;
+    let installer = installer_runtime(instance.installer_path.as_deref()?, runtimes, is_windows)?;
+    if owner.node_path == installer.node_path {
+        return None;
+    }
+    Some(format!(
+        "安装器 npm 属于 Node.js {}（{}），而 {} 安装在 Node.js {}（{}）的全局目录中，更新可能装到另一个运行时",
+        describe(installer),
+        installer.node_path,
+        instance.tool_name,
+        describe(owner),
+        owner.node_path,
+    ))
+}
+
+/// 运行时的简短描述（如 `v20.11.0 (nvm)`）
+pub fn describe(runtime: &NodeRuntime) -> String {
+    format!(
+        "{} ({})",
+        runtime.version.as_deref().unwrap_or("?"),
+        runtime.source.as_str()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Tool;
+    use crate::utils::MockExecutor;
+    use tempfile::TempDir;
+
+    fn platform() -> PlatformInfo {
+        PlatformInfo {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            is_windows: false,
+            is_macos: false,
+            is_linux: true,
+            is_musl: false,
+        }
+    }
+
+    fn touch(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+
+    fn runtime(node_path: &str, version: &str, prefix: &str) -> NodeRuntime {
+        NodeRuntime {
+            node_path: node_path.to_string(),
+            source: NodeRuntimeSource::Nvm,
+            version: Some(version.to_string()),
+            global_prefix: Some(prefix.to_string()),
+            active: false,
+        }
+    }
+
+    /// 临时目录中的 nvm（两个版本）与系统 node，PATH 指向 nvm 的 v20
+    fn fake_machine() -> (TempDir, NodeSearchDirs, Vec<String>) {
+        let temp = TempDir::new().unwrap();
+        let nvm = temp.path().join(".nvm");
+        let system = temp.path().join("usr/bin");
+        for node in [
+            nvm.join("versions/node/v18.19.0/bin/node"),
+            nvm.join("versions/node/v20.11.0/bin/node"),
+            nvm.join("versions/node/v20.11.0/bin/npm"),
+            system.join("node"),
+            system.join("npm"),
+        ] {
+            touch(&node);
+        }
+        let mut dirs = NodeSearchDirs::default();
+        dirs.add(
+            NodeRuntimeSource::Nvm,
+            nvm.clone(),
+            nvm.join("versions/node"),
+            "bin",
+        );
+        dirs.system = vec![system.clone()];
+        let path_entries = vec![
+            nvm.join("versions/node/v20.11.0/bin")
+                .to_string_lossy()
+                .to_string(),
+            system.to_string_lossy().to_string(),
+        ];
+        (temp, dirs, path_entries)
+    }
+
+    #[test]
+    fn test_candidates_follow_path_then_managers() {
+        let (temp, dirs, path_entries) = fake_machine();
+        let candidates = candidate_node_binaries(&dirs, &path_entries, false);
+        let relative: Vec<(String, NodeRuntimeSource, bool)> = candidates
+            .iter()
+            .map(|c| {
+                let path = c.path.strip_prefix(temp.path()).unwrap();
+                (path.to_string_lossy().to_string(), c.source, c.active)
+            })
+            .collect();
+        assert_eq!(
+            relative,
+            vec![
+                (
+                    ".nvm/versions/node/v20.11.0/bin/node".to_string(),
+                    NodeRuntimeSource::Nvm,
+                    true
+                ),
+                ("usr/bin/node".to_string(), NodeRuntimeSource::System, false),
+                (
+                    ".nvm/versions/node/v18.19.0/bin/node".to_string(),
+                    NodeRuntimeSource::Nvm,
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_records_version_and_prefix() {
+        let (temp, dirs, path_entries) = fake_machine();
+        let root = temp.path().to_string_lossy().to_string();
+        let nvm_bin = format!("{root}/.nvm/versions/node/v20.11.0/bin");
+        let mock = MockExecutor::new()
+            .on_success(format!("{nvm_bin}/node --version"), "v20.11.0\n")
+            .on_success(
+                format!("{nvm_bin}/npm prefix -g"),
+                format!("{root}/.nvm/versions/node/v20.11.0\n"),
+            )
+            .on_success(format!("{root}/usr/bin/node --version"), "v18.19.1\n")
+            .on_success(format!("{root}/usr/bin/npm prefix -g"), "/usr\n");
+
+        let runtimes = scan_node_runtimes(&mock, &platform(), &dirs, &path_entries).await;
+        assert_eq!(runtimes.len(), 3);
+        assert_eq!(runtimes[0].version.as_deref(), Some("v20.11.0"));
+        assert_eq!(
+            runtimes[0].global_prefix,
+            Some(format!("{root}/.nvm/versions/node/v20.11.0"))
+        );
+        assert!(runtimes[0].active);
+        assert_eq!(runtimes[1].global_prefix.as_deref(), Some("/usr"));
+        // v18 没有 npm，且 node 执行失败
+        assert_eq!(runtimes[2].version, None);
+        assert_eq!(runtimes[2].global_prefix, None);
+
+        // npm 执行时该运行时的目录位于 PATH 最前
+        let env = mock.env_of(&format!("{nvm_bin}/npm prefix -g")).unwrap();
+        assert!(env["PATH"].starts_with(&format!("{nvm_bin}:")));
+    }
+
+    #[test]
+    fn test_owning_runtime_matches_prefix() {
+        let runtimes = vec![
+            runtime("/usr/bin/node", "v18.19.1", "/usr"),
+            runtime(
+                "/home/alice/.nvm/versions/node/v20.11.0/bin/node",
+                "v20.11.0",
+                "/home/alice/.nvm/versions/node/v20.11.0",
+            ),
+        ];
+        let owner = owning_runtime(
+            "/home/alice/.nvm/versions/node/v20.11.0/bin/claude",
+            &runtimes,
+            false,
+        )
+        .unwrap();
+        assert_eq!(owner.version.as_deref(), Some("v20.11.0"));
+        assert_eq!(
+            owning_runtime("/usr/bin/codex", &runtimes, false).and_then(|r| r.version.as_deref()),
+            Some("v18.19.1")
+        );
+        // 前缀需在路径分隔处匹配
+        assert!(owning_runtime("/usrlocal/bin/codex", &runtimes, false).is_none());
+
+        let windows = vec![runtime(
+            r"C:\Program Files\nodejs\node.exe",
+            "v20.11.0",
+            r"C:\Users\alice\AppData\Roaming\npm",
+        )];
+        assert!(owning_runtime(
+            r"c:\users\alice\appdata\roaming\npm\claude.cmd",
+            &windows,
+            true
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_runtime_mismatch() {
+        let runtimes = vec![
+            runtime("/usr/bin/node", "v18.19.1", "/usr"),
+            runtime(
+                "/home/alice/.nvm/versions/node/v20.11.0/bin/node",
+                "v20.11.0",
+                "/home/alice/.nvm/versions/node/v20.11.0",
+            ),
+        ];
+        let tool = Tool::claude_code();
+        let mut instance = ToolInstance::from_tool_local(
+            &tool,
+            true,
+            None,
+            Some("/home/alice/.nvm/versions/node/v20.11.0/bin/claude".to_string()),
+        );
+        instance.install_method = Some(InstallMethod::Npm);
+        instance.installer_path = Some("/usr/bin/npm".to_string());
+
+        let warning = runtime_mismatch(&instance, &runtimes, false).unwrap();
+        assert!(warning.contains("v18.19.1 (nvm)"));
+        assert!(warning.contains("v20.11.0 (nvm)"));
+
+        instance.installer_path =
+            Some("/home/alice/.nvm/versions/node/v20.11.0/bin/npm".to_string());
+        assert!(runtime_mismatch(&instance, &runtimes, false).is_none());
+    }
+}
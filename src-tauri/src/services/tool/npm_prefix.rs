@@ -0,0 +1,237 @@
+// npm prefix 检查
+//
+// 常见的支持问题：用户把 npm prefix 设到了不在 PATH 中的目录，npm 安装"成功"但始终找不到工具。
+// npm 安装后检测失败时，读取 `npm config get prefix` / `npm bin -g` 得到全局 bin 目录，
+// 不在实际搜索的 PATH 中时返回该目录与修复用的 shell 片段；
+// 也可把该目录登记为额外检测路径，让 DuckCoding 立即找到工具（不修改用户的 shell 配置）。
+
+use crate::utils::config::{read_global_config, write_global_config};
+use crate::utils::{set_extra_path_entries, CommandRunner, PlatformInfo, PROBE_TIMEOUT};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// npm 全局 bin 目录不在 PATH 中的错误码
+pub const PREFIX_NOT_ON_PATH: &str = "PREFIX_NOT_ON_PATH";
+
+/// npm 全局 bin 目录不在 PATH 中（错误码 `PREFIX_NOT_ON_PATH`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NpmPrefixIssue {
+    /// `npm config get prefix` 的输出
+    pub prefix: String,
+    /// 全局安装的可执行文件所在目录
+    pub bin_dir: String,
+    /// 把目录加入 PATH 的 shell 片段
+    pub shell_snippet: String,
+}
+
+/// 检查 npm 全局 bin 目录是否在 `path_entries` 中，不在时返回问题详情
+///
+/// npm 不可用或读不到 prefix 时返回 None（不是 prefix 的问题）
+pub async fn check_npm_prefix(
+    runner: &dyn CommandRunner,
+    platform: &PlatformInfo,
+    path_entries: &[String],
+    shell: Option<&str>,
+) -> Option<NpmPrefixIssue> {
+    let runner = runner.with_timeout(PROBE_TIMEOUT);
+    // 经 shell 执行，Windows 上才能找到 npm.cmd
+    let result = runner.execute_async("npm config get prefix").await;
+    let prefix = result.stdout.trim();
+    if !result.success || prefix.is_empty() || prefix == "undefined" {
+        return None;
+    }
+
+    // npm 9 起移除了 `npm bin`，失败时按平台约定推算
+    let bin = runner.execute_async("npm bin -g").await;
+    let bin_dir = match bin.stdout.trim() {
+        dir if bin.success && !dir.is_empty() => dir.to_string(),
+        _ => npm_bin_dir(prefix, platform.is_windows),
+    };
+
+    let normalized = platform.merge_path_entries([bin_dir.clone()], false);
+    let on_path = normalized.first().is_some_and(|dir| {
+        platform
+            .merge_path_entries(path_entries.iter().cloned(), false)
+            .iter()
+            .any(|entry| same_dir(entry, dir, platform.is_windows))
+    });
+    if on_path {
+        return None;
+    }
+
+    tracing::warn!(prefix, bin_dir = %bin_dir, "npm 全局 bin 目录不在 PATH 中");
+    Some(NpmPrefixIssue {
+        prefix: prefix.to_string(),
+        shell_snippet: shell_snippet(&bin_dir, platform.is_windows, shell),
+        bin_dir,
+    })
+}
+
+/// prefix 对应的全局 bin 目录：Windows 为 prefix 本身（如 `%APPDATA%\npm`），其余为 `<prefix>/bin`
+pub fn npm_bin_dir(prefix: &str, is_windows: bool) -> String {
+    let prefix = prefix.trim_end_matches(['/', '\\']);
+    if is_windows {
+        prefix.to_string()
+    } else {
+        format!("{prefix}/bin")
+    }
+}
+
+pub(crate) fn same_dir(a: &str, b: &str, is_windows: bool) -> bool {
+    if is_windows {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// 生成把目录加入 PATH 的 shell 片段（Windows 为 PowerShell，Unix 按登录 shell 选择配置文件）
+fn shell_snippet(bin_dir: &str, is_windows: bool, shell: Option<&str>) -> String {
+    if is_windows {
+        let escaped = bin_dir.replace('\'', "''");
+        return format!(
+            "$dir = '{escaped}'\n\
+             $userPath = [Environment]::GetEnvironmentVariable('Path', 'User')\n\
+             [Environment]::SetEnvironmentVariable('Path', \"$userPath;$dir\", 'User')"
+        );
+    }
+
+    let shell_name = shell.and_then(|s| s.rsplit('/').next()).unwrap_or_default();
+    if shell_name == "fish" {
+        return format!("fish_add_path '{}'", bin_dir.replace('\'', "\\'"));
+    }
+    let rc_file = if shell_name == "zsh" {
+        "~/.zshrc"
+    } else {
+        "~/.bashrc"
+    };
+    let escaped = bin_dir.replace('"', "\\\"").replace('\'', r"'\''");
+    format!("echo 'export PATH=\"{escaped}:$PATH\"' >> {rc_file}")
+}
+
+/// 把目录登记为额外检测路径（写入全局配置并立即生效），返回登记后的完整列表
+pub fn register_extra_path(dir: &str) -> Result<Vec<String>> {
+    let dir = dir.trim();
+    if dir.is_empty() {
+        return Err(anyhow!("路径不能为空"));
+    }
+    if !std::path::Path::new(dir).is_dir() {
+        return Err(anyhow!("目录不存在: {}", dir));
+    }
+
+    let mut config = read_global_config()
+        .map_err(|e| anyhow!(e))?
+        .ok_or_else(|| anyhow!("配置文件不存在"))?;
+    let platform = PlatformInfo::current();
+    config.extra_path_entries = platform.merge_path_entries(
+        config
+            .extra_path_entries
+            .iter()
+            .cloned()
+            .chain(std::iter::once(dir.to_string())),
+        false,
+    );
+    write_global_config(&config).map_err(|e| anyhow!(e))?;
+    set_extra_path_entries(config.extra_path_entries.clone());
+    tracing::info!(dir, "已登记额外检测路径");
+    Ok(config.extra_path_entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::MockExecutor;
+
+    fn platform(is_windows: bool) -> PlatformInfo {
+        let os = if is_windows { "windows" } else { "linux" };
+        PlatformInfo {
+            os: os.to_string(),
+            arch: "x86_64".to_string(),
+            is_windows,
+            is_macos: false,
+            is_linux: !is_windows,
+            is_musl: false,
+        }
+    }
+
+    fn entries(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_unix_prefix_not_on_path() {
+        // npm 9+：`npm bin -g` 不存在，按 prefix 推算
+        let mock =
+            MockExecutor::new().on_success("npm config get prefix", "/home/alice/.npm-packages\n");
+        let issue = check_npm_prefix(
+            &mock,
+            &platform(false),
+            &entries(&["/usr/local/bin", "/usr/bin"]),
+            Some("/usr/bin/zsh"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(issue.bin_dir, "/home/alice/.npm-packages/bin");
+        assert_eq!(
+            issue.shell_snippet,
+            r#"echo 'export PATH="/home/alice/.npm-packages/bin:$PATH"' >> ~/.zshrc"#
+        );
+
+        // 已在 PATH 中（忽略结尾分隔符）
+        let on_path = check_npm_prefix(
+            &mock,
+            &platform(false),
+            &entries(&["/home/alice/.npm-packages/bin/", "/usr/bin"]),
+            None,
+        )
+        .await;
+        assert_eq!(on_path, None);
+
+        // `npm bin -g` 可用时以其输出为准
+        let mock = mock.on_success("npm bin -g", "/opt/npm/bin\n");
+        let issue = check_npm_prefix(&mock, &platform(false), &[], Some("/usr/bin/fish"))
+            .await
+            .unwrap();
+        assert_eq!(issue.bin_dir, "/opt/npm/bin");
+        assert_eq!(issue.shell_snippet, "fish_add_path '/opt/npm/bin'");
+    }
+
+    #[tokio::test]
+    async fn test_windows_appdata_npm() {
+        let mock = MockExecutor::new().on_success(
+            "npm config get prefix",
+            "C:\\Users\\alice\\AppData\\Roaming\\npm\r\n",
+        );
+
+        // 默认 %APPDATA%\npm 在 PATH 中（不区分大小写）
+        let on_path = check_npm_prefix(
+            &mock,
+            &platform(true),
+            &entries(&["C:\\Windows", "c:\\users\\alice\\appdata\\roaming\\npm\\"]),
+            None,
+        )
+        .await;
+        assert_eq!(on_path, None);
+
+        let issue = check_npm_prefix(&mock, &platform(true), &entries(&["C:\\Windows"]), None)
+            .await
+            .unwrap();
+        assert_eq!(issue.bin_dir, "C:\\Users\\alice\\AppData\\Roaming\\npm");
+        assert!(issue
+            .shell_snippet
+            .starts_with("$dir = 'C:\\Users\\alice\\AppData\\Roaming\\npm'"));
+        assert!(issue
+            .shell_snippet
+            .contains("SetEnvironmentVariable('Path'"));
+    }
+
+    #[tokio::test]
+    async fn test_npm_unavailable_is_not_prefix_issue() {
+        let mock = MockExecutor::new();
+        assert_eq!(
+            check_npm_prefix(&mock, &platform(false), &[], None).await,
+            None
+        );
+        assert_eq!(npm_bin_dir("/usr/local/", false), "/usr/local/bin");
+    }
+}
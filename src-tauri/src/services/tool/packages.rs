@@ -0,0 +1,91 @@
+// Package Coordinates - 工具包坐标解析
+//
+// 内置坐标（npm 包名、Homebrew 名称等）定义在 `Tool` 上，全局配置的 `package_overrides`
+// 可按工具覆盖其中部分字段（fork、内部镜像）。安装、更新与版本查询统一通过这里取坐标，
+// 不再在各处硬编码包名。
+
+use crate::models::{PackageCoordinates, Tool};
+use crate::utils::config::read_global_config;
+use std::collections::HashMap;
+
+/// 读取全局配置中的包坐标覆盖（读取失败时视为无覆盖）
+pub fn package_overrides() -> HashMap<String, PackageCoordinates> {
+    match read_global_config() {
+        Ok(Some(config)) => config.package_overrides,
+        Ok(None) => HashMap::new(),
+        Err(e) => {
+            tracing::warn!(error = %e, "读取包坐标覆盖失败，使用内置坐标");
+            HashMap::new()
+        }
+    }
+}
+
+/// 获取工具定义，并叠加用户配置的包坐标覆盖
+pub fn resolve_tool(tool_id: &str) -> Option<Tool> {
+    Tool::by_id(tool_id).map(|tool| apply_overrides(tool, &package_overrides()))
+}
+
+/// 所有工具（已叠加包坐标覆盖）
+pub fn resolve_all_tools() -> Vec<Tool> {
+    let overrides = package_overrides();
+    Tool::all()
+        .into_iter()
+        .map(|tool| apply_overrides(tool, &overrides))
+        .collect()
+}
+
+/// 工具的包坐标（未知工具返回空坐标）
+pub fn package_coordinates(tool_id: &str) -> PackageCoordinates {
+    resolve_tool(tool_id)
+        .map(|tool| tool.package_coordinates)
+        .unwrap_or_default()
+}
+
+fn apply_overrides(tool: Tool, overrides: &HashMap<String, PackageCoordinates>) -> Tool {
+    match overrides.get(&tool.id) {
+        Some(over) => tool.with_package_override(over),
+        None => tool,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InstallMethod;
+
+    #[test]
+    fn test_builtin_coordinates() {
+        let codex = Tool::codex();
+        assert_eq!(codex.npm_package(), Some("@openai/codex"));
+        assert_eq!(
+            codex.package_coordinates.for_method(&InstallMethod::Brew),
+            Some("codex")
+        );
+        assert_eq!(
+            codex.package_coordinates.for_method(&InstallMethod::Other),
+            None
+        );
+        assert_eq!(Tool::gemini_cli().package_coordinates.brew, None);
+    }
+
+    #[test]
+    fn test_override_replaces_only_filled_fields() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "codex".to_string(),
+            PackageCoordinates {
+                npm: Some("@acme/codex-fork".to_string()),
+                brew: Some("  ".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let codex = apply_overrides(Tool::codex(), &overrides);
+        assert_eq!(codex.npm_package(), Some("@acme/codex-fork"));
+        // 空白值不覆盖
+        assert_eq!(codex.package_coordinates.brew.as_deref(), Some("codex"));
+
+        let claude = apply_overrides(Tool::claude_code(), &overrides);
+        assert_eq!(claude.npm_package(), Some("@anthropic-ai/claude-code"));
+    }
+}
@@ -0,0 +1,279 @@
+// Path Check - 检测 shell 中实际运行的工具与所选实例是否一致
+//
+// 仪表板上选择的实例（如 ~/.claude/bin 下的官方安装）未必是终端里 `claude` 实际解析到的文件：
+// PATH 中更靠前的目录（如 npm 全局 bin）会抢先命中，导致应用到配置后的行为与预期不符。
+// 这里在登录 shell 的 PATH 与 DuckCoding 的有效 PATH 上逐目录解析命令（不启动子进程），
+// 把规范化后的结果与所选实例的 install_path 比较。WSL / SSH 实例不在本机 PATH 上，跳过检查。
+
+use crate::models::{Tool, ToolInstance, ToolType};
+use crate::utils::{captured_shell_env, CommandExecutor, PlatformInfo};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 解析命令时使用的 PATH 来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathSource {
+    /// 用户登录 shell 的 PATH（未捕获时为当前进程的 PATH）
+    Shell,
+    /// DuckCoding 执行命令时使用的增强 PATH
+    Effective,
+}
+
+/// 修复建议
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PathMismatchFix {
+    /// 切换到与实际运行的文件一致的实例
+    SwitchInstance { instance_id: String },
+    /// 调整 PATH，使所选实例的目录排在 `shadowed_by` 之前
+    ReorderPath {
+        preferred_dir: String,
+        shadowed_by: String,
+    },
+}
+
+/// 所选实例与实际运行的二进制不一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathMismatch {
+    pub tool_id: String,
+    pub instance_id: String,
+    /// 所选实例的安装路径
+    pub selected_path: String,
+    /// 在 PATH 上实际解析到的路径
+    pub resolved_path: String,
+    pub source: PathSource,
+    /// 修复建议（按推荐顺序）
+    pub suggestions: Vec<PathMismatchFix>,
+}
+
+/// 在给定的 PATH 上解析命令（与 which / where 的查找顺序一致）
+pub fn resolve_on_path(command: &str, path: &str, platform: &PlatformInfo) -> Option<PathBuf> {
+    let names: Vec<String> = if platform.is_windows && Path::new(command).extension().is_none() {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+        pathext
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!("{command}{}", ext.to_lowercase()))
+            .collect()
+    } else {
+        vec![command.to_string()]
+    };
+
+    platform
+        .split_path(path)
+        .into_iter()
+        .flat_map(|dir| names.iter().map(move |name| Path::new(&dir).join(name)))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// 规范化路径（解析符号链接）；文件不存在时保留原路径
+fn canonical(path: &Path) -> PathBuf {
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    // Windows 上 canonicalize 返回 `\\?\C:\...`，去掉前缀以便比较
+    match resolved.to_str().and_then(|s| s.strip_prefix(r"\\?\")) {
+        Some(stripped) => PathBuf::from(stripped),
+        None => resolved,
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    let (a, b) = (canonical(a), canonical(b));
+    if cfg!(windows) {
+        a.to_string_lossy()
+            .eq_ignore_ascii_case(&b.to_string_lossy())
+    } else {
+        a == b
+    }
+}
+
+/// 需要检查的 PATH（按优先级）：登录 shell 的 PATH、DuckCoding 的有效 PATH
+pub fn candidate_paths() -> Vec<(PathSource, String)> {
+    let shell_path = captured_shell_env()
+        .and_then(|env| env.get("PATH").cloned())
+        .or_else(|| std::env::var("PATH").ok())
+        .unwrap_or_default();
+    vec![
+        (PathSource::Shell, shell_path),
+        (
+            PathSource::Effective,
+            CommandExecutor::new().effective_path(),
+        ),
+    ]
+}
+
+/// 比较所选实例与 PATH 上实际解析到的命令
+///
+/// 只检查已安装且记录了安装路径的本地实例；命令在所有 PATH 上都解析不到时不视为不一致
+/// （可能是未加入 PATH 的手动实例，启动时 DuckCoding 会直接使用安装路径）。
+pub fn check_instance(
+    selected: &ToolInstance,
+    all_instances: &[ToolInstance],
+    paths: &[(PathSource, String)],
+    platform: &PlatformInfo,
+) -> Option<PathMismatch> {
+    if selected.tool_type != ToolType::Local || !selected.installed {
+        return None;
+    }
+    let selected_path = Path::new(selected.install_path.as_deref()?);
+    let tool = Tool::by_id(&selected.base_id)?;
+    let command = tool.check_command.split_whitespace().next()?;
+
+    let (source, resolved) = paths.iter().find_map(|(source, path)| {
+        resolve_on_path(command, path, platform).map(|resolved| (*source, resolved))
+    })?;
+    if same_file(selected_path, &resolved) {
+        return None;
+    }
+
+    let mut suggestions = Vec::new();
+    if let Some(other) = all_instances.iter().find(|instance| {
+        instance.base_id == selected.base_id
+            && instance.tool_type == ToolType::Local
+            && instance.instance_id != selected.instance_id
+            && instance
+                .install_path
+                .as_deref()
+                .is_some_and(|path| same_file(Path::new(path), &resolved))
+    }) {
+        suggestions.push(PathMismatchFix::SwitchInstance {
+            instance_id: other.instance_id.clone(),
+        });
+    }
+    if let (Some(preferred), Some(shadowed)) = (selected_path.parent(), resolved.parent()) {
+        suggestions.push(PathMismatchFix::ReorderPath {
+            preferred_dir: preferred.to_string_lossy().to_string(),
+            shadowed_by: shadowed.to_string_lossy().to_string(),
+        });
+    }
+
+    Some(PathMismatch {
+        tool_id: selected.base_id.clone(),
+        instance_id: selected.instance_id.clone(),
+        selected_path: selected_path.to_string_lossy().to_string(),
+        resolved_path: resolved.to_string_lossy().to_string(),
+        source,
+        suggestions,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn make_executable(dir: &Path, name: &str) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn local_instance(id: &str, path: &Path) -> ToolInstance {
+        let mut instance = ToolInstance::from_tool_local(
+            &Tool::claude_code(),
+            true,
+            Some("1.0.0".to_string()),
+            Some(path.to_string_lossy().to_string()),
+        );
+        instance.instance_id = id.to_string();
+        instance
+    }
+
+    #[test]
+    fn test_detects_shadowing_binary() {
+        let dir = TempDir::new().unwrap();
+        let official = make_executable(&dir.path().join("official"), "claude");
+        let npm = make_executable(&dir.path().join("npm"), "claude");
+        let platform = PlatformInfo::current();
+        let path = format!(
+            "{}:{}",
+            dir.path().join("npm").display(),
+            dir.path().join("official").display()
+        );
+
+        let selected = local_instance("claude-code-local", &official);
+        let other = local_instance("claude-code-local-npm", &npm);
+        let instances = vec![selected.clone(), other];
+        let mismatch = check_instance(
+            &selected,
+            &instances,
+            &[(PathSource::Shell, path.clone())],
+            &platform,
+        )
+        .expect("npm 版本排在前面，应报告不一致");
+
+        assert_eq!(mismatch.source, PathSource::Shell);
+        assert_eq!(PathBuf::from(&mismatch.resolved_path), npm);
+        assert_eq!(
+            mismatch.suggestions[0],
+            PathMismatchFix::SwitchInstance {
+                instance_id: "claude-code-local-npm".to_string()
+            }
+        );
+        assert!(matches!(
+            mismatch.suggestions[1],
+            PathMismatchFix::ReorderPath { .. }
+        ));
+
+        // 选中 npm 实例时一致
+        let selected = local_instance("claude-code-local-npm", &npm);
+        assert!(check_instance(
+            &selected,
+            &instances,
+            &[(PathSource::Shell, path)],
+            &platform
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_symlink_resolves_to_same_file() {
+        let dir = TempDir::new().unwrap();
+        let real = make_executable(&dir.path().join("lib"), "claude");
+        let bin = dir.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::os::unix::fs::symlink(&real, bin.join("claude")).unwrap();
+
+        let selected = local_instance("claude-code-local", &real);
+        let paths = [(PathSource::Effective, bin.display().to_string())];
+        assert!(check_instance(&selected, &[], &paths, &PlatformInfo::current()).is_none());
+    }
+
+    #[test]
+    fn test_skips_wsl_and_unresolved() {
+        let dir = TempDir::new().unwrap();
+        let official = make_executable(&dir.path().join("official"), "claude");
+        let npm_dir = dir.path().join("npm");
+        make_executable(&npm_dir, "claude");
+        let platform = PlatformInfo::current();
+        let paths = [(PathSource::Shell, npm_dir.display().to_string())];
+
+        let mut wsl = local_instance("claude-code-wsl-ubuntu", &official);
+        wsl.tool_type = ToolType::WSL;
+        assert!(check_instance(&wsl, &[], &paths, &platform).is_none());
+
+        // PATH 上找不到命令时不报告
+        let selected = local_instance("claude-code-local", &official);
+        let empty = [(
+            PathSource::Shell,
+            dir.path().join("none").display().to_string(),
+        )];
+        assert!(check_instance(&selected, &[], &empty, &platform).is_none());
+    }
+}
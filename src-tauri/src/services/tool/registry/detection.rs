@@ -3,7 +3,13 @@
 //! 负责工具的自动检测、持久化和缓存管理
 
 use super::ToolRegistry;
+use crate::core::LocalizedError;
+use crate::models::telemetry::TelemetryEvent;
 use crate::models::{InstallMethod, Tool, ToolInstance, ToolType};
+use crate::services::onboarding_manager::record_tools_detected;
+use crate::services::telemetry::record_event;
+use crate::services::tool::status_cache::TOOL_STATUS_CACHE;
+use crate::utils::{detect_brew_install, PROBE_TIMEOUT};
 use anyhow::Result;
 
 impl ToolRegistry {
@@ -39,6 +45,14 @@ impl ToolRegistry {
         }
         drop(db);
 
+        record_tools_detected(
+            instances
+                .iter()
+                .filter(|instance| instance.installed)
+                .map(|instance| instance.base_id.as_str()),
+        );
+        record_event(TelemetryEvent::LocalToolsDetected);
+
         tracing::info!("本地工具检测并持久化完成");
         Ok(instances)
     }
@@ -53,12 +67,19 @@ impl ToolRegistry {
         tracing::debug!("检测工具: {}", tool_name);
 
         // 使用 Detector 进行检测
-        let installed = detector.is_installed(&self.command_executor).await;
+        let installed = detector.is_installed(&*self.command_executor).await;
 
         let (version, install_path, install_method) = if installed {
-            let version = detector.get_version(&self.command_executor).await;
-            let path = detector.get_install_path(&self.command_executor).await;
-            let method = detector.detect_install_method(&self.command_executor).await;
+            let version = detector.get_version(&*self.command_executor).await;
+            let path = detector.get_install_path(&*self.command_executor).await;
+            let method = detector
+                .detect_install_method(&*self.command_executor)
+                .await;
+            // 安装在 Homebrew Cellar 下（包括 Linuxbrew）时按 Homebrew 安装处理，更新走 brew upgrade
+            let method = match path.as_deref().and_then(detect_brew_install) {
+                Some(_) => Some(InstallMethod::Brew),
+                None => method,
+            };
             (version, path, method)
         } else {
             (None, None, None)
@@ -75,7 +96,12 @@ impl ToolRegistry {
                         "which npm"
                     };
 
-                    match self.command_executor.execute_async(npm_detect_cmd).await {
+                    match self
+                        .command_executor
+                        .with_timeout(PROBE_TIMEOUT)
+                        .execute_async(npm_detect_cmd)
+                        .await
+                    {
                         result if result.success => {
                             let path = result.stdout.lines().next().unwrap_or("").trim();
                             if !path.is_empty() {
@@ -88,17 +114,28 @@ impl ToolRegistry {
                     }
                 }
                 InstallMethod::Brew => {
-                    // 检测 brew 路径（仅 macOS）
-                    match self.command_executor.execute_async("which brew").await {
-                        result if result.success => {
-                            let path = result.stdout.trim();
-                            if !path.is_empty() {
-                                Some(path.to_string())
-                            } else {
-                                None
-                            }
-                        }
-                        _ => None,
+                    // 优先使用工具所在前缀的 brew（macOS 与 Linuxbrew 相同），否则在 PATH 中查找
+                    let prefix_brew = install_path
+                        .as_deref()
+                        .and_then(detect_brew_install)
+                        .filter(|brew| std::path::Path::new(brew).is_file());
+                    match prefix_brew {
+                        Some(brew) => Some(brew),
+                        None => match self
+                            .command_executor
+                            .with_timeout(PROBE_TIMEOUT)
+                            .execute_args_async("which", &["brew"])
+                            .await
+                        {
+                            result if result.success => result
+                                .stdout
+                                .lines()
+                                .next()
+                                .map(str::trim)
+                                .filter(|path| !path.is_empty())
+                                .map(str::to_string),
+                            _ => None,
+                        },
                     }
                 }
                 _ => None,
@@ -146,20 +183,24 @@ impl ToolRegistry {
             install_path,
             installer_path, // 使用检测到的安装器路径
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: true,
             created_at: now,
             updated_at: now,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         }
     }
 
     /// 检测单个本地工具并持久化（公开方法）
     ///
     /// 工作流程：
-    /// 1. 删除该工具的所有现有本地实例（避免重复）
-    /// 2. 执行检测
-    /// 3. 检查路径是否与其他工具冲突
-    /// 4. 如果检测到且无冲突，保存到数据库
+    /// 1. 执行检测
+    /// 2. 检查路径是否与其他工具冲突
+    /// 3. 在同一事务内删除该工具的旧本地实例并保存检测结果（未检测到时只删除）
     ///
     /// 返回：工具实例
     pub async fn detect_and_persist_single_tool(&self, tool_id: &str) -> Result<ToolInstance> {
@@ -169,22 +210,12 @@ impl ToolRegistry {
             .ok_or_else(|| anyhow::anyhow!("未找到工具 {} 的检测器", tool_id))?;
 
         tracing::info!("开始检测单个工具: {}", tool_id);
+        let detector_name = detector.tool_name().to_string();
 
-        // 1. 删除该工具的所有本地实例（避免重复）
-        let db = self.db.read().await;
-        let all_instances = db.get_all_instances()?;
-        for inst in &all_instances {
-            if inst.base_id == tool_id && inst.tool_type == ToolType::Local {
-                tracing::info!("删除旧实例: {}", inst.instance_id);
-                let _ = db.delete_instance(&inst.instance_id);
-            }
-        }
-        drop(db);
-
-        // 2. 执行检测
+        // 1. 执行检测
         let instance = self.detect_single_tool_by_detector(detector).await;
 
-        // 3. 检查路径冲突（如果检测到路径）
+        // 2. 检查路径冲突（如果检测到路径）
         if instance.installed {
             if let Some(detected_path) = &instance.install_path {
                 let db = self.db.read().await;
@@ -197,24 +228,36 @@ impl ToolRegistry {
                         && inst.tool_type == ToolType::Local
                         && inst.base_id != tool_id // 排除同一工具
                 }) {
-                    return Err(anyhow::anyhow!(
-                        "路径冲突：检测到的路径 {} 已被 {} 使用",
-                        detected_path,
-                        existing.tool_name
-                    ));
+                    return Err(LocalizedError::new("CONFLICT", "tool.detect.path_conflict")
+                        .arg("path", detected_path.clone())
+                        .arg("name", existing.tool_name.clone())
+                        .into());
                 }
             }
         }
 
-        // 4. 保存到数据库
-        let db = self.db.read().await;
+        // 3. 替换该工具的本地实例（删除与写入在同一事务内）
+        let detected: &[ToolInstance] = if instance.installed {
+            std::slice::from_ref(&instance)
+        } else {
+            &[]
+        };
+        self.db
+            .write()
+            .await
+            .replace_local_instances(Some(tool_id), detected)?;
         if instance.installed {
-            db.upsert_instance(&instance)?;
             tracing::info!("工具 {} 检测并保存成功", instance.tool_name);
         } else {
             tracing::info!("工具 {} 未检测到", instance.tool_name);
         }
-        drop(db);
+
+        TOOL_STATUS_CACHE.update(crate::models::ToolStatus {
+            id: tool_id.to_string(),
+            name: detector_name,
+            installed: instance.installed,
+            version: instance.version.clone(),
+        });
 
         Ok(instance)
     }
@@ -233,44 +276,24 @@ impl ToolRegistry {
 
         let results = futures_util::future::join_all(futures).await;
 
-        // 获取数据库中现有的本地工具实例
-        let db = self.db.read().await;
-        let existing_local = db.get_local_instances().unwrap_or_default();
-
-        // 收集检测到的工具 ID
-        let detected_ids: std::collections::HashSet<String> = results
-            .iter()
-            .filter(|r| r.installed)
-            .map(|r| r.instance_id.clone())
-            .collect();
-
-        // 删除数据库中存在但本地已不存在的工具
-        for existing in &existing_local {
-            if !detected_ids.contains(&existing.instance_id) {
-                tracing::info!("工具 {} 已不存在，从数据库删除", existing.tool_name);
-                if let Err(e) = db.delete_instance(&existing.instance_id) {
-                    tracing::warn!("删除工具实例失败: {}", e);
-                }
-            }
-        }
-
-        // 更新或插入检测到的工具
-        let mut instances = Vec::new();
-        for instance in results {
-            if instance.installed {
+        let instances: Vec<ToolInstance> = results
+            .into_iter()
+            .filter(|instance| instance.installed)
+            .inspect(|instance| {
                 tracing::info!(
                     "工具 {} 检测完成: installed={}, version={:?}",
                     instance.tool_name,
                     instance.installed,
                     instance.version
                 );
-                if let Err(e) = db.upsert_instance(&instance) {
-                    tracing::warn!("保存工具实例失败: {}", e);
-                }
-                instances.push(instance);
-            }
-        }
-        drop(db);
+            })
+            .collect();
+
+        // 删除本地已不存在的工具并写入检测到的工具（同一事务内完成）
+        self.db
+            .write()
+            .await
+            .replace_local_instances(None, &instances)?;
 
         tracing::info!("本地工具刷新完成，共 {} 个已安装工具", instances.len());
         Ok(instances)
@@ -325,3 +348,119 @@ impl ToolRegistry {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::MockRegistry;
+    use crate::core::LocalizedError;
+    use crate::models::{InstallMethod, ToolType};
+    use crate::utils::MockExecutor;
+    use serial_test::serial;
+
+    fn claude_via_npm() -> MockExecutor {
+        MockExecutor::new()
+            .with_command("claude", "/usr/local/bin/claude")
+            .with_command("npm", "/usr/local/bin/npm")
+            .on_success("claude --version", "2.0.61 (Claude Code)")
+            .on_success(
+                "npm list -g @anthropic-ai/claude-code*",
+                "└── @anthropic-ai/claude-code@2.0.61",
+            )
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_detect_and_persist_single_tool() {
+        let env = MockRegistry::new(claude_via_npm());
+
+        let instance = env
+            .registry
+            .detect_and_persist_single_tool("claude-code")
+            .await
+            .unwrap();
+        assert!(instance.installed);
+        assert_eq!(instance.version.as_deref(), Some("2.0.61"));
+        assert_eq!(
+            instance.install_path.as_deref(),
+            Some("/usr/local/bin/claude")
+        );
+        assert_eq!(instance.install_method, Some(InstallMethod::Npm));
+        assert_eq!(
+            instance.installer_path.as_deref(),
+            Some("/usr/local/bin/npm")
+        );
+
+        let local = env.registry.db.read().await.get_local_instances().unwrap();
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].base_id, "claude-code");
+
+        // 工具被卸载后重新检测：删除本地实例
+        let _ = env.mock.clone().without_command("claude");
+        let instance = env
+            .registry
+            .detect_and_persist_single_tool("claude-code")
+            .await
+            .unwrap();
+        assert!(!instance.installed);
+        assert!(env
+            .registry
+            .db
+            .read()
+            .await
+            .get_local_instances()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_detect_rejects_path_used_by_other_tool() {
+        let mock = claude_via_npm()
+            .with_command("codex", "/usr/local/bin/claude")
+            .on_success("codex --version", "codex-cli 0.65.0");
+        let env = MockRegistry::new(mock);
+
+        env.registry
+            .detect_and_persist_single_tool("claude-code")
+            .await
+            .unwrap();
+        let err = env
+            .registry
+            .detect_and_persist_single_tool("codex")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<LocalizedError>().unwrap().code,
+            "CONFLICT"
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_refresh_local_tools_replaces_instances() {
+        let env = MockRegistry::new(claude_via_npm());
+        env.registry
+            .detect_and_persist_single_tool("claude-code")
+            .await
+            .unwrap();
+
+        // 只剩 CodeX（npm 安装）
+        let _ = env
+            .mock
+            .clone()
+            .without_command("claude")
+            .with_command("codex", "/usr/local/bin/codex")
+            .on_success("codex --version", "codex-cli 0.65.0")
+            .on_success("npm list -g @openai/codex*", "└── @openai/codex@0.65.0");
+
+        let instances = env.registry.refresh_local_tools().await.unwrap();
+        assert_eq!(instances.len(), 1);
+        assert_eq!(instances[0].base_id, "codex");
+        assert_eq!(instances[0].version.as_deref(), Some("0.65.0"));
+
+        let local = env.registry.db.read().await.get_local_instances().unwrap();
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].base_id, "codex");
+        assert_eq!(local[0].tool_type, ToolType::Local);
+    }
+}
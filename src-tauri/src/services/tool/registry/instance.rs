@@ -3,9 +3,15 @@
 //! 负责工具实例的添加、删除操作（Local/WSL/SSH）
 
 use super::ToolRegistry;
-use crate::models::{InstallMethod, SSHConfig, Tool, ToolInstance, ToolType};
-use crate::utils::WSLExecutor;
+use crate::core::LocalizedError;
+use crate::models::dashboard::validate_env_overrides;
+use crate::models::telemetry::TelemetryEvent;
+use crate::models::{restore_masked_env, InstallMethod, SSHConfig, Tool, ToolInstance, ToolType};
+use crate::services::telemetry::record_event;
+use crate::services::trash::unique_id;
+use crate::utils::{wsl_to_windows_path, SSHExecutor, WSLExecutor};
 use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
 
 impl ToolRegistry {
     /// 添加WSL工具实例
@@ -32,8 +38,14 @@ impl ToolRegistry {
             .detect_tool_in_distro(Some(distro_name), cmd_name)
             .await?;
 
+        // 同时记录安装路径的 Windows 表示，供界面「打开文件夹」使用
+        let windows_install_path = match &install_path {
+            Some(path) => wsl_to_windows_path(distro_name, path).await.ok(),
+            None => None,
+        };
+
         // 创建实例
-        let instance = ToolInstance::create_wsl_instance(
+        let mut instance = ToolInstance::create_wsl_instance(
             base_id.to_string(),
             tool.name.clone(),
             distro_name.to_string(),
@@ -41,33 +53,65 @@ impl ToolRegistry {
             version,
             install_path,
         );
+        instance.windows_install_path = windows_install_path;
 
         // 保存到数据库
         let db = self.db.write().await;
         db.add_instance(&instance)?;
         drop(db);
 
+        record_event(TelemetryEvent::WslInstanceAdded);
         Ok(instance)
     }
 
-    /// 添加SSH工具实例（本期仅存储配置，不实现检测）
+    /// 添加SSH工具实例
+    ///
+    /// 先测试连接：认证失败时拒绝保存（除非 force）；
+    /// 其他连接问题不阻止保存，实例记为未安装
     pub async fn add_ssh_instance(
         &self,
         base_id: &str,
         ssh_config: SSHConfig,
+        force: bool,
     ) -> Result<ToolInstance> {
         // 获取工具定义
         let tool =
             Tool::by_id(base_id).ok_or_else(|| anyhow::anyhow!("未知的工具ID: {}", base_id))?;
 
-        // 创建SSH实例（本期不检测，installed设为false）
+        let test = SSHExecutor::new(ssh_config.clone()).test_connection().await;
+        if test.is_auth_failure() && !force {
+            return Err(anyhow::anyhow!(
+                "SSH 认证失败，未保存实例: {}",
+                test.error.unwrap_or_default()
+            ));
+        }
+
+        let (installed, version, install_path) = if test.success {
+            match Self::detect_ssh_tool(&tool, &ssh_config, &Default::default()).await {
+                Ok(detected) => detected,
+                Err(e) => {
+                    tracing::warn!(host = %ssh_config.host, error = %e, "SSH 检测工具失败，实例记为未安装");
+                    (false, None, None)
+                }
+            }
+        } else {
+            tracing::warn!(
+                host = %ssh_config.host,
+                stage = ?test.failed_stage,
+                error = test.error.as_deref().unwrap_or_default(),
+                "SSH 连接测试未通过，实例记为未安装"
+            );
+            (false, None, None)
+        };
+
+        // 创建SSH实例
         let instance = ToolInstance::create_ssh_instance(
             base_id.to_string(),
             tool.name.clone(),
             ssh_config,
-            false, // 本期不检测
-            None,
-            None,
+            installed,
+            version,
+            install_path,
         );
 
         // 检查是否已存在
@@ -78,11 +122,12 @@ impl ToolRegistry {
         db.add_instance(&instance)?;
         drop(db);
 
+        record_event(TelemetryEvent::SshInstanceAdded);
         Ok(instance)
     }
 
-    /// 删除工具实例（仅限SSH类型）
-    pub async fn delete_instance(&self, instance_id: &str) -> Result<()> {
+    /// 删除工具实例（仅限SSH类型），返回被删除的实例
+    pub async fn delete_instance(&self, instance_id: &str) -> Result<ToolInstance> {
         let db = self.db.write().await;
 
         // 获取实例
@@ -92,19 +137,80 @@ impl ToolRegistry {
 
         // 检查是否为SSH类型
         if instance.tool_type != ToolType::SSH {
-            return Err(anyhow::anyhow!("仅允许删除SSH类型的实例"));
+            return Err(LocalizedError::new("VALIDATION", "tool.instance.ssh_only_delete").into());
         }
 
         // 检查是否为内置实例
         if instance.is_builtin {
-            return Err(anyhow::anyhow!("不允许删除内置实例"));
+            return Err(
+                LocalizedError::new("CONFLICT", "tool.instance.builtin_delete_forbidden").into(),
+            );
         }
 
         // 删除
         db.delete_instance(instance_id)?;
         drop(db);
 
-        Ok(())
+        Ok(instance)
+    }
+
+    /// 从回收站恢复实例；ID 已被占用时生成新 ID，返回恢复后的实例
+    pub async fn restore_instance(&self, mut instance: ToolInstance) -> Result<ToolInstance> {
+        let db = self.db.write().await;
+        instance.instance_id = unique_id(&instance.instance_id, |id| db.instance_exists(id))?;
+        instance.updated_at = chrono::Utc::now().timestamp();
+        db.add_instance(&instance)?;
+        Ok(instance)
+    }
+
+    /// 设置实例的标签与备注（空字符串视为清除），返回更新后的实例
+    pub async fn set_instance_label(
+        &self,
+        instance_id: &str,
+        label: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<ToolInstance> {
+        let db = self.db.write().await;
+        let instance = db.get_instance(instance_id)?.ok_or_else(|| {
+            LocalizedError::new("NOT_FOUND", "tool.instance_not_found").arg("id", instance_id)
+        })?;
+        db.set_instance_label(&instance.instance_id, label, notes)?;
+        db.get_instance(&instance.instance_id)?
+            .ok_or_else(|| anyhow::anyhow!("实例不存在: {}", instance.instance_id))
+    }
+
+    /// 设置实例的环境变量覆盖（空表示清除），返回更新后的实例
+    ///
+    /// 前端只拿到敏感变量的脱敏值，原样提交回来时保留原值；变量名两端的空白会被去除。
+    pub async fn set_instance_env(
+        &self,
+        instance_id: &str,
+        env: BTreeMap<String, String>,
+    ) -> Result<ToolInstance> {
+        let db = self.db.write().await;
+        let instance = db.get_instance(instance_id)?.ok_or_else(|| {
+            LocalizedError::new("NOT_FOUND", "tool.instance_not_found").arg("id", instance_id)
+        })?;
+        let env: BTreeMap<String, String> = env
+            .into_iter()
+            .map(|(key, value)| (key.trim().to_string(), value))
+            .collect();
+        validate_env_overrides(&env).map_err(|e| anyhow::anyhow!(e))?;
+
+        let env = restore_masked_env(&instance.env_overrides, env);
+        db.set_instance_env(&instance.instance_id, &env)?;
+        tracing::info!(
+            instance_id = %instance.instance_id,
+            keys = ?env.keys().collect::<Vec<_>>(),
+            "已更新实例环境变量覆盖"
+        );
+        db.get_instance(&instance.instance_id)?
+            .ok_or_else(|| anyhow::anyhow!("实例不存在: {}", instance.instance_id))
+    }
+
+    /// 结构迁移改写过的实例 ID（key: 旧 ID，value: 新 ID）
+    pub async fn instance_id_aliases(&self) -> Result<HashMap<String, String>> {
+        self.db.read().await.instance_id_aliases()
     }
 
     /// 添加手动配置的工具实例
@@ -154,9 +260,10 @@ impl ToolRegistry {
             inst.install_path.as_ref() == Some(&path.to_string())
                 && inst.tool_type == ToolType::Local
         }) {
-            anyhow::bail!(
-                "路径冲突：该路径已被 {} 使用，无法重复添加",
-                existing.tool_name
+            return Err(
+                LocalizedError::new("CONFLICT", "tool.instance.path_conflict")
+                    .arg("name", existing.tool_name.clone())
+                    .into(),
             );
         }
 
@@ -168,11 +275,10 @@ impl ToolRegistry {
             _ => tool_id,
         };
 
-        // 5. 创建 ToolInstance（使用时间戳确保唯一性）
+        // 5. 创建 ToolInstance（ID 由安装路径决定，同一路径不会重复添加）
         let now = chrono::Utc::now().timestamp();
-        let instance_id = format!("{}-local-{}", tool_id, now);
         let instance = ToolInstance {
-            instance_id: instance_id.clone(),
+            instance_id: ToolInstance::manual_local_instance_id(tool_id, path),
             base_id: tool_id.to_string(),
             tool_name: tool_name.to_string(),
             tool_type: ToolType::Local,
@@ -182,10 +288,15 @@ impl ToolRegistry {
             install_path: Some(path.to_string()),
             installer_path,
             wsl_distro: None,
+            windows_install_path: None,
             ssh_config: None,
             is_builtin: false,
             created_at: now,
             updated_at: now,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
         };
 
         // 6. 保存到数据库
@@ -203,6 +314,12 @@ impl ToolRegistry {
 
 #[cfg(test)]
 mod tests {
+    use super::super::test_support::MockRegistry;
+    use crate::models::{Tool, ToolInstance};
+    use crate::utils::MockExecutor;
+    use serial_test::serial;
+    use std::collections::BTreeMap;
+
     #[test]
     fn test_tool_name_mapping() {
         // 这个测试验证 add_tool_instance 中的工具名称映射逻辑
@@ -222,4 +339,58 @@ mod tests {
             assert_eq!(tool_name, expected_name, "工具名称映射应该正确");
         }
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_set_instance_env_keeps_masked_secrets() {
+        let env = MockRegistry::new(MockExecutor::new());
+        let instance = ToolInstance::from_tool_local(&Tool::codex(), true, None, None);
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&instance)
+            .unwrap();
+
+        let updated = env
+            .registry
+            .set_instance_env(
+                "codex-local",
+                BTreeMap::from([
+                    (" CODEX_HOME ".to_string(), "/data/codex".to_string()),
+                    (
+                        "OPENAI_API_KEY".to_string(),
+                        "sk-1234567890abcdef".to_string(),
+                    ),
+                ]),
+            )
+            .await
+            .unwrap();
+        let masked = updated.masked().env_overrides;
+        assert_eq!(masked["CODEX_HOME"], "/data/codex");
+        assert_eq!(masked["OPENAI_API_KEY"], "sk-1...cdef");
+
+        // 提交脱敏值时保留原值
+        let updated = env
+            .registry
+            .set_instance_env("codex-local", masked)
+            .await
+            .unwrap();
+        assert_eq!(
+            updated.env_overrides["OPENAI_API_KEY"],
+            "sk-1234567890abcdef"
+        );
+
+        let invalid = BTreeMap::from([("A=B".to_string(), "1".to_string())]);
+        assert!(env
+            .registry
+            .set_instance_env("codex-local", invalid)
+            .await
+            .is_err());
+        assert!(env
+            .registry
+            .set_instance_env("missing", BTreeMap::new())
+            .await
+            .is_err());
+    }
 }
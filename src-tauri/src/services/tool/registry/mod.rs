@@ -5,10 +5,15 @@
 mod detection;
 mod instance;
 mod query;
+mod ssh;
 mod version_ops;
 
+use crate::models::ToolInstance;
 use crate::services::tool::{DetectorRegistry, ToolInstanceDB};
-use crate::utils::{CommandExecutor, WSLExecutor};
+use crate::utils::config::read_global_config;
+use crate::utils::{
+    capture_shell_env, set_extra_path_entries, CommandExecutor, CommandRunner, WSLExecutor,
+};
 use anyhow::Result;
 use std::sync::Arc;
 use tokio::sync::RwLock; // 改用 RwLock
@@ -23,29 +28,62 @@ pub struct ToolDetectionProgress {
     pub version: Option<String>,
 }
 
+/// 本地工具状态视图（附带需要后台重新检测的工具）
+#[derive(Debug, Clone, Default)]
+pub struct ToolStatusView {
+    pub statuses: Vec<crate::models::ToolStatus>,
+    /// 缓存已过期、需在后台重新检测的工具 ID（stale-while-revalidate）
+    pub stale: Vec<String>,
+}
+
 /// 工具注册表 - 统一管理所有工具实例
 pub struct ToolRegistry {
     pub(super) db: Arc<RwLock<ToolInstanceDB>>, // 改用 RwLock
     pub(super) detector_registry: DetectorRegistry,
-    pub(super) command_executor: CommandExecutor,
+    pub(super) command_executor: Arc<dyn CommandRunner>,
     pub(super) wsl_executor: WSLExecutor,
 }
 
 impl ToolRegistry {
     /// 创建新的工具注册表
     pub async fn new() -> Result<Self> {
+        // 打开时自动执行结构迁移并导入旧版 tools.json
         let db = ToolInstanceDB::new()?;
 
-        // 初始化配置文件（如果不存在）
-        // 注意：迁移逻辑已移到 MigrationManager，这里仅初始化
-        db.init_tables()?;
+        let config = read_global_config().ok().flatten();
+        // 用户登记的额外检测路径（如不在 PATH 中的 npm 全局 bin 目录）
+        if let Some(config) = &config {
+            set_extra_path_entries(config.extra_path_entries.clone());
+        }
+
+        // 捕获登录 shell 环境（每次会话一次，失败时静默回退）
+        let shell_env_enabled = config
+            .map(|config| config.shell_env_capture_enabled)
+            .unwrap_or(true);
+        capture_shell_env(shell_env_enabled).await;
+
+        Ok(Self::with_runner(db, Arc::new(CommandExecutor::new())))
+    }
 
-        Ok(Self {
+    /// 使用指定数据库与命令执行器创建注册表（不读取全局配置、不捕获 shell 环境）
+    ///
+    /// 测试中配合临时目录数据库与 `MockExecutor` 使用
+    pub fn with_runner(db: ToolInstanceDB, runner: Arc<dyn CommandRunner>) -> Self {
+        Self {
             db: Arc::new(RwLock::new(db)), // 改用 RwLock
             detector_registry: DetectorRegistry::new(),
-            command_executor: CommandExecutor::new(),
+            command_executor: runner,
             wsl_executor: WSLExecutor::new(),
-        })
+        }
+    }
+
+    /// 执行实例二进制所用的执行器（注入实例的环境变量覆盖）
+    pub(super) fn runner_for(&self, instance: &ToolInstance) -> Arc<dyn CommandRunner> {
+        if instance.env_overrides.is_empty() {
+            self.command_executor.clone()
+        } else {
+            self.command_executor.envs(&instance.env_overrides)
+        }
     }
 
     /// 检查数据库中是否已有本地工具数据
@@ -54,3 +92,41 @@ impl ToolRegistry {
         db.has_local_tools()
     }
 }
+
+#[cfg(test)]
+mod test_support {
+    use super::ToolRegistry;
+    use crate::services::tool::ToolInstanceDB;
+    use crate::utils::MockExecutor;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// 隔离的测试环境：临时目录中的数据库与配置目录 + MockExecutor
+    ///
+    /// 会设置 DUCKCODING_CONFIG_DIR，使用方需标记 `#[serial]`；drop 时恢复
+    pub(super) struct MockRegistry {
+        pub registry: ToolRegistry,
+        pub mock: MockExecutor,
+        pub dir: TempDir,
+    }
+
+    impl MockRegistry {
+        pub fn new(mock: MockExecutor) -> Self {
+            let dir = TempDir::new().unwrap();
+            std::env::set_var("DUCKCODING_CONFIG_DIR", dir.path().join("config"));
+            let db = ToolInstanceDB::open(&dir.path().join("tools.db")).unwrap();
+            let registry = ToolRegistry::with_runner(db, Arc::new(mock.clone()));
+            Self {
+                registry,
+                mock,
+                dir,
+            }
+        }
+    }
+
+    impl Drop for MockRegistry {
+        fn drop(&mut self) {
+            std::env::remove_var("DUCKCODING_CONFIG_DIR");
+        }
+    }
+}
@@ -2,13 +2,22 @@
 //!
 //! 负责工具状态查询、扫描、验证等辅助操作
 
-use super::ToolRegistry;
-use crate::models::{ToolInstance, ToolType};
+use super::{ToolRegistry, ToolStatusView};
+use crate::models::{
+    EnvVars, InstallMethod, Tool, ToolDefinition, ToolDefinitions, ToolInstance, ToolType,
+    TOOL_DEFINITIONS_VERSION,
+};
+use crate::services::tool::node_env;
+use crate::services::tool::packages::resolve_all_tools;
+use crate::services::tool::path_check::{self, PathMismatch};
+use crate::services::tool::status_cache::{Freshness, TOOL_STATUS_CACHE};
+use crate::utils::config::read_global_config;
 use crate::utils::{
-    parse_version_string, scan_installer_paths, scan_tool_executables, ToolCandidate,
+    parse_version_string, scan_installer_paths, scan_tool_executables, CommandRunner, PlatformInfo,
+    ToolCandidate, PROBE_TIMEOUT,
 };
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 impl ToolRegistry {
     /// 获取所有工具实例（按工具ID分组）- 只从数据库读取
@@ -46,6 +55,36 @@ impl ToolRegistry {
         Ok(grouped)
     }
 
+    /// 为 npm 安装的本地实例标注所属的 Node.js 运行时（按全局 prefix 匹配安装路径）
+    ///
+    /// 没有这类实例时不扫描；扫描结果缓存 5 分钟
+    pub async fn annotate_node_runtimes<'a>(
+        &self,
+        instances: impl IntoIterator<Item = &'a mut ToolInstance>,
+    ) {
+        let mut npm_instances: Vec<&mut ToolInstance> = instances
+            .into_iter()
+            .filter(|instance| {
+                instance.tool_type == ToolType::Local
+                    && instance.install_method == Some(InstallMethod::Npm)
+                    && instance.install_path.is_some()
+            })
+            .collect();
+        if npm_instances.is_empty() {
+            return;
+        }
+
+        let runtimes = node_env::node_runtimes(self.command_executor.as_ref(), false).await;
+        let is_windows = PlatformInfo::current().is_windows;
+        for instance in npm_instances.iter_mut() {
+            instance.node_runtime = instance
+                .install_path
+                .as_deref()
+                .and_then(|path| node_env::owning_runtime(path, &runtimes, is_windows))
+                .cloned();
+        }
+    }
+
     /// 刷新所有工具实例（重新检测本地工具并更新数据库）
     pub async fn refresh_all(&self) -> Result<HashMap<String, Vec<ToolInstance>>> {
         // 重新检测本地工具并保存
@@ -56,54 +95,233 @@ impl ToolRegistry {
     }
 
     /// 获取本地工具的轻量级状态（供 Dashboard 使用）
-    /// 优先从数据库读取，如果数据库为空则执行检测并持久化
-    pub async fn get_local_tool_status(&self) -> Result<Vec<crate::models::ToolStatus>> {
+    ///
+    /// 优先读取状态缓存，缓存缺失时从数据库读取（以实例的更新时间作为获取时间）。
+    /// 超过 TTL 的条目：开启 stale-while-revalidate 时返回旧值并列入 `stale` 交由调用方后台检测，
+    /// 否则立即重新检测该工具。
+    pub async fn get_local_tool_status_view(&self) -> Result<ToolStatusView> {
         tracing::debug!("获取本地工具轻量级状态");
 
-        // 从数据库读取所有实例（不主动检测）
-        let grouped = self.get_all_grouped().await?;
+        let settings = read_global_config()
+            .ok()
+            .flatten()
+            .map(|config| config.tool_status_cache)
+            .unwrap_or_default();
+        let ttl = settings.ttl();
 
-        // 转换为轻量级 ToolStatus
-        let mut statuses = Vec::new();
+        let mut grouped = None;
+        let mut view = ToolStatusView::default();
         let detectors = self.detector_registry.all_detectors();
 
         for detector in detectors {
             let tool_id = detector.tool_id();
-            let tool_name = detector.tool_name();
 
-            if let Some(instances) = grouped.get(tool_id) {
-                // 找到 Local 类型的实例
-                if let Some(local_instance) =
-                    instances.iter().find(|i| i.tool_type == ToolType::Local)
-                {
-                    statuses.push(crate::models::ToolStatus {
-                        id: tool_id.to_string(),
-                        name: tool_name.to_string(),
-                        installed: local_instance.installed,
-                        version: local_instance.version.clone(),
-                    });
-                } else {
-                    // 没有本地实例，返回未安装状态
-                    statuses.push(crate::models::ToolStatus {
+            let (status, freshness) = match TOOL_STATUS_CACHE.lookup(tool_id, ttl) {
+                Some(cached) => cached,
+                None => {
+                    // 缓存缺失：从数据库读取（不主动检测）
+                    if grouped.is_none() {
+                        grouped = Some(self.get_all_grouped().await?);
+                    }
+                    let local =
+                        grouped
+                            .as_ref()
+                            .and_then(|g| g.get(tool_id))
+                            .and_then(|instances| {
+                                instances.iter().find(|i| i.tool_type == ToolType::Local)
+                            });
+                    let status = crate::models::ToolStatus {
                         id: tool_id.to_string(),
-                        name: tool_name.to_string(),
-                        installed: false,
-                        version: None,
-                    });
+                        name: detector.tool_name().to_string(),
+                        installed: local.is_some_and(|i| i.installed),
+                        version: local.and_then(|i| i.version.clone()),
+                    };
+                    // 没有实例时获取时间记为 0，首次查询即触发检测
+                    let fetched_at = local.map(|i| i.updated_at * 1000).unwrap_or(0);
+                    TOOL_STATUS_CACHE.seed(status, fetched_at);
+                    TOOL_STATUS_CACHE
+                        .lookup(tool_id, ttl)
+                        .expect("刚写入的缓存条目应存在")
                 }
-            } else {
-                // 数据库中没有该工具的任何实例
-                statuses.push(crate::models::ToolStatus {
-                    id: tool_id.to_string(),
-                    name: tool_name.to_string(),
-                    installed: false,
-                    version: None,
+            };
+
+            match freshness {
+                Freshness::Fresh => view.statuses.push(status),
+                Freshness::Stale if settings.stale_while_revalidate => {
+                    view.statuses.push(status);
+                    view.stale.push(tool_id.to_string());
+                }
+                Freshness::Stale => view
+                    .statuses
+                    .push(TOOL_STATUS_CACHE.refresh_tool(self, tool_id).await?),
+            }
+        }
+
+        tracing::debug!(
+            count = view.statuses.len(),
+            stale = ?view.stale,
+            "获取本地工具状态完成"
+        );
+        Ok(view)
+    }
+
+    /// 获取所有工具的完整定义（供前端渲染，不读写状态缓存）
+    ///
+    /// 内置工具按 `Tool::all()` 的顺序排在前面，其余 Detector 注册的工具按 ID 排序追加。
+    /// 配置目录叠加本地实例的环境变量覆盖；`detectable` 为此刻在 PATH 中能否找到可执行文件。
+    pub async fn get_tool_definitions(&self) -> Result<ToolDefinitions> {
+        let grouped = self.get_all_grouped().await?;
+        let builtin = resolve_all_tools();
+
+        let mut detectors = self.detector_registry.all_detectors();
+        detectors.sort_by_key(|detector| {
+            let position = builtin
+                .iter()
+                .position(|tool| tool.id == detector.tool_id());
+            (
+                position.unwrap_or(usize::MAX),
+                detector.tool_id().to_string(),
+            )
+        });
+
+        let futures = detectors.into_iter().map(|detector| {
+            let local = grouped
+                .get(detector.tool_id())
+                .and_then(|instances| instances.iter().find(|i| i.tool_type == ToolType::Local));
+            let runner = match local {
+                Some(instance) => self.runner_for(instance),
+                None => self.command_executor.clone(),
+            };
+            let env_overrides = local
+                .map(|instance| instance.env_overrides.clone())
+                .unwrap_or_default();
+            let known = builtin
+                .iter()
+                .find(|tool| tool.id == detector.tool_id())
+                .cloned();
+
+            async move {
+                let is_builtin = known.is_some();
+                let tool = known.unwrap_or_else(|| Tool {
+                    id: detector.tool_id().to_string(),
+                    name: detector.tool_name().to_string(),
+                    group_name: format!("{} 专用分组", detector.tool_name()),
+                    package_coordinates: detector.package_coordinates(),
+                    check_command: detector.check_command().to_string(),
+                    config_dir: detector.config_dir(),
+                    config_file: detector.config_file().to_string(),
+                    env_vars: EnvVars {
+                        api_key: String::new(),
+                        base_url: String::new(),
+                    },
+                    use_proxy_for_version_check: detector.use_proxy_for_version_check(),
                 });
+                let detectable = detector.is_installed(&*runner).await;
+                tool_definition(tool, is_builtin, &env_overrides, detectable)
+            }
+        });
+
+        let tools = futures_util::future::join_all(futures).await;
+        Ok(ToolDefinitions {
+            version: TOOL_DEFINITIONS_VERSION,
+            tools,
+        })
+    }
+
+    /// 获取本地工具的轻量级状态（忽略需要后台刷新的工具列表）
+    pub async fn get_local_tool_status(&self) -> Result<Vec<crate::models::ToolStatus>> {
+        Ok(self.get_local_tool_status_view().await?.statuses)
+    }
+
+    /// 检查所选实例与 PATH 上实际运行的命令是否一致
+    ///
+    /// `selections` 为仪表板记录的实例选择（key: tool_id）；未选择的工具按内置本地实例检查。
+    pub async fn check_path_mismatches(
+        &self,
+        selections: &HashMap<String, String>,
+    ) -> Result<Vec<PathMismatch>> {
+        let grouped = self.get_all_grouped().await?;
+        let paths = path_check::candidate_paths();
+        let platform = PlatformInfo::current();
+
+        let mut mismatches = Vec::new();
+        for (tool_id, instances) in &grouped {
+            let selected_id = selections
+                .get(tool_id)
+                .cloned()
+                .unwrap_or_else(|| format!("{}-local", tool_id));
+            let Some(selected) = instances.iter().find(|i| i.instance_id == selected_id) else {
+                continue;
+            };
+            if let Some(mismatch) =
+                path_check::check_instance(selected, instances, &paths, &platform)
+            {
+                tracing::info!(
+                    tool_id = %tool_id,
+                    selected = %mismatch.selected_path,
+                    resolved = %mismatch.resolved_path,
+                    "所选实例与 PATH 上的命令不一致"
+                );
+                mismatches.push(mismatch);
             }
         }
+        mismatches.sort_by(|a, b| a.tool_id.cmp(&b.tool_id));
+        Ok(mismatches)
+    }
 
-        tracing::debug!("获取本地工具状态完成，共 {} 个工具", statuses.len());
-        Ok(statuses)
+    /// 重新检测单个工具并写回数据库（由 `ToolStatusCache::refresh_tool` 更新缓存）
+    ///
+    /// 已有本地实例时只验证其安装路径并更新版本，保留用户手动指定的路径；
+    /// 没有本地实例时执行完整检测。
+    pub async fn redetect_tool_status(&self, tool_id: &str) -> Result<crate::models::ToolStatus> {
+        let detector = self
+            .detector_registry
+            .get(tool_id)
+            .ok_or_else(|| anyhow::anyhow!("未找到工具 {} 的检测器", tool_id))?;
+
+        let db = self.db.read().await;
+        let local = db
+            .get_local_instances()?
+            .into_iter()
+            .find(|i| i.base_id == tool_id);
+        drop(db);
+
+        let status = match local {
+            Some(mut instance) if instance.install_path.is_some() => {
+                let path = instance.install_path.clone().unwrap_or_default();
+                let runner = self.runner_for(&instance);
+                match Self::validate_tool_path_with(&*runner, &path).await {
+                    Ok(raw) => {
+                        instance.installed = true;
+                        instance.version = Some(parse_version_string(&raw));
+                    }
+                    Err(e) => {
+                        tracing::info!(tool_id, path = %path, error = %e, "工具已不可用");
+                        instance.installed = false;
+                    }
+                }
+                instance.updated_at = chrono::Utc::now().timestamp();
+                self.db.read().await.update_instance(&instance)?;
+
+                crate::models::ToolStatus {
+                    id: tool_id.to_string(),
+                    name: detector.tool_name().to_string(),
+                    installed: instance.installed,
+                    version: instance.version,
+                }
+            }
+            _ => {
+                let instance = self.detect_and_persist_single_tool(tool_id).await?;
+                crate::models::ToolStatus {
+                    id: tool_id.to_string(),
+                    name: detector.tool_name().to_string(),
+                    installed: instance.installed,
+                    version: instance.version,
+                }
+            }
+        };
+
+        Ok(status)
     }
 
     /// 刷新本地工具状态并返回轻量级视图（供刷新按钮使用）
@@ -139,6 +357,10 @@ impl ToolRegistry {
             }
         }
 
+        for status in &statuses {
+            TOOL_STATUS_CACHE.update(status.clone());
+        }
+
         tracing::info!("刷新完成，共 {} 个已安装工具", instances.len());
         Ok(statuses)
     }
@@ -154,13 +376,21 @@ impl ToolRegistry {
     pub async fn scan_tool_candidates(&self, tool_id: &str) -> Result<Vec<ToolCandidate>> {
         // 1. 扫描所有工具路径
         let tool_paths = scan_tool_executables(tool_id);
+        Ok(self.probe_tool_candidates(tool_paths).await)
+    }
+
+    /// 对扫描到的工具路径逐个执行 `--version`，无法获取版本的路径会被跳过
+    async fn probe_tool_candidates(&self, tool_paths: Vec<String>) -> Vec<ToolCandidate> {
         let mut candidates = Vec::new();
 
         // 2. 对每个工具路径：获取版本和安装器
         for tool_path in tool_paths {
             // 获取版本
-            let version_cmd = format!("{} --version", tool_path);
-            let result = self.command_executor.execute_async(&version_cmd).await;
+            let result = self
+                .command_executor
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_args_async(&tool_path, &["--version"])
+                .await;
 
             let version = if result.success {
                 let raw = result.stdout.trim();
@@ -186,7 +416,7 @@ impl ToolRegistry {
             });
         }
 
-        Ok(candidates)
+        candidates
     }
 
     /// 验证用户指定的工具路径是否有效
@@ -198,6 +428,11 @@ impl ToolRegistry {
     /// - Ok(String): 版本号字符串
     /// - Err: 验证失败
     pub async fn validate_tool_path(&self, path: &str) -> Result<String> {
+        Self::validate_tool_path_with(&*self.command_executor, path).await
+    }
+
+    /// 使用指定执行器验证工具路径（重新验证已有实例时注入其环境变量覆盖）
+    async fn validate_tool_path_with(runner: &dyn CommandRunner, path: &str) -> Result<String> {
         use std::path::PathBuf;
 
         let path_buf = PathBuf::from(path);
@@ -213,8 +448,10 @@ impl ToolRegistry {
         }
 
         // 执行 --version 命令
-        let version_cmd = format!("{} --version", path);
-        let result = self.command_executor.execute_async(&version_cmd).await;
+        let result = runner
+            .with_timeout(PROBE_TIMEOUT)
+            .execute_args_async(path, &["--version"])
+            .await;
 
         if !result.success {
             anyhow::bail!("命令执行失败，退出码: {:?}", result.exit_code);
@@ -235,49 +472,235 @@ impl ToolRegistry {
     }
 }
 
+/// 为工具定义补充解析后的路径与当前平台的安装方式
+fn tool_definition(
+    tool: Tool,
+    builtin: bool,
+    env_overrides: &BTreeMap<String, String>,
+    detectable: bool,
+) -> ToolDefinition {
+    let resolved_config_dir = tool.resolved_config_dir(env_overrides);
+    let resolved_config_path = resolved_config_dir.join(&tool.config_file);
+    let install_methods = tool.available_install_methods();
+    let recommended = tool.recommended_install_method();
+    let recommended_install_method = if install_methods.contains(&recommended) {
+        Some(recommended)
+    } else {
+        install_methods.first().cloned()
+    };
+
+    ToolDefinition {
+        tool,
+        builtin,
+        resolved_config_dir,
+        resolved_config_path,
+        install_methods,
+        recommended_install_method,
+        detectable,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::super::test_support::MockRegistry;
+    use crate::utils::{CommandResult, MockExecutor};
+    use serial_test::serial;
 
     #[tokio::test]
+    #[serial]
     async fn test_validate_tool_path_with_invalid_path() {
-        let registry = ToolRegistry::new().await.expect("创建 Registry 失败");
+        let env = MockRegistry::new(MockExecutor::new());
 
         // 测试不存在的路径
-        let result = registry.validate_tool_path("/nonexistent/path").await;
+        let result = env.registry.validate_tool_path("/nonexistent/path").await;
         assert!(result.is_err(), "不存在的路径应该返回错误");
         assert!(
             result.unwrap_err().to_string().contains("路径不存在"),
             "错误信息应包含'路径不存在'"
         );
+
+        // 目录不是文件
+        let dir = env.dir.path().to_string_lossy().to_string();
+        let result = env.registry.validate_tool_path(&dir).await;
+        assert!(result.unwrap_err().to_string().contains("路径不是文件"));
+        assert!(env.mock.calls().is_empty(), "路径无效时不应执行命令");
     }
 
     #[tokio::test]
-    async fn test_has_local_tools_in_db() {
-        let registry = ToolRegistry::new().await.expect("创建 Registry 失败");
+    #[serial]
+    async fn test_validate_tool_path_runs_version() {
+        let env = MockRegistry::new(MockExecutor::new());
+        let tool = env.dir.path().join("claude");
+        std::fs::write(&tool, "").unwrap();
+        let tool = tool.to_string_lossy().to_string();
+
+        // 命令失败
+        let result = env.registry.validate_tool_path(&tool).await;
+        assert!(result.unwrap_err().to_string().contains("命令执行失败"));
+
+        // 输出中没有版本号
+        let _ = env
+            .mock
+            .clone()
+            .on_success(format!("{tool} --version"), "usage: claude");
+        assert!(env.registry.validate_tool_path(&tool).await.is_err());
+
+        let _ = env
+            .mock
+            .clone()
+            .on_success(format!("{tool} --version"), "2.0.61 (Claude Code)");
+        let version = env.registry.validate_tool_path(&tool).await.unwrap();
+        assert!(version.contains("2.0.61"));
+    }
 
-        // 这个测试依赖于实际数据库状态，仅验证方法可调用
-        let result = registry.has_local_tools_in_db().await;
-        assert!(result.is_ok(), "has_local_tools_in_db 应该可以执行");
+    #[tokio::test]
+    #[serial]
+    async fn test_redetect_injects_instance_env() {
+        use crate::models::{Tool, ToolInstance};
+
+        let env = MockRegistry::new(MockExecutor::new());
+        let tool = env.dir.path().join("claude");
+        std::fs::write(&tool, "").unwrap();
+        let tool = tool.to_string_lossy().to_string();
+        let _ = env
+            .mock
+            .clone()
+            .on_success(format!("{tool} --version"), "2.0.62 (Claude Code)");
+
+        let mut instance = ToolInstance::from_tool_local(
+            &Tool::claude_code(),
+            true,
+            Some("2.0.61".to_string()),
+            Some(tool.clone()),
+        );
+        instance.env_overrides =
+            [("CLAUDE_CONFIG_DIR".to_string(), "/data/claude".to_string())].into();
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&instance)
+            .unwrap();
+
+        let status = env
+            .registry
+            .redetect_tool_status("claude-code")
+            .await
+            .unwrap();
+        assert_eq!(status.version.as_deref(), Some("2.0.62"));
+        assert_eq!(
+            env.mock.env_of(&format!("{tool} --version")),
+            Some(instance.env_overrides)
+        );
     }
 
     #[tokio::test]
-    async fn test_get_local_tool_status() {
-        let registry = ToolRegistry::new().await.expect("创建 Registry 失败");
-
-        // 测试获取本地工具状态
-        let result = registry.get_local_tool_status().await;
-        assert!(result.is_ok(), "get_local_tool_status 应该可以执行");
-
-        // 验证返回的工具列表包含已知工具
-        if let Ok(statuses) = result {
-            let tool_ids: Vec<String> = statuses.iter().map(|s| s.id.clone()).collect();
-            assert!(
-                tool_ids.contains(&"claude-code".to_string())
-                    || tool_ids.contains(&"codex".to_string())
-                    || tool_ids.contains(&"gemini-cli".to_string()),
-                "应该包含至少一个已知工具"
+    #[serial]
+    async fn test_probe_tool_candidates_skips_failed_paths() {
+        let mock = MockExecutor::new()
+            .on_success("/opt/a/codex --version", "codex-cli 0.65.0")
+            .on(
+                "/opt/b/codex --version",
+                CommandResult::failure(1, "broken"),
             );
-        }
+        let env = MockRegistry::new(mock);
+
+        let candidates = env
+            .registry
+            .probe_tool_candidates(vec!["/opt/a/codex".to_string(), "/opt/b/codex".to_string()])
+            .await;
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].tool_path, "/opt/a/codex");
+        assert_eq!(candidates[0].version, "0.65.0");
+        assert!(env.mock.called("/opt/b/codex --version"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_has_local_tools_in_db() {
+        let mock = MockExecutor::new()
+            .with_command("gemini", "/usr/local/bin/gemini")
+            .on_success("gemini --version", "0.13.0");
+        let env = MockRegistry::new(mock);
+
+        assert!(!env.registry.has_local_tools_in_db().await.unwrap());
+        env.registry
+            .detect_and_persist_single_tool("gemini-cli")
+            .await
+            .unwrap();
+        assert!(env.registry.has_local_tools_in_db().await.unwrap());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_get_local_tool_status() {
+        let env = MockRegistry::new(MockExecutor::new());
+
+        let statuses = env.registry.get_local_tool_status().await.unwrap();
+        let mut tool_ids: Vec<&str> = statuses.iter().map(|s| s.id.as_str()).collect();
+        tool_ids.sort();
+        assert_eq!(tool_ids, vec!["claude-code", "codex", "gemini-cli"]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_get_tool_definitions() {
+        use crate::models::{InstallMethod, Tool, ToolInstance, TOOL_DEFINITIONS_VERSION};
+
+        let env = MockRegistry::new(MockExecutor::new().with_command("claude", "/usr/bin/claude"));
+        let mut instance = ToolInstance::from_tool_local(
+            &Tool::claude_code(),
+            true,
+            Some("2.0.61".to_string()),
+            Some("/usr/bin/claude".to_string()),
+        );
+        instance.env_overrides =
+            [("CLAUDE_CONFIG_DIR".to_string(), "/data/claude".to_string())].into();
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&instance)
+            .unwrap();
+
+        let definitions = env.registry.get_tool_definitions().await.unwrap();
+        assert_eq!(definitions.version, TOOL_DEFINITIONS_VERSION);
+        let ids: Vec<&str> = definitions
+            .tools
+            .iter()
+            .map(|d| d.tool.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["claude-code", "codex", "gemini-cli"]);
+
+        let claude = &definitions.tools[0];
+        assert!(claude.builtin);
+        assert!(claude.detectable);
+        assert_eq!(
+            claude.resolved_config_path,
+            std::path::Path::new("/data/claude").join("settings.json")
+        );
+        assert_eq!(
+            claude.recommended_install_method,
+            Some(InstallMethod::Official)
+        );
+        assert!(!definitions.tools[1].detectable);
+        // 检测时注入本地实例的环境变量覆盖
+        let which = if cfg!(windows) { "where" } else { "which" };
+        assert_eq!(
+            env.mock.env_of(&format!("{which} claude")),
+            Some(instance.env_overrides)
+        );
+    }
+
+    #[test]
+    fn test_tool_definition_for_unknown_tool() {
+        use crate::models::Tool;
+
+        let mut tool = Tool::gemini_cli();
+        tool.id = "aider".to_string();
+        let definition = super::tool_definition(tool, false, &Default::default(), false);
+        assert!(definition.install_methods.is_empty());
+        assert_eq!(definition.recommended_install_method, None);
+        assert!(definition.resolved_config_path.ends_with("settings.json"));
     }
 }
@@ -0,0 +1,101 @@
+//! SSH 远程实例模块
+//!
+//! 通过 SSHExecutor 检测、安装远程工具，以及检查远程实例的健康状态
+
+use super::ToolRegistry;
+use crate::models::{SSHConfig, Tool, ToolInstance, ToolType};
+use crate::services::tool::resolve_tool;
+use crate::utils::wsl_executor::sh_quote;
+use crate::utils::{ssh_config_for_host, SSHExecutor, SshError, INSTALL_TIMEOUT};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+impl ToolRegistry {
+    /// 在远程主机上检测工具：(是否安装, 版本, 路径)，`env` 在执行工具时注入
+    pub(super) async fn detect_ssh_tool(
+        tool: &Tool,
+        ssh_config: &SSHConfig,
+        env: &BTreeMap<String, String>,
+    ) -> Result<(bool, Option<String>, Option<String>), SshError> {
+        let cmd_name = tool.check_command.split_whitespace().next().unwrap_or("");
+        SSHExecutor::new(ssh_config.clone())
+            .detect_tool(cmd_name, env)
+            .await
+    }
+
+    /// 按 ~/.ssh/config 中的主机别名创建 SSH 实例
+    pub async fn create_ssh_instance_from_host(
+        &self,
+        alias: &str,
+        tool_id: &str,
+        force: bool,
+    ) -> Result<ToolInstance> {
+        let ssh_config = ssh_config_for_host(alias)?;
+        self.add_ssh_instance(tool_id, ssh_config, force).await
+    }
+
+    /// 获取 SSH 实例及其工具定义
+    async fn get_ssh_instance(&self, instance_id: &str) -> Result<(ToolInstance, SSHConfig, Tool)> {
+        let instance = self
+            .db
+            .read()
+            .await
+            .get_instance(instance_id)?
+            .ok_or_else(|| anyhow::anyhow!("实例不存在: {}", instance_id))?;
+
+        if instance.tool_type != ToolType::SSH {
+            return Err(anyhow::anyhow!("实例 {} 不是SSH类型", instance_id));
+        }
+        let ssh_config = instance
+            .ssh_config
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("实例 {} 缺少SSH配置", instance_id))?;
+        let tool = resolve_tool(&instance.base_id)
+            .ok_or_else(|| anyhow::anyhow!("未知的工具ID: {}", instance.base_id))?;
+
+        Ok((instance, ssh_config, tool))
+    }
+
+    /// 重新检测 SSH 实例（健康检查），并更新数据库
+    pub async fn refresh_ssh_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        let (mut instance, ssh_config, tool) = self.get_ssh_instance(instance_id).await?;
+
+        let (installed, version, install_path) =
+            Self::detect_ssh_tool(&tool, &ssh_config, &instance.env_overrides)
+                .await
+                .map_err(|e| anyhow::anyhow!(e))?;
+
+        instance.installed = installed;
+        instance.version = version;
+        instance.install_path = install_path;
+        instance.updated_at = chrono::Utc::now().timestamp();
+
+        self.db.write().await.update_instance(&instance)?;
+        Ok(instance)
+    }
+
+    /// 在 SSH 实例的远程主机上通过 npm 安装工具，完成后重新检测
+    pub async fn install_ssh_instance(&self, instance_id: &str) -> Result<ToolInstance> {
+        let (_, ssh_config, tool) = self.get_ssh_instance(instance_id).await?;
+
+        let package = tool
+            .npm_package()
+            .ok_or_else(|| anyhow::anyhow!("{} 不支持 npm 安装", tool.name))?;
+        let command = format!(
+            "npm install -g {} --registry https://registry.npmmirror.com",
+            sh_quote(&format!("{package}@latest"))
+        );
+        tracing::info!(host = %ssh_config.host, tool = %tool.id, "在远程主机上安装工具");
+
+        let result = SSHExecutor::new(ssh_config)
+            .with_timeout(INSTALL_TIMEOUT)
+            .execute(&command)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if !result.success {
+            anyhow::bail!("❌ 远程 npm 安装失败\n\n{}", result.failure_detail());
+        }
+
+        self.refresh_ssh_instance(instance_id).await
+    }
+}
@@ -3,9 +3,14 @@
 //! 负责工具版本的检查、更新、刷新操作
 
 use super::ToolRegistry;
+use crate::models::dashboard::ActivityKind;
+use crate::models::telemetry::TelemetryEvent;
 use crate::models::{InstallMethod, Tool, ToolType, UpdateResult};
-use crate::services::{tool::InstallerService, VersionService};
-use crate::utils::parse_version_string;
+use crate::services::dashboard_manager::record_activity;
+use crate::services::telemetry::record_event;
+use crate::services::tool::{node_env, resolve_tool, InstallerService};
+use crate::services::VersionService;
+use crate::utils::{parse_version_string, PlatformInfo, PROBE_TIMEOUT};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -30,13 +35,27 @@ impl ToolRegistry {
             .find(|inst| inst.instance_id == instance_id && inst.tool_type == ToolType::Local)
             .ok_or_else(|| anyhow::anyhow!("未找到实例: {}", instance_id))?;
 
-        // 2. 使用 InstallerService 执行更新
-        let installer = InstallerService::new();
-        let result = installer
+        // 2. 安装器 npm 属于另一个 Node.js 运行时时提示（新版本会装进另一棵全局目录）
+        let runtime_warning = if instance.install_method == Some(InstallMethod::Npm) {
+            let runtimes = node_env::node_runtimes(self.command_executor.as_ref(), false).await;
+            node_env::runtime_mismatch(instance, &runtimes, PlatformInfo::current().is_windows)
+        } else {
+            None
+        };
+        if let Some(warning) = &runtime_warning {
+            tracing::warn!(instance_id, "{}", warning);
+        }
+
+        // 3. 使用 InstallerService 执行更新
+        let installer = InstallerService::with_runner(self.command_executor.clone());
+        let mut result = installer
             .update_instance_by_installer(instance, force)
             .await?;
+        if let Some(warning) = runtime_warning {
+            result.message = format!("{}；注意：{}", result.message, warning);
+        }
 
-        // 3. 如果更新成功，更新数据库中的版本号
+        // 4. 如果更新成功，更新数据库中的版本号
         if result.success {
             if let Some(ref new_version) = result.current_version {
                 let db = self.db.write().await;
@@ -48,6 +67,12 @@ impl ToolRegistry {
                     tracing::warn!("更新数据库版本失败: {}", e);
                 }
             }
+            record_activity(
+                ActivityKind::ToolUpdated,
+                instance_id,
+                result.current_version.clone(),
+            );
+            record_event(TelemetryEvent::ToolUpdated);
         }
 
         Ok(result)
@@ -74,16 +99,19 @@ impl ToolRegistry {
 
         // 2. 使用 install_path 执行 --version 获取当前版本
         let current_version = if let Some(path) = &instance.install_path {
-            let version_cmd = format!("{} --version", path);
-            tracing::info!("实例 {} 版本检查命令: {:?}", instance_id, version_cmd);
+            tracing::info!("实例 {} 版本检查: {:?} --version", instance_id, path);
 
-            let result = self.command_executor.execute_async(&version_cmd).await;
+            let result = self
+                .runner_for(instance)
+                .with_timeout(PROBE_TIMEOUT)
+                .execute_args_async(path, &["--version"])
+                .await;
 
             if result.success {
                 let raw_version = result.stdout.trim();
                 Some(parse_version_string(raw_version))
             } else {
-                anyhow::bail!("版本号获取错误：无法执行命令 {}", version_cmd);
+                anyhow::bail!("版本号获取错误：无法执行命令 {:?} --version", path);
             }
         } else {
             // 没有路径，使用数据库中的版本
@@ -92,14 +120,14 @@ impl ToolRegistry {
 
         // 3. 检查远程最新版本
         let tool_id = &instance.base_id;
-        let version_service = VersionService::new();
+        let version_service = VersionService::new().with_runner(self.command_executor.clone());
         let version_info = version_service
             .check_version(
                 &Tool::by_id(tool_id).ok_or_else(|| anyhow::anyhow!("未知工具: {}", tool_id))?,
             )
             .await;
 
-        let update_result = match version_info {
+        let mut update_result = match version_info {
             Ok(info) => UpdateResult {
                 success: true,
                 message: "检查完成".to_string(),
@@ -109,6 +137,9 @@ impl ToolRegistry {
                 mirror_version: info.mirror_version,
                 mirror_is_stale: Some(info.mirror_is_stale),
                 tool_id: Some(tool_id.clone()),
+                skipped: false,
+                download_size: None,
+                duration_ms: None,
             },
             Err(e) => UpdateResult {
                 success: true,
@@ -119,10 +150,28 @@ impl ToolRegistry {
                 mirror_version: None,
                 mirror_is_stale: None,
                 tool_id: Some(tool_id.clone()),
+                skipped: false,
+                download_size: None,
+                duration_ms: None,
             },
         };
 
-        // 4. 如果当前版本有变化，更新数据库
+        // 4. 有更新时附带下载大小（仅 npm 安装可获取，结果按版本缓存）
+        if update_result.has_update && instance.install_method == Some(InstallMethod::Npm) {
+            let target = update_result
+                .mirror_version
+                .as_deref()
+                .or(update_result.latest_version.as_deref());
+            let tool = resolve_tool(tool_id);
+            if let (Some(package), Some(target)) =
+                (tool.as_ref().and_then(Tool::npm_package), target)
+            {
+                update_result.download_size =
+                    VersionService::npm_download_size(package, target).await;
+            }
+        }
+
+        // 5. 如果当前版本有变化，更新数据库
         if current_version != instance.version {
             let db = self.db.write().await;
             let mut updated_instance = instance.clone();
@@ -162,10 +211,13 @@ impl ToolRegistry {
         {
             // 使用 install_path 检测版本
             let new_version = if let Some(path) = &instance.install_path {
-                let version_cmd = format!("{} --version", path);
-                tracing::info!("工具 {} 版本检查: {:?}", instance.tool_name, version_cmd);
+                tracing::info!("工具 {} 版本检查: {:?} --version", instance.tool_name, path);
 
-                let result = self.command_executor.execute_async(&version_cmd).await;
+                let result = self
+                    .runner_for(instance)
+                    .with_timeout(PROBE_TIMEOUT)
+                    .execute_args_async(path, &["--version"])
+                    .await;
 
                 if result.success {
                     let raw_version = result.stdout.trim();
@@ -220,7 +272,10 @@ impl ToolRegistry {
         let detectors = self.detector_registry.all_detectors();
         for detector in detectors {
             let tool_id = detector.tool_id();
-            if let Some(method) = detector.detect_install_method(&self.command_executor).await {
+            if let Some(method) = detector
+                .detect_install_method(&*self.command_executor)
+                .await
+            {
                 methods.insert(tool_id.to_string(), method);
             }
         }
@@ -228,3 +283,162 @@ impl ToolRegistry {
         Ok(methods)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_support::MockRegistry;
+    use crate::models::{InstallMethod, ToolInstance, ToolType};
+    use crate::utils::{CommandResult, MockExecutor};
+    use serial_test::serial;
+
+    fn brew_instance() -> ToolInstance {
+        ToolInstance {
+            instance_id: "codex-local-test".to_string(),
+            base_id: "codex".to_string(),
+            tool_name: "CodeX".to_string(),
+            tool_type: ToolType::Local,
+            install_method: Some(InstallMethod::Brew),
+            installed: true,
+            version: Some("0.64.0".to_string()),
+            install_path: Some("/opt/homebrew/bin/codex".to_string()),
+            installer_path: Some("/opt/homebrew/bin/brew".to_string()),
+            wsl_distro: None,
+            windows_install_path: None,
+            ssh_config: None,
+            is_builtin: false,
+            created_at: 0,
+            updated_at: 0,
+            label: None,
+            notes: None,
+            env_overrides: Default::default(),
+            node_runtime: None,
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_update_instance_records_new_version() {
+        let mock = MockExecutor::new()
+            .on_success(
+                "/opt/homebrew/bin/brew upgrade codex",
+                "==> Upgrading codex",
+            )
+            .on_success("/opt/homebrew/bin/codex --version", "codex-cli 0.65.0");
+        let env = MockRegistry::new(mock);
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&brew_instance())
+            .unwrap();
+
+        let result = env
+            .registry
+            .update_instance("codex-local-test", false)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.current_version.as_deref(), Some("0.65.0"));
+        assert!(result.duration_ms.is_some());
+
+        let stored = env
+            .registry
+            .db
+            .read()
+            .await
+            .get_instance("codex-local-test")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version.as_deref(), Some("0.65.0"));
+        assert_eq!(
+            env.mock.calls(),
+            vec![
+                "/opt/homebrew/bin/brew upgrade codex",
+                "/opt/homebrew/bin/codex --version"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_update_instance_failure_keeps_version() {
+        let mock = MockExecutor::new().on(
+            "/opt/homebrew/bin/brew upgrade codex",
+            CommandResult::failure(1, "Error: codex not installed"),
+        );
+        let env = MockRegistry::new(mock);
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&brew_instance())
+            .unwrap();
+
+        let err = env
+            .registry
+            .update_instance("codex-local-test", false)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("codex not installed"));
+
+        let stored = env
+            .registry
+            .db
+            .read()
+            .await
+            .get_instance("codex-local-test")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.version.as_deref(), Some("0.64.0"));
+
+        assert!(env
+            .registry
+            .update_instance("missing", false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_refresh_all_tool_versions() {
+        let mock =
+            MockExecutor::new().on_success("/opt/homebrew/bin/codex --version", "codex-cli 0.66.0");
+        let env = MockRegistry::new(mock);
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&brew_instance())
+            .unwrap();
+
+        let statuses = env.registry.refresh_all_tool_versions().await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].version.as_deref(), Some("0.66.0"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_version_checks_inject_instance_env() {
+        let mock =
+            MockExecutor::new().on_success("/opt/homebrew/bin/codex --version", "codex-cli 0.66.0");
+        let env = MockRegistry::new(mock);
+        let mut instance = brew_instance();
+        instance.env_overrides = [(
+            "NODE_OPTIONS".to_string(),
+            "--max-old-space-size=8192".to_string(),
+        )]
+        .into();
+        env.registry
+            .db
+            .read()
+            .await
+            .add_instance(&instance)
+            .unwrap();
+
+        env.registry.refresh_all_tool_versions().await.unwrap();
+        assert_eq!(
+            env.mock.env_of("/opt/homebrew/bin/codex --version"),
+            Some(instance.env_overrides)
+        );
+    }
+}
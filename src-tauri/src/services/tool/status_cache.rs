@@ -0,0 +1,575 @@
+// Tool Status Cache - 工具状态缓存
+//
+// 在工具实例数据库之上缓存各工具的轻量级状态，并记录获取时间：
+// - 超过 TTL 的条目视为过期，仅重新检测该工具
+// - 开启 stale-while-revalidate 时先返回旧值，后台检测完成后如结果变化则广播事件
+// - 持久化到 tool_status_cache.json，冷启动时直接使用上次的检测结果（仍受 TTL 约束）
+// - 同一工具的并发检测合并为一次（single-flight）：先到的调用方执行检测，其余调用方等待同一结果；
+//   检测进行中的工具通过 `tool-detection-changed` 事件通知前端
+
+use crate::data::{DataManager, Durability};
+use crate::models::ToolStatus;
+use crate::services::tool::ToolRegistry;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+/// 工具状态变化事件名（前端监听）
+pub const TOOL_STATUS_CHANGED_EVENT: &str = "tool-status-changed";
+
+/// 工具检测开始 / 结束事件名（前端据此显示每个工具的检测中状态）
+pub const TOOL_DETECTION_CHANGED_EVENT: &str = "tool-detection-changed";
+
+/// 全局工具状态缓存
+pub static TOOL_STATUS_CACHE: Lazy<ToolStatusCache> = Lazy::new(ToolStatusCache::new);
+
+/// 缓存条目的新鲜度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    Fresh,
+    Stale,
+}
+
+/// 缓存条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedToolStatus {
+    status: ToolStatus,
+    /// 获取时间（Unix 毫秒）
+    fetched_at: i64,
+}
+
+/// 检测结果（错误转为字符串，便于在等待同一检测的调用方之间共享）
+type DetectionResult = Result<ToolStatus, String>;
+
+/// 检测进行状态变化
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolDetectionState {
+    pub tool_id: String,
+    pub detecting: bool,
+}
+
+/// 缓存快照：已有的状态（不论是否过期）与正在检测的工具
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStatusSnapshot {
+    pub statuses: Vec<ToolStatus>,
+    pub detecting: Vec<String>,
+}
+
+/// 持久化文件格式
+#[derive(Debug, Serialize, Deserialize)]
+struct ToolStatusCacheFile {
+    /// 写入时的应用版本，版本变化后整体失效（检测逻辑可能已改变）
+    app_version: String,
+    entries: HashMap<String, CachedToolStatus>,
+}
+
+/// 工具状态缓存
+pub struct ToolStatusCache {
+    entries: RwLock<HashMap<String, CachedToolStatus>>,
+    /// 正在后台重新检测的工具（避免重复检测）
+    refreshing: Mutex<HashSet<String>>,
+    /// 正在进行的检测；后到的调用方订阅其结果而不是再检测一次
+    in_flight: Mutex<HashMap<String, watch::Receiver<Option<DetectionResult>>>>,
+    changes: broadcast::Sender<ToolStatus>,
+    detection_changes: broadcast::Sender<ToolDetectionState>,
+    /// 持久化文件路径（未启用持久化时为 None）
+    persist_path: RwLock<Option<PathBuf>>,
+}
+
+impl ToolStatusCache {
+    pub fn new() -> Self {
+        let (changes, _) = broadcast::channel(16);
+        let (detection_changes, _) = broadcast::channel(16);
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            refreshing: Mutex::new(HashSet::new()),
+            in_flight: Mutex::new(HashMap::new()),
+            changes,
+            detection_changes,
+            persist_path: RwLock::new(None),
+        }
+    }
+
+    /// 默认持久化文件路径
+    pub fn default_path() -> anyhow::Result<PathBuf> {
+        Ok(crate::utils::config::config_dir()
+            .map_err(|e| anyhow::anyhow!(e))?
+            .join("tool_status_cache.json"))
+    }
+
+    /// 启用持久化：加载上次保存的条目，之后每次变更都写回该文件
+    ///
+    /// 应用版本不一致或文件损坏时丢弃已保存的内容。
+    pub fn enable_persistence(&self, path: PathBuf) {
+        self.load_from(&path, env!("CARGO_PKG_VERSION"));
+        *self.persist_path.write().unwrap() = Some(path);
+    }
+
+    fn load_from(&self, path: &Path, app_version: &str) {
+        if !path.exists() {
+            return;
+        }
+        let file = DataManager::new()
+            .json_uncached()
+            .read(path)
+            .ok()
+            .and_then(|value| serde_json::from_value::<ToolStatusCacheFile>(value).ok());
+
+        match file {
+            Some(file) if file.app_version == app_version => {
+                let mut entries = self.entries.write().unwrap();
+                for (tool_id, entry) in file.entries {
+                    // 内存中已有的条目（启动后检测到的结果）优先
+                    entries.entry(tool_id).or_insert(entry);
+                }
+                tracing::debug!(count = entries.len(), "已恢复工具状态缓存");
+            }
+            Some(_) => {
+                tracing::debug!("应用版本已变化，丢弃工具状态缓存");
+                let _ = std::fs::remove_file(path);
+            }
+            None => {
+                tracing::debug!(path = %path.display(), "工具状态缓存文件损坏，已丢弃");
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    /// 写回持久化文件（未启用持久化时跳过）
+    fn persist(&self) {
+        let Some(path) = self.persist_path.read().unwrap().clone() else {
+            return;
+        };
+        let file = ToolStatusCacheFile {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: self.entries.read().unwrap().clone(),
+        };
+        // 缓存丢失只会多一次检测，跳过 fsync
+        let result = serde_json::to_value(&file)
+            .map_err(anyhow::Error::from)
+            .and_then(|value| {
+                DataManager::new()
+                    .with_durability(Durability::Relaxed)
+                    .json_uncached()
+                    .write(&path, &value)
+                    .map_err(anyhow::Error::from)
+            });
+        if let Err(e) = result {
+            tracing::warn!(error = ?e, "持久化工具状态缓存失败");
+        }
+    }
+
+    /// 订阅状态变化（仅在检测结果与缓存值不同时发送）
+    pub fn subscribe(&self) -> broadcast::Receiver<ToolStatus> {
+        self.changes.subscribe()
+    }
+
+    /// 订阅检测开始 / 结束
+    pub fn subscribe_detection(&self) -> broadcast::Receiver<ToolDetectionState> {
+        self.detection_changes.subscribe()
+    }
+
+    /// 查询缓存，返回状态及其新鲜度（无条目时返回 `None`）
+    pub fn lookup(&self, tool_id: &str, ttl: Duration) -> Option<(ToolStatus, Freshness)> {
+        self.lookup_at(tool_id, ttl, now_millis())
+    }
+
+    fn lookup_at(&self, tool_id: &str, ttl: Duration, now: i64) -> Option<(ToolStatus, Freshness)> {
+        let entries = self.entries.read().unwrap();
+        let entry = entries.get(tool_id)?;
+        let age = now.saturating_sub(entry.fetched_at);
+        let freshness = if age >= 0 && (age as u128) < ttl.as_millis() {
+            Freshness::Fresh
+        } else {
+            Freshness::Stale
+        };
+        Some((entry.status.clone(), freshness))
+    }
+
+    /// 读取单个工具的缓存状态（不论是否过期）
+    pub fn get_status(&self, tool_id: &str) -> Option<ToolStatus> {
+        self.entries
+            .read()
+            .unwrap()
+            .get(tool_id)
+            .map(|entry| entry.status.clone())
+    }
+
+    /// 立即返回缓存中的全部状态（不论是否过期，不触发检测）及正在检测的工具
+    ///
+    /// 供偏好速度而非新鲜度的界面使用（如托盘菜单、悬浮窗首屏）。
+    pub fn try_get_cached(&self) -> ToolStatusSnapshot {
+        let mut statuses: Vec<ToolStatus> = self
+            .entries
+            .read()
+            .unwrap()
+            .values()
+            .map(|entry| entry.status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.id.cmp(&b.id));
+        ToolStatusSnapshot {
+            statuses,
+            detecting: self.detecting(),
+        }
+    }
+
+    /// 该工具是否正在检测
+    pub fn is_detecting(&self, tool_id: &str) -> bool {
+        self.in_flight.lock().unwrap().contains_key(tool_id)
+    }
+
+    /// 正在检测的工具（按 ID 排序）
+    pub fn detecting(&self) -> Vec<String> {
+        let mut tool_ids: Vec<String> = self.in_flight.lock().unwrap().keys().cloned().collect();
+        tool_ids.sort();
+        tool_ids
+    }
+
+    /// 重新检测单个工具并就地更新其条目，返回最新状态
+    ///
+    /// 安装、更新某个工具后调用，其余工具的缓存保持不变。
+    /// 检测失败时移除该条目，下次读取时重新检测。
+    pub async fn refresh_tool(
+        &self,
+        registry: &ToolRegistry,
+        tool_id: &str,
+    ) -> anyhow::Result<ToolStatus> {
+        self.detect_once(tool_id, || registry.redetect_tool_status(tool_id))
+            .await
+    }
+
+    /// 执行检测并更新缓存；同一工具已在检测中时不再调用 `detect`，等待进行中的检测结果
+    pub async fn detect_once<F, Fut>(&self, tool_id: &str, detect: F) -> anyhow::Result<ToolStatus>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<ToolStatus>>,
+    {
+        let pending = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(tool_id) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(tool_id.to_string(), receiver);
+                    Ok(sender)
+                }
+            }
+        };
+        let sender = match pending {
+            Ok(sender) => sender,
+            Err(receiver) => return wait_for_detection(tool_id, receiver).await,
+        };
+
+        // 检测结束（含调用方被取消）时移除进行中标记
+        let _guard = InFlightGuard {
+            cache: self,
+            tool_id,
+        };
+        let _ = self.detection_changes.send(ToolDetectionState {
+            tool_id: tool_id.to_string(),
+            detecting: true,
+        });
+
+        let result = detect().await;
+        match &result {
+            Ok(status) => {
+                self.update(status.clone());
+            }
+            Err(_) => self.invalidate(tool_id),
+        }
+        let shared = match &result {
+            Ok(status) => Ok(status.clone()),
+            Err(e) => Err(format!("{e:#}")),
+        };
+        let _ = sender.send(Some(shared));
+        result
+    }
+
+    /// 以已知获取时间写入条目（如从工具实例数据库读取的实例），不广播变化
+    pub fn seed(&self, status: ToolStatus, fetched_at: i64) {
+        self.entries
+            .write()
+            .unwrap()
+            .insert(status.id.clone(), CachedToolStatus { status, fetched_at });
+    }
+
+    /// 写入刚检测到的状态；与缓存值不同时广播变化并返回 `true`
+    pub fn update(&self, status: ToolStatus) -> bool {
+        let previous = self.entries.write().unwrap().insert(
+            status.id.clone(),
+            CachedToolStatus {
+                status: status.clone(),
+                fetched_at: now_millis(),
+            },
+        );
+
+        self.persist();
+
+        let changed = previous.is_none_or(|p| p.status != status);
+        if changed {
+            let _ = self.changes.send(status);
+        }
+        changed
+    }
+
+    /// 使单个工具的缓存失效
+    pub fn invalidate(&self, tool_id: &str) {
+        self.entries.write().unwrap().remove(tool_id);
+        self.persist();
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+        self.persist();
+    }
+
+    /// 标记开始后台检测；该工具已在检测中时返回 `false`
+    pub fn begin_refresh(&self, tool_id: &str) -> bool {
+        self.refreshing.lock().unwrap().insert(tool_id.to_string())
+    }
+
+    /// 标记后台检测结束
+    pub fn end_refresh(&self, tool_id: &str) {
+        self.refreshing.lock().unwrap().remove(tool_id);
+    }
+}
+
+/// 检测结束时移除进行中标记并广播
+struct InFlightGuard<'a> {
+    cache: &'a ToolStatusCache,
+    tool_id: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.cache.in_flight.lock().unwrap().remove(self.tool_id);
+        let _ = self.cache.detection_changes.send(ToolDetectionState {
+            tool_id: self.tool_id.to_string(),
+            detecting: false,
+        });
+    }
+}
+
+/// 等待进行中的检测结果；执行检测的调用方被取消时返回错误
+async fn wait_for_detection(
+    tool_id: &str,
+    mut receiver: watch::Receiver<Option<DetectionResult>>,
+) -> anyhow::Result<ToolStatus> {
+    let result = match receiver.wait_for(Option::is_some).await {
+        Ok(result) => result.clone(),
+        Err(_) => None,
+    };
+    match result {
+        Some(result) => result.map_err(anyhow::Error::msg),
+        None => Err(anyhow::anyhow!("工具 {} 的检测已中断", tool_id)),
+    }
+}
+
+impl Default for ToolStatusCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(id: &str, installed: bool, version: Option<&str>) -> ToolStatus {
+        ToolStatus {
+            id: id.to_string(),
+            name: id.to_string(),
+            installed,
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_entry_expires_after_ttl() {
+        let cache = ToolStatusCache::new();
+        let ttl = Duration::from_secs(600);
+        cache.seed(status("claude-code", true, Some("2.0.61")), 1_000_000);
+
+        let (_, fresh) = cache
+            .lookup_at("claude-code", ttl, 1_000_000 + 599_000)
+            .unwrap();
+        assert_eq!(fresh, Freshness::Fresh);
+
+        let (cached, stale) = cache
+            .lookup_at("claude-code", ttl, 1_000_000 + 600_000)
+            .unwrap();
+        assert_eq!(stale, Freshness::Stale);
+        // 过期后仍返回旧值（stale-while-revalidate）
+        assert_eq!(cached.version.as_deref(), Some("2.0.61"));
+
+        assert!(cache.lookup_at("codex", ttl, 0).is_none());
+    }
+
+    #[test]
+    fn test_update_broadcasts_only_on_change() {
+        let cache = ToolStatusCache::new();
+        let mut rx = cache.subscribe();
+        cache.seed(status("claude-code", true, Some("2.0.61")), 0);
+
+        // 结果相同：刷新时间但不广播
+        assert!(!cache.update(status("claude-code", true, Some("2.0.61"))));
+        assert!(rx.try_recv().is_err());
+        let (_, freshness) = cache
+            .lookup("claude-code", Duration::from_secs(600))
+            .unwrap();
+        assert_eq!(freshness, Freshness::Fresh);
+
+        // 终端中卸载后重新检测
+        assert!(cache.update(status("claude-code", false, None)));
+        assert!(!rx.try_recv().unwrap().installed);
+    }
+
+    #[test]
+    fn test_get_status_ignores_freshness() {
+        let cache = ToolStatusCache::new();
+        cache.seed(status("codex", true, Some("0.65.0")), 0);
+        assert_eq!(
+            cache.get_status("codex").unwrap().version.as_deref(),
+            Some("0.65.0")
+        );
+        assert!(cache.get_status("claude-code").is_none());
+    }
+
+    #[test]
+    fn test_refresh_deduplicated() {
+        let cache = ToolStatusCache::new();
+        assert!(cache.begin_refresh("codex"));
+        assert!(!cache.begin_refresh("codex"));
+        cache.end_refresh("codex");
+        assert!(cache.begin_refresh("codex"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_detections_coalesced() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = ToolStatusCache::new();
+        let runs = &AtomicUsize::new(0);
+        let release = &tokio::sync::Notify::new();
+        let detect = move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            release.notified().await;
+            Ok::<_, anyhow::Error>(status("codex", true, Some("0.65.0")))
+        };
+
+        let (a, b, c, ()) = tokio::join!(
+            cache.detect_once("codex", detect),
+            cache.detect_once("codex", detect),
+            cache.detect_once("codex", detect),
+            async {
+                // 三个调用方都已进入：仅第一个在检测，快照立即返回
+                assert!(cache.is_detecting("codex"));
+                let snapshot = cache.try_get_cached();
+                assert!(snapshot.statuses.is_empty());
+                assert_eq!(snapshot.detecting, vec!["codex".to_string()]);
+                release.notify_one();
+            },
+        );
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        for result in [a, b, c] {
+            assert_eq!(result.unwrap().version.as_deref(), Some("0.65.0"));
+        }
+        assert!(!cache.is_detecting("codex"));
+        assert_eq!(cache.try_get_cached().statuses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_detection_shares_error() {
+        let cache = ToolStatusCache::new();
+        cache.seed(status("codex", true, Some("0.65.0")), 0);
+        let release = &tokio::sync::Notify::new();
+        let detect = move || async move {
+            release.notified().await;
+            Err::<ToolStatus, _>(anyhow::anyhow!("命令执行超时"))
+        };
+
+        let (a, b, ()) = tokio::join!(
+            cache.detect_once("codex", detect),
+            cache.detect_once("codex", detect),
+            async { release.notify_one() },
+        );
+        assert!(a.is_err());
+        assert!(b.unwrap_err().to_string().contains("命令执行超时"));
+        assert!(cache.get_status("codex").is_none());
+
+        // 检测结束后可再次检测
+        let rerun = cache
+            .detect_once("codex", || async { Ok(status("codex", false, None)) })
+            .await;
+        assert!(!rerun.unwrap().installed);
+    }
+
+    #[test]
+    fn test_persisted_entries_restored_with_ttl() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_status_cache.json");
+        let ttl = Duration::from_secs(600);
+
+        let cache = ToolStatusCache::new();
+        cache.enable_persistence(path.clone());
+        cache.update(status("claude-code", true, Some("2.0.61")));
+        cache.seed(status("codex", true, Some("0.65.0")), 0);
+        cache.invalidate("gemini-cli");
+
+        let restored = ToolStatusCache::new();
+        restored.enable_persistence(path.clone());
+        let (cached, freshness) = restored.lookup("claude-code", ttl).unwrap();
+        assert_eq!(cached.version.as_deref(), Some("2.0.61"));
+        assert_eq!(freshness, Freshness::Fresh);
+        // 旧条目仍按获取时间判断过期
+        let (_, freshness) = restored.lookup("codex", ttl).unwrap();
+        assert_eq!(freshness, Freshness::Stale);
+    }
+
+    #[test]
+    fn test_persisted_entries_discarded_on_version_change_or_corruption() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("tool_status_cache.json");
+        let ttl = Duration::from_secs(600);
+
+        let cache = ToolStatusCache::new();
+        cache.enable_persistence(path.clone());
+        cache.update(status("claude-code", true, Some("2.0.61")));
+
+        let upgraded = ToolStatusCache::new();
+        upgraded.load_from(&path, "999.0.0");
+        assert!(upgraded.lookup("claude-code", ttl).is_none());
+        assert!(!path.exists());
+
+        std::fs::write(&path, "{ not json").unwrap();
+        let restored = ToolStatusCache::new();
+        restored.enable_persistence(path.clone());
+        assert!(restored.lookup("claude-code", ttl).is_none());
+    }
+
+    #[test]
+    fn test_invalidate_and_clear() {
+        let cache = ToolStatusCache::new();
+        let ttl = Duration::from_secs(600);
+        cache.update(status("codex", true, None));
+        cache.update(status("gemini-cli", true, None));
+
+        cache.invalidate("codex");
+        assert!(cache.lookup("codex", ttl).is_none());
+        assert!(cache.lookup("gemini-cli", ttl).is_some());
+
+        cache.clear();
+        assert!(cache.lookup("gemini-cli", ttl).is_none());
+    }
+}
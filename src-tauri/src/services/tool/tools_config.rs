@@ -1,6 +1,6 @@
 // Tools Config - tools.json 数据模型
 //
-// 用于版本控制和多端同步的工具配置文件
+// 旧版工具实例存储格式，现仅用于导入到 tools.db
 
 use crate::models::{InstallMethod, SSHConfig, ToolInstance, ToolType};
 use serde::{Deserialize, Serialize};
@@ -57,6 +57,8 @@ pub struct WSLToolInstance {
     pub installed: bool,
     pub version: Option<String>,
     pub install_path: Option<String>,
+    #[serde(default)]
+    pub windows_install_path: Option<String>, // 安装路径的 Windows 表示
     pub install_method: Option<InstallMethod>,
     pub is_builtin: bool,
     pub created_at: i64,
@@ -128,10 +130,15 @@ impl ToolsConfig {
                     install_path: local.install_path.clone(),
                     installer_path: local.installer_path.clone(), // 新增
                     wsl_distro: None,
+                    windows_install_path: None,
                     ssh_config: None,
                     is_builtin: local.is_builtin,
                     created_at: local.created_at,
                     updated_at: local.updated_at,
+                    label: None,
+                    notes: None,
+                    env_overrides: Default::default(),
+                    node_runtime: None,
                 });
             }
 
@@ -148,10 +155,15 @@ impl ToolsConfig {
                     install_path: wsl.install_path.clone(),
                     installer_path: None, // WSL环境暂不支持
                     wsl_distro: Some(wsl.distro_name.clone()),
+                    windows_install_path: wsl.windows_install_path.clone(),
                     ssh_config: None,
                     is_builtin: wsl.is_builtin,
                     created_at: wsl.created_at,
                     updated_at: wsl.updated_at,
+                    label: None,
+                    notes: None,
+                    env_overrides: Default::default(),
+                    node_runtime: None,
                 });
             }
 
@@ -168,10 +180,15 @@ impl ToolsConfig {
                     install_path: ssh.install_path.clone(),
                     installer_path: None, // SSH环境暂不支持
                     wsl_distro: None,
+                    windows_install_path: None,
                     ssh_config: Some(ssh.ssh_config.clone()),
                     is_builtin: ssh.is_builtin,
                     created_at: ssh.created_at,
                     updated_at: ssh.updated_at,
+                    label: None,
+                    notes: None,
+                    env_overrides: Default::default(),
+                    node_runtime: None,
                 });
             }
         }
@@ -233,6 +250,7 @@ impl ToolsConfig {
                                 installed: instance.installed,
                                 version: instance.version,
                                 install_path: instance.install_path,
+                                windows_install_path: instance.windows_install_path,
                                 install_method: instance.install_method,
                                 is_builtin: instance.is_builtin,
                                 created_at: instance.created_at,
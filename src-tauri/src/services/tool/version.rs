@@ -1,9 +1,32 @@
 use crate::models::Tool;
 use crate::services::tool::DetectorRegistry;
-use crate::utils::CommandExecutor;
+use crate::utils::{CommandExecutor, CommandRunner};
 use anyhow::Result;
+use once_cell::sync::Lazy;
 use semver::Version;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// npm 官方 registry
+const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+
+/// npm 包大小缓存（`包名@版本` → 字节数），同一版本只查询一次
+static NPM_SIZE_CACHE: Lazy<Mutex<HashMap<String, Option<u64>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// npm registry 版本元数据（仅保留 dist 字段）
+#[derive(Debug, Deserialize)]
+struct NpmVersionMeta {
+    dist: NpmDist,
+}
+
+#[derive(Debug, Deserialize)]
+struct NpmDist {
+    tarball: Option<String>,
+    #[serde(rename = "unpackedSize")]
+    unpacked_size: Option<u64>,
+}
 
 /// 版本信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -64,7 +87,7 @@ struct ToolVersionFromMirror {
 /// 版本服务
 pub struct VersionService {
     detector_registry: DetectorRegistry,
-    command_executor: CommandExecutor,
+    command_executor: Arc<dyn CommandRunner>,
     mirror_api_url: String,
     #[allow(dead_code)]
     use_local_fallback: bool, // 是否启用本地 fallback
@@ -80,7 +103,7 @@ impl VersionService {
 
         VersionService {
             detector_registry: DetectorRegistry::new(),
-            command_executor: CommandExecutor::new(),
+            command_executor: Arc::new(CommandExecutor::new()),
             mirror_api_url: "https://mirror.duckcoding.com/api/v1/tools".to_string(),
             use_local_fallback,
         }
@@ -94,12 +117,18 @@ impl VersionService {
 
         VersionService {
             detector_registry: DetectorRegistry::new(),
-            command_executor: CommandExecutor::new(),
+            command_executor: Arc::new(CommandExecutor::new()),
             mirror_api_url: mirror_url,
             use_local_fallback,
         }
     }
 
+    /// 使用指定的命令执行器（测试中传入 `MockExecutor`）
+    pub fn with_runner(mut self, runner: Arc<dyn CommandRunner>) -> Self {
+        self.command_executor = runner;
+        self
+    }
+
     /// 检查工具版本（新架构：使用 tool_id）
     pub async fn check_version(&self, tool: &Tool) -> Result<VersionInfo> {
         self.check_version_by_id(&tool.id).await
@@ -114,7 +143,7 @@ impl VersionService {
             .ok_or_else(|| anyhow::anyhow!("未知的工具 ID: {}", tool_id))?;
 
         // 使用 Detector 获取已安装版本
-        let installed_version = detector.get_version(&self.command_executor).await;
+        let installed_version = detector.get_version(&*self.command_executor).await;
 
         // 1. 尝试从镜像站获取最新版本
         match self.get_latest_from_mirror(tool_id).await {
@@ -202,6 +231,76 @@ impl VersionService {
         crate::utils::version::parse_version(version)
     }
 
+    /// 查询 npm 包的下载大小（字节）
+    ///
+    /// 优先取 tarball 的 Content-Length，其次为 `dist.unpackedSize`；无法获取时返回 None。
+    /// 结果按版本缓存，检查更新与执行更新之间不会重复请求。
+    pub async fn npm_download_size(package: &str, version: &str) -> Option<u64> {
+        let version = crate::utils::parse_version_string(version);
+        let key = format!("{package}@{version}");
+        if let Some(size) = NPM_SIZE_CACHE.lock().unwrap().get(&key) {
+            return *size;
+        }
+
+        let size = match Self::fetch_npm_download_size(package, &version).await {
+            Ok(size) => size,
+            Err(e) => {
+                tracing::debug!(package, version = %version, error = ?e, "获取 npm 包大小失败");
+                None
+            }
+        };
+        NPM_SIZE_CACHE.lock().unwrap().insert(key, size);
+        size
+    }
+
+    async fn fetch_npm_download_size(package: &str, version: &str) -> Result<Option<u64>> {
+        let client = crate::http_client::build_client().map_err(|e| anyhow::anyhow!(e))?;
+        let url = format!("{NPM_REGISTRY_URL}/{package}/{version}");
+        let meta = client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<NpmVersionMeta>()
+            .await?;
+
+        if let Some(tarball) = &meta.dist.tarball {
+            let tarball_size = client
+                .head(tarball)
+                .timeout(std::time::Duration::from_secs(10))
+                .send()
+                .await
+                .ok()
+                .filter(|r| r.status().is_success())
+                .and_then(|r| r.content_length())
+                .filter(|len| *len > 0);
+            if tarball_size.is_some() {
+                return Ok(tarball_size);
+            }
+        }
+        Ok(meta.dist.unpacked_size)
+    }
+
+    /// 探测网络连通性（请求镜像站，短超时）
+    ///
+    /// 任何 HTTP 响应都视为在线，仅连接失败或超时视为离线
+    pub async fn probe_connectivity(&self) -> bool {
+        let client = match crate::http_client::build_client() {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::debug!(error = %e, "创建连通性探测客户端失败");
+                return false;
+            }
+        };
+        client
+            .head(&self.mirror_api_url)
+            .timeout(std::time::Duration::from_secs(5))
+            .send()
+            .await
+            .is_ok()
+    }
+
     /// 批量从镜像站获取所有工具版本（优化：一次请求）
     async fn get_all_from_mirror(&self) -> Result<MirrorApiResponse> {
         #[cfg(debug_assertions)]
@@ -239,7 +338,7 @@ impl VersionService {
                 // 成功获取镜像站数据，为每个工具构建 VersionInfo
                 for detector in &detectors {
                     let tool_id = detector.tool_id();
-                    let installed_version = detector.get_version(&self.command_executor).await;
+                    let installed_version = detector.get_version(&*self.command_executor).await;
 
                     // 从镜像站数据中查找该工具
                     if let Some(mirror_tool) = mirror_data.tools.iter().find(|t| t.id == tool_id) {
@@ -295,7 +394,7 @@ impl VersionService {
                 tracing::warn!(error = ?e, "镜像站 API 不可用，回退到本地检查");
                 for detector in &detectors {
                     let tool_id = detector.tool_id();
-                    let installed_version = detector.get_version(&self.command_executor).await;
+                    let installed_version = detector.get_version(&*self.command_executor).await;
                     results.push(VersionInfo {
                         tool_id: tool_id.to_string(),
                         installed_version: installed_version.clone(),
@@ -0,0 +1,263 @@
+// Tool Setup Service
+//
+// 一键配置工具（检测 → 安装 → 绑定供应商 → 应用配置 → 健康检查）的报告类型与配置回滚
+
+use crate::data::backup::{self, BackupPolicy};
+use crate::data::Durability;
+use crate::models::provider::Provider;
+use crate::models::Tool;
+use crate::services::profile_manager::ProfileManager;
+use crate::services::proxy::credentials::credential_from_provider;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 配置流程阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStage {
+    /// 检测工具（优先使用缓存）
+    Detect,
+    /// 未安装时按首选方式安装
+    Install,
+    /// 绑定工具使用的供应商
+    BindProvider,
+    /// 将供应商配置写入工具（Profile + 原生配置文件）
+    ApplyConfig,
+    /// 重新检测并校验配置
+    HealthCheck,
+}
+
+/// 阶段结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Succeeded,
+    /// 无需执行（如工具已安装）
+    Skipped,
+    Failed,
+    /// 因前序阶段失败而未执行
+    NotRun,
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StageOutcome {
+    pub stage: SetupStage,
+    pub status: StageStatus,
+    pub message: String,
+}
+
+/// 配置报告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SetupReport {
+    pub tool_id: String,
+    pub provider_id: String,
+    pub success: bool,
+    pub stages: Vec<StageOutcome>,
+    /// 失败后是否已回滚配置改动
+    pub rolled_back: bool,
+}
+
+impl SetupReport {
+    pub fn new(tool_id: &str, provider_id: &str) -> Self {
+        Self {
+            tool_id: tool_id.to_string(),
+            provider_id: provider_id.to_string(),
+            success: false,
+            stages: Vec::new(),
+            rolled_back: false,
+        }
+    }
+
+    pub fn record(&mut self, stage: SetupStage, status: StageStatus, message: impl Into<String>) {
+        self.stages.push(StageOutcome {
+            stage,
+            status,
+            message: message.into(),
+        });
+    }
+
+    /// 结束报告：未执行的阶段记为 `NotRun`，没有失败阶段即为成功
+    pub fn finish(mut self) -> Self {
+        const ALL: [SetupStage; 5] = [
+            SetupStage::Detect,
+            SetupStage::Install,
+            SetupStage::BindProvider,
+            SetupStage::ApplyConfig,
+            SetupStage::HealthCheck,
+        ];
+        for stage in ALL {
+            if !self.stages.iter().any(|s| s.stage == stage) {
+                self.record(stage, StageStatus::NotRun, String::new());
+            }
+        }
+        self.success = self
+            .stages
+            .iter()
+            .all(|s| s.status != StageStatus::Failed && s.status != StageStatus::NotRun);
+        self
+    }
+}
+
+/// `setup_tool` 选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SetupOptions {
+    /// 安装方式（npm / brew / official），缺省使用工具的推荐方式
+    pub install_method: Option<String>,
+    /// 已安装时也强制重新安装
+    pub force_install: bool,
+    /// 写入的 Profile 名称，缺省为供应商 ID
+    pub profile_name: Option<String>,
+    /// Codex 的 wire_api（缺省 responses）
+    pub wire_api: Option<String>,
+    /// Gemini CLI 的模型
+    pub model: Option<String>,
+}
+
+impl SetupOptions {
+    /// 写入的 Profile 名称（未指定或为空时使用供应商 ID）
+    pub fn profile_name(&self, provider: &Provider) -> String {
+        self.profile_name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| provider.id.clone())
+    }
+}
+
+/// 将供应商凭证保存为工具 Profile 并激活（同步写入工具原生配置文件）
+pub fn apply_provider_profile(
+    manager: &ProfileManager,
+    tool_id: &str,
+    provider: &Provider,
+    options: &SetupOptions,
+) -> Result<String> {
+    let credential = credential_from_provider(provider.clone()).map_err(anyhow::Error::msg)?;
+    let profile_name = options.profile_name(provider);
+    let (api_key, base_url) = (credential.api_key, credential.base_url);
+    match tool_id {
+        "claude-code" => manager.save_claude_profile(&profile_name, api_key, base_url),
+        "codex" => {
+            manager.save_codex_profile(&profile_name, api_key, base_url, options.wire_api.clone())
+        }
+        "gemini-cli" => {
+            manager.save_gemini_profile(&profile_name, api_key, base_url, options.model.clone())
+        }
+        other => Err(anyhow::anyhow!("不支持的工具 ID: {}", other)),
+    }?;
+    manager.activate_profile(tool_id, &profile_name)?;
+    Ok(profile_name)
+}
+
+/// 应用供应商配置会改动的工具原生配置文件
+pub fn native_config_files(tool: &Tool) -> Vec<PathBuf> {
+    let names: &[&str] = match tool.id.as_str() {
+        "claude-code" => &["settings.json"],
+        "codex" => &["config.toml", "auth.json"],
+        "gemini-cli" => &[".env", "settings.json"],
+        _ => &[],
+    };
+    names
+        .iter()
+        .map(|name| tool.config_dir.join(name))
+        .collect()
+}
+
+/// 一组文件在改动前的内容（用于失败回滚）
+#[derive(Debug)]
+pub struct ConfigSnapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl ConfigSnapshot {
+    /// 记录文件当前内容，并为已存在的文件在同级 `backups/` 留一份备份
+    pub fn capture(paths: &[PathBuf]) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        for path in paths {
+            let content = match std::fs::read(path) {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e).with_context(|| format!("读取配置失败: {path:?}")),
+            };
+            if content.is_some() {
+                backup::snapshot(path, &BackupPolicy::beside(path))?;
+            }
+            files.push((path.clone(), content));
+        }
+        Ok(Self { files })
+    }
+
+    /// 快照中的文件与内容（`None` 表示当时文件不存在）
+    pub fn files(&self) -> &[(PathBuf, Option<Vec<u8>>)] {
+        &self.files
+    }
+
+    /// 恢复到快照时的状态（原本不存在的文件会被删除）
+    pub fn restore(&self) -> Result<()> {
+        for (path, content) in &self.files {
+            restore_file(path, content.as_deref())?;
+        }
+        Ok(())
+    }
+}
+
+/// 原子写回文件内容，`None` 时删除文件
+pub fn restore_file(path: &Path, content: Option<&[u8]>) -> Result<()> {
+    match content {
+        Some(content) => backup::atomic_write(path, content, Durability::Durable)
+            .with_context(|| format!("恢复配置失败: {path:?}")),
+        None if path.exists() => {
+            std::fs::remove_file(path).with_context(|| format!("删除配置失败: {path:?}"))
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_snapshot_restores_modified_and_created_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("settings.json");
+        let created = temp_dir.path().join("auth.json");
+        std::fs::write(&existing, b"{\"old\":true}").unwrap();
+
+        let snapshot = ConfigSnapshot::capture(&[existing.clone(), created.clone()]).unwrap();
+        std::fs::write(&existing, b"{\"new\":true}").unwrap();
+        std::fs::write(&created, b"{}").unwrap();
+
+        snapshot.restore().unwrap();
+        assert_eq!(std::fs::read(&existing).unwrap(), b"{\"old\":true}");
+        assert!(!created.exists());
+        assert!(temp_dir.path().join("backups").is_dir());
+    }
+
+    #[test]
+    fn test_report_marks_remaining_stages_not_run() {
+        let mut report = SetupReport::new("codex", "duckcoding");
+        report.record(SetupStage::Detect, StageStatus::Succeeded, "已安装");
+        report.record(SetupStage::Install, StageStatus::Skipped, "");
+        report.record(SetupStage::BindProvider, StageStatus::Failed, "写入失败");
+        let report = report.finish();
+
+        assert!(!report.success);
+        assert_eq!(report.stages.len(), 5);
+        assert_eq!(report.stages[3].stage, SetupStage::ApplyConfig);
+        assert_eq!(report.stages[3].status, StageStatus::NotRun);
+    }
+
+    #[test]
+    fn test_report_succeeds_when_all_stages_pass() {
+        let mut report = SetupReport::new("codex", "duckcoding");
+        report.record(SetupStage::Detect, StageStatus::Succeeded, "");
+        report.record(SetupStage::Install, StageStatus::Skipped, "");
+        report.record(SetupStage::BindProvider, StageStatus::Succeeded, "");
+        report.record(SetupStage::ApplyConfig, StageStatus::Succeeded, "");
+        report.record(SetupStage::HealthCheck, StageStatus::Succeeded, "");
+        assert!(report.finish().success);
+    }
+}
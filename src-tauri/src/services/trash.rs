@@ -0,0 +1,338 @@
+// Trash Service
+//
+// 回收站：删除的供应商与工具实例先移入 `config_dir/trash/`（每条记录一个 JSON 文件，
+// 包含完整的序列化记录与删除时间），可在 `TRASH_RETENTION_DAYS` 天内恢复，过期自动清除。
+// 供应商的访问令牌与 API Key 保存在记录中（与 providers.json 相同的文件权限），恢复时原样写回。
+
+use crate::data::DataManager;
+use crate::models::provider::Provider;
+use crate::models::ToolInstance;
+use crate::utils::config::config_dir;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 回收站记录保留天数
+pub const TRASH_RETENTION_DAYS: i64 = 30;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// 被删除的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "record", rename_all = "snake_case")]
+pub enum TrashedRecord {
+    Provider(Provider),
+    ToolInstance(ToolInstance),
+}
+
+impl TrashedRecord {
+    pub fn kind(&self) -> TrashKind {
+        match self {
+            Self::Provider(_) => TrashKind::Provider,
+            Self::ToolInstance(_) => TrashKind::ToolInstance,
+        }
+    }
+
+    /// 记录原来的 ID
+    pub fn original_id(&self) -> &str {
+        match self {
+            Self::Provider(provider) => &provider.id,
+            Self::ToolInstance(instance) => &instance.instance_id,
+        }
+    }
+
+    /// 展示名称
+    pub fn display_name(&self) -> String {
+        match self {
+            Self::Provider(provider) => provider.name.clone(),
+            Self::ToolInstance(instance) => instance
+                .label
+                .clone()
+                .unwrap_or_else(|| instance.instance_id.clone()),
+        }
+    }
+}
+
+/// 回收站记录类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashKind {
+    Provider,
+    ToolInstance,
+}
+
+impl TrashKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Provider => "provider",
+            Self::ToolInstance => "tool_instance",
+        }
+    }
+}
+
+/// 回收站中的一条记录（落盘格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    /// 删除时间（Unix 时间戳）
+    pub deleted_at: i64,
+    pub item: TrashedRecord,
+}
+
+/// 回收站列表项（不含完整记录，避免把令牌发送到前端）
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashItem {
+    pub id: String,
+    pub kind: TrashKind,
+    pub original_id: String,
+    pub name: String,
+    pub deleted_at: i64,
+    /// 到期自动清除的时间（Unix 时间戳）
+    pub expires_at: i64,
+}
+
+impl From<&TrashEntry> for TrashItem {
+    fn from(entry: &TrashEntry) -> Self {
+        Self {
+            id: entry.id.clone(),
+            kind: entry.item.kind(),
+            original_id: entry.item.original_id().to_string(),
+            name: entry.item.display_name(),
+            deleted_at: entry.deleted_at,
+            expires_at: entry.deleted_at + TRASH_RETENTION_DAYS * SECONDS_PER_DAY,
+        }
+    }
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub kind: TrashKind,
+    pub original_id: String,
+    /// 恢复后的 ID（原 ID 已被占用时重新生成）
+    pub restored_id: String,
+}
+
+/// 回收站管理器
+pub struct TrashManager {
+    data_manager: DataManager,
+    dir: PathBuf,
+}
+
+impl TrashManager {
+    pub fn new() -> Result<Self> {
+        let dir = config_dir()
+            .map_err(|e| anyhow!("获取配置目录失败: {}", e))?
+            .join("trash");
+        Ok(Self::at(dir))
+    }
+
+    /// 使用指定目录（测试用）
+    pub fn at(dir: PathBuf) -> Self {
+        Self {
+            data_manager: DataManager::new(),
+            dir,
+        }
+    }
+
+    fn entry_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    /// 移入回收站，返回新记录
+    pub fn put(&self, item: TrashedRecord) -> Result<TrashEntry> {
+        let deleted_at = chrono::Utc::now().timestamp();
+        let base = format!(
+            "{}-{}-{}",
+            deleted_at,
+            item.kind().as_str(),
+            sanitize_file_part(item.original_id())
+        );
+        let id = unique_id(&base, |id| Ok(self.entry_path(id).exists()))?;
+        let entry = TrashEntry {
+            id,
+            deleted_at,
+            item,
+        };
+        let value = serde_json::to_value(&entry).context("序列化回收站记录失败")?;
+        self.data_manager
+            .json_uncached()
+            .write(&self.entry_path(&entry.id), &value)?;
+        Ok(entry)
+    }
+
+    /// 读取一条记录
+    pub fn get(&self, id: &str) -> Result<TrashEntry> {
+        let path = self.entry_path(id);
+        if id.contains(['/', '\\']) || !path.exists() {
+            return Err(anyhow!("回收站记录不存在: {}", id));
+        }
+        let value = self.data_manager.json_uncached().read(&path)?;
+        serde_json::from_value(value).with_context(|| format!("解析回收站记录失败: {}", id))
+    }
+
+    /// 列出全部记录（按删除时间倒序）；无法解析的文件跳过
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut entries = Vec::new();
+        for file in fs::read_dir(&self.dir).context("读取回收站目录失败")? {
+            let path = file?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            match self.get(id) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => tracing::warn!(path = ?path, error = ?e, "跳过无法读取的回收站记录"),
+            }
+        }
+        entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(entries)
+    }
+
+    /// 删除一条记录
+    pub fn remove(&self, id: &str) -> Result<()> {
+        // 校验记录存在且 ID 合法
+        self.get(id)?;
+        fs::remove_file(self.entry_path(id)).with_context(|| format!("删除回收站记录失败: {}", id))
+    }
+
+    /// 清空回收站，返回清除的记录数
+    pub fn purge_all(&self) -> Result<usize> {
+        let entries = self.list()?;
+        for entry in &entries {
+            self.remove(&entry.id)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// 清除删除时间早于保留期限的记录，返回清除的记录数
+    pub fn purge_expired(&self, now: i64) -> Result<usize> {
+        let cutoff = now - TRASH_RETENTION_DAYS * SECONDS_PER_DAY;
+        let mut purged = 0;
+        for entry in self.list()? {
+            if entry.deleted_at < cutoff {
+                self.remove(&entry.id)?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+/// 生成未被占用的 ID：`base`、`base-restored`、`base-restored-2`……
+pub fn unique_id(base: &str, mut taken: impl FnMut(&str) -> Result<bool>) -> Result<String> {
+    if !taken(base)? {
+        return Ok(base.to_string());
+    }
+    for n in 1.. {
+        let candidate = if n == 1 {
+            format!("{base}-restored")
+        } else {
+            format!("{base}-restored-{n}")
+        };
+        if !taken(&candidate)? {
+            return Ok(candidate);
+        }
+    }
+    unreachable!()
+}
+
+/// 文件名中只保留字母、数字、`-` 与 `_`
+fn sanitize_file_part(raw: &str) -> String {
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// 清除过期的回收站记录（失败只记日志）
+pub fn purge_expired_trash() {
+    match TrashManager::new().and_then(|trash| trash.purge_expired(chrono::Utc::now().timestamp()))
+    {
+        Ok(0) => {}
+        Ok(count) => tracing::info!(count, "已清除过期的回收站记录"),
+        Err(e) => tracing::warn!(error = ?e, "清除过期回收站记录失败"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn provider(id: &str) -> Provider {
+        let mut provider = crate::models::provider::ProviderStore::default().providers[0].clone();
+        provider.id = id.to_string();
+        provider.name = format!("Provider {id}");
+        provider.access_token = "secret-token".to_string();
+        provider.is_default = false;
+        provider
+    }
+
+    #[test]
+    fn test_put_list_and_remove() {
+        let dir = TempDir::new().unwrap();
+        let trash = TrashManager::at(dir.path().join("trash"));
+        assert!(trash.list().unwrap().is_empty());
+
+        let entry = trash.put(TrashedRecord::Provider(provider("a/b"))).unwrap();
+        assert!(!entry.id.contains('/'));
+
+        let listed = trash.list().unwrap();
+        assert_eq!(listed.len(), 1);
+        let item = TrashItem::from(&listed[0]);
+        assert_eq!(item.kind, TrashKind::Provider);
+        assert_eq!(item.original_id, "a/b");
+
+        match trash.get(&entry.id).unwrap().item {
+            TrashedRecord::Provider(p) => assert_eq!(p.access_token, "secret-token"),
+            other => panic!("unexpected record: {other:?}"),
+        }
+
+        trash.remove(&entry.id).unwrap();
+        assert!(trash.list().unwrap().is_empty());
+        assert!(trash.get("../providers").is_err());
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let dir = TempDir::new().unwrap();
+        let trash = TrashManager::at(dir.path().join("trash"));
+        let old = trash.put(TrashedRecord::Provider(provider("old"))).unwrap();
+        trash.put(TrashedRecord::Provider(provider("new"))).unwrap();
+
+        // 把第一条记录的删除时间改到保留期限之前
+        let mut entry = trash.get(&old.id).unwrap();
+        entry.deleted_at -= (TRASH_RETENTION_DAYS + 1) * SECONDS_PER_DAY;
+        fs::write(
+            trash.entry_path(&old.id),
+            serde_json::to_string(&entry).unwrap(),
+        )
+        .unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        assert_eq!(trash.purge_expired(now).unwrap(), 1);
+        let remaining = trash.list().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].item.original_id(), "new");
+    }
+
+    #[test]
+    fn test_unique_id_on_collision() {
+        let taken = ["p", "p-restored"];
+        let id = unique_id("p", |id| Ok(taken.contains(&id))).unwrap();
+        assert_eq!(id, "p-restored-2");
+        assert_eq!(unique_id("q", |_| Ok(false)).unwrap(), "q");
+    }
+}
@@ -0,0 +1,208 @@
+// 应用自身的版本检查
+//
+// 通过 GitHub Releases 获取 DuckCoding 最新发布版本：
+// - 复用全局代理的 HTTP 客户端，配置了 GitHub 令牌时附带认证
+// - 结果缓存一天（app_update_cache.json），离线模式下只读缓存
+// - 未启用 tauri-updater，有新版本时由前端打开发布页
+
+use crate::data::{DataManager, Durability};
+use crate::models::update::AppUpdateCheck;
+use crate::services::update::skipped_versions::{is_skipped, APP_SUBJECT};
+use crate::utils::config::{config_dir, read_global_config};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 最新发布版本 API
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/DuckCoding-dev/DuckCoding/releases/latest";
+
+/// 检查结果缓存有效期（毫秒）
+const CACHE_TTL_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// GitHub Release（仅保留用到的字段）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+}
+
+/// 缓存文件格式
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRelease {
+    /// 获取时间（Unix 毫秒）
+    fetched_at: i64,
+    release: GitHubRelease,
+}
+
+/// 应用版本检查服务
+pub struct AppUpdateService {
+    releases_url: String,
+}
+
+impl AppUpdateService {
+    pub fn new() -> Self {
+        Self {
+            releases_url: LATEST_RELEASE_URL.to_string(),
+        }
+    }
+
+    /// 检查是否有新版本
+    ///
+    /// `current` 为正在运行的版本（来自 tauri 的 package info）；
+    /// `force` 为 true 时忽略缓存有效期（离线模式下仍只读缓存）。
+    pub async fn check(&self, current: &str, force: bool) -> Result<AppUpdateCheck> {
+        let config = read_global_config().ok().flatten();
+        let offline = config.as_ref().is_some_and(|c| c.offline_mode);
+        let token = config
+            .and_then(|c| c.github_token)
+            .filter(|t| !t.trim().is_empty());
+
+        let cached = Self::load_cache();
+        let now = chrono::Utc::now().timestamp_millis();
+
+        if let Some(cached) = &cached {
+            if offline || (!force && now - cached.fetched_at < CACHE_TTL_MS) {
+                return Ok(with_skip(build_check(current, &cached.release)));
+            }
+        }
+        if offline {
+            return Err(anyhow!("离线模式下无法检查应用更新"));
+        }
+
+        let check = match self.fetch_latest(token.as_deref()).await {
+            Ok(release) => {
+                Self::save_cache(&CachedRelease {
+                    fetched_at: now,
+                    release: release.clone(),
+                });
+                build_check(current, &release)
+            }
+            Err(e) => match cached {
+                // 请求失败时退回过期的缓存
+                Some(cached) => {
+                    tracing::warn!(error = ?e, "检查应用更新失败，使用缓存结果");
+                    build_check(current, &cached.release)
+                }
+                None => return Err(e),
+            },
+        };
+        Ok(with_skip(check))
+    }
+
+    async fn fetch_latest(&self, token: Option<&str>) -> Result<GitHubRelease> {
+        let client = crate::http_client::build_client().map_err(|e| anyhow!(e))?;
+        let mut request = client
+            .get(&self.releases_url)
+            .header("Accept", "application/vnd.github+json");
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().await.context("请求 GitHub Releases 失败")?;
+        if !response.status().is_success() {
+            return Err(anyhow!("GitHub Releases 返回状态码: {}", response.status()));
+        }
+        response
+            .json::<GitHubRelease>()
+            .await
+            .context("解析 GitHub Releases 响应失败")
+    }
+
+    fn cache_path() -> Option<PathBuf> {
+        config_dir()
+            .ok()
+            .map(|dir| dir.join("app_update_cache.json"))
+    }
+
+    /// 读取缓存（不存在或损坏时返回 None）
+    fn load_cache() -> Option<CachedRelease> {
+        let path = Self::cache_path().filter(|p| p.exists())?;
+        let value = DataManager::new().json_uncached().read(&path).ok()?;
+        serde_json::from_value(value).ok()
+    }
+
+    fn save_cache(cached: &CachedRelease) {
+        let Some(path) = Self::cache_path() else {
+            return;
+        };
+        let result = serde_json::to_value(cached)
+            .map_err(anyhow::Error::from)
+            .and_then(|value| {
+                DataManager::new()
+                    .with_durability(Durability::Relaxed)
+                    .json_uncached()
+                    .write(&path, &value)
+                    .map_err(anyhow::Error::from)
+            });
+        if let Err(e) = result {
+            tracing::warn!(error = ?e, "保存应用更新检查缓存失败");
+        }
+    }
+}
+
+impl Default for AppUpdateService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 标记最新版本是否已被用户跳过
+fn with_skip(mut check: AppUpdateCheck) -> AppUpdateCheck {
+    check.skipped = check.has_update && is_skipped(APP_SUBJECT, &check.latest);
+    check
+}
+
+/// 以正在运行的版本对比发布版本（tag 可带 `v` 前缀）
+fn build_check(current: &str, release: &GitHubRelease) -> AppUpdateCheck {
+    let latest = crate::utils::parse_version_string(&release.tag_name);
+    let has_update = match (
+        crate::utils::version::parse_version(current),
+        crate::utils::version::parse_version(&latest),
+    ) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => false,
+    };
+
+    AppUpdateCheck {
+        current: current.to_string(),
+        latest,
+        release_notes: release.body.clone().filter(|b| !b.trim().is_empty()),
+        download_url: Some(release.html_url.clone()),
+        has_update,
+        skipped: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str) -> GitHubRelease {
+        GitHubRelease {
+            tag_name: tag.to_string(),
+            body: Some("- 修复若干问题".to_string()),
+            html_url: format!("https://github.com/DuckCoding-dev/DuckCoding/releases/tag/{tag}"),
+        }
+    }
+
+    #[test]
+    fn test_build_check_compares_semver() {
+        let check = build_check("1.5.1", &release("v1.10.0"));
+        assert!(check.has_update);
+        assert_eq!(check.latest, "1.10.0");
+        assert!(check.download_url.unwrap().ends_with("/v1.10.0"));
+
+        assert!(!build_check("1.5.1", &release("v1.5.1")).has_update);
+        // 本地为开发中的更高版本时不提示
+        assert!(!build_check("1.6.0", &release("v1.5.1")).has_update);
+    }
+
+    #[test]
+    fn test_unparseable_tag_is_not_an_update() {
+        let check = build_check("1.5.1", &release("nightly"));
+        assert!(!check.has_update);
+    }
+}
@@ -2,8 +2,11 @@
 //
 // 包含应用自身的更新检查、下载、安装等功能
 
+pub mod app_update_service;
+pub mod skipped_versions;
 pub mod update_service;
 
 // 直接从 models 导入并重新导出类型
-pub use crate::models::update::{UpdateInfo, UpdateStatus};
+pub use crate::models::update::{AppUpdateCheck, UpdateInfo, UpdateStatus};
+pub use app_update_service::AppUpdateService;
 pub use update_service::UpdateService;
@@ -0,0 +1,103 @@
+// 跳过版本记录
+//
+// 用户可对某个对象（DuckCoding 本身或某个工具）跳过指定版本：
+// - 定时检查不再提醒已跳过的版本，手动检查仍返回并带 `skipped` 标记
+// - 出现比已跳过版本更新的版本时自动清除记录，恢复提醒
+
+use crate::models::tool::UpdateResult;
+use crate::utils::config::{read_global_config, write_global_config};
+use crate::utils::version::{parse_version, parse_version_string};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// DuckCoding 应用自身的对象 ID
+pub const APP_SUBJECT: &str = "duckcoding";
+
+/// 记录跳过的版本（同一对象只保留最后一次跳过的版本）
+pub fn skip_version(subject: &str, version: &str) -> Result<()> {
+    let mut config = read_global_config()
+        .map_err(|e| anyhow!(e))?
+        .ok_or_else(|| anyhow!("配置文件不存在"))?;
+    config
+        .skipped_versions
+        .insert(subject.to_string(), parse_version_string(version));
+    write_global_config(&config).map_err(|e| anyhow!(e))?;
+    tracing::info!(subject, version, "已跳过版本");
+    Ok(())
+}
+
+/// `latest` 是否已被跳过；出现更新的版本时清除该对象的跳过记录
+pub fn is_skipped(subject: &str, latest: &str) -> bool {
+    let Ok(Some(mut config)) = read_global_config() else {
+        return false;
+    };
+    let (skipped, changed) = resolve(&mut config.skipped_versions, subject, latest);
+    if changed {
+        match write_global_config(&config) {
+            Ok(()) => tracing::info!(subject, latest, "出现更新的版本，已清除跳过记录"),
+            Err(e) => tracing::warn!(error = %e, "清除跳过记录失败"),
+        }
+    }
+    skipped
+}
+
+/// 为工具更新检查结果标记是否已跳过（按实际可安装的镜像版本判断）
+pub fn mark_skipped(result: &mut UpdateResult) {
+    if !result.has_update {
+        return;
+    }
+    let latest = result
+        .mirror_version
+        .as_deref()
+        .or(result.latest_version.as_deref());
+    if let (Some(tool_id), Some(latest)) = (result.tool_id.as_deref(), latest) {
+        result.skipped = is_skipped(tool_id, latest);
+    }
+}
+
+/// 判断是否跳过，返回 `(已跳过, 记录是否被清除)`
+fn resolve(skipped: &mut HashMap<String, String>, subject: &str, latest: &str) -> (bool, bool) {
+    let Some(version) = skipped.get(subject) else {
+        return (false, false);
+    };
+    let latest = parse_version_string(latest);
+    if *version == latest {
+        return (true, false);
+    }
+
+    let newer = match (parse_version(&latest), parse_version(version)) {
+        (Some(latest), Some(version)) => latest > version,
+        // 无法比较时视为新版本
+        _ => true,
+    };
+    if newer {
+        skipped.remove(subject);
+    }
+    (false, newer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skipped_version_suppressed() {
+        let mut skipped = HashMap::from([("codex".to_string(), "0.65.0".to_string())]);
+        assert_eq!(resolve(&mut skipped, "codex", "v0.65.0"), (true, false));
+        assert_eq!(
+            resolve(&mut skipped, "claude-code", "2.0.61"),
+            (false, false)
+        );
+    }
+
+    #[test]
+    fn test_newer_version_clears_skip() {
+        let mut skipped = HashMap::from([("codex".to_string(), "0.65.0".to_string())]);
+        // 镜像滞后返回更旧的版本时保留记录
+        assert_eq!(resolve(&mut skipped, "codex", "0.64.0"), (false, false));
+        assert!(skipped.contains_key("codex"));
+
+        assert_eq!(resolve(&mut skipped, "codex", "0.66.0"), (false, true));
+        assert!(skipped.is_empty());
+    }
+}
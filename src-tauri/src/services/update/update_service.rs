@@ -1,8 +1,10 @@
+use crate::data::PathPolicy;
 use crate::models::update::{
     DownloadProgress, DownloadTask, PackageFormatInfo, PlatformInfo as UpdatePlatformInfo,
     UpdateApiResponse, UpdateInfo, UpdateStatus, UpdateUrls,
 };
 use crate::services::downloader::{DownloadEvent, FileDownloader};
+use crate::services::update::skipped_versions::{is_skipped, APP_SUBJECT};
 use anyhow::{anyhow, Context, Result};
 #[cfg(target_os = "macos")]
 use std::path::Path;
@@ -89,6 +91,7 @@ impl UpdateService {
 
         // 检查版本是否需要更新
         let has_update = self.compare_versions(&self.current_version, &api_response.version);
+        let skipped = has_update && is_skipped(APP_SUBJECT, &api_response.version);
 
         // 获取对应平台的更新URL
         let update_url = self.get_platform_update_url(&api_response.update);
@@ -109,6 +112,7 @@ impl UpdateService {
             release_notes: api_response.release_notes,
             file_size,
             required: api_response.required.unwrap_or(false),
+            skipped,
         })
     }
 
@@ -277,6 +281,9 @@ impl UpdateService {
 
     /// 安装更新
     pub async fn install_update(&self, update_path: &str) -> Result<()> {
+        // 安装包路径来自前端，只允许安装更新目录内下载的文件
+        PathPolicy::new([self.update_dir.clone()]).check(std::path::Path::new(update_path))?;
+
         let current_status = self.status.read().await.clone();
         if current_status != UpdateStatus::Downloaded {
             return Err(anyhow!("Update not downloaded yet"));
@@ -341,6 +348,7 @@ impl UpdateService {
             is_windows: cfg!(target_os = "windows"),
             is_macos: cfg!(target_os = "macos"),
             is_linux: cfg!(target_os = "linux"),
+            platform_id: crate::utils::PlatformInfo::current().platform_id(),
         }
     }
 
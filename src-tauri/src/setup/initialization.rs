@@ -1,9 +1,14 @@
-use duckcoding::core::init_logger;
+use duckcoding::core::{i18n, init_logger};
+use duckcoding::data::durable::set_durable_writes_enabled;
 use duckcoding::services::profile_manager::ProfileManager;
+use duckcoding::services::proxy::metrics::PROXY_METRICS;
 use duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use duckcoding::services::tool::{ToolStatusCache, TOOL_STATUS_CACHE};
 use duckcoding::utils::config::read_global_config;
 use duckcoding::{ProxyManager, ToolRegistry};
+use once_cell::sync::Lazy;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex as TokioMutex;
 
 /// 启动初始化上下文
@@ -35,6 +40,26 @@ fn init_logging() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 应用持久化写入（fsync）设置
+fn apply_durable_writes_setting() {
+    let enabled = read_global_config()
+        .ok()
+        .flatten()
+        .map(|cfg| cfg.durable_writes_enabled)
+        .unwrap_or(true);
+    set_durable_writes_enabled(enabled);
+    if !enabled {
+        tracing::info!("持久化写入（fsync）已关闭");
+    }
+}
+
+/// 应用后端文案语言设置
+fn apply_backend_locale() {
+    if let Some(cfg) = read_global_config().ok().flatten() {
+        i18n::set_locale(cfg.backend_locale);
+    }
+}
+
 /// 初始化内置 Profile（用于透明代理配置切换）
 ///
 /// 为每个启用且配置完整的代理工具创建内置 Profile
@@ -120,48 +145,64 @@ async fn run_migrations() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// 自动启动配置的代理
-async fn auto_start_proxies(
-    proxy_manager: &Arc<ProxyManager>,
-    _profile_manager: &Arc<tokio::sync::RwLock<ProfileManager>>,
-) {
-    duckcoding::auto_start_proxies(proxy_manager).await;
+/// 异步启动配置了 `auto_start` 的代理
+///
+/// 在 Tauri setup 阶段调用，确保状态事件转发已就绪后再启动，
+/// 前端可以收到自启动代理的状态变化。
+pub fn spawn_auto_start_proxies(proxy_manager: Arc<ProxyManager>) {
+    tauri::async_runtime::spawn(async move {
+        duckcoding::auto_start_proxies(&proxy_manager).await;
+    });
+}
+
+/// 应用启动时刻（initialize_app 开始执行时）
+static STARTED_AT: Lazy<Instant> = Lazy::new(Instant::now);
+
+/// 距应用启动经过的时间（用于记录启动耗时）
+pub fn elapsed_since_start() -> Duration {
+    STARTED_AT.elapsed()
 }
 
 /// 执行所有启动初始化任务
 ///
 /// 按顺序执行：日志 → Profile → 迁移 → 工具注册表 → 代理管理器
 pub async fn initialize_app() -> Result<InitializationContext, Box<dyn std::error::Error>> {
+    Lazy::force(&STARTED_AT);
+
     // 1. 初始化日志
     init_logging()?;
 
-    // 2. 初始化内置 Profile
+    // 2. 应用持久化写入设置（需在任何写入之前）
+    apply_durable_writes_setting();
+    apply_backend_locale();
+
+    // 3. 初始化内置 Profile
     if let Err(e) = initialize_proxy_profiles() {
         tracing::warn!(error = ?e, "初始化内置 Profile 失败");
     }
 
-    // 3. 执行数据迁移
+    // 4. 执行数据迁移
     run_migrations().await?;
 
-    // 4. 创建工具注册表
+    // 5. 创建工具注册表
     let tool_registry = ToolRegistry::new().await.expect("无法创建工具注册表");
 
-    // 5. 创建 ProfileManager 单例
+    // 6. 创建 ProfileManager 单例
     let profile_manager = Arc::new(tokio::sync::RwLock::new(
         ProfileManager::new().expect("初始化 ProfileManager 失败"),
     ));
 
-    // 6. 创建代理管理器并异步启动自启动代理
+    // 7. 创建代理管理器（自启动在 Tauri setup 阶段执行）
     let proxy_manager = Arc::new(ProxyManager::new());
-    let proxy_manager_for_auto_start = proxy_manager.clone();
-    let profile_manager_for_auto_start = profile_manager.clone();
-    tauri::async_runtime::spawn(async move {
-        auto_start_proxies(
-            &proxy_manager_for_auto_start,
-            &profile_manager_for_auto_start,
-        )
-        .await;
-    });
+
+    // 8. 恢复代理指标并启动定期持久化
+    PROXY_METRICS.start_persistence();
+
+    // 9. 恢复上次的工具状态检测结果（冷启动无需重新检测）
+    match ToolStatusCache::default_path() {
+        Ok(path) => TOOL_STATUS_CACHE.enable_persistence(path),
+        Err(e) => tracing::warn!(error = ?e, "无法定位工具状态缓存文件"),
+    }
 
     Ok(InitializationContext {
         proxy_manager,
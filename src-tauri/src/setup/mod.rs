@@ -5,5 +5,5 @@ pub mod tray;
 pub mod initialization;
 
 // 重新导出常用函数供 main.rs 使用
-pub use initialization::initialize_app;
+pub use initialization::{elapsed_since_start, initialize_app, spawn_auto_start_proxies};
 pub use tray::focus_main_window;
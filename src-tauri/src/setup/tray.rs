@@ -1,29 +1,305 @@
+use crate::commands::dashboard_commands::DashboardManagerState;
+use crate::commands::profile_commands::ProfileManagerState;
+use crate::commands::provider_commands::ProviderManagerState;
+use crate::commands::proxy_commands::{start_tool_proxy, stop_tool_proxy, ProxyManagerState};
+use crate::commands::tool_commands::check_all_updates;
+use ::duckcoding::models::{Tool, UpdateResult};
+use ::duckcoding::services::dashboard_manager::subscribe_dashboard_changes;
+use ::duckcoding::services::provider_manager::subscribe_provider_changes;
+use ::duckcoding::services::proxy_config_manager::ProxyConfigManager;
+use ::duckcoding::services::tool::TOOL_STATUS_CACHE;
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Manager, Runtime, WebviewWindow,
 };
+use tokio::sync::broadcast;
+
+const TRAY_ID: &str = "duckcoding-tray";
+
+/// 代理开关菜单项 ID 前缀（后接工具 ID）
+const PROXY_TOGGLE_PREFIX: &str = "proxy_toggle:";
+
+/// 托盘请求前端切换页面
+const TRAY_OPEN_PAGE_EVENT: &str = "tray-open-page";
+
+/// 系统托盘是否创建成功（Linux 无托盘宿主时为 false）
+static TRAY_AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// 各工具是否有可用更新（由启动后的更新检查与托盘「检查更新」写入）
+static TOOL_UPDATES: Lazy<Mutex<BTreeMap<String, bool>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// 托盘菜单展示的状态
+#[derive(Debug, Clone, Default)]
+pub struct TraySnapshot {
+    pub proxies: Vec<TrayProxy>,
+    /// 仪表板当前选择的供应商名称
+    pub provider_name: Option<String>,
+    pub tools: Vec<TrayTool>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayProxy {
+    pub tool_id: String,
+    pub tool_name: String,
+    pub running: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrayTool {
+    pub name: String,
+    /// `None` 表示尚未检测
+    pub installed: Option<bool>,
+    pub version: Option<String>,
+    pub update_available: bool,
+}
+
+/// 系统托盘是否可用
+pub fn tray_available() -> bool {
+    TRAY_AVAILABLE.load(Ordering::Relaxed)
+}
+
+fn proxy_label(proxy: &TrayProxy) -> String {
+    let state = if proxy.running {
+        "运行中"
+    } else {
+        "已停止"
+    };
+    format!("透明代理 · {}：{state}", proxy.tool_name)
+}
+
+fn tool_label(tool: &TrayTool) -> String {
+    let mut label = match (tool.installed, &tool.version) {
+        (None, _) => format!("{}：检测中", tool.name),
+        (Some(false), _) => format!("{}：未安装", tool.name),
+        (Some(true), Some(version)) => format!("{} {version}", tool.name),
+        (Some(true), None) => tool.name.clone(),
+    };
+    if tool.update_available {
+        label.push_str(" · 有可用更新");
+    }
+    label
+}
 
 /// 创建系统托盘菜单
-pub fn create_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
-    let show_item = MenuItem::with_id(app, "show", "显示窗口", true, None::<&str>)?;
-    let check_update_item = MenuItem::with_id(app, "check_update", "检查更新", true, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+pub fn create_tray_menu<R: Runtime>(
+    app: &AppHandle<R>,
+    snapshot: &TraySnapshot,
+) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "show",
+        "显示窗口",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "open_dashboard",
+        "打开仪表板",
+        true,
+        None::<&str>,
+    )?)?;
 
-    let menu = Menu::with_items(
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    if snapshot.proxies.is_empty() {
+        menu.append(&MenuItem::with_id(
+            app,
+            "proxy_none",
+            "透明代理：未启用",
+            false,
+            None::<&str>,
+        )?)?;
+    }
+    for proxy in &snapshot.proxies {
+        menu.append(&CheckMenuItem::with_id(
+            app,
+            format!("{PROXY_TOGGLE_PREFIX}{}", proxy.tool_id),
+            proxy_label(proxy),
+            true,
+            proxy.running,
+            None::<&str>,
+        )?)?;
+    }
+    let provider = snapshot.provider_name.as_deref().unwrap_or("未选择");
+    menu.append(&MenuItem::with_id(
         app,
-        &[
-            &show_item,
-            &PredefinedMenuItem::separator(app)?,
-            &check_update_item,
-            &PredefinedMenuItem::separator(app)?,
-            &quit_item,
-        ],
-    )?;
+        "active_provider",
+        format!("当前供应商：{provider}"),
+        false,
+        None::<&str>,
+    )?)?;
+
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    for tool in &snapshot.tools {
+        menu.append(&MenuItem::with_id(
+            app,
+            format!("tool:{}", tool.name),
+            tool_label(tool),
+            false,
+            None::<&str>,
+        )?)?;
+    }
 
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "check_update",
+        "检查更新",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?)?;
     Ok(menu)
 }
 
+/// 记录工具更新检查结果（已跳过的版本不标记）
+pub fn record_tool_updates(results: &[UpdateResult]) {
+    let mut updates = TOOL_UPDATES.lock().unwrap();
+    for result in results {
+        if let Some(tool_id) = &result.tool_id {
+            updates.insert(tool_id.clone(), result.has_update && !result.skipped);
+        }
+    }
+}
+
+/// 汇总托盘菜单所需的状态
+async fn collect_snapshot<R: Runtime>(app: &AppHandle<R>) -> TraySnapshot {
+    let proxy_manager = app.state::<ProxyManagerState>().manager.clone();
+    let proxy_configs = ProxyConfigManager::new()
+        .map_err(|e| tracing::warn!(error = ?e, "读取代理配置失败"))
+        .ok();
+    let updates = TOOL_UPDATES.lock().unwrap().clone();
+
+    let mut snapshot = TraySnapshot {
+        provider_name: selected_provider_name(app),
+        ..Default::default()
+    };
+    for tool in Tool::all() {
+        let running = proxy_manager.is_running(&tool.id).await;
+        let enabled = proxy_configs
+            .as_ref()
+            .and_then(|mgr| mgr.get_config(&tool.id).ok().flatten())
+            .is_some_and(|config| config.enabled);
+        if running || enabled {
+            snapshot.proxies.push(TrayProxy {
+                tool_id: tool.id.clone(),
+                tool_name: tool.name.clone(),
+                running,
+            });
+        }
+
+        let status = TOOL_STATUS_CACHE.get_status(&tool.id);
+        snapshot.tools.push(TrayTool {
+            name: tool.name.clone(),
+            installed: status.as_ref().map(|s| s.installed),
+            version: status.and_then(|s| s.version),
+            update_available: updates.get(&tool.id).copied().unwrap_or(false),
+        });
+    }
+    snapshot
+}
+
+fn selected_provider_name<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    let provider_id = app
+        .state::<DashboardManagerState>()
+        .manager
+        .get_selected_provider_id()
+        .ok()
+        .flatten()?;
+    app.state::<ProviderManagerState>()
+        .manager
+        .get_provider(&provider_id)
+        .ok()
+        .flatten()
+        .map(|provider| provider.name)
+}
+
+/// 按最新状态重建托盘菜单（托盘不可用时忽略）
+pub fn refresh_tray_menu<R: Runtime>(app: &AppHandle<R>) {
+    if !tray_available() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let snapshot = collect_snapshot(&app).await;
+        let Some(tray) = app.tray_by_id(TRAY_ID) else {
+            return;
+        };
+        match create_tray_menu(&app, &snapshot) {
+            Ok(menu) => {
+                if let Err(e) = tray.set_menu(Some(menu)) {
+                    tracing::warn!(error = ?e, "更新托盘菜单失败");
+                }
+            }
+            Err(e) => tracing::warn!(error = ?e, "创建托盘菜单失败"),
+        }
+    });
+}
+
+/// 工具状态、供应商、仪表板、代理状态变化时刷新托盘菜单
+fn spawn_refresh_listeners<R: Runtime>(app: &AppHandle<R>) {
+    refresh_on(app, TOOL_STATUS_CACHE.subscribe());
+    refresh_on(app, subscribe_provider_changes());
+    refresh_on(app, subscribe_dashboard_changes());
+    refresh_on(app, app.state::<ProxyManagerState>().manager.subscribe());
+}
+
+fn refresh_on<R: Runtime, T: Clone + Send + 'static>(
+    app: &AppHandle<R>,
+    mut events: broadcast::Receiver<T>,
+) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => refresh_tray_menu(&app),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// 切换工具的透明代理（复用启动 / 停止代理命令，含 Profile 切换与回滚）
+async fn toggle_proxy<R: Runtime>(app: AppHandle<R>, tool_id: String) {
+    let running = app
+        .state::<ProxyManagerState>()
+        .manager
+        .is_running(&tool_id)
+        .await;
+    let result = if running {
+        stop_tool_proxy(tool_id.clone(), app.state(), app.state()).await
+    } else {
+        start_tool_proxy(tool_id.clone(), app.state(), app.state()).await
+    };
+    match result {
+        Ok(message) => tracing::info!(tool_id = %tool_id, message = %message, "托盘切换代理"),
+        Err(e) => tracing::error!(tool_id = %tool_id, error = %e, "托盘切换代理失败"),
+    }
+    refresh_tray_menu(&app);
+}
+
+/// 检查应用与工具更新：前端弹出应用更新对话框，工具更新结果写入托盘标记
+async fn check_updates_now<R: Runtime>(app: AppHandle<R>) {
+    if let Err(e) = app.emit("request-check-update", ()) {
+        tracing::error!(error = ?e, "发送更新检查事件失败");
+    }
+    match check_all_updates().await {
+        Ok(results) => {
+            record_tool_updates(&results);
+            refresh_tray_menu(&app);
+        }
+        Err(e) => tracing::warn!(error = %e, "托盘检查工具更新失败"),
+    }
+}
+
 /// 聚焦主窗口
 pub fn focus_main_window<R: Runtime>(app: &AppHandle<R>) {
     if let Some(window) = app.get_webview_window("main") {
@@ -86,6 +362,14 @@ pub fn restore_window_state<R: Runtime>(window: &WebviewWindow<R>) {
 
 /// 隐藏窗口到系统托盘
 pub fn hide_window_to_tray<R: Runtime>(window: &WebviewWindow<R>) {
+    if !tray_available() {
+        // 没有托盘时隐藏窗口将无法找回，改为最小化
+        tracing::info!("系统托盘不可用，最小化窗口");
+        if let Err(e) = window.minimize() {
+            tracing::error!(error = ?e, "最小化窗口失败");
+        }
+        return;
+    }
     tracing::info!("隐藏窗口到系统托盘");
     if let Err(e) = window.hide() {
         tracing::error!(error = ?e, "隐藏窗口失败");
@@ -110,11 +394,13 @@ pub fn hide_window_to_tray<R: Runtime>(window: &WebviewWindow<R>) {
 }
 
 /// 设置系统托盘（包含事件处理）
+///
+/// 托盘创建失败（如 Linux 桌面没有 AppIndicator 宿主）时只记录警告，应用照常运行。
 pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
-    let tray_menu = create_tray_menu(app.handle())?;
+    let tray_menu = create_tray_menu(app.handle(), &TraySnapshot::default())?;
     let app_handle2 = app.handle().clone();
 
-    let _tray = TrayIconBuilder::new()
+    let builder = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&tray_menu)
         .show_menu_on_left_click(false)
@@ -125,18 +411,25 @@ pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
                     tracing::info!("从托盘显示窗口");
                     focus_main_window(app);
                 }
+                "open_dashboard" => {
+                    focus_main_window(app);
+                    if let Err(e) = app.emit(TRAY_OPEN_PAGE_EVENT, "dashboard") {
+                        tracing::error!(error = ?e, "发送打开仪表板事件失败");
+                    }
+                }
                 "check_update" => {
                     tracing::info!("从托盘请求检查更新");
-                    // 发送检查更新事件到前端
-                    if let Err(e) = app.emit("request-check-update", ()) {
-                        tracing::error!(error = ?e, "发送更新检查事件失败");
-                    }
+                    tauri::async_runtime::spawn(check_updates_now(app.clone()));
                 }
                 "quit" => {
                     tracing::info!("从托盘退出应用");
                     app.exit(0);
                 }
-                _ => {}
+                id => {
+                    if let Some(tool_id) = id.strip_prefix(PROXY_TOGGLE_PREFIX) {
+                        tauri::async_runtime::spawn(toggle_proxy(app.clone(), tool_id.to_string()));
+                    }
+                }
             }
         })
         .on_tray_icon_event(move |_tray, event| {
@@ -154,8 +447,26 @@ pub fn setup_system_tray<R: Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
                     // 不打印太多日志
                 }
             }
-        })
-        .build(app)?;
+        });
+
+    // Linux 缺少 AppIndicator 库时底层会 panic，这里按不可用处理
+    #[cfg(target_os = "linux")]
+    let built = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| builder.build(app)))
+        .unwrap_or_else(|_| {
+            Err(tauri::Error::Io(std::io::Error::other(
+                "未找到 AppIndicator 运行库",
+            )))
+        });
+    #[cfg(not(target_os = "linux"))]
+    let built = builder.build(app);
+
+    if let Err(e) = built {
+        tracing::warn!(error = ?e, "系统托盘不可用，已跳过托盘功能");
+        return Ok(());
+    }
+    TRAY_AVAILABLE.store(true, Ordering::Relaxed);
+    spawn_refresh_listeners(app.handle());
+    refresh_tray_menu(app.handle());
 
     Ok(())
 }
@@ -185,3 +496,49 @@ pub fn setup_window_close_handler<R: Runtime>(app: &tauri::App<R>) -> tauri::Res
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_label() {
+        let mut tool = TrayTool {
+            name: "Codex".to_string(),
+            installed: None,
+            version: None,
+            update_available: false,
+        };
+        assert_eq!(tool_label(&tool), "Codex：检测中");
+        tool.installed = Some(false);
+        assert_eq!(tool_label(&tool), "Codex：未安装");
+        tool.installed = Some(true);
+        tool.version = Some("0.46.0".to_string());
+        tool.update_available = true;
+        assert_eq!(tool_label(&tool), "Codex 0.46.0 · 有可用更新");
+    }
+
+    #[test]
+    fn test_record_tool_updates_ignores_skipped() {
+        let result = |tool_id: &str, has_update: bool, skipped: bool| UpdateResult {
+            success: true,
+            message: String::new(),
+            has_update,
+            current_version: None,
+            latest_version: None,
+            mirror_version: None,
+            mirror_is_stale: None,
+            tool_id: Some(tool_id.to_string()),
+            skipped,
+            download_size: None,
+            duration_ms: None,
+        };
+        record_tool_updates(&[
+            result("tray-test-a", true, false),
+            result("tray-test-b", true, true),
+        ]);
+        let updates = TOOL_UPDATES.lock().unwrap();
+        assert_eq!(updates.get("tray-test-a"), Some(&true));
+        assert_eq!(updates.get("tray-test-b"), Some(&false));
+    }
+}
@@ -1,26 +1,115 @@
+use super::command_output::{
+    cap_text, decode_output, detect_issues, read_lines, CapturedOutput, OutputDecoding,
+    OutputIssue, OutputLine, OutputStream, DEFAULT_OUTPUT_LIMIT,
+};
 use super::platform::PlatformInfo;
+use super::shell_env::captured_shell_env;
+use std::collections::{BTreeMap, HashMap};
 use std::io;
-use std::process::{Command, Output};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+/// 版本检查、which 等探测命令的默认超时
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 安装、更新等长时间命令的默认超时
+pub const INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
 /// 命令执行结果
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CommandResult {
     pub success: bool,
     pub stdout: String,
     pub stderr: String,
     pub exit_code: Option<i32>,
+    /// 是否因超时被终止
+    pub timed_out: bool,
+    /// 是否被取消
+    pub cancelled: bool,
+    /// 输出使用的解码方式
+    pub decoding: OutputDecoding,
+    /// 输出是否超过上限被截断（保留开头与结尾）
+    pub truncated: bool,
+    /// 在完整输出（截断前）中识别出的常见错误
+    pub issues: Vec<OutputIssue>,
 }
 
 impl CommandResult {
     pub fn from_output(output: Output) -> Self {
+        Self::from_output_with_limit(output, DEFAULT_OUTPUT_LIMIT)
+    }
+
+    /// 从完整输出构建结果，每个流最多保留 `limit` 字节
+    pub fn from_output_with_limit(output: Output, limit: usize) -> Self {
+        let (stdout, stdout_decoding) = decode_output(&output.stdout);
+        let (stderr, stderr_decoding) = decode_output(&output.stderr);
+
+        let mut issues = Vec::new();
+        detect_issues(&stdout, &mut issues);
+        detect_issues(&stderr, &mut issues);
+        let (stdout, stdout_truncated) = cap_text(stdout.trim(), limit);
+        let (stderr, stderr_truncated) = cap_text(stderr.trim(), limit);
+
         CommandResult {
             success: output.status.success(),
-            stdout: String::from_utf8_lossy(&output.stdout).trim().to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            stdout,
+            stderr,
             exit_code: output.status.code(),
+            timed_out: false,
+            cancelled: false,
+            decoding: stdout_decoding.merge(stderr_decoding),
+            truncated: stdout_truncated || stderr_truncated,
+            issues,
+        }
+    }
+
+    /// 失败详情：stderr（为空时用 stdout），附带识别出的错误建议
+    pub fn failure_detail(&self) -> String {
+        let mut detail = if self.stderr.is_empty() {
+            self.stdout.clone()
+        } else {
+            self.stderr.clone()
+        };
+        for issue in &self.issues {
+            detail.push_str("\n\n💡 ");
+            detail.push_str(issue.hint());
+        }
+        detail
+    }
+
+    /// 成功结果（退出码 0）
+    pub fn ok(stdout: impl Into<String>) -> Self {
+        CommandResult {
+            success: true,
+            stdout: stdout.into(),
+            stderr: String::new(),
+            exit_code: Some(0),
+            timed_out: false,
+            cancelled: false,
+            decoding: OutputDecoding::Utf8,
+            truncated: false,
+            issues: Vec::new(),
+        }
+    }
+
+    /// 失败结果（指定退出码与 stderr）
+    pub fn failure(exit_code: i32, stderr: impl Into<String>) -> Self {
+        CommandResult {
+            success: false,
+            stdout: String::new(),
+            stderr: stderr.into(),
+            exit_code: Some(exit_code),
+            timed_out: false,
+            cancelled: false,
+            decoding: OutputDecoding::Utf8,
+            truncated: false,
+            issues: Vec::new(),
         }
     }
 
@@ -30,23 +119,209 @@ impl CommandResult {
             stdout: String::new(),
             stderr: error.to_string(),
             exit_code: None,
+            timed_out: false,
+            cancelled: false,
+            decoding: OutputDecoding::Utf8,
+            truncated: false,
+            issues: Vec::new(),
+        }
+    }
+
+    /// 超时或取消导致子进程被终止
+    fn interrupted(timed_out: bool, message: String) -> Self {
+        CommandResult {
+            success: false,
+            stdout: String::new(),
+            stderr: message,
+            exit_code: None,
+            timed_out,
+            cancelled: !timed_out,
+            decoding: OutputDecoding::Utf8,
+            truncated: false,
+            issues: Vec::new(),
         }
     }
 }
 
 /// 命令执行器
+///
+/// 超时与取消只作用于异步方法：触发后终止整个进程树（Unix 为进程组，Windows 为 `taskkill /T`）。
+/// 环境变量与工作目录只作用于本执行器启动的子进程，不修改当前进程的环境。
 #[derive(Clone)]
 pub struct CommandExecutor {
     platform: PlatformInfo,
+    timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+    /// 注入的环境变量（None 表示移除该变量）
+    envs: BTreeMap<String, Option<String>>,
+    current_dir: Option<PathBuf>,
+    clear_env: bool,
+    /// 每个输出流保留的最大字节数
+    output_limit: usize,
+    /// 是否合并本次会话捕获的 shell 环境（见 `shell_env`）
+    shell_env: bool,
 }
 
 impl CommandExecutor {
     pub fn new() -> Self {
         CommandExecutor {
             platform: PlatformInfo::current(),
+            timeout: None,
+            cancel: None,
+            envs: BTreeMap::new(),
+            current_dir: None,
+            clear_env: false,
+            output_limit: DEFAULT_OUTPUT_LIMIT,
+            shell_env: true,
         }
     }
 
+    /// 返回设置了超时的执行器副本
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..self.clone()
+        }
+    }
+
+    /// 返回绑定了取消令牌的执行器副本（令牌取消后终止正在运行的子进程）
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        Self {
+            cancel: Some(token),
+            ..self.clone()
+        }
+    }
+
+    /// 返回注入了环境变量的执行器副本
+    ///
+    /// 设置 `PATH` 会替换默认的增强 PATH
+    pub fn env(&self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let mut executor = self.clone();
+        executor.envs.insert(key.into(), Some(value.into()));
+        executor
+    }
+
+    /// 返回批量注入环境变量的执行器副本
+    pub fn envs<K, V>(&self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut executor = self.clone();
+        for (key, value) in vars {
+            executor.envs.insert(key.into(), Some(value.into()));
+        }
+        executor
+    }
+
+    /// 返回移除了指定环境变量的执行器副本（子进程不继承该变量）
+    pub fn env_remove(&self, key: impl Into<String>) -> Self {
+        let mut executor = self.clone();
+        executor.envs.insert(key.into(), None);
+        executor
+    }
+
+    /// 返回指定工作目录的执行器副本
+    pub fn current_dir(&self, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            current_dir: Some(dir.into()),
+            ..self.clone()
+        }
+    }
+
+    /// 返回是否清空继承环境的执行器副本
+    ///
+    /// 清空后子进程只能看到注入的变量与 PATH
+    pub fn clear_env(&self, clear: bool) -> Self {
+        Self {
+            clear_env: clear,
+            ..self.clone()
+        }
+    }
+
+    /// 返回设置了输出上限的执行器副本（每个流，字节）
+    ///
+    /// 超出上限时保留开头与结尾各一半，流式回调在超出后只收到一条省略标记
+    pub fn with_output_limit(&self, limit: usize) -> Self {
+        Self {
+            output_limit: limit,
+            ..self.clone()
+        }
+    }
+
+    /// 返回是否合并 shell 环境的执行器副本（默认合并；未捕获时无影响）
+    pub fn with_shell_env(&self, enabled: bool) -> Self {
+        Self {
+            shell_env: enabled,
+            ..self.clone()
+        }
+    }
+
+    /// 子进程实际使用的 PATH（诊断用）
+    pub fn effective_path(&self) -> String {
+        self.base_path()
+    }
+
+    /// 需要合并的 shell 环境
+    fn shell_env(&self) -> Option<&'static HashMap<String, String>> {
+        self.shell_env.then(captured_shell_env).flatten()
+    }
+
+    /// 子进程使用的 PATH：显式注入的优先，否则为（shell PATH +）增强 PATH
+    fn base_path(&self) -> String {
+        match self.envs.get("PATH") {
+            Some(Some(path)) => path.clone(),
+            _ => match self.shell_env().and_then(|env| env.get("PATH")) {
+                Some(shell_path) => self
+                    .platform
+                    .merge_path_entries(
+                        self.platform
+                            .split_path(shell_path)
+                            .into_iter()
+                            .chain(self.platform.enhanced_path_entries(false)),
+                        false,
+                    )
+                    .join(self.platform.path_separator()),
+                None => self.platform.build_enhanced_path(),
+            },
+        }
+    }
+
+    /// 把环境变量与工作目录应用到子进程
+    fn apply_env(&self, command: &mut Command, path_env: &str) {
+        if self.clear_env {
+            command.env_clear();
+        } else if let Some(shell_env) = self.shell_env() {
+            for (key, value) in shell_env {
+                if key != "PATH" && !self.envs.contains_key(key) {
+                    command.env(key, value);
+                }
+            }
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        command.env("PATH", path_env);
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+    }
+
+    /// 构建子进程并应用环境
+    fn prepare(&self, build: &impl Fn(&str) -> Command, path_env: &str) -> Command {
+        let mut command = build(path_env);
+        self.apply_env(&mut command, path_env);
+        command
+    }
+
+    /// 探测类命令使用的执行器：未显式设置超时时使用 `PROBE_TIMEOUT`
+    fn probe(&self) -> Self {
+        self.with_timeout(self.timeout.unwrap_or(PROBE_TIMEOUT))
+    }
+
     /// 执行命令（使用增强的 PATH）
     ///
     /// 智能重试策略：
@@ -54,79 +329,275 @@ impl CommandExecutor {
     /// 2. 如果失败且 exit code = 127（命令未找到），尝试扫描安装器
     /// 3. 将安装器目录加入 PATH 后重试
     pub fn execute(&self, command_str: &str) -> CommandResult {
-        let enhanced_path = self.platform.build_enhanced_path();
+        let tool_path = command_str.split_whitespace().next().unwrap_or("");
+        self.run_with_retry(tool_path, |_| self.shell_command(command_str))
+    }
 
-        // 第一次尝试
-        let result = self.execute_with_path(command_str, &enhanced_path);
+    /// 执行命令（异步，支持超时与取消）
+    pub async fn execute_async(&self, command_str: &str) -> CommandResult {
+        self.execute_shell_streaming(command_str, |_| {}).await
+    }
+
+    /// 经 shell 执行命令并逐行回调输出（异步，支持超时与取消）
+    pub async fn execute_shell_streaming(
+        &self,
+        command_str: &str,
+        mut on_line: impl FnMut(OutputLine) + Send,
+    ) -> CommandResult {
+        let tool_path = command_str.split_whitespace().next().unwrap_or("");
+        self.run_with_retry_async(tool_path, |_| self.shell_command(command_str), &mut on_line)
+            .await
+    }
+
+    /// 以程序 + 参数列表执行命令（不经过 shell）
+    ///
+    /// 参数原样传给子进程，路径中的空格、引号、中文或 shell 元字符都不会被解释。
+    /// Windows 上 `.cmd` / `.bat` 脚本（如 npm 的 shim）只能经 `cmd /C` 启动，会按 cmd 规则转义参数。
+    /// 需要管道、重定向等 shell 语义的场景（如官方安装脚本）继续使用 `execute`。
+    pub fn execute_args(&self, program: &str, args: &[&str]) -> CommandResult {
+        self.run_with_retry(program, |path| self.build_args_command(program, args, path))
+    }
 
-        // 如果是 127 错误（命令未找到），尝试扫描安装器并重试
-        if !result.success && result.exit_code == Some(127) {
+    /// 以程序 + 参数列表执行命令（异步，不经过 shell，支持超时与取消）
+    pub async fn execute_args_async(&self, program: &str, args: &[&str]) -> CommandResult {
+        self.execute_streaming(program, args, |_| {}).await
+    }
+
+    /// 以程序 + 参数列表执行命令并逐行回调输出（异步，不经过 shell）
+    ///
+    /// 输出按行实时回调（已去掉 `\n` / `\r\n`，超长行会被切分），
+    /// 返回的结果仍包含累积的完整输出（每个流有上限）与退出码
+    pub async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        mut on_line: impl FnMut(OutputLine) + Send,
+    ) -> CommandResult {
+        self.run_with_retry_async(
+            program,
+            |path| self.build_args_command(program, args, path),
+            &mut on_line,
+        )
+        .await
+    }
+
+    /// 127 通常是 shebang 解释器（如 node）不在 PATH 中，扫描安装器后值得重试
+    fn should_retry(tool_path: &str, result: &CommandResult) -> bool {
+        let retry = !result.success && result.exit_code == Some(127);
+        if retry {
             tracing::warn!(
                 "命令执行失败 (exit 127): {}，尝试扫描安装器后重试",
-                command_str
+                tool_path
             );
+        }
+        retry
+    }
+
+    fn run_with_retry(&self, tool_path: &str, build: impl Fn(&str) -> Command) -> CommandResult {
+        let base_path = self.base_path();
 
-            if let Some(extended_path) =
-                self.scan_installer_and_extend_path(command_str, &enhanced_path)
-            {
+        let result = self.output_blocking(self.prepare(&build, &base_path));
+
+        if Self::should_retry(tool_path, &result) {
+            if let Some(extended_path) = self.extend_path_for_tool(tool_path, &base_path) {
                 tracing::info!("扫描到安装器路径，使用扩展 PATH 重试: {}", extended_path);
-                return self.execute_with_path(command_str, &extended_path);
+                return self.output_blocking(self.prepare(&build, &extended_path));
             }
         }
 
         result
     }
 
-    /// 使用指定的 PATH 执行命令
-    fn execute_with_path(&self, command_str: &str, path_env: &str) -> CommandResult {
-        let output = if self.platform.is_windows {
-            #[cfg(target_os = "windows")]
-            {
-                Command::new("cmd")
-                    .args(["/C", command_str])
-                    .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                    .env("PATH", path_env)
-                    .output()
+    async fn run_with_retry_async(
+        &self,
+        tool_path: &str,
+        build: impl Fn(&str) -> Command,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        let base_path = self.base_path();
+
+        let result = self
+            .output_async(self.prepare(&build, &base_path), on_line)
+            .await;
+
+        if Self::should_retry(tool_path, &result) {
+            if let Some(extended_path) = self.extend_path_for_tool(tool_path, &base_path) {
+                tracing::info!("扫描到安装器路径，使用扩展 PATH 重试: {}", extended_path);
+                return self
+                    .output_async(self.prepare(&build, &extended_path), on_line)
+                    .await;
             }
-            #[cfg(not(target_os = "windows"))]
-            {
-                Command::new("cmd")
-                    .args(["/C", command_str])
-                    .env("PATH", path_env)
-                    .output()
+        }
+
+        result
+    }
+
+    fn output_blocking(&self, mut command: Command) -> CommandResult {
+        command.stdin(Stdio::null());
+        match command.output() {
+            Ok(output) => CommandResult::from_output_with_limit(output, self.output_limit),
+            Err(e) => CommandResult::from_error(e),
+        }
+    }
+
+    /// 异步执行子进程，逐行回调输出；超时或取消时终止整个进程树
+    ///
+    /// 返回的结果包含累积（有上限）的输出；被终止时保留已读到的部分输出
+    async fn output_async(
+        &self,
+        command: Command,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        let mut command = tokio::process::Command::from(command);
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        // 独立进程组，超时后可以连同孙进程（如 npm 派生的 node）一起终止
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => return CommandResult::from_error(e),
+        };
+        let pid = child.id();
+
+        let (tx, mut rx) = mpsc::channel(256);
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(read_lines(stdout, OutputStream::Stdout, tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(read_lines(stderr, OutputStream::Stderr, tx.clone()));
+        }
+        drop(tx);
+
+        let timeout = async {
+            match self.timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        let cancelled = async {
+            match &self.cancel {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(timeout, cancelled);
+
+        let mut captured = CapturedOutput::new(self.output_limit);
+        let mut pipes_open = true;
+        // Ok: 进程正常退出；Err(true): 超时；Err(false): 取消
+        let outcome = loop {
+            tokio::select! {
+                line = rx.recv(), if pipes_open => match line {
+                    Some(raw) => {
+                        if let Some(line) = captured.push_raw(raw) {
+                            on_line(line);
+                        }
+                    }
+                    None => pipes_open = false,
+                },
+                // 先读完管道再等待退出，避免遗漏尾部输出
+                status = child.wait(), if !pipes_open => break Ok(status),
+                _ = &mut timeout => break Err(true),
+                _ = &mut cancelled => break Err(false),
             }
-        } else {
-            Command::new("sh")
-                .args(["-c", command_str])
-                .env("PATH", path_env)
-                .output()
         };
 
-        match output {
-            Ok(output) => CommandResult::from_output(output),
+        let status = match outcome {
+            Ok(status) => status,
+            Err(timed_out) => {
+                let message = if timed_out {
+                    let secs = self.timeout.unwrap_or_default().as_secs();
+                    tracing::warn!(pid, timeout_secs = secs, "命令执行超时，终止进程树");
+                    format!("命令执行超时（{secs}秒），已终止")
+                } else {
+                    tracing::info!(pid, "命令已取消，终止进程树");
+                    "命令已取消".to_string()
+                };
+                kill_process_tree(pid).await;
+                while let Ok(raw) = rx.try_recv() {
+                    captured.push_raw(raw);
+                }
+                let (stdout, _, truncated) = captured.finish();
+                let mut result = CommandResult::interrupted(timed_out, message);
+                result.stdout = stdout.trim().to_string();
+                result.decoding = captured.decoding;
+                result.truncated = truncated;
+                result.issues = captured.issues;
+                return result;
+            }
+        };
+
+        let (stdout, stderr, truncated) = captured.finish();
+        match status {
+            Ok(status) => CommandResult {
+                success: status.success(),
+                stdout: stdout.trim().to_string(),
+                stderr: stderr.trim().to_string(),
+                exit_code: status.code(),
+                timed_out: false,
+                cancelled: false,
+                decoding: captured.decoding,
+                truncated,
+                issues: captured.issues,
+            },
             Err(e) => CommandResult::from_error(e),
         }
     }
 
-    /// 扫描安装器并扩展 PATH
-    ///
-    /// 从命令字符串中提取工具路径，扫描安装器，返回扩展后的 PATH
+    /// 构建经 shell 执行的子进程（环境由 `apply_env` 统一设置）
+    fn shell_command(&self, command_str: &str) -> Command {
+        if self.platform.is_windows {
+            let mut command = Command::new("cmd");
+            command.args(["/C", command_str]);
+            #[cfg(target_os = "windows")]
+            command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.args(["-c", command_str]);
+            command
+        }
+    }
+
+    /// 构建不经过 shell 的子进程（`path_env` 用于 Windows 上解析程序位置）
+    #[cfg(target_os = "windows")]
+    fn build_args_command(&self, program: &str, args: &[&str], path_env: &str) -> Command {
+        // .cmd/.bat 同样交给标准库启动：它按 cmd 规则转义参数（含 `%VAR%`），
+        // 无法安全传递的参数直接报错，而不是交给 cmd 解释
+        let resolved = resolve_windows_program(program, path_env);
+        let mut command = Command::new(&resolved);
+        command.args(args);
+        command.creation_flags(0x08000000); // CREATE_NO_WINDOW
+        command
+    }
+
+    /// 构建不经过 shell 的子进程
+    #[cfg(not(target_os = "windows"))]
+    fn build_args_command(&self, program: &str, args: &[&str], _path_env: &str) -> Command {
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    }
+
+    /// 扫描工具对应的安装器，返回把安装器目录加到最前的 PATH
     ///
     /// # 参数
-    /// - command_str: 命令字符串（如 "/usr/local/bin/gemini --version"）
+    /// - tool_path: 工具路径（如 "/usr/local/bin/gemini"）
     /// - base_path: 基础 PATH
     ///
     /// # 返回
     /// - Some(String): 扩展后的 PATH
-    /// - None: 未找到安装器或无法提取路径
-    fn scan_installer_and_extend_path(&self, command_str: &str, base_path: &str) -> Option<String> {
+    /// - None: 未找到安装器或不是绝对路径
+    fn extend_path_for_tool(&self, tool_path: &str, base_path: &str) -> Option<String> {
         use crate::utils::scan_installer_paths;
         use std::collections::HashSet;
 
-        // 1. 从命令字符串中提取工具路径（第一个词）
-        let tool_path = command_str.split_whitespace().next()?;
-
-        // 仅处理绝对路径（以 / 或 C:\ 开头）
+        // 1. 仅处理绝对路径（以 / 或 C:\ 开头）
         if !tool_path.starts_with('/') && !tool_path.contains(":\\") {
             return None;
         }
@@ -172,37 +643,22 @@ impl CommandExecutor {
         ))
     }
 
-    /// 执行命令（异步）
-    pub async fn execute_async(&self, command_str: &str) -> CommandResult {
-        let command_str = command_str.to_string();
-        let platform = self.platform.clone();
-
-        tokio::task::spawn_blocking(move || {
-            let executor = CommandExecutor { platform };
-            executor.execute(&command_str)
-        })
-        .await
-        .unwrap_or_else(|e| CommandResult {
-            success: false,
-            stdout: String::new(),
-            stderr: format!("任务执行失败: {e}"),
-            exit_code: None,
-        })
-    }
-
     /// 检查命令是否存在
     pub fn command_exists(&self, command: &str) -> bool {
         // 从命令字符串中提取命令名（第一个词）
         // 例如: "claude --version" -> "claude"
         let cmd_name = command.split_whitespace().next().unwrap_or(command);
 
-        let check_cmd = if self.platform.is_windows {
-            format!("where {cmd_name}")
-        } else {
-            format!("which {cmd_name}")
-        };
+        self.execute_args(self.which_program(), &[cmd_name]).success
+    }
 
-        self.execute(&check_cmd).success
+    /// 查找命令路径所用的程序（where / which）
+    fn which_program(&self) -> &'static str {
+        if self.platform.is_windows {
+            "where"
+        } else {
+            "which"
+        }
     }
 
     /// 检查命令是否存在（异步）
@@ -211,20 +667,17 @@ impl CommandExecutor {
         // 例如: "claude --version" -> "claude"
         let cmd_name = command.split_whitespace().next().unwrap_or(command);
 
-        let check_cmd = if self.platform.is_windows {
-            format!("where {cmd_name}")
-        } else {
-            format!("which {cmd_name}")
-        };
+        let which = self.which_program();
 
         tracing::info!(
-            "检查命令是否存在: command={}, cmd_name={}, check_cmd={}",
+            "检查命令是否存在: command={}, cmd_name={}, check_cmd={} {}",
             command,
             cmd_name,
-            check_cmd
+            which,
+            cmd_name
         );
 
-        let result = self.execute_async(&check_cmd).await;
+        let result = self.probe().execute_args_async(which, &[cmd_name]).await;
 
         tracing::info!(
             "命令检查结果: command={}, cmd_name={}, success={}, stdout={:?}, stderr={:?}",
@@ -245,6 +698,51 @@ impl Default for CommandExecutor {
     }
 }
 
+/// 终止进程及其所有子进程
+async fn kill_process_tree(pid: Option<u32>) {
+    let Some(pid) = pid else {
+        return;
+    };
+
+    #[cfg(unix)]
+    let result = tokio::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .status()
+        .await;
+
+    #[cfg(target_os = "windows")]
+    let result = tokio::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .status()
+        .await;
+
+    if let Err(e) = result {
+        tracing::warn!(pid, error = %e, "终止进程树失败");
+    }
+}
+
+/// 在 PATH 中解析 Windows 程序（补全 .exe / .cmd / .bat 扩展名）
+///
+/// `Command::new("npm")` 在 Windows 上只会查找 npm.exe，找不到 npm.cmd 这类 shim
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn resolve_windows_program(program: &str, path_env: &str) -> PathBuf {
+    let candidate = Path::new(program);
+    if candidate.extension().is_some() || candidate.components().count() > 1 {
+        return candidate.to_path_buf();
+    }
+
+    for dir in path_env.split(';').filter(|d| !d.is_empty()) {
+        for ext in ["exe", "cmd", "bat"] {
+            let path = Path::new(dir).join(format!("{program}.{ext}"));
+            if path.is_file() {
+                return path;
+            }
+        }
+    }
+    candidate.to_path_buf()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,4 +777,258 @@ mod tests {
 
         assert!(result.success);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_args_without_shell() {
+        let executor = CommandExecutor::new();
+
+        // shell 元字符原样传递，不会被解释
+        let result = executor
+            .execute_args_async("echo", &["$HOME", "a;b", "'q'"])
+            .await;
+        assert!(result.success);
+        assert_eq!(result.stdout, "$HOME a;b 'q'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_args_with_special_paths() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("带 空格 \"引号\" 目录");
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("工具 cli");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\nfor a in \"$@\"; do echo \"[$a]\"; done\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let executor = CommandExecutor::new();
+        let result = executor.execute_args(
+            script.to_str().unwrap(),
+            &["--version", "a b", "say \"hi\"", "中文参数"],
+        );
+
+        assert!(result.success, "stderr: {}", result.stderr);
+        assert_eq!(
+            result.stdout,
+            "[--version]\n[a b]\n[say \"hi\"]\n[中文参数]"
+        );
+    }
+
+    #[test]
+    fn test_missing_program_reports_error() {
+        let executor = CommandExecutor::new();
+        let result = executor.execute_args("/nonexistent/路径 with space/tool", &["--version"]);
+        assert!(!result.success);
+        assert!(!result.stderr.is_empty());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_batch_script_args_not_interpreted() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let script = temp.path().join("shim.cmd");
+        std::fs::write(&script, "@echo off\r\necho [%1]\r\n").unwrap();
+
+        let marker = temp.path().join("injected.txt");
+        let arg = format!("%PATH% & echo injected > \"{}\" & \"", marker.display());
+        let result = CommandExecutor::new().execute_args(script.to_str().unwrap(), &[&arg]);
+
+        // 标准库无法安全传递时拒绝启动；启动时参数原样到达脚本，不会被 cmd 解释
+        assert!(!marker.exists(), "argument was executed by cmd");
+        if result.success {
+            assert!(
+                result.stdout.contains("%PATH%"),
+                "stdout: {}",
+                result.stdout
+            );
+            let path = std::env::var("PATH").unwrap_or_default();
+            if let Some(first) = path.split(';').find(|d| !d.is_empty()) {
+                assert!(
+                    !result.stdout.contains(first),
+                    "PATH leaked: {}",
+                    result.stdout
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_windows_program_keeps_explicit_paths() {
+        assert_eq!(
+            resolve_windows_program(r"C:\Program Files\nodejs\npm.cmd", ""),
+            PathBuf::from(r"C:\Program Files\nodejs\npm.cmd")
+        );
+        assert_eq!(resolve_windows_program("npm", ""), PathBuf::from("npm"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_kills_process_tree() {
+        let executor = CommandExecutor::new().with_timeout(Duration::from_millis(300));
+
+        let started = std::time::Instant::now();
+        // 子 shell 再派生 sleep，验证孙进程也被终止（否则 wait 会等到管道关闭）
+        let result = executor.execute_async("sleep 30 & sleep 30; wait").await;
+
+        assert!(result.timed_out);
+        assert!(!result.cancelled);
+        assert!(!result.success);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_cancellation_kills_child() {
+        let token = CancellationToken::new();
+        let executor = CommandExecutor::new().with_cancellation(token.clone());
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            token.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = executor.execute_args_async("sleep", &["30"]).await;
+        canceller.await.unwrap();
+
+        assert!(result.cancelled);
+        assert!(!result.timed_out);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_fast_command_unaffected_by_timeout() {
+        let executor = CommandExecutor::new().with_timeout(Duration::from_secs(10));
+        let result = executor.execute_async("echo done").await;
+        assert!(result.success);
+        assert!(!result.timed_out);
+        assert!(result.stdout.contains("done"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_streaming_delivers_lines() {
+        let executor = CommandExecutor::new();
+        let mut lines = Vec::new();
+        let result = executor
+            .execute_streaming(
+                "sh",
+                &[
+                    "-c",
+                    "printf 'one\\r\\ntwo\\n'; echo err >&2; printf tail; exit 3",
+                ],
+                |line| lines.push(line),
+            )
+            .await;
+
+        let stdout: Vec<&str> = lines
+            .iter()
+            .filter(|l| l.stream == OutputStream::Stdout)
+            .map(|l| l.line.as_str())
+            .collect();
+        assert_eq!(stdout, vec!["one", "two", "tail"]);
+        assert!(lines
+            .iter()
+            .any(|l| l.stream == OutputStream::Stderr && l.line == "err"));
+
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stdout, "one\ntwo\ntail");
+        assert_eq!(result.stderr, "err");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_injected_env_and_current_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = CommandExecutor::new()
+            .env("DUCKCODING_TEST_VAR", "injected")
+            .envs([("npm_config_registry", "https://registry.npmmirror.com")])
+            .current_dir(dir.path());
+
+        let result = executor
+            .execute_args_async(
+                "sh",
+                &[
+                    "-c",
+                    "echo $DUCKCODING_TEST_VAR; echo $npm_config_registry; pwd -P",
+                ],
+            )
+            .await;
+        assert!(result.success, "stderr: {}", result.stderr);
+
+        let lines: Vec<&str> = result.stdout.lines().collect();
+        assert_eq!(lines[0], "injected");
+        assert_eq!(lines[1], "https://registry.npmmirror.com");
+        assert_eq!(
+            Path::new(lines[2]),
+            dir.path().canonicalize().unwrap().as_path()
+        );
+        // 只作用于子进程
+        assert!(std::env::var("DUCKCODING_TEST_VAR").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_clear_env_keeps_path() {
+        let executor = CommandExecutor::new()
+            .clear_env(true)
+            .env("ONLY_VAR", "1")
+            .env_remove("HOME");
+        let result = executor.execute_args_async("env", &[]).await;
+        assert!(result.success, "stderr: {}", result.stderr);
+
+        let mut keys: Vec<&str> = result
+            .stdout
+            .lines()
+            .filter_map(|line| line.split('=').next())
+            .collect();
+        keys.sort();
+        assert_eq!(keys, vec!["ONLY_VAR", "PATH"]);
+    }
+
+    #[test]
+    fn test_injected_path_replaces_enhanced_path() {
+        let executor = CommandExecutor::new();
+        assert_eq!(
+            executor.base_path(),
+            executor.platform.build_enhanced_path()
+        );
+        assert_eq!(
+            executor.env("PATH", "/custom/bin").base_path(),
+            "/custom/bin"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_output_limit_truncates_and_keeps_issues() {
+        let executor = CommandExecutor::new().with_output_limit(4096);
+        let mut streamed = 0;
+        let result = executor
+            .execute_streaming(
+                "sh",
+                &[
+                    "-c",
+                    "echo 'npm ERR! code EACCES' >&2; i=0; while [ $i -lt 2000 ]; do echo \"line $i\"; i=$((i+1)); done",
+                ],
+                |line| streamed += line.line.len() + 1,
+            )
+            .await;
+
+        assert!(result.success);
+        assert!(result.truncated);
+        assert!(result.stdout.len() < 4096 + 64);
+        assert!(result.stdout.starts_with("line 0\n"));
+        assert!(result.stdout.ends_with("line 1999"));
+        assert!(streamed < 4096 + 64);
+        assert_eq!(result.issues, vec![OutputIssue::PermissionDenied]);
+        assert!(result.failure_detail().contains("💡"));
+    }
 }
@@ -0,0 +1,565 @@
+// 子进程输出的逐行切分与累积
+//
+// 流式读取 stdout/stderr 时按行回调，分块可能在任意位置截断：
+// - 兼容 `\n` 与 `\r\n` 换行
+// - 单行超过 `MAX_LINE_BYTES` 时强制切分，避免无换行的输出无限占用内存
+// - 累积的完整输出有上限，超出时保留开头与结尾，中间以标记代替
+// - 常见错误特征（EACCES、ECONNRESET 等）在截断前逐行识别，不受上限影响
+// - 先按 UTF-8 解码，失败时按系统代码页（Windows 中文环境为 GBK）解码，最后有损转换
+
+use encoding_rs::Encoding;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc;
+
+/// 单行最大字节数，超出时强制切分
+pub const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// 每个输出流默认累积的最大字节数
+pub const DEFAULT_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+/// 输出流
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// 一行输出（不含换行符）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputLine {
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// 输出使用的解码方式（多行混合时取最差的一种）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputDecoding {
+    /// 合法的 UTF-8
+    #[default]
+    Utf8,
+    /// 按系统代码页解码（编码名称，如 "GBK"）
+    CodePage(&'static str),
+    /// 有损转换，非法字节被替换为 U+FFFD
+    Lossy,
+}
+
+impl OutputDecoding {
+    fn rank(self) -> u8 {
+        match self {
+            OutputDecoding::Utf8 => 0,
+            OutputDecoding::CodePage(_) => 1,
+            OutputDecoding::Lossy => 2,
+        }
+    }
+
+    /// 合并两种解码方式，保留较差的一种
+    pub fn merge(self, other: OutputDecoding) -> OutputDecoding {
+        if other.rank() > self.rank() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+/// 系统 ANSI 代码页对应的编码（非 Windows 或代码页为 UTF-8 时为 None）
+static SYSTEM_ENCODING: Lazy<Option<&'static Encoding>> = Lazy::new(detect_system_encoding);
+
+#[cfg(target_os = "windows")]
+fn detect_system_encoding() -> Option<&'static Encoding> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetACP() -> u32;
+    }
+    // SAFETY: GetACP 没有参数，只读取进程的代码页设置
+    let code_page = unsafe { GetACP() };
+    encoding_for_code_page(code_page)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn detect_system_encoding() -> Option<&'static Encoding> {
+    None
+}
+
+/// Windows 代码页编号到编码的映射（只覆盖常见的 ANSI 代码页）
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn encoding_for_code_page(code_page: u32) -> Option<&'static Encoding> {
+    let encoding = match code_page {
+        936 => encoding_rs::GBK,
+        54936 => encoding_rs::GB18030,
+        950 => encoding_rs::BIG5,
+        932 => encoding_rs::SHIFT_JIS,
+        949 => encoding_rs::EUC_KR,
+        874 => encoding_rs::WINDOWS_874,
+        1250 => encoding_rs::WINDOWS_1250,
+        1251 => encoding_rs::WINDOWS_1251,
+        1252 => encoding_rs::WINDOWS_1252,
+        1253 => encoding_rs::WINDOWS_1253,
+        1254 => encoding_rs::WINDOWS_1254,
+        1255 => encoding_rs::WINDOWS_1255,
+        1256 => encoding_rs::WINDOWS_1256,
+        1257 => encoding_rs::WINDOWS_1257,
+        1258 => encoding_rs::WINDOWS_1258,
+        _ => return None,
+    };
+    Some(encoding)
+}
+
+/// 解码子进程输出：UTF-8 → 系统代码页 → 有损转换
+pub fn decode_output(bytes: &[u8]) -> (String, OutputDecoding) {
+    decode_with_fallback(bytes, *SYSTEM_ENCODING)
+}
+
+fn decode_with_fallback(
+    bytes: &[u8],
+    fallback: Option<&'static Encoding>,
+) -> (String, OutputDecoding) {
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (text.to_string(), OutputDecoding::Utf8);
+    }
+    if let Some(encoding) = fallback {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return (text.into_owned(), OutputDecoding::CodePage(encoding.name()));
+        }
+    }
+    (
+        String::from_utf8_lossy(bytes).into_owned(),
+        OutputDecoding::Lossy,
+    )
+}
+
+/// 增量切分字节流为行
+#[derive(Debug, Default)]
+pub struct LineSplitter {
+    buffer: Vec<u8>,
+}
+
+impl LineSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 输入一个分块，返回其中完整的行
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let mut lines = Vec::new();
+        for &byte in chunk {
+            if byte == b'\n' {
+                lines.push(Self::strip_cr(std::mem::take(&mut self.buffer)));
+            } else {
+                self.buffer.push(byte);
+                if self.buffer.len() >= MAX_LINE_BYTES {
+                    lines.push(std::mem::take(&mut self.buffer));
+                }
+            }
+        }
+        lines
+    }
+
+    /// 流结束时取出最后一行（没有结尾换行的部分）
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        let rest = Self::strip_cr(std::mem::take(&mut self.buffer));
+        (!rest.is_empty()).then_some(rest)
+    }
+
+    fn strip_cr(mut line: Vec<u8>) -> Vec<u8> {
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        line
+    }
+}
+
+/// 未解码的一行输出
+#[derive(Debug)]
+pub struct RawLine {
+    pub stream: OutputStream,
+    pub bytes: Vec<u8>,
+}
+
+/// 按块读取输出流，切分为行后发送到通道
+///
+/// 接收端关闭时提前结束；读取出错视同流结束
+pub async fn read_lines<R: AsyncRead + Unpin>(
+    mut reader: R,
+    stream: OutputStream,
+    tx: mpsc::Sender<RawLine>,
+) {
+    let to_line = |bytes: Vec<u8>| RawLine { stream, bytes };
+
+    let mut splitter = LineSplitter::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        for line in splitter.feed(&buf[..n]) {
+            if tx.send(to_line(line)).await.is_err() {
+                return;
+            }
+        }
+    }
+    if let Some(line) = splitter.finish() {
+        let _ = tx.send(to_line(line)).await;
+    }
+}
+
+/// 输出中识别出的常见错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputIssue {
+    /// 权限不足（EACCES / EPERM）
+    PermissionDenied,
+    /// 网络错误（ECONNRESET / ETIMEDOUT 等）
+    NetworkError,
+}
+
+impl OutputIssue {
+    const PERMISSION_PATTERNS: [&'static str; 5] = [
+        "EACCES",
+        "EPERM",
+        "permission denied",
+        "access is denied",
+        "拒绝访问",
+    ];
+
+    const NETWORK_PATTERNS: [&'static str; 6] = [
+        "ECONNRESET",
+        "ETIMEDOUT",
+        "ECONNREFUSED",
+        "ENOTFOUND",
+        "EAI_AGAIN",
+        "socket hang up",
+    ];
+
+    /// 识别一行输出中的错误特征
+    pub fn detect(line: &str) -> Option<OutputIssue> {
+        let lower = line.to_lowercase();
+        let matches = |patterns: &[&str]| {
+            patterns
+                .iter()
+                .any(|p| line.contains(p) || lower.contains(&p.to_lowercase()))
+        };
+        if matches(&Self::PERMISSION_PATTERNS) {
+            Some(OutputIssue::PermissionDenied)
+        } else if matches(&Self::NETWORK_PATTERNS) {
+            Some(OutputIssue::NetworkError)
+        } else {
+            None
+        }
+    }
+
+    /// 面向用户的处理建议
+    pub fn hint(&self) -> &'static str {
+        match self {
+            OutputIssue::PermissionDenied => {
+                "权限不足：请检查 npm 全局目录的写入权限，或以管理员身份运行"
+            }
+            OutputIssue::NetworkError => "网络连接异常：请检查网络或代理设置后重试",
+        }
+    }
+}
+
+/// 单个流的累积缓冲：保留开头与结尾各一半上限，中间超出部分只计数
+#[derive(Debug)]
+struct StreamBuffer {
+    limit: usize,
+    head: String,
+    tail: VecDeque<String>,
+    tail_bytes: usize,
+    dropped_bytes: usize,
+    streamed_bytes: usize,
+    stream_suppressed: bool,
+}
+
+impl StreamBuffer {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            head: String::new(),
+            tail: VecDeque::new(),
+            tail_bytes: 0,
+            dropped_bytes: 0,
+            streamed_bytes: 0,
+            stream_suppressed: false,
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        let bytes = line.len() + 1;
+        let head_limit = self.limit / 2;
+        if self.tail.is_empty() && self.head.len() + bytes <= head_limit {
+            if !self.head.is_empty() {
+                self.head.push('\n');
+            }
+            self.head.push_str(line);
+            return;
+        }
+
+        self.tail.push_back(line.to_string());
+        self.tail_bytes += bytes;
+        while self.tail_bytes > self.limit - head_limit {
+            let Some(dropped) = self.tail.pop_front() else {
+                break;
+            };
+            self.tail_bytes -= dropped.len() + 1;
+            self.dropped_bytes += dropped.len() + 1;
+        }
+    }
+
+    /// 流式回调是否还在上限内；首次超出时返回省略标记
+    fn admit_stream(&mut self, line: &str) -> StreamAdmission {
+        if self.stream_suppressed {
+            return StreamAdmission::Suppressed;
+        }
+        self.streamed_bytes += line.len() + 1;
+        if self.streamed_bytes <= self.limit {
+            StreamAdmission::Emit
+        } else {
+            self.stream_suppressed = true;
+            StreamAdmission::Marker
+        }
+    }
+
+    fn finish(&mut self) -> (String, bool) {
+        let mut text = std::mem::take(&mut self.head);
+        let truncated = self.dropped_bytes > 0;
+        if truncated {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("… {} bytes truncated …", self.dropped_bytes));
+        }
+        for line in self.tail.drain(..) {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&line);
+        }
+        (text, truncated)
+    }
+}
+
+enum StreamAdmission {
+    Emit,
+    Marker,
+    Suppressed,
+}
+
+/// 按上限截断一段文本（保留开头与结尾），返回截断后的文本与是否截断
+pub fn cap_text(text: &str, limit: usize) -> (String, bool) {
+    let mut buffer = StreamBuffer::new(limit);
+    for line in text.lines() {
+        buffer.push(line);
+    }
+    buffer.finish()
+}
+
+/// 识别整段文本中的错误特征（去重，按首次出现排序）
+pub fn detect_issues(text: &str, issues: &mut Vec<OutputIssue>) {
+    for issue in text.lines().filter_map(OutputIssue::detect) {
+        if !issues.contains(&issue) {
+            issues.push(issue);
+        }
+    }
+}
+
+/// 累积的输出（每个流最多保留 `limit` 字节）
+#[derive(Debug)]
+pub struct CapturedOutput {
+    stdout: StreamBuffer,
+    stderr: StreamBuffer,
+    pub decoding: OutputDecoding,
+    pub issues: Vec<OutputIssue>,
+}
+
+impl CapturedOutput {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            stdout: StreamBuffer::new(limit),
+            stderr: StreamBuffer::new(limit),
+            decoding: OutputDecoding::default(),
+            issues: Vec::new(),
+        }
+    }
+
+    fn buffer(&mut self, stream: OutputStream) -> &mut StreamBuffer {
+        match stream {
+            OutputStream::Stdout => &mut self.stdout,
+            OutputStream::Stderr => &mut self.stderr,
+        }
+    }
+
+    /// 解码一行原始输出并累积
+    ///
+    /// 返回需要流式回调的行；该流回调的字节数超过上限后只返回一次省略标记，之后返回 None
+    pub fn push_raw(&mut self, raw: RawLine) -> Option<OutputLine> {
+        let (line, decoding) = decode_output(&raw.bytes);
+        self.decoding = self.decoding.merge(decoding);
+        self.push(raw.stream, &line);
+
+        match self.buffer(raw.stream).admit_stream(&line) {
+            StreamAdmission::Emit => Some(OutputLine {
+                stream: raw.stream,
+                line,
+            }),
+            StreamAdmission::Marker => Some(OutputLine {
+                stream: raw.stream,
+                line: "… 输出过多，后续内容已省略 …".to_string(),
+            }),
+            StreamAdmission::Suppressed => None,
+        }
+    }
+
+    /// 累积一行（截断前先识别错误特征）
+    pub fn push(&mut self, stream: OutputStream, line: &str) {
+        if let Some(issue) = OutputIssue::detect(line) {
+            if !self.issues.contains(&issue) {
+                self.issues.push(issue);
+            }
+        }
+        self.buffer(stream).push(line);
+    }
+
+    /// 取出累积的 stdout、stderr 以及是否发生截断
+    pub fn finish(&mut self) -> (String, String, bool) {
+        let (stdout, stdout_truncated) = self.stdout.finish();
+        let (stderr, stderr_truncated) = self.stderr.finish();
+        (stdout, stderr, stdout_truncated || stderr_truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_partial_chunks_and_crlf() {
+        let mut splitter = LineSplitter::new();
+        let mut lines = Vec::new();
+        for chunk in [&b"hel"[..], b"lo\r", b"\nwor", b"ld\n\npartial"] {
+            lines.extend(splitter.feed(chunk));
+        }
+        lines.extend(splitter.finish());
+
+        let lines: Vec<String> = lines
+            .into_iter()
+            .map(|l| String::from_utf8(l).unwrap())
+            .collect();
+        assert_eq!(lines, vec!["hello", "world", "", "partial"]);
+        assert!(splitter.finish().is_none());
+    }
+
+    #[test]
+    fn test_long_line_is_split() {
+        let mut splitter = LineSplitter::new();
+        let lines = splitter.feed(&vec![b'x'; MAX_LINE_BYTES * 2 + 10]);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.len() == MAX_LINE_BYTES));
+        assert_eq!(splitter.finish().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_decode_gbk_output() {
+        // "错误: 权限不足" 的 GBK 编码
+        let (gbk, _, _) = encoding_rs::GBK.encode("错误: 权限不足");
+        assert!(std::str::from_utf8(&gbk).is_err());
+
+        let (text, decoding) = decode_with_fallback(&gbk, Some(encoding_rs::GBK));
+        assert_eq!(text, "错误: 权限不足");
+        assert_eq!(decoding, OutputDecoding::CodePage("GBK"));
+
+        // 没有可用代码页时退化为有损转换，不会报错或返回空串
+        let (text, decoding) = decode_with_fallback(&gbk, None);
+        assert_eq!(decoding, OutputDecoding::Lossy);
+        assert!(text.starts_with('\u{FFFD}') && text.contains(": "));
+
+        let (text, decoding) =
+            decode_with_fallback("v1.2.3 中文".as_bytes(), Some(encoding_rs::GBK));
+        assert_eq!(text, "v1.2.3 中文");
+        assert_eq!(decoding, OutputDecoding::Utf8);
+    }
+
+    #[test]
+    fn test_code_page_mapping_and_merge() {
+        assert_eq!(encoding_for_code_page(936), Some(encoding_rs::GBK));
+        assert_eq!(encoding_for_code_page(65001), None);
+
+        let gbk = OutputDecoding::CodePage("GBK");
+        assert_eq!(OutputDecoding::Utf8.merge(gbk), gbk);
+        assert_eq!(gbk.merge(OutputDecoding::Utf8), gbk);
+        assert_eq!(gbk.merge(OutputDecoding::Lossy), OutputDecoding::Lossy);
+    }
+
+    #[test]
+    fn test_captured_output_keeps_head_and_tail() {
+        let limit = 1000;
+        let mut captured = CapturedOutput::new(limit);
+        for i in 0..1000 {
+            captured.push(OutputStream::Stdout, &format!("line {i:04}"));
+        }
+        captured.push(OutputStream::Stderr, "short");
+
+        let (stdout, stderr, truncated) = captured.finish();
+        assert!(truncated);
+        assert!(stdout.len() <= limit + 64);
+        assert!(stdout.starts_with("line 0000\nline 0001"));
+        assert!(stdout.ends_with("line 0999"));
+        assert!(stdout.contains("bytes truncated …"));
+        assert_eq!(stderr, "short");
+
+        let (text, truncated) = cap_text("a\nb", limit);
+        assert_eq!(text, "a\nb");
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_streamed_lines_respect_limit() {
+        let mut captured = CapturedOutput::new(100);
+        let emitted: Vec<OutputLine> = (0..50)
+            .filter_map(|i| {
+                captured.push_raw(RawLine {
+                    stream: OutputStream::Stdout,
+                    bytes: format!("line {i}").into_bytes(),
+                })
+            })
+            .collect();
+
+        let total: usize = emitted.iter().map(|l| l.line.len() + 1).sum();
+        assert!(total < 200);
+        assert!(emitted.last().unwrap().line.contains("已省略"));
+    }
+
+    #[test]
+    fn test_issues_detected_before_truncation() {
+        let mut captured = CapturedOutput::new(200);
+        captured.push(
+            OutputStream::Stderr,
+            "npm ERR! code EACCES npm ERR! syscall mkdir",
+        );
+        for _ in 0..100 {
+            captured.push(OutputStream::Stderr, "npm verb noise noise noise");
+        }
+        captured.push(OutputStream::Stderr, "npm ERR! errno ECONNRESET");
+        for _ in 0..100 {
+            captured.push(OutputStream::Stderr, "npm verb more noise");
+        }
+
+        let (_, stderr, truncated) = captured.finish();
+        assert!(truncated);
+        assert!(!stderr.contains("ECONNRESET"));
+        assert_eq!(
+            captured.issues,
+            vec![OutputIssue::PermissionDenied, OutputIssue::NetworkError]
+        );
+        assert_eq!(
+            OutputIssue::detect("Error: Access is denied."),
+            Some(OutputIssue::PermissionDenied)
+        );
+        assert_eq!(OutputIssue::detect("added 1 package"), None);
+    }
+}
@@ -0,0 +1,374 @@
+// Command Runner - 可替换的命令执行接口
+//
+// 工具检测、安装、更新等子进程调用统一依赖 CommandRunner：
+// - 正式运行使用 CommandExecutor（真实子进程）
+// - 测试使用 MockExecutor，按命令匹配返回预设结果并记录调用，不启动任何进程
+
+use super::command::{CommandExecutor, CommandResult};
+use super::command_output::{OutputLine, OutputStream};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 命令执行接口
+///
+/// 方法语义与 `CommandExecutor` 的同名方法一致；
+/// `with_timeout` / `envs` / `env_remove` 返回新的执行器，不修改自身
+#[async_trait]
+pub trait CommandRunner: Send + Sync {
+    /// 设置异步执行的超时
+    fn with_timeout(&self, timeout: Duration) -> Arc<dyn CommandRunner>;
+
+    /// 向子进程注入环境变量（如实例级环境变量覆盖）
+    fn envs(&self, vars: &BTreeMap<String, String>) -> Arc<dyn CommandRunner>;
+
+    /// 从子进程环境中移除变量
+    fn env_remove(&self, key: &str) -> Arc<dyn CommandRunner>;
+
+    /// 经 shell 执行命令（同步）
+    fn execute(&self, command: &str) -> CommandResult;
+
+    /// 经 shell 执行命令（异步）
+    async fn execute_async(&self, command: &str) -> CommandResult {
+        let mut ignore = |_: OutputLine| {};
+        self.execute_shell_streaming(command, &mut ignore).await
+    }
+
+    /// 经 shell 执行命令并逐行回调输出
+    async fn execute_shell_streaming(
+        &self,
+        command: &str,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult;
+
+    /// 以程序 + 参数列表执行命令（同步，不经过 shell）
+    fn execute_args(&self, program: &str, args: &[&str]) -> CommandResult;
+
+    /// 以程序 + 参数列表执行命令（异步，不经过 shell）
+    async fn execute_args_async(&self, program: &str, args: &[&str]) -> CommandResult {
+        let mut ignore = |_: OutputLine| {};
+        self.execute_streaming(program, args, &mut ignore).await
+    }
+
+    /// 以程序 + 参数列表执行命令并逐行回调输出
+    async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult;
+
+    /// 检查命令是否存在（which / where）
+    async fn command_exists_async(&self, command: &str) -> bool {
+        let cmd_name = command.split_whitespace().next().unwrap_or(command);
+        self.execute_args_async(which_program(), &[cmd_name])
+            .await
+            .success
+    }
+}
+
+/// 查找命令路径所用的程序（where / which）
+fn which_program() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "where"
+    } else {
+        "which"
+    }
+}
+
+#[async_trait]
+impl CommandRunner for CommandExecutor {
+    fn with_timeout(&self, timeout: Duration) -> Arc<dyn CommandRunner> {
+        Arc::new(CommandExecutor::with_timeout(self, timeout))
+    }
+
+    fn envs(&self, vars: &BTreeMap<String, String>) -> Arc<dyn CommandRunner> {
+        Arc::new(CommandExecutor::envs(self, vars.clone()))
+    }
+
+    fn env_remove(&self, key: &str) -> Arc<dyn CommandRunner> {
+        Arc::new(CommandExecutor::env_remove(self, key))
+    }
+
+    fn execute(&self, command: &str) -> CommandResult {
+        CommandExecutor::execute(self, command)
+    }
+
+    async fn execute_async(&self, command: &str) -> CommandResult {
+        CommandExecutor::execute_async(self, command).await
+    }
+
+    async fn execute_shell_streaming(
+        &self,
+        command: &str,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        CommandExecutor::execute_shell_streaming(self, command, on_line).await
+    }
+
+    fn execute_args(&self, program: &str, args: &[&str]) -> CommandResult {
+        CommandExecutor::execute_args(self, program, args)
+    }
+
+    async fn execute_args_async(&self, program: &str, args: &[&str]) -> CommandResult {
+        CommandExecutor::execute_args_async(self, program, args).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        CommandExecutor::execute_streaming(self, program, args, on_line).await
+    }
+
+    async fn command_exists_async(&self, command: &str) -> bool {
+        CommandExecutor::command_exists_async(self, command).await
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    /// (命令模式, 返回结果)，后添加的规则优先
+    rules: Vec<(String, CommandResult)>,
+    /// 已执行的命令行（按顺序）
+    calls: Vec<String>,
+    /// 每次执行时注入的环境变量（与 `calls` 一一对应）
+    call_envs: Vec<BTreeMap<String, String>>,
+}
+
+/// 脚本化的命令执行器（用于测试）
+///
+/// 命令行按 `program arg1 arg2`（shell 命令为原始字符串）与规则匹配：
+/// 模式以 `*` 结尾时按前缀匹配，否则要求完全相同。
+/// 未匹配的命令返回退出码 127（命令不存在）。
+/// `with_timeout` / `envs` / `env_remove` 返回的执行器与原执行器共享规则和调用记录。
+#[derive(Clone, Default)]
+pub struct MockExecutor {
+    state: Arc<Mutex<MockState>>,
+    /// 通过 `envs` 注入的环境变量（只记录，不影响匹配）
+    envs: BTreeMap<String, String>,
+}
+
+impl MockExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加规则：匹配 `pattern` 的命令返回 `result`
+    pub fn on(self, pattern: impl Into<String>, result: CommandResult) -> Self {
+        self.state
+            .lock()
+            .unwrap()
+            .rules
+            .push((pattern.into(), result));
+        self
+    }
+
+    /// 添加规则：匹配 `pattern` 的命令成功并输出 `stdout`
+    pub fn on_success(self, pattern: impl Into<String>, stdout: impl Into<String>) -> Self {
+        self.on(pattern, CommandResult::ok(stdout))
+    }
+
+    /// 声明命令存在：which / where 返回 `path`
+    pub fn with_command(self, name: &str, path: &str) -> Self {
+        self.on_success(format!("{} {}", which_program(), name), path)
+    }
+
+    /// 声明命令不存在：which / where 失败
+    pub fn without_command(self, name: &str) -> Self {
+        self.on(
+            format!("{} {}", which_program(), name),
+            CommandResult::failure(1, ""),
+        )
+    }
+
+    /// 已执行的命令行（按顺序）
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// 是否执行过匹配 `pattern` 的命令
+    pub fn called(&self, pattern: &str) -> bool {
+        self.calls()
+            .iter()
+            .any(|call| pattern_matches(pattern, call))
+    }
+
+    /// 最近一次匹配 `pattern` 的命令执行时注入的环境变量
+    pub fn env_of(&self, pattern: &str) -> Option<BTreeMap<String, String>> {
+        let state = self.state.lock().unwrap();
+        state
+            .calls
+            .iter()
+            .zip(&state.call_envs)
+            .rev()
+            .find(|(call, _)| pattern_matches(pattern, call))
+            .map(|(_, envs)| envs.clone())
+    }
+
+    fn run(&self, command_line: String) -> CommandResult {
+        let mut state = self.state.lock().unwrap();
+        let result = state
+            .rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pattern_matches(pattern, &command_line))
+            .map(|(_, result)| result.clone())
+            .unwrap_or_else(|| {
+                CommandResult::failure(127, format!("command not found: {command_line}"))
+            });
+        state.calls.push(command_line);
+        state.call_envs.push(self.envs.clone());
+        result
+    }
+
+    fn run_streaming(
+        &self,
+        command_line: String,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        let result = self.run(command_line);
+        for line in result.stdout.lines() {
+            on_line(OutputLine {
+                stream: OutputStream::Stdout,
+                line: line.to_string(),
+            });
+        }
+        for line in result.stderr.lines() {
+            on_line(OutputLine {
+                stream: OutputStream::Stderr,
+                line: line.to_string(),
+            });
+        }
+        result
+    }
+}
+
+fn pattern_matches(pattern: &str, command_line: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => command_line.starts_with(prefix),
+        None => pattern == command_line,
+    }
+}
+
+fn args_command_line(program: &str, args: &[&str]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().copied())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[async_trait]
+impl CommandRunner for MockExecutor {
+    fn with_timeout(&self, _timeout: Duration) -> Arc<dyn CommandRunner> {
+        Arc::new(self.clone())
+    }
+
+    fn envs(&self, vars: &BTreeMap<String, String>) -> Arc<dyn CommandRunner> {
+        let mut executor = self.clone();
+        executor.envs.extend(vars.clone());
+        Arc::new(executor)
+    }
+
+    fn env_remove(&self, _key: &str) -> Arc<dyn CommandRunner> {
+        Arc::new(self.clone())
+    }
+
+    fn execute(&self, command: &str) -> CommandResult {
+        self.run(command.to_string())
+    }
+
+    async fn execute_shell_streaming(
+        &self,
+        command: &str,
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        self.run_streaming(command.to_string(), on_line)
+    }
+
+    fn execute_args(&self, program: &str, args: &[&str]) -> CommandResult {
+        self.run(args_command_line(program, args))
+    }
+
+    async fn execute_streaming(
+        &self,
+        program: &str,
+        args: &[&str],
+        on_line: &mut (dyn FnMut(OutputLine) + Send),
+    ) -> CommandResult {
+        self.run_streaming(args_command_line(program, args), on_line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_matches_exact_and_prefix() {
+        let mock = MockExecutor::new()
+            .on_success("claude --version", "2.0.61 (Claude Code)")
+            .on("npm list -g *", CommandResult::failure(1, "empty"));
+
+        let result = mock.execute_args_async("claude", &["--version"]).await;
+        assert!(result.success);
+        assert_eq!(result.stdout, "2.0.61 (Claude Code)");
+
+        let result = mock.execute_async("npm list -g @openai/codex").await;
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(1));
+
+        let result = mock.execute_async("codex --version").await;
+        assert_eq!(result.exit_code, Some(127));
+
+        assert_eq!(
+            mock.calls(),
+            vec![
+                "claude --version",
+                "npm list -g @openai/codex",
+                "codex --version"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_later_rule_wins_and_state_is_shared() {
+        let mock = MockExecutor::new()
+            .on_success("node *", "old")
+            .on_success("node --version", "v22.0.0");
+
+        let derived = mock
+            .with_timeout(Duration::from_secs(1))
+            .env_remove("HTTP_PROXY");
+        assert_eq!(
+            derived
+                .execute_args_async("node", &["--version"])
+                .await
+                .stdout,
+            "v22.0.0"
+        );
+        assert!(mock.called("node --version"));
+    }
+
+    #[tokio::test]
+    async fn test_mock_command_exists_and_streaming() {
+        let mock = MockExecutor::new()
+            .with_command("npm", "/usr/local/bin/npm")
+            .on_success("npm install *", "line 1\nline 2");
+
+        assert!(mock.command_exists_async("npm").await);
+        assert!(!mock.command_exists_async("brew").await);
+
+        let mut lines = Vec::new();
+        let result = mock
+            .execute_shell_streaming("npm install -g @openai/codex", &mut |line: OutputLine| {
+                lines.push(line.line)
+            })
+            .await;
+        assert!(result.success);
+        assert_eq!(lines, vec!["line 1", "line 2"]);
+    }
+}
@@ -1,21 +1,84 @@
-use crate::data::DataManager;
+use crate::data::{BackupPolicy, DataManager};
 use crate::GlobalConfig;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// DuckCoding 配置目录 (~/.duckcoding)，若不存在则创建
-pub fn config_dir() -> Result<PathBuf, String> {
-    if let Ok(override_dir) = std::env::var("DUCKCODING_CONFIG_DIR") {
-        let path = PathBuf::from(override_dir);
-        if !path.exists() {
-            fs::create_dir_all(&path)
-                .map_err(|e| format!("Failed to create config directory: {e}"))?;
+/// 便携模式标记文件（放在可执行文件旁）
+pub const PORTABLE_MARKER: &str = "portable";
+
+/// 便携模式的数据目录（可执行文件旁）
+const PORTABLE_DATA_DIR: &str = "data";
+
+/// 配置目录的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigDirSource {
+    /// 环境变量 DUCKCODING_CONFIG_DIR
+    Env,
+    /// 便携模式（可执行文件旁存在 `portable` 标记）
+    Portable,
+    /// $XDG_CONFIG_HOME/duckcoding（仅 Linux）
+    Xdg,
+    /// ~/.duckcoding
+    Default,
+}
+
+/// 解析配置目录（不创建）
+///
+/// 优先级：DUCKCODING_CONFIG_DIR > 便携模式 > $XDG_CONFIG_HOME/duckcoding（Linux）> ~/.duckcoding。
+/// Linux 上已有 ~/.duckcoding 且 XDG 目录尚不存在时继续使用旧目录，避免数据“丢失”。
+pub fn resolve_config_dir() -> Result<(PathBuf, ConfigDirSource), String> {
+    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from));
+    Ok(resolve_config_dir_from(
+        std::env::var("DUCKCODING_CONFIG_DIR").ok(),
+        exe_dir.as_deref(),
+        std::env::var("XDG_CONFIG_HOME").ok(),
+        &home_dir,
+        cfg!(target_os = "linux"),
+    ))
+}
+
+fn resolve_config_dir_from(
+    env_override: Option<String>,
+    exe_dir: Option<&Path>,
+    xdg_config_home: Option<String>,
+    home_dir: &Path,
+    is_linux: bool,
+) -> (PathBuf, ConfigDirSource) {
+    if let Some(dir) = env_override.filter(|d| !d.is_empty()) {
+        return (PathBuf::from(dir), ConfigDirSource::Env);
+    }
+
+    if let Some(exe_dir) = exe_dir {
+        if exe_dir.join(PORTABLE_MARKER).is_file() {
+            return (exe_dir.join(PORTABLE_DATA_DIR), ConfigDirSource::Portable);
         }
-        return Ok(path);
     }
 
-    let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-    let config_dir = home_dir.join(".duckcoding");
+    let legacy = home_dir.join(".duckcoding");
+    if is_linux {
+        // XDG 规范要求忽略相对路径
+        let xdg = xdg_config_home
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .map(|dir| dir.join("duckcoding"));
+        if let Some(xdg) = xdg {
+            if xdg.exists() || !legacy.exists() {
+                return (xdg, ConfigDirSource::Xdg);
+            }
+        }
+    }
+
+    (legacy, ConfigDirSource::Default)
+}
+
+/// DuckCoding 配置目录（默认 ~/.duckcoding，见 `resolve_config_dir`），若不存在则创建
+pub fn config_dir() -> Result<PathBuf, String> {
+    let (config_dir, _) = resolve_config_dir()?;
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
             .map_err(|e| format!("Failed to create config directory: {e}"))?;
@@ -23,6 +86,42 @@ pub fn config_dir() -> Result<PathBuf, String> {
     Ok(config_dir)
 }
 
+/// DuckCoding 各项数据的存放位置（诊断用）
+#[derive(Debug, Clone, Serialize)]
+pub struct AppPaths {
+    pub config_dir: PathBuf,
+    pub source: ConfigDirSource,
+    pub global_config: PathBuf,
+    pub providers: PathBuf,
+    pub dashboard: PathBuf,
+    pub tool_instances: PathBuf,
+    pub sessions_db: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+/// 汇总各项数据的路径（日志目录优先使用日志配置中的自定义路径）
+pub fn app_paths() -> Result<AppPaths, String> {
+    let (config_dir, source) = resolve_config_dir()?;
+    let log_dir = read_global_config()
+        .ok()
+        .flatten()
+        .and_then(|config| config.log_config.file_path)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| config_dir.join("logs").join("app"));
+
+    Ok(AppPaths {
+        global_config: config_dir.join("config.json"),
+        providers: config_dir.join("providers.json"),
+        dashboard: config_dir.join("dashboard.json"),
+        tool_instances: config_dir.join("tools.db"),
+        sessions_db: config_dir.join("sessions.db"),
+        log_dir,
+        config_dir,
+        source,
+    })
+}
+
 /// 全局配置文件路径
 pub fn global_config_path() -> Result<PathBuf, String> {
     Ok(config_dir()?.join("config.json"))
@@ -63,8 +162,11 @@ pub fn write_global_config(config: &GlobalConfig) -> Result<(), String> {
         serde_json::to_value(config).map_err(|e| format!("Failed to serialize config: {e}"))?;
 
     manager
-        .json_uncached()
-        .write(&config_path, &config_value)
+        .write_json_with_backup(
+            &config_path,
+            &config_value,
+            &BackupPolicy::beside(&config_path),
+        )
         .map_err(|e| format!("Failed to write config: {e}"))?;
 
     Ok(())
@@ -100,4 +202,65 @@ mod tests {
         assert!(dir.ends_with("nested"));
         env::remove_var("DUCKCODING_CONFIG_DIR");
     }
+
+    #[test]
+    fn resolve_prefers_env_then_portable() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let exe_dir = temp.path().join("usb");
+        fs::create_dir_all(&exe_dir).unwrap();
+
+        let resolve = |env: Option<&str>| {
+            resolve_config_dir_from(env.map(String::from), Some(&exe_dir), None, &home, false)
+        };
+        assert_eq!(
+            resolve(None),
+            (home.join(".duckcoding"), ConfigDirSource::Default)
+        );
+
+        fs::write(exe_dir.join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(
+            resolve(None),
+            (exe_dir.join("data"), ConfigDirSource::Portable)
+        );
+        assert_eq!(
+            resolve(Some("/custom")),
+            (PathBuf::from("/custom"), ConfigDirSource::Env)
+        );
+    }
+
+    #[test]
+    fn resolve_xdg_on_linux_keeps_legacy_dir() {
+        let temp = TempDir::new().unwrap();
+        let home = temp.path().join("home");
+        let xdg = temp.path().join("xdg");
+        let xdg_value = Some(xdg.to_string_lossy().to_string());
+
+        // 没有旧目录时使用 XDG
+        assert_eq!(
+            resolve_config_dir_from(None, None, xdg_value.clone(), &home, true),
+            (xdg.join("duckcoding"), ConfigDirSource::Xdg)
+        );
+        // 非 Linux 与相对路径的 XDG 被忽略
+        assert_eq!(
+            resolve_config_dir_from(None, None, xdg_value.clone(), &home, false).1,
+            ConfigDirSource::Default
+        );
+        assert_eq!(
+            resolve_config_dir_from(None, None, Some("rel".into()), &home, true).1,
+            ConfigDirSource::Default
+        );
+
+        // 已有旧目录时继续使用，除非 XDG 目录也已存在
+        fs::create_dir_all(home.join(".duckcoding")).unwrap();
+        assert_eq!(
+            resolve_config_dir_from(None, None, xdg_value.clone(), &home, true).1,
+            ConfigDirSource::Default
+        );
+        fs::create_dir_all(xdg.join("duckcoding")).unwrap();
+        assert_eq!(
+            resolve_config_dir_from(None, None, xdg_value, &home, true).1,
+            ConfigDirSource::Xdg
+        );
+    }
 }
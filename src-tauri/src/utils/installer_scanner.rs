@@ -3,8 +3,8 @@
 // 从工具路径智能扫描安装器路径（npm、brew 等）
 
 use crate::models::InstallMethod;
-use crate::utils::PlatformInfo;
-use std::path::PathBuf;
+use crate::utils::{homebrew_prefixes, PlatformInfo};
+use std::path::{Path, PathBuf};
 
 /// 工具候选结果
 #[derive(Debug, Clone, serde::Serialize)]
@@ -104,9 +104,54 @@ pub fn scan_installer_paths(tool_path: &str) -> Vec<InstallerCandidate> {
         (c.level, type_priority)
     });
 
+    // 6. 工具位于 Homebrew Cellar 下（如 Linuxbrew）时优先使用该前缀的 brew
+    if let Some(brew) = detect_brew_install(tool_path).filter(|brew| Path::new(brew).is_file()) {
+        candidates.retain(|c| c.path != brew);
+        candidates.insert(
+            0,
+            InstallerCandidate {
+                path: brew,
+                installer_type: InstallMethod::Brew,
+                level: 1,
+            },
+        );
+    }
+
     candidates
 }
 
+/// 安装路径位于某个 Homebrew 前缀的 Cellar / Caskroom 下时返回该前缀
+///
+/// 只看 Cellar / Caskroom：前缀下的 `bin`、`lib/node_modules` 也可能是用 brew 安装的 node
+/// 执行 `npm install -g` 得到的，应按 npm 安装处理
+pub fn homebrew_prefix_of(path: &Path, prefixes: &[PathBuf]) -> Option<PathBuf> {
+    prefixes
+        .iter()
+        .find(|prefix| {
+            ["Cellar", "Caskroom"]
+                .iter()
+                .any(|dir| path.starts_with(prefix.join(dir)))
+        })
+        .cloned()
+}
+
+/// 按安装路径识别 Homebrew 安装（解析符号链接后位于 Cellar / Caskroom 下），返回该前缀的 brew 路径
+pub fn detect_brew_install(tool_path: &str) -> Option<String> {
+    let resolved = std::fs::canonicalize(tool_path).unwrap_or_else(|_| PathBuf::from(tool_path));
+    let prefixes: Vec<PathBuf> = homebrew_prefixes()
+        .into_iter()
+        .map(|prefix| std::fs::canonicalize(&prefix).unwrap_or(prefix))
+        .collect();
+    let prefix = homebrew_prefix_of(&resolved, &prefixes)?;
+    Some(
+        prefix
+            .join("bin")
+            .join("brew")
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
 /// 扫描所有可能的工具实例（用于自动扫描）
 ///
 /// 工作流程：
@@ -180,4 +225,39 @@ mod tests {
         // 应该在 /usr/local/bin/ 和 /usr/local/ 中查找
         println!("Found {} candidates", candidates.len());
     }
+
+    #[test]
+    fn test_homebrew_prefix_classification() {
+        let prefixes = [
+            PathBuf::from("/opt/homebrew"),
+            PathBuf::from("/home/linuxbrew/.linuxbrew"),
+            PathBuf::from("/usr/local"),
+        ];
+        let classify = |path: &str| homebrew_prefix_of(Path::new(path), &prefixes);
+
+        assert_eq!(
+            classify("/home/linuxbrew/.linuxbrew/Cellar/node/22.1.0/bin/node"),
+            Some(PathBuf::from("/home/linuxbrew/.linuxbrew"))
+        );
+        assert_eq!(
+            classify("/home/linuxbrew/.linuxbrew/Caskroom/claude-code/2.0.61/claude"),
+            Some(PathBuf::from("/home/linuxbrew/.linuxbrew"))
+        );
+        assert_eq!(
+            classify("/usr/local/Cellar/gemini-cli/0.13.0/bin/gemini"),
+            Some(PathBuf::from("/usr/local"))
+        );
+
+        // brew 的 node 执行 npm install -g 安装的工具不算 Homebrew 安装
+        assert_eq!(
+            classify("/home/linuxbrew/.linuxbrew/lib/node_modules/@openai/codex/bin/codex.js"),
+            None
+        );
+        assert_eq!(classify("/usr/local/bin/claude"), None);
+        // 只按路径组件匹配
+        assert_eq!(
+            classify("/home/linuxbrew/.linuxbrew-old/Cellar/codex/1.0/bin/codex"),
+            None
+        );
+    }
 }
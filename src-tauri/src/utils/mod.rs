@@ -1,17 +1,29 @@
 pub mod auto_startup;
 pub mod command;
+pub mod command_output;
+pub mod command_runner;
 pub mod config;
 pub mod file_helpers;
 pub mod installer_scanner;
 pub mod platform;
+pub mod shell_env;
+pub mod ssh_config;
+pub mod ssh_executor;
 pub mod version;
 pub mod wsl_executor;
+pub mod wsl_path;
 
 pub use auto_startup::*;
 pub use command::*;
+pub use command_output::{OutputDecoding, OutputIssue, OutputLine, OutputStream};
+pub use command_runner::{CommandRunner, MockExecutor};
 pub use config::*;
 pub use file_helpers::*;
 pub use installer_scanner::*;
 pub use platform::*;
+pub use shell_env::*;
+pub use ssh_config::*;
+pub use ssh_executor::*;
 pub use version::*;
 pub use wsl_executor::*;
+pub use wsl_path::*;
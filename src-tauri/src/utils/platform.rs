@@ -1,4 +1,9 @@
+use crate::utils::version::parse_version;
+use once_cell::sync::Lazy;
+use semver::Version;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
 /// 平台信息
 #[derive(Debug, Clone)]
@@ -8,6 +13,34 @@ pub struct PlatformInfo {
     pub is_windows: bool,
     pub is_macos: bool,
     pub is_linux: bool,
+    /// Linux 上是否为 musl libc（Alpine 等）
+    pub is_musl: bool,
+}
+
+/// Linuxbrew（Homebrew on Linux）的默认安装前缀
+pub const LINUXBREW_PREFIX: &str = "/home/linuxbrew/.linuxbrew";
+
+/// 用户登记的额外检测路径（来自全局配置的 `extra_path_entries`）
+static EXTRA_PATH_ENTRIES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// 设置额外检测路径（启动时从全局配置加载，登记新路径后更新）
+pub fn set_extra_path_entries(entries: Vec<String>) {
+    *EXTRA_PATH_ENTRIES.write().unwrap() = entries;
+}
+
+/// 当前的额外检测路径
+pub fn extra_path_entries() -> Vec<String> {
+    EXTRA_PATH_ENTRIES.read().unwrap().clone()
+}
+
+/// 当前系统的 libc 是否为 musl（运行时检测，进程内只检测一次）
+static IS_MUSL: Lazy<bool> = Lazy::new(detect_musl);
+
+/// 没有可用官方安装包的平台
+#[derive(Debug, thiserror::Error)]
+#[error("当前平台（{platform}）没有官方安装包，请改用 npm 安装")]
+pub struct UnsupportedPlatform {
+    pub platform: String,
 }
 
 impl PlatformInfo {
@@ -20,21 +53,42 @@ impl PlatformInfo {
             is_windows: os == "windows",
             is_macos: os == "macos",
             is_linux: os == "linux",
+            is_musl: os == "linux" && *IS_MUSL,
             os,
             arch,
         }
     }
 
     /// 获取平台标识符（用于下载）
+    ///
+    /// musl 系统追加 `-musl` 后缀（如 `linux-x64-musl`），glibc 构建在 musl 上无法运行
     pub fn platform_id(&self) -> String {
-        match (self.os.as_str(), self.arch.as_str()) {
+        let base = match (self.os.as_str(), self.arch.as_str()) {
             ("macos", "aarch64") => "darwin-arm64".to_string(),
             ("macos", "x86_64") => "darwin-x64".to_string(),
             ("linux", "x86_64") => "linux-x64".to_string(),
             ("linux", "aarch64") => "linux-arm64".to_string(),
+            ("linux", "arm") => "linux-armv7".to_string(),
             ("windows", "x86_64") => "win32-x64".to_string(),
             ("windows", "aarch64") => "win32-arm64".to_string(),
+            ("freebsd", "x86_64") => "freebsd-x64".to_string(),
+            ("freebsd", "aarch64") => "freebsd-arm64".to_string(),
             _ => format!("{}-{}", self.os, self.arch),
+        };
+        if self.is_linux && self.is_musl {
+            format!("{base}-musl")
+        } else {
+            base
+        }
+    }
+
+    /// 在支持的平台列表中查找当前平台，找不到时返回 `UnsupportedPlatform`
+    pub fn require_supported(&self, supported: &[&str]) -> Result<String, UnsupportedPlatform> {
+        let platform = self.platform_id();
+        if supported.contains(&platform.as_str()) {
+            Ok(platform)
+        } else {
+            Err(UnsupportedPlatform { platform })
         }
     }
 
@@ -52,14 +106,24 @@ impl PlatformInfo {
     /// 策略：在当前 PATH 前追加工具常见路径，保留所有现有环境
     /// - 增强路径包含：Homebrew、npm global、nvm、用户 bin 等
     /// - 当前 PATH：继承系统/shell 的完整 PATH
+    /// - 重复目录只保留第一次出现的位置，顺序稳定
     ///
     /// 示例（macOS）：
     /// ```
     /// /Users/user/.nvm/current/bin:/opt/homebrew/bin:/usr/local/bin:$PATH
     /// ```
     pub fn build_enhanced_path(&self) -> String {
-        let separator = self.path_separator();
+        self.build_enhanced_path_with(false)
+    }
 
+    /// 构建增强的 PATH，`existing_only` 为 true 时跳过不存在的目录（每个目录一次 stat）
+    pub fn build_enhanced_path_with(&self, existing_only: bool) -> String {
+        self.enhanced_path_entries(existing_only)
+            .join(self.path_separator())
+    }
+
+    /// 增强 PATH 的各个目录（已去重，按优先级排列）
+    pub fn enhanced_path_entries(&self, existing_only: bool) -> Vec<String> {
         // 实时获取当前 PATH（而非缓存），确保获得最新环境
         let current_path = env::var("PATH").unwrap_or_default();
 
@@ -69,15 +133,59 @@ impl PlatformInfo {
             self.unix_system_paths()
         };
 
-        // 合并策略：增强路径在前（高优先级），当前 PATH 在后（保留完整环境）
-        format!(
-            "{}{}{}",
-            system_paths.join(separator),
-            separator,
-            current_path
+        // 合并策略：用户登记的额外路径最先，增强路径其次，当前 PATH 在后（保留完整环境）
+        self.merge_path_entries(
+            extra_path_entries()
+                .into_iter()
+                .chain(system_paths)
+                .chain(self.split_path(&current_path)),
+            existing_only,
         )
     }
 
+    /// 按分隔符拆分 PATH 字符串
+    pub fn split_path(&self, path: &str) -> Vec<String> {
+        path.split(self.path_separator())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// 合并 PATH 目录：去掉空项与重复项（保留第一次出现），可选跳过不存在的目录
+    ///
+    /// 比较时忽略结尾的路径分隔符；Windows 上不区分大小写
+    pub fn merge_path_entries(
+        &self,
+        entries: impl IntoIterator<Item = String>,
+        existing_only: bool,
+    ) -> Vec<String> {
+        let mut seen = std::collections::HashSet::new();
+        entries
+            .into_iter()
+            .filter_map(|entry| {
+                let trimmed = entry.trim();
+                let normalized = match trimmed.trim_end_matches(['/', '\\']) {
+                    "" => trimmed,
+                    stripped => stripped,
+                };
+                if normalized.is_empty() {
+                    return None;
+                }
+                let key = if self.is_windows {
+                    normalized.to_lowercase()
+                } else {
+                    normalized.to_string()
+                };
+                if !seen.insert(key) {
+                    return None;
+                }
+                if existing_only && !Path::new(normalized).is_dir() {
+                    return None;
+                }
+                Some(normalized.to_string())
+            })
+            .collect()
+    }
+
     /// Windows 系统路径
     fn windows_system_paths(&self) -> Vec<String> {
         let mut paths = vec![
@@ -100,14 +208,17 @@ impl PlatformInfo {
 
     /// Unix 系统路径
     fn unix_system_paths(&self) -> Vec<String> {
-        let mut paths = vec![
-            "/opt/homebrew/bin".to_string(),
-            "/usr/local/bin".to_string(),
-            "/usr/bin".to_string(),
-            "/bin".to_string(),
-            "/usr/sbin".to_string(),
-            "/sbin".to_string(),
-        ];
+        // Homebrew 前缀的 bin（/usr/local 的 bin 在下面按系统路径加入）
+        let mut paths: Vec<String> = homebrew_prefixes()
+            .into_iter()
+            .filter(|prefix| prefix != Path::new("/usr/local"))
+            .map(|prefix| prefix.join("bin").to_string_lossy().to_string())
+            .collect();
+        paths.extend(
+            ["/usr/local/bin", "/usr/bin", "/bin", "/usr/sbin", "/sbin"]
+                .into_iter()
+                .map(str::to_string),
+        );
 
         if let Some(home_dir) = dirs::home_dir() {
             let home_str = home_dir.to_string_lossy();
@@ -151,25 +262,13 @@ impl PlatformInfo {
                 }
             }
 
-            // 扫描 nvm 所有已安装版本，选择最新的（最后的兜底）
+            // 扫描 nvm 已安装版本：优先 alias/default，否则按语义化版本选最新（最后的兜底）
             if !nvm_detected {
-                if let Ok(entries) = std::fs::read_dir(format!("{home_str}/.nvm/versions/node")) {
-                    let mut versions: Vec<String> = entries
-                        .filter_map(|e| e.ok())
-                        .filter(|e| e.path().is_dir())
-                        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
-                        .collect();
-
-                    // 简单排序（字母序，v20 > v18）
-                    versions.sort();
-                    if let Some(latest_version) = versions.last() {
-                        let latest_bin =
-                            format!("{home_str}/.nvm/versions/node/{latest_version}/bin");
-                        if std::path::Path::new(&latest_bin).exists() {
-                            paths.insert(0, latest_bin);
-                            // nvm 路径已添加
-                        }
-                    }
+                let nvm_dir = std::env::var("NVM_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| home_dir.join(".nvm"));
+                if let Some(bin) = select_nvm_node_bin(&nvm_dir) {
+                    paths.insert(0, bin.to_string_lossy().to_string());
                 }
             }
 
@@ -203,6 +302,134 @@ impl PlatformInfo {
     }
 }
 
+/// 可能的 Homebrew 安装前缀（按优先级，已去重）
+///
+/// `$HOMEBREW_PREFIX`、Apple Silicon 默认前缀、Linuxbrew 默认前缀、用户目录下的 `~/.linuxbrew`、
+/// Intel macOS 的 `/usr/local`
+pub fn homebrew_prefixes() -> Vec<PathBuf> {
+    homebrew_prefixes_with(env::var("HOMEBREW_PREFIX").ok(), dirs::home_dir())
+}
+
+fn homebrew_prefixes_with(env_prefix: Option<String>, home: Option<PathBuf>) -> Vec<PathBuf> {
+    let mut prefixes: Vec<PathBuf> = Vec::new();
+    let candidates = env_prefix
+        .filter(|prefix| !prefix.trim().is_empty())
+        .map(|prefix| PathBuf::from(prefix.trim().trim_end_matches('/')))
+        .into_iter()
+        .chain([
+            PathBuf::from("/opt/homebrew"),
+            PathBuf::from(LINUXBREW_PREFIX),
+        ])
+        .chain(home.map(|home| home.join(".linuxbrew")))
+        .chain([PathBuf::from("/usr/local")]);
+    for prefix in candidates {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes
+}
+
+/// 检测 musl：先找 musl 动态链接器（无需启动进程），再看 `ldd --version` 的输出
+fn detect_musl() -> bool {
+    if !cfg!(target_os = "linux") {
+        return false;
+    }
+    if cfg!(target_env = "musl") {
+        return true;
+    }
+
+    let has_musl_loader = ["/lib", "/usr/lib"].iter().any(|dir| {
+        std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .any(|entry| entry.file_name().to_string_lossy().starts_with("ld-musl-"))
+            })
+            .unwrap_or(false)
+    });
+    if has_musl_loader {
+        return true;
+    }
+
+    // ldd 在 musl 上输出到 stderr 且退出码非 0
+    std::process::Command::new("ldd")
+        .arg("--version")
+        .output()
+        .map(|output| {
+            let text = format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            text.to_lowercase().contains("musl")
+        })
+        .unwrap_or(false)
+}
+
+/// 在 nvm 目录中选择兜底使用的 Node 版本，返回其 bin 目录
+///
+/// 优先 `alias/default` 指向的版本（支持 `20`、`v18.20.0`、`lts/iron`、`node` 等写法），
+/// 否则选择语义化版本最新的已安装版本；非版本目录和缺少 bin 的版本会被忽略
+pub(crate) fn select_nvm_node_bin(nvm_dir: &Path) -> Option<PathBuf> {
+    let mut installed: Vec<(Version, PathBuf)> = std::fs::read_dir(nvm_dir.join("versions/node"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with('v') {
+                return None;
+            }
+            let version = parse_version(&name)?;
+            let bin = entry.path().join("bin");
+            bin.is_dir().then_some((version, bin))
+        })
+        .collect();
+    installed.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if let Some(alias) = read_nvm_alias(nvm_dir, "default") {
+        let matched = installed
+            .iter()
+            .rev()
+            .find(|(version, _)| nvm_alias_matches(&alias, version));
+        if let Some((_, bin)) = matched {
+            return Some(bin.clone());
+        }
+    }
+
+    installed.pop().map(|(_, bin)| bin)
+}
+
+/// 读取 nvm 别名（`lts/xxx` 会再解析一层）
+fn read_nvm_alias(nvm_dir: &Path, name: &str) -> Option<String> {
+    let alias = std::fs::read_to_string(nvm_dir.join("alias").join(name)).ok()?;
+    let alias = alias.trim().to_string();
+    if alias.starts_with("lts/") && name != alias {
+        return std::fs::read_to_string(nvm_dir.join("alias").join(&alias))
+            .ok()
+            .map(|target| target.trim().to_string());
+    }
+    (!alias.is_empty()).then_some(alias)
+}
+
+/// 别名是否匹配版本：`node` / `stable` 匹配任意版本，数字按前缀逐段比较
+fn nvm_alias_matches(alias: &str, version: &Version) -> bool {
+    if alias == "node" || alias == "stable" {
+        return true;
+    }
+    let parts: Vec<u64> = match alias
+        .trim_start_matches('v')
+        .split('.')
+        .map(str::parse)
+        .collect()
+    {
+        Ok(parts) => parts,
+        Err(_) => return false,
+    };
+    let actual = [version.major, version.minor, version.patch];
+    !parts.is_empty() && parts.len() <= 3 && parts.iter().zip(actual).all(|(a, b)| *a == b)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,4 +457,203 @@ mod tests {
         let id = platform.platform_id();
         assert!(id.contains("-"));
     }
+
+    /// 构造 nvm 目录：v8/v18/v20 正常，v21 缺少 bin，另有非版本目录
+    fn fake_nvm() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let node = dir.path().join("versions/node");
+        for version in ["v8.17.0", "v18.20.0", "v20.11.0", "v9.11.2"] {
+            std::fs::create_dir_all(node.join(version).join("bin")).unwrap();
+        }
+        std::fs::create_dir_all(node.join("v21.0.0")).unwrap();
+        std::fs::create_dir_all(node.join("broken").join("bin")).unwrap();
+        std::fs::create_dir_all(dir.path().join("alias/lts")).unwrap();
+        dir
+    }
+
+    fn selected(dir: &tempfile::TempDir) -> String {
+        let bin = select_nvm_node_bin(dir.path()).unwrap();
+        bin.parent()
+            .unwrap()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_nvm_picks_highest_semver() {
+        let dir = fake_nvm();
+        assert_eq!(selected(&dir), "v20.11.0");
+    }
+
+    #[test]
+    fn test_nvm_prefers_default_alias() {
+        let dir = fake_nvm();
+        let alias = dir.path().join("alias/default");
+
+        std::fs::write(&alias, "18\n").unwrap();
+        assert_eq!(selected(&dir), "v18.20.0");
+
+        std::fs::write(&alias, "v8.17.0").unwrap();
+        assert_eq!(selected(&dir), "v8.17.0");
+
+        std::fs::write(dir.path().join("alias/lts/hydrogen"), "v18.20.0\n").unwrap();
+        std::fs::write(&alias, "lts/hydrogen").unwrap();
+        assert_eq!(selected(&dir), "v18.20.0");
+
+        // 别名指向未安装（或缺少 bin）的版本时回退到最新版本
+        std::fs::write(&alias, "21").unwrap();
+        assert_eq!(selected(&dir), "v20.11.0");
+    }
+
+    #[test]
+    fn test_nvm_missing_versions_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(select_nvm_node_bin(dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_merge_path_entries_dedupes_in_order() {
+        let unix = PlatformInfo {
+            os: "linux".to_string(),
+            arch: "x86_64".to_string(),
+            is_windows: false,
+            is_macos: false,
+            is_linux: true,
+            is_musl: false,
+        };
+        let merged = unix.merge_path_entries(
+            [
+                "/a/bin", "/usr/bin", "", "/a/bin/", "/b", "/usr/bin", "/", "/A/bin",
+            ]
+            .map(String::from),
+            false,
+        );
+        assert_eq!(merged, ["/a/bin", "/usr/bin", "/b", "/", "/A/bin"]);
+
+        let windows = PlatformInfo {
+            os: "windows".to_string(),
+            arch: "x86_64".to_string(),
+            is_windows: true,
+            is_macos: false,
+            is_linux: false,
+            is_musl: false,
+        };
+        let merged = windows.merge_path_entries(
+            [
+                r"C:\Program Files\nodejs",
+                r"c:\program files\nodejs\",
+                r"C:\Windows",
+            ]
+            .map(String::from),
+            false,
+        );
+        assert_eq!(merged, [r"C:\Program Files\nodejs", r"C:\Windows"]);
+    }
+
+    #[test]
+    fn test_merge_path_entries_existing_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let existing = dir.path().to_string_lossy().to_string();
+        let missing = dir.path().join("missing").to_string_lossy().to_string();
+
+        let platform = PlatformInfo::current();
+        let entries = vec![missing.clone(), existing.clone()];
+        assert_eq!(
+            platform.merge_path_entries(entries.clone(), false),
+            [missing, existing.clone()]
+        );
+        assert_eq!(platform.merge_path_entries(entries, true), [existing]);
+    }
+
+    #[test]
+    fn test_enhanced_path_is_stable_and_unique() {
+        let platform = PlatformInfo::current();
+        let first = platform.enhanced_path_entries(false);
+        assert_eq!(first, platform.enhanced_path_entries(false));
+
+        let mut unique = first.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), first.len());
+    }
+
+    #[test]
+    fn test_homebrew_prefixes_order() {
+        let prefixes = homebrew_prefixes_with(
+            Some("/home/linuxbrew/.linuxbrew/".to_string()),
+            Some(PathBuf::from("/home/alice")),
+        );
+        assert_eq!(
+            prefixes,
+            [
+                "/home/linuxbrew/.linuxbrew",
+                "/opt/homebrew",
+                "/home/alice/.linuxbrew",
+                "/usr/local",
+            ]
+            .map(PathBuf::from)
+        );
+
+        let prefixes = homebrew_prefixes_with(Some("/srv/brew".to_string()), None);
+        assert_eq!(prefixes[0], PathBuf::from("/srv/brew"));
+        assert!(prefixes.contains(&PathBuf::from(LINUXBREW_PREFIX)));
+    }
+
+    fn platform(os: &str, arch: &str, is_musl: bool) -> PlatformInfo {
+        PlatformInfo {
+            os: os.to_string(),
+            arch: arch.to_string(),
+            is_windows: os == "windows",
+            is_macos: os == "macos",
+            is_linux: os == "linux",
+            is_musl,
+        }
+    }
+
+    #[test]
+    fn test_platform_id_mappings() {
+        assert_eq!(
+            platform("linux", "x86_64", false).platform_id(),
+            "linux-x64"
+        );
+        assert_eq!(
+            platform("linux", "x86_64", true).platform_id(),
+            "linux-x64-musl"
+        );
+        assert_eq!(
+            platform("linux", "aarch64", true).platform_id(),
+            "linux-arm64-musl"
+        );
+        assert_eq!(platform("linux", "arm", false).platform_id(), "linux-armv7");
+        assert_eq!(
+            platform("freebsd", "x86_64", false).platform_id(),
+            "freebsd-x64"
+        );
+        assert_eq!(
+            platform("macos", "aarch64", false).platform_id(),
+            "darwin-arm64"
+        );
+        assert_eq!(
+            platform("linux", "riscv64", false).platform_id(),
+            "linux-riscv64"
+        );
+    }
+
+    #[test]
+    fn test_require_supported() {
+        let supported = ["linux-x64", "linux-x64-musl"];
+        assert_eq!(
+            platform("linux", "x86_64", true)
+                .require_supported(&supported)
+                .unwrap(),
+            "linux-x64-musl"
+        );
+        let err = platform("linux", "arm", false)
+            .require_supported(&supported)
+            .unwrap_err();
+        assert_eq!(err.platform, "linux-armv7");
+        assert!(err.to_string().contains("linux-armv7"));
+    }
 }
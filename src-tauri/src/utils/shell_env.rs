@@ -0,0 +1,134 @@
+// Shell Env - 捕获用户登录 shell 的环境（macOS）
+//
+// macOS 从 Finder / Dock 启动的 GUI 应用只继承 launchd 的最小 PATH，
+// direnv、pyenv/nvm 等在 shell 配置中设置的环境都看不到；而每条探测命令都经 `zsh -ilc` 执行又太慢。
+// 因此每次会话只运行一次 `$SHELL -ilc env`（5 秒超时），缓存 PATH 与少量白名单变量，
+// 由 CommandExecutor 合并到子进程环境。失败时静默回退到原有行为。
+
+use crate::utils::CommandExecutor;
+use once_cell::sync::OnceCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 捕获 shell 环境的超时
+pub const SHELL_ENV_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 合并到子进程的变量（除 PATH 外只取与工具/Node 运行时相关的少量变量）
+pub const SHELL_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "NVM_DIR",
+    "VOLTA_HOME",
+    "FNM_DIR",
+    "N_PREFIX",
+    "PNPM_HOME",
+    "BUN_INSTALL",
+    "ASDF_DIR",
+    "ASDF_DATA_DIR",
+    "MISE_DATA_DIR",
+    "HOMEBREW_PREFIX",
+];
+
+/// 输出起止标记，用于跳过 shell 配置文件打印的内容
+const START_MARKER: &str = "__DUCKCODING_ENV_START__";
+const END_MARKER: &str = "__DUCKCODING_ENV_END__";
+
+/// 本次会话捕获的环境（None 表示未捕获或捕获失败）
+static SHELL_ENV: OnceCell<Option<HashMap<String, String>>> = OnceCell::new();
+
+/// 捕获登录 shell 的环境（每次会话只执行一次）
+///
+/// 仅在 macOS 上执行；`enabled` 为 false 时记为未捕获，之后的调用不再尝试
+pub async fn capture_shell_env(enabled: bool) -> Option<&'static HashMap<String, String>> {
+    if let Some(env) = SHELL_ENV.get() {
+        return env.as_ref();
+    }
+
+    let captured = if enabled && cfg!(target_os = "macos") {
+        run_capture().await
+    } else {
+        None
+    };
+    SHELL_ENV.get_or_init(|| captured).as_ref()
+}
+
+/// 已捕获的 shell 环境
+pub fn captured_shell_env() -> Option<&'static HashMap<String, String>> {
+    SHELL_ENV.get().and_then(Option::as_ref)
+}
+
+async fn run_capture() -> Option<HashMap<String, String>> {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/zsh".to_string());
+    let script = format!("printf '%s\\n' {START_MARKER}; env; printf '%s\\n' {END_MARKER}");
+
+    let started = std::time::Instant::now();
+    let result = CommandExecutor::new()
+        .with_shell_env(false)
+        .with_timeout(SHELL_ENV_TIMEOUT)
+        .execute_args_async(&shell, &["-ilc", &script])
+        .await;
+
+    let env = parse_env_output(&result.stdout);
+    if !result.success || !env.contains_key("PATH") {
+        tracing::debug!(
+            shell = %shell,
+            timed_out = result.timed_out,
+            stderr = %result.stderr,
+            "捕获 shell 环境失败，使用默认环境"
+        );
+        return None;
+    }
+
+    tracing::info!(
+        shell = %shell,
+        vars = env.len(),
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "已捕获 shell 环境"
+    );
+    Some(env)
+}
+
+/// 解析标记之间的 `env` 输出，只保留白名单变量
+fn parse_env_output(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .skip_while(|line| line.trim() != START_MARKER)
+        .skip(1)
+        .take_while(|line| line.trim() != END_MARKER)
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| SHELL_ENV_ALLOWLIST.contains(key))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env_output() {
+        let output = format!(
+            "Welcome banner from .zshrc\nPATH=/fake\n{START_MARKER}\n\
+             PATH=/Users/x/.volta/bin:/usr/bin\nVOLTA_HOME=/Users/x/.volta\n\
+             SECRET_TOKEN=abc\nMULTI=line1\nline2=not-a-var\n{END_MARKER}\nNVM_DIR=/after\n"
+        );
+        let env = parse_env_output(&output);
+        assert_eq!(
+            env.get("PATH").map(String::as_str),
+            Some("/Users/x/.volta/bin:/usr/bin")
+        );
+        assert_eq!(
+            env.get("VOLTA_HOME").map(String::as_str),
+            Some("/Users/x/.volta")
+        );
+        // 白名单外与标记外的变量被忽略
+        assert_eq!(env.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_env_output_without_markers() {
+        assert!(parse_env_output("PATH=/usr/bin\n").is_empty());
+    }
+}
@@ -0,0 +1,501 @@
+// SSH Config - 解析 ~/.ssh/config 中的主机条目
+//
+// 仅解析创建 SSH 实例所需的字段（HostName、User、Port、IdentityFile、ProxyJump），
+// 按 OpenSSH 的规则取值：同一字段以第一个匹配块中的值为准，`Host *` 等通配块同样参与匹配。
+// - 只包含通配符的 Host 不会列出
+// - Include 只展开一层（被包含文件中的 Include 忽略）
+// - Match 块无法静态求值，整块跳过
+
+use crate::models::{SSHConfig, SshAuthMethod};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// ~/.ssh/config 中的一个主机条目（已解析）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SshConfigHost {
+    /// Host 别名
+    pub alias: String,
+    /// 实际主机地址（未配置 HostName 时为别名）
+    pub host_name: String,
+    /// 用户名（未配置时为 None，连接时使用本机用户名）
+    pub user: Option<String>,
+    /// 端口
+    pub port: u16,
+    /// 密钥文件（已展开 `~`）
+    pub identity_file: Option<String>,
+    /// 跳板机（ProxyJump 原值）
+    pub proxy_jump: Option<String>,
+}
+
+impl SshConfigHost {
+    /// 构建 SSH 实例的连接配置，ProxyJump 按 `hosts` 中的别名解析为跳板机
+    ///
+    /// `a,b` 形式的多级跳板依次经过 a、b；跳板机自身的 ProxyJump 不再展开
+    pub fn resolve_ssh_config(&self, hosts: &[SshConfigHost]) -> SSHConfig {
+        let mut config = self.to_ssh_config();
+        let hops = self
+            .proxy_jump
+            .as_deref()
+            .map(|jump| jump.split(',').map(str::trim).filter(|hop| !hop.is_empty()));
+
+        let mut jump_host: Option<Box<SSHConfig>> = None;
+        for hop in hops.into_iter().flatten() {
+            let mut hop_config = parse_jump_spec(hop, hosts);
+            hop_config.jump_host = jump_host.take();
+            jump_host = Some(Box::new(hop_config));
+        }
+        config.jump_host = jump_host;
+        config
+    }
+
+    /// 构建连接配置（不含跳板机）
+    pub fn to_ssh_config(&self) -> SSHConfig {
+        let user = self.user.clone().unwrap_or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default()
+        });
+        SSHConfig {
+            display_name: self.alias.clone(),
+            host: self.host_name.clone(),
+            port: self.port,
+            user,
+            key_path: self.identity_file.clone(),
+            auth_method: if self.identity_file.is_some() {
+                SshAuthMethod::Key
+            } else {
+                SshAuthMethod::Agent
+            },
+            password: None,
+            passphrase: None,
+            jump_host: None,
+        }
+    }
+}
+
+/// 解析一级跳板：`[user@]host[:port]`，host 为已配置的别名时沿用其配置
+fn parse_jump_spec(spec: &str, hosts: &[SshConfigHost]) -> SSHConfig {
+    let (user, rest) = match spec.rsplit_once('@') {
+        Some((user, rest)) => (Some(user), rest),
+        None => (None, spec),
+    };
+    let (host, port) = match rest.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host, port.parse().ok()),
+        _ => (rest, None),
+    };
+
+    let mut config = match hosts.iter().find(|h| h.alias == host) {
+        Some(known) => known.to_ssh_config(),
+        None => SshConfigHost {
+            alias: host.to_string(),
+            host_name: host.to_string(),
+            user: None,
+            port: 22,
+            identity_file: None,
+            proxy_jump: None,
+        }
+        .to_ssh_config(),
+    };
+    if let Some(user) = user {
+        config.user = user.to_string();
+    }
+    if let Some(port) = port {
+        config.port = port;
+    }
+    config
+}
+
+/// 一个 Host 块：匹配模式与其中的配置项（按出现顺序）
+struct HostBlock {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+/// 默认的 ssh 配置文件路径
+pub fn default_ssh_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+/// 列出 ~/.ssh/config 中的主机（文件不存在时返回空列表）
+pub fn list_ssh_config_hosts() -> Result<Vec<SshConfigHost>> {
+    match default_ssh_config_path() {
+        Some(path) if path.exists() => load_ssh_config_hosts(&path),
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// 按别名构建 ~/.ssh/config 中主机的连接配置（含跳板机）
+pub fn ssh_config_for_host(alias: &str) -> Result<SSHConfig> {
+    let hosts = list_ssh_config_hosts()?;
+    let host = hosts
+        .iter()
+        .find(|host| host.alias == alias)
+        .ok_or_else(|| anyhow::anyhow!("~/.ssh/config 中未找到主机: {}", alias))?;
+    Ok(host.resolve_ssh_config(&hosts))
+}
+
+/// 读取并解析指定的 ssh 配置文件（展开一层 Include）
+pub fn load_ssh_config_hosts(path: &Path) -> Result<Vec<SshConfigHost>> {
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("读取 SSH 配置失败: {}", path.display()))?;
+
+    let mut lines = Vec::new();
+    for line in content.lines() {
+        match split_option(line) {
+            Some((key, value)) if key == "include" => {
+                for included in resolve_include(base_dir, &value) {
+                    match std::fs::read_to_string(&included) {
+                        Ok(text) => lines.extend(text.lines().map(str::to_string)),
+                        Err(e) => {
+                            tracing::debug!(path = %included.display(), error = %e, "读取 Include 文件失败");
+                        }
+                    }
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    Ok(parse_ssh_config(&lines.join("\n")))
+}
+
+/// 解析 ssh 配置文本（不处理 Include）
+pub fn parse_ssh_config(content: &str) -> Vec<SshConfigHost> {
+    let mut blocks: Vec<HostBlock> = Vec::new();
+    // 文件开头（第一个 Host 之前）的配置项对所有主机生效
+    let mut current = Some(HostBlock {
+        patterns: vec!["*".to_string()],
+        options: Vec::new(),
+    });
+
+    for line in content.lines() {
+        let Some((key, value)) = split_option(line) else {
+            continue;
+        };
+        match key.as_str() {
+            "host" => {
+                blocks.extend(current.take());
+                current = Some(HostBlock {
+                    patterns: split_args(&value),
+                    options: Vec::new(),
+                });
+            }
+            "match" => {
+                blocks.extend(current.take());
+            }
+            "include" => {
+                tracing::debug!(value = %value, "忽略嵌套的 Include");
+            }
+            _ => {
+                if let Some(block) = current.as_mut() {
+                    block.options.push((key, value));
+                }
+            }
+        }
+    }
+    blocks.extend(current);
+
+    let mut aliases: Vec<&str> = Vec::new();
+    for block in &blocks {
+        for pattern in &block.patterns {
+            let concrete = !pattern.starts_with('!') && !pattern.contains(['*', '?']);
+            if concrete && !aliases.contains(&pattern.as_str()) {
+                aliases.push(pattern);
+            }
+        }
+    }
+
+    aliases
+        .into_iter()
+        .map(|alias| resolve_host(alias, &blocks))
+        .collect()
+}
+
+/// 按 OpenSSH 规则求出某个别名的配置（每个字段取第一个匹配值）
+fn resolve_host(alias: &str, blocks: &[HostBlock]) -> SshConfigHost {
+    let lookup = |name: &str| {
+        blocks
+            .iter()
+            .filter(|block| block_matches(&block.patterns, alias))
+            .flat_map(|block| block.options.iter())
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| unquote(value).to_string())
+    };
+
+    SshConfigHost {
+        alias: alias.to_string(),
+        host_name: lookup("hostname")
+            .map(|name| name.replace("%h", alias))
+            .unwrap_or_else(|| alias.to_string()),
+        user: lookup("user"),
+        port: lookup("port")
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(22),
+        identity_file: lookup("identityfile")
+            .filter(|file| !file.eq_ignore_ascii_case("none"))
+            .map(|file| expand_tilde(&file)),
+        proxy_jump: lookup("proxyjump").filter(|jump| !jump.eq_ignore_ascii_case("none")),
+    }
+}
+
+/// Host 模式列表是否匹配别名（任一否定模式匹配即不匹配）
+fn block_matches(patterns: &[String], alias: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if glob_match(negated, alias) {
+                return false;
+            }
+        } else if glob_match(pattern, alias) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// 支持 `*` 和 `?` 的通配匹配（不区分大小写，与 OpenSSH 一致）
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 拆分一行配置为 (小写关键字, 值)；空行与注释返回 None
+fn split_option(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let key = line[..split_at].to_lowercase();
+    let value = line[split_at..]
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+        .trim_end();
+    Some((key, value.to_string()))
+}
+
+/// 按空白拆分参数（支持双引号）
+fn split_args(value: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+fn expand_tilde(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// 展开 Include 的路径：相对路径相对于 ~/.ssh，文件名部分支持通配符
+fn resolve_include(base_dir: &Path, value: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    for arg in split_args(value) {
+        let expanded = expand_tilde(&arg);
+        let path = if Path::new(&expanded).is_absolute() {
+            PathBuf::from(expanded)
+        } else {
+            base_dir.join(expanded)
+        };
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !file_name.contains(['*', '?']) {
+            paths.push(path);
+            continue;
+        }
+
+        let Some(dir) = path.parent() else { continue };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut matched: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| p.is_file())
+            .filter(|p| {
+                p.file_name()
+                    .map(|name| glob_match(&file_name, &name.to_string_lossy()))
+                    .unwrap_or(false)
+            })
+            .collect();
+        matched.sort();
+        paths.extend(matched);
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+# 全局配置
+ServerAliveInterval 30
+
+Host dev
+    HostName dev.example.com
+    User duck
+    Port 2222
+    IdentityFile /keys/dev_ed25519
+
+Host prod prod-backup
+    HostName=%h.internal
+    ProxyJump bastion
+
+Host *.lan !skip.lan
+    User lan-user
+
+Host *
+    User fallback
+    IdentityFile /keys/default
+
+Match host foo
+    User ignored
+"#;
+
+    #[test]
+    fn test_parse_hosts_and_first_value_wins() {
+        let hosts = parse_ssh_config(SAMPLE);
+        let aliases: Vec<&str> = hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, ["dev", "prod", "prod-backup"]);
+
+        let dev = &hosts[0];
+        assert_eq!(dev.host_name, "dev.example.com");
+        assert_eq!(dev.user.as_deref(), Some("duck"));
+        assert_eq!(dev.port, 2222);
+        assert_eq!(dev.identity_file.as_deref(), Some("/keys/dev_ed25519"));
+        assert_eq!(dev.proxy_jump, None);
+
+        // 未单独配置的字段由 Host * 补全；%h 展开为别名
+        let prod = &hosts[1];
+        assert_eq!(prod.host_name, "prod.internal");
+        assert_eq!(prod.user.as_deref(), Some("fallback"));
+        assert_eq!(prod.port, 22);
+        assert_eq!(prod.identity_file.as_deref(), Some("/keys/default"));
+        assert_eq!(prod.proxy_jump.as_deref(), Some("bastion"));
+        assert_eq!(hosts[2].host_name, "prod-backup.internal");
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.lan", "box.LAN"));
+        assert!(glob_match("web-??", "web-01"));
+        assert!(!glob_match("web-??", "web-1"));
+        assert!(glob_match("*", ""));
+        assert!(block_matches(
+            &["*.lan".into(), "!skip.lan".into()],
+            "a.lan"
+        ));
+        assert!(!block_matches(
+            &["*.lan".into(), "!skip.lan".into()],
+            "skip.lan"
+        ));
+    }
+
+    #[test]
+    fn test_to_ssh_config() {
+        let host = &parse_ssh_config(SAMPLE)[0];
+        let config = host.to_ssh_config();
+        assert_eq!(config.display_name, "dev");
+        assert_eq!(config.host, "dev.example.com");
+        assert_eq!(config.port, 2222);
+        assert_eq!(config.user, "duck");
+        assert_eq!(config.auth_method, SshAuthMethod::Key);
+
+        let agent =
+            parse_ssh_config("Host bare\n  HostName 10.0.0.1\n  User me\n")[0].to_ssh_config();
+        assert_eq!(agent.auth_method, SshAuthMethod::Agent);
+        assert_eq!(agent.key_path, None);
+    }
+
+    #[test]
+    fn test_resolve_proxy_jump_chain() {
+        let hosts = parse_ssh_config(
+            "Host bastion\n  HostName bastion.example.com\n  User jump\n  IdentityFile /keys/jump\n\n\
+             Host inner\n  HostName 10.0.0.5\n  User ops\n  ProxyJump bastion,admin@gw.internal:2200\n",
+        );
+        let config = hosts[1].resolve_ssh_config(&hosts);
+        assert_eq!(config.host, "10.0.0.5");
+
+        // 最后一级跳板直连目标，其跳板为第一级
+        let gw = config.jump_host.as_deref().unwrap();
+        assert_eq!(gw.host, "gw.internal");
+        assert_eq!(gw.user, "admin");
+        assert_eq!(gw.port, 2200);
+
+        let bastion = gw.jump_host.as_deref().unwrap();
+        assert_eq!(bastion.host, "bastion.example.com");
+        assert_eq!(bastion.user, "jump");
+        assert_eq!(bastion.key_path.as_deref(), Some("/keys/jump"));
+        assert!(bastion.jump_host.is_none());
+    }
+
+    #[test]
+    fn test_include_one_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_d = dir.path().join("config.d");
+        std::fs::create_dir(&config_d).unwrap();
+        std::fs::write(
+            config_d.join("work.conf"),
+            "Host work\n  HostName work.example.com\nInclude nested.conf\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("nested.conf"), "Host nested\n").unwrap();
+        std::fs::write(
+            dir.path().join("config"),
+            "Include config.d/*.conf\n\nHost home\n  HostName 192.168.1.2\n",
+        )
+        .unwrap();
+
+        let hosts = load_ssh_config_hosts(&dir.path().join("config")).unwrap();
+        let aliases: Vec<&str> = hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, ["work", "home"]);
+        assert_eq!(hosts[0].host_name, "work.example.com");
+    }
+}
@@ -0,0 +1,1061 @@
+// SSH Executor - 远程命令执行
+//
+// 基于系统自带的 OpenSSH 客户端（Windows 10+ / macOS / Linux 均内置），不引入额外的 SSH 库：
+// - ssh-agent / 密钥文件直接交给 ssh 处理
+// - 密码与密钥口令通过临时 SSH_ASKPASS 脚本提供，不出现在命令行参数中
+// - 主机密钥严格校验 known_hosts，未知主机需先经 `host_key_status` / `trust_host_key` 确认指纹
+// - 远程命令经登录 shell 执行，与 WSL 一致
+// - 跳板机通过 ProxyCommand 嵌套 `ssh -W`（direct-tcpip 通道）逐级建立
+
+use crate::models::{SSHConfig, SshAuthMethod};
+use crate::utils::command_output::OutputIssue;
+use crate::utils::wsl_executor::{login_shell_script, sh_quote};
+use crate::utils::{parse_version_string, CommandExecutor, CommandResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 建立连接的默认超时
+pub const SSH_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 远程命令的默认超时（含连接时间）
+pub const SSH_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 测试连接时各阶段的超时
+const TEST_DNS_TIMEOUT: Duration = Duration::from_secs(5);
+const TEST_TCP_TIMEOUT: Duration = Duration::from_secs(5);
+const TEST_EXEC_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// ssh 自身出错（而非远程命令失败）时的退出码
+const SSH_ERROR_EXIT_CODE: i32 = 255;
+
+/// 传递密码给 askpass 脚本的环境变量
+const SECRET_ENV: &str = "DUCKCODING_SSH_SECRET";
+
+/// SSH 错误
+#[derive(Debug, thiserror::Error)]
+pub enum SshError {
+    #[error("主机 {host} 的密钥尚未受信任，请确认指纹后再连接")]
+    UnknownHostKey { host: String },
+
+    #[error("主机 {host} 的密钥与 known_hosts 记录不一致，可能存在中间人攻击")]
+    HostKeyChanged { host: String },
+
+    #[error("SSH 认证失败: {0}")]
+    AuthFailed(String),
+
+    #[error("SSH 连接失败: {0}")]
+    ConnectFailed(String),
+
+    #[error("SSH 命令执行超时（{0}秒）")]
+    Timeout(u64),
+
+    #[error("主机密钥指纹已变化，请重新确认")]
+    FingerprintMismatch,
+
+    #[error("SSH 客户端不可用: {0}")]
+    ClientUnavailable(String),
+
+    #[error("跳板机 {0} 仅支持 ssh-agent 或无口令密钥认证")]
+    UnsupportedJumpAuth(String),
+
+    #[error("SSH 配置无效: {0}")]
+    InvalidConfig(String),
+}
+
+/// 主机密钥状态
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HostKeyStatus {
+    /// 已在 known_hosts 中
+    Known,
+    /// 未知主机，附带扫描到的指纹（SHA256）供用户确认
+    Unknown { fingerprints: Vec<String> },
+}
+
+/// 测试连接的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshTestStage {
+    /// 解析主机名
+    Dns,
+    /// 建立 TCP 连接
+    Connect,
+    /// 主机密钥校验与认证
+    Auth,
+    /// 执行测试命令
+    Exec,
+}
+
+/// 测试连接失败的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshTestHop {
+    /// 跳板机
+    Bastion,
+    /// 目标主机
+    Target,
+}
+
+/// 测试连接结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SshTestResult {
+    pub success: bool,
+    /// 失败的阶段（成功时为 None）
+    pub failed_stage: Option<SshTestStage>,
+    /// 失败发生在跳板机还是目标主机（成功时为 None）
+    pub failed_hop: Option<SshTestHop>,
+    /// 失败原因（已去除密码等敏感信息）
+    pub error: Option<String>,
+    /// 主机密钥尚未受信任（需先确认指纹）
+    pub host_key_unknown: bool,
+    /// TCP 建连耗时（毫秒）
+    pub latency_ms: Option<u64>,
+    /// 整个测试耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+impl SshTestResult {
+    /// 是否为认证失败
+    pub fn is_auth_failure(&self) -> bool {
+        self.failed_stage == Some(SshTestStage::Auth)
+    }
+}
+
+/// 临时 askpass 脚本，离开作用域时删除
+struct AskPassScript {
+    path: PathBuf,
+}
+
+impl AskPassScript {
+    fn create() -> std::io::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+
+        // cmd 会在解析前展开 `%VAR%` 并解释 `& | < > ^`，口令只交给 PowerShell 从环境变量读取
+        #[cfg(target_os = "windows")]
+        let (name, content) = (
+            format!("duckcoding-askpass-{}-{}.cmd", std::process::id(), nanos),
+            format!(
+                "@powershell.exe -NoProfile -NonInteractive -Command \"\
+                 [Console]::OutputEncoding = New-Object System.Text.UTF8Encoding $false; \
+                 [Console]::Out.Write($env:{})\"\r\n",
+                SECRET_ENV
+            ),
+        );
+        #[cfg(not(target_os = "windows"))]
+        let (name, content) = (
+            format!("duckcoding-askpass-{}-{}.sh", std::process::id(), nanos),
+            format!("#!/bin/sh\nprintf '%s\\n' \"${}\"\n", SECRET_ENV),
+        );
+
+        let path = create_private_temp_file(&name, &content, 0o700)?;
+        Ok(Self { path })
+    }
+}
+
+/// 在临时目录中新建仅当前用户可访问的文件
+///
+/// 文件已存在时失败（`create_new`），不会写入预先放置的文件或跟随符号链接。
+fn create_private_temp_file(name: &str, content: &str, mode: u32) -> std::io::Result<PathBuf> {
+    let path = std::env::temp_dir().join(name);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(mode);
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    let mut file = options.open(&path)?;
+    if let Err(e) = file.write_all(content.as_bytes()) {
+        drop(file);
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// 校验将作为 ssh 参数的主机名或用户名
+///
+/// 以 `-` 开头的值会被 ssh 当作选项（如 `-oProxyCommand=...` 可执行任意命令），
+/// 空白与控制字符会破坏 ProxyCommand 的拼接。
+fn check_destination_part(kind: &str, value: &str) -> Result<(), SshError> {
+    if value.starts_with('-') {
+        return Err(SshError::InvalidConfig(format!(
+            "{kind}不能以 - 开头: {value}"
+        )));
+    }
+    if value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(SshError::InvalidConfig(format!(
+            "{kind}不能包含空白或控制字符: {value:?}"
+        )));
+    }
+    Ok(())
+}
+
+impl Drop for AskPassScript {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// SSH 命令执行器
+#[derive(Clone)]
+pub struct SSHExecutor {
+    config: SSHConfig,
+    connect_timeout: Duration,
+    timeout: Duration,
+}
+
+impl SSHExecutor {
+    pub fn new(config: SSHConfig) -> Self {
+        Self {
+            config,
+            connect_timeout: SSH_CONNECT_TIMEOUT,
+            timeout: SSH_COMMAND_TIMEOUT,
+        }
+    }
+
+    /// 返回设置了连接超时的执行器副本
+    pub fn with_connect_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            connect_timeout: timeout,
+            ..self.clone()
+        }
+    }
+
+    /// 返回设置了命令超时的执行器副本
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        Self {
+            timeout,
+            ..self.clone()
+        }
+    }
+
+    /// known_hosts 中的主机名（非 22 端口为 `[host]:port`）
+    fn known_hosts_name(&self) -> String {
+        if self.config.port == 22 {
+            self.config.host.clone()
+        } else {
+            format!("[{}]:{}", self.config.host, self.config.port)
+        }
+    }
+
+    /// 需要通过 askpass 提供的口令
+    fn secret(&self) -> Option<&str> {
+        match self.config.auth_method {
+            SshAuthMethod::Password => self.config.password.as_deref(),
+            SshAuthMethod::Key => self.config.passphrase.as_deref(),
+            SshAuthMethod::Agent => None,
+        }
+        .filter(|s| !s.is_empty())
+    }
+
+    /// 构建 ssh 参数（不含远程命令）
+    fn ssh_args(&self) -> Vec<String> {
+        let mut args = vec![
+            "-T".to_string(),
+            "-p".to_string(),
+            self.config.port.to_string(),
+            "-o".to_string(),
+            format!("ConnectTimeout={}", self.connect_timeout.as_secs().max(1)),
+            "-o".to_string(),
+            "StrictHostKeyChecking=yes".to_string(),
+            "-o".to_string(),
+            "ServerAliveInterval=15".to_string(),
+            "-o".to_string(),
+            "NumberOfPasswordPrompts=1".to_string(),
+        ];
+
+        if let Some(proxy) = self.proxy_command() {
+            args.extend(["-o".to_string(), format!("ProxyCommand={}", proxy)]);
+        }
+
+        let interactive = self.secret().is_some();
+        if !interactive {
+            // 没有可提供的口令时禁止任何交互提示，避免挂起
+            args.extend(["-o".to_string(), "BatchMode=yes".to_string()]);
+        }
+
+        match self.config.auth_method {
+            SshAuthMethod::Password => {
+                args.extend([
+                    "-o".to_string(),
+                    "PreferredAuthentications=password,keyboard-interactive".to_string(),
+                    "-o".to_string(),
+                    "PubkeyAuthentication=no".to_string(),
+                ]);
+            }
+            SshAuthMethod::Key | SshAuthMethod::Agent => {
+                args.extend(["-o".to_string(), "PasswordAuthentication=no".to_string()]);
+                if let Some(key) = self.config.key_path.as_deref().filter(|k| !k.is_empty()) {
+                    args.extend(["-i".to_string(), key.to_string()]);
+                    if self.config.auth_method == SshAuthMethod::Key {
+                        args.extend(["-o".to_string(), "IdentitiesOnly=yes".to_string()]);
+                    }
+                }
+            }
+        }
+
+        // 主机与用户已由 check_config 校验，`--` 之后的参数不再按选项解析
+        args.push("--".to_string());
+        args.push(format!("{}@{}", self.config.user, self.config.host));
+        args
+    }
+
+    /// 经跳板机连接时的 ProxyCommand（`ssh <跳板机参数> -W %h:%p`），可多级嵌套
+    fn proxy_command(&self) -> Option<String> {
+        let jump = self.config.jump_host.as_deref()?;
+        let mut jump_args = self.hop(jump).ssh_args();
+        let destination = jump_args.pop().unwrap_or_default();
+        // 去掉目标前的 `--`，放到 `-W` 之后
+        jump_args.pop();
+
+        let mut parts = vec!["ssh".to_string()];
+        // ProxyCommand 中的 % 会被当作转义符展开
+        parts.extend(
+            jump_args
+                .iter()
+                .map(|arg| quote_proxy_arg(&arg.replace('%', "%%"))),
+        );
+        parts.extend([
+            "-W".to_string(),
+            "%h:%p".to_string(),
+            "--".to_string(),
+            quote_proxy_arg(&destination.replace('%', "%%")),
+        ]);
+        Some(parts.join(" "))
+    }
+
+    /// 以相同超时设置连接跳板机的执行器
+    fn hop(&self, jump: &SSHConfig) -> Self {
+        Self {
+            config: jump.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// 校验目标主机与各级跳板机的主机名、用户名
+    fn check_config(&self) -> Result<(), SshError> {
+        let mut hop = Some(&self.config);
+        while let Some(config) = hop {
+            if config.host.trim().is_empty() {
+                return Err(SshError::InvalidConfig("主机名不能为空".to_string()));
+            }
+            check_destination_part("主机名", &config.host)?;
+            check_destination_part("用户名", &config.user)?;
+            hop = config.jump_host.as_deref();
+        }
+        Ok(())
+    }
+
+    /// 跳板机无法交互输入口令（askpass 只服务于目标主机）
+    fn check_jump_hosts(&self) -> Result<(), SshError> {
+        let mut jump = self.config.jump_host.as_deref();
+        while let Some(hop) = jump {
+            if self.hop(hop).secret().is_some() || hop.auth_method == SshAuthMethod::Password {
+                return Err(SshError::UnsupportedJumpAuth(hop.host.clone()));
+            }
+            jump = hop.jump_host.as_deref();
+        }
+        Ok(())
+    }
+
+    /// 在远程主机上执行命令（经登录 shell）
+    ///
+    /// 远程命令本身失败时返回 Ok（见 `CommandResult::success`）；
+    /// 连接、认证、主机密钥等 ssh 层面的错误返回 `SshError`。
+    pub async fn execute(&self, command: &str) -> Result<CommandResult, SshError> {
+        let remote = format!("sh -c {}", sh_quote(&login_shell_script(command)));
+        let mut args = self.ssh_args();
+        args.push(remote);
+        self.run_ssh(&args).await
+    }
+
+    async fn run_ssh(&self, args: &[String]) -> Result<CommandResult, SshError> {
+        self.check_config()?;
+        self.check_jump_hosts()?;
+        let mut executor = CommandExecutor::new().with_timeout(self.timeout);
+
+        // 口令只通过环境变量传给 askpass 脚本
+        let _askpass = match self.secret() {
+            Some(secret) => {
+                let script = AskPassScript::create().map_err(|e| {
+                    SshError::ClientUnavailable(format!("创建 askpass 脚本失败: {e}"))
+                })?;
+                executor = executor
+                    .env("SSH_ASKPASS", script.path.to_string_lossy())
+                    .env("SSH_ASKPASS_REQUIRE", "force")
+                    .env("DISPLAY", ":0")
+                    .env(SECRET_ENV, secret);
+                Some(script)
+            }
+            None => None,
+        };
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let result = executor.execute_args_async("ssh", &args).await;
+
+        if result.timed_out {
+            return Err(SshError::Timeout(self.timeout.as_secs()));
+        }
+        if result.exit_code.is_none() && !result.success {
+            return Err(SshError::ClientUnavailable(result.stderr));
+        }
+        if result.exit_code == Some(SSH_ERROR_EXIT_CODE) {
+            return Err(classify_ssh_error(&self.config.host, &result));
+        }
+        Ok(result)
+    }
+
+    /// 测试连接：依次解析主机名、建立 TCP 连接、认证并执行 `echo ok`
+    ///
+    /// 各阶段单独计时，返回首个失败的阶段；错误信息中的密码和口令会被遮蔽。
+    /// 配置了跳板机时先完整测试跳板机，目标主机只经跳板机访问（跳过本机 DNS/TCP 阶段）。
+    pub async fn test_connection(&self) -> SshTestResult {
+        let started = std::time::Instant::now();
+        let mut result = SshTestResult {
+            success: false,
+            failed_stage: None,
+            failed_hop: None,
+            error: None,
+            host_key_unknown: false,
+            latency_ms: None,
+            elapsed_ms: 0,
+        };
+        let fail = |mut result: SshTestResult, stage: SshTestStage, error: String| {
+            result.failed_stage = Some(stage);
+            result.failed_hop = Some(SshTestHop::Target);
+            result.error = Some(self.redact(&error));
+            result.elapsed_ms = started.elapsed().as_millis() as u64;
+            result
+        };
+
+        if let Some(jump) = self.config.jump_host.as_deref() {
+            let mut bastion = Box::pin(self.hop(jump).test_connection()).await;
+            if !bastion.success {
+                bastion.failed_hop = Some(SshTestHop::Bastion);
+                bastion.error = bastion
+                    .error
+                    .map(|e| format!("跳板机 {}: {}", jump.host, e));
+                bastion.elapsed_ms = started.elapsed().as_millis() as u64;
+                return bastion;
+            }
+            result.latency_ms = bastion.latency_ms;
+        } else {
+            // 1. DNS
+            let lookup = tokio::time::timeout(
+                TEST_DNS_TIMEOUT,
+                tokio::net::lookup_host((self.config.host.as_str(), self.config.port)),
+            )
+            .await;
+            let addrs: Vec<std::net::SocketAddr> = match lookup {
+                Ok(Ok(addrs)) => addrs.collect(),
+                Ok(Err(e)) => {
+                    return fail(result, SshTestStage::Dns, format!("无法解析主机名: {e}"))
+                }
+                Err(_) => {
+                    return fail(
+                        result,
+                        SshTestStage::Dns,
+                        format!("解析主机名超时（{}秒）", TEST_DNS_TIMEOUT.as_secs()),
+                    )
+                }
+            };
+            if addrs.is_empty() {
+                return fail(result, SshTestStage::Dns, "主机名没有可用地址".to_string());
+            }
+
+            // 2. TCP
+            let mut last_error = String::new();
+            for addr in &addrs {
+                let tcp_started = std::time::Instant::now();
+                match tokio::time::timeout(TEST_TCP_TIMEOUT, tokio::net::TcpStream::connect(addr))
+                    .await
+                {
+                    Ok(Ok(_)) => {
+                        result.latency_ms = Some(tcp_started.elapsed().as_millis() as u64);
+                        break;
+                    }
+                    Ok(Err(e)) => last_error = format!("{addr}: {e}"),
+                    Err(_) => {
+                        last_error = format!("{addr}: 连接超时（{}秒）", TEST_TCP_TIMEOUT.as_secs())
+                    }
+                }
+            }
+            if result.latency_ms.is_none() {
+                return fail(result, SshTestStage::Connect, last_error);
+            }
+        }
+
+        // 3. 认证 + 4. 执行
+        let executor = self.with_timeout(TEST_EXEC_TIMEOUT);
+        let mut args = executor.ssh_args();
+        args.push("echo ok".to_string());
+        match executor.run_ssh(&args).await {
+            Ok(output) if output.success && output.stdout.trim_end().ends_with("ok") => {
+                result.success = true;
+                result.elapsed_ms = started.elapsed().as_millis() as u64;
+                result
+            }
+            Ok(output) => fail(result, SshTestStage::Exec, output.failure_detail()),
+            Err(e @ SshError::UnknownHostKey { .. }) => {
+                result.host_key_unknown = true;
+                fail(result, SshTestStage::Auth, e.to_string())
+            }
+            Err(e @ SshError::UnsupportedJumpAuth(_)) => {
+                let mut result = fail(result, SshTestStage::Auth, e.to_string());
+                result.failed_hop = Some(SshTestHop::Bastion);
+                result
+            }
+            Err(e @ (SshError::AuthFailed(_) | SshError::HostKeyChanged { .. })) => {
+                fail(result, SshTestStage::Auth, e.to_string())
+            }
+            Err(e @ SshError::ConnectFailed(_)) => {
+                fail(result, SshTestStage::Connect, e.to_string())
+            }
+            Err(e) => fail(result, SshTestStage::Exec, e.to_string()),
+        }
+    }
+
+    /// 遮蔽文本中的密码和口令
+    fn redact(&self, text: &str) -> String {
+        [&self.config.password, &self.config.passphrase]
+            .into_iter()
+            .flatten()
+            .filter(|secret| !secret.is_empty())
+            .fold(text.to_string(), |text, secret| {
+                text.replace(secret.as_str(), "******")
+            })
+    }
+
+    /// 查询主机密钥是否已受信任；未知时扫描指纹
+    pub async fn host_key_status(&self) -> Result<HostKeyStatus, SshError> {
+        let known = CommandExecutor::new()
+            .with_timeout(self.connect_timeout)
+            .execute_args_async("ssh-keygen", &["-F", &self.known_hosts_name()])
+            .await;
+        if known.success && !known.stdout.trim().is_empty() {
+            return Ok(HostKeyStatus::Known);
+        }
+
+        let (_, fingerprints) = self.scan_host_keys().await?;
+        Ok(HostKeyStatus::Unknown { fingerprints })
+    }
+
+    /// 信任主机密钥（首次连接时由用户确认指纹后调用）
+    ///
+    /// 重新扫描并与用户确认过的指纹比对，一致时才写入 known_hosts
+    pub async fn trust_host_key(&self, expected_fingerprints: &[String]) -> Result<(), SshError> {
+        let (keys, fingerprints) = self.scan_host_keys().await?;
+        if expected_fingerprints.is_empty()
+            || fingerprints.len() != expected_fingerprints.len()
+            || !fingerprints
+                .iter()
+                .all(|fp| expected_fingerprints.contains(fp))
+        {
+            return Err(SshError::FingerprintMismatch);
+        }
+
+        let ssh_dir = dirs::home_dir()
+            .ok_or_else(|| SshError::ClientUnavailable("无法获取用户主目录".to_string()))?
+            .join(".ssh");
+        let append = || -> std::io::Result<()> {
+            std::fs::create_dir_all(&ssh_dir)?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&ssh_dir, std::fs::Permissions::from_mode(0o700))?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(ssh_dir.join("known_hosts"))?;
+            for line in &keys {
+                writeln!(file, "{}", line)?;
+            }
+            Ok(())
+        };
+        append().map_err(|e| SshError::ClientUnavailable(format!("写入 known_hosts 失败: {e}")))?;
+
+        tracing::info!(host = %self.config.host, port = self.config.port, "已信任 SSH 主机密钥");
+        Ok(())
+    }
+
+    /// 扫描主机公钥，返回 (known_hosts 行, SHA256 指纹)
+    async fn scan_host_keys(&self) -> Result<(Vec<String>, Vec<String>), SshError> {
+        self.check_config()?;
+        let timeout = self.connect_timeout.as_secs().max(1).to_string();
+        let port = self.config.port.to_string();
+        let scan = match self.config.jump_host.as_deref() {
+            // 目标主机只能经跳板机访问时，在跳板机上扫描
+            Some(jump) => {
+                let command = format!(
+                    "ssh-keyscan -T {} -p {} -- {}",
+                    timeout,
+                    port,
+                    sh_quote(&self.config.host)
+                );
+                self.hop(jump)
+                    .with_timeout(self.connect_timeout * 2 + Duration::from_secs(5))
+                    .execute(&command)
+                    .await?
+            }
+            None => {
+                CommandExecutor::new()
+                    .with_timeout(self.connect_timeout + Duration::from_secs(5))
+                    .execute_args_async(
+                        "ssh-keyscan",
+                        &["-T", &timeout, "-p", &port, "--", &self.config.host],
+                    )
+                    .await
+            }
+        };
+
+        let keys: Vec<String> = scan
+            .stdout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+        if keys.is_empty() {
+            return Err(SshError::ConnectFailed(format!(
+                "无法获取主机 {} 的公钥: {}",
+                self.config.host,
+                scan.stderr.trim()
+            )));
+        }
+
+        // ssh-keygen -lf 读取文件计算指纹
+        let name = format!(
+            "duckcoding-hostkeys-{}-{}-{}",
+            std::process::id(),
+            self.config.port,
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.subsec_nanos())
+                .unwrap_or_default()
+        );
+        let path = create_private_temp_file(&name, &(keys.join("\n") + "\n"), 0o600)
+            .map_err(|e| SshError::ClientUnavailable(format!("写入临时文件失败: {e}")))?;
+        let fingerprint = CommandExecutor::new()
+            .with_timeout(self.connect_timeout)
+            .execute_args_async("ssh-keygen", &["-lf", &path.to_string_lossy()])
+            .await;
+        let _ = std::fs::remove_file(&path);
+
+        let fingerprints = parse_fingerprints(&fingerprint.stdout);
+        if fingerprints.is_empty() {
+            return Err(SshError::ClientUnavailable(format!(
+                "计算主机密钥指纹失败: {}",
+                fingerprint.stderr.trim()
+            )));
+        }
+        Ok((keys, fingerprints))
+    }
+
+    /// 检测远程主机上的工具：(是否安装, 版本, 路径)
+    ///
+    /// `env` 在执行 `--version` 时注入（实例级环境变量覆盖）
+    pub async fn detect_tool(
+        &self,
+        command: &str,
+        env: &BTreeMap<String, String>,
+    ) -> Result<(bool, Option<String>, Option<String>), SshError> {
+        let which = self
+            .execute(&format!("command -v {}", sh_quote(command)))
+            .await?;
+        let path = which
+            .stdout
+            .lines()
+            .last()
+            .map(str::trim)
+            .filter(|p| which.success && !p.is_empty())
+            .map(str::to_string);
+
+        let Some(path) = path else {
+            return Ok((false, None, None));
+        };
+
+        let version = self.execute(&version_command(&path, env)).await?;
+        let version = version
+            .success
+            .then(|| parse_version_string(version.stdout.trim()))
+            .filter(|v| !v.is_empty());
+
+        Ok((true, version, Some(path)))
+    }
+}
+
+/// 远程执行 `<path> --version` 的命令行，有环境变量时经 `env` 注入
+fn version_command(path: &str, env: &BTreeMap<String, String>) -> String {
+    let mut command = String::new();
+    if !env.is_empty() {
+        command.push_str("env ");
+        for (key, value) in env {
+            command.push_str(&sh_quote(&format!("{key}={value}")));
+            command.push(' ');
+        }
+    }
+    command.push_str(&format!("{} --version", sh_quote(path)));
+    command
+}
+
+/// 把 ssh 自身的错误输出归类
+fn classify_ssh_error(host: &str, result: &CommandResult) -> SshError {
+    let stderr = result.stderr.trim();
+    let lower = stderr.to_lowercase();
+
+    if lower.contains("remote host identification has changed") {
+        return SshError::HostKeyChanged {
+            host: host.to_string(),
+        };
+    }
+    if lower.contains("host key verification failed") || lower.contains("host key is known") {
+        return SshError::UnknownHostKey {
+            host: host.to_string(),
+        };
+    }
+    if lower.contains("permission denied") || lower.contains("too many authentication failures") {
+        return SshError::AuthFailed(stderr.to_string());
+    }
+    if result.issues.contains(&OutputIssue::NetworkError) {
+        return SshError::ConnectFailed(stderr.to_string());
+    }
+    SshError::ConnectFailed(if stderr.is_empty() {
+        "ssh 异常退出".to_string()
+    } else {
+        stderr.to_string()
+    })
+}
+
+/// 解析 `ssh-keygen -lf` 输出中的指纹（`256 SHA256:xxx host (ED25519)`）
+fn parse_fingerprints(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .filter(|fp| fp.starts_with("SHA256:"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 引用 ProxyCommand 中的参数（Unix 由 shell 解析，Windows 由 ssh 按命令行拆分）
+fn quote_proxy_arg(arg: &str) -> String {
+    #[cfg(target_os = "windows")]
+    {
+        if arg.contains([' ', '\t', '"']) {
+            format!("\"{}\"", arg.replace('"', "\\\""))
+        } else {
+            arg.to_string()
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        if arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+%".contains(c))
+        {
+            arg.to_string()
+        } else {
+            sh_quote(arg)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(auth_method: SshAuthMethod) -> SSHConfig {
+        SSHConfig {
+            display_name: "dev".to_string(),
+            host: "dev.example.com".to_string(),
+            port: 2222,
+            user: "duck".to_string(),
+            key_path: Some("/home/duck/.ssh/id_ed25519".to_string()),
+            auth_method,
+            password: Some("secret".to_string()),
+            passphrase: None,
+            jump_host: None,
+        }
+    }
+
+    fn result(stderr: &str) -> CommandResult {
+        let mut result = CommandResult::from_error(std::io::Error::other(stderr.to_string()));
+        result.exit_code = Some(SSH_ERROR_EXIT_CODE);
+        result
+    }
+
+    #[test]
+    fn test_proxy_command_through_jump_hosts() {
+        let mut outer = config(SshAuthMethod::Agent);
+        outer.host = "bastion.example.com".to_string();
+        outer.port = 22;
+        outer.user = "jump".to_string();
+        outer.key_path = Some("/keys/my key".to_string());
+        let mut inner = outer.clone();
+        inner.host = "gw.internal".to_string();
+        inner.jump_host = Some(Box::new(outer));
+        let mut target = config(SshAuthMethod::Key);
+        target.password = None;
+        target.jump_host = Some(Box::new(inner));
+
+        let args = SSHExecutor::new(target.clone()).ssh_args();
+        let proxy = args
+            .iter()
+            .find_map(|a| a.strip_prefix("ProxyCommand="))
+            .unwrap();
+        assert!(proxy.starts_with("ssh "));
+        assert!(proxy.ends_with("-W %h:%p -- jump@gw.internal"));
+        // 第一级跳板的 ProxyCommand 嵌套在第二级参数中，其中的 %h:%p 被转义
+        assert!(proxy.contains("%%h:%%p"));
+        assert!(proxy.contains("bastion.example.com"));
+        assert_eq!(args.last().unwrap(), "duck@dev.example.com");
+        assert_eq!(args[args.len() - 2], "--");
+
+        // 跳板机不支持口令
+        let mut bad = target;
+        let mut jump = config(SshAuthMethod::Password);
+        jump.host = "bastion".to_string();
+        bad.jump_host = Some(Box::new(jump));
+        assert!(matches!(
+            SSHExecutor::new(bad).check_jump_hosts(),
+            Err(SshError::UnsupportedJumpAuth(host)) if host == "bastion"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_bastion_failure() {
+        let mut jump = config(SshAuthMethod::Agent);
+        jump.host = "bastion.invalid".to_string();
+        let mut target = config(SshAuthMethod::Agent);
+        target.host = "10.0.0.5".to_string();
+        target.jump_host = Some(Box::new(jump));
+
+        let result = SSHExecutor::new(target).test_connection().await;
+        assert_eq!(result.failed_hop, Some(SshTestHop::Bastion));
+        assert_eq!(result.failed_stage, Some(SshTestStage::Dns));
+        assert!(result.error.unwrap().contains("bastion.invalid"));
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut cfg = config(SshAuthMethod::Password);
+        cfg.passphrase = Some("pass-phrase".to_string());
+        let executor = SSHExecutor::new(cfg);
+        assert_eq!(
+            executor.redact("bad password secret / pass-phrase"),
+            "bad password ****** / ******"
+        );
+
+        // 空口令不会把整段文本替换掉
+        let mut cfg = config(SshAuthMethod::Agent);
+        cfg.password = Some(String::new());
+        assert_eq!(SSHExecutor::new(cfg).redact("denied"), "denied");
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_dns_stage() {
+        let mut cfg = config(SshAuthMethod::Agent);
+        cfg.host = "nonexistent.invalid".to_string();
+        let result = SSHExecutor::new(cfg).test_connection().await;
+        assert!(!result.success);
+        assert_eq!(result.failed_stage, Some(SshTestStage::Dns));
+        assert!(result.latency_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_connect_stage() {
+        // 先占用再释放一个端口，保证其上没有监听
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut cfg = config(SshAuthMethod::Password);
+        cfg.host = "127.0.0.1".to_string();
+        cfg.port = port;
+        let result = SSHExecutor::new(cfg).test_connection().await;
+        assert_eq!(result.failed_stage, Some(SshTestStage::Connect));
+        assert!(!result.error.unwrap_or_default().contains("secret"));
+    }
+
+    #[test]
+    fn test_ssh_args_by_auth_method() {
+        let agent = SSHExecutor::new(config(SshAuthMethod::Agent)).ssh_args();
+        assert!(agent.contains(&"BatchMode=yes".to_string()));
+        assert!(agent.contains(&"StrictHostKeyChecking=yes".to_string()));
+        assert!(agent.contains(&"-i".to_string()));
+        assert!(!agent.contains(&"IdentitiesOnly=yes".to_string()));
+        assert_eq!(agent.last().unwrap(), "duck@dev.example.com");
+        // 密码不会出现在参数中
+        assert!(!agent.iter().any(|a| a.contains("secret")));
+
+        // 没有口令的密钥认证同样禁止交互
+        let key = SSHExecutor::new(config(SshAuthMethod::Key)).ssh_args();
+        assert!(key.contains(&"IdentitiesOnly=yes".to_string()));
+        assert!(key.contains(&"BatchMode=yes".to_string()));
+
+        let password = SSHExecutor::new(config(SshAuthMethod::Password));
+        let args = password.ssh_args();
+        assert!(!args.contains(&"BatchMode=yes".to_string()));
+        assert!(!args.contains(&"-i".to_string()));
+        assert!(!args.iter().any(|a| a.contains("secret")));
+        assert_eq!(password.secret(), Some("secret"));
+        assert_eq!(password.known_hosts_name(), "[dev.example.com]:2222");
+    }
+
+    #[tokio::test]
+    async fn test_option_like_destinations_rejected() {
+        let mut cfg = config(SshAuthMethod::Agent);
+        cfg.host = "-oProxyCommand=touch /tmp/pwned".to_string();
+        assert!(matches!(
+            SSHExecutor::new(cfg).execute("true").await,
+            Err(SshError::InvalidConfig(_))
+        ));
+
+        let mut cfg = config(SshAuthMethod::Agent);
+        cfg.user = "duck\nProxyCommand".to_string();
+        assert!(SSHExecutor::new(cfg).check_config().is_err());
+
+        let mut jump = config(SshAuthMethod::Agent);
+        jump.user = "-F/tmp/evil".to_string();
+        let mut cfg = config(SshAuthMethod::Agent);
+        cfg.jump_host = Some(Box::new(jump));
+        assert!(matches!(
+            SSHExecutor::new(cfg).check_config(),
+            Err(SshError::InvalidConfig(_))
+        ));
+
+        assert!(SSHExecutor::new(config(SshAuthMethod::Agent))
+            .check_config()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_classify_ssh_errors() {
+        let host = "dev.example.com";
+        assert!(matches!(
+            classify_ssh_error(host, &result("No ED25519 host key is known for dev.example.com and you have requested strict checking.\nHost key verification failed.")),
+            SshError::UnknownHostKey { .. }
+        ));
+        assert!(matches!(
+            classify_ssh_error(host, &result("@@@ WARNING: REMOTE HOST IDENTIFICATION HAS CHANGED! @@@\nHost key verification failed.")),
+            SshError::HostKeyChanged { .. }
+        ));
+        assert!(matches!(
+            classify_ssh_error(
+                host,
+                &result("duck@dev.example.com: Permission denied (publickey).")
+            ),
+            SshError::AuthFailed(_)
+        ));
+        assert!(matches!(
+            classify_ssh_error(
+                host,
+                &result("ssh: connect to host dev.example.com port 2222: Connection refused")
+            ),
+            SshError::ConnectFailed(_)
+        ));
+    }
+
+    #[test]
+    fn test_version_command_with_env() {
+        assert_eq!(
+            version_command("/usr/bin/codex", &BTreeMap::new()),
+            "'/usr/bin/codex' --version"
+        );
+        let env = BTreeMap::from([
+            ("CODEX_HOME".to_string(), "/data/it's".to_string()),
+            (
+                "NODE_OPTIONS".to_string(),
+                "--max-old-space-size=8192".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            version_command("/usr/bin/codex", &env),
+            r"env 'CODEX_HOME=/data/it'\''s' 'NODE_OPTIONS=--max-old-space-size=8192' '/usr/bin/codex' --version"
+        );
+    }
+
+    #[test]
+    fn test_parse_fingerprints() {
+        let output =
+            "256 SHA256:uNiVztksCsDhcc0u9e8BujQXVUpKZIDTMczCvj3tD2s dev.example.com (ED25519)\n\
+                      3072 SHA256:abcdEFGH [dev.example.com]:2222 (RSA)\n";
+        assert_eq!(
+            parse_fingerprints(output),
+            vec![
+                "SHA256:uNiVztksCsDhcc0u9e8BujQXVUpKZIDTMczCvj3tD2s".to_string(),
+                "SHA256:abcdEFGH".to_string()
+            ]
+        );
+        assert!(parse_fingerprints("").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_askpass_script_prints_secret() {
+        let script = AskPassScript::create().unwrap();
+        let output = std::process::Command::new(&script.path)
+            .env(SECRET_ENV, "p@ss word")
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "p@ss word\n");
+
+        let path = script.path.clone();
+        drop(script);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_askpass_script_keeps_special_characters() {
+        let secret = "p&|<>^%PATH%!x!\"q\" on";
+        let script = AskPassScript::create().unwrap();
+        let output = std::process::Command::new(&script.path)
+            .env(SECRET_ENV, secret)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(stdout.trim_end_matches(['\r', '\n']), secret);
+    }
+
+    /// 需要可访问的 sshd：设置 DUCKCODING_TEST_SSH_HOST / _PORT / _USER 后运行 `cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn test_execute_against_real_sshd() {
+        let Ok(host) = std::env::var("DUCKCODING_TEST_SSH_HOST") else {
+            return;
+        };
+        let executor = SSHExecutor::new(SSHConfig {
+            display_name: "ci".to_string(),
+            host,
+            port: std::env::var("DUCKCODING_TEST_SSH_PORT")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(22),
+            user: std::env::var("DUCKCODING_TEST_SSH_USER").unwrap_or_else(|_| "root".into()),
+            key_path: std::env::var("DUCKCODING_TEST_SSH_KEY").ok(),
+            auth_method: SshAuthMethod::Agent,
+            password: None,
+            passphrase: None,
+            jump_host: None,
+        });
+
+        if let HostKeyStatus::Unknown { fingerprints } = executor.host_key_status().await.unwrap() {
+            executor.trust_host_key(&fingerprints).await.unwrap();
+        }
+        let result = executor.execute("echo \"it's ok\"").await.unwrap();
+        assert!(result.success);
+        assert!(result.stdout.ends_with("it's ok"));
+    }
+}
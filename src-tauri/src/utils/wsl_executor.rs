@@ -1,5 +1,6 @@
 use crate::utils::CommandResult;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 #[cfg(target_os = "windows")]
@@ -10,6 +11,119 @@ use std::process::Command;
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
+/// WSL 发行版运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WslDistroState {
+    Running,
+    Stopped,
+    Installing,
+    Converting,
+    Uninstalling,
+    Unknown,
+}
+
+impl WslDistroState {
+    /// 解析 `wsl -l -v` 的状态列（兼容英文与中文系统）
+    fn parse(value: &str) -> Self {
+        match value.to_ascii_lowercase().as_str() {
+            "running" | "正在运行" => Self::Running,
+            "stopped" | "已停止" => Self::Stopped,
+            "installing" | "正在安装" => Self::Installing,
+            "converting" | "正在转换" => Self::Converting,
+            "uninstalling" | "正在卸载" => Self::Uninstalling,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// WSL 发行版信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WslDistro {
+    pub name: String,
+    pub state: WslDistroState,
+    /// WSL 版本（1 或 2）
+    pub version: Option<u8>,
+    /// 是否为默认发行版（`wsl -l -v` 中带 `*` 标记）
+    pub is_default: bool,
+}
+
+/// 解码 wsl.exe 的输出
+///
+/// wsl.exe 管理命令输出 UTF-16LE（通常不带 BOM），发行版内的命令输出 UTF-8
+fn decode_wsl_output(bytes: &[u8]) -> String {
+    let has_bom = bytes.starts_with(&[0xFF, 0xFE]);
+    // 无 BOM 时按奇数位的 0 字节比例判断（ASCII 字符的高字节为 0）
+    let looks_utf16 = bytes.len() >= 2
+        && bytes.len() % 2 == 0
+        && bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count() * 2 >= bytes.len() / 2;
+
+    let text = if has_bom || looks_utf16 {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    };
+    text.replace(['\0', '\u{feff}'], "")
+}
+
+/// 解析 `wsl -l -v` 的输出
+///
+/// 第一行为本地化的表头（NAME / 名称），之后每行为 `[*] 名称 状态 版本`
+fn parse_distro_list(output: &str) -> Vec<WslDistro> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .skip(1)
+        .filter_map(|line| {
+            let line = line.trim();
+            let (is_default, rest) = match line.strip_prefix('*') {
+                Some(rest) => (true, rest.trim_start()),
+                None => (false, line),
+            };
+
+            let mut fields: Vec<&str> = rest.split_whitespace().collect();
+            if fields.len() < 3 {
+                return None;
+            }
+            let version = fields.pop()?.parse::<u8>().ok();
+            let state = WslDistroState::parse(fields.pop()?);
+            Some(WslDistro {
+                name: fields.join(" "),
+                state,
+                version,
+                is_default,
+            })
+        })
+        .collect()
+}
+
+/// WSL 命令的默认超时（冷启动发行版可能需要十几秒）
+pub const WSL_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// which / --version 等探测命令的超时
+pub const WSL_PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 按 POSIX shell 规则把字符串转义为单引号字面量
+pub(crate) fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 把命令包装为在用户登录 shell 中执行的 sh 脚本
+///
+/// WSL / SSH 直接执行的命令不会加载 .profile / .bashrc，nvm 等安装的工具不在 PATH 中。
+/// 这里按用户的默认 shell 选择：zsh / bash 以 `-lic` 执行（Ubuntu 的 .bashrc 在非交互模式下
+/// 会提前返回，nvm 的初始化代码在其后），没有 bash 时退回 `sh -lc`。
+pub(crate) fn login_shell_script(command: &str) -> String {
+    let quoted = sh_quote(command);
+    format!(
+        r#"shell=$(getent passwd "$(id -un)" 2>/dev/null | cut -d: -f7); case "$shell" in */zsh|*/bash) exec "$shell" -lic {quoted} ;; esac; if command -v bash >/dev/null 2>&1; then exec bash -lic {quoted}; fi; exec sh -lc {quoted}"#
+    )
+}
+
 /// WSL 命令执行器
 pub struct WSLExecutor;
 
@@ -55,29 +169,10 @@ impl WSLExecutor {
                 return Err(anyhow::anyhow!("WSL --list 命令执行失败"));
             }
 
-            // 解析输出（可能是 UTF-16 编码）
-            let distros = if output.stdout.starts_with(&[0xFF, 0xFE]) {
-                // UTF-16 LE BOM
-                String::from_utf16_lossy(
-                    &output
-                        .stdout
-                        .chunks_exact(2)
-                        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-                        .collect::<Vec<u16>>(),
-                )
-            } else {
-                String::from_utf8_lossy(&output.stdout).to_string()
-            };
-
-            // 解析每一行，过滤空行和特殊字符
-            let distributions: Vec<String> = distros
+            // 解析每一行，过滤空行
+            let distributions: Vec<String> = decode_wsl_output(&output.stdout)
                 .lines()
-                .map(|line| {
-                    line.trim()
-                        .replace(['\0', '\u{feff}'], "") // BOM
-                        .trim()
-                        .to_string()
-                })
+                .map(|line| line.trim().to_string())
                 .filter(|line| !line.is_empty())
                 .collect();
 
@@ -90,16 +185,63 @@ impl WSLExecutor {
         }
     }
 
+    /// 列出所有发行版及其状态、版本、是否默认（`wsl -l -v`）
+    pub async fn list_distros() -> Result<Vec<WslDistro>> {
+        #[cfg(target_os = "windows")]
+        {
+            let output = tokio::process::Command::new("wsl.exe")
+                .args(["--list", "--verbose"])
+                .creation_flags(0x08000000) // CREATE_NO_WINDOW
+                .output()
+                .await
+                .context("执行 wsl --list --verbose 失败")?;
+
+            let text = decode_wsl_output(&output.stdout);
+            if !output.status.success() {
+                return Err(anyhow::anyhow!(
+                    "WSL --list --verbose 命令执行失败: {}",
+                    text.trim()
+                ));
+            }
+            Ok(parse_distro_list(&text))
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            Err(anyhow::anyhow!("WSL 仅在 Windows 平台可用"))
+        }
+    }
+
+    /// 发行版是否正在运行
+    pub async fn is_distro_running(name: &str) -> Result<bool> {
+        let distros = Self::list_distros().await?;
+        let distro = distros
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| anyhow::anyhow!("未找到 WSL 发行版: {}", name))?;
+        Ok(distro.state == WslDistroState::Running)
+    }
+
     /// 执行 WSL 命令（使用默认发行版）
     pub async fn execute(&self, command: &str) -> Result<CommandResult> {
         self.execute_in_distro(None, command).await
     }
 
-    /// 在指定的 WSL 发行版中执行命令
+    /// 在指定的 WSL 发行版中执行命令（默认超时 `WSL_COMMAND_TIMEOUT`）
     pub async fn execute_in_distro(
         &self,
         distro_name: Option<&str>,
         command: &str,
+    ) -> Result<CommandResult> {
+        self.execute_with_timeout_in_distro(distro_name, command, WSL_COMMAND_TIMEOUT)
+            .await
+    }
+
+    /// 在指定的 WSL 发行版中执行命令（不限时）
+    async fn execute_in_distro_unbounded(
+        &self,
+        distro_name: Option<&str>,
+        command: &str,
     ) -> Result<CommandResult> {
         #[cfg(target_os = "windows")]
         {
@@ -114,46 +256,32 @@ impl WSLExecutor {
     }
 
     /// Windows 平台下执行 WSL 命令
+    ///
+    /// 通过 `login_shell_script` 包装为登录 shell 执行；超时后 wsl.exe 进程随 future 一起被终止
     #[cfg(target_os = "windows")]
     async fn execute_windows(
         &self,
         distro_name: Option<&str>,
         command: &str,
     ) -> Result<CommandResult> {
-        let command = command.to_string();
-        let distro_name = distro_name.map(|s| s.to_string());
+        let mut cmd = tokio::process::Command::new("wsl.exe");
 
-        tokio::task::spawn_blocking(move || {
-            let mut cmd = Command::new("wsl.exe");
+        // 如果指定了发行版，添加 -d 参数
+        if let Some(distro) = distro_name {
+            cmd.arg("-d").arg(distro);
+        }
 
-            // 如果指定了发行版，添加 -d 参数
-            if let Some(distro) = distro_name {
-                cmd.arg("-d").arg(distro);
-            }
+        let output = cmd
+            .args(["--exec", "sh", "-c"])
+            .arg(login_shell_script(command))
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .await
+            .context("执行 WSL 命令失败")?;
 
-            let output = cmd
-                .arg("--exec")
-                .arg("bash")
-                .arg("-c")
-                .arg(&command)
-                .creation_flags(0x08000000) // CREATE_NO_WINDOW
-                .output()
-                .context("执行 WSL 命令失败")?;
-
-            let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-            let success = output.status.success();
-            let exit_code = output.status.code();
-
-            Ok(CommandResult {
-                success,
-                stdout,
-                stderr,
-                exit_code,
-            })
-        })
-        .await
-        .context("WSL 命令执行器 spawn 失败")?
+        Ok(CommandResult::from_output(output))
     }
 
     /// 带超时的执行（使用默认发行版）
@@ -173,7 +301,12 @@ impl WSLExecutor {
         command: &str,
         timeout: Duration,
     ) -> Result<CommandResult> {
-        match tokio::time::timeout(timeout, self.execute_in_distro(distro_name, command)).await {
+        match tokio::time::timeout(
+            timeout,
+            self.execute_in_distro_unbounded(distro_name, command),
+        )
+        .await
+        {
             Ok(result) => result,
             Err(_) => Err(anyhow::anyhow!("WSL 命令执行超时")),
         }
@@ -192,7 +325,7 @@ impl WSLExecutor {
     ) -> bool {
         let check_cmd = format!("which {}", command);
         match self
-            .execute_with_timeout_in_distro(distro_name, &check_cmd, Duration::from_secs(10))
+            .execute_with_timeout_in_distro(distro_name, &check_cmd, WSL_PROBE_TIMEOUT)
             .await
         {
             Ok(result) => result.success && !result.stdout.trim().is_empty(),
@@ -213,7 +346,7 @@ impl WSLExecutor {
     ) -> Option<String> {
         let version_cmd = format!("{} --version", command);
         match self
-            .execute_with_timeout_in_distro(distro_name, &version_cmd, Duration::from_secs(10))
+            .execute_with_timeout_in_distro(distro_name, &version_cmd, WSL_PROBE_TIMEOUT)
             .await
         {
             Ok(result) if result.success => self.extract_version(&result.stdout),
@@ -234,7 +367,7 @@ impl WSLExecutor {
     ) -> Option<String> {
         let which_cmd = format!("which {}", command);
         match self
-            .execute_with_timeout_in_distro(distro_name, &which_cmd, Duration::from_secs(10))
+            .execute_with_timeout_in_distro(distro_name, &which_cmd, WSL_PROBE_TIMEOUT)
             .await
         {
             Ok(result) if result.success => {
@@ -293,6 +426,89 @@ impl Default for WSLExecutor {
     }
 }
 
+#[cfg(test)]
+mod parse_tests {
+    use super::*;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(|u| u.to_le_bytes()).collect()
+    }
+
+    /// Windows 10 (21H2) 英文系统的 `wsl -l -v` 输出
+    const WINDOWS_10_OUTPUT: &str = "  NAME                   STATE           VERSION\r\n\
+* Ubuntu-20.04           Running         2\r\n\
+  docker-desktop-data    Stopped         2\r\n\
+  Debian                 Stopped         1\r\n";
+
+    /// Windows 11 中文系统的 `wsl -l -v` 输出
+    const WINDOWS_11_OUTPUT: &str = "  NAME            STATE           VERSION\r\n\
+  Ubuntu-22.04    已停止          2\r\n\
+* Ubuntu          正在运行        2\r\n\
+  kali-linux      Installing      2\r\n\r\n";
+
+    #[test]
+    fn test_parse_windows_10_output() {
+        let distros = parse_distro_list(&decode_wsl_output(&utf16le(WINDOWS_10_OUTPUT)));
+        assert_eq!(distros.len(), 3);
+        assert_eq!(
+            distros[0],
+            WslDistro {
+                name: "Ubuntu-20.04".to_string(),
+                state: WslDistroState::Running,
+                version: Some(2),
+                is_default: true,
+            }
+        );
+        assert_eq!(distros[1].name, "docker-desktop-data");
+        assert_eq!(distros[1].state, WslDistroState::Stopped);
+        assert!(!distros[1].is_default);
+        assert_eq!(distros[2].version, Some(1));
+    }
+
+    #[test]
+    fn test_parse_windows_11_output() {
+        // 带 BOM 的 UTF-16LE
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(utf16le(WINDOWS_11_OUTPUT));
+        let distros = parse_distro_list(&decode_wsl_output(&bytes));
+
+        let names: Vec<&str> = distros.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["Ubuntu-22.04", "Ubuntu", "kali-linux"]);
+        assert_eq!(distros[0].state, WslDistroState::Stopped);
+        assert_eq!(distros[1].state, WslDistroState::Running);
+        assert!(distros[1].is_default);
+        assert_eq!(distros[2].state, WslDistroState::Installing);
+        assert_eq!(distros.iter().filter(|d| d.is_default).count(), 1);
+    }
+
+    #[test]
+    fn test_sh_quote() {
+        assert_eq!(sh_quote("claude --version"), "'claude --version'");
+        assert_eq!(sh_quote("it's"), r"'it'\''s'");
+        assert_eq!(sh_quote(""), "''");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_login_shell_script_preserves_quoting() {
+        let command = r#"printf '%s|' "a b" 'it'"'"'s' "$((1 + 2))""#;
+        let output = std::process::Command::new("sh")
+            .args(["-c", &login_shell_script(command)])
+            .stdin(std::process::Stdio::null())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.ends_with("a b|it's|3|"), "stdout: {stdout}");
+    }
+
+    #[test]
+    fn test_decode_utf8_output_unchanged() {
+        assert_eq!(decode_wsl_output(b"/usr/bin/node\n"), "/usr/bin/node\n");
+        assert!(parse_distro_list("").is_empty());
+    }
+}
+
 #[cfg(all(test, target_os = "windows"))]
 mod tests {
     use super::*;
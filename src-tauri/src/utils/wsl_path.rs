@@ -0,0 +1,227 @@
+// WSL Path - Windows 与 WSL 路径互转
+//
+// 优先在发行版内调用 `wslpath` 转换（能处理自定义挂载点等情况），
+// 失败时按标准形式纯 Rust 转换：
+// - `C:\Users\x` ↔ `/mnt/c/Users/x`
+// - `\\wsl.localhost\<发行版>\home\x`（或旧的 `\\wsl$\`）↔ `/home/x`
+
+use crate::utils::wsl_executor::sh_quote;
+use crate::utils::WSLExecutor;
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 发行版默认用户主目录缓存（按发行版名称）
+static HOME_DIR_CACHE: Lazy<Mutex<HashMap<String, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// WSL 网络共享的前缀（新版与旧版）
+const WSL_UNC_PREFIXES: [&str; 2] = [r"\\wsl.localhost\", r"\\wsl$\"];
+
+/// 把 WSL 路径转换为 Windows 路径（纯 Rust 实现）
+///
+/// `/mnt/<盘符>/...` 转换为盘符路径，其余绝对路径转换为 `\\wsl.localhost\<发行版>\...`
+pub fn wsl_to_windows_path_fallback(distro: &str, path: &str) -> Option<String> {
+    if !path.starts_with('/') {
+        return None;
+    }
+
+    if let Some(rest) = path.strip_prefix("/mnt/") {
+        let mut parts = rest.splitn(2, '/');
+        let drive = parts.next().unwrap_or("");
+        if drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic()) {
+            let tail = parts.next().unwrap_or("").replace('/', "\\");
+            return Some(format!("{}:\\{}", drive.to_ascii_uppercase(), tail));
+        }
+    }
+
+    let tail = path.trim_start_matches('/').replace('/', "\\");
+    Some(format!(r"\\wsl.localhost\{}\{}", distro, tail))
+}
+
+/// 把 Windows 路径转换为 WSL 路径（纯 Rust 实现）
+///
+/// 返回 (发行版, 路径)：WSL 网络共享路径带有发行版名称，盘符路径为 None
+pub fn windows_to_wsl_path_fallback(path: &str) -> Option<(Option<String>, String)> {
+    for prefix in WSL_UNC_PREFIXES {
+        let matched = path
+            .get(..prefix.len())
+            .filter(|head| head.eq_ignore_ascii_case(prefix));
+        if matched.is_some() {
+            let rest = &path[prefix.len()..];
+            let (distro, tail) = rest.split_once('\\').unwrap_or((rest, ""));
+            if distro.is_empty() {
+                return None;
+            }
+            let tail = tail.trim_end_matches('\\').replace('\\', "/");
+            return Some((Some(distro.to_string()), format!("/{}", tail)));
+        }
+    }
+
+    let mut chars = path.chars();
+    let drive = chars.next()?;
+    if !drive.is_ascii_alphabetic() || chars.next()? != ':' {
+        return None;
+    }
+    let tail = path[2..]
+        .trim_start_matches(['\\', '/'])
+        .trim_end_matches(['\\', '/'])
+        .replace('\\', "/");
+    let base = format!("/mnt/{}", drive.to_ascii_lowercase());
+    Some((
+        None,
+        if tail.is_empty() {
+            base
+        } else {
+            format!("{}/{}", base, tail)
+        },
+    ))
+}
+
+/// 获取发行版默认用户的主目录（带缓存）
+pub async fn wsl_home_dir(distro: &str) -> Result<String> {
+    if let Some(home) = HOME_DIR_CACHE.lock().unwrap().get(distro) {
+        return Ok(home.clone());
+    }
+
+    let result = WSLExecutor::new()
+        .execute_in_distro(Some(distro), "printf '%s' \"$HOME\"")
+        .await?;
+    let home = result.stdout.trim();
+    if !result.success || !home.starts_with('/') {
+        return Err(anyhow!("获取 WSL 发行版 {} 的主目录失败", distro));
+    }
+
+    HOME_DIR_CACHE
+        .lock()
+        .unwrap()
+        .insert(distro.to_string(), home.to_string());
+    Ok(home.to_string())
+}
+
+/// 把 WSL 路径转换为 Windows 路径
+///
+/// 支持 `~/` 开头的路径（展开为发行版默认用户的主目录）
+pub async fn wsl_to_windows_path(distro: &str, path: &str) -> Result<String> {
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => format!("{}/{}", wsl_home_dir(distro).await?, rest),
+        None => path.to_string(),
+    };
+
+    if WSLExecutor::is_available() {
+        let command = format!("wslpath -w {}", sh_quote(&path));
+        match WSLExecutor::new()
+            .execute_in_distro(Some(distro), &command)
+            .await
+        {
+            Ok(result) if result.success && !result.stdout.trim().is_empty() => {
+                return Ok(result.stdout.trim().to_string());
+            }
+            Ok(result) => {
+                tracing::debug!(distro, path = %path, stderr = %result.stderr, "wslpath 转换失败，使用内置规则");
+            }
+            Err(e) => {
+                tracing::debug!(distro, path = %path, error = ?e, "执行 wslpath 失败，使用内置规则");
+            }
+        }
+    }
+
+    wsl_to_windows_path_fallback(distro, &path)
+        .ok_or_else(|| anyhow!("无法转换 WSL 路径: {}", path))
+}
+
+/// 把 Windows 路径转换为 WSL 路径（在默认发行版或路径所属发行版中解析）
+pub async fn windows_to_wsl_path(path: &str) -> Result<String> {
+    let fallback = windows_to_wsl_path_fallback(path);
+
+    if WSLExecutor::is_available() {
+        let distro = fallback.as_ref().and_then(|(distro, _)| distro.clone());
+        let command = format!("wslpath -u {}", sh_quote(path));
+        match WSLExecutor::new()
+            .execute_in_distro(distro.as_deref(), &command)
+            .await
+        {
+            Ok(result) if result.success && !result.stdout.trim().is_empty() => {
+                return Ok(result.stdout.trim().to_string());
+            }
+            Ok(result) => {
+                tracing::debug!(path, stderr = %result.stderr, "wslpath 转换失败，使用内置规则");
+            }
+            Err(e) => {
+                tracing::debug!(path, error = ?e, "执行 wslpath 失败，使用内置规则");
+            }
+        }
+    }
+
+    fallback
+        .map(|(_, wsl_path)| wsl_path)
+        .ok_or_else(|| anyhow!("无法转换 Windows 路径: {}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wsl_to_windows_fallback() {
+        assert_eq!(
+            wsl_to_windows_path_fallback("Ubuntu", "/mnt/c/Users/x").as_deref(),
+            Some(r"C:\Users\x")
+        );
+        assert_eq!(
+            wsl_to_windows_path_fallback("Ubuntu", "/mnt/d").as_deref(),
+            Some(r"D:\")
+        );
+        assert_eq!(
+            wsl_to_windows_path_fallback("Ubuntu", "/home/user/.nvm/bin/claude").as_deref(),
+            Some(r"\\wsl.localhost\Ubuntu\home\user\.nvm\bin\claude")
+        );
+        // 发行版名称含空格；/mnt/wsl 不是盘符
+        assert_eq!(
+            wsl_to_windows_path_fallback("Ubuntu 22.04 LTS", "/mnt/wsl/x").as_deref(),
+            Some(r"\\wsl.localhost\Ubuntu 22.04 LTS\mnt\wsl\x")
+        );
+        assert!(wsl_to_windows_path_fallback("Ubuntu", "relative/path").is_none());
+    }
+
+    #[test]
+    fn test_windows_to_wsl_fallback() {
+        assert_eq!(
+            windows_to_wsl_path_fallback(r"C:\Users\x"),
+            Some((None, "/mnt/c/Users/x".to_string()))
+        );
+        assert_eq!(
+            windows_to_wsl_path_fallback(r"d:\"),
+            Some((None, "/mnt/d".to_string()))
+        );
+        assert_eq!(
+            windows_to_wsl_path_fallback(r"\\wsl.localhost\Ubuntu 22.04 LTS\home\user\"),
+            Some((
+                Some("Ubuntu 22.04 LTS".to_string()),
+                "/home/user".to_string()
+            ))
+        );
+        assert_eq!(
+            windows_to_wsl_path_fallback(r"\\WSL$\Debian\etc"),
+            Some((Some("Debian".to_string()), "/etc".to_string()))
+        );
+        assert_eq!(
+            windows_to_wsl_path_fallback(r"\\wsl.localhost\Ubuntu"),
+            Some((Some("Ubuntu".to_string()), "/".to_string()))
+        );
+        // 其他网络共享与相对路径无法转换
+        assert!(windows_to_wsl_path_fallback(r"\\server\share\x").is_none());
+        assert!(windows_to_wsl_path_fallback(r"\\wsl.localhost\").is_none());
+        assert!(windows_to_wsl_path_fallback("relative").is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let windows = wsl_to_windows_path_fallback("Arch", "/usr/local/bin/codex").unwrap();
+        assert_eq!(
+            windows_to_wsl_path_fallback(&windows),
+            Some((Some("Arch".to_string()), "/usr/local/bin/codex".to_string()))
+        );
+    }
+}
@@ -111,8 +111,8 @@ function App() {
         const update = await checkForAppUpdates();
         setUpdateInfo(update);
 
-        // 如果有可用更新，直接打开更新弹窗
-        if (update.has_update) {
+        // 如果有可用更新，直接打开更新弹窗（自动检查时跳过用户已忽略的版本）
+        if (update.has_update && (force || !update.skipped)) {
           setIsUpdateDialogOpen(true);
         }
       } catch (error) {
@@ -224,6 +224,11 @@ function App() {
       });
     });
 
+    // 监听托盘菜单触发的页面切换（如「打开仪表板」）
+    const unlistenTrayOpenPage = listen<string>('tray-open-page', (event) => {
+      setActiveTab(event.payload as TabType);
+    });
+
     // 监听打开设置事件（用于引导流程）
     const unlistenOpenSettings = listen<{ tab?: string; restrictToTab?: boolean }>(
       'open-settings',
@@ -279,11 +284,24 @@ function App() {
       unlistenRequestCheck.then((fn) => fn());
       unlistenNotFound.then((fn) => fn());
       unlistenOpenSettings.then((fn) => fn());
+      unlistenTrayOpenPage.then((fn) => fn());
       unlistenOnboardingNavigate.then((fn) => fn());
       unlistenClearRestriction.then((fn) => fn());
     };
   }, [toast]);
 
+  // 后台重新检测到工具状态变化时更新列表
+  useEffect(() => {
+    const unlisten = listen<ToolStatus>('tool-status-changed', (event) => {
+      const changed = event.payload;
+      setTools((prev) => prev.map((tool) => (tool.id === changed.id ? changed : tool)));
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // 使用关闭动作 Hook
   const {
     closeDialogOpen,
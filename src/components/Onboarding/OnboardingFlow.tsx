@@ -23,6 +23,16 @@ export default function OnboardingFlow({ steps, onComplete }: OnboardingFlowProp
   const isLast = currentStepIndex === steps.length - 1;
 
   const handleNext = async (skipped = false) => {
+    // 在后端记录步骤状态（清空 WebView 数据后仍可恢复）
+    try {
+      await invoke('mark_onboarding_step', {
+        step: currentStep.id,
+        skipped: skipped && !!currentStep.skippable,
+      });
+    } catch (error) {
+      console.error('记录引导步骤失败:', error);
+    }
+
     // 记录跳过的步骤
     if (skipped && currentStep.skippable) {
       const newSkippedSteps = [...skippedSteps, currentStep.id];
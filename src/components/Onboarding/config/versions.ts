@@ -15,11 +15,13 @@ import UpdateCompleteStep from '@/components/Onboarding/steps/v2/UpdateCompleteS
 
 import ToolDetectionStep from '@/components/Onboarding/steps/v3/ToolDetectionStep';
 
+import TelemetryStep from '@/components/Onboarding/steps/v4/TelemetryStep';
+
 /**
  * 当前引导版本
  * 每次添加新版本时更新此常量
  */
-export const CURRENT_ONBOARDING_VERSION = 'v3';
+export const CURRENT_ONBOARDING_VERSION = 'v4';
 
 /**
  * 各版本的引导步骤配置
@@ -95,6 +97,15 @@ export const VERSION_STEPS: VersionStepsMap = {
       skippable: false,
     },
   ],
+  v4: [
+    {
+      id: 'telemetry',
+      title: '匿名使用统计',
+      description: '选择是否帮助改进 DuckCoding（默认关闭）',
+      component: TelemetryStep,
+      skippable: true,
+    },
+  ],
 };
 
 /**
@@ -0,0 +1,96 @@
+// filepath: e:\DuckCoding\src\components\Onboarding\steps\v4\TelemetryStep.tsx
+
+import { useEffect, useState } from 'react';
+import type { StepProps } from '../../../../types/onboarding';
+import { getTelemetryPreview, setTelemetryEnabled } from '@/lib/tauri-commands';
+import type { TelemetryPing } from '@/lib/tauri-commands';
+
+export default function TelemetryStep({ onNext, onPrevious, isFirst }: StepProps) {
+  const [preview, setPreview] = useState<TelemetryPing | null>(null);
+  const [saving, setSaving] = useState(false);
+
+  useEffect(() => {
+    getTelemetryPreview()
+      .then(setPreview)
+      .catch((error) => console.error('获取使用统计预览失败:', error));
+  }, []);
+
+  const handleChoice = async (enabled: boolean) => {
+    setSaving(true);
+    try {
+      await setTelemetryEnabled(enabled);
+    } catch (error) {
+      console.error('保存使用统计设置失败:', error);
+    } finally {
+      setSaving(false);
+    }
+    onNext();
+  };
+
+  return (
+    <div className="onboarding-step telemetry-step">
+      <div className="step-content">
+        <h2 className="step-title">匿名使用统计（可选）</h2>
+        <p className="step-description">
+          帮助我们了解哪些功能真正被使用（例如透明代理与直接应用配置、本地与 WSL 环境），默认关闭
+        </p>
+
+        <div className="info-box">
+          <div className="info-icon">🔒</div>
+          <div className="info-content">
+            <h3>只统计功能名称和次数</h3>
+            <p>
+              不记录任何参数、路径、供应商或 API Key，也不包含设备或用户标识。开启后每天最多发送一次。
+            </p>
+          </div>
+        </div>
+
+        <div className="config-hint">
+          <h3>发送内容示例</h3>
+          <pre className="step-code">
+            {JSON.stringify(
+              preview ?? {
+                schema_version: 1,
+                app_version: '',
+                os: '',
+                period_start: 0,
+                period_end: 0,
+                counters: { proxy_started: 3 },
+              },
+              null,
+              2,
+            )}
+          </pre>
+        </div>
+
+        <p className="step-note">
+          您可以在设置中随时关闭，关闭时会删除尚未发送的数据；发送历史可随时查看
+        </p>
+
+        <div className="action-buttons">
+          <button type="button" className="btn-secondary" onClick={onPrevious} disabled={isFirst}>
+            上一步
+          </button>
+          <div className="action-right">
+            <button
+              type="button"
+              className="btn-secondary"
+              onClick={() => handleChoice(false)}
+              disabled={saving}
+            >
+              不参与
+            </button>
+            <button
+              type="button"
+              className="btn-primary"
+              onClick={() => handleChoice(true)}
+              disabled={saving}
+            >
+              参与统计
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
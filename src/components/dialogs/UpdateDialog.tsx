@@ -22,7 +22,7 @@ import {
 } from 'lucide-react';
 import { useUpdate } from '@/hooks/useUpdate';
 import { useToast } from '@/hooks/use-toast';
-import type { UpdateInfo } from '@/lib/tauri-commands';
+import { skipVersion, type UpdateInfo } from '@/lib/tauri-commands';
 import { useState } from 'react';
 
 interface UpdateDialogProps {
@@ -82,6 +82,25 @@ export function UpdateDialog({
     onOpenChange(newOpen);
   };
 
+  // 跳过当前版本：启动时的自动检查不再提醒，出现更新的版本时自动恢复
+  const handleSkipVersion = async () => {
+    if (!updateInfo) return;
+    try {
+      await skipVersion('duckcoding', updateInfo.latest_version);
+      toast({
+        title: '已跳过此版本',
+        description: `不再提醒 v${updateInfo.latest_version}，有更新的版本时会再次提示`,
+      });
+      onOpenChange(false);
+    } catch (error) {
+      toast({
+        title: '跳过版本失败',
+        description: String(error),
+        variant: 'destructive',
+      });
+    }
+  };
+
   // 当对话框打开时，如果没有更新信息则自动检查更新
   useEffect(() => {
     // 延迟一下，等待 props 更新完成
@@ -410,6 +429,11 @@ export function UpdateDialog({
             <Download className="mr-2 h-4 w-4" />
             立即更新
           </Button>
+          {!updateInfo.required && (
+            <Button onClick={handleSkipVersion} variant="ghost" className="w-full">
+              {updateInfo.skipped ? '已跳过此版本' : '跳过此版本'}
+            </Button>
+          )}
         </div>
       );
     }
@@ -0,0 +1,92 @@
+import { useEffect, useState } from 'react';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import {
+  listActiveOperations,
+  OPERATION_FINISHED_EVENT,
+  OPERATION_PROGRESS_EVENT,
+  type OperationFinished,
+  type OperationInfo,
+  type OperationKind,
+  type OperationProgress,
+} from '@/lib/tauri-commands';
+
+/**
+ * 跟踪进行中的长耗时操作（安装、更新、检测、扫描）
+ *
+ * 挂载时先读取后端登记表，随后按 operation-progress / operation-finished 事件增量更新。
+ * @param kinds - 只关注的操作类型（默认全部）
+ */
+export function useOperations(kinds?: OperationKind[]) {
+  const [operations, setOperations] = useState<OperationInfo[]>([]);
+  const kindsKey = kinds?.join(',') ?? '';
+
+  useEffect(() => {
+    const wanted = kindsKey ? kindsKey.split(',') : null;
+    const matches = (kind: OperationKind) => !wanted || wanted.includes(kind);
+    const unlisteners: UnlistenFn[] = [];
+    let disposed = false;
+
+    listActiveOperations()
+      .then((active) => {
+        if (!disposed) {
+          setOperations(active.filter((op) => matches(op.kind)));
+        }
+      })
+      .catch((error) => console.error('获取进行中的操作失败:', error));
+
+    const register = async () => {
+      const unlistenProgress = await listen<OperationProgress>(
+        OPERATION_PROGRESS_EVENT,
+        ({ payload }) => {
+          if (!matches(payload.kind)) {
+            return;
+          }
+          setOperations((prev) => {
+            const existing = prev.find((op) => op.operation_id === payload.operation_id);
+            if (!existing) {
+              const target = payload.payload?.target;
+              return [
+                ...prev,
+                {
+                  operation_id: payload.operation_id,
+                  kind: payload.kind,
+                  target: typeof target === 'string' ? target : '',
+                  phase: payload.phase,
+                  percent: payload.percent,
+                  message: payload.message,
+                  started_at: Date.now(),
+                  cancellable: payload.kind === 'detection' || payload.kind === 'scan',
+                },
+              ];
+            }
+            return prev.map((op) =>
+              op.operation_id === payload.operation_id
+                ? { ...op, phase: payload.phase, percent: payload.percent, message: payload.message }
+                : op,
+            );
+          });
+        },
+      );
+      const unlistenFinished = await listen<OperationFinished>(
+        OPERATION_FINISHED_EVENT,
+        ({ payload }) => {
+          setOperations((prev) => prev.filter((op) => op.operation_id !== payload.operation_id));
+        },
+      );
+      if (disposed) {
+        unlistenProgress();
+        unlistenFinished();
+      } else {
+        unlisteners.push(unlistenProgress, unlistenFinished);
+      }
+    };
+    register().catch((error) => console.error('监听操作进度失败:', error));
+
+    return () => {
+      disposed = true;
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, [kindsKey]);
+
+  return operations;
+}
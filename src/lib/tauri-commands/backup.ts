@@ -0,0 +1,38 @@
+// 完整状态备份命令模块
+// 供应商、工具实例、仪表板、代理配置与 Profile 打包备份到用户选择的目录，包含密钥时加密
+
+import { invokeCommand } from './error';
+import type { BackupRunResult, RestoreReport, StateBackupSettings } from './types';
+
+/**
+ * 获取完整状态备份设置
+ */
+export async function getStateBackupSettings(): Promise<StateBackupSettings> {
+  return await invokeCommand<StateBackupSettings>('get_state_backup_settings');
+}
+
+/**
+ * 保存完整状态备份设置（备份内容包含密钥时必须设置口令）
+ */
+export async function saveStateBackupSettings(
+  settings: StateBackupSettings,
+): Promise<StateBackupSettings> {
+  return await invokeCommand<StateBackupSettings>('save_state_backup_settings', { settings });
+}
+
+/**
+ * 按当前设置立即备份（结果同时通过 state-backup 事件推送）
+ */
+export async function runBackupNow(): Promise<BackupRunResult> {
+  return await invokeCommand<BackupRunResult>('run_backup_now');
+}
+
+/**
+ * 从备份文件恢复全部状态，返回每个组成的结果；恢复前自动保存当前状态的安全快照
+ */
+export async function restoreFullBackup(path: string, passphrase?: string): Promise<RestoreReport> {
+  return await invokeCommand<RestoreReport>('restore_full_backup', {
+    path,
+    passphrase: passphrase ?? null,
+  });
+}
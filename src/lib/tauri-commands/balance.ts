@@ -1,8 +1,14 @@
 // 余额监控命令模块
-// 负责余额配置的 CRUD 和数据迁移
+// 负责余额配置的 CRUD、数据迁移和供应商余额查询
 
 import { invoke } from '@tauri-apps/api/core';
-import type { BalanceStore, BalanceConfigBackend } from './types';
+import type {
+  AggregateBalance,
+  Balance,
+  BalanceDetail,
+  BalanceStore,
+  BalanceConfigBackend,
+} from './types';
 import type { BalanceConfig } from '@/pages/BalancePage/types';
 
 /**
@@ -86,3 +92,37 @@ export async function migrateBalanceFromLocalstorage(configs: BalanceConfig[]):
     configs: configs.map(toBackendConfig),
   });
 }
+
+/**
+ * 查询供应商余额（默认复用轮询缓存）
+ */
+export async function getProviderBalance(
+  providerId: string,
+  forceRefresh?: boolean,
+): Promise<Balance> {
+  return invoke<Balance>('get_provider_balance_info', { providerId, forceRefresh });
+}
+
+/**
+ * 查询供应商余额详情（含本计费周期按模型的用量明细）
+ */
+export async function getProviderBalanceDetail(
+  providerId: string,
+  forceRefresh?: boolean,
+): Promise<BalanceDetail> {
+  return invoke<BalanceDetail>('get_provider_balance_detail_info', { providerId, forceRefresh });
+}
+
+/**
+ * 获取供应商余额历史（按时间升序）
+ */
+export async function getBalanceHistory(providerId: string): Promise<Balance[]> {
+  return invoke<Balance[]>('get_balance_history', { providerId });
+}
+
+/**
+ * 汇总所有供应商的剩余额度（美元，仅使用缓存，不发起请求）
+ */
+export async function getAggregateBalance(): Promise<AggregateBalance> {
+  return invoke<AggregateBalance>('get_aggregate_balance');
+}
@@ -2,7 +2,9 @@
 // 负责全局配置、工具配置、代理配置、外部变更监听等功能
 
 import { invoke } from '@tauri-apps/api/core';
+import { invokeCommand } from './error';
 import type {
+  BackendLocale,
   GlobalConfig,
   ClaudeSettingsPayload,
   CodexSettingsPayload,
@@ -14,7 +16,10 @@ import type {
   TestProxyResult,
   ProxyTestConfig,
   ExternalConfigChange,
+  FileProvenance,
+  EffectiveConfig,
   ImportExternalChangeResult,
+  AppPaths,
 } from './types';
 
 // ==================== 全局配置 ====================
@@ -220,6 +225,26 @@ export async function ackExternalChange(tool: string): Promise<void> {
   return await invoke<void>('ack_external_change', { tool });
 }
 
+/**
+ * 查询配置文件来源（是否仍为 DuckCoding 最后一次写入的内容）
+ */
+export async function getFileProvenance(path: string): Promise<FileProvenance> {
+  return await invoke<FileProvenance>('get_file_provenance', { path });
+}
+
+/**
+ * 解析工具实际生效的配置（标注每个值的来源，并与当前应用的配置比较）
+ */
+export async function resolveEffectiveConfig(
+  toolId: string,
+  projectDir?: string,
+): Promise<EffectiveConfig> {
+  return await invokeCommand<EffectiveConfig>('resolve_effective_config', {
+    toolId,
+    projectDir: projectDir ?? null,
+  });
+}
+
 /**
  * 导入原生配置变更为 Profile
  */
@@ -235,6 +260,136 @@ export async function importNativeChange(
   });
 }
 
+// ==================== 数据目录 ====================
+
+/**
+ * 获取 DuckCoding 各项数据的存放位置
+ * @returns 配置目录及其来源、各数据文件与日志目录
+ */
+export async function getAppPaths(): Promise<AppPaths> {
+  return await invoke<AppPaths>('get_app_paths');
+}
+
+/**
+ * 可在文件管理器中打开的位置
+ * - instance_install：工具实例安装位置（id 为实例 ID）
+ * - tool_config：工具配置目录（id 为工具 ID 或实例 ID）
+ * - app_config：DuckCoding 配置目录
+ * - logs：日志目录
+ */
+export type RevealKind = 'instance_install' | 'tool_config' | 'app_config' | 'logs';
+
+/**
+ * 在系统文件管理器中打开指定位置（路径不存在时抛出 NOT_FOUND）
+ * @returns 实际打开的路径
+ */
+export async function revealPath(kind: RevealKind, id?: string): Promise<string> {
+  return await invokeCommand<string>('reveal_path', { kind, id });
+}
+
+// ==================== 清理 DuckCoding 管理的状态 ====================
+
+/**
+ * 清理范围
+ * - tool_configs：还原被代理接入 / 代理 Profile 改动的工具配置
+ * - proxy：停止并禁用透明代理
+ * - providers：删除自定义供应商（破坏性）
+ * - instance_db：删除工具实例数据库（破坏性）
+ * - app_config_dir：删除整个应用配置目录（破坏性）
+ */
+export type CleanupScope =
+  | 'tool_configs'
+  | 'proxy'
+  | 'providers'
+  | 'instance_db'
+  | 'app_config_dir';
+
+export interface CleanupItem {
+  scope: CleanupScope;
+  target: string;
+  action: string;
+  status: 'planned' | 'done' | 'failed';
+  message: string;
+}
+
+export interface CleanupReport {
+  dry_run: boolean;
+  items: CleanupItem[];
+  /** 执行破坏性范围所需的确认令牌（仅预演时返回） */
+  confirmation_token?: string;
+  success: boolean;
+}
+
+/**
+ * 清理 DuckCoding 管理的状态
+ *
+ * 先以 dryRun 预演获取计划与确认令牌，包含破坏性范围时执行需携带该令牌
+ */
+export async function cleanupManagedState(
+  scopes: CleanupScope[],
+  dryRun: boolean,
+  confirmationToken?: string,
+): Promise<CleanupReport> {
+  return await invokeCommand<CleanupReport>('cleanup_managed_state', {
+    scopes,
+    dryRun,
+    confirmationToken,
+  });
+}
+
+// ==================== Shell 环境捕获配置 ====================
+
+/**
+ * 获取 shell 环境捕获配置状态（仅 macOS 生效）
+ * @returns 是否启用
+ */
+export async function getShellEnvCaptureConfig(): Promise<boolean> {
+  return await invoke<boolean>('get_shell_env_capture_config');
+}
+
+/**
+ * 更新 shell 环境捕获配置（需要重启应用生效）
+ * @param enabled - 是否启用
+ */
+export async function updateShellEnvCaptureConfig(enabled: boolean): Promise<void> {
+  return await invoke<void>('update_shell_env_capture_config', { enabled });
+}
+
+// ==================== 持久化写入配置 ====================
+
+/**
+ * 获取持久化写入（fsync）配置状态
+ * @returns 是否启用
+ */
+export async function getDurableWritesConfig(): Promise<boolean> {
+  return await invoke<boolean>('get_durable_writes_config');
+}
+
+/**
+ * 更新持久化写入配置（立即生效）
+ * @param enabled - 是否启用
+ */
+export async function updateDurableWritesConfig(enabled: boolean): Promise<void> {
+  return await invoke<void>('update_durable_writes_config', { enabled });
+}
+
+// ==================== 后端文案语言 ====================
+
+/**
+ * 获取后端文案语言（错误提示等）
+ */
+export async function getBackendLocale(): Promise<BackendLocale> {
+  return await invoke<BackendLocale>('get_backend_locale');
+}
+
+/**
+ * 设置后端文案语言（立即生效并持久化）
+ * @param locale - 语言代码
+ */
+export async function setBackendLocale(locale: BackendLocale): Promise<void> {
+  return await invoke<void>('set_backend_locale', { locale });
+}
+
 // ==================== 单实例模式配置 ====================
 
 /**
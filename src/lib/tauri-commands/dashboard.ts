@@ -1,7 +1,8 @@
 // Dashboard 管理命令模块
 // 负责仪表板状态管理：工具实例选择、选中供应商 Tab
 
-import { invoke } from '@tauri-apps/api/core';
+import { invokeCommand } from './error';
+import type { AllProxyStatus, Provider, ToolStatus } from './types';
 
 /**
  * 获取工具实例选择
@@ -9,7 +10,7 @@ import { invoke } from '@tauri-apps/api/core';
  * @returns 实例 ID（如 "claude-code-local"）或 null
  */
 export async function getToolInstanceSelection(toolId: string): Promise<string | null> {
-  return invoke<string | null>('get_tool_instance_selection', { toolId });
+  return invokeCommand<string | null>('get_tool_instance_selection', { toolId });
 }
 
 /**
@@ -18,7 +19,7 @@ export async function getToolInstanceSelection(toolId: string): Promise<string |
  * @param instanceId 实例 ID
  */
 export async function setToolInstanceSelection(toolId: string, instanceId: string): Promise<void> {
-  return invoke<void>('set_tool_instance_selection', { toolId, instanceId });
+  return invokeCommand<void>('set_tool_instance_selection', { toolId, instanceId });
 }
 
 /**
@@ -26,7 +27,7 @@ export async function setToolInstanceSelection(toolId: string, instanceId: strin
  * @returns 供应商 ID 或 null
  */
 export async function getSelectedProviderId(): Promise<string | null> {
-  return invoke<string | null>('get_selected_provider_id');
+  return invokeCommand<string | null>('get_selected_provider_id');
 }
 
 /**
@@ -34,5 +35,179 @@ export async function getSelectedProviderId(): Promise<string | null> {
  * @param providerId 供应商 ID（传 null 表示清除）
  */
 export async function setSelectedProviderId(providerId: string | null): Promise<void> {
-  return invoke<void>('set_selected_provider_id', { providerId });
+  return invokeCommand<void>('set_selected_provider_id', { providerId });
+}
+
+/** 工具实例的启动偏好 */
+export interface LaunchPreferences {
+  cwd?: string; // 上次使用的工作目录
+  extra_args: string[]; // 额外命令行参数（最多 32 个）
+  env_overrides: Record<string, string>;
+}
+
+/** 返回前端的启动偏好（附带实例级环境变量覆盖，启动时先应用实例级覆盖） */
+export interface LaunchPreferencesView extends LaunchPreferences {
+  instance_env_overrides: Record<string, string>; // 实例级环境变量覆盖（敏感值已脱敏）
+}
+
+/**
+ * 获取工具实例的启动偏好
+ * @param instanceId 实例 ID
+ */
+export async function getLaunchPreferences(
+  instanceId: string,
+): Promise<LaunchPreferencesView | null> {
+  return invokeCommand<LaunchPreferencesView | null>('get_launch_preferences', { instanceId });
+}
+
+/**
+ * 设置工具实例的启动偏好（本地实例要求工作目录存在）
+ * @param instanceId 实例 ID
+ * @param preferences 启动偏好
+ */
+export async function setLaunchPreferences(
+  instanceId: string,
+  preferences: LaunchPreferences,
+): Promise<void> {
+  return invokeCommand<void>('set_launch_preferences', { instanceId, preferences });
+}
+
+/**
+ * 置顶供应商
+ * @param providerId 供应商 ID
+ * @returns 置顶后的供应商 ID 列表（按置顶顺序）
+ */
+export async function pinProvider(providerId: string): Promise<string[]> {
+  return invokeCommand<string[]>('pin_provider', { providerId });
+}
+
+/**
+ * 取消置顶供应商
+ * @param providerId 供应商 ID
+ * @returns 剩余的置顶供应商 ID 列表
+ */
+export async function unpinProvider(providerId: string): Promise<string[]> {
+  return invokeCommand<string[]>('unpin_provider', { providerId });
+}
+
+/** 活动类型 */
+export type ActivityKind =
+  | 'tool_installed'
+  | 'tool_updated'
+  | 'profile_activated'
+  | 'proxy_started'
+  | 'proxy_stopped';
+
+/** 最近活动记录 */
+export interface ActivityEntry {
+  timestamp: number; // Unix 时间戳（秒）
+  kind: ActivityKind;
+  subject: string; // 工具 ID 或实例 ID
+  detail?: string; // 新版本号、Profile 名称等
+}
+
+/**
+ * 获取最近活动记录（按时间倒序）
+ * @param limit 最多返回条数，默认 20
+ */
+export async function getRecentActivity(limit?: number): Promise<ActivityEntry[]> {
+  return invokeCommand<ActivityEntry[]>('get_recent_activity', { limit });
+}
+
+/**
+ * 清空活动记录
+ */
+export async function clearActivity(): Promise<void> {
+  return invokeCommand<void>('clear_activity');
+}
+
+/** 清理失效选择后后端发送的事件名称 */
+export const DASHBOARD_SELECTION_CLEARED_EVENT = 'dashboard-selection-cleared';
+
+/** 被清理的仪表板选择（key: 工具 ID） */
+export interface DashboardSelectionCleared {
+  removed_instance_selections: Record<string, string>;
+  cleared_provider_id: string | null;
+  removed_provider_bindings: Record<string, string>;
+  removed_pinned_provider_ids: string[];
+  removed_launch_preferences: string[];
+}
+
+/**
+ * 手动修复仪表板选择（移除指向已删除实例或供应商的记录）
+ * @returns 被清理的内容
+ */
+export async function repairDashboardSelections(): Promise<DashboardSelectionCleared> {
+  return invokeCommand<DashboardSelectionCleared>('repair_dashboard_selections');
+}
+
+/** 仪表板快照中的一个分区（加载失败时 data 为 null，error 为错误信息） */
+export interface SnapshotSection<T> {
+  data: T | null;
+  error: string | null;
+}
+
+/** 仪表板快照（供应商令牌已脱敏） */
+export interface DashboardSnapshot {
+  tool_status: SnapshotSection<ToolStatus[]>;
+  selections: SnapshotSection<{
+    tool_instance_selections: Record<string, string>;
+    selected_provider_id: string | null;
+  }>;
+  providers: SnapshotSection<{
+    pinned: Provider[]; // 按置顶顺序
+    others: Provider[];
+  }>;
+  proxy_status: SnapshotSection<AllProxyStatus>;
+  path_mismatches: SnapshotSection<PathMismatch[]>;
+  binding_readiness: SnapshotSection<ToolReadiness[]> | null; // 仅在 validateBindings 为 true 时返回
+}
+
+/** 工具绑定供应商的就绪状态 */
+export type ReadinessStatus =
+  | 'ready'
+  | 'rejected'
+  | 'unreachable'
+  | 'timeout'
+  | 'unverifiable'
+  | 'provider_missing';
+
+/** 就绪检查失败时的修复提示 */
+export type ReadinessHint =
+  | { kind: 'revalidate_provider'; provider_id: string; provider_name: string }
+  | { kind: 'rebind_provider'; tool_id: string };
+
+export interface ToolReadiness {
+  tool_id: string;
+  provider_id: string;
+  provider_name: string | null;
+  status: ReadinessStatus;
+  error: string | null; // 供应商返回的错误详情
+  hint: ReadinessHint | null;
+  checked_at: number;
+  cached: boolean;
+}
+
+/** 路径不一致的修复建议 */
+export type PathMismatchFix =
+  | { kind: 'switch_instance'; instance_id: string }
+  | { kind: 'reorder_path'; preferred_dir: string; shadowed_by: string };
+
+/** 所选实例与 shell 中实际运行的命令不一致（WSL/SSH 实例不检查） */
+export interface PathMismatch {
+  tool_id: string;
+  instance_id: string;
+  selected_path: string; // 所选实例的安装路径
+  resolved_path: string; // PATH 上实际解析到的路径
+  source: 'shell' | 'effective'; // 登录 shell 的 PATH 或 DuckCoding 的增强 PATH
+  suggestions: PathMismatchFix[]; // 按推荐顺序
+}
+
+/**
+ * 一次性获取仪表板首屏数据（工具状态、选择、供应商、代理状态）
+ *
+ * @param validateBindings 同时验证各工具绑定的供应商（最多约 3 秒）
+ */
+export async function getDashboardSnapshot(validateBindings = false): Promise<DashboardSnapshot> {
+  return invokeCommand<DashboardSnapshot>('get_dashboard_snapshot', { validateBindings });
 }
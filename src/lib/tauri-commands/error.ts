@@ -0,0 +1,73 @@
+// 命令错误模块
+// 解析后端 CommandError（{ code, message, details }），便于按错误码分支或本地化
+
+import { invoke, type InvokeArgs } from '@tauri-apps/api/core';
+
+/**
+ * 后端稳定错误码
+ */
+export type CommandErrorCode =
+  | 'NOT_FOUND'
+  | 'VALIDATION'
+  | 'IO'
+  | 'NETWORK'
+  | 'CONFLICT'
+  | 'LOCKED'
+  | 'UNSUPPORTED'
+  | 'INTERNAL';
+
+/**
+ * 后端返回的错误结构
+ */
+export interface CommandErrorPayload {
+  code: CommandErrorCode;
+  message: string; // 可读的兜底文案
+  details: string | null; // 完整错误链
+}
+
+/**
+ * 命令错误（message 为兜底文案，可直接展示）
+ */
+export class CommandError extends Error {
+  readonly code: CommandErrorCode;
+  readonly details: string | null;
+
+  constructor(payload: CommandErrorPayload) {
+    super(payload.message);
+    this.name = 'CommandError';
+    this.code = payload.code;
+    this.details = payload.details;
+  }
+
+  toString(): string {
+    return this.message;
+  }
+}
+
+/**
+ * 判断是否为后端 CommandError 结构
+ */
+export function isCommandErrorPayload(error: unknown): error is CommandErrorPayload {
+  return (
+    typeof error === 'object' &&
+    error !== null &&
+    typeof (error as CommandErrorPayload).code === 'string' &&
+    typeof (error as CommandErrorPayload).message === 'string'
+  );
+}
+
+/**
+ * 调用返回 CommandError 的命令，失败时抛出 CommandError 实例
+ *
+ * 旧命令返回的字符串错误原样抛出
+ */
+export async function invokeCommand<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await invoke<T>(cmd, args);
+  } catch (error) {
+    if (isCommandErrorPayload(error)) {
+      throw new CommandError(error);
+    }
+    throw error;
+  }
+}
@@ -4,9 +4,15 @@
 // 类型定义
 export * from './types';
 
+// 命令错误
+export * from './error';
+
 // 工具管理
 export * from './tool';
 
+// 长耗时操作进度
+export * from './operation';
+
 // 配置管理
 export * from './config';
 
@@ -34,6 +40,18 @@ export * from './update';
 // 日志管理
 export * from './log';
 
+// 匿名使用统计
+export * from './telemetry';
+
+// 回收站
+export * from './trash';
+
+// 完整状态备份
+export * from './backup';
+
+// 启动自检
+export * from './selfcheck';
+
 // 平台信息
 export * from './platform';
 
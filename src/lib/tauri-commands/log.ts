@@ -2,7 +2,7 @@
 // 负责日志配置的查询和更新
 
 import { invoke } from '@tauri-apps/api/core';
-import type { LogConfig } from './types';
+import type { LogConfig, LogLevel, LogLine } from './types';
 
 /**
  * 检测当前是否为 Release 构建
@@ -26,3 +26,34 @@ export async function getLogConfig(): Promise<LogConfig> {
 export async function updateLogConfig(newConfig: LogConfig): Promise<string> {
   return await invoke<string>('update_log_config', { newConfig });
 }
+
+/**
+ * 获取当前生效的日志级别
+ */
+export async function getLogLevel(): Promise<LogLevel> {
+  return await invoke<LogLevel>('get_log_level');
+}
+
+/**
+ * 运行时切换日志级别（立即生效并保存到配置）
+ */
+export async function setLogLevel(level: LogLevel): Promise<void> {
+  await invoke('set_log_level', { level });
+}
+
+/**
+ * 读取最近的日志
+ * @param lines - 最多返回的行数
+ * @param levelFilter - 只返回该级别及以上的日志
+ */
+export async function getLogTail(lines: number, levelFilter?: LogLevel): Promise<LogLine[]> {
+  return await invoke<LogLine[]>('get_log_tail', { lines, levelFilter: levelFilter ?? null });
+}
+
+/**
+ * 导出诊断包（脱敏配置、PATH、日志尾部），导出后在文件管理器中定位
+ * @returns 诊断包路径
+ */
+export async function exportDiagnostics(): Promise<string> {
+  return await invoke<string>('export_diagnostics');
+}
@@ -0,0 +1,66 @@
+// 长耗时操作命令模块
+// 安装、更新、检测、扫描统一通过 operation-progress / operation-finished 事件上报进度
+
+import { invokeCommand, type CommandErrorPayload } from './error';
+
+/** 操作进度事件名 */
+export const OPERATION_PROGRESS_EVENT = 'operation-progress';
+/** 操作结束事件名 */
+export const OPERATION_FINISHED_EVENT = 'operation-finished';
+
+export type OperationKind = 'install' | 'update' | 'detection' | 'scan' | 'setup';
+
+/**
+ * operation-progress 事件负载
+ * 第一条事件的 phase 为 'started'，payload.target 为操作对象
+ */
+export interface OperationProgress {
+  operation_id: string;
+  kind: OperationKind;
+  phase: string;
+  percent?: number;
+  message: string;
+  payload?: Record<string, unknown>;
+}
+
+/**
+ * operation-finished 事件负载
+ */
+export interface OperationFinished<T = unknown> {
+  operation_id: string;
+  kind: OperationKind;
+  success: boolean;
+  cancelled: boolean;
+  result?: T;
+  error?: CommandErrorPayload;
+}
+
+/**
+ * 进行中的操作
+ */
+export interface OperationInfo {
+  operation_id: string;
+  kind: OperationKind;
+  target: string;
+  phase: string;
+  percent?: number;
+  message: string;
+  /** 开始时间（Unix 毫秒） */
+  started_at: number;
+  cancellable: boolean;
+}
+
+/**
+ * 列出进行中的操作
+ */
+export async function listActiveOperations(): Promise<OperationInfo[]> {
+  return await invokeCommand<OperationInfo[]>('list_active_operations');
+}
+
+/**
+ * 取消操作（仅 cancellable 为 true 的操作支持）
+ * @param operationId - 操作 ID
+ */
+export async function cancelOperation(operationId: string): Promise<void> {
+  await invokeCommand('cancel_operation', { operationId });
+}
@@ -1,35 +1,48 @@
 // 供应商管理命令模块
-// 负责供应商的 CRUD、验证
+// 负责供应商的 CRUD、验证、从其他切换工具导入
 
-import { invoke } from '@tauri-apps/api/core';
-import type { Provider, _ProviderFormData, ProviderValidationResult, ApiInfo } from './types';
+import { invokeCommand } from './error';
+import type {
+  Provider,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  ProviderDiscovery,
+  ConfirmedProviderImport,
+  ProviderImportResult,
+  BenchmarkOptions,
+  BenchmarkReport,
+  ProviderBenchmark,
+  ProviderTrialInfo,
+  ProviderTrialEvent,
+} from './types';
 
 /**
  * 列出所有供应商
  */
 export async function listProviders(): Promise<Provider[]> {
-  return invoke<Provider[]>('list_providers');
+  return invokeCommand<Provider[]>('list_providers');
 }
 
 /**
  * 创建新供应商
  */
 export async function createProvider(provider: Provider): Promise<Provider> {
-  return invoke<Provider>('create_provider', { provider });
+  return invokeCommand<Provider>('create_provider', { provider });
 }
 
 /**
  * 更新供应商
  */
 export async function updateProvider(id: string, provider: Provider): Promise<Provider> {
-  return invoke<Provider>('update_provider', { id, provider });
+  return invokeCommand<Provider>('update_provider', { id, provider });
 }
 
 /**
- * 删除供应商
+ * 删除供应商（默认移入回收站，hard 为 true 时永久删除）
  */
-export async function deleteProvider(id: string): Promise<void> {
-  return invoke<void>('delete_provider', { id });
+export async function deleteProvider(id: string, hard = false): Promise<void> {
+  return invokeCommand<void>('delete_provider', { id, hard });
 }
 
 /**
@@ -39,7 +52,7 @@ export async function validateProviderConfig(
   provider: Provider,
 ): Promise<ProviderValidationResult> {
   try {
-    return await invoke<ProviderValidationResult>('validate_provider_config', { provider });
+    return await invokeCommand<ProviderValidationResult>('validate_provider_config', { provider });
   } catch (error) {
     return {
       success: false,
@@ -48,6 +61,56 @@ export async function validateProviderConfig(
   }
 }
 
+/**
+ * 批量测速供应商（providerIds 为空时测速全部），结果按中位延迟排序
+ */
+export async function benchmarkProviders(
+  providerIds: string[],
+  samples: number,
+  options?: BenchmarkOptions,
+): Promise<BenchmarkReport> {
+  return invokeCommand<BenchmarkReport>('benchmark_providers', { providerIds, samples, options });
+}
+
+/**
+ * 各供应商最近一次的测速结果（按供应商 ID）
+ */
+export async function getProviderBenchmarks(): Promise<Record<string, ProviderBenchmark>> {
+  return invokeCommand<Record<string, ProviderBenchmark>>('get_provider_benchmarks');
+}
+
+/**
+ * 开始限时试用供应商，到期后自动恢复工具原配置（进度通过 provider-trial 事件推送）
+ */
+export async function startProviderTrial(
+  providerId: string,
+  toolId: string,
+  durationMinutes: number,
+): Promise<ProviderTrialInfo> {
+  return invokeCommand<ProviderTrialInfo>('start_provider_trial', {
+    providerId,
+    toolId,
+    durationMinutes,
+  });
+}
+
+/**
+ * 提前结束试用：keepConfig 为 true 时保留当前配置，否则立即恢复原配置
+ */
+export async function cancelProviderTrial(
+  trialId: string,
+  keepConfig?: boolean,
+): Promise<ProviderTrialEvent> {
+  return invokeCommand<ProviderTrialEvent>('cancel_provider_trial', { trialId, keepConfig });
+}
+
+/**
+ * 进行中的供应商试用
+ */
+export async function getActiveTrials(): Promise<ProviderTrialInfo[]> {
+  return invokeCommand<ProviderTrialInfo[]>('get_active_trials');
+}
+
 /**
  * 获取供应商的 API 地址列表
  * 从 {websiteUrl}/api/status 获取 data.api_info 数组
@@ -55,9 +118,25 @@ export async function validateProviderConfig(
  */
 export async function fetchProviderApiAddresses(websiteUrl: string): Promise<ApiInfo[]> {
   try {
-    return await invoke<ApiInfo[]>('fetch_provider_api_addresses', { websiteUrl });
+    return await invokeCommand<ApiInfo[]>('fetch_provider_api_addresses', { websiteUrl });
   } catch (error) {
     console.error('获取 API 地址列表失败:', error);
     return [];
   }
 }
+
+/**
+ * 从 cc-switch、claude-code-router 等工具的配置中发现供应商（不写入任何数据）
+ */
+export async function discoverExternalProviders(): Promise<ProviderDiscovery> {
+  return invokeCommand<ProviderDiscovery>('discover_external_providers');
+}
+
+/**
+ * 导入用户确认的供应商并设置工具绑定
+ */
+export async function importDiscoveredProviders(
+  items: ConfirmedProviderImport[],
+): Promise<ProviderImportResult> {
+  return invokeCommand<ProviderImportResult>('import_discovered_providers', { items });
+}
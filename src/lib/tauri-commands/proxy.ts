@@ -2,7 +2,20 @@
 // 负责透明代理的启动、停止、状态查询和配置管理
 
 import { invoke } from '@tauri-apps/api/core';
-import type { AllProxyStatus, ToolProxyConfig, ToolId } from './types';
+import type {
+  AllProxyStatus,
+  CaptureSnapshot,
+  ConcurrencyLimits,
+  ExportFormat,
+  ExportRange,
+  MetricsRange,
+  ProxyMetricsReport,
+  ProxyProfileInfo,
+  ProxySession,
+  ToolProxyConfig,
+  ToolId,
+  UpstreamTimeouts,
+} from './types';
 
 // ==================== 多工具透明代理 API（新架构）====================
 
@@ -50,3 +63,156 @@ export async function updateProxyConfig(toolId: ToolId, config: ToolProxyConfig)
 export async function getAllProxyConfigs(): Promise<Record<string, ToolProxyConfig>> {
   return await invoke<Record<string, ToolProxyConfig>>('get_all_proxy_configs');
 }
+
+/**
+ * 更新端点白名单（运行中的代理立即生效）
+ * @param prefixes - 允许转发的路径前缀，如 /v1/messages
+ */
+export async function updateProxyAllowlist(toolId: ToolId, prefixes: string[]): Promise<void> {
+  return await invoke<void>('update_proxy_allowlist', { toolId, prefixes });
+}
+
+/**
+ * 更新并发上限（运行中的代理立即生效）
+ */
+export async function updateProxyLimits(toolId: ToolId, limits: ConcurrencyLimits): Promise<void> {
+  return await invoke<void>('update_proxy_limits', { toolId, limits });
+}
+
+/**
+ * 更新上游超时（运行中的代理立即生效）
+ */
+export async function updateProxyTimeouts(
+  toolId: ToolId,
+  timeouts: UpstreamTimeouts,
+): Promise<void> {
+  return await invoke<void>('update_proxy_timeouts', { toolId, timeouts });
+}
+
+/**
+ * 一键将工具配置指向本地代理（要求代理已运行并绑定供应商）
+ */
+export async function configureToolForProxy(toolId: ToolId): Promise<string> {
+  return await invoke<string>('configure_tool_for_proxy', { toolId });
+}
+
+/**
+ * 还原工具接入代理前的原始配置
+ */
+export async function unconfigureToolForProxy(toolId: ToolId): Promise<string> {
+  return await invoke<string>('unconfigure_tool_for_proxy', { toolId });
+}
+
+// ==================== 代理配置档 API ====================
+
+/**
+ * 将工具当前代理配置保存为命名配置档（同名覆盖）
+ */
+export async function saveProxyProfile(toolId: ToolId, name: string): Promise<void> {
+  return await invoke<void>('save_proxy_profile', { toolId, name });
+}
+
+/**
+ * 列出工具的代理配置档
+ */
+export async function listProxyProfiles(toolId: ToolId): Promise<ProxyProfileInfo[]> {
+  return await invoke<ProxyProfileInfo[]>('list_proxy_profiles', { toolId });
+}
+
+/**
+ * 激活代理配置档（代理运行中时热切换）
+ */
+export async function activateProxyProfile(toolId: ToolId, name: string): Promise<string> {
+  return await invoke<string>('activate_proxy_profile', { toolId, name });
+}
+
+/**
+ * 删除代理配置档
+ */
+export async function deleteProxyProfile(toolId: ToolId, name: string): Promise<void> {
+  return await invoke<void>('delete_proxy_profile', { toolId, name });
+}
+
+// ==================== 代理指标 API ====================
+
+/**
+ * 获取代理请求指标（延迟百分位、错误率、状态码分布）
+ * @param range - 统计范围，默认最近 1 小时
+ */
+export async function getProxyMetrics(range?: MetricsRange): Promise<ProxyMetricsReport> {
+  return await invoke<ProxyMetricsReport>('get_proxy_metrics', { range });
+}
+
+/**
+ * 获取代理会话统计（请求数、token 总量、持续时长）
+ * @param range - 统计范围，默认最近 1 小时
+ */
+export async function getProxySessions(range?: MetricsRange): Promise<ProxySession[]> {
+  return await invoke<ProxySession[]>('get_proxy_sessions', { range });
+}
+
+/**
+ * 导出代理用量汇总（按天、供应商、端点）
+ * @returns 导出文件路径
+ */
+export async function exportProxyUsage(
+  range: ExportRange,
+  format?: ExportFormat,
+): Promise<string> {
+  return await invoke<string>('export_proxy_usage', { range, format });
+}
+
+/**
+ * 导出代理访问日志（合并范围内的滚动日志文件，已脱敏）
+ * @returns 导出文件路径
+ */
+export async function exportProxyAccessLog(range: ExportRange): Promise<string> {
+  return await invoke<string>('export_proxy_access_log', { range });
+}
+
+// ==================== 调试捕获 API ====================
+
+/**
+ * 开启代理调试捕获（仅保存在内存，到期自动关闭）
+ * @param minutes - 捕获时长（分钟）
+ */
+export async function enableProxyCapture(
+  minutes: number,
+  maxExchanges?: number,
+  maxBodyBytes?: number,
+): Promise<CaptureSnapshot> {
+  return await invoke<CaptureSnapshot>('enable_proxy_capture', {
+    minutes,
+    maxExchanges,
+    maxBodyBytes,
+  });
+}
+
+/**
+ * 立即关闭代理调试捕获
+ */
+export async function disableProxyCapture(): Promise<void> {
+  return await invoke<void>('disable_proxy_capture');
+}
+
+/**
+ * 获取已捕获的请求/响应
+ */
+export async function getCapturedExchanges(): Promise<CaptureSnapshot> {
+  return await invoke<CaptureSnapshot>('get_captured_exchanges');
+}
+
+/**
+ * 清空已捕获内容
+ */
+export async function clearCaptures(): Promise<void> {
+  return await invoke<void>('clear_captures');
+}
+
+/**
+ * 弹出保存对话框并导出已捕获内容到 JSON 文件（已脱敏）
+ * @returns 导出的条数，用户取消时为 null
+ */
+export async function exportCapturedExchanges(): Promise<number | null> {
+  return await invoke<number | null>('export_captured_exchanges');
+}
@@ -0,0 +1,22 @@
+// 启动自检命令模块
+// 启动时后台执行一次（完成后发送 startup-selfcheck-completed 事件），也可随时重新执行
+
+import { invokeCommand } from './error';
+import type { SelfCheckItem } from './types';
+
+/** 启动自检完成事件（负载为 SelfCheckItem[]） */
+export const SELFCHECK_COMPLETED_EVENT = 'startup-selfcheck-completed';
+
+/**
+ * 重新执行启动自检
+ */
+export async function runStartupSelfcheck(): Promise<SelfCheckItem[]> {
+  return await invokeCommand<SelfCheckItem[]>('run_startup_selfcheck');
+}
+
+/**
+ * 获取最近一次启动自检的结果（尚未完成时为 null）
+ */
+export async function getStartupSelfcheck(): Promise<SelfCheckItem[] | null> {
+  return await invokeCommand<SelfCheckItem[] | null>('get_startup_selfcheck');
+}
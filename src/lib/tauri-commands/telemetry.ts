@@ -0,0 +1,33 @@
+// 匿名使用统计命令模块
+// 统计默认关闭；可查看设置、待发送计数、发送历史以及下一次发送的完整内容
+
+import { invokeCommand } from './error';
+import type { TelemetryPing, TelemetryStatus } from './types';
+
+/**
+ * 获取使用统计设置、待发送计数与发送历史
+ */
+export async function getTelemetryStatus(): Promise<TelemetryStatus> {
+  return await invokeCommand<TelemetryStatus>('get_telemetry_status');
+}
+
+/**
+ * 预览下一次将要发送的内容（与实际发送完全一致）
+ */
+export async function getTelemetryPreview(): Promise<TelemetryPing> {
+  return await invokeCommand<TelemetryPing>('get_telemetry_preview');
+}
+
+/**
+ * 开启或关闭使用统计（关闭时删除尚未发送的计数）
+ */
+export async function setTelemetryEnabled(enabled: boolean): Promise<TelemetryStatus> {
+  return await invokeCommand<TelemetryStatus>('set_telemetry_enabled', { enabled });
+}
+
+/**
+ * 设置使用统计的上报地址（仅支持 http/https）
+ */
+export async function setTelemetryEndpoint(endpoint: string): Promise<TelemetryStatus> {
+  return await invokeCommand<TelemetryStatus>('set_telemetry_endpoint', { endpoint });
+}
@@ -1,24 +1,32 @@
 // 工具管理命令模块
 // 负责工具的安装、更新、检测、实例管理等功能
 
-import { invoke } from '@tauri-apps/api/core';
+import { invokeCommand } from './error';
 import type {
   ToolStatus,
+  ToolStatusSnapshot,
+  PackageCoordinates,
+  ToolDefinitions,
   InstallResult,
   UpdateResult,
   NodeEnvironment,
+  EffectivePath,
   ToolCandidate,
   InstallerCandidate,
   SSHConfig,
+  WslDistro,
+  HostKeyStatus,
+  SshTestResult,
+  SshConfigHost,
 } from './types';
-import type { ToolInstance } from '@/types/tool-management';
+import type { NodeRuntime, ToolInstance } from '@/types/tool-management';
 
 /**
  * 检查所有工具的安装状态
  * 优先从数据库读取（< 10ms），首次启动自动检测并持久化
  */
 export async function checkInstallations(): Promise<ToolStatus[]> {
-  return await invoke<ToolStatus[]>('check_installations');
+  return await invokeCommand<ToolStatus[]>('check_installations');
 }
 
 /**
@@ -26,14 +34,45 @@ export async function checkInstallations(): Promise<ToolStatus[]> {
  * 用于用户手动刷新或外部安装/卸载工具后更新状态
  */
 export async function refreshToolStatus(): Promise<ToolStatus[]> {
-  return await invoke<ToolStatus[]>('refresh_tool_status');
+  return await invokeCommand<ToolStatus[]>('refresh_tool_status');
+}
+
+/**
+ * 立即返回缓存中的工具状态（可能已过期，不触发检测）及正在检测的工具
+ * 检测进度通过 tool-detection-changed 事件推送
+ */
+export async function getCachedToolStatus(): Promise<ToolStatusSnapshot> {
+  return await invokeCommand<ToolStatusSnapshot>('get_cached_tool_status');
+}
+
+/**
+ * 获取各工具的包坐标（已叠加配置中的覆盖），key 为工具 ID
+ */
+export async function getPackageCoordinates(): Promise<Record<string, PackageCoordinates>> {
+  return await invokeCommand<Record<string, PackageCoordinates>>('get_package_coordinates');
 }
 
 /**
  * 检查 Node.js 和 npm 环境
  */
 export async function checkNodeEnvironment(): Promise<NodeEnvironment> {
-  return await invoke<NodeEnvironment>('check_node_environment');
+  return await invokeCommand<NodeEnvironment>('check_node_environment');
+}
+
+/**
+ * 列出本机所有 Node.js 运行时及其版本与 npm 全局目录
+ * @param refresh - 跳过缓存（5 分钟）重新扫描
+ */
+export async function getNodeEnvironments(refresh?: boolean): Promise<NodeRuntime[]> {
+  return await invokeCommand<NodeRuntime[]>('get_node_environments', { refresh });
+}
+
+/**
+ * 获取工具检测实际搜索的 PATH（诊断用）
+ * @param existingOnly - 是否去掉不存在的目录
+ */
+export async function getEffectivePath(existingOnly = false): Promise<EffectivePath> {
+  return await invokeCommand<EffectivePath>('get_effective_path', { existingOnly });
 }
 
 /**
@@ -47,7 +86,14 @@ export async function installTool(
   method: string,
   force?: boolean,
 ): Promise<InstallResult> {
-  return await invoke<InstallResult>('install_tool', { tool, method, force });
+  return await invokeCommand<InstallResult>('install_tool', { tool, method, force });
+}
+
+/**
+ * 把目录登记为额外检测路径（npm prefix 不在 PATH 中时的自动修复），返回重新检测后的状态
+ */
+export async function registerExtraDetectionPath(tool: string, path: string): Promise<ToolStatus> {
+  return await invokeCommand<ToolStatus>('register_extra_detection_path', { tool, path });
 }
 
 /**
@@ -55,7 +101,7 @@ export async function installTool(
  * @deprecated 请使用 checkUpdateForInstance
  */
 export async function checkUpdate(tool: string): Promise<UpdateResult> {
-  return await invoke<UpdateResult>('check_update', { tool });
+  return await invokeCommand<UpdateResult>('check_update', { tool });
 }
 
 /**
@@ -64,14 +110,14 @@ export async function checkUpdate(tool: string): Promise<UpdateResult> {
  * @returns 更新信息
  */
 export async function checkUpdateForInstance(instanceId: string): Promise<UpdateResult> {
-  return await invoke<UpdateResult>('check_update_for_instance', { instanceId });
+  return await invokeCommand<UpdateResult>('check_update_for_instance', { instanceId });
 }
 
 /**
  * 检查所有工具的更新
  */
 export async function checkAllUpdates(): Promise<UpdateResult[]> {
-  return await invoke<UpdateResult[]>('check_all_updates');
+  return await invokeCommand<UpdateResult[]>('check_all_updates');
 }
 
 /**
@@ -79,7 +125,7 @@ export async function checkAllUpdates(): Promise<UpdateResult[]> {
  * @returns 更新后的工具状态列表
  */
 export async function refreshAllToolVersions(): Promise<ToolStatus[]> {
-  return await invoke<ToolStatus[]>('refresh_all_tool_versions');
+  return await invokeCommand<ToolStatus[]>('refresh_all_tool_versions');
 }
 
 /**
@@ -92,7 +138,7 @@ export async function updateToolInstance(
   instanceId: string,
   force?: boolean,
 ): Promise<UpdateResult> {
-  return await invoke<UpdateResult>('update_tool_instance', { instanceId, force });
+  return await invokeCommand<UpdateResult>('update_tool_instance', { instanceId, force });
 }
 
 /**
@@ -100,7 +146,7 @@ export async function updateToolInstance(
  * @deprecated 请使用 updateToolInstance
  */
 export async function updateTool(tool: string, force?: boolean): Promise<UpdateResult> {
-  return await invoke<UpdateResult>('update_tool', { tool, force });
+  return await invokeCommand<UpdateResult>('update_tool', { tool, force });
 }
 
 /**
@@ -108,7 +154,7 @@ export async function updateTool(tool: string, force?: boolean): Promise<UpdateR
  * @returns 按工具ID分组的实例集合
  */
 export async function getToolInstances(): Promise<Record<string, ToolInstance[]>> {
-  return await invoke<Record<string, ToolInstance[]>>('get_tool_instances');
+  return await invokeCommand<Record<string, ToolInstance[]>>('get_tool_instances');
 }
 
 /**
@@ -116,7 +162,7 @@ export async function getToolInstances(): Promise<Record<string, ToolInstance[]>
  * @returns 刷新后的实例集合
  */
 export async function refreshToolInstances(): Promise<Record<string, ToolInstance[]>> {
-  return await invoke<Record<string, ToolInstance[]>>('refresh_tool_instances');
+  return await invokeCommand<Record<string, ToolInstance[]>>('refresh_tool_instances');
 }
 
 /**
@@ -124,7 +170,15 @@ export async function refreshToolInstances(): Promise<Record<string, ToolInstanc
  * @returns WSL发行版名称列表
  */
 export async function listWslDistributions(): Promise<string[]> {
-  return await invoke<string[]>('list_wsl_distributions');
+  return await invokeCommand<string[]>('list_wsl_distributions');
+}
+
+/**
+ * 列出WSL发行版（含状态、版本与默认发行版标记）
+ * @returns WSL发行版列表
+ */
+export async function listWslDistros(): Promise<WslDistro[]> {
+  return await invokeCommand<WslDistro[]>('list_wsl_distros');
 }
 
 /**
@@ -137,31 +191,150 @@ export async function addWslToolInstance(
   baseId: string,
   distroName: string,
 ): Promise<ToolInstance> {
-  return await invoke<ToolInstance>('add_wsl_tool_instance', { baseId, distroName });
+  return await invokeCommand<ToolInstance>('add_wsl_tool_instance', { baseId, distroName });
 }
 
 /**
  * 添加SSH工具实例
  * @param baseId - 工具ID
  * @param sshConfig - SSH连接配置
+ * @param force - 认证失败时仍然保存
  * @returns 创建的实例
  */
 export async function addSshToolInstance(
   baseId: string,
   sshConfig: SSHConfig,
+  force = false,
 ): Promise<ToolInstance> {
-  return await invoke<ToolInstance>('add_ssh_tool_instance', {
+  return await invokeCommand<ToolInstance>('add_ssh_tool_instance', {
     baseId,
     sshConfig,
+    force,
   });
 }
 
+/**
+ * 列出 ~/.ssh/config 中的主机（跳过纯通配符条目）
+ * @returns 已解析的主机列表
+ */
+export async function listSshConfigHosts(): Promise<SshConfigHost[]> {
+  return await invokeCommand<SshConfigHost[]>('list_ssh_config_hosts');
+}
+
+/**
+ * 从 ~/.ssh/config 的主机创建SSH工具实例
+ * @param alias - Host 别名
+ * @param toolId - 工具ID
+ * @param force - 认证失败时仍然保存
+ * @returns 创建的实例
+ */
+export async function createSshInstanceFromHost(
+  alias: string,
+  toolId: string,
+  force = false,
+): Promise<ToolInstance> {
+  return await invokeCommand<ToolInstance>('create_ssh_instance_from_host', {
+    alias,
+    toolId,
+    force,
+  });
+}
+
+/**
+ * 测试SSH连接
+ * @param sshConfig - SSH连接配置
+ * @returns 分阶段的测试结果（失败阶段、错误、延迟）
+ */
+export async function testSshConnection(sshConfig: SSHConfig): Promise<SshTestResult> {
+  return await invokeCommand<SshTestResult>('test_ssh_connection', { sshConfig });
+}
+
+/**
+ * 查询SSH主机密钥状态
+ * @param sshConfig - SSH连接配置
+ * @returns 已知，或未知主机的指纹（供用户确认）
+ */
+export async function getSshHostKeyStatus(sshConfig: SSHConfig): Promise<HostKeyStatus> {
+  return await invokeCommand<HostKeyStatus>('get_ssh_host_key_status', { sshConfig });
+}
+
+/**
+ * 信任SSH主机密钥（写入 ~/.ssh/known_hosts）
+ * @param sshConfig - SSH连接配置
+ * @param fingerprints - 用户确认过的指纹
+ */
+export async function trustSshHostKey(sshConfig: SSHConfig, fingerprints: string[]): Promise<void> {
+  await invokeCommand('trust_ssh_host_key', { sshConfig, fingerprints });
+}
+
+/**
+ * 重新检测SSH工具实例
+ * @param instanceId - 实例ID
+ * @returns 更新后的实例
+ */
+export async function refreshSshToolInstance(instanceId: string): Promise<ToolInstance> {
+  return await invokeCommand<ToolInstance>('refresh_ssh_tool_instance', { instanceId });
+}
+
+/**
+ * 在SSH实例的远程主机上安装工具
+ * @param instanceId - 实例ID
+ * @returns 安装后重新检测的实例
+ */
+export async function installSshToolInstance(instanceId: string): Promise<ToolInstance> {
+  return await invokeCommand<ToolInstance>('install_ssh_tool_instance', { instanceId });
+}
+
 /**
  * 删除工具实例（仅SSH类型）
  * @param instanceId - 实例ID
+ * @param hard - 为 true 时永久删除，否则移入回收站
+ */
+export async function deleteToolInstance(instanceId: string, hard = false): Promise<void> {
+  return await invokeCommand<void>('delete_tool_instance', { instanceId, hard });
+}
+
+/**
+ * 设置工具实例的标签与备注（传空值表示清除）
+ * @param instanceId - 实例ID
+ * @param label - 标签
+ * @param notes - 备注
+ */
+export async function setToolInstanceLabel(
+  instanceId: string,
+  label?: string | null,
+  notes?: string | null,
+): Promise<ToolInstance> {
+  return await invokeCommand<ToolInstance>('set_tool_instance_label', { instanceId, label, notes });
+}
+
+/**
+ * 设置工具实例的环境变量覆盖（版本检查、重新验证、健康检查时注入；传空对象表示清除）
+ * 敏感变量返回脱敏值，原样提交脱敏值时保留原值
+ * @param instanceId - 实例ID
+ * @param env - 环境变量
+ */
+export async function setInstanceEnv(
+  instanceId: string,
+  env: Record<string, string>,
+): Promise<ToolInstance> {
+  return await invokeCommand<ToolInstance>('set_instance_env', { instanceId, env });
+}
+
+/**
+ * 获取所有工具的完整定义（解析后的配置路径、包坐标、当前平台的安装方式与可检测性）
  */
-export async function deleteToolInstance(instanceId: string): Promise<void> {
-  return await invoke<void>('delete_tool_instance', { instanceId });
+export async function getToolDefinitions(): Promise<ToolDefinitions> {
+  return await invokeCommand<ToolDefinitions>('get_tool_definitions');
+}
+
+/**
+ * 弹出保存对话框导出环境报告（工具实例、Node.js 运行时、供应商绑定、代理设置），
+ * 按所选扩展名决定 Markdown 或 JSON；不含凭证，路径中的用户目录替换为 ~
+ * @returns 写入的路径，用户取消时为 null
+ */
+export async function exportEnvironmentReport(): Promise<string | null> {
+  return await invokeCommand<string | null>('export_environment_report');
 }
 
 /**
@@ -171,7 +344,7 @@ export async function deleteToolInstance(instanceId: string): Promise<void> {
  * @returns 版本号字符串
  */
 export async function validateToolPath(toolId: string, path: string): Promise<string> {
-  return await invoke<string>('validate_tool_path', { toolId, path });
+  return await invokeCommand<string>('validate_tool_path', { toolId, path });
 }
 
 /**
@@ -180,7 +353,7 @@ export async function validateToolPath(toolId: string, path: string): Promise<st
  * @returns 工具候选列表
  */
 export async function scanAllToolCandidates(toolId: string): Promise<ToolCandidate[]> {
-  return await invoke<ToolCandidate[]>('scan_all_tool_candidates', { toolId });
+  return await invokeCommand<ToolCandidate[]>('scan_all_tool_candidates', { toolId });
 }
 
 /**
@@ -189,7 +362,7 @@ export async function scanAllToolCandidates(toolId: string): Promise<ToolCandida
  * @returns 安装器候选列表
  */
 export async function scanInstallerForToolPath(toolPath: string): Promise<InstallerCandidate[]> {
-  return await invoke<InstallerCandidate[]>('scan_installer_for_tool_path', { toolPath });
+  return await invokeCommand<InstallerCandidate[]>('scan_installer_for_tool_path', { toolPath });
 }
 
 /**
@@ -206,7 +379,7 @@ export async function addManualToolInstance(
   installMethod: string,
   installerPath?: string,
 ): Promise<ToolStatus> {
-  return await invoke<ToolStatus>('add_manual_tool_instance', {
+  return await invokeCommand<ToolStatus>('add_manual_tool_instance', {
     toolId,
     path,
     installMethod,
@@ -220,7 +393,7 @@ export async function addManualToolInstance(
  * @returns 工具状态信息
  */
 export async function detectToolWithoutSave(toolId: string): Promise<ToolStatus> {
-  return await invoke<ToolStatus>('detect_tool_without_save', { toolId });
+  return await invokeCommand<ToolStatus>('detect_tool_without_save', { toolId });
 }
 
 /**
@@ -233,5 +406,53 @@ export async function detectSingleTool(
   toolId: string,
   forceRedetect?: boolean,
 ): Promise<ToolStatus> {
-  return await invoke<ToolStatus>('detect_single_tool', { toolId, forceRedetect });
+  return await invokeCommand<ToolStatus>('detect_single_tool', { toolId, forceRedetect });
+}
+
+export type SetupStage = 'detect' | 'install' | 'bind_provider' | 'apply_config' | 'health_check';
+
+export type SetupStageStatus = 'succeeded' | 'skipped' | 'failed' | 'not_run';
+
+/**
+ * 一键配置选项
+ */
+export interface SetupOptions {
+  /** 安装方式（npm / brew / official），缺省使用推荐方式 */
+  install_method?: string;
+  /** 已安装时也强制重新安装 */
+  force_install?: boolean;
+  /** 写入的 Profile 名称，缺省为供应商 ID */
+  profile_name?: string;
+  /** Codex 的 wire_api */
+  wire_api?: string;
+  /** Gemini CLI 的模型 */
+  model?: string;
+}
+
+/**
+ * 一键配置报告（每个阶段的结果）
+ */
+export interface SetupReport {
+  tool_id: string;
+  provider_id: string;
+  success: boolean;
+  stages: { stage: SetupStage; status: SetupStageStatus; message: string }[];
+  /** 失败后是否已回滚配置改动 */
+  rolled_back: boolean;
+}
+
+/**
+ * 一键配置工具：检测 → 未安装时安装 → 绑定供应商 → 应用配置 → 健康检查
+ * 进度通过 operation-progress 事件上报（kind 为 setup）
+ * @param toolId - 工具ID
+ * @param providerId - 供应商ID
+ * @param options - 配置选项
+ * @returns 各阶段结果，遇到首个失败即停止并回滚配置改动
+ */
+export async function setupTool(
+  toolId: string,
+  providerId: string,
+  options?: SetupOptions,
+): Promise<SetupReport> {
+  return await invokeCommand<SetupReport>('setup_tool', { toolId, providerId, options });
 }
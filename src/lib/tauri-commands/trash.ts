@@ -0,0 +1,26 @@
+// 回收站命令模块
+// 删除的供应商与工具实例保留 30 天，期间可恢复
+
+import { invokeCommand } from './error';
+import type { RestoreResult, TrashItem } from './types';
+
+/**
+ * 列出回收站中的记录（按删除时间倒序，同时清除过期记录）
+ */
+export async function listTrash(): Promise<TrashItem[]> {
+  return await invokeCommand<TrashItem[]>('list_trash');
+}
+
+/**
+ * 从回收站恢复记录（原 ID 被占用时会生成新 ID）
+ */
+export async function restoreFromTrash(id: string): Promise<RestoreResult> {
+  return await invokeCommand<RestoreResult>('restore_from_trash', { id });
+}
+
+/**
+ * 永久删除回收站记录，不传 id 时清空回收站；返回删除的记录数
+ */
+export async function purgeTrash(id?: string): Promise<number> {
+  return await invokeCommand<number>('purge_trash', { id: id ?? null });
+}
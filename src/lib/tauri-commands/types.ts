@@ -9,6 +9,14 @@ import type {
   _ProviderFormData,
   ProviderValidationResult,
   ApiInfo,
+  ProviderDiscovery,
+  ConfirmedProviderImport,
+  ProviderImportResult,
+  BenchmarkOptions,
+  BenchmarkReport,
+  ProviderBenchmark,
+  ProviderTrialInfo,
+  ProviderTrialEvent,
 } from '@/types/provider';
 
 // 重新导出 Profile 相关类型供其他模块使用
@@ -18,7 +26,21 @@ export type { ProfileData, ProfileDescriptor, ProfilePayload, ToolId };
 export type { SSHConfig };
 
 // 重新导出供应商管理类型
-export type { Provider, ProviderStore, _ProviderFormData, ProviderValidationResult, ApiInfo };
+export type {
+  Provider,
+  ProviderStore,
+  _ProviderFormData,
+  ProviderValidationResult,
+  ApiInfo,
+  ProviderDiscovery,
+  ConfirmedProviderImport,
+  ProviderImportResult,
+  BenchmarkOptions,
+  BenchmarkReport,
+  ProviderBenchmark,
+  ProviderTrialInfo,
+  ProviderTrialEvent,
+};
 
 export interface ToolStatus {
   mirrorIsStale: boolean;
@@ -35,6 +57,15 @@ export interface InstallResult {
   success: boolean;
   message: string;
   output: string;
+  error_code?: string; // 失败原因的错误码，如 PREFIX_NOT_ON_PATH
+  npm_prefix_issue?: NpmPrefixIssue;
+}
+
+// npm 全局 bin 目录不在 PATH 中（错误码 PREFIX_NOT_ON_PATH）
+export interface NpmPrefixIssue {
+  prefix: string; // npm config get prefix 的输出
+  bin_dir: string; // 全局安装的可执行文件所在目录
+  shell_snippet: string; // 把目录加入 PATH 的 shell 片段
 }
 
 export interface UpdateResult {
@@ -46,6 +77,9 @@ export interface UpdateResult {
   mirror_version?: string | null; // 镜像实际可安装的版本
   mirror_is_stale?: boolean | null; // 镜像是否滞后
   tool_id?: string;
+  skipped?: boolean; // 最新版本已被用户跳过（定时检查不再提醒）
+  download_size?: number | null; // 更新包下载大小（字节）
+  duration_ms?: number | null; // 更新实际耗时（仅更新完成后返回）
 }
 
 export interface ActiveConfig {
@@ -79,6 +113,255 @@ export interface GlobalConfig {
   external_poll_interval_ms?: number;
   // 单实例模式开关（默认 true，仅生产环境生效）
   single_instance_enabled?: boolean;
+  // 模型定价覆盖与花费提醒
+  pricing?: PricingSettings;
+  balance_poll?: BalancePollSettings;
+  // 启动时捕获登录 shell 环境（仅 macOS，默认 true）
+  shell_env_capture_enabled?: boolean;
+  durable_writes_enabled?: boolean;
+  tool_status_cache?: ToolStatusCacheSettings;
+  // 离线模式：不发起更新检查等非必要的网络请求
+  offline_mode?: boolean;
+  // 访问 GitHub API 时使用的令牌（可选）
+  github_token?: string | null;
+  // 用户跳过的版本（对象 ID → 版本，对象 ID 为 duckcoding 或工具 ID）
+  skipped_versions?: Record<string, string>;
+  // 后端文案语言（错误提示等，默认 zh-CN）
+  backend_locale?: BackendLocale;
+  // 匿名使用统计（默认关闭）
+  telemetry?: TelemetrySettings;
+  // 按工具覆盖包坐标（工具 ID → 要替换的字段），用于 fork 或内部镜像
+  package_overrides?: Record<string, PackageCoordinates>;
+  // 额外的检测路径（置于增强 PATH 最前面）
+  extra_path_entries?: string[];
+  // 完整状态自动备份（默认关闭）
+  state_backup?: StateBackupSettings;
+}
+
+// 工具在各安装渠道中的包坐标（未提供的渠道省略）
+export interface PackageCoordinates {
+  npm?: string; // npm 包名，如 @openai/codex
+  brew?: string; // Homebrew formula / cask 名
+  winget?: string; // winget 包 ID
+  official?: string; // 官方安装脚本在镜像站上的路径标识
+}
+
+// 安装方式
+export type InstallMethod = 'Official' | 'Npm' | 'Brew' | 'Other';
+
+// 工具定义载荷（version 在字段不兼容变更时递增）
+export interface ToolDefinitions {
+  version: number;
+  tools: ToolDefinition[];
+}
+
+// 单个工具的完整元数据（由后端解析，前端直接渲染）
+export interface ToolDefinition {
+  id: string;
+  name: string;
+  group_name: string;
+  package_coordinates: PackageCoordinates;
+  check_command: string;
+  config_dir: string; // 内置配置目录
+  config_file: string;
+  env_vars: { api_key: string; base_url: string };
+  use_proxy_for_version_check: boolean;
+  builtin: boolean; // 是否为内置工具
+  resolved_config_dir: string; // 叠加环境变量覆盖后的配置目录
+  resolved_config_path: string; // 叠加环境变量覆盖后的配置文件路径
+  install_methods: InstallMethod[]; // 当前平台支持的安装方式
+  recommended_install_method: InstallMethod | null;
+  detectable: boolean; // 当前环境能否找到可执行文件
+}
+
+export type BackendLocale = 'zh-CN' | 'en-US';
+
+export interface ToolStatusCacheSettings {
+  ttl_secs: number; // 工具状态缓存有效期（秒），默认 600
+  stale_while_revalidate: boolean; // 过期时先返回旧值并在后台重新检测
+}
+
+export interface TelemetrySettings {
+  enabled: boolean; // 是否开启匿名使用统计（默认关闭）
+  prompted: boolean; // 是否已在引导中询问过
+  endpoint: string; // 上报地址
+}
+
+// 一次上报的完整内容（预览与实际发送一致）
+export interface TelemetryPing {
+  schema_version: number;
+  app_version: string;
+  os: string; // windows / macos / linux
+  period_start: number; // 统计区间（Unix 秒）
+  period_end: number;
+  counters: Record<string, number>; // 事件名 → 次数
+}
+
+export interface TelemetrySendRecord {
+  sent_at: number; // 发送时间（Unix 秒）
+  endpoint: string;
+  ping: TelemetryPing;
+}
+
+export interface TelemetryStatus {
+  settings: TelemetrySettings;
+  allowed_events: string[]; // 允许统计的事件名
+  queued: Record<string, number>; // 待发送的计数
+  last_sent_at: number | null;
+  history: TelemetrySendRecord[];
+}
+
+// 完整状态自动备份频率
+export type BackupSchedule = 'off' | 'daily' | 'weekly';
+
+// 备份内容：providers、proxy_config、profiles 包含密钥
+export type BackupComponent =
+  | 'providers'
+  | 'instance_db'
+  | 'dashboard'
+  | 'proxy_config'
+  | 'profiles';
+
+export interface StateBackupSettings {
+  schedule: BackupSchedule;
+  target_dir: string | null; // 备份目录（如同步盘中的目录）
+  retention: number; // 保留份数，默认 7
+  components: BackupComponent[];
+  passphrase: string | null; // 加密口令（备份内容包含密钥时必填）
+}
+
+export interface BackupComponentResult {
+  component: BackupComponent;
+  success: boolean;
+  message: string;
+}
+
+export interface BackupRunResult {
+  path: string; // 生成的备份文件
+  encrypted: boolean;
+  created_at: number; // Unix 秒
+  size: number; // 字节
+  components: BackupComponentResult[];
+  pruned: string[]; // 按保留份数删除的旧备份
+}
+
+export interface RestoreReport {
+  created_at: number; // 备份创建时间（Unix 秒）
+  app_version: string; // 创建备份的 DuckCoding 版本
+  safety_snapshot: string | null; // 恢复前自动保存的当前状态（可用于撤销恢复）
+  components: BackupComponentResult[];
+  success: boolean;
+}
+
+// state-backup 事件负载（备份成功或失败时推送）
+export interface StateBackupEvent {
+  kind: 'succeeded' | 'failed';
+  automatic: boolean; // 是否为定时自动备份
+  path?: string;
+  message: string;
+}
+
+export interface BalancePollSettings {
+  enabled: boolean; // 是否开启供应商余额后台轮询
+  interval_mins: number; // 轮询间隔（分钟）
+}
+
+// 余额单位：usd 美元；quota 为 NEW API 额度（500000 = 1 美元）；unknown 无法识别
+export type BalanceUnit = 'usd' | 'quota' | 'unknown';
+
+// 供应商余额（保留供应商原始单位）
+export interface Balance {
+  provider_id: string;
+  remaining: number | null;
+  used: number | null;
+  unit: BalanceUnit;
+  raw?: unknown; // 原始响应（历史记录中不包含）
+  parsed: boolean; // 响应格式无法识别时为 false
+  fetched_at: number; // Unix 时间戳（毫秒）
+}
+
+// 单个模型在计费周期内的用量
+export interface ModelUsage {
+  model: string;
+  requests: number;
+  tokens: number;
+  cost: number; // 单位同 unit
+  unit: BalanceUnit;
+  share: number; // 占周期总花费比例（0~1）
+}
+
+// 余额详情：摘要 + 按模型用量明细
+export interface BalanceDetail {
+  summary: Balance;
+  period_start: number; // 计费周期起始（毫秒）
+  models: ModelUsage[]; // 按花费降序，不支持统计的供应商为空
+  models_error?: string;
+}
+
+// 供应商未计入余额汇总的原因
+export type BalanceExclusionReason =
+  | 'missing_credentials'
+  | 'not_fetched'
+  | 'unparsed'
+  | 'no_remaining'
+  | 'unknown_unit';
+
+// 跨供应商余额汇总（仅基于缓存）
+export interface AggregateBalance {
+  total_remaining_usd: number;
+  included: string[];
+  excluded: { provider_id: string; reason: BalanceExclusionReason }[];
+  oldest_fetched_at: number | null; // 计入合计的最早获取时间（毫秒）
+}
+
+// provider-balance-updated 事件负载
+export interface BalanceUpdatedEvent {
+  provider_id: string;
+  balance: Balance;
+}
+
+// data-file-changed 事件负载
+export interface DataFileChangedEvent {
+  kind: 'dashboard' | 'providers' | 'tool_config';
+  path: string;
+  tool_id?: string;
+}
+
+// tool-status-changed 事件负载：后台重新检测后与缓存不同的工具状态
+export type ToolStatusChangedEvent = ToolStatus;
+
+// tool-detection-changed 事件负载：工具检测开始 / 结束
+export interface ToolDetectionChangedEvent {
+  tool_id: string;
+  detecting: boolean;
+}
+
+// 缓存中的工具状态（可能已过期）与正在检测的工具
+export interface ToolStatusSnapshot {
+  statuses: ToolStatus[];
+  detecting: string[];
+}
+
+// providers-changed 事件负载：providers.json 写入完成后发送
+export interface ProvidersChangedEvent {
+  updated_at: number;
+}
+
+// dashboard-changed 事件负载：dashboard.json 写入完成后发送
+export interface DashboardChangedEvent {
+  updated_at: number;
+}
+
+export interface ModelRate {
+  input_per_mtok: number; // 美元 / 百万 token
+  output_per_mtok: number;
+}
+
+export interface PricingSettings {
+  default_rate?: ModelRate; // 未知模型的默认单价
+  model_overrides?: Record<string, ModelRate>; // 模型名前缀 → 单价
+  provider_overrides?: Record<string, Record<string, ModelRate>>; // 供应商 ID → 模型名前缀 → 单价
+  daily_spend_alert_usd?: number; // 当日预估花费提醒阈值
 }
 
 export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
@@ -92,6 +375,11 @@ export interface LogConfig {
   file_path: string | null;
 }
 
+export interface LogLine {
+  level: LogLevel | null; // 无法识别级别时为 null（如多行消息的续行）
+  text: string;
+}
+
 export interface GenerateApiKeyResult {
   success: boolean;
   message: string;
@@ -107,12 +395,30 @@ export interface UsageData {
   token_used: number;
   count: number;
   quota: number;
+  estimated_cost_usd: number; // 按模型单价估算的花费（美元）
+  cost_is_estimate: boolean; // 模型不在定价表中，按默认单价估算
+}
+
+export interface DailyCost {
+  date: string; // YYYY-MM-DD
+  tokens: number;
+  cost_usd: number;
+  estimated: boolean;
+}
+
+export interface SpendAlert {
+  date: string;
+  spent_usd: number;
+  threshold_usd: number;
 }
 
 export interface UsageStatsResult {
   success: boolean;
   message: string;
   data: UsageData[];
+  daily_costs: DailyCost[];
+  total_cost_usd: number;
+  spend_alert?: SpendAlert; // 当日预估花费达到提醒阈值
 }
 
 export interface UserQuotaResult {
@@ -131,6 +437,24 @@ export interface NodeEnvironment {
   npm_version: string | null;
 }
 
+export type ConfigDirSource = 'env' | 'portable' | 'xdg' | 'default';
+
+export interface AppPaths {
+  config_dir: string;
+  source: ConfigDirSource;
+  global_config: string;
+  providers: string;
+  dashboard: string;
+  tool_instances: string;
+  sessions_db: string;
+  log_dir: string;
+}
+
+export interface EffectivePath {
+  entries: string[];
+  shell_env_applied: boolean;
+}
+
 export interface UpdateInfo {
   current_version: string;
   latest_version: string;
@@ -140,6 +464,17 @@ export interface UpdateInfo {
   release_notes?: string;
   file_size?: number;
   required: boolean;
+  skipped?: boolean; // 最新版本已被用户跳过
+}
+
+// 应用自身的版本检查结果（来自 GitHub Releases，缓存一天）
+export interface AppUpdateCheck {
+  current: string;
+  latest: string;
+  release_notes?: string;
+  download_url?: string; // 发布页地址
+  has_update: boolean;
+  skipped?: boolean; // 最新版本已被用户跳过
 }
 
 export interface DownloadProgress {
@@ -156,8 +491,49 @@ export interface PlatformInfo {
   is_windows: boolean;
   is_macos: boolean;
   is_linux: boolean;
+  platform_id?: string;
+}
+
+export type WslDistroState =
+  | 'running'
+  | 'stopped'
+  | 'installing'
+  | 'converting'
+  | 'uninstalling'
+  | 'unknown';
+
+export interface WslDistro {
+  name: string;
+  state: WslDistroState;
+  version: number | null;
+  is_default: boolean;
+}
+
+export interface SshConfigHost {
+  alias: string;
+  host_name: string;
+  user: string | null;
+  port: number;
+  identity_file: string | null;
+  proxy_jump: string | null;
 }
 
+export type SshTestStage = 'dns' | 'connect' | 'auth' | 'exec';
+
+export type SshTestHop = 'bastion' | 'target';
+
+export interface SshTestResult {
+  success: boolean;
+  failed_stage: SshTestStage | null;
+  failed_hop: SshTestHop | null;
+  error: string | null;
+  host_key_unknown: boolean;
+  latency_ms: number | null;
+  elapsed_ms: number;
+}
+
+export type HostKeyStatus = { status: 'known' } | { status: 'unknown'; fingerprints: string[] };
+
 export interface PackageFormatInfo {
   platform: string;
   preferred_formats: string[];
@@ -205,6 +581,56 @@ export interface ExternalConfigChange {
   fallback_poll?: boolean;
 }
 
+export interface LedgerEntry {
+  path: string;
+  sha256: string;
+  written_at: number;
+  action: string;
+}
+
+export type ProvenanceStatus = 'duck_coding' | 'modified_externally' | 'unknown' | 'missing';
+
+export interface FileProvenance {
+  path: string;
+  status: ProvenanceStatus;
+  current_sha256?: string | null;
+  last_write?: LedgerEntry | null;
+}
+
+export type ConfigSource =
+  | 'managed'
+  | 'environment'
+  | 'project_local_settings'
+  | 'project_settings'
+  | 'user_settings'
+  | 'config_toml'
+  | 'auth_json'
+  | 'env_file'
+  | 'default';
+
+export interface ValueOrigin {
+  source: ConfigSource;
+  path?: string | null;
+  field: string;
+  value: string;
+}
+
+export interface EffectiveValue {
+  key: string;
+  effective?: ValueOrigin | null;
+  overridden: ValueOrigin[];
+  expected?: string | null;
+  mismatch: boolean;
+}
+
+export interface EffectiveConfig {
+  tool_id: string;
+  project_dir?: string | null;
+  applied_profile?: string | null;
+  via_proxy: boolean;
+  values: EffectiveValue[];
+}
+
 export interface ImportExternalChangeResult {
   profileName: string;
   wasNew: boolean;
@@ -241,16 +667,182 @@ export interface ToolProxyConfig {
   allow_public: boolean;
   session_endpoint_config_enabled: boolean; // 工具级：是否允许会话自定义端点
   auto_start: boolean; // 应用启动时自动运行代理（默认关闭）
+  credential_injection?: CredentialInjectionConfig; // 凭证注入（使用绑定供应商的真实凭证）
+  retry_policy?: RetryPolicyConfig; // 429/过载重试策略（默认关闭）
+  provider_retry_overrides?: Record<string, RetryPolicyConfig>; // 按供应商覆盖重试策略
+  allowed_path_prefixes?: string[]; // 端点白名单（其余路径返回 403）
+  tool_routing?: ToolRoutingState; // 工具已指向本地代理时的原始配置记录
+  limits?: ConcurrencyLimits; // 并发上限（0 表示不限制）
+  log_health_checks?: boolean; // 健康检查请求是否输出访问日志
+  drain_timeout_secs?: number; // 停止/重启监听时等待进行中请求完成的最长时间（秒）
+  timeouts?: UpstreamTimeouts; // 上游分阶段超时（0 表示不限制）
+  metrics_endpoint_enabled?: boolean; // 是否提供 Prometheus 指标端点（/__duckcoding/metrics）
+  provider_translations?: Record<string, ApiTranslation>; // 按供应商开启的协议转换
+}
+
+// 上游协议转换模式：anthropic_to_openai_chat 将 Claude Code 请求转换为 OpenAI Chat Completions
+export type ApiTranslation = 'none' | 'anthropic_to_openai_chat';
+
+// 上游请求超时（秒）
+export interface UpstreamTimeouts {
+  connect_secs: number;
+  first_byte_secs: number;
+  total_secs: number; // 非流式请求总时长
+  stream_idle_secs: number; // 流式响应相邻分块的最长间隔
+}
+
+// 代理并发上限
+export interface ConcurrencyLimits {
+  max_connections: number;
+  max_in_flight_requests: number;
+  retry_after_secs: number;
+}
+
+// 代理并发使用情况
+export interface ProxyUtilization {
+  active_connections: number;
+  max_connections: number;
+  in_flight_requests: number;
+  max_in_flight_requests: number;
+  rejected: number;
+}
+
+// 工具接入代理记录
+export interface ToolRoutingState {
+  proxy_url: string;
+  applied_at: string;
+  originals: Record<string, string | null>;
+}
+
+// 上游限流/过载重试策略
+export interface RetryPolicyConfig {
+  enabled: boolean;
+  max_retries: number;
+  base_delay_ms: number;
+  max_delay_ms: number;
+  max_body_bytes: number; // 超过该大小的请求体不重试
 }
 
+// 凭证注入配置
+export interface CredentialInjectionConfig {
+  enabled: boolean;
+  provider_id?: string; // 绑定的供应商 ID
+  path_prefixes: string[]; // 为空时使用工具默认前缀
+  client_header?: { name: string; value?: string };
+}
+
+// 代理守护状态（后端 ProxyRunState）
+export type ProxyRunState =
+  | { state: 'stopped' }
+  | { state: 'running' }
+  | { state: 'restarting'; attempt: number }
+  | { state: 'crashed'; reason: string };
+
 export interface TransparentProxyStatus {
   running: boolean;
   port: number;
+  state: ProxyRunState;
+  blocked_requests: number; // 最近 24 小时被白名单拒绝的请求数
+  active_profile: string | null; // 当前激活的代理配置档
+  routed_through_proxy: boolean; // 工具配置是否已指向本地代理
+  utilization: ProxyUtilization | null; // 并发使用情况
+}
+
+// 代理配置档摘要
+export interface ProxyProfileInfo {
+  name: string;
+  port: number;
+  allow_public: boolean;
+  saved_at: string;
+  active: boolean;
 }
 
 // 多工具代理状态映射
 export type AllProxyStatus = Record<string, TransparentProxyStatus>;
 
+// 代理指标
+export type MetricsRange = '1h' | '6h' | '24h';
+
+export type ExportFormat = 'csv' | 'json';
+
+// 导出日期范围（本地日期 YYYY-MM-DD，含首尾）
+export interface ExportRange {
+  from: string;
+  to: string;
+}
+
+export interface MetricsSeries {
+  provider: string;
+  endpoint: string;
+  request_count: number;
+  error_count: number;
+  error_rate: number;
+  avg_ms: number;
+  p50_ms: number;
+  p95_ms: number;
+  status_codes: Record<string, number>;
+  input_tokens: number;
+  output_tokens: number;
+}
+
+export interface ProxyMetricsReport {
+  range: MetricsRange;
+  generated_at: number;
+  total_requests: number;
+  total_errors: number;
+  blocked_requests: number;
+  rejected_requests: number;
+  upstream_timeouts: Record<string, number>; // 按阶段统计的上游超时次数
+  upstream_retries: number; // 因限流/过载/超时而重试的次数
+  utilization?: Record<string, ProxyUtilization>;
+  series: MetricsSeries[];
+}
+
+// 代理会话统计（会话 ID 来自 X-Session-Id 请求头或按客户端连接推导）
+export type SessionSource = 'header' | 'derived';
+
+export interface ProxySession {
+  session_id: string;
+  tool_id: string;
+  source: SessionSource;
+  user_agent?: string;
+  first_seen: string;
+  last_seen: string;
+  duration_ms: number;
+  request_count: number;
+  error_count: number;
+  input_tokens: number;
+  output_tokens: number;
+  total_tokens: number;
+}
+
+// 代理调试捕获条目（headers 已脱敏）
+export interface CapturedExchange {
+  id: number;
+  tool_id: string;
+  timestamp: string;
+  method: string;
+  path: string;
+  session_id?: string;
+  request_headers: [string, string][];
+  request_body: string;
+  request_truncated: boolean;
+  status?: number;
+  response_headers: [string, string][];
+  response_body: string;
+  response_truncated: boolean;
+  streaming: boolean;
+  duration_ms: number;
+}
+
+export interface CaptureSnapshot {
+  enabled: boolean;
+  expires_at?: string;
+  max_exchanges: number;
+  max_body_bytes: number;
+  exchanges: CapturedExchange[];
+}
+
 // 会话记录（后端数据模型）
 export interface SessionRecord {
   session_id: string;
@@ -317,3 +909,56 @@ export interface BalanceConfigBackend {
 
 // 前端 BalanceConfig 格式（camelCase）- 从 BalancePage 导入
 export type { BalanceConfig } from '@/pages/BalancePage/types';
+
+// 回收站记录类型
+export type TrashKind = 'provider' | 'tool_instance';
+
+// 回收站记录（不含完整数据）
+export interface TrashItem {
+  id: string;
+  kind: TrashKind;
+  original_id: string;
+  name: string;
+  deleted_at: number;
+  expires_at: number; // 到期自动清除的时间
+}
+
+// 回收站恢复结果
+export interface RestoreResult {
+  kind: TrashKind;
+  original_id: string;
+  restored_id: string; // 原 ID 被占用时为新生成的 ID
+}
+
+// 启动自检项
+export type SelfCheckKind =
+  | 'config_dir_writable'
+  | 'instance_db'
+  | 'node_environment'
+  | 'wsl'
+  | 'proxy_ports'
+  | 'secret_store'
+  | 'network_share';
+
+// 启动自检修复建议
+export type RemediationCode =
+  | 'fix_config_dir_permissions'
+  | 'repair_instance_db'
+  | 'install_node'
+  | 'install_npm'
+  | 'repair_wsl'
+  | 'install_wsl_distro'
+  | 'change_proxy_port'
+  | 'restrict_secret_file_permissions'
+  | 'repair_provider_store'
+  | 'use_local_config_dir'
+  | 'retry_check';
+
+// 启动自检结果
+export interface SelfCheckItem {
+  check: SelfCheckKind;
+  status: 'pass' | 'warn' | 'fail' | 'skipped';
+  detail: string;
+  remediation_code: RemediationCode | null;
+  duration_ms: number;
+}
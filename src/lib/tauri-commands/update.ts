@@ -2,7 +2,7 @@
 // 负责应用程序的自动更新检查、下载、安装和回滚
 
 import { invoke } from '@tauri-apps/api/core';
-import type { UpdateInfo } from './types';
+import type { AppUpdateCheck, UpdateInfo } from './types';
 
 /**
  * 检查应用更新
@@ -11,6 +11,20 @@ export async function checkForAppUpdates(): Promise<UpdateInfo> {
   return await invoke<UpdateInfo>('check_for_app_updates');
 }
 
+/**
+ * 跳过某个对象的指定版本（subject 为 'duckcoding' 或工具 ID），定时检查不再提醒
+ */
+export async function skipVersion(subject: string, version: string): Promise<void> {
+  await invoke('skip_version', { subject, version });
+}
+
+/**
+ * 通过 GitHub Releases 检查 DuckCoding 新版本（结果缓存一天，force 时忽略缓存）
+ */
+export async function checkAppUpdate(force?: boolean): Promise<AppUpdateCheck> {
+  return await invoke<AppUpdateCheck>('check_app_update', { force });
+}
+
 /**
  * 下载应用更新
  * @param url - 更新包下载链接
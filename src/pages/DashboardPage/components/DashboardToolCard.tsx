@@ -8,7 +8,7 @@ import {
   SelectTrigger,
   SelectValue,
 } from '@/components/ui/select';
-import { RefreshCw, Loader2, Key, Monitor, CheckCircle2, Package } from 'lucide-react';
+import { RefreshCw, Loader2, Key, Monitor, CheckCircle2, Package, Network } from 'lucide-react';
 import { logoMap } from '@/utils/constants';
 import { formatVersionLabel } from '@/utils/formatting';
 import type { ToolStatus } from '@/lib/tauri-commands';
@@ -22,6 +22,7 @@ interface DashboardToolCardProps {
   instanceSelection?: string; // 实例ID字符串
   instanceOptions: Array<{ value: string; label: string }>; // 实例选项列表
   toolInstances: ToolInstance[]; // 工具实例数据
+  routedThroughProxy?: boolean; // 工具配置是否已指向本地代理
   onUpdate: () => void;
   onCheckUpdates: () => void;
   onConfigure: () => void;
@@ -38,6 +39,7 @@ export function DashboardToolCard({
   instanceSelection,
   instanceOptions,
   toolInstances,
+  routedThroughProxy,
   onUpdate,
   onCheckUpdates,
   onConfigure,
@@ -87,6 +89,15 @@ export function DashboardToolCard({
                 最新版
               </Badge>
             )}
+            {routedThroughProxy && (
+              <Badge
+                variant="secondary"
+                className="gap-1 bg-blue-100 text-blue-800 dark:bg-blue-900 dark:text-blue-200"
+              >
+                <Network className="h-3 w-3" />
+                经由代理
+              </Badge>
+            )}
           </div>
 
           {/* 第二行：实例选择器（仅已安装工具显示） */}
@@ -1,12 +1,15 @@
 // Dashboard 供应商和实例选择管理 Hook
 
 import { useState, useEffect, useCallback } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import {
   listProviders,
   type Provider,
   getToolInstances,
   getToolInstanceSelection,
   setToolInstanceSelection,
+  type DashboardChangedEvent,
+  type ProvidersChangedEvent,
 } from '@/lib/tauri-commands';
 import type { ToolInstance } from '@/types/tool-management';
 
@@ -99,6 +102,23 @@ export function useDashboardProviders() {
     loadAllInstanceSelections();
   }, [loadProviders, loadToolInstances, loadAllInstanceSelections]);
 
+  /**
+   * 其他窗口修改供应商或实例选择后重新加载
+   */
+  useEffect(() => {
+    const unlistenProviders = listen<ProvidersChangedEvent>('providers-changed', () => {
+      loadProviders();
+    });
+    const unlistenDashboard = listen<DashboardChangedEvent>('dashboard-changed', () => {
+      loadAllInstanceSelections();
+    });
+
+    return () => {
+      unlistenProviders.then((fn) => fn());
+      unlistenDashboard.then((fn) => fn());
+    };
+  }, [loadProviders, loadAllInstanceSelections]);
+
   /**
    * 获取工具的可用实例选项（用于下拉列表）
    * value: instance_id
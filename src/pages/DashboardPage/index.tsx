@@ -16,8 +16,13 @@ import {
   refreshAllToolVersions,
   getSelectedProviderId,
   setSelectedProviderId as saveSelectedProviderId,
+  getAllProxyStatus,
 } from '@/lib/tauri-commands';
-import type { UserQuotaResult, UsageStatsResult } from '@/lib/tauri-commands/types';
+import type {
+  AllProxyStatus,
+  UserQuotaResult,
+  UsageStatsResult,
+} from '@/lib/tauri-commands/types';
 
 interface DashboardPageProps {
   tools: ToolStatus[];
@@ -32,6 +37,7 @@ export function DashboardPage({ tools: toolsProp, loading: loadingProp }: Dashbo
   const [quotaLoading, setQuotaLoading] = useState(false);
   const [stats, setStats] = useState<UsageStatsResult | null>(null);
   const [statsLoading, setStatsLoading] = useState(false);
+  const [proxyStatus, setProxyStatus] = useState<AllProxyStatus>({});
 
   // 使用仪表板 Hook
   const {
@@ -78,6 +84,13 @@ export function DashboardPage({ tools: toolsProp, loading: loadingProp }: Dashbo
     loadSelectedProviderId();
   }, []);
 
+  // 加载透明代理状态（用于展示工具是否经由代理）
+  useEffect(() => {
+    getAllProxyStatus()
+      .then(setProxyStatus)
+      .catch((error) => console.error('加载代理状态失败:', error));
+  }, []);
+
   // 初始化时选中第一个供应商（如果后端没有保存的值）
   useEffect(() => {
     if (providerIdLoaded && providers.length > 0 && !selectedProviderId) {
@@ -339,6 +352,7 @@ export function DashboardPage({ tools: toolsProp, loading: loadingProp }: Dashbo
                     instanceSelection={instanceSelections[tool.id]}
                     instanceOptions={getInstanceOptions(tool.id)}
                     toolInstances={toolInstances[tool.id] || []}
+                    routedThroughProxy={proxyStatus[tool.id]?.routed_through_proxy}
                     onUpdate={() => onUpdate(tool.id)}
                     onCheckUpdates={() => checkSingleToolUpdate(tool.id)}
                     onConfigure={() => switchToConfig(tool.id)}
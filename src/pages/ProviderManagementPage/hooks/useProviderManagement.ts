@@ -1,10 +1,12 @@
 import { useState, useEffect, useCallback } from 'react';
+import { listen } from '@tauri-apps/api/event';
 import {
   listProviders,
   createProvider,
   updateProvider,
   deleteProvider,
   type Provider,
+  type ProvidersChangedEvent,
 } from '@/lib/tauri-commands';
 
 export function useProviderManagement() {
@@ -81,6 +83,17 @@ export function useProviderManagement() {
     loadProviders();
   }, [loadProviders]);
 
+  // 其他窗口修改供应商后重新加载
+  useEffect(() => {
+    const unlisten = listen<ProvidersChangedEvent>('providers-changed', () => {
+      loadProviders();
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadProviders]);
+
   return {
     providers,
     loading,
@@ -10,11 +10,17 @@ import {
   updateSingleInstanceConfig,
   getStartupConfig,
   updateStartupConfig,
+  getShellEnvCaptureConfig,
+  updateShellEnvCaptureConfig,
+  getDurableWritesConfig,
+  updateDurableWritesConfig,
 } from '@/lib/tauri-commands';
 
 export function BasicSettingsTab() {
   const [singleInstanceEnabled, setSingleInstanceEnabled] = useState(true);
   const [startupEnabled, setStartupEnabled] = useState(false);
+  const [shellEnvEnabled, setShellEnvEnabled] = useState(true);
+  const [durableWritesEnabled, setDurableWritesEnabled] = useState(true);
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const { toast } = useToast();
@@ -24,12 +30,16 @@ export function BasicSettingsTab() {
     const loadConfig = async () => {
       setLoading(true);
       try {
-        const [singleInstance, startup] = await Promise.all([
+        const [singleInstance, startup, shellEnv, durableWrites] = await Promise.all([
           getSingleInstanceConfig(),
           getStartupConfig(),
+          getShellEnvCaptureConfig(),
+          getDurableWritesConfig(),
         ]);
         setSingleInstanceEnabled(singleInstance);
         setStartupEnabled(startup);
+        setShellEnvEnabled(shellEnv);
+        setDurableWritesEnabled(durableWrites);
       } catch (error) {
         console.error('加载配置失败:', error);
         toast({
@@ -80,6 +90,50 @@ export function BasicSettingsTab() {
     }
   };
 
+  // 保存 shell 环境捕获配置
+  const handleShellEnvToggle = async (checked: boolean) => {
+    setSaving(true);
+    try {
+      await updateShellEnvCaptureConfig(checked);
+      setShellEnvEnabled(checked);
+      toast({
+        title: '设置已保存',
+        description: '重启应用后生效',
+      });
+    } catch (error) {
+      console.error('保存 shell 环境捕获配置失败:', error);
+      toast({
+        title: '保存失败',
+        description: String(error),
+        variant: 'destructive',
+      });
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  // 保存持久化写入配置
+  const handleDurableWritesToggle = async (checked: boolean) => {
+    setSaving(true);
+    try {
+      await updateDurableWritesConfig(checked);
+      setDurableWritesEnabled(checked);
+      toast({
+        title: '设置已保存',
+        description: checked ? '已启用持久化写入' : '已关闭持久化写入',
+      });
+    } catch (error) {
+      console.error('保存持久化写入配置失败:', error);
+      toast({
+        title: '保存失败',
+        description: String(error),
+        variant: 'destructive',
+      });
+    } finally {
+      setSaving(false);
+    }
+  };
+
   // 保存开机自启动配置
   const handleStartupToggle = async (checked: boolean) => {
     setSaving(true);
@@ -158,6 +212,36 @@ export function BasicSettingsTab() {
         <div>
           <h4 className="text-sm font-semibold text-muted-foreground mb-3">开发者设置</h4>
 
+          <div className="flex items-center justify-between mb-4">
+            <div className="space-y-1">
+              <Label htmlFor="shell-env">读取 Shell 环境（macOS）</Label>
+              <p className="text-sm text-muted-foreground">
+                启动时读取一次登录 Shell 的 PATH 等变量，用于检测 nvm、volta 等安装的工具
+              </p>
+            </div>
+            <Switch
+              id="shell-env"
+              checked={shellEnvEnabled}
+              onCheckedChange={handleShellEnvToggle}
+              disabled={loading || saving}
+            />
+          </div>
+
+          <div className="flex items-center justify-between mb-4">
+            <div className="space-y-1">
+              <Label htmlFor="durable-writes">持久化写入</Label>
+              <p className="text-sm text-muted-foreground">
+                保存供应商、凭证等关键配置后立即同步到磁盘，防止断电丢失；网络文件系统上较慢时可关闭
+              </p>
+            </div>
+            <Switch
+              id="durable-writes"
+              checked={durableWritesEnabled}
+              onCheckedChange={handleDurableWritesToggle}
+              disabled={loading || saving}
+            />
+          </div>
+
           <div className="flex items-center justify-between">
             <div className="space-y-1">
               <Label htmlFor="single-instance">单实例模式</Label>
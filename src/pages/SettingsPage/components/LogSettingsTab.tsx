@@ -10,12 +10,22 @@ import {
   SelectTrigger,
   SelectValue,
 } from '@/components/ui/select';
-import { FileText, Info, Loader2, Save, AlertCircle } from 'lucide-react';
+import {
+  FileText,
+  FolderOpen,
+  Info,
+  Loader2,
+  Save,
+  AlertCircle,
+  PackageOpen,
+} from 'lucide-react';
 import { useToast } from '@/hooks/use-toast';
 import {
   getLogConfig,
   updateLogConfig,
   isReleaseBuild,
+  exportDiagnostics,
+  revealPath,
   type LogConfig,
 } from '@/lib/tauri-commands';
 import { Alert, AlertDescription } from '@/components/ui/alert';
@@ -24,6 +34,7 @@ export function LogSettingsTab() {
   const { toast } = useToast();
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
+  const [exporting, setExporting] = useState(false);
   const [isRelease, setIsRelease] = useState(false);
   const [config, setConfig] = useState<LogConfig>({
     level: 'info',
@@ -100,6 +111,40 @@ export function LogSettingsTab() {
     }
   };
 
+  // 导出诊断包
+  const handleExportDiagnostics = async () => {
+    try {
+      setExporting(true);
+      const path = await exportDiagnostics();
+      toast({
+        title: '诊断包已导出',
+        description: path,
+      });
+    } catch (error) {
+      console.error('Failed to export diagnostics:', error);
+      toast({
+        title: '导出失败',
+        description: String(error),
+        variant: 'destructive',
+      });
+    } finally {
+      setExporting(false);
+    }
+  };
+
+  // 打开日志目录
+  const handleOpenLogDir = async () => {
+    try {
+      await revealPath('logs');
+    } catch (error) {
+      toast({
+        title: '打开日志目录失败',
+        description: error instanceof Error ? error.message : String(error),
+        variant: 'destructive',
+      });
+    }
+  };
+
   if (loading) {
     return (
       <div className="flex items-center justify-center p-12">
@@ -239,7 +284,19 @@ export function LogSettingsTab() {
         )}
 
         {/* 保存按钮 */}
-        <div className="flex justify-end pt-2">
+        <div className="flex justify-end gap-2 pt-2">
+          <Button variant="outline" onClick={handleOpenLogDir}>
+            <FolderOpen className="mr-2 h-4 w-4" />
+            打开日志目录
+          </Button>
+          <Button variant="outline" onClick={handleExportDiagnostics} disabled={exporting}>
+            {exporting ? (
+              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+            ) : (
+              <PackageOpen className="mr-2 h-4 w-4" />
+            )}
+            导出诊断包
+          </Button>
           <Button
             onClick={handleSave}
             disabled={saving}
@@ -5,12 +5,14 @@ import {
   refreshAllToolVersions,
   addWslToolInstance,
   addSshToolInstance,
+  testSshConnection,
   deleteToolInstance,
   checkUpdateForInstance,
   updateToolInstance,
 } from '@/lib/tauri-commands';
 import type { ToolInstance, SSHConfig } from '@/types/tool-management';
 import { useToast } from '@/hooks/use-toast';
+import { formatBytes } from '@/utils/formatting';
 
 // 更新状态信息
 interface UpdateInfo {
@@ -110,7 +112,17 @@ export function useToolManagement() {
           if (!sshConfig) {
             throw new Error('SSH配置不能为空');
           }
-          await addSshToolInstance(baseId, sshConfig);
+          const test = await testSshConnection(sshConfig);
+          let force = false;
+          if (test.failed_stage === 'auth') {
+            force = window.confirm(
+              `SSH 认证失败：${test.error ?? '未知错误'}\n\n仍然保存该实例吗？`,
+            );
+            if (!force) {
+              return;
+            }
+          }
+          await addSshToolInstance(baseId, sshConfig, force);
           toast({ title: '添加成功', description: 'SSH工具实例已添加' });
           await refreshTools();
         }
@@ -168,9 +180,10 @@ export function useToolManagement() {
         const toolName = typeIndex > 0 ? parts.slice(0, typeIndex).join('-') : parts[0];
 
         if (result.has_update) {
+          const size = result.download_size ? `（下载约 ${formatBytes(result.download_size)}）` : '';
           toast({
             title: '发现新版本',
-            description: `${toolName}: ${result.current_version || '未知'} → ${result.latest_version || '未知'}`,
+            description: `${toolName}: ${result.current_version || '未知'} → ${result.latest_version || '未知'}${size}`,
           });
         } else {
           toast({
@@ -225,9 +238,12 @@ export function useToolManagement() {
         const result = await updateToolInstance(instanceId);
 
         if (result.success) {
+          const duration = result.duration_ms
+            ? `，用时 ${(result.duration_ms / 1000).toFixed(1)} 秒`
+            : '';
           toast({
             title: '更新成功',
-            description: `${baseId} 已更新到 ${result.latest_version || '最新版本'}`,
+            description: `${baseId} 已更新到 ${result.latest_version || '最新版本'}${duration}`,
           });
 
           // 清除更新状态
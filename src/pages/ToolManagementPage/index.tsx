@@ -9,7 +9,9 @@ import { ToolListSection } from './components/ToolListSection';
 import { AddInstanceDialog } from './components/AddInstanceDialog/AddInstanceDialog';
 import { VersionManagementDialog } from './components/VersionManagementDialog';
 import { useToolManagement } from './hooks/useToolManagement';
+import { exportEnvironmentReport } from '@/lib/tauri-commands';
 import type { ToolStatus } from '@/lib/tauri-commands';
+import { useToast } from '@/hooks/use-toast';
 
 interface ToolManagementPageProps {
   tools: ToolStatus[];
@@ -38,6 +40,27 @@ export function ToolManagementPage({
     updating,
   } = useToolManagement();
 
+  const { toast } = useToast();
+  const [exportingReport, setExportingReport] = useState(false);
+
+  // 导出环境报告（保存位置由后端弹出的对话框选择，按扩展名决定 Markdown 或 JSON）
+  const handleExportReport = async () => {
+    try {
+      setExportingReport(true);
+      const written = await exportEnvironmentReport();
+      if (!written) return;
+      toast({ title: '环境报告已导出', description: written });
+    } catch (err) {
+      toast({
+        title: '导出环境报告失败',
+        description: err instanceof Error ? err.message : String(err),
+        variant: 'destructive',
+      });
+    } finally {
+      setExportingReport(false);
+    }
+  };
+
   // 通知父组件刷新工具列表
   const onRefreshTools = () => {
     window.dispatchEvent(new CustomEvent('refresh-tools'));
@@ -110,6 +133,10 @@ export function ToolManagementPage({
           <Button variant="outline" onClick={onRefreshTools}>
             刷新状态
           </Button>
+          <Button variant="outline" onClick={handleExportReport} disabled={exportingReport}>
+            {exportingReport && <Loader2 className="mr-2 h-4 w-4 animate-spin" />}
+            导出环境报告
+          </Button>
         </div>
       </div>
 
@@ -29,6 +29,7 @@ export interface UpdateInfo {
   release_notes?: string;
   file_size?: number;
   required: boolean;
+  skipped?: boolean; // 最新版本已被用户跳过
 }
 
 export interface DownloadProgress {
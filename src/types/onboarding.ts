@@ -14,6 +14,27 @@ export interface OnboardingStatus {
   completed_at?: string;
 }
 
+/**
+ * 后端记录的引导步骤状态（与 Rust OnboardingState 对应）
+ */
+export interface OnboardingState {
+  /** 已完成的步骤 → 完成时间（Unix 秒） */
+  completed_steps: Record<string, number>;
+  /** 跳过的步骤 ID 列表 */
+  skipped_steps: string[];
+  /** 引导中检测到或已配置的工具 ID */
+  chosen_tools: string[];
+  /** 首次记录步骤的时间（Unix 秒） */
+  started_at?: number;
+  /** 最后更新时间（Unix 秒） */
+  updated_at: number;
+}
+
+/** 后端自动记录的步骤：本地工具检测完成 */
+export const STEP_TOOLS_DETECTED = 'tools_detected';
+/** 后端自动记录的步骤：已将供应商配置应用到工具 */
+export const STEP_PROVIDER_APPLIED = 'provider_applied';
+
 /**
  * 引导步骤定义
  */
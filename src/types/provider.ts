@@ -2,6 +2,9 @@
  * 供应商管理系统类型定义
  */
 
+/** 余额接口模板 */
+export type BalanceTemplate = 'new_api' | 'one_api' | 'anthropic_usage';
+
 /**
  * 供应商信息
  */
@@ -18,6 +21,12 @@ export interface Provider {
   user_id: string;
   /** 访问令牌 */
   access_token: string;
+  /** 模型调用 API Key（可选，透明代理凭证注入使用） */
+  api_key?: string;
+  /** 余额接口模板（默认 new_api） */
+  balance_template?: BalanceTemplate;
+  /** 自定义请求头（验证、余额查询与透明代理转发时附加；名称含 key/token 等的值在只读展示时脱敏） */
+  custom_headers?: Record<string, string>;
   /** 用户名（可选） */
   username?: string;
   /** 是否为默认供应商 */
@@ -79,3 +88,139 @@ export interface ProviderValidationResult {
   /** 错误消息（验证失败时） */
   error?: string;
 }
+
+/** 外部切换工具配置来源 */
+export type ProviderImportSource = 'cc_switch' | 'claude_code_router';
+
+/**
+ * 从外部配置发现的供应商（草稿，尚未写入）
+ */
+export interface DiscoveredProvider {
+  source: ProviderImportSource;
+  /** 来源配置文件 */
+  source_path: string;
+  /** 供应商草稿 */
+  provider: Provider;
+  /** 建议绑定到该供应商的工具 ID */
+  suggested_tools: string[];
+  /** 已存在相同 API 地址的供应商 ID */
+  existing_provider_id?: string;
+  /** 解析说明（如被忽略的字段） */
+  notes: string[];
+}
+
+/**
+ * 外部供应商发现结果
+ */
+export interface ProviderDiscovery {
+  providers: DiscoveredProvider[];
+  /** 已检查且存在的配置文件 */
+  scanned_files: string[];
+  /** 整体说明（如配置文件无法解析） */
+  notes: string[];
+}
+
+/**
+ * 用户确认导入的条目
+ */
+export interface ConfirmedProviderImport {
+  provider: Provider;
+  /** 需要绑定的工具 ID */
+  bind_tools?: string[];
+  /** 使用已存在的供应商（只设置绑定，不创建） */
+  existing_provider_id?: string;
+}
+
+/**
+ * 供应商导入结果
+ */
+export interface ProviderImportResult {
+  created: Provider[];
+  bindings: { tool_id: string; provider_id: string }[];
+  /** 失败的条目说明 */
+  failures: string[];
+}
+
+/**
+ * 测速请求类型：models 为 GET /v1/models（不消耗额度），completion 为 1 token 补全
+ */
+export type BenchmarkProbe = 'models' | 'completion';
+
+/**
+ * 测速参数
+ */
+export interface BenchmarkOptions {
+  probe?: BenchmarkProbe;
+  /** 补全测速使用的模型（completion 时必填） */
+  model?: string;
+  /** 全局时间预算（毫秒），默认 60 秒 */
+  budget_ms?: number;
+}
+
+/**
+ * 单个供应商的测速结果
+ */
+export interface ProviderBenchmark {
+  provider_id: string;
+  provider_name: string;
+  probe: BenchmarkProbe;
+  samples: number;
+  successes: number;
+  /** 失败次数（含超时，不含被中止的请求） */
+  errors: number;
+  timeouts: number;
+  /** 因全局预算耗尽被中止的请求数 */
+  aborted: number;
+  min_ms: number | null;
+  median_ms: number | null;
+  p95_ms: number | null;
+  last_error: string | null;
+  /** 测速时间（Unix 时间戳） */
+  benchmarked_at: number;
+}
+
+/**
+ * 测速对比结果（按中位延迟升序）
+ */
+export interface BenchmarkReport {
+  results: ProviderBenchmark[];
+  budget_exceeded: boolean;
+  elapsed_ms: number;
+}
+
+/**
+ * 进行中的供应商试用（到期后自动恢复原配置）
+ */
+export interface ProviderTrialInfo {
+  id: string;
+  tool_id: string;
+  provider_id: string;
+  /** 开始时间（Unix 时间戳） */
+  started_at: number;
+  /** 到期时间（Unix 时间戳） */
+  ends_at: number;
+  /** 是否已发出即将到期提醒 */
+  warned: boolean;
+}
+
+/**
+ * 试用事件类型：revert_skipped 表示配置在试用期间被修改，未自动恢复
+ */
+export type TrialEventKind =
+  | 'applied'
+  | 'warning'
+  | 'reverted'
+  | 'revert_skipped'
+  | 'revert_failed'
+  | 'kept';
+
+/**
+ * provider-trial 事件负载
+ */
+export interface ProviderTrialEvent {
+  kind: TrialEventKind;
+  trial: ProviderTrialInfo;
+  /** 试用期间被修改的文件（revert_skipped 时存在） */
+  modified_files?: string[];
+  message: string;
+}
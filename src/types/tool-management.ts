@@ -24,6 +24,11 @@ export enum ToolSource {
   External = 'External',
 }
 
+/**
+ * SSH 认证方式
+ */
+export type SshAuthMethod = 'agent' | 'key' | 'password';
+
 /**
  * SSH 连接配置
  */
@@ -38,6 +43,14 @@ export interface SSHConfig {
   user: string;
   /** SSH 密钥路径（可选） */
   key_path?: string;
+  /** 认证方式（默认 agent） */
+  auth_method?: SshAuthMethod;
+  /** 密码（仅 password 认证） */
+  password?: string;
+  /** 密钥口令（仅 key 认证且密钥加密时） */
+  passphrase?: string;
+  /** 跳板机（可多级嵌套，仅支持 agent 或无口令密钥认证） */
+  jump_host?: SSHConfig;
 }
 
 /**
@@ -62,6 +75,8 @@ export interface ToolInstance {
   install_path?: string;
   /** WSL发行版名称（仅WSL类型使用） */
   wsl_distro?: string;
+  /** 安装路径的 Windows 表示（仅WSL类型使用，用于打开文件夹） */
+  windows_install_path?: string;
   /** SSH配置（仅SSH类型使用） */
   ssh_config?: SSHConfig;
   /** 是否为内置实例 */
@@ -70,6 +85,34 @@ export interface ToolInstance {
   created_at: number;
   /** 更新时间（Unix timestamp） */
   updated_at: number;
+  /** 用户填写的标签 */
+  label?: string;
+  /** 用户备注 */
+  notes?: string;
+  /** 执行该实例二进制时注入的环境变量（敏感值已脱敏） */
+  env_overrides: Record<string, string>;
+  /** npm 安装实例所属的 Node.js 运行时（按全局目录匹配安装路径） */
+  node_runtime?: NodeRuntime;
+}
+
+/**
+ * Node.js 运行时来源
+ */
+export type NodeRuntimeSource = 'path' | 'nvm' | 'fnm' | 'volta' | 'asdf' | 'system';
+
+/**
+ * 本机的一个 Node.js 运行时
+ */
+export interface NodeRuntime {
+  /** node 可执行文件路径 */
+  node_path: string;
+  source: NodeRuntimeSource;
+  /** node --version（如 v20.11.0） */
+  version: string | null;
+  /** 同目录 npm 的 npm prefix -g */
+  global_prefix: string | null;
+  /** 是否为 PATH 中第一个 node（终端中实际使用的运行时） */
+  active: boolean;
 }
 
 /**
@@ -17,6 +17,21 @@ export function formatVersionLabel(version: string | null): string {
   return match ? match[1] : trimmed;
 }
 
+/**
+ * 字节数格式化（如 12.3 MB）
+ */
+export function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  const units = ['KB', 'MB', 'GB'];
+  let value = bytes / 1024;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return `${value.toFixed(1)} ${units[unit]}`;
+}
+
 /**
  * 脱敏显示 API Key
  */